@@ -0,0 +1,65 @@
+use criterion::{BatchSize, Criterion};
+use gpui::{Application, SharedString, TextRun, TextSystem, WindowTextSystem, black, font, px};
+use std::{hint::black_box, sync::Arc};
+
+const LINE_COUNT: usize = 10_000;
+
+/// A buffer of source-code-like lines, so that shaping sees a realistic mix of words,
+/// punctuation and indentation.
+fn buffer() -> SharedString {
+    let mut text = String::new();
+    for index in 0..LINE_COUNT {
+        let indent = "    ".repeat(index % 4);
+        text.push_str(&format!(
+            "{indent}let value_{index} = compute(\"line {index}\", {index}) * 2; // note\n"
+        ));
+    }
+    text.into()
+}
+
+fn shape_buffer(criterion: &mut Criterion, text_system: Arc<TextSystem>) {
+    let text = buffer();
+    let runs = [TextRun {
+        len: text.len(),
+        font: font(".SystemUIFont"),
+        color: black(),
+        background_color: None,
+        underline: None,
+        strikethrough: None,
+    }];
+
+    criterion.bench_function("text/shape_10k_lines", |bencher| {
+        bencher.iter_batched(
+            || WindowTextSystem::new(text_system.clone()),
+            |window_text_system| {
+                let lines = window_text_system
+                    .shape_text(text.clone(), px(14.), &runs, None, None)
+                    .map(|lines| lines.len());
+                black_box(lines)
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    criterion.bench_function("text/wrap_10k_lines", |bencher| {
+        bencher.iter_batched(
+            || WindowTextSystem::new(text_system.clone()),
+            |window_text_system| {
+                let lines = window_text_system
+                    .shape_text(text.clone(), px(14.), &runs, Some(px(400.)), None)
+                    .map(|lines| lines.len());
+                black_box(lines)
+            },
+            BatchSize::PerIteration,
+        )
+    });
+}
+
+fn main() {
+    Application::headless().run(|cx| {
+        let mut criterion = Criterion::default().configure_from_args();
+        shape_buffer(&mut criterion, cx.text_system().clone());
+        criterion.final_summary();
+        cx.quit();
+    });
+}
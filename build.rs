@@ -76,6 +76,9 @@ mod macos {
             .header("src/platform/mac/dispatch.h")
             .allowlist_var("_dispatch_main_q")
             .allowlist_var("_dispatch_source_type_data_add")
+            .allowlist_var("_dispatch_source_type_memorypressure")
+            .allowlist_var("DISPATCH_MEMORYPRESSURE_WARN")
+            .allowlist_var("DISPATCH_MEMORYPRESSURE_CRITICAL")
             .allowlist_var("DISPATCH_QUEUE_PRIORITY_HIGH")
             .allowlist_var("DISPATCH_QUEUE_PRIORITY_DEFAULT")
             .allowlist_var("DISPATCH_QUEUE_PRIORITY_LOW")
@@ -86,6 +89,7 @@ mod macos {
             .allowlist_function("dispatch_time")
             .allowlist_function("dispatch_source_merge_data")
             .allowlist_function("dispatch_source_create")
+            .allowlist_function("dispatch_source_get_data")
             .allowlist_function("dispatch_source_set_event_handler_f")
             .allowlist_function("dispatch_resume")
             .allowlist_function("dispatch_suspend")
@@ -128,9 +132,12 @@ mod macos {
             "ShadowInputIndex".into(),
             "Shadow".into(),
             "QuadInputIndex".into(),
+            "TiledQuad".into(),
+            "TiledQuadInputIndex".into(),
             "Underline".into(),
             "UnderlineInputIndex".into(),
             "Quad".into(),
+            "QuadShadow".into(),
             "BorderStyle".into(),
             "SpriteInputIndex".into(),
             "MonochromeSprite".into(),
@@ -266,6 +273,7 @@ mod windows {
         // Define all modules
         let modules = [
             "quad",
+            "tiled_quad",
             "shadow",
             "path_rasterization",
             "path_sprite",
@@ -0,0 +1,125 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use gpui_core::{
+    Bounds, BoundsTree, ContentMask, Corners, Hsla, PrimitiveBatch, Quad, ScaledPixels, Scene,
+    TransformationMatrix, Underline, point, size,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::hint::black_box;
+
+const QUAD_COUNT: usize = 10_000;
+const COLUMNS: usize = 100;
+
+fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+    Bounds::new(
+        point(ScaledPixels(x), ScaledPixels(y)),
+        size(ScaledPixels(width), ScaledPixels(height)),
+    )
+}
+
+/// A grid of rounded cells, each with a label underline, resembling a large table.
+fn paint_grid(scene: &mut Scene) {
+    let content_mask = ContentMask {
+        bounds: bounds(0., 0., 2400., 2400.),
+        ..Default::default()
+    };
+    for index in 0..QUAD_COUNT {
+        let x = (index % COLUMNS) as f32 * 24.;
+        let y = (index / COLUMNS) as f32 * 24.;
+        scene.insert_primitive(Quad {
+            bounds: bounds(x, y, 22., 22.),
+            content_mask: content_mask.clone(),
+            background: Hsla::blue().into(),
+            corner_radii: Corners::all(ScaledPixels(4.)),
+            ..Default::default()
+        });
+        if index % 4 == 0 {
+            scene.insert_primitive(Underline {
+                order: 0,
+                pad: 0,
+                bounds: bounds(x + 2., y + 18., 18., 1.),
+                content_mask: content_mask.clone(),
+                color: Hsla::black(),
+                thickness: ScaledPixels(1.),
+                wavy: 0,
+                wave_amplitude: ScaledPixels(0.),
+                wavelength: ScaledPixels(0.),
+                transformation: TransformationMatrix::unit(),
+            });
+        }
+    }
+}
+
+fn scene_construction(criterion: &mut Criterion) {
+    let mut scene = Scene::default();
+    criterion.bench_function("scene/insert_10k_quads", |bencher| {
+        bencher.iter(|| {
+            scene.clear();
+            paint_grid(&mut scene);
+            scene.finish();
+            black_box(scene.len())
+        })
+    });
+}
+
+fn scene_encode(criterion: &mut Criterion) {
+    let mut scene = Scene::default();
+    paint_grid(&mut scene);
+    scene.finish();
+    criterion.bench_function("scene/encode_10k_quads", |bencher| {
+        bencher.iter(|| {
+            let mut instance_bytes = 0;
+            for batch in scene.batches() {
+                black_box(batch.shared_content_mask());
+                instance_bytes += match batch {
+                    PrimitiveBatch::Shadows(shadows) => size_of_val(shadows),
+                    PrimitiveBatch::BackdropBlurs(blurs) => size_of_val(blurs),
+                    PrimitiveBatch::Quads(quads) => size_of_val(quads),
+                    PrimitiveBatch::TiledQuads(quads) => size_of_val(quads),
+                    PrimitiveBatch::Paths(paths) => size_of_val(paths),
+                    PrimitiveBatch::Underlines(underlines) => size_of_val(underlines),
+                    PrimitiveBatch::MonochromeSprites { sprites, .. } => size_of_val(sprites),
+                    PrimitiveBatch::PolychromeSprites { sprites, .. } => size_of_val(sprites),
+                    PrimitiveBatch::Surfaces(surfaces) => size_of_val(surfaces),
+                    PrimitiveBatch::CustomRenderPrimitives(primitives) => size_of_val(primitives),
+                };
+            }
+            black_box(instance_bytes)
+        })
+    });
+    criterion.bench_function("scene/svg_10k_quads", |bencher| {
+        bencher.iter(|| black_box(scene.to_svg().len()))
+    });
+}
+
+fn bounds_tree_insertion(criterion: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let all_bounds = (0..QUAD_COUNT)
+        .map(|_| {
+            bounds(
+                rng.random_range(0.0..2000.),
+                rng.random_range(0.0..2000.),
+                rng.random_range(1.0..200.),
+                rng.random_range(1.0..200.),
+            )
+        })
+        .collect::<Vec<_>>();
+    criterion.bench_function("bounds_tree/insert_10k", |bencher| {
+        bencher.iter_batched_ref(
+            BoundsTree::<ScaledPixels>::default,
+            |tree| {
+                for bounds in &all_bounds {
+                    black_box(tree.insert(*bounds));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    scene_construction,
+    scene_encode,
+    bounds_tree_insertion
+);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use crate::{Bounds, DevicePixels};
+use crate::{Bounds, DevicePixels, Size};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(C)]
@@ -24,7 +24,7 @@ pub enum AtlasTextureKind {
     Polychrome = 1,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(C)]
 pub struct TileId(pub u32);
 
@@ -39,3 +39,12 @@ impl From<TileId> for etagere::AllocId {
         Self::deserialize(id.0)
     }
 }
+
+/// Provides the pixels of atlas textures, so that scenes can be exported along with the glyphs
+/// and images their sprites draw.
+pub trait AtlasPixels {
+    /// Returns the size of the texture and its pixels, row by row from the top: a byte of
+    /// coverage per pixel for monochrome textures, or four bytes of BGRA with straight alpha per
+    /// pixel for polychrome ones. Returns `None` if the texture's pixels aren't available.
+    fn texture_pixels(&self, texture_id: AtlasTextureId) -> Option<(Size<DevicePixels>, &[u8])>;
+}
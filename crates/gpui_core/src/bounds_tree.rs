@@ -5,7 +5,7 @@ use std::{
     ops::{Add, Sub},
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BoundsTree<U>
 where
     U: Clone + Debug + Default + PartialEq,
@@ -32,12 +32,21 @@ where
         self.stack.clear();
     }
 
+    /// Inserts the bounds, returning an order one greater than the order of any bounds it
+    /// intersects.
     pub fn insert(&mut self, new_bounds: Bounds<U>) -> u32 {
+        self.insert_at_least(new_bounds, 1)
+    }
+
+    /// Inserts the bounds, returning an order one greater than the order of any bounds it
+    /// intersects, and at least `min_order`.
+    pub fn insert_at_least(&mut self, new_bounds: Bounds<U>, min_order: u32) -> u32 {
         // If the tree is empty, make the root the new leaf.
         let Some(mut index) = self.root else {
-            let new_node = self.push_leaf(new_bounds, 1);
+            let ordering = cmp::max(min_order, 1);
+            let new_node = self.push_leaf(new_bounds, ordering);
             self.root = Some(new_node);
-            return 1;
+            return ordering;
         };
 
         // Search for the best place to add the new leaf based on heuristics.
@@ -90,7 +99,7 @@ where
             max_intersecting_ordering = cmp::max(max_intersecting_ordering, *sibling_ordering);
         }
 
-        let ordering = max_intersecting_ordering + 1;
+        let ordering = cmp::max(max_intersecting_ordering + 1, min_order);
         let new_node = self.push_leaf(new_bounds, ordering);
         let new_parent = self.push_internal(sibling, new_node);
 
@@ -298,6 +307,30 @@ mod tests {
         assert_eq!(tree.insert(bounds6), 2); // bounds6 overlaps with bounds4, so it should have a different order
     }
 
+    #[test]
+    fn test_insert_at_least() {
+        let mut tree = BoundsTree::<f32>::default();
+        let bounds = |x: f32| Bounds {
+            origin: Point { x, y: 0.0 },
+            size: Size {
+                width: 10.0,
+                height: 10.0,
+            },
+        };
+
+        assert_eq!(tree.insert_at_least(bounds(0.0), 3), 3);
+        assert_eq!(
+            tree.insert(bounds(5.0)),
+            4,
+            "later bounds are ordered above the raised order"
+        );
+        assert_eq!(
+            tree.insert_at_least(bounds(8.0), 2),
+            5,
+            "the minimum doesn't lower the order"
+        );
+    }
+
     #[test]
     fn test_random_iterations() {
         let max_bounds = 100;
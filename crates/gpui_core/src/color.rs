@@ -69,6 +69,45 @@ impl Rgba {
             }
         }
     }
+
+    /// Returns the relative luminance of the color as defined by WCAG 2, from 0.0 for black to
+    /// 1.0 for white. Alpha is ignored.
+    pub fn relative_luminance(&self) -> f32 {
+        0.2126 * srgb_to_linear(self.r)
+            + 0.7152 * srgb_to_linear(self.g)
+            + 0.0722 * srgb_to_linear(self.b)
+    }
+
+    /// Returns the WCAG 2 contrast ratio between this color and `other`, from 1.0 for colors of
+    /// the same luminance to 21.0 for black and white. Alpha is ignored, so translucent colors
+    /// should be blended onto what they're drawn over first.
+    pub fn contrast_ratio(&self, other: Rgba) -> f32 {
+        let luminance = self.relative_luminance();
+        let other_luminance = other.relative_luminance();
+        (luminance.max(other_luminance) + 0.05) / (luminance.min(other_luminance) + 0.05)
+    }
+}
+
+/// A WCAG 2 conformance level for the contrast between text and its background.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContrastLevel {
+    /// Level AA, which requires a contrast ratio of 4.5:1, or 3:1 for large text.
+    Aa,
+    /// Level AAA, which requires a contrast ratio of 7:1, or 4.5:1 for large text.
+    Aaa,
+}
+
+impl ContrastLevel {
+    /// Returns the lowest contrast ratio that conforms to this level. Large text is at least 24
+    /// pixels, or 18.66 pixels when bold.
+    pub fn minimum_ratio(self, large_text: bool) -> f32 {
+        match (self, large_text) {
+            (ContrastLevel::Aa, false) => 4.5,
+            (ContrastLevel::Aa, true) => 3.,
+            (ContrastLevel::Aaa, false) => 7.,
+            (ContrastLevel::Aaa, true) => 4.5,
+        }
+    }
 }
 
 impl From<Rgba> for u32 {
@@ -504,6 +543,18 @@ impl Hsla {
         }
     }
 
+    /// Returns the WCAG 2 contrast ratio between this color and `other`. See
+    /// [`Rgba::contrast_ratio`].
+    pub fn contrast_ratio(self, other: Hsla) -> f32 {
+        self.to_rgb().contrast_ratio(other.to_rgb())
+    }
+
+    /// Returns whether text in this color on an `other` background conforms to the given WCAG
+    /// level.
+    pub fn meets_contrast(self, other: Hsla, level: ContrastLevel, large_text: bool) -> bool {
+        self.contrast_ratio(other) >= level.minimum_ratio(large_text)
+    }
+
     /// Returns a new HSLA color with the same hue, and lightness, but with no saturation.
     pub fn grayscale(&self) -> Self {
         Hsla {
@@ -652,6 +703,113 @@ impl<'de> Deserialize<'de> for Hsla {
     }
 }
 
+/// Converts an sRGB color component to linear light.
+fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light color component to sRGB, clamping it to the sRGB range.
+fn linear_to_srgb(component: f32) -> f32 {
+    let component = component.clamp(0., 1.);
+    if component <= 0.003_130_8 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// A color in the Oklch color space, whose lightness is perceptually uniform across hues.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklch {
+    /// The perceived lightness, from 0.0 for black to 1.0 for white.
+    pub lightness: f32,
+    /// The colorfulness, from 0.0 for grey to around 0.37 for the most saturated sRGB colors.
+    pub chroma: f32,
+    /// The hue angle in radians.
+    pub hue: f32,
+}
+
+impl Oklch {
+    /// Returns the color with `amount` added to its lightness, clamped to the valid range.
+    pub fn lighten(self, amount: f32) -> Self {
+        Self {
+            lightness: (self.lightness + amount).clamp(0., 1.),
+            ..self
+        }
+    }
+
+    fn to_linear_srgb(self) -> [f32; 3] {
+        let a = self.chroma * self.hue.cos();
+        let b = self.chroma * self.hue.sin();
+        let l = (self.lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m = (self.lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s = (self.lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+        [
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        ]
+    }
+}
+
+impl From<Rgba> for Oklch {
+    fn from(color: Rgba) -> Self {
+        let (r, g, b) = (
+            srgb_to_linear(color.r),
+            srgb_to_linear(color.g),
+            srgb_to_linear(color.b),
+        );
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        let a = 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s;
+        let b = 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s;
+        Self {
+            lightness: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            chroma: a.hypot(b),
+            hue: b.atan2(a).rem_euclid(std::f32::consts::TAU),
+        }
+    }
+}
+
+impl From<Oklch> for Rgba {
+    /// Converts to sRGB, reducing chroma while the color is out of gamut so that lightness and
+    /// hue are preserved.
+    fn from(color: Oklch) -> Self {
+        let in_gamut = |color: Oklch| {
+            color
+                .to_linear_srgb()
+                .iter()
+                .all(|component| (-0.0001..=1.0001).contains(component))
+        };
+        let mut color = color;
+        if !in_gamut(color) {
+            let mut low = 0.;
+            let mut high = color.chroma;
+            for _ in 0..16 {
+                let chroma = (low + high) / 2.;
+                if in_gamut(Oklch { chroma, ..color }) {
+                    low = chroma;
+                } else {
+                    high = chroma;
+                }
+            }
+            color.chroma = low;
+        }
+        let [r, g, b] = color.to_linear_srgb();
+        Rgba {
+            r: linear_to_srgb(r),
+            g: linear_to_srgb(g),
+            b: linear_to_srgb(b),
+            a: 1.,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[repr(C)]
 pub enum BackgroundTag {
@@ -870,6 +1028,22 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_oklch_round_trip() {
+        let color = Oklch::from(rgb(0x7c3aed));
+        let round_trip = Oklch::from(Rgba::from(color));
+        assert!((round_trip.lightness - color.lightness).abs() < 0.001);
+        assert!((round_trip.hue - color.hue).abs() < 0.001);
+
+        let out_of_gamut = Rgba::from(Oklch {
+            chroma: 0.5,
+            ..color
+        });
+        let reduced = Oklch::from(out_of_gamut);
+        assert!(reduced.chroma < 0.5);
+        assert!((reduced.lightness - color.lightness).abs() < 0.01);
+    }
+
     #[test]
     fn test_deserialize_three_value_hex_to_rgba() {
         let actual: Rgba = serde_json::from_value(json!("#f09")).unwrap();
@@ -939,4 +1113,20 @@ mod tests {
         assert!(!background.is_transparent());
         assert!(background.opacity(0.0).is_transparent());
     }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = rgba(0x000000ff);
+        let white = rgba(0xffffffff);
+        assert!((black.contrast_ratio(white) - 21.).abs() < 0.01);
+        assert!((white.contrast_ratio(black) - 21.).abs() < 0.01);
+        assert_eq!(white.contrast_ratio(white), 1.);
+
+        let grey = Hsla::from(rgba(0x767676ff));
+        assert!(grey.meets_contrast(white.into(), ContrastLevel::Aa, false));
+        assert!(!grey.meets_contrast(white.into(), ContrastLevel::Aaa, false));
+        let light_grey = Hsla::from(rgba(0x888888ff));
+        assert!(!light_grey.meets_contrast(white.into(), ContrastLevel::Aa, false));
+        assert!(light_grey.meets_contrast(white.into(), ContrastLevel::Aa, true));
+    }
 }
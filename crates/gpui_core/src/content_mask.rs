@@ -1,14 +1,19 @@
-use crate::{Bounds, Pixels, ScaledPixels};
-use std::fmt::Debug;
+use crate::{Bounds, Corner, Corners, Pixels, ScaledPixels};
+use std::{
+    fmt::Debug,
+    ops::{Add, Sub},
+};
 
 /// Indicates which region of the window is visible. Content falling outside of this mask will not be
-/// rendered. Currently, only rectangular content masks are supported, but we give the mask its own type
-/// to leave room to support more complex shapes in the future.
+/// rendered. The region is a rectangle, optionally with rounded corners.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[repr(C)]
 pub struct ContentMask<P: Clone + Debug + Default + PartialEq> {
     /// The bounds
     pub bounds: Bounds<P>,
+    /// The radii of the mask's corners. Content outside a rounded corner is clipped with
+    /// antialiasing.
+    pub corner_radii: Corners<P>,
 }
 
 impl ContentMask<Pixels> {
@@ -16,12 +21,71 @@ impl ContentMask<Pixels> {
     pub fn scale(&self, factor: f32) -> ContentMask<ScaledPixels> {
         ContentMask {
             bounds: self.bounds.scale(factor),
+            corner_radii: self.corner_radii.scale(factor),
         }
     }
+}
 
+impl<P> ContentMask<P>
+where
+    P: Ord + Add<P, Output = P> + Sub<Output = P> + Clone + Debug + Default + PartialEq,
+{
     /// Intersect the content mask with the given content mask.
+    ///
+    /// The intersection keeps the rounded corners of the masks whose corners it shares. Where a
+    /// rounded corner of one mask cuts into the other without sharing a corner with it, the
+    /// intersection is approximated by the rectangle.
     pub fn intersect(&self, other: &Self) -> Self {
         let bounds = self.bounds.intersect(&other.bounds);
-        ContentMask { bounds }
+        let corner_radius = |corner: Corner| {
+            let point = bounds.corner(corner);
+            [self, other]
+                .into_iter()
+                .filter(|mask| mask.bounds.corner(corner) == point)
+                .map(|mask| mask.corner_radii.corner(corner))
+                .max()
+                .unwrap_or_default()
+        };
+        let corner_radii = Corners {
+            top_left: corner_radius(Corner::TopLeft),
+            top_right: corner_radius(Corner::TopRight),
+            bottom_right: corner_radius(Corner::BottomRight),
+            bottom_left: corner_radius(Corner::BottomLeft),
+        };
+        ContentMask {
+            bounds,
+            corner_radii,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point, px, size};
+
+    #[test]
+    fn test_intersect_keeps_shared_rounded_corners() {
+        let rounded = ContentMask {
+            bounds: Bounds::new(point(px(0.), px(0.)), size(px(100.), px(100.))),
+            corner_radii: Corners::all(px(8.)),
+        };
+        let left_half = ContentMask {
+            bounds: Bounds::new(point(px(0.), px(0.)), size(px(50.), px(100.))),
+            corner_radii: Corners::default(),
+        };
+
+        let mask = rounded.intersect(&left_half);
+        assert_eq!(mask.bounds, left_half.bounds);
+        assert_eq!(
+            mask.corner_radii,
+            Corners {
+                top_left: px(8.),
+                top_right: px(0.),
+                bottom_right: px(0.),
+                bottom_left: px(8.),
+            }
+        );
+        assert_eq!(left_half.intersect(&rounded), mask);
     }
 }
@@ -0,0 +1,340 @@
+//! A C ABI for building [`Scene`]s and reading their batches, so that engines written in other
+//! languages can produce or consume GPUI's renderer-agnostic scene format.
+//!
+//! Scenes are created with [`gpui_scene_new`] and freed with [`gpui_scene_free`]. Primitives are
+//! pushed in paint order, and once [`gpui_scene_finish`] has been called,
+//! [`gpui_scene_batches`] lists them in the order they're drawn. Batches point into the
+//! scene's primitive arrays, whose `#[repr(C)]` layouts are the ones GPUI's renderers upload.
+//!
+//! Text is pushed as glyphs: sprites whose tiles hold the coverage of glyphs rasterized into an
+//! atlas that the caller manages.
+//!
+//! The `gpui_core_ffi` crate builds this module as shared and static libraries, and declares it
+//! for C in its `include/gpui_core.h`.
+
+use std::ffi::c_void;
+
+use crate::{
+    AtlasTile, Bounds, ContentMask, Corners, Edges, MonochromeSprite, PrimitiveBatch, Quad, Rgba,
+    ScaledPixels, Scene, TransformationMatrix, solid_background,
+};
+
+/// A rectangle with a solid background and border.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct QuadDesc {
+    /// The bounds of the quad.
+    pub bounds: Bounds<ScaledPixels>,
+    /// The bounds the quad is clipped to.
+    pub clip: Bounds<ScaledPixels>,
+    /// The color of the quad's background.
+    pub background: Rgba,
+    /// The color of the quad's border.
+    pub border_color: Rgba,
+    /// The radius of every corner.
+    pub corner_radius: ScaledPixels,
+    /// The width of every side of the border.
+    pub border_width: ScaledPixels,
+}
+
+/// A glyph drawn from a monochrome atlas tile.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct GlyphDesc {
+    /// The bounds of the glyph.
+    pub bounds: Bounds<ScaledPixels>,
+    /// The bounds the glyph is clipped to.
+    pub clip: Bounds<ScaledPixels>,
+    /// The color the glyph is drawn in.
+    pub color: Rgba,
+    /// The tile that holds the glyph's coverage.
+    pub tile: AtlasTile,
+}
+
+/// The kind of primitives in a [`Batch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum BatchKind {
+    /// [`crate::Shadow`]s.
+    Shadows,
+    /// [`Quad`]s.
+    Quads,
+    /// [`crate::Underline`]s.
+    Underlines,
+    /// [`MonochromeSprite`]s, including glyphs.
+    MonochromeSprites,
+    /// [`crate::PolychromeSprite`]s.
+    PolychromeSprites,
+}
+
+/// A run of primitives of one kind that can be drawn together.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct Batch {
+    /// The kind of the primitives.
+    pub kind: BatchKind,
+    /// The index of the atlas texture that the tiles of sprite batches are in.
+    pub texture_index: u32,
+    /// The first primitive, whose type depends on `kind`.
+    pub primitives: *const c_void,
+    /// The number of primitives.
+    pub len: usize,
+}
+
+/// Creates an empty scene.
+#[unsafe(no_mangle)]
+pub extern "C" fn gpui_scene_new() -> *mut Scene {
+    Box::into_raw(Box::default())
+}
+
+/// Frees a scene created with [`gpui_scene_new`].
+///
+/// # Safety
+///
+/// `scene` must be null or a scene created with [`gpui_scene_new`] that hasn't been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_free(scene: *mut Scene) {
+    if !scene.is_null() {
+        drop(unsafe { Box::from_raw(scene) });
+    }
+}
+
+/// Removes every primitive from a scene, so that it can be reused for the next frame.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_clear(scene: *mut Scene) {
+    if let Some(scene) = unsafe { scene.as_mut() } {
+        scene.clear();
+    }
+}
+
+/// Starts a layer: primitives pushed until the matching [`gpui_scene_pop_layer`] batch
+/// together even where they overlap.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_push_layer(scene: *mut Scene, bounds: Bounds<ScaledPixels>) {
+    if let Some(scene) = unsafe { scene.as_mut() } {
+        scene.push_layer(bounds);
+    }
+}
+
+/// Ends the layer started by the last [`gpui_scene_push_layer`].
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_pop_layer(scene: *mut Scene) {
+    if let Some(scene) = unsafe { scene.as_mut() } {
+        scene.pop_layer();
+    }
+}
+
+/// Paints a quad above the primitives pushed before it.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`], and `quad` must be null
+/// or point to a valid [`QuadDesc`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_push_quad(scene: *mut Scene, quad: *const QuadDesc) {
+    let (Some(scene), Some(quad)) = (unsafe { scene.as_mut() }, unsafe { quad.as_ref() }) else {
+        return;
+    };
+    scene.insert_primitive(Quad {
+        order: 0,
+        border_style: Default::default(),
+        bounds: quad.bounds,
+        content_mask: ContentMask {
+            bounds: quad.clip,
+            ..Default::default()
+        },
+        background: solid_background(quad.background),
+        border_color: quad.border_color.into(),
+        corner_radii: Corners::all(quad.corner_radius),
+        vertical_corner_radii: Corners::default(),
+        border_widths: Edges::all(quad.border_width),
+        shadow: Default::default(),
+        transformation: TransformationMatrix::unit(),
+        dash_speed: 0.,
+        pad: 0,
+    });
+}
+
+/// Paints a glyph above the primitives pushed before it.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`], and `glyph` must be null
+/// or point to a valid [`GlyphDesc`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_push_glyph(scene: *mut Scene, glyph: *const GlyphDesc) {
+    let (Some(scene), Some(glyph)) = (unsafe { scene.as_mut() }, unsafe { glyph.as_ref() }) else {
+        return;
+    };
+    scene.insert_primitive(MonochromeSprite {
+        order: 0,
+        distance_field: 0,
+        bounds: glyph.bounds,
+        content_mask: ContentMask {
+            bounds: glyph.clip,
+            ..Default::default()
+        },
+        color: glyph.color.into(),
+        tile: glyph.tile.clone(),
+        transformation: TransformationMatrix::unit(),
+    });
+}
+
+/// Sorts the scene's primitives into draw order. Call this after pushing a frame's primitives
+/// and before reading its batches.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_finish(scene: *mut Scene) {
+    if let Some(scene) = unsafe { scene.as_mut() } {
+        scene.finish();
+    }
+}
+
+/// Writes up to `capacity` of the scene's batches, in draw order, to `batches`, and returns the
+/// total number of batches. Call it with a `capacity` of zero to size the buffer.
+///
+/// The batches point into the scene, and are invalidated when it's next modified or freed.
+///
+/// # Safety
+///
+/// `scene` must be null or a live scene created with [`gpui_scene_new`], and `batches` must be
+/// valid for writing `capacity` batches.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gpui_scene_batches(
+    scene: *const Scene,
+    batches: *mut Batch,
+    capacity: usize,
+) -> usize {
+    let Some(scene) = (unsafe { scene.as_ref() }) else {
+        return 0;
+    };
+    let mut count = 0;
+    for batch in scene.batches().filter_map(|batch| ffi_batch(&batch)) {
+        if count < capacity {
+            unsafe { batches.add(count).write(batch) };
+        }
+        count += 1;
+    }
+    count
+}
+
+fn ffi_batch(batch: &PrimitiveBatch) -> Option<Batch> {
+    fn primitives<T>(kind: BatchKind, texture_index: u32, primitives: &[T]) -> Batch {
+        Batch {
+            kind,
+            texture_index,
+            primitives: primitives.as_ptr().cast(),
+            len: primitives.len(),
+        }
+    }
+
+    match batch {
+        PrimitiveBatch::Shadows(shadows) => Some(primitives(BatchKind::Shadows, 0, shadows)),
+        PrimitiveBatch::Quads(quads) => Some(primitives(BatchKind::Quads, 0, quads)),
+        PrimitiveBatch::Underlines(underlines) => {
+            Some(primitives(BatchKind::Underlines, 0, underlines))
+        }
+        PrimitiveBatch::MonochromeSprites {
+            texture_id,
+            sprites,
+        } => Some(primitives(
+            BatchKind::MonochromeSprites,
+            texture_id.index,
+            sprites,
+        )),
+        PrimitiveBatch::PolychromeSprites {
+            texture_id,
+            sprites,
+        } => Some(primitives(
+            BatchKind::PolychromeSprites,
+            texture_id.index,
+            sprites,
+        )),
+        // These can't be pushed through the C ABI.
+        PrimitiveBatch::BackdropBlurs(_)
+        | PrimitiveBatch::TiledQuads(_)
+        | PrimitiveBatch::Paths(_)
+        | PrimitiveBatch::Surfaces(_)
+        | PrimitiveBatch::CustomRenderPrimitives(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtlasTextureId, AtlasTextureKind, DevicePixels, TileId, point, size};
+
+    #[test]
+    fn test_scene_batches() {
+        let bounds = Bounds::new(
+            point(ScaledPixels(0.), ScaledPixels(0.)),
+            size(ScaledPixels(10.), ScaledPixels(10.)),
+        );
+        let quad = QuadDesc {
+            bounds,
+            clip: bounds,
+            background: Rgba {
+                r: 1.,
+                g: 0.,
+                b: 0.,
+                a: 1.,
+            },
+            border_color: Rgba::default(),
+            corner_radius: ScaledPixels(2.),
+            border_width: ScaledPixels(0.),
+        };
+        let glyph = GlyphDesc {
+            bounds,
+            clip: bounds,
+            color: Rgba::default(),
+            tile: AtlasTile {
+                texture_id: AtlasTextureId {
+                    index: 3,
+                    kind: AtlasTextureKind::Monochrome,
+                },
+                tile_id: TileId(0),
+                padding: 0,
+                bounds: Bounds::new(
+                    point(DevicePixels(0), DevicePixels(0)),
+                    size(DevicePixels(10), DevicePixels(10)),
+                ),
+            },
+        };
+
+        unsafe {
+            let scene = gpui_scene_new();
+            gpui_scene_push_quad(scene, &quad);
+            gpui_scene_push_glyph(scene, &glyph);
+            gpui_scene_finish(scene);
+
+            assert_eq!(gpui_scene_batches(scene, std::ptr::null_mut(), 0), 2);
+            let mut batches = Vec::with_capacity(2);
+            let count = gpui_scene_batches(scene, batches.as_mut_ptr(), 2);
+            batches.set_len(count);
+
+            assert_eq!(batches[0].kind, BatchKind::Quads);
+            assert_eq!(batches[0].len, 1);
+            let quad = &*batches[0].primitives.cast::<Quad>();
+            assert_eq!(quad.corner_radii.top_left, ScaledPixels(2.));
+            assert_eq!(batches[1].kind, BatchKind::MonochromeSprites);
+            assert_eq!(batches[1].texture_index, 3);
+
+            gpui_scene_free(scene);
+        }
+    }
+}
@@ -2335,6 +2335,62 @@ impl<T: Div<f32, Output = T> + Ord + Clone + Debug + Default + PartialEq> Corner
             bottom_left: cmp::min(self.bottom_left, max),
         }
     }
+
+    /// Clamps the radii of elliptical corners to fit a quad, as with CSS's
+    /// `border-radius: 20px / 10px`. These corners are the horizontal radii, which are clamped to
+    /// half the quad's width, and vertical radii are clamped to half its height. A corner without
+    /// a vertical radius takes its horizontal radius on both axes, and a corner with no radius on
+    /// either axis is square.
+    ///
+    /// # Arguments
+    ///
+    /// * `vertical` - The vertical radii of the corners.
+    /// * `size` - The size of the quad which limits the size of the corner radii.
+    ///
+    /// # Returns
+    ///
+    /// The horizontal and vertical radii, clamped to fit.
+    #[must_use]
+    pub fn clamp_elliptical_radii_for_quad_size(
+        self,
+        vertical: Corners<T>,
+        size: Size<T>,
+    ) -> (Corners<T>, Corners<T>) {
+        let max_horizontal = size.width / 2.;
+        let max_vertical = size.height / 2.;
+        let clamp = |horizontal: T, vertical: T| {
+            let vertical = if vertical == T::default() {
+                horizontal.clone()
+            } else {
+                vertical
+            };
+            let horizontal = cmp::min(horizontal, max_horizontal.clone());
+            let vertical = cmp::min(vertical, max_vertical.clone());
+            if horizontal == T::default() || vertical == T::default() {
+                (T::default(), T::default())
+            } else {
+                (horizontal, vertical)
+            }
+        };
+        let (top_left, top_left_vertical) = clamp(self.top_left, vertical.top_left);
+        let (top_right, top_right_vertical) = clamp(self.top_right, vertical.top_right);
+        let (bottom_right, bottom_right_vertical) = clamp(self.bottom_right, vertical.bottom_right);
+        let (bottom_left, bottom_left_vertical) = clamp(self.bottom_left, vertical.bottom_left);
+        (
+            Corners {
+                top_left,
+                top_right,
+                bottom_right,
+                bottom_left,
+            },
+            Corners {
+                top_left: top_left_vertical,
+                top_right: top_right_vertical,
+                bottom_right: bottom_right_vertical,
+                bottom_left: bottom_left_vertical,
+            },
+        )
+    }
 }
 
 impl<T: Clone + Debug + Default + PartialEq> Corners<T> {
@@ -3279,17 +3335,20 @@ impl Serialize for AbsoluteLength {
     }
 }
 
-/// A non-auto length that can be defined in pixels, rems, or percent of parent.
+/// A non-auto length that can be defined in pixels, rems, characters, or percent of parent.
 ///
 /// This enum represents lengths that have a specific value, as opposed to lengths that are automatically
-/// determined by the context. It includes absolute lengths in pixels or rems, and relative lengths as a
-/// fraction of the parent's size.
+/// determined by the context. It includes absolute lengths in pixels or rems, lengths in characters of
+/// the element's font, and relative lengths as a fraction of the parent's size.
 #[derive(Clone, Copy, Neg, PartialEq)]
 pub enum DefiniteLength {
     /// An absolute length specified in pixels or rems.
     Absolute(AbsoluteLength),
     /// A relative length specified as a fraction of the parent's size, between 0 and 1.
     Fraction(f32),
+    /// A length in columns of text, like the CSS `ch` unit: a multiple of the advance of the `0`
+    /// character in the element's font. Layout measures it in the element's text style.
+    Ch(f32),
 }
 
 impl DefiniteLength {
@@ -3297,6 +3356,8 @@ impl DefiniteLength {
     ///
     /// If the `DefiniteLength` is an absolute length, it will be directly converted to `Pixels`.
     /// If it is a fraction, the fraction will be multiplied by the `base_size` to get the length in pixels.
+    /// No font is known here, so a `ch` is taken to be half a rem wide, as CSS does when the `0`
+    /// character can't be measured.
     ///
     /// # Arguments
     ///
@@ -3328,6 +3389,7 @@ impl DefiniteLength {
                 AbsoluteLength::Pixels(px) => px * fraction,
                 AbsoluteLength::Rems(rems) => rems * rem_size * fraction,
             },
+            DefiniteLength::Ch(columns) => rem_size * 0.5 * columns,
         }
     }
 }
@@ -3343,11 +3405,12 @@ impl Display for DefiniteLength {
         match self {
             DefiniteLength::Absolute(length) => write!(f, "{length}"),
             DefiniteLength::Fraction(fraction) => write!(f, "{}%", (fraction * 100.0) as i32),
+            DefiniteLength::Ch(columns) => write!(f, "{columns}ch"),
         }
     }
 }
 
-const EXPECTED_DEFINITE_LENGTH: &str = "expected number with 'px', 'rem', or '%' suffix";
+const EXPECTED_DEFINITE_LENGTH: &str = "expected number with 'px', 'rem', 'ch', or '%' suffix";
 
 impl TryFrom<&'_ str> for DefiniteLength {
     type Error = anyhow::Error;
@@ -3358,6 +3421,11 @@ impl TryFrom<&'_ str> for DefiniteLength {
                 format!("invalid DefiniteLength '{value}', expected {EXPECTED_DEFINITE_LENGTH}")
             })?;
             Ok(DefiniteLength::Fraction(fraction / 100.0))
+        } else if let Some(columns) = value.strip_suffix("ch") {
+            let columns: f32 = columns.parse::<f32>().with_context(|| {
+                format!("invalid DefiniteLength '{value}', expected {EXPECTED_DEFINITE_LENGTH}")
+            })?;
+            Ok(DefiniteLength::Ch(columns))
         } else if let Ok(absolute_length) = value.try_into() {
             Ok(DefiniteLength::Absolute(absolute_length))
         } else {
@@ -3376,7 +3444,7 @@ impl JsonSchema for DefiniteLength {
     fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
         json_schema!({
             "type": "string",
-            "pattern": r"^-?\d+(\.\d+)?(px|rem|%)$"
+            "pattern": r"^-?\d+(\.\d+)?(px|rem|ch|%)$"
         })
     }
 }
@@ -3534,6 +3602,14 @@ pub const fn relative(fraction: f32) -> DefiniteLength {
     DefiniteLength::Fraction(fraction)
 }
 
+/// Constructs a `DefiniteLength` of the given number of text columns, like the CSS `ch` unit.
+///
+/// A column is as wide as the `0` character in the font of the element the length is used on, so
+/// in a monospace font `ch(n)` fits exactly `n` characters.
+pub const fn ch(columns: f32) -> DefiniteLength {
+    DefiniteLength::Ch(columns)
+}
+
 /// Returns the Golden Ratio, i.e. `~(1.0 + sqrt(5.0)) / 2.0`.
 pub const fn phi() -> DefiniteLength {
     relative(1.618_034)
@@ -3792,6 +3868,7 @@ impl IsZero for DefiniteLength {
         match self {
             DefiniteLength::Absolute(length) => length.is_zero(),
             DefiniteLength::Fraction(fraction) => *fraction == 0.,
+            DefiniteLength::Ch(columns) => *columns == 0.,
         }
     }
 }
@@ -3838,45 +3915,6 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_bounds_intersects() {
-        let bounds1 = Bounds {
-            origin: Point { x: 0.0, y: 0.0 },
-            size: Size {
-                width: 5.0,
-                height: 5.0,
-            },
-        };
-        let bounds2 = Bounds {
-            origin: Point { x: 4.0, y: 4.0 },
-            size: Size {
-                width: 5.0,
-                height: 5.0,
-            },
-        };
-        let bounds3 = Bounds {
-            origin: Point { x: 10.0, y: 10.0 },
-            size: Size {
-                width: 5.0,
-                height: 5.0,
-            },
-        };
-
-        // Test Case 1: Intersecting bounds
-        assert!(bounds1.intersects(&bounds2));
-
-        // Test Case 2: Non-Intersecting bounds
-        assert!(!bounds1.intersects(&bounds3));
-
-        // Test Case 3: Bounds intersecting with themselves
-        assert!(bounds1.intersects(&bounds1));
-    }
-}
-
 // Conversions between gpui_core and taffy geometry types.
 
 impl<T, T2> From<taffy::geometry::Point<T>> for Point<T2>
@@ -3928,3 +3966,89 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_intersects() {
+        let bounds1 = Bounds {
+            origin: Point { x: 0.0, y: 0.0 },
+            size: Size {
+                width: 5.0,
+                height: 5.0,
+            },
+        };
+        let bounds2 = Bounds {
+            origin: Point { x: 4.0, y: 4.0 },
+            size: Size {
+                width: 5.0,
+                height: 5.0,
+            },
+        };
+        let bounds3 = Bounds {
+            origin: Point { x: 10.0, y: 10.0 },
+            size: Size {
+                width: 5.0,
+                height: 5.0,
+            },
+        };
+
+        // Test Case 1: Intersecting bounds
+        assert!(bounds1.intersects(&bounds2));
+
+        // Test Case 2: Non-Intersecting bounds
+        assert!(!bounds1.intersects(&bounds3));
+
+        // Test Case 3: Bounds intersecting with themselves
+        assert!(bounds1.intersects(&bounds1));
+    }
+
+    #[test]
+    fn test_clamp_elliptical_radii_for_quad_size() {
+        let horizontal = Corners {
+            top_left: px(80.),
+            top_right: px(20.),
+            bottom_right: px(0.),
+            bottom_left: px(10.),
+        };
+        let vertical = Corners {
+            bottom_left: px(0.),
+            ..Corners::all(px(30.))
+        };
+        let (horizontal, vertical) =
+            horizontal.clamp_elliptical_radii_for_quad_size(vertical, size(px(100.), px(40.)));
+        assert_eq!(
+            horizontal,
+            Corners {
+                top_left: px(50.),
+                top_right: px(20.),
+                bottom_right: px(0.),
+                bottom_left: px(10.),
+            }
+        );
+        assert_eq!(
+            vertical,
+            Corners {
+                top_left: px(20.),
+                top_right: px(20.),
+                bottom_right: px(0.),
+                bottom_left: px(10.),
+            }
+        );
+    }
+
+    #[test]
+    fn test_ch_length() {
+        let length = DefiniteLength::try_from("2.5ch").unwrap();
+        assert_eq!(length, ch(2.5));
+        assert_eq!(length.to_string(), "2.5ch");
+        assert_eq!(
+            length.to_pixels(AbsoluteLength::Pixels(px(100.)), px(16.)),
+            px(20.),
+            "without a font, a column is half a rem wide"
+        );
+        assert!(DefiniteLength::try_from("ch").is_err());
+    }
+}
@@ -9,10 +9,14 @@ mod atlas;
 pub mod bounds_tree;
 mod color;
 mod content_mask;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod geometry;
 pub mod scene;
 mod shared_string;
 mod shared_uri;
+#[cfg(test)]
+mod test_helpers;
 
 pub use atlas::*;
 pub use bounds_tree::*;
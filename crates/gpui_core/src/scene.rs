@@ -1,3 +1,9 @@
+mod builder;
+mod pdf;
+mod svg;
+
+pub use builder::*;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -6,29 +12,93 @@ use crate::{
     Point, Radians, ScaledPixels, Size, bounds_tree::BoundsTree, point,
 };
 use std::{
+    any::Any,
     fmt::Debug,
     iter::Peekable,
     ops::{Add, Range, Sub},
     slice,
+    sync::Arc,
 };
 
 #[allow(non_camel_case_types, unused)]
 pub type PathVertex_ScaledPixels = PathVertex<ScaledPixels>;
 
+/// The position of a primitive in the sequence in which the scene is drawn. Primitives are drawn
+/// in ascending order, and primitives with the same order are drawn by [`PrimitiveKind`].
 pub type DrawOrder = u32;
 
+/// The primitives painted into a window during a frame, sorted into batches for the renderer.
+///
+/// Wherever primitives overlap, the one painted last is drawn on top, regardless of its kind.
+/// Each primitive is drawn according to the key `(order, kind)`:
+///
+/// - Outside a layer, a primitive's order is one greater than the order of any primitive painted
+///   before it whose painted bounds overlap its own. Primitives that don't overlap share orders,
+///   so that primitives of the same kind are drawn in large batches.
+/// - Inside a layer, primitives share the layer's order, so that they batch together even where
+///   they overlap. The layer's order is raised whenever a primitive's kind would otherwise draw
+///   it beneath a primitive painted before it in the layer.
+///
+/// Stacking elements above others, as deferred draws do, is a matter of painting them later.
+///
+/// The scene is also divided into [`SceneLayer`]s, each drawn entirely above the ones before it.
+/// Orders restart above the previous layer's, so an overlay's primitives never interleave with
+/// the base content's batches, however much they overlap it.
 #[derive(Default)]
 pub struct Scene {
     pub paint_operations: Vec<PaintOperation>,
     primitive_bounds: BoundsTree<ScaledPixels>,
-    layer_stack: Vec<DrawOrder>,
+    layer_stack: Vec<Layer>,
+    scene_layer: SceneLayer,
+    /// The lowest order of the current scene layer's primitives.
+    min_order: DrawOrder,
+    max_order: DrawOrder,
+    /// The lowest orders of the scene layers above the base layer that have been started.
+    scene_layer_start_orders: Vec<DrawOrder>,
     pub shadows: Vec<Shadow>,
+    pub backdrop_blurs: Vec<BackdropBlur>,
     pub quads: Vec<Quad>,
+    pub tiled_quads: Vec<TiledQuad>,
     pub paths: Vec<Path<ScaledPixels>>,
     pub underlines: Vec<Underline>,
     pub monochrome_sprites: Vec<MonochromeSprite>,
     pub polychrome_sprites: Vec<PolychromeSprite>,
     pub surfaces: Vec<PaintSurface>,
+    pub custom_render_primitives: Vec<CustomRenderPrimitive>,
+}
+
+impl Clone for Scene {
+    fn clone(&self) -> Self {
+        let mut scene = Self::default();
+        scene.clone_from(self);
+        scene
+    }
+
+    /// Copies `source` into the allocations of this scene's primitive lists, so that keeping a
+    /// copy of every frame is cheap.
+    fn clone_from(&mut self, source: &Self) {
+        self.paint_operations.clone_from(&source.paint_operations);
+        self.primitive_bounds.clone_from(&source.primitive_bounds);
+        self.layer_stack.clone_from(&source.layer_stack);
+        self.scene_layer = source.scene_layer;
+        self.min_order = source.min_order;
+        self.max_order = source.max_order;
+        self.scene_layer_start_orders
+            .clone_from(&source.scene_layer_start_orders);
+        self.shadows.clone_from(&source.shadows);
+        self.backdrop_blurs.clone_from(&source.backdrop_blurs);
+        self.quads.clone_from(&source.quads);
+        self.tiled_quads.clone_from(&source.tiled_quads);
+        self.paths.clone_from(&source.paths);
+        self.underlines.clone_from(&source.underlines);
+        self.monochrome_sprites
+            .clone_from(&source.monochrome_sprites);
+        self.polychrome_sprites
+            .clone_from(&source.polychrome_sprites);
+        self.surfaces.clone_from(&source.surfaces);
+        self.custom_render_primitives
+            .clone_from(&source.custom_render_primitives);
+    }
 }
 
 impl Scene {
@@ -36,13 +106,20 @@ impl Scene {
         self.paint_operations.clear();
         self.primitive_bounds.clear();
         self.layer_stack.clear();
+        self.scene_layer = SceneLayer::Base;
+        self.min_order = 0;
+        self.max_order = 0;
+        self.scene_layer_start_orders.clear();
         self.paths.clear();
         self.shadows.clear();
+        self.backdrop_blurs.clear();
         self.quads.clear();
+        self.tiled_quads.clear();
         self.underlines.clear();
         self.monochrome_sprites.clear();
         self.polychrome_sprites.clear();
         self.surfaces.clear();
+        self.custom_render_primitives.clear();
     }
 
     pub fn len(&self) -> usize {
@@ -53,15 +130,64 @@ impl Scene {
         self.paint_operations.is_empty()
     }
 
+    /// Whether the scene has borders whose dashes move, and so must be presented every frame
+    /// for them to keep moving.
+    pub fn has_moving_dashes(&self) -> bool {
+        self.quads.iter().any(|quad| quad.dash_speed != 0.)
+    }
+
+    /// Returns the scene layer that primitives are currently inserted into.
+    pub fn scene_layer(&self) -> SceneLayer {
+        self.scene_layer
+    }
+
+    /// Inserts the primitives painted from now on into `scene_layer`, which draws them above
+    /// everything painted so far. Scene layers can't be returned to once left, so switching to a
+    /// lower layer than the current one has no effect.
+    pub fn set_scene_layer(&mut self, scene_layer: SceneLayer) {
+        if scene_layer <= self.scene_layer {
+            return;
+        }
+        debug_assert!(
+            self.layer_stack.is_empty(),
+            "scene layer changed inside a layer"
+        );
+        self.scene_layer = scene_layer;
+        self.primitive_bounds.clear();
+        self.min_order = self.max_order;
+        self.scene_layer_start_orders.push(self.min_order + 1);
+    }
+
     pub fn push_layer(&mut self, bounds: Bounds<ScaledPixels>) {
-        let order = self.primitive_bounds.insert(bounds);
-        self.layer_stack.push(order);
+        // A nested layer is drawn above everything painted so far in its parent.
+        let min_order = self
+            .layer_stack
+            .last()
+            .map_or(self.min_order + 1, |parent| parent.order + 1);
+        let order = self.primitive_bounds.insert_at_least(bounds, min_order);
+        self.layer_stack.push(Layer {
+            bounds,
+            painted_bounds: bounds,
+            start_order: order,
+            order,
+            last_kind: None,
+        });
         self.paint_operations
             .push(PaintOperation::StartLayer(bounds));
     }
 
     pub fn pop_layer(&mut self) {
-        self.layer_stack.pop();
+        if let Some(layer) = self.layer_stack.pop() {
+            match self.layer_stack.last_mut() {
+                Some(parent) => parent.include(&layer),
+                // Primitives painted after the layer must be drawn above all of it.
+                None if layer.order > layer.start_order || layer.painted_bounds != layer.bounds => {
+                    self.primitive_bounds
+                        .insert_at_least(layer.painted_bounds, layer.order);
+                }
+                None => {}
+            }
+        }
         self.paint_operations.push(PaintOperation::EndLayer);
     }
 
@@ -75,20 +201,30 @@ impl Scene {
             return;
         }
 
-        let order = self
-            .layer_stack
-            .last()
-            .copied()
-            .unwrap_or_else(|| self.primitive_bounds.insert(clipped_bounds));
+        let order = match self.layer_stack.last_mut() {
+            Some(layer) => layer.insert(clipped_bounds, primitive.kind()),
+            None => self
+                .primitive_bounds
+                .insert_at_least(clipped_bounds, self.min_order + 1),
+        };
+        self.max_order = self.max_order.max(order);
         match &mut primitive {
             Primitive::Shadow(shadow) => {
                 shadow.order = order;
                 self.shadows.push(shadow.clone());
             }
+            Primitive::BackdropBlur(blur) => {
+                blur.order = order;
+                self.backdrop_blurs.push(blur.clone());
+            }
             Primitive::Quad(quad) => {
                 quad.order = order;
                 self.quads.push(quad.clone());
             }
+            Primitive::TiledQuad(quad) => {
+                quad.order = order;
+                self.tiled_quads.push(quad.clone());
+            }
             Primitive::Path(path) => {
                 path.order = order;
                 path.id = PathId(self.paths.len());
@@ -110,6 +246,10 @@ impl Scene {
                 surface.order = order;
                 self.surfaces.push(surface.clone());
             }
+            Primitive::CustomRender(primitive) => {
+                primitive.order = order;
+                self.custom_render_primitives.push(primitive.clone());
+            }
         }
         self.paint_operations
             .push(PaintOperation::Primitive(primitive));
@@ -127,7 +267,9 @@ impl Scene {
 
     pub fn finish(&mut self) {
         self.shadows.sort_by_key(|shadow| shadow.order);
+        self.backdrop_blurs.sort_by_key(|blur| blur.order);
         self.quads.sort_by_key(|quad| quad.order);
+        self.tiled_quads.sort_by_key(|quad| quad.order);
         self.paths.sort_by_key(|path| path.order);
         self.underlines.sort_by_key(|underline| underline.order);
         self.monochrome_sprites
@@ -135,6 +277,26 @@ impl Scene {
         self.polychrome_sprites
             .sort_by_key(|sprite| (sprite.order, sprite.tile.tile_id));
         self.surfaces.sort_by_key(|surface| surface.order);
+        self.custom_render_primitives
+            .sort_by_key(|primitive| primitive.order);
+
+        #[cfg(debug_assertions)]
+        self.debug_assert_draw_order();
+    }
+
+    /// Checks that batches are drawn in the order of their primitives' draw order keys.
+    #[cfg(debug_assertions)]
+    fn debug_assert_draw_order(&self) {
+        let mut previous_key = (0, PrimitiveKind::Shadow);
+        for batch in self.batches() {
+            for key in batch.draw_order_keys() {
+                debug_assert!(
+                    key >= previous_key,
+                    "primitive drawn out of order: {key:?} after {previous_key:?}"
+                );
+                previous_key = key;
+            }
+        }
     }
 
     pub fn batches(&self) -> impl Iterator<Item = PrimitiveBatch<'_>> {
@@ -142,9 +304,15 @@ impl Scene {
             shadows: &self.shadows,
             shadows_start: 0,
             shadows_iter: self.shadows.iter().peekable(),
+            backdrop_blurs: &self.backdrop_blurs,
+            backdrop_blurs_start: 0,
+            backdrop_blurs_iter: self.backdrop_blurs.iter().peekable(),
             quads: &self.quads,
             quads_start: 0,
             quads_iter: self.quads.iter().peekable(),
+            tiled_quads: &self.tiled_quads,
+            tiled_quads_start: 0,
+            tiled_quads_iter: self.tiled_quads.iter().peekable(),
             paths: &self.paths,
             paths_start: 0,
             paths_iter: self.paths.iter().peekable(),
@@ -160,22 +328,161 @@ impl Scene {
             surfaces: &self.surfaces,
             surfaces_start: 0,
             surfaces_iter: self.surfaces.iter().peekable(),
+            custom_render_primitives: &self.custom_render_primitives,
+            custom_render_primitives_start: 0,
+            custom_render_primitives_iter: self.custom_render_primitives.iter().peekable(),
         }
     }
+
+    /// Counts the primitives in the scene and the batches they form, for diagnosing slow frames.
+    /// Call after [`Scene::finish`], as batches are only formed from sorted primitives.
+    pub fn stats(&self) -> SceneStats {
+        let mut stats = SceneStats {
+            shadows: self.shadows.len(),
+            backdrop_blurs: self.backdrop_blurs.len(),
+            quads: self.quads.len(),
+            tiled_quads: self.tiled_quads.len(),
+            paths: self.paths.len(),
+            underlines: self.underlines.len(),
+            monochrome_sprites: self.monochrome_sprites.len(),
+            polychrome_sprites: self.polychrome_sprites.len(),
+            surfaces: self.surfaces.len(),
+            custom_render_primitives: self.custom_render_primitives.len(),
+            batches: 0,
+            batch_breaks: 0,
+        };
+        let mut batch_groups = Vec::new();
+        let mut scene_layer_start_orders = self.scene_layer_start_orders.iter().peekable();
+        for batch in self.batches() {
+            // Each scene layer is batched independently of the layers beneath it.
+            if scene_layer_start_orders
+                .next_if(|start_order| batch.order() >= **start_order)
+                .is_some()
+            {
+                batch_groups.clear();
+            }
+            stats.batches += 1;
+            let group = (batch.kind(), batch.texture_id());
+            if batch_groups.contains(&group) {
+                stats.batch_breaks += 1;
+            } else {
+                batch_groups.push(group);
+            }
+        }
+        stats
+    }
+}
+
+/// Primitive and batch counts for a [`Scene`], returned by [`Scene::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SceneStats {
+    /// The number of shadows in the scene.
+    pub shadows: usize,
+    /// The number of backdrop blurs in the scene.
+    pub backdrop_blurs: usize,
+    /// The number of quads in the scene.
+    pub quads: usize,
+    /// The number of tiled quads in the scene, each counted once however many tiles it covers.
+    pub tiled_quads: usize,
+    /// The number of paths in the scene.
+    pub paths: usize,
+    /// The number of underlines and strikethroughs in the scene.
+    pub underlines: usize,
+    /// The number of monochrome sprites, such as glyphs and icons, in the scene.
+    pub monochrome_sprites: usize,
+    /// The number of polychrome sprites, such as images and emoji, in the scene.
+    pub polychrome_sprites: usize,
+    /// The number of surfaces in the scene.
+    pub surfaces: usize,
+    /// The number of custom render primitives in the scene.
+    pub custom_render_primitives: usize,
+    /// The number of batches the renderer draws the scene in.
+    pub batches: usize,
+    /// The number of batches beyond one for each kind of primitive and atlas texture in each
+    /// scene layer. Each break is a point where primitives of one kind had to be split into
+    /// separate draw calls, because overlapping primitives of another kind are drawn between them.
+    pub batch_breaks: usize,
+}
+
+impl SceneStats {
+    /// The total number of primitives in the scene.
+    pub fn primitives(&self) -> usize {
+        self.shadows
+            + self.backdrop_blurs
+            + self.quads
+            + self.tiled_quads
+            + self.paths
+            + self.underlines
+            + self.monochrome_sprites
+            + self.polychrome_sprites
+            + self.surfaces
+            + self.custom_render_primitives
+    }
+}
+
+/// A pass of the scene, drawn entirely above the passes before it. See [`Scene::set_scene_layer`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SceneLayer {
+    /// The window's content.
+    #[default]
+    Base,
+    /// Popups painted above the content, such as menus, tooltips and drag previews.
+    Overlay,
+    /// Debugging aids painted above everything else.
+    Debug,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Default)]
 pub enum PrimitiveKind {
     Shadow,
+    BackdropBlur,
     #[default]
     Quad,
+    TiledQuad,
     Path,
     Underline,
     MonochromeSprite,
     PolychromeSprite,
     Surface,
+    CustomRender,
+}
+
+/// The state of a layer pushed with [`Scene::push_layer`].
+#[derive(Clone, Copy, Debug)]
+struct Layer {
+    bounds: Bounds<ScaledPixels>,
+    /// The layer's bounds, grown to include the painted bounds of its primitives.
+    painted_bounds: Bounds<ScaledPixels>,
+    start_order: DrawOrder,
+    order: DrawOrder,
+    /// The kind of the last primitive drawn at the layer's current order.
+    last_kind: Option<PrimitiveKind>,
+}
+
+impl Layer {
+    /// Returns the draw order of a primitive painted into the layer.
+    fn insert(&mut self, bounds: Bounds<ScaledPixels>, kind: PrimitiveKind) -> DrawOrder {
+        if self.last_kind.is_some_and(|last_kind| kind < last_kind) {
+            self.order += 1;
+        }
+        self.last_kind = Some(kind);
+        self.painted_bounds = self.painted_bounds.union(&bounds);
+        self.order
+    }
+
+    /// Draws the rest of the layer above a nested layer that was popped.
+    fn include(&mut self, nested: &Layer) {
+        self.painted_bounds = self.painted_bounds.union(&nested.painted_bounds);
+        if nested.order > self.order {
+            self.order = nested.order;
+            self.last_kind = nested.last_kind;
+        }
+    }
 }
 
+// Nearly every operation is a primitive, so boxing them would only add allocations.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
 pub enum PaintOperation {
     Primitive(Primitive),
     StartLayer(Bounds<ScaledPixels>),
@@ -185,36 +492,90 @@ pub enum PaintOperation {
 #[derive(Clone)]
 pub enum Primitive {
     Shadow(Shadow),
+    BackdropBlur(BackdropBlur),
     Quad(Quad),
+    TiledQuad(TiledQuad),
     Path(Path<ScaledPixels>),
     Underline(Underline),
     MonochromeSprite(MonochromeSprite),
     PolychromeSprite(PolychromeSprite),
     Surface(PaintSurface),
+    CustomRender(CustomRenderPrimitive),
 }
 
 impl Primitive {
-    pub fn bounds(&self) -> &Bounds<ScaledPixels> {
+    /// The bounds the primitive draws into, used to order it against the primitives it overlaps.
+    pub fn bounds(&self) -> Bounds<ScaledPixels> {
+        match self {
+            Primitive::Shadow(shadow) => shadow.transformation.transform_bounds(
+                shadow
+                    .bounds
+                    .dilate(ScaledPixels(shadow.blur_radius.0 * 3.)),
+            ),
+            // The blur samples the backdrop around the region, so it's drawn above everything
+            // painted there before it.
+            Primitive::BackdropBlur(blur) => {
+                blur.bounds.dilate(ScaledPixels(blur.blur_radius.0 * 3.))
+            }
+            Primitive::Quad(quad) => quad.transformation.transform_bounds(quad.painted_bounds()),
+            Primitive::TiledQuad(quad) => quad.transformation.transform_bounds(quad.bounds),
+            Primitive::Path(path) => path.bounds,
+            Primitive::Underline(underline) => {
+                underline.transformation.transform_bounds(underline.bounds)
+            }
+            Primitive::MonochromeSprite(sprite) => {
+                sprite.transformation.transform_bounds(sprite.bounds)
+            }
+            Primitive::PolychromeSprite(sprite) => {
+                sprite.transformation.transform_bounds(sprite.bounds)
+            }
+            Primitive::Surface(surface) => surface.bounds,
+            Primitive::CustomRender(primitive) => primitive.bounds,
+        }
+    }
+
+    pub fn kind(&self) -> PrimitiveKind {
         match self {
-            Primitive::Shadow(shadow) => &shadow.bounds,
-            Primitive::Quad(quad) => &quad.bounds,
-            Primitive::Path(path) => &path.bounds,
-            Primitive::Underline(underline) => &underline.bounds,
-            Primitive::MonochromeSprite(sprite) => &sprite.bounds,
-            Primitive::PolychromeSprite(sprite) => &sprite.bounds,
-            Primitive::Surface(surface) => &surface.bounds,
+            Primitive::Shadow(_) => PrimitiveKind::Shadow,
+            Primitive::BackdropBlur(_) => PrimitiveKind::BackdropBlur,
+            Primitive::Quad(_) => PrimitiveKind::Quad,
+            Primitive::TiledQuad(_) => PrimitiveKind::TiledQuad,
+            Primitive::Path(_) => PrimitiveKind::Path,
+            Primitive::Underline(_) => PrimitiveKind::Underline,
+            Primitive::MonochromeSprite(_) => PrimitiveKind::MonochromeSprite,
+            Primitive::PolychromeSprite(_) => PrimitiveKind::PolychromeSprite,
+            Primitive::Surface(_) => PrimitiveKind::Surface,
+            Primitive::CustomRender(_) => PrimitiveKind::CustomRender,
         }
     }
 
     pub fn content_mask(&self) -> &ContentMask<ScaledPixels> {
         match self {
             Primitive::Shadow(shadow) => &shadow.content_mask,
+            Primitive::BackdropBlur(blur) => &blur.content_mask,
             Primitive::Quad(quad) => &quad.content_mask,
+            Primitive::TiledQuad(quad) => &quad.content_mask,
             Primitive::Path(path) => &path.content_mask,
             Primitive::Underline(underline) => &underline.content_mask,
             Primitive::MonochromeSprite(sprite) => &sprite.content_mask,
             Primitive::PolychromeSprite(sprite) => &sprite.content_mask,
             Primitive::Surface(surface) => &surface.content_mask,
+            Primitive::CustomRender(primitive) => &primitive.content_mask,
+        }
+    }
+
+    fn content_mask_mut(&mut self) -> &mut ContentMask<ScaledPixels> {
+        match self {
+            Primitive::Shadow(shadow) => &mut shadow.content_mask,
+            Primitive::BackdropBlur(blur) => &mut blur.content_mask,
+            Primitive::Quad(quad) => &mut quad.content_mask,
+            Primitive::TiledQuad(quad) => &mut quad.content_mask,
+            Primitive::Path(path) => &mut path.content_mask,
+            Primitive::Underline(underline) => &mut underline.content_mask,
+            Primitive::MonochromeSprite(sprite) => &mut sprite.content_mask,
+            Primitive::PolychromeSprite(sprite) => &mut sprite.content_mask,
+            Primitive::Surface(surface) => &mut surface.content_mask,
+            Primitive::CustomRender(primitive) => &mut primitive.content_mask,
         }
     }
 }
@@ -223,9 +584,15 @@ struct BatchIterator<'a> {
     shadows: &'a [Shadow],
     shadows_start: usize,
     shadows_iter: Peekable<slice::Iter<'a, Shadow>>,
+    backdrop_blurs: &'a [BackdropBlur],
+    backdrop_blurs_start: usize,
+    backdrop_blurs_iter: Peekable<slice::Iter<'a, BackdropBlur>>,
     quads: &'a [Quad],
     quads_start: usize,
     quads_iter: Peekable<slice::Iter<'a, Quad>>,
+    tiled_quads: &'a [TiledQuad],
+    tiled_quads_start: usize,
+    tiled_quads_iter: Peekable<slice::Iter<'a, TiledQuad>>,
     paths: &'a [Path<ScaledPixels>],
     paths_start: usize,
     paths_iter: Peekable<slice::Iter<'a, Path<ScaledPixels>>>,
@@ -241,6 +608,9 @@ struct BatchIterator<'a> {
     surfaces: &'a [PaintSurface],
     surfaces_start: usize,
     surfaces_iter: Peekable<slice::Iter<'a, PaintSurface>>,
+    custom_render_primitives: &'a [CustomRenderPrimitive],
+    custom_render_primitives_start: usize,
+    custom_render_primitives_iter: Peekable<slice::Iter<'a, CustomRenderPrimitive>>,
 }
 
 impl<'a> Iterator for BatchIterator<'a> {
@@ -252,7 +622,15 @@ impl<'a> Iterator for BatchIterator<'a> {
                 self.shadows_iter.peek().map(|s| s.order),
                 PrimitiveKind::Shadow,
             ),
+            (
+                self.backdrop_blurs_iter.peek().map(|b| b.order),
+                PrimitiveKind::BackdropBlur,
+            ),
             (self.quads_iter.peek().map(|q| q.order), PrimitiveKind::Quad),
+            (
+                self.tiled_quads_iter.peek().map(|q| q.order),
+                PrimitiveKind::TiledQuad,
+            ),
             (self.paths_iter.peek().map(|q| q.order), PrimitiveKind::Path),
             (
                 self.underlines_iter.peek().map(|u| u.order),
@@ -270,6 +648,10 @@ impl<'a> Iterator for BatchIterator<'a> {
                 self.surfaces_iter.peek().map(|s| s.order),
                 PrimitiveKind::Surface,
             ),
+            (
+                self.custom_render_primitives_iter.peek().map(|p| p.order),
+                PrimitiveKind::CustomRender,
+            ),
         ];
         orders_and_kinds.sort_by_key(|(order, kind)| (order.unwrap_or(u32::MAX), *kind));
 
@@ -298,6 +680,22 @@ impl<'a> Iterator for BatchIterator<'a> {
                     &self.shadows[shadows_start..shadows_end],
                 ))
             }
+            PrimitiveKind::BackdropBlur => {
+                let blurs_start = self.backdrop_blurs_start;
+                let mut blurs_end = blurs_start + 1;
+                self.backdrop_blurs_iter.next();
+                while self
+                    .backdrop_blurs_iter
+                    .next_if(|blur| (blur.order, batch_kind) < max_order_and_kind)
+                    .is_some()
+                {
+                    blurs_end += 1;
+                }
+                self.backdrop_blurs_start = blurs_end;
+                Some(PrimitiveBatch::BackdropBlurs(
+                    &self.backdrop_blurs[blurs_start..blurs_end],
+                ))
+            }
             PrimitiveKind::Quad => {
                 let quads_start = self.quads_start;
                 let mut quads_end = quads_start + 1;
@@ -312,6 +710,22 @@ impl<'a> Iterator for BatchIterator<'a> {
                 self.quads_start = quads_end;
                 Some(PrimitiveBatch::Quads(&self.quads[quads_start..quads_end]))
             }
+            PrimitiveKind::TiledQuad => {
+                let quads_start = self.tiled_quads_start;
+                let mut quads_end = quads_start + 1;
+                self.tiled_quads_iter.next();
+                while self
+                    .tiled_quads_iter
+                    .next_if(|quad| (quad.order, batch_kind) < max_order_and_kind)
+                    .is_some()
+                {
+                    quads_end += 1;
+                }
+                self.tiled_quads_start = quads_end;
+                Some(PrimitiveBatch::TiledQuads(
+                    &self.tiled_quads[quads_start..quads_end],
+                ))
+            }
             PrimitiveKind::Path => {
                 let paths_start = self.paths_start;
                 let mut paths_end = paths_start + 1;
@@ -400,6 +814,22 @@ impl<'a> Iterator for BatchIterator<'a> {
                     &self.surfaces[surfaces_start..surfaces_end],
                 ))
             }
+            PrimitiveKind::CustomRender => {
+                let primitives_start = self.custom_render_primitives_start;
+                let mut primitives_end = primitives_start + 1;
+                self.custom_render_primitives_iter.next();
+                while self
+                    .custom_render_primitives_iter
+                    .next_if(|primitive| (primitive.order, batch_kind) < max_order_and_kind)
+                    .is_some()
+                {
+                    primitives_end += 1;
+                }
+                self.custom_render_primitives_start = primitives_end;
+                Some(PrimitiveBatch::CustomRenderPrimitives(
+                    &self.custom_render_primitives[primitives_start..primitives_end],
+                ))
+            }
         }
     }
 }
@@ -407,7 +837,9 @@ impl<'a> Iterator for BatchIterator<'a> {
 #[derive(Debug)]
 pub enum PrimitiveBatch<'a> {
     Shadows(&'a [Shadow]),
+    BackdropBlurs(&'a [BackdropBlur]),
     Quads(&'a [Quad]),
+    TiledQuads(&'a [TiledQuad]),
     Paths(&'a [Path<ScaledPixels>]),
     Underlines(&'a [Underline]),
     MonochromeSprites {
@@ -419,6 +851,150 @@ pub enum PrimitiveBatch<'a> {
         sprites: &'a [PolychromeSprite],
     },
     Surfaces(&'a [PaintSurface]),
+    CustomRenderPrimitives(&'a [CustomRenderPrimitive]),
+}
+
+impl PrimitiveBatch<'_> {
+    /// Returns the kind of primitive in the batch.
+    pub fn kind(&self) -> PrimitiveKind {
+        match self {
+            PrimitiveBatch::Shadows(_) => PrimitiveKind::Shadow,
+            PrimitiveBatch::BackdropBlurs(_) => PrimitiveKind::BackdropBlur,
+            PrimitiveBatch::Quads(_) => PrimitiveKind::Quad,
+            PrimitiveBatch::TiledQuads(_) => PrimitiveKind::TiledQuad,
+            PrimitiveBatch::Paths(_) => PrimitiveKind::Path,
+            PrimitiveBatch::Underlines(_) => PrimitiveKind::Underline,
+            PrimitiveBatch::MonochromeSprites { .. } => PrimitiveKind::MonochromeSprite,
+            PrimitiveBatch::PolychromeSprites { .. } => PrimitiveKind::PolychromeSprite,
+            PrimitiveBatch::Surfaces(_) => PrimitiveKind::Surface,
+            PrimitiveBatch::CustomRenderPrimitives(_) => PrimitiveKind::CustomRender,
+        }
+    }
+
+    /// Returns the draw order of the first primitive in the batch.
+    pub fn order(&self) -> DrawOrder {
+        fn first_order<T>(primitives: &[T], order: impl Fn(&T) -> DrawOrder) -> DrawOrder {
+            primitives.first().map_or(0, order)
+        }
+
+        match self {
+            PrimitiveBatch::Shadows(shadows) => first_order(shadows, |shadow| shadow.order),
+            PrimitiveBatch::BackdropBlurs(blurs) => first_order(blurs, |blur| blur.order),
+            PrimitiveBatch::Quads(quads) => first_order(quads, |quad| quad.order),
+            PrimitiveBatch::TiledQuads(quads) => first_order(quads, |quad| quad.order),
+            PrimitiveBatch::Paths(paths) => first_order(paths, |path| path.order),
+            PrimitiveBatch::Underlines(underlines) => {
+                first_order(underlines, |underline| underline.order)
+            }
+            PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                first_order(sprites, |sprite| sprite.order)
+            }
+            PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                first_order(sprites, |sprite| sprite.order)
+            }
+            PrimitiveBatch::Surfaces(surfaces) => first_order(surfaces, |surface| surface.order),
+            PrimitiveBatch::CustomRenderPrimitives(primitives) => {
+                first_order(primitives, |primitive| primitive.order)
+            }
+        }
+    }
+
+    /// Returns the atlas texture the batch samples from, for sprite batches.
+    pub fn texture_id(&self) -> Option<AtlasTextureId> {
+        match self {
+            PrimitiveBatch::MonochromeSprites { texture_id, .. }
+            | PrimitiveBatch::PolychromeSprites { texture_id, .. } => Some(*texture_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the content mask shared by every primitive in the batch, if they all share one.
+    /// Renderers can then clip the whole batch with a scissor rectangle, rather than shading
+    /// pixels only to discard them.
+    pub fn shared_content_mask(&self) -> Option<&ContentMask<ScaledPixels>> {
+        fn shared<T>(
+            primitives: &[T],
+            content_mask: impl Fn(&T) -> &ContentMask<ScaledPixels>,
+        ) -> Option<&ContentMask<ScaledPixels>> {
+            let (first, rest) = primitives.split_first()?;
+            let first = content_mask(first);
+            rest.iter()
+                .all(|primitive| content_mask(primitive) == first)
+                .then_some(first)
+        }
+
+        match self {
+            PrimitiveBatch::Shadows(shadows) => shared(shadows, |shadow| &shadow.content_mask),
+            PrimitiveBatch::BackdropBlurs(blurs) => shared(blurs, |blur| &blur.content_mask),
+            PrimitiveBatch::Quads(quads) => shared(quads, |quad| &quad.content_mask),
+            PrimitiveBatch::TiledQuads(quads) => shared(quads, |quad| &quad.content_mask),
+            PrimitiveBatch::Paths(paths) => shared(paths, |path| &path.content_mask),
+            PrimitiveBatch::Underlines(underlines) => {
+                shared(underlines, |underline| &underline.content_mask)
+            }
+            PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                shared(sprites, |sprite| &sprite.content_mask)
+            }
+            PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                shared(sprites, |sprite| &sprite.content_mask)
+            }
+            PrimitiveBatch::Surfaces(surfaces) => shared(surfaces, |surface| &surface.content_mask),
+            PrimitiveBatch::CustomRenderPrimitives(primitives) => {
+                shared(primitives, |primitive| &primitive.content_mask)
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn draw_order_keys(&self) -> Vec<(DrawOrder, PrimitiveKind)> {
+        fn keys<T>(
+            primitives: &[T],
+            kind: PrimitiveKind,
+            order: impl Fn(&T) -> DrawOrder,
+        ) -> Vec<(DrawOrder, PrimitiveKind)> {
+            primitives
+                .iter()
+                .map(|primitive| (order(primitive), kind))
+                .collect()
+        }
+
+        match self {
+            PrimitiveBatch::Shadows(shadows) => {
+                keys(shadows, PrimitiveKind::Shadow, |shadow| shadow.order)
+            }
+            PrimitiveBatch::BackdropBlurs(blurs) => {
+                keys(blurs, PrimitiveKind::BackdropBlur, |blur| blur.order)
+            }
+            PrimitiveBatch::Quads(quads) => keys(quads, PrimitiveKind::Quad, |quad| quad.order),
+            PrimitiveBatch::TiledQuads(quads) => {
+                keys(quads, PrimitiveKind::TiledQuad, |quad| quad.order)
+            }
+            PrimitiveBatch::Paths(paths) => keys(paths, PrimitiveKind::Path, |path| path.order),
+            PrimitiveBatch::Underlines(underlines) => {
+                keys(underlines, PrimitiveKind::Underline, |underline| {
+                    underline.order
+                })
+            }
+            PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                keys(sprites, PrimitiveKind::MonochromeSprite, |sprite| {
+                    sprite.order
+                })
+            }
+            PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                keys(sprites, PrimitiveKind::PolychromeSprite, |sprite| {
+                    sprite.order
+                })
+            }
+            PrimitiveBatch::Surfaces(surfaces) => {
+                keys(surfaces, PrimitiveKind::Surface, |surface| surface.order)
+            }
+            PrimitiveBatch::CustomRenderPrimitives(primitives) => {
+                keys(primitives, PrimitiveKind::CustomRender, |primitive| {
+                    primitive.order
+                })
+            }
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -431,7 +1007,54 @@ pub struct Quad {
     pub background: Background,
     pub border_color: Hsla,
     pub corner_radii: Corners<ScaledPixels>,
+    /// The vertical radii of elliptical corners, whose horizontal radii are `corner_radii`. A
+    /// zero radius makes the corner circular, so quads with circular corners leave this unset.
+    pub vertical_corner_radii: Corners<ScaledPixels>,
     pub border_widths: Edges<ScaledPixels>,
+    pub shadow: QuadShadow,
+    pub transformation: TransformationMatrix,
+    /// How many dash periods per second the dashes of a dashed or dotted border move clockwise
+    /// around the quad, which the renderers animate from the frame time. Moving dashes are laid
+    /// out around the whole perimeter, even when the corners are square.
+    pub dash_speed: f32,
+    pub pad: u32, // align to 8 bytes
+}
+
+impl Quad {
+    /// The bounds the quad draws into, which includes its blurred shadow.
+    pub fn painted_bounds(&self) -> Bounds<ScaledPixels> {
+        if self.shadow.color.a > 0. {
+            let margin = ScaledPixels(self.shadow.blur_radius.0 * 3.);
+            self.bounds.union(&self.shadow.bounds.dilate(margin))
+        } else {
+            self.bounds
+        }
+    }
+
+    /// Returns the quad's shadow as a standalone primitive, if it has one.
+    pub fn cast_shadow(&self) -> Option<Shadow> {
+        (self.shadow.color.a > 0.).then(|| Shadow {
+            order: self.order,
+            blur_radius: self.shadow.blur_radius,
+            bounds: self.shadow.bounds,
+            corner_radii: self.corner_radii,
+            content_mask: self.content_mask.clone(),
+            color: self.shadow.color,
+            transformation: self.transformation,
+        })
+    }
+}
+
+/// A shadow drawn beneath a [`Quad`] by the quad's own draw call, so that decorating an element
+/// with a background and a shadow doesn't switch between pipelines. The shadow is cast by a
+/// rounded rectangle with the quad's corner radii. It isn't drawn if its color is transparent.
+#[derive(Default, Debug, Clone)]
+#[repr(C)]
+pub struct QuadShadow {
+    pub bounds: Bounds<ScaledPixels>,
+    pub color: Hsla,
+    pub blur_radius: ScaledPixels,
+    pub pad: u32, // align to 8 bytes
 }
 
 impl From<Quad> for Primitive {
@@ -440,6 +1063,34 @@ impl From<Quad> for Primitive {
     }
 }
 
+/// A quad repeated across a grid of tiles and drawn as a single instance, however many tiles it
+/// covers, for backgrounds such as row stripes, checkerboards and the grid lines of a canvas.
+///
+/// `bounds` is divided into tiles of `tile_size` starting from its origin, and the quad is drawn
+/// at `quad_bounds` within each tile, relative to the tile's origin. Tiles alternate between
+/// `color` and `alternate_color` like the squares of a checkerboard, so tiles as wide as the
+/// bounds make row stripes, and giving both the same color makes a plain grid. The whole grid
+/// is then transformed by `transformation`.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct TiledQuad {
+    pub order: DrawOrder,
+    pub corner_radius: ScaledPixels,
+    pub bounds: Bounds<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
+    pub tile_size: Size<ScaledPixels>,
+    pub quad_bounds: Bounds<ScaledPixels>,
+    pub color: Hsla,
+    pub alternate_color: Hsla,
+    pub transformation: TransformationMatrix,
+}
+
+impl From<TiledQuad> for Primitive {
+    fn from(quad: TiledQuad) -> Self {
+        Primitive::TiledQuad(quad)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct Underline {
@@ -450,6 +1101,11 @@ pub struct Underline {
     pub color: Hsla,
     pub thickness: ScaledPixels,
     pub wavy: u32,
+    /// How far a wavy underline's center line strays from the middle of its bounds.
+    pub wave_amplitude: ScaledPixels,
+    /// The length of one period of a wavy underline.
+    pub wavelength: ScaledPixels,
+    pub transformation: TransformationMatrix,
 }
 
 impl From<Underline> for Primitive {
@@ -467,6 +1123,7 @@ pub struct Shadow {
     pub corner_radii: Corners<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
     pub color: Hsla,
+    pub transformation: TransformationMatrix,
 }
 
 impl From<Shadow> for Primitive {
@@ -475,6 +1132,25 @@ impl From<Shadow> for Primitive {
     }
 }
 
+/// A rounded rectangle that shows a gaussian blur of everything drawn beneath it, for
+/// frosted-glass panels. Renderers that can't read back what they've drawn skip it.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct BackdropBlur {
+    pub order: DrawOrder,
+    /// The standard deviation of the blur.
+    pub blur_radius: ScaledPixels,
+    pub bounds: Bounds<ScaledPixels>,
+    pub corner_radii: Corners<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
+}
+
+impl From<BackdropBlur> for Primitive {
+    fn from(blur: BackdropBlur) -> Self {
+        Primitive::BackdropBlur(blur)
+    }
+}
+
 /// The style of a border.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[repr(C)]
@@ -484,6 +1160,8 @@ pub enum BorderStyle {
     Solid = 0,
     /// A dashed border.
     Dashed = 1,
+    /// A dotted border, of round dots as wide as the border.
+    Dotted = 2,
 }
 
 /// A data type representing a 2 dimensional transformation that can be applied to an element.
@@ -535,6 +1213,14 @@ impl TransformationMatrix {
         })
     }
 
+    /// Skew around the origin, slanting vertical lines by `x` and horizontal lines by `y`
+    pub fn skew(self, x: Radians, y: Radians) -> Self {
+        self.compose(Self {
+            rotation_scale: [[1.0, x.0.tan()], [y.0.tan(), 1.0]],
+            translation: [0.0, 0.0],
+        })
+    }
+
     /// Perform matrix multiplication with another transformation
     /// to produce a new transformation that is the result of
     /// applying both transformations: first, `other`, then `self`.
@@ -570,6 +1256,30 @@ impl TransformationMatrix {
         }
     }
 
+    /// Returns the smallest bounds containing the given bounds after transformation.
+    pub fn transform_bounds(&self, bounds: Bounds<ScaledPixels>) -> Bounds<ScaledPixels> {
+        if *self == Self::unit() {
+            return bounds;
+        }
+        let corners = [
+            bounds.origin,
+            bounds.top_right(),
+            bounds.bottom_right(),
+            bounds.bottom_left(),
+        ]
+        .map(|corner| {
+            let corner = self.apply(point(Pixels(corner.x.0), Pixels(corner.y.0)));
+            point(ScaledPixels(corner.x.0), ScaledPixels(corner.y.0))
+        });
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for corner in &corners[1..] {
+            min = min.min(corner);
+            max = max.max(corner);
+        }
+        Bounds::from_corners(min, max)
+    }
+
     /// Apply transformation to a point, mainly useful for debugging
     pub fn apply(&self, point: Point<Pixels>) -> Point<Pixels> {
         let input = [point.x.0, point.y.0];
@@ -593,7 +1303,12 @@ impl Default for TransformationMatrix {
 #[repr(C)]
 pub struct MonochromeSprite {
     pub order: DrawOrder,
-    pub pad: u32, // align to 8 bytes
+    /// Zero when the tile holds the coverage of a glyph, which is corrected for contrast and
+    /// gamma, and [`MonochromeSprite::LINEAR_COVERAGE`] when the tile holds coverage that is used
+    /// as is. Otherwise the tile holds a signed distance field, and this packs the value of its
+    /// edge into the low 16 bits and the width of the edge's falloff into the high 16 bits, both
+    /// scaled so that `u16::MAX` is 1.
+    pub distance_field: u32,
     pub bounds: Bounds<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
     pub color: Hsla,
@@ -601,6 +1316,12 @@ pub struct MonochromeSprite {
     pub transformation: TransformationMatrix,
 }
 
+impl MonochromeSprite {
+    /// The [`MonochromeSprite::distance_field`] of sprites whose tile holds coverage that is used
+    /// without correcting it for contrast and gamma.
+    pub const LINEAR_COVERAGE: u32 = u32::MAX;
+}
+
 impl From<MonochromeSprite> for Primitive {
     fn from(sprite: MonochromeSprite) -> Self {
         Primitive::MonochromeSprite(sprite)
@@ -611,13 +1332,15 @@ impl From<MonochromeSprite> for Primitive {
 #[repr(C)]
 pub struct PolychromeSprite {
     pub order: DrawOrder,
-    pub pad: u32, // align to 8 bytes
+    /// Nonzero to sample the texel nearest each pixel, rather than filtering linearly.
+    pub nearest: u32,
     pub grayscale: bool,
     pub opacity: f32,
     pub bounds: Bounds<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
     pub corner_radii: Corners<ScaledPixels>,
     pub tile: AtlasTile,
+    pub transformation: TransformationMatrix,
 }
 
 impl From<PolychromeSprite> for Primitive {
@@ -641,6 +1364,35 @@ impl From<PaintSurface> for Primitive {
     }
 }
 
+/// A region of the scene that application code draws with the platform renderer's GPU API.
+#[derive(Clone)]
+pub struct CustomRenderPrimitive {
+    pub order: DrawOrder,
+    pub bounds: Bounds<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
+    /// The application's renderer.
+    pub renderer: Arc<dyn Any + Send + Sync>,
+    /// Converts `renderer` into the trait object the platform renderers draw with. It's a
+    /// function pointer of a type only they know, which they downcast this to.
+    pub renderer_cast: &'static (dyn Any + Send + Sync),
+}
+
+impl Debug for CustomRenderPrimitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomRenderPrimitive")
+            .field("order", &self.order)
+            .field("bounds", &self.bounds)
+            .field("content_mask", &self.content_mask)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<CustomRenderPrimitive> for Primitive {
+    fn from(primitive: CustomRenderPrimitive) -> Self {
+        Primitive::CustomRender(primitive)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PathId(pub usize);
 
@@ -781,6 +1533,24 @@ where
     }
 }
 
+impl Path<ScaledPixels> {
+    /// Maps the path's vertices into the window with `transformation`, leaving its content mask
+    /// untransformed.
+    pub fn transform(&mut self, transformation: &TransformationMatrix) {
+        if *transformation == TransformationMatrix::unit() {
+            return;
+        }
+        for vertex in &mut self.vertices {
+            let position = transformation.apply(point(
+                Pixels(vertex.xy_position.x.0),
+                Pixels(vertex.xy_position.y.0),
+            ));
+            vertex.xy_position = point(ScaledPixels(position.x.0), ScaledPixels(position.y.0));
+        }
+        self.bounds = transformation.transform_bounds(self.bounds);
+    }
+}
+
 impl From<Path<ScaledPixels>> for Primitive {
     fn from(path: Path<ScaledPixels>) -> Self {
         Primitive::Path(path)
@@ -804,3 +1574,245 @@ impl PathVertex<Pixels> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{bounds, quad};
+
+    fn underline(bounds: Bounds<ScaledPixels>) -> Underline {
+        Underline {
+            order: 0,
+            pad: 0,
+            bounds,
+            content_mask: ContentMask {
+                bounds: self::bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            color: Hsla::default(),
+            thickness: ScaledPixels(1.),
+            wavy: 0,
+            wave_amplitude: ScaledPixels(0.),
+            wavelength: ScaledPixels(0.),
+            transformation: TransformationMatrix::unit(),
+        }
+    }
+
+    fn batch_kinds(scene: &Scene) -> Vec<PrimitiveKind> {
+        scene.batches().map(|batch| batch.kind()).collect()
+    }
+
+    #[test]
+    fn test_layer_draw_order() {
+        let mut scene = Scene::default();
+        scene.push_layer(bounds(0., 0., 50., 10.));
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(quad(bounds(20., 0., 10., 10.)));
+        scene.insert_primitive(underline(bounds(0., 8., 50., 1.)));
+        scene.insert_primitive(quad(bounds(40., 0., 10., 10.)));
+        scene.pop_layer();
+        scene.insert_primitive(underline(bounds(0., 5., 50., 1.)));
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [
+                PrimitiveKind::Quad,
+                PrimitiveKind::Underline,
+                PrimitiveKind::Quad,
+                PrimitiveKind::Underline,
+            ],
+            "a quad painted after an underline in a layer is drawn above it"
+        );
+        assert_eq!(scene.quads[1].order, scene.quads[0].order);
+        assert!(scene.quads[2].order > scene.underlines[0].order);
+        assert!(
+            scene.underlines[1].order > scene.quads[2].order,
+            "primitives painted after a layer are drawn above all of it"
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(underline(bounds(0., 5., 10., 1.)));
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(quad(bounds(20., 0., 10., 10.)));
+        scene.finish();
+
+        let stats = scene.stats();
+        assert_eq!(stats.quads, 3);
+        assert_eq!(stats.underlines, 1);
+        assert_eq!(stats.primitives(), 4);
+        assert_eq!(stats.batches, 3);
+        assert_eq!(
+            stats.batch_breaks, 1,
+            "the quad above the underline can't batch with the quads beneath it"
+        );
+    }
+
+    #[test]
+    fn test_scene_layer_draw_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(underline(bounds(0., 5., 10., 1.)));
+        scene.set_scene_layer(SceneLayer::Overlay);
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(underline(bounds(50., 5., 10., 1.)));
+        scene.set_scene_layer(SceneLayer::Base);
+        assert_eq!(scene.scene_layer(), SceneLayer::Overlay);
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [
+                PrimitiveKind::Quad,
+                PrimitiveKind::Underline,
+                PrimitiveKind::Quad,
+                PrimitiveKind::Underline,
+            ]
+        );
+        assert!(
+            scene.underlines[1].order > scene.underlines[0].order,
+            "an overlay is drawn above the base content even where it doesn't overlap it"
+        );
+        assert_eq!(
+            scene.stats().batch_breaks,
+            0,
+            "each scene layer is batched independently"
+        );
+    }
+
+    #[test]
+    fn test_shared_content_mask() {
+        let mut clipped = quad(bounds(40., 0., 10., 10.));
+        clipped.content_mask.bounds = bounds(40., 0., 20., 20.);
+        let quads = [
+            quad(bounds(0., 0., 10., 10.)),
+            quad(bounds(20., 0., 10., 10.)),
+        ];
+        assert_eq!(
+            PrimitiveBatch::Quads(&quads).shared_content_mask(),
+            Some(&quads[0].content_mask)
+        );
+        let quads = [quad(bounds(0., 0., 10., 10.)), clipped];
+        assert_eq!(PrimitiveBatch::Quads(&quads).shared_content_mask(), None);
+    }
+
+    #[test]
+    fn test_blurred_shadow_draw_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(Shadow {
+            order: 0,
+            blur_radius: ScaledPixels(2.),
+            bounds: bounds(12., 0., 10., 10.),
+            corner_radii: Corners::default(),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            color: Hsla::default(),
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [PrimitiveKind::Quad, PrimitiveKind::Shadow],
+            "the blur of a shadow painted later is drawn above the quad"
+        );
+    }
+
+    #[test]
+    fn test_backdrop_blur_draw_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.insert_primitive(BackdropBlur {
+            order: 0,
+            blur_radius: ScaledPixels(2.),
+            bounds: bounds(12., 0., 10., 10.),
+            corner_radii: Corners::default(),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+        });
+        scene.insert_primitive(quad(bounds(12., 0., 10., 10.)));
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [
+                PrimitiveKind::Quad,
+                PrimitiveKind::BackdropBlur,
+                PrimitiveKind::Quad
+            ],
+            "the backdrop a blur samples is drawn before it, and its panel after it"
+        );
+    }
+
+    #[test]
+    fn test_transformed_primitive_draw_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(underline(bounds(20., 0., 10., 1.)));
+        let mut moved = quad(bounds(0., 0., 10., 10.));
+        moved.transformation =
+            TransformationMatrix::unit().translate(point(ScaledPixels(20.), ScaledPixels(0.)));
+        assert_eq!(
+            Primitive::Quad(moved.clone()).bounds(),
+            bounds(20., 0., 10., 10.)
+        );
+        scene.insert_primitive(moved);
+        scene.insert_primitive(quad(bounds(0., 0., 10., 10.)));
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [
+                PrimitiveKind::Quad,
+                PrimitiveKind::Underline,
+                PrimitiveKind::Quad
+            ],
+            "a quad moved over the underline is drawn above it"
+        );
+    }
+
+    #[test]
+    fn test_custom_render_draw_order() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(bounds(0., 0., 20., 20.)));
+        scene.insert_primitive(CustomRenderPrimitive {
+            order: 0,
+            bounds: bounds(10., 10., 20., 20.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            renderer: Arc::new(7_u32),
+            renderer_cast: &(),
+        });
+        scene.insert_primitive(quad(bounds(20., 20., 20., 20.)));
+        scene.finish();
+
+        assert_eq!(
+            batch_kinds(&scene),
+            [
+                PrimitiveKind::Quad,
+                PrimitiveKind::CustomRender,
+                PrimitiveKind::Quad
+            ],
+            "a custom render region is drawn between the primitives it overlaps"
+        );
+        let Some(PrimitiveBatch::CustomRenderPrimitives(primitives)) = scene.batches().nth(1)
+        else {
+            panic!("expected a custom render batch");
+        };
+        assert_eq!(
+            primitives[0].renderer.downcast_ref::<u32>(),
+            Some(&7),
+            "the renderer is stored as given"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{Bounds, ContentMask, Primitive, ScaledPixels, Scene, SceneLayer};
+
+/// Builds a [`Scene`] from primitives directly, for custom elements and external renderers that
+/// produce scenes without going through GPUI's element system.
+///
+/// # Coordinate space
+///
+/// All bounds, content masks and path vertices are in device pixels ([`ScaledPixels`]), with the
+/// origin at the top left of the window's drawable area and the y axis pointing down. Convert
+/// logical [`Pixels`](crate::Pixels) with their `scale` methods and the window's scale factor.
+/// A primitive's transformation is applied to its bounds in this same space.
+///
+/// # Ordering
+///
+/// Wherever primitives overlap, the one inserted last is drawn on top, whatever their kinds.
+/// Primitives inside a [layer](SceneBuilder::push_layer) are drawn in insertion order too, but
+/// batch together even where they overlap. A [scene layer](SceneBuilder::set_scene_layer) is
+/// drawn entirely above everything inserted before it. The order in which primitives that don't
+/// overlap are drawn is unspecified.
+///
+/// # Clipping
+///
+/// A primitive is drawn only inside its own content mask, intersected with every clip pushed with
+/// [`SceneBuilder::push_clip`]. Primitives whose bounds fall entirely outside that mask are
+/// dropped. The default content mask is empty, so each primitive's content mask must cover it.
+///
+/// # Compatibility
+///
+/// The methods of this builder and the invariants above are kept across releases of
+/// `gpui_core`. While it's versioned `0.x`, a change to them comes with a minor version bump, and
+/// patch releases never change them. The fields of the primitive types, the internals of
+/// [`Scene`], and how its primitives are batched aren't covered, and may change in any release.
+#[derive(Default)]
+pub struct SceneBuilder {
+    scene: Scene,
+    clip_stack: Vec<ContentMask<ScaledPixels>>,
+    layer_depth: usize,
+}
+
+impl SceneBuilder {
+    /// Creates a builder for an empty scene.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clips the primitives inserted until the matching [`SceneBuilder::pop_clip`] to `mask`,
+    /// within any clips already pushed.
+    pub fn push_clip(&mut self, mask: ContentMask<ScaledPixels>) {
+        let mask = match self.clip_stack.last() {
+            Some(clip) => clip.intersect(&mask),
+            None => mask,
+        };
+        self.clip_stack.push(mask);
+    }
+
+    /// Removes the clip pushed most recently. Does nothing if no clip is pushed.
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    /// Batches the primitives inserted until the matching [`SceneBuilder::pop_layer`] together,
+    /// drawing them above everything inserted so far within `bounds`.
+    pub fn push_layer(&mut self, bounds: Bounds<ScaledPixels>) {
+        self.layer_depth += 1;
+        self.scene.push_layer(bounds);
+    }
+
+    /// Ends the layer pushed most recently. Does nothing if no layer is pushed.
+    pub fn pop_layer(&mut self) {
+        if self.layer_depth > 0 {
+            self.layer_depth -= 1;
+            self.scene.pop_layer();
+        }
+    }
+
+    /// Inserts the primitives from now on into `scene_layer`, above everything inserted so far.
+    /// Scene layers can't be returned to once left, so switching to a lower layer than the
+    /// current one has no effect. Ends any layers that are still pushed.
+    pub fn set_scene_layer(&mut self, scene_layer: SceneLayer) {
+        self.pop_layers();
+        self.scene.set_scene_layer(scene_layer);
+    }
+
+    /// Inserts `primitive`, clipped to the current clip, above the primitives it overlaps.
+    pub fn insert(&mut self, primitive: impl Into<Primitive>) -> &mut Self {
+        let mut primitive = primitive.into();
+        if let Some(clip) = self.clip_stack.last() {
+            let content_mask = primitive.content_mask_mut();
+            *content_mask = content_mask.intersect(clip);
+        }
+        self.scene.insert_primitive(primitive);
+        self
+    }
+
+    /// Ends any layers that are still pushed and returns the scene, sorted and ready to draw.
+    pub fn build(mut self) -> Scene {
+        self.pop_layers();
+        self.scene.finish();
+        self.scene
+    }
+
+    fn pop_layers(&mut self) {
+        while self.layer_depth > 0 {
+            self.pop_layer();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        PrimitiveBatch,
+        test_helpers::{bounds, quad},
+    };
+
+    #[test]
+    fn test_clips_and_balances_layers() {
+        let mut builder = SceneBuilder::new();
+        builder.push_clip(ContentMask {
+            bounds: bounds(0., 0., 50., 50.),
+            ..Default::default()
+        });
+        builder.push_layer(bounds(0., 0., 50., 50.));
+        builder.insert(quad(bounds(10., 10., 20., 20.)));
+        builder.insert(quad(bounds(60., 60., 20., 20.)));
+        builder.pop_clip();
+        builder.pop_clip();
+        let scene = builder.build();
+
+        assert_eq!(scene.quads.len(), 1);
+        assert_eq!(scene.quads[0].content_mask.bounds, bounds(0., 0., 50., 50.));
+        assert!(matches!(
+            scene.batches().next(),
+            Some(PrimitiveBatch::Quads(quads)) if quads.len() == 1
+        ));
+    }
+}
@@ -0,0 +1,883 @@
+use super::svg::{
+    CornerEllipses, SpriteTileKey, border_inner_bounds, corner_ellipses, gradient_direction,
+    inset_corner_ellipses, is_visible, number, sprite_pixels, wave_points,
+};
+use crate::{
+    AtlasPixels, AtlasTextureKind, AtlasTile, Background, BackgroundTag, BorderStyle, Bounds,
+    ContentMask, Corners, Edges, Hsla, MonochromeSprite, Path, PolychromeSprite, PrimitiveBatch,
+    Quad, ScaledPixels, Scene, Size, TransformationMatrix, Underline, point,
+};
+use std::collections::HashMap;
+
+/// The distance of a cubic Bézier control point from the end of a quarter circle, as a
+/// fraction of its radius.
+const ARC_CONTROL_RATIO: f32 = 0.552_284_8;
+
+impl Scene {
+    /// Converts the scene into a PDF document with pages of `page_size` points, e.g. to print
+    /// a window. The scene is scaled by `scale` points per device pixel and split vertically
+    /// across as many pages as its content needs.
+    ///
+    /// Quads, paths and underlines are converted in draw order, along with their gradients and
+    /// content masks. Shadows are left out since PDF has no blur, tiled quads are left out for
+    /// now, and glyphs, images and surfaces are left out for the same reasons as in
+    /// [`Scene::to_svg`]; see [`Scene::to_pdf_with_sprites`].
+    ///
+    /// The scene must have been [finished](Scene::finish).
+    pub fn to_pdf(&self, page_size: Size<f32>, scale: f32) -> Vec<u8> {
+        self.pdf(page_size, scale, None)
+    }
+
+    /// Converts the scene into a PDF document like [`Scene::to_pdf`], along with its glyphs,
+    /// icons and images, whose pixels are read from `atlas`. Each distinct sprite is embedded
+    /// once as an image: glyphs and icons as soft masks painted with their color, and images as
+    /// pictures.
+    ///
+    /// The scene must have been [finished](Scene::finish).
+    pub fn to_pdf_with_sprites(
+        &self,
+        page_size: Size<f32>,
+        scale: f32,
+        atlas: &dyn AtlasPixels,
+    ) -> Vec<u8> {
+        self.pdf(page_size, scale, Some(atlas))
+    }
+
+    fn pdf(&self, page_size: Size<f32>, scale: f32, atlas: Option<&dyn AtlasPixels>) -> Vec<u8> {
+        let mut content = PdfContent::default();
+        for batch in self.batches() {
+            match batch {
+                PrimitiveBatch::Quads(quads) => {
+                    for quad in quads {
+                        content.quad(quad);
+                    }
+                }
+                PrimitiveBatch::Paths(paths) => {
+                    for path in paths {
+                        content.path(path);
+                    }
+                }
+                PrimitiveBatch::Underlines(underlines) => {
+                    for underline in underlines {
+                        content.underline(underline);
+                    }
+                }
+                PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                    if let Some(atlas) = atlas {
+                        for sprite in sprites {
+                            content.monochrome_sprite(sprite, atlas);
+                        }
+                    }
+                }
+                PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                    if let Some(atlas) = atlas {
+                        for sprite in sprites {
+                            content.polychrome_sprite(sprite, atlas);
+                        }
+                    }
+                }
+                PrimitiveBatch::Shadows(_)
+                | PrimitiveBatch::BackdropBlurs(_)
+                | PrimitiveBatch::TiledQuads(_)
+                | PrimitiveBatch::Surfaces(_)
+                | PrimitiveBatch::CustomRenderPrimitives(_) => {}
+            }
+        }
+        content.finish(page_size, scale)
+    }
+}
+
+/// The drawing operators of a scene in device pixels, along with the graphics states, shadings
+/// and images they refer to.
+#[derive(Default)]
+struct PdfContent {
+    operators: String,
+    opacities: Vec<f32>,
+    shadings: Vec<String>,
+    images: Vec<PdfImage>,
+    /// The images drawing each sprite tile so far, in grayscale or not, or `None` for tiles
+    /// without pixels.
+    sprite_images: HashMap<(SpriteTileKey, bool), Option<usize>>,
+    /// The images painting a glyph's coverage with a color, keyed by the coverage image.
+    painted_glyphs: HashMap<(usize, [u8; 3]), usize>,
+    width: f32,
+    height: f32,
+}
+
+/// An image with 8 bits per component, row by row from the top.
+struct PdfImage {
+    width: i32,
+    height: i32,
+    color_space: &'static str,
+    samples: Vec<u8>,
+    /// The grayscale image providing this image's alpha.
+    soft_mask: Option<usize>,
+}
+
+impl PdfContent {
+    fn quad(&mut self, quad: &Quad) {
+        self.begin_clip(&quad.bounds, &quad.content_mask, &quad.transformation);
+        let corners = corner_ellipses(
+            &quad.bounds,
+            &quad.corner_radii,
+            &quad.vertical_corner_radii,
+        );
+        let mut outline = String::new();
+        rounded_rect(&mut outline, &quad.bounds, &corners);
+
+        if is_visible(&quad.background) {
+            self.fill(&outline, "f", &quad.background, &quad.bounds);
+        }
+
+        let widths = &quad.border_widths;
+        let has_border = [widths.top, widths.right, widths.bottom, widths.left]
+            .iter()
+            .any(|width| width.0 > 0.);
+        if has_border && quad.border_color.a > 0. {
+            let uniform = widths.top == widths.right
+                && widths.top == widths.bottom
+                && widths.top == widths.left;
+            if quad.border_style != BorderStyle::Solid && uniform {
+                let width = widths.top.0;
+                let half_width = ScaledPixels(width / 2.);
+                let mut center_line = String::new();
+                rounded_rect(
+                    &mut center_line,
+                    &quad.bounds.dilate(ScaledPixels(-half_width.0)),
+                    &inset_corner_ellipses(&corners, &Edges::all(half_width)),
+                );
+                let dashes = if quad.border_style == BorderStyle::Dotted {
+                    format!("1 J [0 {}] 0 d", number(width * 2.))
+                } else {
+                    let dash = number(width * 2.);
+                    format!("[{dash} {dash}] 0 d")
+                };
+                let style = format!("{} w {dashes} ", number(width));
+                self.stroke(&center_line, &style, quad.border_color);
+            } else {
+                rounded_rect(
+                    &mut outline,
+                    &border_inner_bounds(&quad.bounds, widths),
+                    &inset_corner_ellipses(&corners, widths),
+                );
+                self.fill(&outline, "f*", &quad.border_color.into(), &quad.bounds);
+            }
+        }
+
+        self.end_clip();
+    }
+
+    fn path(&mut self, path: &Path<ScaledPixels>) {
+        if !is_visible(&path.color) {
+            return;
+        }
+        self.begin_clip(
+            &path.bounds,
+            &path.content_mask,
+            &TransformationMatrix::unit(),
+        );
+        // Paths are triangle fans whose overlapping triangles cancel out, with curves drawn as
+        // triangles between the curve and its chord. The even-odd rule reproduces both.
+        let mut outline = String::new();
+        for triangle in path.vertices.chunks_exact(3) {
+            let [start, control, end] = [
+                triangle[0].xy_position,
+                triangle[1].xy_position,
+                triangle[2].xy_position,
+            ];
+            outline.push_str(&format!("{} {} m ", number(start.x.0), number(start.y.0)));
+            if triangle[1].st_position == point(0.5, 0.) {
+                // PDF only has cubic curves, which can represent a quadratic curve exactly.
+                let first = point(
+                    start.x.0 + (control.x.0 - start.x.0) * 2. / 3.,
+                    start.y.0 + (control.y.0 - start.y.0) * 2. / 3.,
+                );
+                let second = point(
+                    end.x.0 + (control.x.0 - end.x.0) * 2. / 3.,
+                    end.y.0 + (control.y.0 - end.y.0) * 2. / 3.,
+                );
+                outline.push_str(&format!(
+                    "{} {} {} {} {} {} c h ",
+                    number(first.x),
+                    number(first.y),
+                    number(second.x),
+                    number(second.y),
+                    number(end.x.0),
+                    number(end.y.0),
+                ));
+            } else {
+                outline.push_str(&format!(
+                    "{} {} l {} {} l h ",
+                    number(control.x.0),
+                    number(control.y.0),
+                    number(end.x.0),
+                    number(end.y.0),
+                ));
+            }
+        }
+        self.fill(&outline, "f*", &path.color, &path.bounds);
+        self.end_clip();
+    }
+
+    fn underline(&mut self, underline: &Underline) {
+        if underline.color.a <= 0. {
+            return;
+        }
+        self.begin_clip(
+            &underline.bounds,
+            &underline.content_mask,
+            &underline.transformation,
+        );
+        let bounds = &underline.bounds;
+        if underline.wavy == 0 {
+            let rect = format!(
+                "{} {} {} {} re ",
+                number(bounds.origin.x.0),
+                number(bounds.origin.y.0),
+                number(bounds.size.width.0),
+                number(bounds.size.height.0),
+            );
+            self.fill(&rect, "f", &underline.color.into(), bounds);
+        } else {
+            let mut line = String::new();
+            for (ix, (x, y)) in wave_points(underline).into_iter().enumerate() {
+                let operator = if ix == 0 { "m" } else { "l" };
+                line.push_str(&format!("{} {} {operator} ", number(x), number(y)));
+            }
+            let style = format!("{} w ", number(underline.thickness.0));
+            self.stroke(&line, &style, underline.color);
+        }
+        self.end_clip();
+    }
+
+    fn monochrome_sprite(&mut self, sprite: &MonochromeSprite, atlas: &dyn AtlasPixels) {
+        if sprite.color.a <= 0. {
+            return;
+        }
+        let Some(coverage) = self.sprite_image(&sprite.tile, sprite.distance_field, false, atlas)
+        else {
+            return;
+        };
+        // A soft mask needn't match the resolution of its image, so the color is a single pixel
+        // stretched over the glyph.
+        let rgba = sprite.color.to_rgb();
+        let color = [rgba.r, rgba.g, rgba.b].map(|component| (component * 255.).round() as u8);
+        let images = &mut self.images;
+        let image = *self
+            .painted_glyphs
+            .entry((coverage, color))
+            .or_insert_with(|| {
+                images.push(PdfImage {
+                    width: 1,
+                    height: 1,
+                    color_space: "DeviceRGB",
+                    samples: color.to_vec(),
+                    soft_mask: Some(coverage),
+                });
+                images.len() - 1
+            });
+        self.begin_clip(&sprite.bounds, &sprite.content_mask, &sprite.transformation);
+        let opacity = self.opacity(rgba.a);
+        self.draw_image(image, &sprite.bounds, &opacity);
+        self.end_clip();
+    }
+
+    fn polychrome_sprite(&mut self, sprite: &PolychromeSprite, atlas: &dyn AtlasPixels) {
+        if sprite.opacity <= 0. {
+            return;
+        }
+        let Some(image) = self.sprite_image(&sprite.tile, 0, sprite.grayscale, atlas) else {
+            return;
+        };
+        self.begin_clip(&sprite.bounds, &sprite.content_mask, &sprite.transformation);
+        let bounds = &sprite.bounds;
+        let mut state = String::new();
+        if sprite.corner_radii != Corners::default() {
+            rounded_rect(
+                &mut state,
+                bounds,
+                &corner_ellipses(bounds, &sprite.corner_radii, &Corners::default()),
+            );
+            state.push_str("W n ");
+        }
+        state.push_str(&self.opacity(sprite.opacity));
+        self.draw_image(image, bounds, &state);
+        self.end_clip();
+    }
+
+    /// Returns the index of the image drawing `tile`, adding it if needed: the coverage of
+    /// monochrome tiles, and the colors of polychrome ones with their alpha as a soft mask.
+    fn sprite_image(
+        &mut self,
+        tile: &AtlasTile,
+        distance_field: u32,
+        grayscale: bool,
+        atlas: &dyn AtlasPixels,
+    ) -> Option<usize> {
+        let key = ((tile.texture_id, tile.bounds, distance_field), grayscale);
+        if let Some(image) = self.sprite_images.get(&key) {
+            return *image;
+        }
+        let image = sprite_pixels(atlas, tile, distance_field).map(|pixels| {
+            let (width, height) = (pixels.size.width.0, pixels.size.height.0);
+            let alpha = pixels
+                .rgba
+                .chunks_exact(4)
+                .map(|pixel| pixel[3])
+                .collect::<Vec<_>>();
+            let gray_image = |samples| PdfImage {
+                width,
+                height,
+                color_space: "DeviceGray",
+                samples,
+                soft_mask: None,
+            };
+            if tile.texture_id.kind == AtlasTextureKind::Monochrome {
+                self.images.push(gray_image(alpha));
+                return self.images.len() - 1;
+            }
+
+            let soft_mask = alpha.iter().any(|alpha| *alpha < 255).then(|| {
+                self.images.push(gray_image(alpha));
+                self.images.len() - 1
+            });
+            let image = if grayscale {
+                let samples = pixels
+                    .rgba
+                    .chunks_exact(4)
+                    .map(|pixel| {
+                        let [red, green, blue] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+                        (0.2126 * red + 0.7152 * green + 0.0722 * blue).round() as u8
+                    })
+                    .collect();
+                PdfImage {
+                    soft_mask,
+                    ..gray_image(samples)
+                }
+            } else {
+                let samples = pixels
+                    .rgba
+                    .chunks_exact(4)
+                    .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                    .collect();
+                PdfImage {
+                    color_space: "DeviceRGB",
+                    soft_mask,
+                    ..gray_image(samples)
+                }
+            };
+            self.images.push(image);
+            self.images.len() - 1
+        });
+        self.sprite_images.insert(key, image);
+        image
+    }
+
+    /// Draws an image stretched over `bounds` after applying the operators in `state`. Images
+    /// fill the unit square from the bottom up, so they are flipped into the content's top-down
+    /// coordinates.
+    fn draw_image(&mut self, image: usize, bounds: &Bounds<ScaledPixels>, state: &str) {
+        self.operators.push_str(&format!(
+            "q {state}{} 0 0 {} {} {} cm /Im{image} Do Q\n",
+            number(bounds.size.width.0),
+            number(-bounds.size.height.0),
+            number(bounds.origin.x.0),
+            number(bounds.bottom().0),
+        ));
+    }
+
+    /// Fills `outline` with `background` using the given fill operator.
+    fn fill(
+        &mut self,
+        outline: &str,
+        operator: &str,
+        background: &Background,
+        bounds: &Bounds<ScaledPixels>,
+    ) {
+        let has_area = bounds.size.width.0 > 0. && bounds.size.height.0 > 0.;
+        if background.tag != BackgroundTag::LinearGradient || !has_area {
+            let color = if background.tag == BackgroundTag::LinearGradient {
+                background.colors[0].color
+            } else {
+                background.solid
+            };
+            let paint = self.paint(color, "rg");
+            self.operators
+                .push_str(&format!("q {paint}{outline}{operator} Q\n"));
+            return;
+        }
+
+        // Axial shadings only interpolate between opaque colors, so a translucent gradient is
+        // drawn with the average opacity of its stops.
+        let [from, to] = &background.colors;
+        let opacity = self.opacity((from.color.a + to.color.a) / 2.);
+        let (x, y) = gradient_direction(background);
+        let half_width = bounds.size.width.0 / 2.;
+        let half_height = bounds.size.height.0 / 2.;
+        let center = point(
+            bounds.origin.x.0 + half_width,
+            bounds.origin.y.0 + half_height,
+        );
+        let start = point(center.x - x * half_width, center.y - y * half_height);
+        let end = point(center.x + x * half_width, center.y + y * half_height);
+        let at = |percentage: f32| {
+            point(
+                start.x + (end.x - start.x) * percentage,
+                start.y + (end.y - start.y) * percentage,
+            )
+        };
+        let (first, last) = (at(from.percentage), at(to.percentage));
+        let [from_color, to_color] = [from.color, to.color].map(|color| {
+            let rgba = color.to_rgb();
+            format!("{:.3} {:.3} {:.3}", rgba.r, rgba.g, rgba.b)
+        });
+        self.shadings.push(format!(
+            "<< /ShadingType 2 /ColorSpace /DeviceRGB /Coords [{} {} {} {}] /Function << /FunctionType 2 /Domain [0 1] /C0 [{from_color}] /C1 [{to_color}] /N 1 >> /Extend [true true] >>",
+            number(first.x),
+            number(first.y),
+            number(last.x),
+            number(last.y),
+        ));
+        let clip_operator = if operator == "f*" { "W*" } else { "W" };
+        self.operators.push_str(&format!(
+            "q {opacity}{outline}{clip_operator} n /Sh{} sh Q\n",
+            self.shadings.len() - 1
+        ));
+    }
+
+    fn stroke(&mut self, line: &str, style: &str, color: Hsla) {
+        let paint = self.paint(color, "RG");
+        self.operators
+            .push_str(&format!("q {paint}{style}{line}S Q\n"));
+    }
+
+    /// Returns the operators that set `color` with the given color operator, along with its
+    /// opacity if it is translucent.
+    fn paint(&mut self, color: Hsla, operator: &str) -> String {
+        let rgba = color.to_rgb();
+        format!(
+            "{}{:.3} {:.3} {:.3} {operator} ",
+            self.opacity(rgba.a),
+            rgba.r,
+            rgba.g,
+            rgba.b
+        )
+    }
+
+    /// Returns the operator that applies a graphics state with the given opacity, if it is
+    /// translucent.
+    fn opacity(&mut self, opacity: f32) -> String {
+        let opacity = number(opacity);
+        if opacity >= 1. {
+            return String::new();
+        }
+        let ix = match self
+            .opacities
+            .iter()
+            .position(|existing| *existing == opacity)
+        {
+            Some(ix) => ix,
+            None => {
+                self.opacities.push(opacity);
+                self.opacities.len() - 1
+            }
+        };
+        format!("/GS{ix} gs ")
+    }
+
+    /// Clips subsequent operators to the content mask and then transforms them, until
+    /// [`Self::end_clip`] is called.
+    fn begin_clip(
+        &mut self,
+        bounds: &Bounds<ScaledPixels>,
+        content_mask: &ContentMask<ScaledPixels>,
+        transformation: &TransformationMatrix,
+    ) {
+        let visible = transformation
+            .transform_bounds(*bounds)
+            .intersect(&content_mask.bounds);
+        self.width = self.width.max(visible.right().0);
+        self.height = self.height.max(visible.bottom().0);
+        let mask = content_mask.bounds;
+        if content_mask.corner_radii == Corners::default() {
+            self.operators.push_str(&format!(
+                "q {} {} {} {} re W n\n",
+                number(mask.origin.x.0),
+                number(mask.origin.y.0),
+                number(mask.size.width.0),
+                number(mask.size.height.0),
+            ));
+        } else {
+            self.operators.push_str("q ");
+            rounded_rect(
+                &mut self.operators,
+                &mask,
+                &corner_ellipses(&mask, &content_mask.corner_radii, &Corners::default()),
+            );
+            self.operators.push_str("W n\n");
+        }
+        if *transformation != TransformationMatrix::unit() {
+            let [[a, c], [b, d]] = transformation.rotation_scale;
+            let [e, f] = transformation.translation;
+            self.operators.push_str(&format!(
+                "{} {} {} {} {} {} cm\n",
+                number(a),
+                number(b),
+                number(c),
+                number(d),
+                number(e),
+                number(f),
+            ));
+        }
+    }
+
+    fn end_clip(&mut self) {
+        self.operators.push_str("Q\n");
+    }
+
+    /// Returns the resources of the content, whose images are written as consecutive objects
+    /// starting at `first_image_id`.
+    fn resources(&self, first_image_id: usize) -> String {
+        let mut resources = String::from("<<");
+        if !self.opacities.is_empty() {
+            resources.push_str(" /ExtGState <<");
+            for (ix, opacity) in self.opacities.iter().enumerate() {
+                resources.push_str(&format!(" /GS{ix} << /ca {opacity} /CA {opacity} >>"));
+            }
+            resources.push_str(" >>");
+        }
+        if !self.shadings.is_empty() {
+            resources.push_str(" /Shading <<");
+            for (ix, shading) in self.shadings.iter().enumerate() {
+                resources.push_str(&format!(" /Sh{ix} {shading}"));
+            }
+            resources.push_str(" >>");
+        }
+        if !self.images.is_empty() {
+            resources.push_str(" /XObject <<");
+            for ix in 0..self.images.len() {
+                resources.push_str(&format!(" /Im{ix} {} 0 R", first_image_id + ix));
+            }
+            resources.push_str(" >>");
+        }
+        resources.push_str(" >>");
+        resources
+    }
+
+    /// Writes the document. The content is drawn once into a form, which each page draws
+    /// flipped into PDF's bottom-up coordinates and shifted to its slice of the content. The
+    /// images follow the pages.
+    fn finish(self, page_size: Size<f32>, scale: f32) -> Vec<u8> {
+        let scale = if scale > 0. { scale } else { 1. };
+        let page_height = page_size.height / scale;
+        let page_count = if page_height > 0. {
+            ((self.height / page_height).ceil() as usize).max(1)
+        } else {
+            1
+        };
+
+        let first_image_id = 4 + page_count * 2;
+        let mut writer = PdfWriter::default();
+        let kids = (0..page_count)
+            .map(|page| format!("{} 0 R", 4 + page * 2))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writer.object(1, "<< /Type /Catalog /Pages 2 0 R >>".as_bytes());
+        writer.object(
+            2,
+            format!("<< /Type /Pages /Kids [{kids}] /Count {page_count} >>").as_bytes(),
+        );
+        writer.stream(
+            3,
+            &format!(
+                "/Type /XObject /Subtype /Form /BBox [0 0 {} {}] /Resources {}",
+                number(self.width.ceil()),
+                number(self.height.ceil()),
+                self.resources(first_image_id)
+            ),
+            self.operators.as_bytes(),
+        );
+        for page in 0..page_count {
+            let id = 4 + page * 2;
+            writer.object(
+                id,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << /Content 3 0 R >> >> /Contents {} 0 R >>",
+                    number(page_size.width),
+                    number(page_size.height),
+                    id + 1
+                )
+                .as_bytes(),
+            );
+            writer.stream(
+                id + 1,
+                "",
+                format!(
+                    "q {} 0 0 {} 0 {} cm 1 0 0 1 0 {} cm /Content Do Q\n",
+                    scale,
+                    -scale,
+                    number(page_size.height),
+                    number(-(page as f32) * page_height),
+                )
+                .as_bytes(),
+            );
+        }
+        for (ix, image) in self.images.iter().enumerate() {
+            let soft_mask = image
+                .soft_mask
+                .map(|soft_mask| format!(" /SMask {} 0 R", first_image_id + soft_mask))
+                .unwrap_or_default();
+            writer.stream(
+                first_image_id + ix,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8{soft_mask} /Filter /FlateDecode",
+                    image.width, image.height, image.color_space,
+                ),
+                &miniz_oxide::deflate::compress_to_vec_zlib(&image.samples, 6),
+            );
+        }
+        writer.finish()
+    }
+}
+
+/// Writes numbered objects followed by the cross-reference table that locates them.
+#[derive(Default)]
+struct PdfWriter {
+    output: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn object(&mut self, id: usize, body: &[u8]) {
+        if self.output.is_empty() {
+            self.output.extend_from_slice(b"%PDF-1.4\n");
+        }
+        debug_assert_eq!(id, self.offsets.len() + 1, "objects are written in order");
+        self.offsets.push(self.output.len());
+        self.output
+            .extend_from_slice(format!("{id} 0 obj\n").as_bytes());
+        self.output.extend_from_slice(body);
+        self.output.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, id: usize, dictionary: &str, content: &[u8]) {
+        let mut body =
+            format!("<< {dictionary} /Length {} >>\nstream\n", content.len()).into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\nendstream");
+        self.object(id, &body);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let cross_reference_offset = self.output.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            table.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{cross_reference_offset}\n%%EOF\n",
+            self.offsets.len() + 1
+        ));
+        self.output.extend_from_slice(table.as_bytes());
+        self.output
+    }
+}
+
+/// Appends a subpath for a rectangle with the given corners.
+fn rounded_rect(operators: &mut String, bounds: &Bounds<ScaledPixels>, corners: &CornerEllipses) {
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    let left = bounds.origin.x.0;
+    let top = bounds.origin.y.0;
+    let right = bounds.right().0;
+    let bottom = bounds.bottom().0;
+
+    operators.push_str(&format!("{} {} m ", number(left + top_left.0), number(top)));
+    line_to(operators, right - top_right.0, top);
+    push_corner(
+        operators,
+        top_right,
+        (right - top_right.0, top),
+        (right, top),
+        (right, top + top_right.1),
+    );
+    line_to(operators, right, bottom - bottom_right.1);
+    push_corner(
+        operators,
+        bottom_right,
+        (right, bottom - bottom_right.1),
+        (right, bottom),
+        (right - bottom_right.0, bottom),
+    );
+    line_to(operators, left + bottom_left.0, bottom);
+    push_corner(
+        operators,
+        bottom_left,
+        (left + bottom_left.0, bottom),
+        (left, bottom),
+        (left, bottom - bottom_left.1),
+    );
+    line_to(operators, left, top + top_left.1);
+    push_corner(
+        operators,
+        top_left,
+        (left, top + top_left.1),
+        (left, top),
+        (left + top_left.0, top),
+    );
+    operators.push_str("h ");
+}
+
+fn line_to(operators: &mut String, x: f32, y: f32) {
+    operators.push_str(&format!("{} {} l ", number(x), number(y)));
+}
+
+/// Appends a quarter ellipse from `start` to `end` around the rectangle corner at `corner`.
+fn push_corner(
+    operators: &mut String,
+    radii: (f32, f32),
+    start: (f32, f32),
+    corner: (f32, f32),
+    end: (f32, f32),
+) {
+    if radii.0 <= 0. {
+        return;
+    }
+    let toward_corner = |from: (f32, f32)| {
+        (
+            from.0 + (corner.0 - from.0) * ARC_CONTROL_RATIO,
+            from.1 + (corner.1 - from.1) * ARC_CONTROL_RATIO,
+        )
+    };
+    let (first, second) = (toward_corner(start), toward_corner(end));
+    operators.push_str(&format!(
+        "{} {} {} {} {} {} c ",
+        number(first.0),
+        number(first.1),
+        number(second.0),
+        number(second.1),
+        number(end.0),
+        number(end.1),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        size,
+        test_helpers::{TestAtlas, bounds, glyph, image},
+    };
+
+    #[test]
+    fn test_scene_to_pdf() {
+        let mut scene = Scene::default();
+        let content_mask = ContentMask {
+            bounds: bounds(0., 0., 100., 300.),
+            ..Default::default()
+        };
+        scene.insert_primitive(Quad {
+            bounds: bounds(10., 10., 50., 20.),
+            content_mask: content_mask.clone(),
+            background: Hsla::red().into(),
+            ..Default::default()
+        });
+        scene.insert_primitive(Underline {
+            order: 0,
+            pad: 0,
+            bounds: bounds(10., 250., 50., 1.),
+            content_mask,
+            color: Hsla::blue().opacity(0.5),
+            thickness: ScaledPixels(1.),
+            wavy: 0,
+            wave_amplitude: ScaledPixels(0.),
+            wavelength: ScaledPixels(0.),
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+
+        let pdf = String::from_utf8(scene.to_pdf(size(100., 100.), 0.5)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(
+            pdf.contains("/Count 2"),
+            "content taller than a page is split across pages"
+        );
+        assert!(
+            pdf.contains("q 1.000 0.000 0.000 rg 10 10 m 60 10 l 60 30 l 10 30 l 10 10 l h f Q")
+        );
+        assert!(pdf.contains("/GS0 << /ca 0.5 /CA 0.5 >>"));
+        assert!(pdf.contains("q /GS0 gs 0.000 0.000 1.000 rg 10 250 50 1 re f Q"));
+        assert!(pdf.contains("0 -200 cm /Content Do"));
+
+        let cross_reference_offset = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|offset| offset.parse::<usize>().ok())
+            .unwrap();
+        assert!(pdf[cross_reference_offset..].starts_with("xref\n"));
+    }
+
+    #[test]
+    fn test_sprites_to_pdf() {
+        let mut atlas = TestAtlas::default();
+        let letter = atlas.insert(AtlasTextureKind::Monochrome, 2, 1, vec![255, 128]);
+        let picture = atlas.insert(
+            AtlasTextureKind::Polychrome,
+            2,
+            1,
+            vec![0, 0, 255, 255, 255, 0, 0, 128],
+        );
+        let mut scene = Scene::default();
+        scene.insert_primitive(glyph(letter.clone(), bounds(10., 10., 2., 1.), Hsla::red()));
+        scene.insert_primitive(glyph(letter, bounds(12., 10., 2., 1.), Hsla::blue()));
+        scene.insert_primitive(image(picture, bounds(20., 20., 10., 10.)));
+        scene.finish();
+
+        let without_atlas = scene.to_pdf(size(100., 100.), 1.);
+        assert!(!String::from_utf8_lossy(&without_atlas).contains("/Subtype /Image"));
+
+        let pdf = scene.to_pdf_with_sprites(size(100., 100.), 1., &atlas);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(
+            text.contains("/XObject << /Im0 6 0 R /Im1 7 0 R /Im2 8 0 R /Im3 9 0 R /Im4 10 0 R >>")
+        );
+        assert!(text.contains("q 2 0 0 -1 10 11 cm /Im1 Do Q"));
+        assert!(
+            text.contains("q 2 0 0 -1 12 11 cm /Im2 Do Q"),
+            "glyphs share their coverage, painted with each color"
+        );
+        assert!(text.contains("q 10 0 0 -10 20 30 cm /Im4 Do Q"));
+
+        let find = |haystack: &[u8], needle: &[u8]| {
+            haystack
+                .windows(needle.len())
+                .position(|window| window == needle)
+                .unwrap()
+        };
+        let image = |id: usize| {
+            let object = &pdf[find(&pdf, format!("\n{id} 0 obj\n").as_bytes())..];
+            let data_start = find(object, b"stream\n") + "stream\n".len();
+            let dictionary = String::from_utf8_lossy(&object[..data_start]).into_owned();
+            let data = &object[data_start..];
+            let data = &data[..find(data, b"\nendstream")];
+            (
+                dictionary,
+                miniz_oxide::inflate::decompress_to_vec_zlib(data).unwrap(),
+            )
+        };
+        let (dictionary, samples) = image(6);
+        assert!(dictionary.contains("/Width 2 /Height 1 /ColorSpace /DeviceGray"));
+        assert_eq!(samples, [255, 128], "glyph coverage becomes a soft mask");
+        let (dictionary, samples) = image(7);
+        assert!(dictionary.contains(
+            "/Width 1 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /SMask 6 0 R"
+        ));
+        assert_eq!(samples, [255, 0, 0]);
+        let (_, samples) = image(9);
+        assert_eq!(samples, [255, 128], "image alpha becomes a soft mask");
+        let (dictionary, samples) = image(10);
+        assert!(dictionary.contains("/ColorSpace /DeviceRGB /BitsPerComponent 8 /SMask 9 0 R"));
+        assert_eq!(samples, [255, 0, 0, 0, 0, 255], "BGRA becomes RGB");
+    }
+}
@@ -0,0 +1,1090 @@
+use crate::{
+    AtlasPixels, AtlasTextureId, AtlasTextureKind, AtlasTile, Background, BackgroundTag,
+    BorderStyle, Bounds, ContentMask, Corners, DevicePixels, Edges, Hsla, MonochromeSprite, Path,
+    PolychromeSprite, PrimitiveBatch, Quad, ScaledPixels, Scene, Shadow, TiledQuad,
+    TransformationMatrix, Underline, point, size,
+};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use std::{collections::HashMap, f32::consts::PI};
+use util::ResultExt as _;
+
+/// The number of line segments that approximate each period of a wavy underline.
+const WAVE_SEGMENTS_PER_PERIOD: usize = 8;
+
+impl Scene {
+    /// Converts the scene into a standalone SVG document, e.g. to export a chart or diagram as
+    /// vector graphics. Coordinates are in device pixels.
+    ///
+    /// Quads, tiled quads, shadows, paths and underlines are converted in draw order, along with
+    /// their gradients and content masks. Glyphs and images are rasterized into the sprite atlas,
+    /// whose pixels the scene doesn't hold, so they are left out; see
+    /// [`Scene::to_svg_with_sprites`]. Surfaces are platform buffers, so they are left out too.
+    ///
+    /// The scene must have been [finished](Scene::finish).
+    pub fn to_svg(&self) -> String {
+        self.svg(None)
+    }
+
+    /// Converts the scene into a standalone SVG document like [`Scene::to_svg`], along with its
+    /// glyphs, icons and images, whose pixels are read from `atlas`. Each distinct sprite is
+    /// embedded once as a PNG image: glyphs and icons as masks filled with their color, and
+    /// images as pictures.
+    ///
+    /// The scene must have been [finished](Scene::finish).
+    pub fn to_svg_with_sprites(&self, atlas: &dyn AtlasPixels) -> String {
+        self.svg(Some(atlas))
+    }
+
+    fn svg(&self, atlas: Option<&dyn AtlasPixels>) -> String {
+        let mut document = SvgDocument::default();
+        for batch in self.batches() {
+            match batch {
+                PrimitiveBatch::Shadows(shadows) => {
+                    for shadow in shadows {
+                        document.shadow(shadow);
+                    }
+                }
+                PrimitiveBatch::Quads(quads) => {
+                    for quad in quads {
+                        if let Some(shadow) = quad.cast_shadow() {
+                            document.shadow(&shadow);
+                        }
+                        document.quad(quad);
+                    }
+                }
+                PrimitiveBatch::TiledQuads(quads) => {
+                    for quad in quads {
+                        document.tiled_quad(quad);
+                    }
+                }
+                PrimitiveBatch::Paths(paths) => {
+                    for path in paths {
+                        document.path(path);
+                    }
+                }
+                PrimitiveBatch::Underlines(underlines) => {
+                    for underline in underlines {
+                        document.underline(underline);
+                    }
+                }
+                PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                    if let Some(atlas) = atlas {
+                        for sprite in sprites {
+                            document.monochrome_sprite(sprite, atlas);
+                        }
+                    }
+                }
+                PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                    if let Some(atlas) = atlas {
+                        for sprite in sprites {
+                            document.polychrome_sprite(sprite, atlas);
+                        }
+                    }
+                }
+                PrimitiveBatch::BackdropBlurs(_)
+                | PrimitiveBatch::Surfaces(_)
+                | PrimitiveBatch::CustomRenderPrimitives(_) => {}
+            }
+        }
+        document.finish()
+    }
+}
+
+#[derive(Default)]
+struct SvgDocument {
+    definitions: String,
+    body: String,
+    clip_paths: Vec<ContentMask<ScaledPixels>>,
+    /// The ids of the sprite tiles defined so far, or `None` for tiles without pixels.
+    sprite_tiles: HashMap<SpriteTileKey, Option<String>>,
+    grayscale_filter: bool,
+    definition_count: usize,
+    width: f32,
+    height: f32,
+}
+
+impl SvgDocument {
+    fn quad(&mut self, quad: &Quad) {
+        let groups = self.begin_clip(&quad.bounds, &quad.content_mask, &quad.transformation);
+        let corners = corner_ellipses(
+            &quad.bounds,
+            &quad.corner_radii,
+            &quad.vertical_corner_radii,
+        );
+        let outline = rounded_rect(&quad.bounds, &corners);
+
+        if is_visible(&quad.background) {
+            let fill = self.fill(&quad.background, &quad.bounds);
+            self.body
+                .push_str(&format!("<path d=\"{outline}\"{fill}/>\n"));
+        }
+
+        let widths = &quad.border_widths;
+        let has_border = [widths.top, widths.right, widths.bottom, widths.left]
+            .iter()
+            .any(|width| width.0 > 0.);
+        if has_border && quad.border_color.a > 0. {
+            let uniform = widths.top == widths.right
+                && widths.top == widths.bottom
+                && widths.top == widths.left;
+            if quad.border_style != BorderStyle::Solid && uniform {
+                let width = widths.top.0;
+                let half_width = ScaledPixels(width / 2.);
+                let center_line = rounded_rect(
+                    &quad.bounds.dilate(ScaledPixels(-half_width.0)),
+                    &inset_corner_ellipses(&corners, &Edges::all(half_width)),
+                );
+                let dashes = if quad.border_style == BorderStyle::Dotted {
+                    format!(
+                        "stroke-dasharray=\"0 {}\" stroke-linecap=\"round\"",
+                        number(width * 2.)
+                    )
+                } else {
+                    let dash = number(width * 2.);
+                    format!("stroke-dasharray=\"{dash} {dash}\"")
+                };
+                self.body.push_str(&format!(
+                    "<path d=\"{center_line}\" fill=\"none\"{} stroke-width=\"{}\" {dashes}/>\n",
+                    color_attributes("stroke", quad.border_color),
+                    number(width),
+                ));
+            } else {
+                let inner = rounded_rect(
+                    &border_inner_bounds(&quad.bounds, widths),
+                    &inset_corner_ellipses(&corners, widths),
+                );
+                self.body.push_str(&format!(
+                    "<path d=\"{outline}{inner}\" fill-rule=\"evenodd\"{}/>\n",
+                    color_attributes("fill", quad.border_color),
+                ));
+            }
+        }
+
+        self.end_clip(groups);
+    }
+
+    /// Draws the tiles with a pattern two tiles across and two down, which holds one tile of
+    /// each color on either diagonal.
+    fn tiled_quad(&mut self, quad: &TiledQuad) {
+        let tile_size = quad.tile_size;
+        if tile_size.width.0 <= 0. || tile_size.height.0 <= 0. {
+            return;
+        }
+        let groups = self.begin_clip(&quad.bounds, &quad.content_mask, &quad.transformation);
+        let id = self.next_id("tiles");
+        let corner_radii = Corners::all(quad.corner_radius);
+        let mut tiles = String::new();
+        for (column, row) in [(0., 0.), (1., 0.), (0., 1.), (1., 1.)] {
+            let color = if column == row {
+                quad.color
+            } else {
+                quad.alternate_color
+            };
+            if color.a <= 0. {
+                continue;
+            }
+            let mut bounds = quad.quad_bounds;
+            bounds.origin.x += ScaledPixels(tile_size.width.0 * column);
+            bounds.origin.y += ScaledPixels(tile_size.height.0 * row);
+            tiles.push_str(&format!(
+                "<path d=\"{}\"{}/>",
+                rounded_rect(
+                    &bounds,
+                    &corner_ellipses(&bounds, &corner_radii, &Corners::default())
+                ),
+                color_attributes("fill", color),
+            ));
+        }
+        self.definitions.push_str(&format!(
+            "<pattern id=\"{id}\" patternUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\">{tiles}</pattern>\n",
+            number(quad.bounds.origin.x.0),
+            number(quad.bounds.origin.y.0),
+            number(tile_size.width.0 * 2.),
+            number(tile_size.height.0 * 2.),
+        ));
+        self.body.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"url(#{id})\"/>\n",
+            number(quad.bounds.origin.x.0),
+            number(quad.bounds.origin.y.0),
+            number(quad.bounds.size.width.0),
+            number(quad.bounds.size.height.0),
+        ));
+        self.end_clip(groups);
+    }
+
+    fn shadow(&mut self, shadow: &Shadow) {
+        if shadow.color.a <= 0. {
+            return;
+        }
+        let groups = self.begin_clip(&shadow.bounds, &shadow.content_mask, &shadow.transformation);
+        let outline = rounded_rect(
+            &shadow.bounds,
+            &corner_ellipses(&shadow.bounds, &shadow.corner_radii, &Corners::default()),
+        );
+        let fill = color_attributes("fill", shadow.color);
+        if shadow.blur_radius.0 > 0. {
+            let id = self.next_id("shadow");
+            let region = shadow
+                .bounds
+                .dilate(ScaledPixels(shadow.blur_radius.0 * 3.));
+            self.definitions.push_str(&format!(
+                "<filter id=\"{id}\" filterUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"><feGaussianBlur stdDeviation=\"{}\"/></filter>\n",
+                number(region.origin.x.0),
+                number(region.origin.y.0),
+                number(region.size.width.0),
+                number(region.size.height.0),
+                number(shadow.blur_radius.0 / 2.),
+            ));
+            self.body.push_str(&format!(
+                "<path d=\"{outline}\"{fill} filter=\"url(#{id})\"/>\n"
+            ));
+        } else {
+            self.body
+                .push_str(&format!("<path d=\"{outline}\"{fill}/>\n"));
+        }
+        self.end_clip(groups);
+    }
+
+    fn monochrome_sprite(&mut self, sprite: &MonochromeSprite, atlas: &dyn AtlasPixels) {
+        if sprite.color.a <= 0. {
+            return;
+        }
+        let Some(id) = self.sprite_tile(&sprite.tile, sprite.distance_field, atlas) else {
+            return;
+        };
+        let groups = self.begin_clip(&sprite.bounds, &sprite.content_mask, &sprite.transformation);
+        let bounds = &sprite.bounds;
+        self.body.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{} mask=\"url(#{id})\"/>\n",
+            number(bounds.origin.x.0),
+            number(bounds.origin.y.0),
+            number(bounds.size.width.0),
+            number(bounds.size.height.0),
+            color_attributes("fill", sprite.color),
+        ));
+        self.end_clip(groups);
+    }
+
+    fn polychrome_sprite(&mut self, sprite: &PolychromeSprite, atlas: &dyn AtlasPixels) {
+        if sprite.opacity <= 0. {
+            return;
+        }
+        let Some(id) = self.sprite_tile(&sprite.tile, 0, atlas) else {
+            return;
+        };
+        let mut groups =
+            self.begin_clip(&sprite.bounds, &sprite.content_mask, &sprite.transformation);
+        let bounds = &sprite.bounds;
+        if sprite.corner_radii != Corners::default() {
+            let clip_id = self.next_id("corners");
+            self.definitions.push_str(&format!(
+                "<clipPath id=\"{clip_id}\"><path d=\"{}\"/></clipPath>\n",
+                rounded_rect(
+                    bounds,
+                    &corner_ellipses(bounds, &sprite.corner_radii, &Corners::default())
+                ),
+            ));
+            self.body
+                .push_str(&format!("<g clip-path=\"url(#{clip_id})\">\n"));
+            groups += 1;
+        }
+        let mut attributes = String::new();
+        if sprite.opacity < 1. {
+            attributes.push_str(&format!(" opacity=\"{}\"", number(sprite.opacity)));
+        }
+        if sprite.grayscale {
+            if !self.grayscale_filter {
+                self.grayscale_filter = true;
+                self.definitions.push_str(
+                    "<filter id=\"grayscale\"><feColorMatrix type=\"saturate\" values=\"0\"/></filter>\n",
+                );
+            }
+            attributes.push_str(" filter=\"url(#grayscale)\"");
+        }
+        if sprite.nearest != 0 {
+            attributes.push_str(" image-rendering=\"pixelated\"");
+        }
+        self.body.push_str(&format!(
+            "<use href=\"#{id}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{attributes}/>\n",
+            number(bounds.origin.x.0),
+            number(bounds.origin.y.0),
+            number(bounds.size.width.0),
+            number(bounds.size.height.0),
+        ));
+        self.end_clip(groups);
+    }
+
+    /// Returns the id of the definition drawing `tile`, defining it if needed: a mask stretched
+    /// over the shape it's applied to for monochrome tiles, and a symbol for polychrome ones.
+    fn sprite_tile(
+        &mut self,
+        tile: &AtlasTile,
+        distance_field: u32,
+        atlas: &dyn AtlasPixels,
+    ) -> Option<String> {
+        let key = (tile.texture_id, tile.bounds, distance_field);
+        if let Some(id) = self.sprite_tiles.get(&key) {
+            return id.clone();
+        }
+        let id = sprite_pixels(atlas, tile, distance_field).and_then(|pixels| {
+            let image = pixels.to_png_data_uri()?;
+            let (width, height) = (pixels.size.width.0, pixels.size.height.0);
+            Some(match tile.texture_id.kind {
+                AtlasTextureKind::Monochrome => {
+                    let id = self.next_id("glyph");
+                    self.definitions.push_str(&format!(
+                        "<mask id=\"{id}\" maskContentUnits=\"objectBoundingBox\"><image width=\"1\" height=\"1\" preserveAspectRatio=\"none\" href=\"{image}\"/></mask>\n"
+                    ));
+                    id
+                }
+                AtlasTextureKind::Polychrome => {
+                    let id = self.next_id("image");
+                    self.definitions.push_str(&format!(
+                        "<symbol id=\"{id}\" viewBox=\"0 0 {width} {height}\" preserveAspectRatio=\"none\"><image width=\"{width}\" height=\"{height}\" href=\"{image}\"/></symbol>\n"
+                    ));
+                    id
+                }
+            })
+        });
+        self.sprite_tiles.insert(key, id.clone());
+        id
+    }
+
+    fn path(&mut self, path: &Path<ScaledPixels>) {
+        if !is_visible(&path.color) {
+            return;
+        }
+        let groups = self.begin_clip(
+            &path.bounds,
+            &path.content_mask,
+            &TransformationMatrix::unit(),
+        );
+        // Paths are triangle fans whose overlapping triangles cancel out, with curves drawn as
+        // triangles between the curve and its chord. The even-odd rule reproduces both.
+        let mut outline = String::new();
+        for triangle in path.vertices.chunks_exact(3) {
+            let [start, control, end] = [
+                triangle[0].xy_position,
+                triangle[1].xy_position,
+                triangle[2].xy_position,
+            ];
+            let is_curve = triangle[1].st_position == point(0.5, 0.);
+            outline.push_str(&format!(
+                "M{} {}{}{} {}{}{} {}Z",
+                number(start.x.0),
+                number(start.y.0),
+                if is_curve { "Q" } else { "L" },
+                number(control.x.0),
+                number(control.y.0),
+                if is_curve { " " } else { "L" },
+                number(end.x.0),
+                number(end.y.0),
+            ));
+        }
+        let fill = self.fill(&path.color, &path.bounds);
+        self.body.push_str(&format!(
+            "<path d=\"{outline}\" fill-rule=\"evenodd\"{fill}/>\n"
+        ));
+        self.end_clip(groups);
+    }
+
+    fn underline(&mut self, underline: &Underline) {
+        if underline.color.a <= 0. {
+            return;
+        }
+        let groups = self.begin_clip(
+            &underline.bounds,
+            &underline.content_mask,
+            &underline.transformation,
+        );
+        let bounds = &underline.bounds;
+        if underline.wavy == 0 {
+            self.body.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/>\n",
+                number(bounds.origin.x.0),
+                number(bounds.origin.y.0),
+                number(bounds.size.width.0),
+                number(bounds.size.height.0),
+                color_attributes("fill", underline.color),
+            ));
+        } else {
+            let points = wave_points(underline)
+                .into_iter()
+                .map(|(x, y)| format!("{},{}", number(x), number(y)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.body.push_str(&format!(
+                "<polyline points=\"{points}\" fill=\"none\"{} stroke-width=\"{}\"/>\n",
+                color_attributes("stroke", underline.color),
+                number(underline.thickness.0),
+            ));
+        }
+        self.end_clip(groups);
+    }
+
+    /// Returns the fill attributes for `background`, defining a gradient if needed.
+    fn fill(&mut self, background: &Background, bounds: &Bounds<ScaledPixels>) -> String {
+        if background.tag != BackgroundTag::LinearGradient {
+            return color_attributes("fill", background.solid);
+        }
+        // Gradients are laid out relative to the bounding box of the shape they fill, which
+        // doesn't exist for shapes without area.
+        if bounds.size.width.0 <= 0. || bounds.size.height.0 <= 0. {
+            return color_attributes("fill", background.colors[0].color);
+        }
+
+        let (x, y) = gradient_direction(background);
+        let id = self.next_id("gradient");
+        let mut stops = String::new();
+        for stop in &background.colors {
+            let rgba = stop.color.to_rgb();
+            stops.push_str(&format!(
+                "<stop offset=\"{}\" stop-color=\"#{:06x}\" stop-opacity=\"{}\"/>",
+                number(stop.percentage),
+                u32::from(rgba) >> 8,
+                number(rgba.a),
+            ));
+        }
+        self.definitions.push_str(&format!(
+            "<linearGradient id=\"{id}\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\">{stops}</linearGradient>\n",
+            number(0.5 - x / 2.),
+            number(0.5 - y / 2.),
+            number(0.5 + x / 2.),
+            number(0.5 + y / 2.),
+        ));
+        format!(" fill=\"url(#{id})\"")
+    }
+
+    /// Clips subsequent shapes to the content mask if it cuts off part of `bounds`, returning
+    /// whether a clip group was opened.
+    /// Opens the groups that clip a primitive to its content mask and transform it, returning
+    /// how many were opened. The mask is applied in document space, outside the transform.
+    fn begin_clip(
+        &mut self,
+        bounds: &Bounds<ScaledPixels>,
+        content_mask: &ContentMask<ScaledPixels>,
+        transformation: &TransformationMatrix,
+    ) -> usize {
+        let bounds = transformation.transform_bounds(*bounds);
+        let visible = bounds.intersect(&content_mask.bounds);
+        self.width = self.width.max(visible.right().0);
+        self.height = self.height.max(visible.bottom().0);
+
+        let mut groups = 0;
+        let rounded = content_mask.corner_radii != Corners::default();
+        if visible != bounds || rounded {
+            let ix = match self
+                .clip_paths
+                .iter()
+                .position(|existing| existing == content_mask)
+            {
+                Some(ix) => ix,
+                None => {
+                    self.clip_paths.push(content_mask.clone());
+                    let ix = self.clip_paths.len() - 1;
+                    let mask = content_mask.bounds;
+                    let shape = if rounded {
+                        format!(
+                            "<path d=\"{}\"/>",
+                            rounded_rect(
+                                &mask,
+                                &corner_ellipses(
+                                    &mask,
+                                    &content_mask.corner_radii,
+                                    &Corners::default()
+                                )
+                            )
+                        )
+                    } else {
+                        format!(
+                            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                            number(mask.origin.x.0),
+                            number(mask.origin.y.0),
+                            number(mask.size.width.0),
+                            number(mask.size.height.0),
+                        )
+                    };
+                    self.definitions
+                        .push_str(&format!("<clipPath id=\"clip{ix}\">{shape}</clipPath>\n"));
+                    ix
+                }
+            };
+            self.body
+                .push_str(&format!("<g clip-path=\"url(#clip{ix})\">\n"));
+            groups += 1;
+        }
+
+        if *transformation != TransformationMatrix::unit() {
+            let [[a, c], [b, d]] = transformation.rotation_scale;
+            let [e, f] = transformation.translation;
+            self.body.push_str(&format!(
+                "<g transform=\"matrix({} {} {} {} {} {})\">\n",
+                number(a),
+                number(b),
+                number(c),
+                number(d),
+                number(e),
+                number(f),
+            ));
+            groups += 1;
+        }
+        groups
+    }
+
+    fn end_clip(&mut self, groups: usize) {
+        for _ in 0..groups {
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.definition_count += 1;
+        format!("{prefix}{}", self.definition_count)
+    }
+
+    fn finish(self) -> String {
+        let width = number(self.width.ceil());
+        let height = number(self.height.ceil());
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        if !self.definitions.is_empty() {
+            svg.push_str("<defs>\n");
+            svg.push_str(&self.definitions);
+            svg.push_str("</defs>\n");
+        }
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Identifies the pixels a sprite draws: the region of an atlas texture it samples, and how its
+/// coverage is interpreted.
+pub(super) type SpriteTileKey = (AtlasTextureId, Bounds<DevicePixels>, u32);
+
+/// The pixels a sprite draws from its tile, as RGBA with straight alpha, row by row from the top.
+pub(super) struct SpritePixels {
+    pub(super) size: crate::Size<DevicePixels>,
+    pub(super) rgba: Vec<u8>,
+}
+
+impl SpritePixels {
+    fn to_png_data_uri(&self) -> Option<String> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(
+            &mut png,
+            self.size.width.0 as u32,
+            self.size.height.0 as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().log_err()?;
+        writer.write_image_data(&self.rgba).log_err()?;
+        writer.finish().log_err()?;
+        Some(format!(
+            "data:image/png;base64,{}",
+            BASE64_STANDARD.encode(png)
+        ))
+    }
+}
+
+/// Reads the pixels that a sprite draws from `tile`. Monochrome tiles become white, with the
+/// coverage they draw as alpha, so that they can be tinted with the sprite's color. Returns `None`
+/// if the atlas doesn't have the tile's pixels.
+pub(super) fn sprite_pixels(
+    atlas: &dyn AtlasPixels,
+    tile: &AtlasTile,
+    distance_field: u32,
+) -> Option<SpritePixels> {
+    let (texture_size, pixels) = atlas.texture_pixels(tile.texture_id)?;
+    let bytes_per_pixel = match tile.texture_id.kind {
+        AtlasTextureKind::Monochrome => 1,
+        AtlasTextureKind::Polychrome => 4,
+    };
+    let texture_width = usize::try_from(texture_size.width.0).ok()?;
+    let texture_height = usize::try_from(texture_size.height.0).ok()?;
+    let left = usize::try_from(tile.bounds.origin.x.0).ok()?;
+    let top = usize::try_from(tile.bounds.origin.y.0).ok()?;
+    let width = usize::try_from(tile.bounds.size.width.0).ok()?;
+    let height = usize::try_from(tile.bounds.size.height.0).ok()?;
+    if width == 0
+        || height == 0
+        || left + width > texture_width
+        || top + height > texture_height
+        || pixels.len() < texture_width * texture_height * bytes_per_pixel
+    {
+        return None;
+    }
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in top..top + height {
+        let start = (row * texture_width + left) * bytes_per_pixel;
+        let row_pixels = &pixels[start..start + width * bytes_per_pixel];
+        match tile.texture_id.kind {
+            AtlasTextureKind::Monochrome => {
+                for sample in row_pixels {
+                    rgba.extend_from_slice(&[255, 255, 255, coverage(*sample, distance_field)]);
+                }
+            }
+            AtlasTextureKind::Polychrome => {
+                for pixel in row_pixels.chunks_exact(4) {
+                    rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+                }
+            }
+        }
+    }
+    Some(SpritePixels {
+        size: tile.bounds.size,
+        rgba,
+    })
+}
+
+/// Returns the coverage drawn for a monochrome tile's sample, thresholding the signed distance
+/// fields of [`MonochromeSprite::distance_field`] the way the shaders do at the field's scale.
+fn coverage(sample: u8, distance_field: u32) -> u8 {
+    if distance_field == 0 || distance_field == MonochromeSprite::LINEAR_COVERAGE {
+        return sample;
+    }
+    let edge = (distance_field & 0xffff) as f32 / 65535.;
+    let falloff = ((distance_field >> 16) as f32 / 65535.).max(0.5 / 255.);
+    let value = sample as f32 / 255.;
+    let t = ((value - edge + falloff) / (2. * falloff)).clamp(0., 1.);
+    (t * t * (3. - 2. * t) * 255.).round() as u8
+}
+
+pub(super) fn is_visible(background: &Background) -> bool {
+    match background.tag {
+        BackgroundTag::LinearGradient => background.colors.iter().any(|stop| stop.color.a > 0.),
+        BackgroundTag::Solid | BackgroundTag::PatternSlash => background.solid.a > 0.,
+    }
+}
+
+/// Returns ` {property}="#rrggbb"`, followed by an opacity attribute if the color is translucent.
+fn color_attributes(property: &str, color: Hsla) -> String {
+    let rgba = color.to_rgb();
+    let hex = u32::from(rgba) >> 8;
+    if rgba.a < 1. {
+        format!(
+            " {property}=\"#{hex:06x}\" {property}-opacity=\"{}\"",
+            number(rgba.a)
+        )
+    } else {
+        format!(" {property}=\"#{hex:06x}\"")
+    }
+}
+
+/// The horizontal and vertical radii of a rectangle's corners, clockwise from the top left.
+pub(super) type CornerEllipses = [(f32, f32); 4];
+
+/// Returns the radii of a rectangle's corners, clamped to fit it. Corners with a vertical radius
+/// are elliptical, and are clamped to half the rectangle's width and height. The others are
+/// circular, and are clamped to half its shortest side.
+pub(super) fn corner_ellipses(
+    bounds: &Bounds<ScaledPixels>,
+    corner_radii: &Corners<ScaledPixels>,
+    vertical_corner_radii: &Corners<ScaledPixels>,
+) -> CornerEllipses {
+    let max_horizontal = (bounds.size.width.0 / 2.).max(0.);
+    let max_vertical = (bounds.size.height.0 / 2.).max(0.);
+    let max_radius = max_horizontal.min(max_vertical);
+    let ellipse = |horizontal: ScaledPixels, vertical: ScaledPixels| {
+        if vertical.0 > 0. {
+            square_if_flat((
+                horizontal.0.clamp(0., max_horizontal),
+                vertical.0.min(max_vertical),
+            ))
+        } else {
+            let radius = horizontal.0.clamp(0., max_radius);
+            (radius, radius)
+        }
+    };
+    [
+        ellipse(corner_radii.top_left, vertical_corner_radii.top_left),
+        ellipse(corner_radii.top_right, vertical_corner_radii.top_right),
+        ellipse(
+            corner_radii.bottom_right,
+            vertical_corner_radii.bottom_right,
+        ),
+        ellipse(corner_radii.bottom_left, vertical_corner_radii.bottom_left),
+    ]
+}
+
+/// Returns the radii of the inner edge of a border with the given widths.
+pub(super) fn inset_corner_ellipses(
+    corners: &CornerEllipses,
+    widths: &Edges<ScaledPixels>,
+) -> CornerEllipses {
+    let inset = |(horizontal, vertical): (f32, f32), first: ScaledPixels, second: ScaledPixels| {
+        let width = first.0.max(second.0);
+        square_if_flat(((horizontal - width).max(0.), (vertical - width).max(0.)))
+    };
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    [
+        inset(top_left, widths.top, widths.left),
+        inset(top_right, widths.top, widths.right),
+        inset(bottom_right, widths.bottom, widths.right),
+        inset(bottom_left, widths.bottom, widths.left),
+    ]
+}
+
+/// A corner with no radius on one axis has no curve, so it's square.
+fn square_if_flat((horizontal, vertical): (f32, f32)) -> (f32, f32) {
+    if horizontal > 0. && vertical > 0. {
+        (horizontal, vertical)
+    } else {
+        (0., 0.)
+    }
+}
+
+/// Returns the SVG path data for a rectangle with the given corners.
+fn rounded_rect(bounds: &Bounds<ScaledPixels>, corners: &CornerEllipses) -> String {
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    let left = bounds.origin.x.0;
+    let top = bounds.origin.y.0;
+    let right = bounds.right().0;
+    let bottom = bounds.bottom().0;
+
+    let mut data = format!(
+        "M{} {}H{}",
+        number(left + top_left.0),
+        number(top),
+        number(right - top_right.0)
+    );
+    push_corner(&mut data, top_right, right, top + top_right.1);
+    data.push_str(&format!("V{}", number(bottom - bottom_right.1)));
+    push_corner(&mut data, bottom_right, right - bottom_right.0, bottom);
+    data.push_str(&format!("H{}", number(left + bottom_left.0)));
+    push_corner(&mut data, bottom_left, left, bottom - bottom_left.1);
+    data.push_str(&format!("V{}", number(top + top_left.1)));
+    push_corner(&mut data, top_left, left + top_left.0, top);
+    data.push('Z');
+    data
+}
+
+fn push_corner(data: &mut String, (horizontal, vertical): (f32, f32), end_x: f32, end_y: f32) {
+    if horizontal > 0. {
+        data.push_str(&format!(
+            "A{} {} 0 0 1 {} {}",
+            number(horizontal),
+            number(vertical),
+            number(end_x),
+            number(end_y),
+        ));
+    }
+}
+
+/// Returns points along the center line of a wavy underline.
+pub(super) fn wave_points(underline: &Underline) -> Vec<(f32, f32)> {
+    let bounds = &underline.bounds;
+    let period = underline.wavelength.0.max(1.);
+    let amplitude = underline.wave_amplitude.0;
+    let center_y = bounds.origin.y.0 + bounds.size.height.0 / 2.;
+    let step = period / WAVE_SEGMENTS_PER_PERIOD as f32;
+    let segment_count = (bounds.size.width.0 / step).ceil().max(1.) as usize;
+    (0..=segment_count)
+        .map(|ix| {
+            let x = (ix as f32 * step).min(bounds.size.width.0);
+            let y = center_y + (x / period * 2. * PI).sin() * amplitude;
+            (bounds.origin.x.0 + x, y)
+        })
+        .collect()
+}
+
+/// Returns the direction of a linear gradient, scaled so that its longest component is 1.
+///
+/// Gradient angles follow CSS, where 0 degrees points up and angles increase clockwise.
+pub(super) fn gradient_direction(background: &Background) -> (f32, f32) {
+    let radians = (background.gradient_angle_or_pattern_height % 360. - 90.).to_radians();
+    let (x, y) = (radians.cos(), radians.sin());
+    let longest = x.abs().max(y.abs());
+    (x / longest, y / longest)
+}
+
+/// Returns the bounds inside a border with the given widths.
+pub(super) fn border_inner_bounds(
+    bounds: &Bounds<ScaledPixels>,
+    widths: &Edges<ScaledPixels>,
+) -> Bounds<ScaledPixels> {
+    Bounds {
+        origin: point(bounds.origin.x + widths.left, bounds.origin.y + widths.top),
+        size: size(
+            ScaledPixels((bounds.size.width - widths.left - widths.right).0.max(0.)),
+            ScaledPixels((bounds.size.height - widths.top - widths.bottom).0.max(0.)),
+        ),
+    }
+}
+
+/// Rounds a coordinate to hundredths of a pixel, which keeps documents compact without visible
+/// loss of precision.
+pub(super) fn number(value: f32) -> f32 {
+    let rounded = (value * 100.).round() / 100.;
+    if rounded == 0. { 0. } else { rounded }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Hsla, QuadShadow,
+        test_helpers::{TestAtlas, bounds, glyph, image},
+    };
+
+    #[test]
+    fn test_rounded_rect() {
+        let rect = |corner_radii: Corners<ScaledPixels>, vertical: Corners<ScaledPixels>| {
+            let bounds = bounds(0., 0., 10., 20.);
+            rounded_rect(&bounds, &corner_ellipses(&bounds, &corner_radii, &vertical))
+        };
+        assert_eq!(
+            rect(Corners::default(), Corners::default()),
+            "M0 0H10V20H0V0Z"
+        );
+        assert_eq!(
+            rect(Corners::all(ScaledPixels(8.)), Corners::default()),
+            "M5 0H5A5 5 0 0 1 10 5V15A5 5 0 0 1 5 20H5A5 5 0 0 1 0 15V5A5 5 0 0 1 5 0Z",
+            "radii are clamped to half the shortest side"
+        );
+        assert_eq!(
+            rect(
+                Corners::all(ScaledPixels(8.)),
+                Corners::all(ScaledPixels(8.))
+            ),
+            "M5 0H5A5 8 0 0 1 10 8V12A5 8 0 0 1 5 20H5A5 8 0 0 1 0 12V8A5 8 0 0 1 5 0Z",
+            "elliptical radii are clamped on each axis"
+        );
+    }
+
+    #[test]
+    fn test_scene_to_svg() {
+        let mut scene = Scene::default();
+        let content_mask = ContentMask {
+            bounds: bounds(0., 0., 100., 50.),
+            ..Default::default()
+        };
+        scene.insert_primitive(Quad {
+            bounds: bounds(10., 10., 200., 20.),
+            content_mask: content_mask.clone(),
+            background: Hsla::red().into(),
+            ..Default::default()
+        });
+        scene.insert_primitive(Underline {
+            order: 0,
+            pad: 0,
+            bounds: bounds(10., 40., 50., 1.),
+            content_mask,
+            color: Hsla::blue().opacity(0.5),
+            thickness: ScaledPixels(1.),
+            wavy: 0,
+            wave_amplitude: ScaledPixels(0.),
+            wavelength: ScaledPixels(0.),
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+
+        let svg = scene.to_svg();
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"41\""
+            )
+        );
+        assert!(svg.contains(
+            "<clipPath id=\"clip0\"><rect x=\"0\" y=\"0\" width=\"100\" height=\"50\"/></clipPath>"
+        ));
+        assert!(svg.contains("<g clip-path=\"url(#clip0)\">\n<path d=\"M10 10H210V30H10V10Z\" fill=\"#ff0000\"/>\n</g>"));
+        assert!(svg.contains("<rect x=\"10\" y=\"40\" width=\"50\" height=\"1\" fill=\"#0000ff\" fill-opacity=\"0.5\"/>"));
+        let quad_position = svg.find("<path").unwrap();
+        let underline_position = svg.find("<rect x=\"10\"").unwrap();
+        assert!(
+            quad_position < underline_position,
+            "shapes are in draw order"
+        );
+    }
+
+    #[test]
+    fn test_sprites_to_svg() {
+        let mut atlas = TestAtlas::default();
+        let letter = atlas.insert(AtlasTextureKind::Monochrome, 2, 1, vec![255, 128]);
+        let picture = atlas.insert(AtlasTextureKind::Polychrome, 1, 1, vec![0, 0, 255, 255]);
+        let mut scene = Scene::default();
+        scene.insert_primitive(glyph(letter.clone(), bounds(10., 10., 2., 1.), Hsla::red()));
+        scene.insert_primitive(glyph(letter, bounds(12., 10., 2., 1.), Hsla::blue()));
+        scene.insert_primitive(image(picture, bounds(20., 20., 10., 10.)));
+        scene.finish();
+
+        assert!(!scene.to_svg().contains("<mask"), "sprites need an atlas");
+        let svg = scene.to_svg_with_sprites(&atlas);
+        assert_eq!(
+            svg.matches("<mask id=").count(),
+            1,
+            "each glyph is defined once"
+        );
+        assert!(svg.contains(
+            "<rect x=\"10\" y=\"10\" width=\"2\" height=\"1\" fill=\"#ff0000\" mask=\"url(#glyph1)\"/>"
+        ));
+        assert!(svg.contains(
+            "<rect x=\"12\" y=\"10\" width=\"2\" height=\"1\" fill=\"#0000ff\" mask=\"url(#glyph1)\"/>"
+        ));
+        assert!(
+            svg.contains("<use href=\"#image2\" x=\"20\" y=\"20\" width=\"10\" height=\"10\"/>")
+        );
+
+        let decode = |id: &str| {
+            let definition = &svg[svg.find(&format!("id=\"{id}\"")).unwrap()..];
+            let data = definition.split("base64,").nth(1).unwrap();
+            let data = BASE64_STANDARD
+                .decode(&data[..data.find('"').unwrap()])
+                .unwrap();
+            let mut reader = png::Decoder::new(std::io::Cursor::new(data))
+                .read_info()
+                .unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+            reader.next_frame(&mut pixels).unwrap();
+            pixels
+        };
+        assert_eq!(
+            decode("glyph1"),
+            [255, 255, 255, 255, 255, 255, 255, 128],
+            "glyph coverage becomes the alpha of a white mask"
+        );
+        assert_eq!(decode("image2"), [255, 0, 0, 255], "BGRA becomes RGBA");
+    }
+
+    #[test]
+    fn test_dotted_border_to_svg() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(Quad {
+            bounds: bounds(0., 0., 20., 10.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            border_color: Hsla::black(),
+            border_widths: Edges::all(ScaledPixels(2.)),
+            border_style: BorderStyle::Dotted,
+            ..Default::default()
+        });
+        scene.finish();
+
+        let svg = scene.to_svg();
+        assert!(svg.contains(
+            "<path d=\"M1 1H19V9H1V1Z\" fill=\"none\" stroke=\"#000000\" stroke-width=\"2\" stroke-dasharray=\"0 4\" stroke-linecap=\"round\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_transformed_quad_to_svg() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(Quad {
+            bounds: bounds(0., 0., 10., 10.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            background: Hsla::red().into(),
+            transformation: TransformationMatrix::unit()
+                .translate(point(ScaledPixels(30.), ScaledPixels(20.)))
+                .scale(size(2., 2.)),
+            ..Default::default()
+        });
+        scene.finish();
+
+        let svg = scene.to_svg();
+        assert!(svg.contains("width=\"50\" height=\"40\""));
+        assert!(svg.contains(
+            "<g transform=\"matrix(2 0 0 2 30 20)\">\n<path d=\"M0 0H10V10H0V0Z\" fill=\"#ff0000\"/>\n</g>"
+        ));
+    }
+
+    #[test]
+    fn test_quad_shadow_to_svg() {
+        let mut scene = Scene::default();
+        let quad = Quad {
+            bounds: bounds(10., 10., 20., 20.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            background: Hsla::red().into(),
+            shadow: QuadShadow {
+                bounds: bounds(12., 14., 20., 20.),
+                color: Hsla::black().opacity(0.5),
+                blur_radius: ScaledPixels(2.),
+                pad: 0,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            quad.painted_bounds(),
+            bounds(6., 8., 32., 32.),
+            "the painted bounds cover the blurred shadow"
+        );
+        scene.insert_primitive(quad);
+        scene.finish();
+
+        let svg = scene.to_svg();
+        let shadow_position = svg.find("<path d=\"M12 14").unwrap();
+        let quad_position = svg.find("<path d=\"M10 10").unwrap();
+        assert!(
+            shadow_position < quad_position,
+            "the shadow is beneath the quad"
+        );
+    }
+
+    #[test]
+    fn test_tiled_quad_to_svg() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(TiledQuad {
+            order: 0,
+            corner_radius: ScaledPixels(0.),
+            bounds: bounds(0., 0., 40., 20.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            tile_size: size(ScaledPixels(10.), ScaledPixels(10.)),
+            quad_bounds: bounds(0., 0., 10., 10.),
+            color: Hsla::red(),
+            alternate_color: Hsla::blue(),
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+        assert_eq!(scene.stats().tiled_quads, 1);
+
+        let svg = scene.to_svg();
+        assert!(svg.contains(
+            "<pattern id=\"tiles1\" patternUnits=\"userSpaceOnUse\" x=\"0\" y=\"0\" width=\"20\" height=\"20\"><path d=\"M0 0H10V10H0V0Z\" fill=\"#ff0000\"/><path d=\"M10 0H20V10H10V0Z\" fill=\"#0000ff\"/>"
+        ));
+        assert!(
+            svg.contains(
+                "<rect x=\"0\" y=\"0\" width=\"40\" height=\"20\" fill=\"url(#tiles1)\"/>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_transformed_tiled_quad_to_svg() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(TiledQuad {
+            order: 0,
+            corner_radius: ScaledPixels(0.),
+            bounds: bounds(0., 0., 40., 20.),
+            content_mask: ContentMask {
+                bounds: bounds(0., 0., 100., 100.),
+                ..Default::default()
+            },
+            tile_size: size(ScaledPixels(10.), ScaledPixels(10.)),
+            quad_bounds: bounds(0., 0., 10., 10.),
+            color: Hsla::red(),
+            alternate_color: Hsla::red(),
+            transformation: TransformationMatrix::unit()
+                .translate(point(ScaledPixels(30.), ScaledPixels(20.))),
+        });
+        scene.finish();
+
+        let svg = scene.to_svg();
+        assert!(svg.contains(
+            "<g transform=\"matrix(1 0 0 1 30 20)\">\n<rect x=\"0\" y=\"0\" width=\"40\" height=\"20\" fill=\"url(#tiles1)\"/>\n</g>"
+        ));
+    }
+}
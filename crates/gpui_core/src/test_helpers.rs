@@ -0,0 +1,105 @@
+//! Factories for the primitives that unit tests build scenes from. The benchmarks can't use
+//! `cfg(test)` code, so they keep their own.
+
+use crate::{
+    AtlasPixels, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, ContentMask, DevicePixels,
+    Hsla, MonochromeSprite, PolychromeSprite, Quad, ScaledPixels, Size, TileId,
+    TransformationMatrix, point, size,
+};
+use std::collections::HashMap;
+
+/// Returns bounds in device pixels with the given origin and size.
+pub(crate) fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+    Bounds::new(
+        point(ScaledPixels(x), ScaledPixels(y)),
+        size(ScaledPixels(width), ScaledPixels(height)),
+    )
+}
+
+/// Returns a transparent quad with the given bounds, masked to the 100×100 square at the origin.
+pub(crate) fn quad(bounds: Bounds<ScaledPixels>) -> Quad {
+    Quad {
+        bounds,
+        content_mask: ContentMask {
+            bounds: self::bounds(0., 0., 100., 100.),
+            ..Default::default()
+        },
+        background: Hsla::default().into(),
+        ..Default::default()
+    }
+}
+
+/// An atlas holding the pixels of the textures of the sprites built below.
+#[derive(Default)]
+pub(crate) struct TestAtlas(HashMap<AtlasTextureId, (Size<DevicePixels>, Vec<u8>)>);
+
+impl TestAtlas {
+    /// Adds a texture with the given pixels, returning a tile covering all of it.
+    pub(crate) fn insert(
+        &mut self,
+        kind: AtlasTextureKind,
+        width: i32,
+        height: i32,
+        pixels: Vec<u8>,
+    ) -> AtlasTile {
+        let texture_id = AtlasTextureId {
+            index: self.0.len() as u32,
+            kind,
+        };
+        let texture_size = size(DevicePixels(width), DevicePixels(height));
+        self.0.insert(texture_id, (texture_size, pixels));
+        AtlasTile {
+            texture_id,
+            tile_id: TileId(0),
+            padding: 0,
+            bounds: Bounds::new(point(DevicePixels(0), DevicePixels(0)), texture_size),
+        }
+    }
+}
+
+impl AtlasPixels for TestAtlas {
+    fn texture_pixels(&self, texture_id: AtlasTextureId) -> Option<(Size<DevicePixels>, &[u8])> {
+        let (size, pixels) = self.0.get(&texture_id)?;
+        Some((*size, pixels))
+    }
+}
+
+/// Returns a glyph drawn from `tile` in `color` at `bounds`, masked to the 100×100 square at the
+/// origin.
+pub(crate) fn glyph(
+    tile: AtlasTile,
+    bounds: Bounds<ScaledPixels>,
+    color: Hsla,
+) -> MonochromeSprite {
+    MonochromeSprite {
+        order: 0,
+        distance_field: 0,
+        bounds,
+        content_mask: ContentMask {
+            bounds: self::bounds(0., 0., 100., 100.),
+            ..Default::default()
+        },
+        color,
+        tile,
+        transformation: TransformationMatrix::unit(),
+    }
+}
+
+/// Returns an opaque image drawn from `tile` at `bounds`, masked to the 100×100 square at the
+/// origin.
+pub(crate) fn image(tile: AtlasTile, bounds: Bounds<ScaledPixels>) -> PolychromeSprite {
+    PolychromeSprite {
+        order: 0,
+        nearest: 0,
+        grayscale: false,
+        opacity: 1.,
+        bounds,
+        content_mask: ContentMask {
+            bounds: self::bounds(0., 0., 100., 100.),
+            ..Default::default()
+        },
+        corner_radii: Default::default(),
+        tile,
+        transformation: TransformationMatrix::unit(),
+    }
+}
@@ -0,0 +1,757 @@
+#ifndef GPUI_CORE_H
+#define GPUI_CORE_H
+
+/* Generated by cbindgen from gpui_core's `ffi` module; don't edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum AtlasTextureKind {
+  AtlasTextureKind_Monochrome = 0,
+  AtlasTextureKind_Polychrome = 1,
+} AtlasTextureKind;
+
+/**
+ * The kind of primitives in a [`Batch`].
+ */
+typedef enum BatchKind {
+  /**
+   * [`crate::Shadow`]s.
+   */
+  BatchKind_Shadows,
+  /**
+   * [`Quad`]s.
+   */
+  BatchKind_Quads,
+  /**
+   * [`crate::Underline`]s.
+   */
+  BatchKind_Underlines,
+  /**
+   * [`MonochromeSprite`]s, including glyphs.
+   */
+  BatchKind_MonochromeSprites,
+  /**
+   * [`crate::PolychromeSprite`]s.
+   */
+  BatchKind_PolychromeSprites,
+} BatchKind;
+
+/**
+ * The style of a border.
+ */
+typedef enum BorderStyle {
+  /**
+   * A solid border.
+   */
+  BorderStyle_Solid = 0,
+  /**
+   * A dashed border.
+   */
+  BorderStyle_Dashed = 1,
+  /**
+   * A dotted border, of round dots as wide as the border.
+   */
+  BorderStyle_Dotted = 2,
+} BorderStyle;
+
+typedef enum BackgroundTag {
+  /**
+   * Solid color
+   */
+  BackgroundTag_Solid = 0,
+  /**
+   * Linear gradient
+   */
+  BackgroundTag_LinearGradient = 1,
+  /**
+   * Slash pattern
+   */
+  BackgroundTag_PatternSlash = 2,
+} BackgroundTag;
+
+/**
+ * A color space for color interpolation.
+ *
+ * References:
+ * - <https://developer.mozilla.org/en-US/docs/Web/CSS/color-interpolation-method>
+ * - <https://www.w3.org/TR/css-color-4/#typedef-color-space>
+ */
+typedef enum ColorSpace {
+  /**
+   * The sRGB color space.
+   */
+  ColorSpace_Srgb = 0,
+  /**
+   * The Oklab color space.
+   */
+  ColorSpace_Oklab = 1,
+} ColorSpace;
+
+/**
+ * The primitives painted into a window during a frame, sorted into batches for the renderer.
+ *
+ * Wherever primitives overlap, the one painted last is drawn on top, regardless of its kind.
+ * Each primitive is drawn according to the key `(order, kind)`:
+ *
+ * - Outside a layer, a primitive's order is one greater than the order of any primitive painted
+ *   before it whose painted bounds overlap its own. Primitives that don't overlap share orders,
+ *   so that primitives of the same kind are drawn in large batches.
+ * - Inside a layer, primitives share the layer's order, so that they batch together even where
+ *   they overlap. The layer's order is raised whenever a primitive's kind would otherwise draw
+ *   it beneath a primitive painted before it in the layer.
+ *
+ * Stacking elements above others, as deferred draws do, is a matter of painting them later.
+ *
+ * The scene is also divided into [`SceneLayer`]s, each drawn entirely above the ones before it.
+ * Orders restart above the previous layer's, so an overlay's primitives never interleave with
+ * the base content's batches, however much they overlap it.
+ */
+typedef struct Scene Scene;
+
+/**
+ * Represents scaled pixels that take into account the device's scale factor.
+ *
+ * `ScaledPixels` are used to ensure that UI elements appear at the correct size on devices
+ * with different pixel densities. When a device has a higher scale factor (such as Retina displays),
+ * a single logical pixel may correspond to multiple physical pixels. By using `ScaledPixels`,
+ * dimensions and positions can be specified in a way that scales appropriately across different
+ * display resolutions.
+ */
+typedef float ScaledPixels;
+
+/**
+ * Describes a location in a 2D cartesian space.
+ *
+ * It holds two public fields, `x` and `y`, which represent the coordinates in the space.
+ * The type `T` for the coordinates can be any type that implements `Default`, `Clone`, and `Debug`.
+ *
+ * # Examples
+ *
+ * ```
+ * # use gpui_core::Point;
+ * let point = Point { x: 10, y: 20 };
+ * println!("{:?}", point); // Outputs: Point { x: 10, y: 20 }
+ * ```
+ */
+typedef struct Point_ScaledPixels {
+  /**
+   * The x coordinate of the point.
+   */
+  ScaledPixels x;
+  /**
+   * The y coordinate of the point.
+   */
+  ScaledPixels y;
+} Point_ScaledPixels;
+
+/**
+ * A structure representing a two-dimensional size with width and height in a given unit.
+ *
+ * This struct is generic over the type `T`, which can be any type that implements `Clone`, `Default`, and `Debug`.
+ * It is commonly used to specify dimensions for elements in a UI, such as a window or element.
+ */
+typedef struct Size_ScaledPixels {
+  /**
+   * The width component of the size.
+   */
+  ScaledPixels width;
+  /**
+   * The height component of the size.
+   */
+  ScaledPixels height;
+} Size_ScaledPixels;
+
+/**
+ * Represents a rectangular area in a 2D space with an origin point and a size.
+ *
+ * The `Bounds` struct is generic over a type `T` which represents the type of the coordinate system.
+ * The origin is represented as a `Point<T>` which defines the top left corner of the rectangle,
+ * and the size is represented as a `Size<T>` which defines the width and height of the rectangle.
+ *
+ * # Examples
+ *
+ * ```
+ * # use gpui_core::{Bounds, Point, Size};
+ * let origin = Point { x: 0, y: 0 };
+ * let size = Size { width: 10, height: 20 };
+ * let bounds = Bounds::new(origin, size);
+ *
+ * assert_eq!(bounds.origin, origin);
+ * assert_eq!(bounds.size, size);
+ * ```
+ */
+typedef struct Bounds_ScaledPixels {
+  /**
+   * The origin point of this area.
+   */
+  struct Point_ScaledPixels origin;
+  /**
+   * The size of the rectangle.
+   */
+  struct Size_ScaledPixels size;
+} Bounds_ScaledPixels;
+
+/**
+ * An RGBA color
+ */
+typedef struct Rgba {
+  /**
+   * The red component of the color, in the range 0.0 to 1.0
+   */
+  float r;
+  /**
+   * The green component of the color, in the range 0.0 to 1.0
+   */
+  float g;
+  /**
+   * The blue component of the color, in the range 0.0 to 1.0
+   */
+  float b;
+  /**
+   * The alpha component of the color, in the range 0.0 to 1.0
+   */
+  float a;
+} Rgba;
+
+/**
+ * A rectangle with a solid background and border.
+ */
+typedef struct QuadDesc {
+  /**
+   * The bounds of the quad.
+   */
+  struct Bounds_ScaledPixels bounds;
+  /**
+   * The bounds the quad is clipped to.
+   */
+  struct Bounds_ScaledPixels clip;
+  /**
+   * The color of the quad's background.
+   */
+  struct Rgba background;
+  /**
+   * The color of the quad's border.
+   */
+  struct Rgba border_color;
+  /**
+   * The radius of every corner.
+   */
+  ScaledPixels corner_radius;
+  /**
+   * The width of every side of the border.
+   */
+  ScaledPixels border_width;
+} QuadDesc;
+
+typedef struct AtlasTextureId {
+  uint32_t index;
+  enum AtlasTextureKind kind;
+} AtlasTextureId;
+
+typedef struct TileId {
+  uint32_t _0;
+} TileId;
+
+/**
+ * Represents physical pixels on the display.
+ *
+ * `DevicePixels` is a unit of measurement that refers to the actual pixels on a device's screen.
+ * This type is used when precise pixel manipulation is required, such as rendering graphics or
+ * interfacing with hardware that operates on the pixel level. Unlike logical pixels that may be
+ * affected by the device's scale factor, `DevicePixels` always correspond to real pixels on the
+ * display.
+ */
+typedef int32_t DevicePixels;
+
+/**
+ * Describes a location in a 2D cartesian space.
+ *
+ * It holds two public fields, `x` and `y`, which represent the coordinates in the space.
+ * The type `T` for the coordinates can be any type that implements `Default`, `Clone`, and `Debug`.
+ *
+ * # Examples
+ *
+ * ```
+ * # use gpui_core::Point;
+ * let point = Point { x: 10, y: 20 };
+ * println!("{:?}", point); // Outputs: Point { x: 10, y: 20 }
+ * ```
+ */
+typedef struct Point_DevicePixels {
+  /**
+   * The x coordinate of the point.
+   */
+  DevicePixels x;
+  /**
+   * The y coordinate of the point.
+   */
+  DevicePixels y;
+} Point_DevicePixels;
+
+/**
+ * A structure representing a two-dimensional size with width and height in a given unit.
+ *
+ * This struct is generic over the type `T`, which can be any type that implements `Clone`, `Default`, and `Debug`.
+ * It is commonly used to specify dimensions for elements in a UI, such as a window or element.
+ */
+typedef struct Size_DevicePixels {
+  /**
+   * The width component of the size.
+   */
+  DevicePixels width;
+  /**
+   * The height component of the size.
+   */
+  DevicePixels height;
+} Size_DevicePixels;
+
+/**
+ * Represents a rectangular area in a 2D space with an origin point and a size.
+ *
+ * The `Bounds` struct is generic over a type `T` which represents the type of the coordinate system.
+ * The origin is represented as a `Point<T>` which defines the top left corner of the rectangle,
+ * and the size is represented as a `Size<T>` which defines the width and height of the rectangle.
+ *
+ * # Examples
+ *
+ * ```
+ * # use gpui_core::{Bounds, Point, Size};
+ * let origin = Point { x: 0, y: 0 };
+ * let size = Size { width: 10, height: 20 };
+ * let bounds = Bounds::new(origin, size);
+ *
+ * assert_eq!(bounds.origin, origin);
+ * assert_eq!(bounds.size, size);
+ * ```
+ */
+typedef struct Bounds_DevicePixels {
+  /**
+   * The origin point of this area.
+   */
+  struct Point_DevicePixels origin;
+  /**
+   * The size of the rectangle.
+   */
+  struct Size_DevicePixels size;
+} Bounds_DevicePixels;
+
+typedef struct AtlasTile {
+  struct AtlasTextureId texture_id;
+  struct TileId tile_id;
+  uint32_t padding;
+  struct Bounds_DevicePixels bounds;
+} AtlasTile;
+
+/**
+ * A glyph drawn from a monochrome atlas tile.
+ */
+typedef struct GlyphDesc {
+  /**
+   * The bounds of the glyph.
+   */
+  struct Bounds_ScaledPixels bounds;
+  /**
+   * The bounds the glyph is clipped to.
+   */
+  struct Bounds_ScaledPixels clip;
+  /**
+   * The color the glyph is drawn in.
+   */
+  struct Rgba color;
+  /**
+   * The tile that holds the glyph's coverage.
+   */
+  struct AtlasTile tile;
+} GlyphDesc;
+
+/**
+ * A run of primitives of one kind that can be drawn together.
+ */
+typedef struct Batch {
+  /**
+   * The kind of the primitives.
+   */
+  enum BatchKind kind;
+  /**
+   * The index of the atlas texture that the tiles of sprite batches are in.
+   */
+  uint32_t texture_index;
+  /**
+   * The first primitive, whose type depends on `kind`.
+   */
+  const void *primitives;
+  /**
+   * The number of primitives.
+   */
+  size_t len;
+} Batch;
+
+/**
+ * The position of a primitive in the sequence in which the scene is drawn. Primitives are drawn
+ * in ascending order, and primitives with the same order are drawn by [`PrimitiveKind`].
+ */
+typedef uint32_t DrawOrder;
+
+/**
+ * Represents the corners of a box in a 2D space, such as border radius.
+ *
+ * Each field represents the size of the corner on one side of the box: `top_left`, `top_right`, `bottom_right`, and `bottom_left`.
+ */
+typedef struct Corners_ScaledPixels {
+  /**
+   * The value associated with the top left corner.
+   */
+  ScaledPixels top_left;
+  /**
+   * The value associated with the top right corner.
+   */
+  ScaledPixels top_right;
+  /**
+   * The value associated with the bottom right corner.
+   */
+  ScaledPixels bottom_right;
+  /**
+   * The value associated with the bottom left corner.
+   */
+  ScaledPixels bottom_left;
+} Corners_ScaledPixels;
+
+/**
+ * Indicates which region of the window is visible. Content falling outside of this mask will not be
+ * rendered. The region is a rectangle, optionally with rounded corners.
+ */
+typedef struct ContentMask_ScaledPixels {
+  /**
+   * The bounds
+   */
+  struct Bounds_ScaledPixels bounds;
+  /**
+   * The radii of the mask's corners. Content outside a rounded corner is clipped with
+   * antialiasing.
+   */
+  struct Corners_ScaledPixels corner_radii;
+} ContentMask_ScaledPixels;
+
+/**
+ * An HSLA color
+ */
+typedef struct Hsla {
+  /**
+   * Hue, in a range from 0 to 1
+   */
+  float h;
+  /**
+   * Saturation, in a range from 0 to 1
+   */
+  float s;
+  /**
+   * Lightness, in a range from 0 to 1
+   */
+  float l;
+  /**
+   * Alpha, in a range from 0 to 1
+   */
+  float a;
+} Hsla;
+
+/**
+ * A data type representing a 2 dimensional transformation that can be applied to an element.
+ */
+typedef struct TransformationMatrix {
+  /**
+   * 2x2 matrix containing rotation and scale,
+   * stored row-major
+   */
+  float rotation_scale[2][2];
+  /**
+   * translation vector
+   */
+  float translation[2];
+} TransformationMatrix;
+
+typedef struct Shadow {
+  DrawOrder order;
+  ScaledPixels blur_radius;
+  struct Bounds_ScaledPixels bounds;
+  struct Corners_ScaledPixels corner_radii;
+  struct ContentMask_ScaledPixels content_mask;
+  struct Hsla color;
+  struct TransformationMatrix transformation;
+} Shadow;
+
+/**
+ * A color stop in a linear gradient.
+ *
+ * <https://developer.mozilla.org/en-US/docs/Web/CSS/gradient/linear-gradient#linear-color-stop>
+ */
+typedef struct LinearColorStop {
+  /**
+   * The color of the color stop.
+   */
+  struct Hsla color;
+  /**
+   * The percentage of the gradient, in the range 0.0 to 1.0.
+   */
+  float percentage;
+} LinearColorStop;
+
+/**
+ * A background color, which can be either a solid color or a linear gradient.
+ */
+typedef struct Background {
+  /**
+   * The type of background.
+   */
+  enum BackgroundTag tag;
+  /**
+   * The color space for interpolation.
+   */
+  enum ColorSpace color_space;
+  /**
+   * The solid color (used for solid backgrounds and pattern base color).
+   */
+  struct Hsla solid;
+  /**
+   * The gradient angle in degrees, or pattern height for slash patterns.
+   */
+  float gradient_angle_or_pattern_height;
+  /**
+   * The gradient color stops.
+   */
+  struct LinearColorStop colors[2];
+  /**
+   * Padding for alignment for repr(C) layout.
+   */
+  uint32_t pad;
+} Background;
+
+/**
+ * Represents the edges of a box in a 2D space, such as padding or margin.
+ *
+ * Each field represents the size of the edge on one side of the box: `top`, `right`, `bottom`, and `left`.
+ *
+ * # Examples
+ *
+ * ```
+ * # use gpui_core::Edges;
+ * let edges = Edges {
+ *     top: 10.0,
+ *     right: 20.0,
+ *     bottom: 30.0,
+ *     left: 40.0,
+ * };
+ *
+ * assert_eq!(edges.top, 10.0);
+ * assert_eq!(edges.right, 20.0);
+ * assert_eq!(edges.bottom, 30.0);
+ * assert_eq!(edges.left, 40.0);
+ * ```
+ */
+typedef struct Edges_ScaledPixels {
+  /**
+   * The size of the top edge.
+   */
+  ScaledPixels top;
+  /**
+   * The size of the right edge.
+   */
+  ScaledPixels right;
+  /**
+   * The size of the bottom edge.
+   */
+  ScaledPixels bottom;
+  /**
+   * The size of the left edge.
+   */
+  ScaledPixels left;
+} Edges_ScaledPixels;
+
+/**
+ * A shadow drawn beneath a [`Quad`] by the quad's own draw call, so that decorating an element
+ * with a background and a shadow doesn't switch between pipelines. The shadow is cast by a
+ * rounded rectangle with the quad's corner radii. It isn't drawn if its color is transparent.
+ */
+typedef struct QuadShadow {
+  struct Bounds_ScaledPixels bounds;
+  struct Hsla color;
+  ScaledPixels blur_radius;
+  uint32_t pad;
+} QuadShadow;
+
+typedef struct Quad {
+  DrawOrder order;
+  enum BorderStyle border_style;
+  struct Bounds_ScaledPixels bounds;
+  struct ContentMask_ScaledPixels content_mask;
+  struct Background background;
+  struct Hsla border_color;
+  struct Corners_ScaledPixels corner_radii;
+  /**
+   * The vertical radii of elliptical corners, whose horizontal radii are `corner_radii`. A
+   * zero radius makes the corner circular, so quads with circular corners leave this unset.
+   */
+  struct Corners_ScaledPixels vertical_corner_radii;
+  struct Edges_ScaledPixels border_widths;
+  struct QuadShadow shadow;
+  struct TransformationMatrix transformation;
+  /**
+   * How many dash periods per second the dashes of a dashed or dotted border move clockwise
+   * around the quad, which the renderers animate from the frame time. Moving dashes are laid
+   * out around the whole perimeter, even when the corners are square.
+   */
+  float dash_speed;
+  uint32_t pad;
+} Quad;
+
+typedef struct Underline {
+  DrawOrder order;
+  uint32_t pad;
+  struct Bounds_ScaledPixels bounds;
+  struct ContentMask_ScaledPixels content_mask;
+  struct Hsla color;
+  ScaledPixels thickness;
+  uint32_t wavy;
+  /**
+   * How far a wavy underline's center line strays from the middle of its bounds.
+   */
+  ScaledPixels wave_amplitude;
+  /**
+   * The length of one period of a wavy underline.
+   */
+  ScaledPixels wavelength;
+  struct TransformationMatrix transformation;
+} Underline;
+
+typedef struct MonochromeSprite {
+  DrawOrder order;
+  /**
+   * Zero when the tile holds the coverage of a glyph, which is corrected for contrast and
+   * gamma, and [`MonochromeSprite::LINEAR_COVERAGE`] when the tile holds coverage that is used
+   * as is. Otherwise the tile holds a signed distance field, and this packs the value of its
+   * edge into the low 16 bits and the width of the edge's falloff into the high 16 bits, both
+   * scaled so that `u16::MAX` is 1.
+   */
+  uint32_t distance_field;
+  struct Bounds_ScaledPixels bounds;
+  struct ContentMask_ScaledPixels content_mask;
+  struct Hsla color;
+  struct AtlasTile tile;
+  struct TransformationMatrix transformation;
+} MonochromeSprite;
+/**
+ * The [`MonochromeSprite::distance_field`] of sprites whose tile holds coverage that is used
+ * without correcting it for contrast and gamma.
+ */
+#define MonochromeSprite_LINEAR_COVERAGE UINT32_MAX
+
+typedef struct PolychromeSprite {
+  DrawOrder order;
+  /**
+   * Nonzero to sample the texel nearest each pixel, rather than filtering linearly.
+   */
+  uint32_t nearest;
+  bool grayscale;
+  float opacity;
+  struct Bounds_ScaledPixels bounds;
+  struct ContentMask_ScaledPixels content_mask;
+  struct Corners_ScaledPixels corner_radii;
+  struct AtlasTile tile;
+  struct TransformationMatrix transformation;
+} PolychromeSprite;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an empty scene.
+ */
+struct Scene *gpui_scene_new(void);
+
+/**
+ * Frees a scene created with [`gpui_scene_new`].
+ *
+ * # Safety
+ *
+ * `scene` must be null or a scene created with [`gpui_scene_new`] that hasn't been freed.
+ */
+void gpui_scene_free(struct Scene *scene);
+
+/**
+ * Removes every primitive from a scene, so that it can be reused for the next frame.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`].
+ */
+void gpui_scene_clear(struct Scene *scene);
+
+/**
+ * Starts a layer: primitives pushed until the matching [`gpui_scene_pop_layer`] batch
+ * together even where they overlap.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`].
+ */
+void gpui_scene_push_layer(struct Scene *scene, struct Bounds_ScaledPixels bounds);
+
+/**
+ * Ends the layer started by the last [`gpui_scene_push_layer`].
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`].
+ */
+void gpui_scene_pop_layer(struct Scene *scene);
+
+/**
+ * Paints a quad above the primitives pushed before it.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`], and `quad` must be null
+ * or point to a valid [`QuadDesc`].
+ */
+void gpui_scene_push_quad(struct Scene *scene, const struct QuadDesc *quad);
+
+/**
+ * Paints a glyph above the primitives pushed before it.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`], and `glyph` must be null
+ * or point to a valid [`GlyphDesc`].
+ */
+void gpui_scene_push_glyph(struct Scene *scene, const struct GlyphDesc *glyph);
+
+/**
+ * Sorts the scene's primitives into draw order. Call this after pushing a frame's primitives
+ * and before reading its batches.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`].
+ */
+void gpui_scene_finish(struct Scene *scene);
+
+/**
+ * Writes up to `capacity` of the scene's batches, in draw order, to `batches`, and returns the
+ * total number of batches. Call it with a `capacity` of zero to size the buffer.
+ *
+ * The batches point into the scene, and are invalidated when it's next modified or freed.
+ *
+ * # Safety
+ *
+ * `scene` must be null or a live scene created with [`gpui_scene_new`], and `batches` must be
+ * valid for writing `capacity` batches.
+ */
+size_t gpui_scene_batches(const struct Scene *scene, struct Batch *batches, size_t capacity);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GPUI_CORE_H */
@@ -0,0 +1,5 @@
+//! Builds [`gpui_core::ffi`] as shared and static libraries for C callers, whose declarations
+//! are in `include/gpui_core.h`. Regenerate the header with `cbindgen` from this crate's
+//! directory after changing the ABI.
+
+pub use gpui_core::ffi::*;
@@ -0,0 +1,602 @@
+use std::sync::Arc;
+
+/// The CLDR plural category of a quantity, used to pick the grammatically
+/// correct form of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RelativeTimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+/// A BCP 47 language tag, such as `en-US` or `pt-BR`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tag: Arc<str>,
+}
+
+impl Locale {
+    /// Parses a language tag. POSIX-style identifiers such as `de_DE.UTF-8` are
+    /// normalized to `de-DE`.
+    pub fn new(tag: &str) -> Self {
+        let tag = tag
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .replace('_', "-");
+        if tag.is_empty() || tag == "C" || tag == "POSIX" {
+            return Self::en_us();
+        }
+        Self { tag: tag.into() }
+    }
+
+    pub fn en_us() -> Self {
+        Self {
+            tag: "en-US".into(),
+        }
+    }
+
+    /// Reads the locale from the environment, falling back to `en-US`.
+    pub fn system() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map(|value| Self::new(&value))
+            .unwrap_or_else(Self::en_us)
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The primary language subtag, lowercased (e.g. `pt` for `pt-BR`).
+    pub fn language(&self) -> String {
+        self.tag
+            .split('-')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    }
+
+    /// The region subtag, uppercased (e.g. `BR` for `pt-BR`), if present.
+    pub fn region(&self) -> Option<String> {
+        self.tag
+            .split('-')
+            .skip(1)
+            .find(|subtag| {
+                (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|subtag| subtag.to_ascii_uppercase())
+    }
+
+    /// Returns this locale followed by progressively less specific locales,
+    /// e.g. `zh-Hant-TW`, `zh-Hant`, `zh`.
+    pub fn fallback_chain(&self) -> Vec<Locale> {
+        let subtags = self.tag.split('-').collect::<Vec<_>>();
+        (1..=subtags.len())
+            .rev()
+            .map(|len| Locale {
+                tag: subtags[..len].join("-").into(),
+            })
+            .collect()
+    }
+
+    /// Whether this locale matches `other`, ignoring case.
+    pub fn matches(&self, other: &Locale) -> bool {
+        self.tag.eq_ignore_ascii_case(&other.tag)
+    }
+
+    pub fn decimal_separator(&self) -> char {
+        let language = self.language();
+        if language == "de" && self.region().as_deref() == Some("CH") {
+            return '.';
+        }
+        match language.as_str() {
+            "bg" | "ca" | "cs" | "da" | "de" | "el" | "es" | "et" | "fi" | "fr" | "hr" | "hu"
+            | "id" | "it" | "lt" | "lv" | "nb" | "nl" | "nn" | "no" | "pl" | "pt" | "ro" | "ru"
+            | "sk" | "sl" | "sr" | "sv" | "tr" | "uk" | "vi" => ',',
+            _ => '.',
+        }
+    }
+
+    pub fn grouping_separator(&self) -> char {
+        let language = self.language();
+        if language == "de" && self.region().as_deref() == Some("CH") {
+            return '\u{2019}';
+        }
+        match language.as_str() {
+            "ca" | "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl"
+            | "sr" | "tr" | "vi" => '.',
+            "bg" | "cs" | "et" | "fi" | "fr" | "hu" | "lt" | "lv" | "nb" | "nn" | "no" | "pl"
+            | "ru" | "sk" | "sv" | "uk" => '\u{a0}',
+            _ => ',',
+        }
+    }
+
+    /// Formats `value` with exactly `fraction_digits` digits after the decimal
+    /// separator, grouping the integer digits in threes.
+    pub fn format_decimal(&self, value: f64, fraction_digits: usize) -> String {
+        if value.is_nan() {
+            return "NaN".into();
+        }
+        if value.is_infinite() {
+            return if value < 0. {
+                "-∞".into()
+            } else {
+                "∞".into()
+            };
+        }
+
+        let formatted = format!("{:.*}", fraction_digits, value.abs());
+        let (integer, fraction) = formatted
+            .split_once('.')
+            .unwrap_or((formatted.as_str(), ""));
+
+        let mut result = String::with_capacity(formatted.len() + integer.len() / 3 + 1);
+        if value < 0. && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        let grouping_separator = self.grouping_separator();
+        for (ix, digit) in integer.chars().enumerate() {
+            if ix > 0 && (integer.len() - ix) % 3 == 0 {
+                result.push(grouping_separator);
+            }
+            result.push(digit);
+        }
+        if !fraction.is_empty() {
+            result.push(self.decimal_separator());
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Returns the cardinal plural category of `count` in this locale's language.
+    pub fn plural_category(&self, count: f64) -> PluralCategory {
+        let count = count.abs();
+        let is_integer = count.fract() == 0.;
+        let n = count as u64;
+        let (mod10, mod100) = (n % 10, n % 100);
+        match self.language().as_str() {
+            "id" | "ja" | "km" | "ko" | "lo" | "ms" | "my" | "th" | "vi" | "zh" => {
+                PluralCategory::Other
+            }
+            "fr" | "hi" | "pt" if n <= 1 => PluralCategory::One,
+            "fr" | "hi" | "pt" => PluralCategory::Other,
+            "be" | "bs" | "hr" | "ru" | "sr" | "uk" if is_integer => {
+                if mod10 == 1 && mod100 != 11 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            }
+            "pl" if is_integer => {
+                if n == 1 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            }
+            "cs" | "sk" if is_integer => match n {
+                1 => PluralCategory::One,
+                2..=4 => PluralCategory::Few,
+                _ => PluralCategory::Other,
+            },
+            "ar" if is_integer => match (n, mod100) {
+                (0, _) => PluralCategory::Zero,
+                (1, _) => PluralCategory::One,
+                (2, _) => PluralCategory::Two,
+                (_, 3..=10) => PluralCategory::Few,
+                (_, 11..=99) => PluralCategory::Many,
+                _ => PluralCategory::Other,
+            },
+            _ if is_integer && n == 1 => PluralCategory::One,
+            _ => PluralCategory::Other,
+        }
+    }
+
+    /// Formats `value` with up to three fraction digits, omitting trailing zeros.
+    pub fn format_number(&self, value: f64) -> String {
+        if value.fract() == 0. {
+            return self.format_decimal(value, 0);
+        }
+        let formatted = self.format_decimal(value, 3);
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches(self.decimal_separator())
+            .to_string()
+    }
+
+    /// Formats a byte count using binary multiples, e.g. `1.5 MB` or `1,5 Mo`.
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+        const FRENCH_UNITS: [&str; 5] = ["o", "ko", "Mo", "Go", "To"];
+
+        let units = if self.language() == "fr" {
+            FRENCH_UNITS
+        } else {
+            UNITS
+        };
+        let mut value = bytes as f64;
+        let mut unit_ix = 0;
+        while value >= 1024. && unit_ix < units.len() - 1 {
+            value /= 1024.;
+            unit_ix += 1;
+        }
+
+        let fraction_digits = if unit_ix == 0 || value >= 100. { 0 } else { 1 };
+        let formatted = self.format_decimal(value, fraction_digits);
+        let decimal_zero = format!("{}0", self.decimal_separator());
+        let formatted = formatted
+            .strip_suffix(decimal_zero.as_str())
+            .unwrap_or(&formatted);
+        format!("{formatted}\u{a0}{}", units[unit_ix])
+    }
+
+    /// Describes a time offset relative to now, e.g. `3 minutes ago` for `-180`
+    /// or `in 2 days` for `172800`.
+    pub fn format_relative_time(&self, seconds: i64) -> String {
+        let magnitude = seconds.unsigned_abs();
+        let (count, unit) = if magnitude < 45 {
+            (magnitude, RelativeTimeUnit::Second)
+        } else if magnitude < 45 * 60 {
+            ((magnitude + 30) / 60, RelativeTimeUnit::Minute)
+        } else if magnitude < 22 * 3600 {
+            ((magnitude + 1800) / 3600, RelativeTimeUnit::Hour)
+        } else if magnitude < 26 * 86_400 {
+            ((magnitude + 43_200) / 86_400, RelativeTimeUnit::Day)
+        } else if magnitude < 320 * 86_400 {
+            (
+                (magnitude + 15 * 86_400) / (30 * 86_400),
+                RelativeTimeUnit::Month,
+            )
+        } else {
+            (
+                ((magnitude + 182 * 86_400) / (365 * 86_400)).max(1),
+                RelativeTimeUnit::Year,
+            )
+        };
+        let is_past = seconds < 0;
+
+        let language = self.language();
+        if unit == RelativeTimeUnit::Second && count < 10 {
+            return match language.as_str() {
+                "de" => "jetzt",
+                "es" => "ahora",
+                "fr" => "maintenant",
+                "ja" => "今",
+                "zh" => "现在",
+                _ => "now",
+            }
+            .to_string();
+        }
+
+        let one = self.plural_category(count as f64) == PluralCategory::One;
+        let number = self.format_number(count as f64);
+        match language.as_str() {
+            "de" => {
+                let unit = match (unit, one) {
+                    (RelativeTimeUnit::Second, true) => "Sekunde",
+                    (RelativeTimeUnit::Second, false) => "Sekunden",
+                    (RelativeTimeUnit::Minute, true) => "Minute",
+                    (RelativeTimeUnit::Minute, false) => "Minuten",
+                    (RelativeTimeUnit::Hour, true) => "Stunde",
+                    (RelativeTimeUnit::Hour, false) => "Stunden",
+                    (RelativeTimeUnit::Day, true) => "Tag",
+                    (RelativeTimeUnit::Day, false) => "Tagen",
+                    (RelativeTimeUnit::Month, true) => "Monat",
+                    (RelativeTimeUnit::Month, false) => "Monaten",
+                    (RelativeTimeUnit::Year, true) => "Jahr",
+                    (RelativeTimeUnit::Year, false) => "Jahren",
+                };
+                if is_past {
+                    format!("vor {number} {unit}")
+                } else {
+                    format!("in {number} {unit}")
+                }
+            }
+            "es" => {
+                let unit = match (unit, one) {
+                    (RelativeTimeUnit::Second, true) => "segundo",
+                    (RelativeTimeUnit::Second, false) => "segundos",
+                    (RelativeTimeUnit::Minute, true) => "minuto",
+                    (RelativeTimeUnit::Minute, false) => "minutos",
+                    (RelativeTimeUnit::Hour, true) => "hora",
+                    (RelativeTimeUnit::Hour, false) => "horas",
+                    (RelativeTimeUnit::Day, true) => "día",
+                    (RelativeTimeUnit::Day, false) => "días",
+                    (RelativeTimeUnit::Month, true) => "mes",
+                    (RelativeTimeUnit::Month, false) => "meses",
+                    (RelativeTimeUnit::Year, true) => "año",
+                    (RelativeTimeUnit::Year, false) => "años",
+                };
+                if is_past {
+                    format!("hace {number} {unit}")
+                } else {
+                    format!("dentro de {number} {unit}")
+                }
+            }
+            "fr" => {
+                let unit = match (unit, one) {
+                    (RelativeTimeUnit::Second, true) => "seconde",
+                    (RelativeTimeUnit::Second, false) => "secondes",
+                    (RelativeTimeUnit::Minute, true) => "minute",
+                    (RelativeTimeUnit::Minute, false) => "minutes",
+                    (RelativeTimeUnit::Hour, true) => "heure",
+                    (RelativeTimeUnit::Hour, false) => "heures",
+                    (RelativeTimeUnit::Day, true) => "jour",
+                    (RelativeTimeUnit::Day, false) => "jours",
+                    (RelativeTimeUnit::Month, _) => "mois",
+                    (RelativeTimeUnit::Year, true) => "an",
+                    (RelativeTimeUnit::Year, false) => "ans",
+                };
+                if is_past {
+                    format!("il y a {number} {unit}")
+                } else {
+                    format!("dans {number} {unit}")
+                }
+            }
+            "ja" => {
+                let unit = match unit {
+                    RelativeTimeUnit::Second => "秒",
+                    RelativeTimeUnit::Minute => "分",
+                    RelativeTimeUnit::Hour => "時間",
+                    RelativeTimeUnit::Day => "日",
+                    RelativeTimeUnit::Month => "か月",
+                    RelativeTimeUnit::Year => "年",
+                };
+                let direction = if is_past { "前" } else { "後" };
+                format!("{number} {unit}{direction}")
+            }
+            "zh" => {
+                let unit = match unit {
+                    RelativeTimeUnit::Second => "秒钟",
+                    RelativeTimeUnit::Minute => "分钟",
+                    RelativeTimeUnit::Hour => "小时",
+                    RelativeTimeUnit::Day => "天",
+                    RelativeTimeUnit::Month => "个月",
+                    RelativeTimeUnit::Year => "年",
+                };
+                let direction = if is_past { "前" } else { "后" };
+                format!("{number}{unit}{direction}")
+            }
+            _ => {
+                let unit = match unit {
+                    RelativeTimeUnit::Second => "second",
+                    RelativeTimeUnit::Minute => "minute",
+                    RelativeTimeUnit::Hour => "hour",
+                    RelativeTimeUnit::Day => "day",
+                    RelativeTimeUnit::Month => "month",
+                    RelativeTimeUnit::Year => "year",
+                };
+                let suffix = if one { "" } else { "s" };
+                if is_past {
+                    format!("{number} {unit}{suffix} ago")
+                } else {
+                    format!("in {number} {unit}{suffix}")
+                }
+            }
+        }
+    }
+
+    /// Formats a calendar date using the locale's short numeric date pattern.
+    pub fn format_date(&self, date: Date) -> String {
+        let Date { year, month, day } = date;
+        match self.language().as_str() {
+            "en" if matches!(self.region().as_deref(), None | Some("US")) => {
+                format!("{month}/{day}/{year}")
+            }
+            "ja" | "zh" => format!("{year}/{month:02}/{day:02}"),
+            "ko" => format!("{year}. {month}. {day}."),
+            "sv" | "lt" => format!("{year}-{month:02}-{day:02}"),
+            "hu" => format!("{year}. {month:02}. {day:02}."),
+            "cs" | "da" | "de" | "fi" | "nb" | "nn" | "no" | "pl" | "ru" | "sk" | "tr" | "uk" => {
+                format!("{day:02}.{month:02}.{year}")
+            }
+            "nl" => format!("{day:02}-{month:02}-{year}"),
+            _ => format!("{day:02}/{month:02}/{year}"),
+        }
+    }
+}
+
+impl From<&str> for Locale {
+    fn from(tag: &str) -> Self {
+        Self::new(tag)
+    }
+}
+
+impl From<String> for Locale {
+    fn from(tag: String) -> Self {
+        Self::new(&tag)
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+/// A proleptic Gregorian calendar date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    /// 1-based month of the year.
+    pub month: u8,
+    /// 1-based day of the month.
+    pub day: u8,
+}
+
+impl Date {
+    /// Converts seconds since the Unix epoch into a UTC calendar date.
+    pub fn from_unix_timestamp(seconds: i64) -> Self {
+        // Howard Hinnant's `civil_from_days` algorithm.
+        let days = seconds.div_euclid(86_400);
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+        let month = (if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        }) as u8;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_parsing() {
+        let locale = Locale::new("pt_BR.UTF-8");
+        assert_eq!(locale.tag(), "pt-BR");
+        assert_eq!(locale.language(), "pt");
+        assert_eq!(locale.region().as_deref(), Some("BR"));
+        assert_eq!(Locale::new("C"), Locale::en_us());
+        assert_eq!(
+            Locale::new("zh-Hant-TW").fallback_chain(),
+            vec![
+                Locale::new("zh-Hant-TW"),
+                Locale::new("zh-Hant"),
+                Locale::new("zh")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(
+            Locale::en_us().format_decimal(1234567.891, 2),
+            "1,234,567.89"
+        );
+        assert_eq!(Locale::new("de-DE").format_decimal(-1234.5, 1), "-1.234,5");
+        assert_eq!(
+            Locale::new("fr-FR").format_decimal(12345., 0),
+            "12\u{a0}345"
+        );
+        assert_eq!(Locale::en_us().format_decimal(-0.001, 1), "0.0");
+        assert_eq!(Locale::en_us().format_decimal(999., 0), "999");
+    }
+
+    #[test]
+    fn test_plural_category() {
+        let english = Locale::en_us();
+        assert_eq!(english.plural_category(1.), PluralCategory::One);
+        assert_eq!(english.plural_category(0.), PluralCategory::Other);
+        assert_eq!(english.plural_category(1.5), PluralCategory::Other);
+
+        let french = Locale::new("fr");
+        assert_eq!(french.plural_category(0.), PluralCategory::One);
+        assert_eq!(french.plural_category(1.5), PluralCategory::One);
+        assert_eq!(french.plural_category(2.), PluralCategory::Other);
+
+        let russian = Locale::new("ru-RU");
+        assert_eq!(russian.plural_category(21.), PluralCategory::One);
+        assert_eq!(russian.plural_category(11.), PluralCategory::Many);
+        assert_eq!(russian.plural_category(23.), PluralCategory::Few);
+        assert_eq!(russian.plural_category(13.), PluralCategory::Many);
+
+        assert_eq!(Locale::new("ja").plural_category(1.), PluralCategory::Other);
+        assert_eq!(Locale::new("ar").plural_category(2.), PluralCategory::Two);
+    }
+
+    #[test]
+    fn test_format_number_and_bytes() {
+        assert_eq!(Locale::en_us().format_number(1234.5), "1,234.5");
+        assert_eq!(Locale::en_us().format_number(2.0), "2");
+        assert_eq!(Locale::new("de").format_number(0.125), "0,125");
+
+        assert_eq!(Locale::en_us().format_bytes(512), "512\u{a0}B");
+        assert_eq!(Locale::en_us().format_bytes(1024), "1\u{a0}KB");
+        assert_eq!(Locale::en_us().format_bytes(1536 * 1024), "1.5\u{a0}MB");
+        assert_eq!(Locale::new("fr").format_bytes(1536 * 1024), "1,5\u{a0}Mo");
+        assert_eq!(
+            Locale::en_us().format_bytes(300 * 1024 * 1024 * 1024),
+            "300\u{a0}GB"
+        );
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let english = Locale::en_us();
+        assert_eq!(english.format_relative_time(-3), "now");
+        assert_eq!(english.format_relative_time(-30), "30 seconds ago");
+        assert_eq!(english.format_relative_time(-60), "1 minute ago");
+        assert_eq!(english.format_relative_time(2 * 86_400), "in 2 days");
+        assert_eq!(english.format_relative_time(-400 * 86_400), "1 year ago");
+        assert_eq!(
+            Locale::new("de").format_relative_time(-2 * 3600),
+            "vor 2 Stunden"
+        );
+        assert_eq!(
+            Locale::new("fr").format_relative_time(-60 * 60 * 24 * 60),
+            "il y a 2 mois"
+        );
+        assert_eq!(Locale::new("ja").format_relative_time(-300), "5 分前");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = Date::from_unix_timestamp(1_709_596_800);
+        assert_eq!(
+            date,
+            Date {
+                year: 2024,
+                month: 3,
+                day: 5
+            }
+        );
+        assert_eq!(Locale::en_us().format_date(date), "3/5/2024");
+        assert_eq!(Locale::new("en-GB").format_date(date), "05/03/2024");
+        assert_eq!(Locale::new("de").format_date(date), "05.03.2024");
+        assert_eq!(Locale::new("ja-JP").format_date(date), "2024/03/05");
+        assert_eq!(
+            Date::from_unix_timestamp(-86_400),
+            Date {
+                year: 1969,
+                month: 12,
+                day: 31
+            }
+        );
+    }
+}
@@ -1,6 +1,7 @@
 pub mod arc_cow;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod command;
+pub mod locale;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod paths;
 #[cfg(not(target_arch = "wasm32"))]
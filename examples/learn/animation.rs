@@ -12,39 +12,20 @@ mod example_prelude;
 
 use std::time::Duration;
 
-use anyhow::Result;
 use gpui::{
-    Animation, AnimationExt as _, App, Application, AssetSource, Colors, Context, Hsla,
-    SharedString, Transformation, Window, WindowBounds, WindowOptions, bounce, centered_bounds, div,
-    ease_in_out, linear, percentage, prelude::*, px, size as gpui_size, svg,
+    Animation, AnimationExt as _, App, Application, Colors, Context, Hsla, Transformation, Window,
+    WindowBounds, WindowOptions, bounce, centered_bounds, div, ease_in_out, linear, percentage,
+    prelude::*, px, size as gpui_size, svg,
 };
 
-struct Assets {}
-
-impl AssetSource for Assets {
-    fn load(&self, path: &str) -> Result<Option<std::borrow::Cow<'static, [u8]>>> {
-        std::fs::read(path)
-            .map(Into::into)
-            .map_err(Into::into)
-            .map(Some)
-    }
-
-    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
-        Ok(std::fs::read_dir(path)?
-            .filter_map(|entry| {
-                Some(SharedString::from(
-                    entry.ok()?.path().to_string_lossy().into_owned(),
-                ))
-            })
-            .collect::<Vec<_>>())
-    }
-}
-
-const ARROW_CIRCLE_SVG: &str = concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/examples/legacy/image/arrow_circle.svg"
+gpui::assets!(
+    #[include = "*.svg"]
+    Assets,
+    "examples/legacy/image"
 );
 
+const ARROW_CIRCLE_SVG: &str = "arrow_circle.svg";
+
 struct AnimationExample;
 
 impl Render for AnimationExample {
@@ -260,19 +241,17 @@ fn section(colors: &Colors, title: &'static str, content: impl IntoElement) -> i
 }
 
 fn main() {
-    Application::new()
-        .with_assets(Assets {})
-        .run(|cx: &mut App| {
-            let bounds = centered_bounds(None, gpui_size(px(500.), px(650.)), cx);
-            cx.open_window(
-                WindowOptions {
-                    window_bounds: Some(WindowBounds::Windowed(bounds)),
-                    ..Default::default()
-                },
-                |_, cx| cx.new(|_| AnimationExample),
-            )
-            .expect("Failed to open window");
+    Application::new().with_assets(Assets).run(|cx: &mut App| {
+        let bounds = centered_bounds(None, gpui_size(px(500.), px(650.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                ..Default::default()
+            },
+            |_, cx| cx.new(|_| AnimationExample),
+        )
+        .expect("Failed to open window");
 
-            example_prelude::init_example(cx, "Animation");
-        });
+        example_prelude::init_example(cx, "Animation");
+    });
 }
@@ -95,7 +95,8 @@ impl BackgroundTaskDemo {
                 this.update(cx, |this, cx| {
                     this.progress = i as u32 + 1;
                     this.result = Some(partial_result);
-                    cx.notify();
+                    // Progress changes faster than it's worth redrawing, so coalesce updates.
+                    cx.notify_throttled(Duration::from_millis(50));
                 })
                 .ok();
             }
@@ -469,6 +469,7 @@ impl Element for TextElement {
                         color: Some(run.color),
                         thickness: px(1.0),
                         wavy: false,
+                        ..Default::default()
                     }),
                     ..run.clone()
                 },
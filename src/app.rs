@@ -6,14 +6,17 @@ use std::{
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
-    sync::{Arc, atomic::Ordering::SeqCst},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering::SeqCst},
+    },
     time::{Duration, Instant},
 };
 
 use anyhow::{Context as _, Result, anyhow};
 use derive_more::{Deref, DerefMut};
 use futures::{
-    Future, FutureExt,
+    Future, FutureExt, StreamExt,
     channel::oneshot,
     future::{LocalBoxFuture, Shared},
 };
@@ -26,7 +29,10 @@ use collections::{FxHashMap, FxHashSet, HashMap, VecDeque};
 pub use context::*;
 pub use entity_map::*;
 use http_client::{HttpClient, Url};
+pub use single_instance::InstanceLaunch;
+use single_instance::SingleInstance;
 use smallvec::SmallVec;
+pub use telemetry::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use test_context::*;
 use util::{ResultExt, debug_panic};
@@ -34,28 +40,36 @@ use util::{ResultExt, debug_panic};
 #[cfg(any(feature = "inspector", debug_assertions))]
 use crate::InspectorElementRegistry;
 use crate::{
-    Action, ActionBuildError, ActionRegistry, Any, AnyView, AnyWindowHandle, AppContext, Asset,
-    AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle, DispatchPhase, DisplayId,
-    EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global, KeyBinding, KeyContext,
-    Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, Point, Priority,
-    PromptBuilder, PromptButton, PromptHandle, PromptLevel, Render, RenderImage,
-    RenderablePromptHandle, Reservation, ScreenCaptureSource, SharedString, SubscriberSet,
-    Subscription, SvgRenderer, Task, TextSystem, Window, WindowAppearance, WindowHandle, WindowId,
-    WindowInvalidator, current_platform,
+    Action, ActionBuildError, ActionRegistry, AnimationClock, Any, AnyView, AnyWindowHandle,
+    AppContext, Asset, AssetSource, BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem,
+    CursorImage, CursorStyle, CustomCursorId, DEFAULT_JANK_BUDGET, DevicePixels, DispatchPhase,
+    DisplayId, EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global, InputLatency, Jank,
+    KeyBinding, KeyContext, KeyValueStore, Keymap, Keystroke, LayoutId, Locale, Localization,
+    LogFilter, MemoryPressureLevel, Menu, MenuItem, MessageArg, MessageCatalog, OwnedMenu,
+    PathPromptOptions, Pixels, Platform, PlatformDisplay, PlatformKeyboardLayout,
+    PlatformKeyboardMapper, Point, Popup, PowerState, Priority, PromptBuilder, PromptButton,
+    PromptHandle, PromptLevel, Render, RenderImage, RenderablePromptHandle, Reservation,
+    SceneRenderTarget, SceneRenderer, SceneRendererFactory, ScreenCaptureSource, SharedString,
+    Size, SubscriberSet, Subscription, SvgRenderer, Task, TextContrast, TextSystem, Window,
+    WindowAppearance, WindowHandle, WindowId, WindowInvalidator, WindowSession, WindowSessions,
+    current_platform,
     default_colors::{Colors, GlobalColors},
-    hash, init_app_menus,
+    forget_crash_window, hash, init_app_menus, install_crash_reporter,
 };
 
 mod async_context;
 mod context;
 mod entity_map;
+mod single_instance;
+mod telemetry;
 #[cfg(any(test, feature = "test-support"))]
 mod test_context;
 
 /// The duration for which futures returned from [Context::on_app_quit] can run before the application fully quits.
 pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(100);
 
+const SYSTEM_IDLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Temporary(?) wrapper around [`RefCell<App>`] to help us debug any double borrows.
 /// Strongly consider removing after stabilization.
 #[doc(hidden)]
@@ -162,6 +176,19 @@ impl Application {
         self
     }
 
+    /// Writes a crash report when the application panics, into a new subdirectory of
+    /// `directory`. Reports hold the panic message and backtrace, the size of each window, and
+    /// the most recent input events, with keys reduced to their modifiers and the kind of key
+    /// pressed. Panics on the main thread also include each window's last rendered frame as an
+    /// SVG. The previously installed panic hook still runs afterwards.
+    ///
+    /// Each frame drawn is copied while crash reports are enabled, and only converted to SVG when
+    /// a report is written.
+    pub fn with_crash_reports(self, directory: impl Into<PathBuf>) -> Self {
+        install_crash_reporter(directory.into());
+        self
+    }
+
     /// Sets the HTTP client for the application.
     pub fn with_http_client(self, http_client: Arc<dyn HttpClient>) -> Self {
         let mut context_lock = self.0.borrow_mut();
@@ -177,6 +204,52 @@ impl Application {
         self
     }
 
+    /// Sets the name that the application's key-value storage is kept under, e.g.
+    /// `"com.example.editor"`, so that it stays the same when the executable is renamed or moved.
+    /// By default, the storage is named after the executable and its path.
+    pub fn with_storage_id(self, storage_id: impl Into<String>) -> Self {
+        self.0.borrow_mut().storage_id = Some(storage_id.into());
+        self
+    }
+
+    /// Ensures that only one instance of the application identified by `app_id` runs at a time.
+    ///
+    /// If an instance is already running, this process's command-line arguments are forwarded to
+    /// it and `None` is returned, in which case the caller should exit. Otherwise, whenever
+    /// another instance is launched later, this instance's windows are raised and `on_launch` is
+    /// called with that instance's arguments, so that it can open the files or URLs it was given.
+    pub fn single_instance<F>(self, app_id: &str, mut on_launch: F) -> Option<Self>
+    where
+        F: 'static + FnMut(InstanceLaunch, &mut App),
+    {
+        let mut launches = match single_instance::claim(app_id, &InstanceLaunch::current()) {
+            Ok(SingleInstance::Primary(launches)) => launches,
+            Ok(SingleInstance::Secondary) => return None,
+            Err(error) => {
+                log::error!("failed to set up single instance support: {error:#}");
+                return Some(self);
+            }
+        };
+
+        let this = Rc::downgrade(&self.0);
+        self.0
+            .borrow()
+            .foreground_executor
+            .spawn(async move {
+                while let Some(launch) = launches.next().await {
+                    let Some(app) = this.upgrade() else {
+                        break;
+                    };
+                    app.borrow_mut().update(|cx| {
+                        single_instance::raise(cx);
+                        on_launch(launch, cx);
+                    });
+                }
+            })
+            .detach();
+        Some(self)
+    }
+
     /// Start the application. The provided callback will be called once the
     /// app is fully launched.
     pub fn run<F>(self, on_finish_launching: F)
@@ -243,6 +316,11 @@ pub(crate) type KeystrokeObserver =
     Box<dyn FnMut(&KeystrokeEvent, &mut Window, &mut App) -> bool + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut App) -> LocalBoxFuture<'static, ()> + 'static>;
 type WindowClosedHandler = Box<dyn FnMut(&mut App)>;
+type JankObserver = Box<dyn FnMut(&Jank, &mut App) -> bool + 'static>;
+type InputLatencyObserver = Box<dyn FnMut(&InputLatency, &mut App) -> bool + 'static>;
+type MemoryPressureObserver = Box<dyn FnMut(MemoryPressureLevel, &mut App) -> bool + 'static>;
+type TelemetryObserver = Box<dyn FnMut(&TelemetryEvent, &mut App) -> bool + 'static>;
+
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
 type NewEntityListener = Box<dyn FnMut(AnyEntity, &mut Option<&mut Window>, &mut App) + 'static>;
 
@@ -610,6 +688,7 @@ pub struct App {
         FxHashMap<TypeId, Vec<Rc<dyn Fn(&dyn Any, DispatchPhase, &mut Self)>>>,
     pending_effects: VecDeque<Effect>,
     pub(crate) pending_notifications: FxHashSet<EntityId>,
+    pub(crate) throttled_notifications: FxHashMap<EntityId, ThrottledNotification>,
     pub(crate) pending_global_notifications: FxHashSet<TypeId>,
     pub(crate) observers: SubscriberSet<EntityId, Handler>,
     // TypeId is the type of the event that the listener callback expects
@@ -617,6 +696,18 @@ pub struct App {
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
+    #[cfg(target_os = "windows")]
+    pub(crate) gpu_reset_observers: SubscriberSet<(), Handler>,
+    pub(crate) jank_observers: SubscriberSet<(), JankObserver>,
+    pub(crate) input_latency_observers: SubscriberSet<(), InputLatencyObserver>,
+    memory_pressure_observers: SubscriberSet<(), MemoryPressureObserver>,
+    telemetry_observers: SubscriberSet<(), TelemetryObserver>,
+    pub(crate) jank_budget: Duration,
+    caret_blink_interval_override: Option<Option<Duration>>,
+    storage_id: Option<String>,
+    light_text_contrast: Option<TextContrast>,
+    dark_text_contrast: Option<TextContrast>,
+    animation_clock: AnimationClock,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
@@ -626,6 +717,7 @@ pub struct App {
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
     pub(crate) prompt_builder: Option<PromptBuilder>,
+    pub(crate) scene_renderer_factory: Option<Rc<SceneRendererFactory>>,
     pub(crate) window_invalidators_by_entity:
         FxHashMap<EntityId, FxHashMap<WindowId, WindowInvalidator>>,
     pub(crate) tracked_entities: FxHashMap<WindowId, FxHashSet<EntityId>>,
@@ -687,6 +779,7 @@ impl App {
                 global_action_listeners: FxHashMap::default(),
                 pending_effects: VecDeque::new(),
                 pending_notifications: FxHashSet::default(),
+                throttled_notifications: FxHashMap::default(),
                 pending_global_notifications: FxHashSet::default(),
                 observers: SubscriberSet::new(),
                 tracked_entities: FxHashMap::default(),
@@ -696,6 +789,18 @@ impl App {
                 keystroke_observers: SubscriberSet::new(),
                 keystroke_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
+                #[cfg(target_os = "windows")]
+                gpu_reset_observers: SubscriberSet::new(),
+                jank_observers: SubscriberSet::new(),
+                input_latency_observers: SubscriberSet::new(),
+                memory_pressure_observers: SubscriberSet::new(),
+                telemetry_observers: SubscriberSet::new(),
+                jank_budget: DEFAULT_JANK_BUDGET,
+                caret_blink_interval_override: None,
+                storage_id: None,
+                light_text_contrast: None,
+                dark_text_contrast: None,
+                animation_clock: AnimationClock::new(Instant::now()),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
                 restart_observers: SubscriberSet::new(),
@@ -704,6 +809,7 @@ impl App {
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
                 prompt_builder: Some(PromptBuilder::Default),
+                scene_renderer_factory: None,
                 #[cfg(any(feature = "inspector", debug_assertions))]
                 inspector_renderer: None,
                 #[cfg(any(feature = "inspector", debug_assertions))]
@@ -733,6 +839,30 @@ impl App {
             }
         }));
 
+        #[cfg(target_os = "windows")]
+        platform.on_gpu_reset(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().update(|cx| {
+                        cx.gpu_reset_observers
+                            .clone()
+                            .retain(&(), |callback| (callback)(cx));
+                        cx.refresh_windows();
+                    });
+                }
+            }
+        }));
+
+        platform.on_memory_pressure(Box::new({
+            let app = Rc::downgrade(&app);
+            move |level| {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().update(|cx| cx.trim_memory(level));
+                }
+            }
+        }));
+
         platform.on_quit(Box::new({
             let cx = app.clone();
             move || {
@@ -795,6 +925,153 @@ impl App {
         subscription
     }
 
+    /// Invokes a handler after the GPU device was lost, e.g. due to a driver update or reset, and
+    /// rendering was restored on a new device.
+    ///
+    /// Windows are redrawn and their sprite atlases repopulated automatically. Use this hook to
+    /// recreate GPU resources the application manages itself, such as surfaces.
+    /// Only available on Windows, where the DirectX renderer recovers from device loss.
+    #[cfg(target_os = "windows")]
+    pub fn on_gpu_reset<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.gpu_reset_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler after a window draws a frame that takes longer than the jank budget, with
+    /// a breakdown of where the time went. Use this to report real-world stutter as telemetry.
+    ///
+    /// The handler runs after the frame is presented, outside of the window's update.
+    pub fn on_jank<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&Jank, &mut App),
+    {
+        let (subscription, activate) = self.jank_observers.insert(
+            (),
+            Box::new(move |jank, cx| {
+                callback(jank, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler when a window presents the first frame drawn after it received input,
+    /// with the time from the platform reporting the earliest of that input to the frame being
+    /// presented. Use this to measure end-to-end responsiveness across platforms.
+    ///
+    /// Input that doesn't cause the window to draw isn't reported. The handler runs after the
+    /// frame is presented, outside of the window's update.
+    pub fn on_input_latency<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&InputLatency, &mut App),
+    {
+        let (subscription, activate) = self.input_latency_observers.insert(
+            (),
+            Box::new(move |input_latency, cx| {
+                callback(input_latency, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler when the system reports that it's running low on memory, after GPUI has
+    /// trimmed its own caches. Use this to free application caches that can be rebuilt.
+    ///
+    /// Memory pressure is reported on macOS. On other platforms, call [`App::trim_memory`] when
+    /// memory runs low by some other measure.
+    pub fn on_memory_pressure<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(MemoryPressureLevel, &mut App),
+    {
+        let (subscription, activate) = self.memory_pressure_observers.insert(
+            (),
+            Box::new(move |level, cx| {
+                callback(level, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Invokes a handler for every click, focus change, action dispatch and back or forward
+    /// navigation, so that apps can report interactions to their own analytics without
+    /// instrumenting each listener. Nothing is reported while no handlers are registered.
+    ///
+    /// Handlers run after the interaction has been handled, outside of any window's update.
+    pub fn on_telemetry<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&TelemetryEvent, &mut App),
+    {
+        let (subscription, activate) = self.telemetry_observers.insert(
+            (),
+            Box::new(move |event, cx| {
+                callback(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Reports an interaction to [`App::on_telemetry`] handlers, if there are any.
+    pub(crate) fn report_telemetry(&mut self, event: impl FnOnce() -> TelemetryEvent) {
+        if self.telemetry_observers.is_empty() {
+            return;
+        }
+        let event = event();
+        self.defer(move |cx| {
+            cx.telemetry_observers
+                .clone()
+                .retain(&(), |callback| callback(&event, cx));
+        });
+    }
+
+    /// Frees memory held by GPUI's caches and notifies [`App::on_memory_pressure`] handlers.
+    /// Text shaping caches and sprites that haven't been drawn recently are trimmed at every
+    /// level, and loaded assets such as images are dropped under [`MemoryPressureLevel::Critical`]
+    /// pressure, to be reloaded when next used.
+    pub fn trim_memory(&mut self, level: MemoryPressureLevel) {
+        self.text_system.trim_caches();
+        for window in self.windows.values().flatten() {
+            window.trim_caches();
+        }
+        if level == MemoryPressureLevel::Critical {
+            self.loading_assets.clear();
+        }
+        self.memory_pressure_observers
+            .clone()
+            .retain(&(), |callback| callback(level, self));
+    }
+
+    /// Replaces the filter of GPUI's logger with one parsed from comma-separated directives in the
+    /// syntax of `RUST_LOG`, such as `warn,gpui::render=debug`. See [`LogFilter`].
+    ///
+    /// Only affects records once [`crate::init_logger`] has installed GPUI's logger.
+    pub fn set_log_filter(&mut self, filter: &str) -> Result<()> {
+        crate::set_log_filter(LogFilter::parse(filter)?);
+        Ok(())
+    }
+
+    /// Sets how long a frame may take to draw before it's reported to [`App::on_jank`] handlers.
+    /// Defaults to one frame at 60 frames per second.
+    pub fn set_jank_budget(&mut self, budget: Duration) {
+        self.jank_budget = budget;
+    }
+
     /// Gracefully quit the application via the platform's standard routine.
     pub fn quit(&self) {
         self.platform.quit();
@@ -1025,6 +1302,20 @@ impl App {
         })
     }
 
+    /// Opens a window that fills a display and puts it in kiosk mode. See
+    /// [`Window::enter_kiosk_mode`].
+    pub fn open_kiosk_window<V: 'static + Render>(
+        &mut self,
+        options: crate::KioskOptions,
+        build_root_view: impl FnOnce(&mut Window, &mut App) -> Entity<V>,
+    ) -> anyhow::Result<WindowHandle<V>> {
+        let window_options = options.window_options(self);
+        self.open_window(window_options, |window, cx| {
+            window.enter_kiosk_mode(options, cx);
+            build_root_view(window, cx)
+        })
+    }
+
     /// Instructs the platform to activate the application by bringing it to the foreground.
     pub fn activate(&self, ignoring_other_apps: bool) {
         self.platform.activate(ignoring_other_apps);
@@ -1075,6 +1366,154 @@ impl App {
             .cloned()
     }
 
+    /// Enables window session persistence, storing sessions as JSON at `path`.
+    ///
+    /// Windows opened with a [`crate::WindowOptions::session_id`] reopen with the bounds,
+    /// display and fullscreen state they had when they were last moved or resized. If the
+    /// display they were on is gone, they are moved onto the primary display.
+    pub fn persist_window_sessions(&mut self, path: impl Into<PathBuf>) {
+        self.set_global(WindowSessions::load(path.into()));
+        self.on_app_quit(|cx| {
+            if cx.has_global::<WindowSessions>() {
+                cx.global_mut::<WindowSessions>().flush();
+            }
+            async {}
+        })
+        .detach();
+    }
+
+    /// Returns the saved session for the window with the given session id.
+    pub fn window_session(&self, session_id: &str) -> Option<WindowSession> {
+        self.try_global::<WindowSessions>()?
+            .get(session_id)
+            .copied()
+    }
+
+    /// Discards the saved session for the window with the given session id, so that it
+    /// opens with its default bounds next time.
+    pub fn forget_window_session(&mut self, session_id: &str) {
+        if self.has_global::<WindowSessions>() {
+            self.update_global(|sessions: &mut WindowSessions, cx| sessions.remove(session_id, cx));
+        }
+    }
+
+    /// Returns whether the system is running on battery power or conserving power.
+    pub fn power_state(&self) -> PowerState {
+        self.platform.power_state()
+    }
+
+    /// Returns the time since the user last interacted with the system.
+    ///
+    /// On platforms that don't report system-wide idle time, this is the time since the last
+    /// input to any of the application's windows.
+    pub fn system_idle_duration(&self) -> Option<Duration> {
+        self.platform.system_idle_duration().or_else(|| {
+            self.windows
+                .values()
+                .flatten()
+                .map(|window| window.last_input_timestamp.get())
+                .max()
+                .map(|timestamp| timestamp.elapsed())
+        })
+    }
+
+    /// Invokes `callback` with `true` once the user has been idle for at least `threshold`, and
+    /// with `false` when they become active again.
+    pub fn on_system_idle(
+        &self,
+        threshold: Duration,
+        mut callback: impl FnMut(bool, &mut App) + 'static,
+    ) -> Subscription {
+        let poll_interval = threshold.min(SYSTEM_IDLE_POLL_INTERVAL);
+        let task = self.spawn(async move |cx| {
+            let mut was_idle = false;
+            loop {
+                cx.background_executor().timer(poll_interval).await;
+                let Ok(is_idle) = cx.update(|cx| {
+                    cx.system_idle_duration()
+                        .is_some_and(|idle_duration| idle_duration >= threshold)
+                }) else {
+                    break;
+                };
+                if is_idle != was_idle {
+                    was_idle = is_idle;
+                    if cx.update(|cx| callback(is_idle, cx)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Subscription::new(move || drop(task))
+    }
+
+    /// Returns how long text carets stay shown, and then hidden, while they blink. `None` means
+    /// carets shouldn't blink.
+    ///
+    /// This follows the system's blink rate unless it's been overridden with
+    /// [`App::set_caret_blink_interval`].
+    pub fn caret_blink_interval(&self) -> Option<Duration> {
+        self.caret_blink_interval_override
+            .unwrap_or_else(|| self.platform.caret_blink_interval())
+    }
+
+    /// Overrides the system's caret blink rate for every window. Pass `None` to stop carets
+    /// from blinking. See [`Window::caret_visible`].
+    pub fn set_caret_blink_interval(&mut self, interval: Option<Duration>) {
+        self.caret_blink_interval_override = Some(interval);
+        self.refresh_windows();
+    }
+
+    /// Returns the contrast that text is rendered with in windows with the given appearance, or
+    /// `None` if they use the renderer's default.
+    pub fn text_contrast(&self, appearance: WindowAppearance) -> Option<TextContrast> {
+        match appearance {
+            WindowAppearance::Light | WindowAppearance::VibrantLight => self.light_text_contrast,
+            WindowAppearance::Dark | WindowAppearance::VibrantDark => self.dark_text_contrast,
+        }
+    }
+
+    /// Sets the contrast that text is rendered with in every window whose appearance is light, or
+    /// dark, like the given `appearance`. Pass `None` to use the renderer's default.
+    pub fn set_text_contrast(
+        &mut self,
+        appearance: WindowAppearance,
+        contrast: Option<TextContrast>,
+    ) {
+        match appearance {
+            WindowAppearance::Light | WindowAppearance::VibrantLight => {
+                self.light_text_contrast = contrast
+            }
+            WindowAppearance::Dark | WindowAppearance::VibrantDark => {
+                self.dark_text_contrast = contrast
+            }
+        }
+        self.refresh_windows();
+    }
+
+    /// Returns the clock that animations are measured against.
+    pub fn animation_clock(&self) -> &AnimationClock {
+        &self.animation_clock
+    }
+
+    /// Sets how fast animations run relative to real time in every window, e.g. `0.1` to play
+    /// them in slow motion. Animations continue from where they are.
+    pub fn set_animation_speed(&mut self, speed: f32) {
+        self.animation_clock.set_speed(speed, Instant::now());
+        self.refresh_windows();
+    }
+
+    /// Freezes every animation where it is until [`App::resume_animations`] is called.
+    pub fn pause_animations(&mut self) {
+        self.animation_clock.set_paused(true, Instant::now());
+        self.refresh_windows();
+    }
+
+    /// Resumes the animations stopped by [`App::pause_animations`].
+    pub fn resume_animations(&mut self) {
+        self.animation_clock.set_paused(false, Instant::now());
+        self.refresh_windows();
+    }
+
     /// Returns the appearance of the application's windows.
     pub fn window_appearance(&self) -> WindowAppearance {
         self.platform.window_appearance()
@@ -1124,6 +1563,42 @@ impl App {
         self.platform.delete_credentials(url)
     }
 
+    /// Reads the value stored under `key` with [`App::write_key_value`], which persists across
+    /// launches of the application.
+    pub fn read_key_value(&self, key: &str) -> Task<Result<Option<String>>> {
+        match self.key_value_store() {
+            Ok(store) => self.platform.read_key_value(store, key),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    /// Stores `value` under `key` in storage that persists across launches of the application,
+    /// for state such as settings and window bounds. On native platforms, this is a file in the
+    /// user's local data directory, named after [`Application::with_storage_id`].
+    pub fn write_key_value(&self, key: &str, value: impl Into<String>) -> Task<Result<()>> {
+        match self.key_value_store() {
+            Ok(store) => self
+                .platform
+                .write_key_value(store, key, Some(value.into())),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    /// Removes the value stored under `key` with [`App::write_key_value`].
+    pub fn delete_key_value(&self, key: &str) -> Task<Result<()>> {
+        match self.key_value_store() {
+            Ok(store) => self.platform.write_key_value(store, key, None),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    fn key_value_store(&self) -> Result<KeyValueStore> {
+        match &self.storage_id {
+            Some(storage_id) => KeyValueStore::for_app(storage_id),
+            None => KeyValueStore::for_app(&KeyValueStore::default_app_id()?),
+        }
+    }
+
     /// Directs the platform's default browser to open the given URL.
     pub fn open_url(&self, url: &str) {
         self.platform.open_url(url);
@@ -1199,6 +1674,13 @@ impl App {
         self.platform.should_auto_hide_scrollbars()
     }
 
+    /// Returns whether the user asked the system to reduce transparency, in which case
+    /// translucent backgrounds and overlays should be drawn opaque. Windows do this for their
+    /// own background automatically; see [`Window::material_color`] for colors.
+    pub fn should_reduce_transparency(&self) -> bool {
+        self.platform.should_reduce_transparency()
+    }
+
     /// Restarts the application.
     pub fn restart(&mut self) {
         self.restart_observers
@@ -1323,6 +1805,7 @@ impl App {
 
             for (entity_id, mut entity) in dropped {
                 self.observers.remove(&entity_id);
+                self.throttled_notifications.remove(&entity_id);
                 self.event_listeners.remove(&entity_id);
                 for release_callback in self.release_listeners.remove(&entity_id) {
                     release_callback(entity.as_mut(), self);
@@ -1414,6 +1897,16 @@ impl App {
         });
     }
 
+    // Kept out of `update_window_id`, whose closures would otherwise instantiate it recursively.
+    fn remove_popups(&mut self, popups: Vec<Popup>) {
+        for popup in popups {
+            popup
+                .handle
+                .update(self, |_, popup, _| popup.remove_window())
+                .ok();
+        }
+    }
+
     fn update_window_id<T, F>(&mut self, id: WindowId, update: F) -> Result<T>
     where
         F: FnOnce(AnyView, &mut Window, &mut App) -> T,
@@ -1430,6 +1923,9 @@ impl App {
             if window.removed {
                 cx.window_handles.remove(&id);
                 cx.windows.remove(id);
+                forget_crash_window(id);
+
+                cx.remove_popups(mem::take(&mut window.popups));
 
                 cx.window_closed_observers.clone().retain(&(), |callback| {
                     callback(cx);
@@ -1793,6 +2289,10 @@ impl App {
     /// event handlers with a lower z-index (mouse) or higher in the tree (keyboard). This is
     /// the opposite of [`Self::propagate`]. It's also possible to cancel a call to [`Self::propagate`] by
     /// calling this method before effects are flushed.
+    ///
+    /// Called from a capture phase listener, such as one registered with
+    /// [`crate::StatefulInteractiveElement::on_click_capture`], this keeps the event from reaching
+    /// the elements inside the listener's element.
     pub fn stop_propagation(&mut self) {
         self.propagate_event = false;
     }
@@ -1973,6 +2473,10 @@ impl App {
     }
 
     fn dispatch_global_action(&mut self, action: &dyn Action) {
+        self.report_telemetry(|| TelemetryEvent::ActionDispatched {
+            window: None,
+            action: action.name(),
+        });
         self.propagate_event = true;
 
         if let Some(mut global_listeners) = self
@@ -2078,6 +2582,31 @@ impl App {
         self.prompt_builder = Some(PromptBuilder::Default);
     }
 
+    /// Draws windows opened from now on with renderers built by `build_renderer` instead of the
+    /// platform renderer. `build_renderer` is called with each window's surface and size in
+    /// device pixels. Windows whose renderer fails to build fall back to the platform renderer.
+    ///
+    /// Each window still creates its platform renderer, which owns the window's native surface:
+    /// the layer backing the view on macOS, the swap chain on Windows and the Vulkan surface on
+    /// Linux. Platform windows handle resizing, transparency and presentation through that
+    /// surface, so it's kept rather than skipped. It draws no frames and rasterizes no sprites
+    /// while the scene renderer is in use, so it only holds its pipelines and an empty atlas.
+    pub fn set_scene_renderer(
+        &mut self,
+        build_renderer: impl Fn(
+            &SceneRenderTarget,
+            Size<DevicePixels>,
+        ) -> Result<Box<dyn SceneRenderer>>
+        + 'static,
+    ) {
+        self.scene_renderer_factory = Some(Rc::new(build_renderer));
+    }
+
+    /// Draws windows opened from now on with the platform renderer.
+    pub fn reset_scene_renderer(&mut self) {
+        self.scene_renderer_factory = None;
+    }
+
     /// Remove an asset from GPUI's cache
     pub fn remove_asset<A: Asset>(&mut self, source: &A::Source) {
         let asset_id = (TypeId::of::<A>(), hash(source));
@@ -2164,6 +2693,27 @@ impl App {
         }
     }
 
+    /// Registers an image to show as the cursor, returning the cursor style that shows it.
+    ///
+    /// The platform keeps a copy of the image until it's dropped with
+    /// [`App::drop_cursor_image`], so drop styles that are no longer needed, such as the
+    /// outline of a brush size the user has moved on from.
+    pub fn register_cursor_image(&self, image: CursorImage) -> CursorStyle {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let id = CustomCursorId(NEXT_ID.fetch_add(1, SeqCst));
+        self.platform.register_cursor_image(id, image);
+        CursorStyle::Custom(id)
+    }
+
+    /// Drops an image registered with [`App::register_cursor_image`]. Elements still using its
+    /// cursor style show the default cursor instead. This is a no-op for other cursor styles.
+    pub fn drop_cursor_image(&self, style: CursorStyle) {
+        if let CursorStyle::Custom(id) = style {
+            self.platform.drop_cursor_image(id);
+        }
+    }
+
     /// Sets the renderer for the inspector.
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub fn set_inspector_renderer(&mut self, f: crate::InspectorRenderer) {
@@ -2179,6 +2729,77 @@ impl App {
         self.inspector_element_registry.register(f);
     }
 
+    /// Returns the locale used to look up messages and to format numbers and dates.
+    pub fn locale(&self) -> Locale {
+        self.try_global::<Localization>()
+            .map(|localization| localization.locale.clone())
+            .unwrap_or_else(Locale::system)
+    }
+
+    /// Switches the application's locale, re-rendering all windows so translated
+    /// text and locale-formatted values are refreshed.
+    pub fn set_locale(&mut self, locale: impl Into<Locale>) {
+        self.default_global::<Localization>().locale = locale.into();
+        self.refresh_windows();
+    }
+
+    /// Sets the locale whose messages are used when the active locale has no translation.
+    /// Defaults to `en-US`.
+    pub fn set_fallback_locale(&mut self, locale: impl Into<Locale>) {
+        self.default_global::<Localization>().fallback_locale = locale.into();
+        self.refresh_windows();
+    }
+
+    /// Registers translated messages for the given locale. Messages are merged into any
+    /// catalog previously registered for the same locale.
+    pub fn add_message_catalog(&mut self, locale: impl Into<Locale>, catalog: MessageCatalog) {
+        self.default_global::<Localization>()
+            .add_catalog(locale.into(), catalog);
+        self.refresh_windows();
+    }
+
+    /// Looks up the message with the given id in the active locale, falling back to less
+    /// specific locales and then to the fallback locale. Returns the id itself if no
+    /// translation exists.
+    pub fn translate(&self, key: &str) -> SharedString {
+        self.translate_with(key, &[])
+    }
+
+    /// Like [`App::translate`], substituting the given arguments into `{name}` placeholders.
+    pub fn translate_with(&self, key: &str, args: &[(&str, MessageArg)]) -> SharedString {
+        match self.try_global::<Localization>() {
+            Some(localization) => localization.translate(key, args),
+            None => SharedString::from(key.to_string()),
+        }
+    }
+
+    /// Formats a number for the application's locale. See [`Locale::format_number`].
+    pub fn format_number(&self, value: f64) -> String {
+        self.locale().format_number(value)
+    }
+
+    /// Formats a byte count for the application's locale. See [`Locale::format_bytes`].
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        self.locale().format_bytes(bytes)
+    }
+
+    /// Formats a time offset for the application's locale. See [`Locale::format_relative_time`].
+    pub fn format_relative_time(&self, seconds: i64) -> String {
+        self.locale().format_relative_time(seconds)
+    }
+
+    /// Register a callback to be invoked when the application's locale changes.
+    pub fn observe_locale(&mut self, mut callback: impl FnMut(&mut App) + 'static) -> Subscription {
+        let mut last_locale = self.locale();
+        self.observe_global::<Localization>(move |cx| {
+            let locale = cx.locale();
+            if locale != last_locale {
+                last_locale = locale;
+                callback(cx);
+            }
+        })
+    }
+
     /// Initializes gpui's default colors for the application.
     ///
     /// These colors can be accessed through `cx.default_colors()`.
@@ -2507,9 +3128,9 @@ impl<'a, T> Drop for GpuiBorrow<'a, T> {
 
 #[cfg(test)]
 mod test {
-    use std::{cell::RefCell, rc::Rc};
+    use std::{cell::RefCell, rc::Rc, time::Duration};
 
-    use crate::{AppContext, TestAppContext};
+    use crate::{AppContext, MemoryPressureLevel, TestAppContext};
 
     #[test]
     fn test_gpui_borrow() {
@@ -2541,4 +3162,64 @@ mod test {
 
         assert_eq!(*observation_count.borrow(), 2);
     }
+
+    #[test]
+    fn test_notify_throttled() {
+        let mut cx = TestAppContext::single();
+        let observation_count = Rc::new(RefCell::new(0));
+        let interval = Duration::from_millis(100);
+
+        let state = cx.update(|cx| {
+            let state = cx.new(|_| ());
+            cx.observe(&state, {
+                let observation_count = observation_count.clone();
+                move |_, _| *observation_count.borrow_mut() += 1
+            })
+            .detach();
+            state
+        });
+
+        for _ in 0..10 {
+            state.update(&mut cx, |_, cx| cx.notify_throttled(interval));
+        }
+        cx.run_until_parked();
+        assert_eq!(
+            *observation_count.borrow(),
+            1,
+            "the first notification is sent immediately and the rest are coalesced"
+        );
+
+        cx.executor().advance_clock(interval);
+        cx.run_until_parked();
+        assert_eq!(*observation_count.borrow(), 2);
+
+        cx.executor().advance_clock(interval * 2);
+        state.update(&mut cx, |_, cx| cx.notify_throttled(interval));
+        assert_eq!(
+            *observation_count.borrow(),
+            3,
+            "notifications after a quiet interval aren't delayed"
+        );
+    }
+
+    #[test]
+    fn test_memory_pressure() {
+        let cx = TestAppContext::single();
+        let levels = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            cx.on_memory_pressure({
+                let levels = levels.clone();
+                move |level, _| levels.borrow_mut().push(level)
+            })
+        });
+
+        cx.update(|cx| {
+            cx.trim_memory(MemoryPressureLevel::Warning);
+            cx.trim_memory(MemoryPressureLevel::Critical);
+        });
+        assert_eq!(
+            *levels.borrow(),
+            [MemoryPressureLevel::Warning, MemoryPressureLevel::Critical]
+        );
+    }
 }
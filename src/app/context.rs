@@ -11,6 +11,7 @@ use std::{
     future::Future,
     ops,
     sync::Arc,
+    time::{Duration, Instant},
 };
 use util::Deferred;
 
@@ -22,6 +23,13 @@ pub struct Context<'a, T> {
     entity_state: WeakEntity<T>,
 }
 
+/// When an entity last notified through [`Context::notify_throttled`], and whether a notification
+/// is waiting for the throttle interval to elapse.
+pub(crate) struct ThrottledNotification {
+    pub(crate) last_notified: Instant,
+    pub(crate) trailing_scheduled: bool,
+}
+
 impl<'a, T> ops::Deref for Context<'a, T> {
     type Target = App;
 
@@ -230,6 +238,54 @@ impl<'a, T: 'static> Context<'a, T> {
         self.app.notify(self.entity_state.entity_id);
     }
 
+    /// Notifies observers of this entity at most once per `interval`, for entities that change
+    /// many times per frame, e.g. while a background task reports progress. Notifications within
+    /// the interval are coalesced into one, which is sent once the interval has elapsed.
+    pub fn notify_throttled(&mut self, interval: Duration) {
+        let entity_id = self.entity_state.entity_id;
+        let now = self.app.background_executor().now();
+        let remaining = match self.app.throttled_notifications.get_mut(&entity_id) {
+            Some(throttle) if throttle.trailing_scheduled => return,
+            Some(throttle) => {
+                interval.saturating_sub(now.saturating_duration_since(throttle.last_notified))
+            }
+            None => Duration::ZERO,
+        };
+
+        if remaining.is_zero() {
+            self.app.throttled_notifications.insert(
+                entity_id,
+                ThrottledNotification {
+                    last_notified: now,
+                    trailing_scheduled: false,
+                },
+            );
+            self.notify();
+            return;
+        }
+
+        if let Some(throttle) = self.app.throttled_notifications.get_mut(&entity_id) {
+            throttle.trailing_scheduled = true;
+        }
+        let timer = self.app.background_executor().timer(remaining);
+        self.spawn(async move |this, cx| {
+            timer.await;
+            this.update(cx, |_, cx| {
+                let now = cx.background_executor().now();
+                cx.app.throttled_notifications.insert(
+                    entity_id,
+                    ThrottledNotification {
+                        last_notified: now,
+                        trailing_scheduled: false,
+                    },
+                );
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     /// Spawn the future returned by the given function.
     /// The function is provided a weak handle to the entity owned by this context and a context that can be held across await points.
     /// The returned task must be held or detached.
@@ -0,0 +1,426 @@
+use crate::App;
+use anyhow::{Context as _, Result};
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, Write as _},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use util::ResultExt;
+
+/// The command-line invocation of a second instance of a single-instance application,
+/// forwarded to the instance that was already running.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceLaunch {
+    /// The arguments the second instance was launched with, excluding the executable path.
+    /// These include any files or URLs the platform asked the application to open.
+    pub args: Vec<String>,
+    /// The working directory of the second instance, for resolving relative paths in `args`.
+    pub working_directory: Option<PathBuf>,
+}
+
+impl InstanceLaunch {
+    pub(crate) fn current() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            working_directory: std::env::current_dir().log_err(),
+        }
+    }
+}
+
+/// The result of claiming the single-instance channel for an application id.
+pub(crate) enum SingleInstance {
+    /// No other instance is running. Launches of later instances are sent to the receiver.
+    Primary(mpsc::UnboundedReceiver<InstanceLaunch>),
+    /// Another instance is running and has been sent this process's launch.
+    Secondary,
+}
+
+/// How long a connecting instance may take to send its launch before it's dropped.
+const LAUNCH_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The largest launch a connecting instance may send, so that any local process that can
+/// connect can't make the running instance buffer an unbounded message.
+const MAX_LAUNCH_BYTES: usize = 1024 * 1024;
+
+/// Hands `launch` off to the running instance of `app_id`, or starts listening for later
+/// instances if there is none.
+pub(crate) fn claim(app_id: &str, launch: &InstanceLaunch) -> Result<SingleInstance> {
+    let message = serde_json::to_vec(launch).context("failed to serialize instance launch")?;
+    match forward(app_id, &message) {
+        Ok(()) => return Ok(SingleInstance::Secondary),
+        Err(error) => log::debug!("no running instance of {app_id} found: {error}"),
+    }
+
+    // Another instance may have started listening since the connection above failed.
+    let Some(listener) = ipc::bind(app_id)? else {
+        forward(app_id, &message).context("failed to forward launch to running instance")?;
+        return Ok(SingleInstance::Secondary);
+    };
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::Builder::new()
+        .name("single instance listener".into())
+        .spawn(move || {
+            // Launches are read one at a time, each within `LAUNCH_READ_TIMEOUT`, so a client
+            // that never finishes sending only delays the launches of other instances.
+            for stream in listener.incoming() {
+                if sender.is_closed() {
+                    break;
+                }
+                let Some(stream) = stream.log_err() else {
+                    continue;
+                };
+                let Some(message) = listener.read_message(stream).log_err() else {
+                    continue;
+                };
+                // Instances probing whether this one is alive connect without sending.
+                if message.is_empty() {
+                    continue;
+                }
+                if let Some(launch) = serde_json::from_slice::<InstanceLaunch>(&message)
+                    .context("received malformed instance launch")
+                    .log_err()
+                {
+                    sender.unbounded_send(launch).ok();
+                }
+            }
+        })
+        .context("failed to spawn single instance listener")?;
+    Ok(SingleInstance::Primary(receiver))
+}
+
+fn forward(app_id: &str, message: &[u8]) -> Result<()> {
+    let mut stream = ipc::connect(app_id)?;
+    stream.write_all(message)?;
+    Ok(())
+}
+
+/// Reads from `reader` until the client closes the connection, failing if that takes longer
+/// than `LAUNCH_READ_TIMEOUT` in total or the message exceeds `MAX_LAUNCH_BYTES`.
+fn read_bounded(
+    mut reader: impl io::Read,
+    mut set_read_timeout: impl FnMut(Duration) -> io::Result<()>,
+) -> io::Result<Vec<u8>> {
+    let deadline = Instant::now() + LAUNCH_READ_TIMEOUT;
+    let mut message = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "instance took too long to send its launch",
+            ));
+        }
+        set_read_timeout(remaining)?;
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            return Ok(message);
+        }
+        if message.len() + read > MAX_LAUNCH_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("instance launch exceeds {MAX_LAUNCH_BYTES} bytes"),
+            ));
+        }
+        message.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// Brings the application and its most relevant window to the front.
+pub(crate) fn raise(cx: &mut App) {
+    cx.activate(true);
+    if let Some(window) = cx
+        .active_window()
+        .or_else(|| cx.windows().into_iter().next())
+    {
+        window
+            .update(cx, |_, window, _| window.activate_window())
+            .log_err();
+    }
+}
+
+/// Returns a directory that only the current user can access, for the sockets and lock files of
+/// its instances. It's created in the temporary directory if there's no `XDG_RUNTIME_DIR`, in
+/// which case it's only trusted if the current user owns it and other users can't access it.
+#[cfg(unix)]
+fn runtime_directory() -> Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt as _, MetadataExt as _};
+
+    // SAFETY: getuid has no preconditions and can't fail.
+    let user_id = unsafe { libc::getuid() };
+    let directory = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("gpui-{user_id}"));
+    match std::fs::DirBuilder::new().mode(0o700).create(&directory) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to create {directory:?}"));
+        }
+    }
+    let metadata = std::fs::symlink_metadata(&directory)
+        .with_context(|| format!("failed to read metadata of {directory:?}"))?;
+    if !metadata.is_dir() || metadata.uid() != user_id || metadata.mode() & 0o077 != 0 {
+        anyhow::bail!("{directory:?} isn't a directory that only the current user can access");
+    }
+    Ok(directory)
+}
+
+/// Returns a directory for the port and lock files of the current user's instances. The
+/// temporary directory is in the user's profile, which other users can't access.
+#[cfg(not(unix))]
+fn runtime_directory() -> Result<PathBuf> {
+    let directory = std::env::temp_dir().join("gpui");
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("failed to create {directory:?}"))?;
+    Ok(directory)
+}
+
+/// The path of a file in the current user's runtime directory that belongs to `app_id`.
+/// Characters that could escape the directory or aren't valid in file names are replaced.
+fn runtime_path(app_id: &str, extension: &str) -> Result<PathBuf> {
+    let name = app_id
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '.') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    Ok(runtime_directory()?.join(format!("{name}.{extension}")))
+}
+
+/// Holds an exclusive lock on the lock file of `app_id` while an instance checks for a running
+/// instance and starts listening, so that two instances starting at once can't both do so.
+fn lock_instances(app_id: &str) -> Result<File> {
+    let path = runtime_path(app_id, "lock")?;
+    let file = File::options()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("failed to open {path:?}"))?;
+    file.lock()
+        .with_context(|| format!("failed to lock {path:?}"))?;
+    Ok(file)
+}
+
+#[cfg(unix)]
+mod ipc {
+    use super::{Result, io, lock_instances, read_bounded, runtime_path};
+    use anyhow::Context as _;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    pub(super) fn connect(app_id: &str) -> Result<UnixStream> {
+        Ok(UnixStream::connect(runtime_path(app_id, "sock")?)?)
+    }
+
+    pub(super) struct Listener(UnixListener);
+
+    impl Listener {
+        pub(super) fn incoming(&self) -> impl Iterator<Item = io::Result<UnixStream>> + '_ {
+            self.0.incoming()
+        }
+
+        pub(super) fn read_message(&self, stream: UnixStream) -> io::Result<Vec<u8>> {
+            read_bounded(&stream, |timeout| stream.set_read_timeout(Some(timeout)))
+        }
+    }
+
+    /// Starts listening for instances of `app_id`, or returns `None` if a running instance is
+    /// already listening.
+    pub(super) fn bind(app_id: &str) -> Result<Option<Listener>> {
+        let _lock = lock_instances(app_id)?;
+        let path = runtime_path(app_id, "sock")?;
+        match UnixStream::connect(&path) {
+            Ok(_) => return Ok(None),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            // The socket was left behind by an instance that didn't shut down cleanly.
+            Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove stale socket {path:?}"))?;
+            }
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to connect to {path:?}"));
+            }
+        }
+        let listener =
+            UnixListener::bind(&path).with_context(|| format!("failed to bind socket {path:?}"))?;
+        Ok(Some(Listener(listener)))
+    }
+}
+
+/// Any local process can connect to the listener's port, so the running instance and the
+/// instances connecting to it authenticate each other with tokens from the port file, which only
+/// the current user can read. The running instance sends its token first, so that an instance
+/// doesn't send its own token or launch to a process that took the port over after the running
+/// instance exited.
+#[cfg(not(unix))]
+mod ipc {
+    use super::{LAUNCH_READ_TIMEOUT, Result, io, lock_instances, read_bounded, runtime_path};
+    use anyhow::Context as _;
+    use rand::Rng as _;
+    use std::{
+        io::{BufRead as _, BufReader, Read as _, Write as _},
+        net::{Ipv4Addr, TcpListener, TcpStream},
+    };
+
+    /// The contents of the port file of a running instance.
+    struct PortFile {
+        port: u16,
+        /// The token that connecting instances present to the running instance.
+        client_token: String,
+        /// The token that the running instance presents to connecting instances.
+        server_token: String,
+    }
+
+    fn read_port_file(app_id: &str) -> Result<PortFile> {
+        let path = runtime_path(app_id, "port")?;
+        let contents = std::fs::read_to_string(&path)?;
+        let mut fields = contents.split_whitespace();
+        let (Some(port), Some(client_token), Some(server_token)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            anyhow::bail!("malformed port file {path:?}");
+        };
+        Ok(PortFile {
+            port: port
+                .parse()
+                .with_context(|| format!("malformed port file {path:?}"))?,
+            client_token: client_token.to_string(),
+            server_token: server_token.to_string(),
+        })
+    }
+
+    fn new_token() -> String {
+        format!("{:032x}", rand::rng().random::<u128>())
+    }
+
+    /// Connects to the running instance, checks that it presents its token, and presents this
+    /// instance's token in return.
+    pub(super) fn connect(app_id: &str) -> Result<TcpStream> {
+        let port_file = read_port_file(app_id)?;
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port_file.port))?;
+        // The running instance may be reading another instance's launch before it accepts this
+        // connection.
+        stream.set_read_timeout(Some(LAUNCH_READ_TIMEOUT * 2))?;
+        let mut server_token = String::new();
+        BufReader::new((&stream).take(port_file.server_token.len() as u64 + 1))
+            .read_line(&mut server_token)?;
+        if server_token.trim_end() != port_file.server_token {
+            anyhow::bail!(
+                "the process listening on port {} isn't a running instance",
+                port_file.port
+            );
+        }
+        writeln!(stream, "{}", port_file.client_token)?;
+        Ok(stream)
+    }
+
+    pub(super) struct Listener {
+        listener: TcpListener,
+        client_token: String,
+        server_token: String,
+    }
+
+    impl Listener {
+        pub(super) fn incoming(&self) -> impl Iterator<Item = io::Result<TcpStream>> + '_ {
+            self.listener.incoming()
+        }
+
+        /// Reads a launch from `stream`, rejecting clients that don't present the token.
+        pub(super) fn read_message(&self, mut stream: TcpStream) -> io::Result<Vec<u8>> {
+            writeln!(stream, "{}", self.server_token)?;
+            let message = read_bounded(&stream, |timeout| stream.set_read_timeout(Some(timeout)))?;
+            let (token, message) = match message.iter().position(|byte| *byte == b'\n') {
+                Some(newline) => (&message[..newline], &message[newline + 1..]),
+                None => (message.as_slice(), [].as_slice()),
+            };
+            if token != self.client_token.as_bytes() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "instance presented an invalid token",
+                ));
+            }
+            Ok(message.to_vec())
+        }
+    }
+
+    /// Starts listening for instances of `app_id`, or returns `None` if a running instance is
+    /// already listening.
+    pub(super) fn bind(app_id: &str) -> Result<Option<Listener>> {
+        let _lock = lock_instances(app_id)?;
+        if connect(app_id).is_ok() {
+            return Ok(None);
+        }
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .context("failed to bind single instance listener")?;
+        let port = listener.local_addr()?.port();
+        let client_token = new_token();
+        let server_token = new_token();
+        let path = runtime_path(app_id, "port")?;
+        std::fs::write(&path, format!("{port} {client_token} {server_token}"))
+            .with_context(|| format!("failed to write {path:?}"))?;
+        Ok(Some(Listener {
+            listener,
+            client_token,
+            server_token,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_listener_defers_to_running_instance() {
+        let app_id = format!("gpui-single-instance-test-{}", std::process::id());
+        let first = ipc::bind(&app_id)
+            .unwrap()
+            .expect("no instance is running yet");
+        // The second listener's check for a running instance connects without sending anything.
+        let running_instance = std::thread::spawn(move || {
+            first
+                .incoming()
+                .map(|stream| first.read_message(stream.unwrap()).unwrap())
+                .find(|received| !received.is_empty())
+        });
+        assert!(ipc::bind(&app_id).unwrap().is_none());
+
+        let launch = InstanceLaunch {
+            args: vec!["file.txt".into()],
+            working_directory: None,
+        };
+        let message = serde_json::to_vec(&launch).unwrap();
+        forward(&app_id, &message).unwrap();
+        assert_eq!(running_instance.join().unwrap(), Some(message));
+
+        let restarted = ipc::bind(&app_id).unwrap();
+        assert!(restarted.is_some());
+        drop(restarted);
+        for extension in ["sock", "port", "lock"] {
+            std::fs::remove_file(runtime_path(&app_id, extension).unwrap()).ok();
+        }
+    }
+
+    #[test]
+    fn test_oversized_launch_is_rejected() {
+        let message = io::Read::take(io::repeat(b'a'), MAX_LAUNCH_BYTES as u64);
+        assert_eq!(
+            read_bounded(message, |_| Ok(())).unwrap().len(),
+            MAX_LAUNCH_BYTES
+        );
+        let message = io::Read::take(io::repeat(b'a'), MAX_LAUNCH_BYTES as u64 + 1);
+        assert_eq!(
+            read_bounded(message, |_| Ok(())).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
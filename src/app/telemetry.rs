@@ -0,0 +1,69 @@
+use crate::{AnyWindowHandle, ElementId, FocusId, NavigationDirection};
+
+/// An interaction reported to [`App::on_telemetry`](crate::App::on_telemetry) handlers.
+#[derive(Clone)]
+pub enum TelemetryEvent {
+    /// An element with click listeners was clicked, with the mouse or by pressing enter or space
+    /// while it was focused.
+    Click {
+        /// The window the element is in.
+        window: AnyWindowHandle,
+        /// The id of the clicked element, if it has one.
+        element_id: Option<ElementId>,
+        /// Whether the click came from the keyboard.
+        keyboard: bool,
+    },
+    /// Focus moved between elements of a window.
+    FocusChanged {
+        /// The window focus moved in.
+        window: AnyWindowHandle,
+        /// The element that was focused before, if any.
+        previous: Option<FocusId>,
+        /// The element that's focused now, if any.
+        focused: Option<FocusId>,
+    },
+    /// An action was dispatched.
+    ActionDispatched {
+        /// The window the action was dispatched to, or `None` for global actions.
+        window: Option<AnyWindowHandle>,
+        /// The name of the action, as returned by [`Action::name`](crate::Action::name).
+        action: &'static str,
+    },
+    /// The mouse's back or forward button was pressed.
+    Navigated {
+        /// The window the button was pressed over.
+        window: AnyWindowHandle,
+        /// Whether the button navigates back or forward.
+        direction: NavigationDirection,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestAppContext;
+    use std::{cell::RefCell, rc::Rc};
+
+    actions!(telemetry_test, [Ping]);
+
+    #[gpui::test]
+    fn test_action_telemetry(cx: &mut TestAppContext) {
+        let actions = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            cx.dispatch_action(&Ping);
+            let actions = actions.clone();
+            cx.on_telemetry(move |event, _| {
+                if let TelemetryEvent::ActionDispatched {
+                    window: None,
+                    action,
+                } = event
+                {
+                    actions.borrow_mut().push(*action);
+                }
+            })
+            .detach();
+            cx.dispatch_action(&Ping);
+        });
+        assert_eq!(*actions.borrow(), ["telemetry_test::Ping"]);
+    }
+}
@@ -341,6 +341,11 @@ impl TestAppContext {
         self.test_window(window_handle).simulate_resize(size);
     }
 
+    /// Simulates the user moving the window to the new origin.
+    pub fn simulate_window_move(&self, window_handle: AnyWindowHandle, origin: Point<Pixels>) {
+        self.test_window(window_handle).simulate_move(origin);
+    }
+
     /// Returns true if there's an alert dialog open.
     pub fn expect_restart(&self) -> oneshot::Receiver<Option<PathBuf>> {
         let (tx, rx) = futures::channel::oneshot::channel();
@@ -750,6 +755,7 @@ impl VisualTestContext {
             position,
             modifiers,
             pressed_button: button.into(),
+            timestamp: None,
         })
     }
 
@@ -766,6 +772,7 @@ impl VisualTestContext {
             button,
             click_count: 1,
             first_mouse: false,
+            timestamp: None,
         })
     }
 
@@ -781,6 +788,7 @@ impl VisualTestContext {
             modifiers,
             button,
             click_count: 1,
+            timestamp: None,
         })
     }
 
@@ -792,12 +800,14 @@ impl VisualTestContext {
             button: MouseButton::Left,
             click_count: 1,
             first_mouse: false,
+            timestamp: None,
         });
         self.simulate_event(MouseUpEvent {
             position,
             modifiers,
             button: MouseButton::Left,
             click_count: 1,
+            timestamp: None,
         });
     }
 
@@ -806,6 +816,7 @@ impl VisualTestContext {
         self.simulate_event(ModifiersChangedEvent {
             modifiers,
             capslock: Capslock { on: false },
+            timestamp: None,
         })
     }
 
@@ -814,6 +825,7 @@ impl VisualTestContext {
         self.simulate_event(ModifiersChangedEvent {
             modifiers: Modifiers::none(),
             capslock: Capslock { on },
+            timestamp: None,
         })
     }
 
@@ -822,6 +834,13 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// Simulates the platform asking the window for a frame, as it does on every display refresh,
+    /// which draws the window if it's dirty and presents it.
+    pub fn simulate_request_frame(&mut self) {
+        self.test_window(self.window).simulate_request_frame();
+        self.background_executor.run_until_parked();
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
@@ -1,4 +1,8 @@
-use crate::{DevicePixels, Pixels, Result, SharedString, Size, size};
+use crate::{App, DevicePixels, Pixels, Result, SharedString, Size, Task, size};
+use anyhow::{Context as _, anyhow};
+use collections::HashMap;
+use futures::AsyncReadExt;
+use parking_lot::{Mutex, RwLock};
 use smallvec::SmallVec;
 
 use image::{Delay, Frame};
@@ -6,7 +10,11 @@ use std::{
     borrow::Cow,
     fmt,
     hash::Hash,
-    sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    mem,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
 };
 
 /// A source of assets for this app to use.
@@ -28,6 +36,143 @@ impl AssetSource for () {
     }
 }
 
+/// Embeds the files in a directory into the binary, compressed, and defines a unit struct that
+/// serves them as an [`AssetSource`]. The directory is relative to the manifest directory of the
+/// crate that invokes the macro, and asset paths are relative to the directory.
+///
+/// Because the files are part of the binary, this works the same on every platform, including
+/// those without a file system to read assets from at runtime. Attributes before the name are
+/// applied to the struct, so `#[include = "*.svg"]` and `#[exclude = "..."]` select which files
+/// are embedded.
+///
+/// ```ignore
+/// gpui::assets!(pub Assets, "assets");
+///
+/// Application::new().with_assets(Assets).run(|cx| { /* ... */ });
+/// ```
+#[macro_export]
+macro_rules! assets {
+    ($(#[$attribute:meta])* $visibility:vis $name:ident, $folder:tt) => {
+        #[derive($crate::private::rust_embed::RustEmbed)]
+        #[folder = $folder]
+        #[crate_path = "gpui::private::rust_embed"]
+        $(#[$attribute])*
+        $visibility struct $name;
+
+        impl $crate::AssetSource for $name {
+            fn load(
+                &self,
+                path: &str,
+            ) -> $crate::Result<Option<std::borrow::Cow<'static, [u8]>>> {
+                Ok(<Self as $crate::private::rust_embed::RustEmbed>::get(path)
+                    .map(|file| file.data))
+            }
+
+            fn list(&self, path: &str) -> $crate::Result<Vec<$crate::SharedString>> {
+                Ok(<Self as $crate::private::rust_embed::RustEmbed>::iter()
+                    .filter(|file| file.starts_with(path))
+                    .map(|file| $crate::SharedString::from(file.into_owned()))
+                    .collect())
+            }
+        }
+    };
+}
+
+/// An [`AssetSource`] that falls back to assets fetched from a URL when they are missing from
+/// another source. This keeps large assets out of the binary while small ones stay embedded.
+///
+/// Loading assets is synchronous, so missing assets are not fetched on demand. Call
+/// [`FetchedAssets::fetch`] ahead of time, and the asset is served from memory once the fetch
+/// completes. Clones share the fetched assets, so keep a clone to fetch into after passing one to
+/// [`Application::with_assets`](crate::Application::with_assets).
+pub struct FetchedAssets<S> {
+    source: Arc<S>,
+    base_url: SharedString,
+    fetched: Arc<RwLock<HashMap<SharedString, Arc<[u8]>>>>,
+}
+
+impl<S> Clone for FetchedAssets<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            base_url: self.base_url.clone(),
+            fetched: self.fetched.clone(),
+        }
+    }
+}
+
+impl<S: AssetSource> FetchedAssets<S> {
+    /// Creates an asset source that serves assets from `source`, and otherwise serves the assets
+    /// fetched from paths relative to `base_url`.
+    pub fn new(source: S, base_url: impl Into<SharedString>) -> Self {
+        Self {
+            source: Arc::new(source),
+            base_url: base_url.into(),
+            fetched: Arc::default(),
+        }
+    }
+
+    /// Fetches the asset at the given path relative to the base URL, unless it was fetched before.
+    pub fn fetch(&self, path: impl Into<SharedString>, cx: &App) -> Task<Result<()>> {
+        let path = path.into();
+        if self.fetched.read().contains_key(&path) {
+            return Task::ready(Ok(()));
+        }
+
+        let url = format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+        let client = cx.http_client();
+        let fetched = self.fetched.clone();
+        cx.background_executor().spawn(async move {
+            let mut response = client
+                .get(&url, ().into(), true)
+                .await
+                .with_context(|| format!("fetching asset from {url:?}"))?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "fetching asset from {url:?} failed with status {}",
+                    response.status()
+                ));
+            }
+            let mut body = Vec::new();
+            response.body_mut().read_to_end(&mut body).await?;
+            fetched.write().insert(path, body.into());
+            Ok(())
+        })
+    }
+
+    /// Returns whether the asset at the given path has been fetched.
+    pub fn is_fetched(&self, path: &str) -> bool {
+        self.fetched.read().contains_key(path)
+    }
+}
+
+impl<S: AssetSource> AssetSource for FetchedAssets<S> {
+    fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>> {
+        if let Some(data) = self.source.load(path)? {
+            return Ok(Some(data));
+        }
+        Ok(self
+            .fetched
+            .read()
+            .get(path)
+            .map(|data| Cow::Owned(data.to_vec())))
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        let mut assets = self.source.list(path)?;
+        for fetched_path in self.fetched.read().keys() {
+            if fetched_path.starts_with(path) && !assets.contains(fetched_path) {
+                assets.push(fetched_path.clone());
+            }
+        }
+        Ok(assets)
+    }
+}
+
 /// A unique identifier for the image cache
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct ImageId(pub usize);
@@ -36,6 +181,40 @@ pub struct ImageId(pub usize);
 pub(crate) struct RenderImageParams {
     pub(crate) image_id: ImageId,
     pub(crate) frame_index: usize,
+    pub(crate) mip_level: u32,
+}
+
+/// How an image's pixels are filtered when it's drawn at a size other than its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ImageFilter {
+    /// Draws the pixel nearest each sample, keeping hard edges when scaled up. Suits pixel art.
+    Nearest,
+    /// Interpolates linearly between neighboring pixels. Images drawn at less than half their
+    /// size skip pixels, and shimmer as they move or resize.
+    Linear,
+    /// Interpolates linearly within a mip level: a copy of the image prefiltered to the power
+    /// of two fraction of its size nearest above the size it's drawn at. Mip levels are generated
+    /// in the background when first needed, and the image is drawn from the smallest level
+    /// generated so far until they're ready.
+    ///
+    /// Unlike trilinear filtering, this doesn't blend between two levels, because each level is a
+    /// separate atlas tile rather than part of a mip chain of the atlas texture.
+    #[default]
+    Mipmapped,
+}
+
+/// A mip level of an image frame, below the full size level.
+#[derive(Clone)]
+pub(crate) struct MipLevel {
+    pub(crate) size: Size<DevicePixels>,
+    pub(crate) bytes: Arc<[u8]>,
+}
+
+/// The mip levels generated so far for a frame, from the largest to the smallest.
+#[derive(Default)]
+struct MipChain {
+    levels: Vec<MipLevel>,
+    generating: bool,
 }
 
 /// A cached and processed image, in BGRA format
@@ -45,6 +224,7 @@ pub struct RenderImage {
     /// The scale factor of this image on render.
     pub(crate) scale_factor: f32,
     data: SmallVec<[Frame; 1]>,
+    mip_chains: Mutex<Vec<MipChain>>,
 }
 
 impl PartialEq for RenderImage {
@@ -60,10 +240,13 @@ impl RenderImage {
     pub fn new(data: impl Into<SmallVec<[Frame; 1]>>) -> Self {
         static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
+        let data = data.into();
+        let mip_chains = data.iter().map(|_| MipChain::default()).collect();
         Self {
             id: ImageId(NEXT_ID.fetch_add(1, SeqCst)),
             scale_factor: 1.0,
-            data: data.into(),
+            data,
+            mip_chains: Mutex::new(mip_chains),
         }
     }
 
@@ -95,6 +278,92 @@ impl RenderImage {
     pub fn frame_count(&self) -> usize {
         self.data.len()
     }
+
+    /// Get the number of mip levels of a frame, from the full size image down to a single pixel.
+    pub fn mip_level_count(&self, frame_index: usize) -> u32 {
+        let size = self.size(frame_index);
+        let longest_side = size.width.0.max(size.height.0).max(1) as u32;
+        u32::BITS - longest_side.leading_zeros()
+    }
+
+    /// Get the number of mip levels of a frame generated so far, including the full size image.
+    pub(crate) fn generated_mip_level_count(&self, frame_index: usize) -> u32 {
+        self.mip_chains
+            .lock()
+            .get(frame_index)
+            .map_or(1, |chain| chain.levels.len() as u32 + 1)
+    }
+
+    /// Get a generated mip level of a frame below the full size level. Each level is half the
+    /// size of the one before it, with each pixel averaging the four it covers.
+    pub(crate) fn mip_level(&self, frame_index: usize, level: u32) -> Option<MipLevel> {
+        let chains = self.mip_chains.lock();
+        let index = (level as usize).checked_sub(1)?;
+        chains.get(frame_index)?.levels.get(index).cloned()
+    }
+
+    /// Marks the mip levels of a frame as being generated. Returns false if they already are.
+    pub(crate) fn begin_generating_mip_levels(&self, frame_index: usize) -> bool {
+        let mut chains = self.mip_chains.lock();
+        let Some(chain) = chains.get_mut(frame_index) else {
+            return false;
+        };
+        !mem::replace(&mut chain.generating, true)
+    }
+
+    /// Generates the mip levels of a frame down to `level`, continuing from the smallest level
+    /// generated before. Each level is downsampled from the one above it.
+    pub(crate) fn generate_mip_levels(&self, frame_index: usize, level: u32) {
+        let Some(full_size_bytes) = self.as_bytes(frame_index) else {
+            return;
+        };
+        let level = level.min(self.mip_level_count(frame_index) - 1);
+        let mut previous = self.mip_chains.lock()[frame_index].levels.last().cloned();
+        for _ in self.generated_mip_level_count(frame_index)..=level {
+            let (source_size, bytes) = match &previous {
+                Some(previous) => (previous.size, &previous.bytes[..]),
+                None => (self.size(frame_index), full_size_bytes),
+            };
+            let (width, height) = (source_size.width.0 as u32, source_size.height.0 as u32);
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+            let next = MipLevel {
+                size: size(next_width.into(), next_height.into()),
+                bytes: downsample(bytes, width, height, next_width, next_height).into(),
+            };
+            let mut chains = self.mip_chains.lock();
+            chains[frame_index].levels.push(next.clone());
+            previous = Some(next);
+        }
+        self.mip_chains.lock()[frame_index].generating = false;
+    }
+}
+
+/// Halves the size of a BGRA image with a box filter. Colors are weighted by alpha, so that
+/// transparent pixels don't darken the edges of opaque ones.
+fn downsample(bytes: &[u8], width: u32, height: u32, next_width: u32, next_height: u32) -> Vec<u8> {
+    let mut downsampled = Vec::with_capacity(next_width as usize * next_height as usize * 4);
+    for y in 0..next_height {
+        for x in 0..next_width {
+            let mut color = [0u32; 3];
+            let mut alpha = 0;
+            for source_y in [2 * y, (2 * y + 1).min(height - 1)] {
+                for source_x in [2 * x, (2 * x + 1).min(width - 1)] {
+                    let index = (source_y as usize * width as usize + source_x as usize) * 4;
+                    let pixel = &bytes[index..index + 4];
+                    let pixel_alpha = pixel[3] as u32;
+                    for (sum, component) in color.iter_mut().zip(pixel) {
+                        *sum += *component as u32 * pixel_alpha;
+                    }
+                    alpha += pixel_alpha;
+                }
+            }
+            downsampled
+                .extend(color.map(|sum| (sum + alpha / 2).checked_div(alpha).unwrap_or(0) as u8));
+            downsampled.push(((alpha + 2) / 4) as u8);
+        }
+    }
+    downsampled
 }
 
 impl fmt::Debug for RenderImage {
@@ -105,3 +374,53 @@ impl fmt::Debug for RenderImage {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_downsample_weights_colors_by_alpha() {
+        let opaque_red = [0, 0, 255, 255];
+        let transparent_black = [0, 0, 0, 0];
+        let bytes = [
+            opaque_red,
+            transparent_black,
+            transparent_black,
+            transparent_black,
+        ]
+        .concat();
+        assert_eq!(downsample(&bytes, 2, 2, 1, 1), [0, 0, 255, 64]);
+
+        let gray = [100, 100, 100, 255];
+        let white = [255, 255, 255, 255];
+        let bytes = [gray, white, gray].concat();
+        assert_eq!(downsample(&bytes, 3, 1, 1, 1), [178, 178, 178, 255]);
+    }
+
+    #[test]
+    fn test_generate_mip_levels_incrementally() {
+        let buffer = RgbaImage::from_pixel(8, 4, Rgba([10, 20, 30, 255]));
+        let image = RenderImage::new(SmallVec::from_const([Frame::new(buffer)]));
+        assert_eq!(image.mip_level_count(0), 4);
+        assert_eq!(image.generated_mip_level_count(0), 1);
+        assert!(image.mip_level(0, 1).is_none());
+
+        assert!(image.begin_generating_mip_levels(0));
+        assert!(!image.begin_generating_mip_levels(0));
+        image.generate_mip_levels(0, 1);
+        assert_eq!(image.generated_mip_level_count(0), 2);
+
+        assert!(image.begin_generating_mip_levels(0));
+        image.generate_mip_levels(0, 10);
+        let sizes = (1..4)
+            .map(|level| image.mip_level(0, level).unwrap().size)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sizes,
+            [size(4, 2), size(2, 1), size(1, 1)].map(|level_size| level_size.map(DevicePixels))
+        );
+        assert_eq!(&*image.mip_level(0, 3).unwrap().bytes, [10, 20, 30, 255]);
+    }
+}
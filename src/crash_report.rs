@@ -0,0 +1,286 @@
+use crate::{Pixels, PlatformInput, Scene, Size, WindowId};
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of input events kept for crash reports.
+const MAX_CRASH_REPORT_EVENTS: usize = 50;
+
+static CRASH_REPORTS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CRASH_CONTEXT: LazyLock<Mutex<CrashContext>> = LazyLock::new(Mutex::default);
+
+thread_local! {
+    /// The scene each window last presented, by window id. Scenes can hold platform buffers that
+    /// can't be sent to other threads, so they're kept on the thread that draws the windows and
+    /// are only written to reports of panics on that thread.
+    static CRASH_SCENES: RefCell<BTreeMap<u64, Scene>> = RefCell::default();
+}
+
+/// What GPUI was last doing, as written to crash reports.
+#[derive(Default)]
+struct CrashContext {
+    windows: BTreeMap<u64, WindowSnapshot>,
+    events: VecDeque<String>,
+}
+
+struct WindowSnapshot {
+    viewport_size: Size<Pixels>,
+    scale_factor: f32,
+}
+
+#[derive(Serialize)]
+struct CrashReport {
+    message: String,
+    location: Option<String>,
+    thread: Option<String>,
+    timestamp_ms: u128,
+    backtrace: String,
+    windows: Vec<WindowReport>,
+    recent_events: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct WindowReport {
+    id: u64,
+    viewport_size: Size<Pixels>,
+    scale_factor: f32,
+    scene: Option<String>,
+}
+
+/// Installs a panic hook that writes a crash report into a new subdirectory of `directory`
+/// before running the previously installed hook.
+pub(crate) fn install_crash_reporter(directory: PathBuf) {
+    CRASH_REPORTS_ENABLED.store(true, Ordering::Relaxed);
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let location = info.location().map(ToString::to_string);
+        match write_crash_report(&directory, message, location) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(error) => eprintln!("failed to write crash report: {error:?}"),
+        }
+        previous_hook(info);
+    }));
+}
+
+/// Records the scene a window is about to present, for crash reports. The scene is copied into
+/// the allocations of the window's previous copy, and only converted to SVG when a report is
+/// written.
+pub(crate) fn record_crash_scene(
+    window_id: WindowId,
+    viewport_size: Size<Pixels>,
+    scale_factor: f32,
+    scene: &Scene,
+) {
+    if !CRASH_REPORTS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    CRASH_SCENES.with_borrow_mut(|scenes| {
+        scenes
+            .entry(window_id.as_u64())
+            .or_default()
+            .clone_from(scene)
+    });
+    CRASH_CONTEXT.lock().windows.insert(
+        window_id.as_u64(),
+        WindowSnapshot {
+            viewport_size,
+            scale_factor,
+        },
+    );
+}
+
+/// Records an input event dispatched to a window, for crash reports. Key events are reduced to
+/// their modifiers and the kind of key pressed, so that reports don't reveal what was typed.
+pub(crate) fn record_crash_event(window_id: WindowId, event: &PlatformInput) {
+    if !CRASH_REPORTS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let event = describe_event(event);
+    let mut context = CRASH_CONTEXT.lock();
+    if context.events.len() == MAX_CRASH_REPORT_EVENTS {
+        context.events.pop_front();
+    }
+    context
+        .events
+        .push_back(format!("window {}: {event}", window_id.as_u64()));
+}
+
+fn describe_event(event: &PlatformInput) -> String {
+    match event {
+        PlatformInput::KeyDown(event) => format!(
+            "KeyDown {{ keystroke: {}, is_held: {} }}",
+            event.keystroke.redacted().unparse(),
+            event.is_held
+        ),
+        PlatformInput::KeyUp(event) => format!(
+            "KeyUp {{ keystroke: {} }}",
+            event.keystroke.redacted().unparse()
+        ),
+        event => format!("{event:?}"),
+    }
+}
+
+/// Drops what was recorded about a closed window.
+pub(crate) fn forget_crash_window(window_id: WindowId) {
+    if CRASH_REPORTS_ENABLED.load(Ordering::Relaxed) {
+        CRASH_SCENES.with_borrow_mut(|scenes| scenes.remove(&window_id.as_u64()));
+        CRASH_CONTEXT.lock().windows.remove(&window_id.as_u64());
+    }
+}
+
+fn write_crash_report(
+    directory: &Path,
+    message: String,
+    location: Option<String>,
+) -> Result<PathBuf> {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let report_directory = directory.join(format!("crash-{timestamp_ms}"));
+    fs::create_dir_all(&report_directory)
+        .with_context(|| format!("creating {}", report_directory.display()))?;
+
+    // The panic may have happened while the context or scenes were borrowed on this thread.
+    let context = CRASH_CONTEXT.try_lock();
+    let scenes = CRASH_SCENES
+        .try_with(|scenes| {
+            let scenes = scenes.try_borrow().ok()?;
+            Some(
+                scenes
+                    .iter()
+                    .map(|(id, scene)| (*id, scene.to_svg()))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let mut windows = Vec::new();
+    if let Some(context) = &context {
+        for (id, snapshot) in &context.windows {
+            let scene = match scenes.get(id) {
+                Some(svg) => {
+                    let scene = format!("window-{id}.svg");
+                    fs::write(report_directory.join(&scene), svg)
+                        .with_context(|| format!("writing {scene}"))?;
+                    Some(scene)
+                }
+                None => None,
+            };
+            windows.push(WindowReport {
+                id: *id,
+                viewport_size: snapshot.viewport_size,
+                scale_factor: snapshot.scale_factor,
+                scene,
+            });
+        }
+    }
+    let report = CrashReport {
+        message,
+        location,
+        thread: std::thread::current().name().map(ToString::to_string),
+        timestamp_ms,
+        backtrace: Backtrace::force_capture().to_string(),
+        windows,
+        recent_events: context
+            .map(|context| context.events.iter().cloned().collect())
+            .unwrap_or_default(),
+    };
+
+    let report_path = report_directory.join("report.json");
+    fs::write(&report_path, serde_json::to_vec_pretty(&report)?)
+        .with_context(|| format!("writing {}", report_path.display()))?;
+    Ok(report_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyDownEvent, Keystroke, Modifiers, px, size};
+
+    #[test]
+    fn test_write_crash_report() {
+        let window_id = WindowId::from(u64::MAX);
+        CRASH_CONTEXT.lock().windows.insert(
+            window_id.as_u64(),
+            WindowSnapshot {
+                viewport_size: size(px(800.), px(600.)),
+                scale_factor: 2.,
+            },
+        );
+        CRASH_SCENES.with_borrow_mut(|scenes| scenes.insert(window_id.as_u64(), Scene::default()));
+
+        let directory = std::env::temp_dir().join(format!("gpui-crash-{}", std::process::id()));
+        let report_path =
+            write_crash_report(&directory, "boom".into(), Some("src/app.rs:1:1".into())).unwrap();
+        CRASH_CONTEXT.lock().windows.remove(&window_id.as_u64());
+        CRASH_SCENES.with_borrow_mut(|scenes| scenes.remove(&window_id.as_u64()));
+
+        let report: serde_json::Value =
+            serde_json::from_slice(&fs::read(&report_path).unwrap()).unwrap();
+        assert_eq!(report["message"], "boom");
+        let window = report["windows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|window| window["id"] == window_id.as_u64())
+            .unwrap();
+        assert_eq!(window["scale_factor"], 2.);
+        let scene_path = report_path.with_file_name(window["scene"].as_str().unwrap());
+        assert!(fs::read_to_string(scene_path).unwrap().starts_with("<svg"));
+
+        fs::remove_dir_all(directory).ok();
+    }
+
+    #[test]
+    fn test_key_events_are_redacted() {
+        let keystroke = Keystroke {
+            modifiers: Modifiers::shift(),
+            key: "p".into(),
+            key_char: Some("P".into()),
+        };
+        let event = PlatformInput::KeyDown(KeyDownEvent {
+            keystroke,
+            is_held: false,
+            prefer_character_input: false,
+            scan_code: Some(35),
+            timestamp: None,
+        });
+        assert_eq!(
+            describe_event(&event),
+            "KeyDown { keystroke: shift-character, is_held: false }"
+        );
+
+        let event = PlatformInput::KeyDown(KeyDownEvent {
+            keystroke: Keystroke::parse("ctrl-backspace").unwrap(),
+            is_held: true,
+            prefer_character_input: false,
+            scan_code: None,
+            timestamp: None,
+        });
+        assert_eq!(
+            describe_event(&event),
+            "KeyDown { keystroke: ctrl-backspace, is_held: true }"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{App, Global, Rgba, Window, WindowAppearance, rgb};
+use crate::{App, Global, Oklch, Rgba, Window, WindowAppearance, rgb};
 use std::ops::Deref;
 use std::sync::Arc;
 
@@ -56,7 +56,8 @@ impl Default for Colors {
 }
 
 impl Colors {
-    /// Returns the default colors for the given window appearance.
+    /// Returns the default colors for the given window's appearance, which follows
+    /// [`Window::set_appearance_override`] when the window has an override.
     pub fn for_appearance(window: &Window) -> Self {
         match window.appearance() {
             WindowAppearance::Light | WindowAppearance::VibrantLight => Self::light(),
@@ -146,12 +147,160 @@ impl Colors {
         }
     }
 
+    /// Derives a full set of colors for the given appearance from a few brand colors.
+    ///
+    /// Backgrounds, surfaces, borders and text are taken from fixed perceptual lightness scales,
+    /// tinted with the hue of the brand's accent. The brand colors are used as given in light
+    /// appearance, and lifted in dark appearance so that they stand out against dark surfaces.
+    /// Hover and pressed states step along the lightness of each color, away from the background.
+    pub fn from_brand(brand: &BrandColors, appearance: DefaultAppearance) -> Self {
+        let accent = Oklch::from(brand.accent);
+        let neutral = |lightness: f32| {
+            Rgba::from(Oklch {
+                lightness,
+                chroma: NEUTRAL_CHROMA,
+                hue: accent.hue,
+            })
+        };
+        let (scale, step) = match appearance {
+            DefaultAppearance::Light => (&LIGHT_NEUTRAL_SCALE, -STATE_LIGHTNESS_STEP),
+            DefaultAppearance::Dark => (&DARK_NEUTRAL_SCALE, STATE_LIGHTNESS_STEP),
+        };
+        let base = |color: Rgba| {
+            let color = Oklch::from(color);
+            match appearance {
+                DefaultAppearance::Light => color,
+                DefaultAppearance::Dark => Oklch {
+                    lightness: (color.lightness + DARK_BRAND_LIGHTNESS_LIFT)
+                        .max(DARK_BRAND_MIN_LIGHTNESS),
+                    ..color
+                },
+            }
+        };
+        // Light colors use the brand colors as given, rather than their lossy Oklch round trip.
+        let resting = |given: Rgba, derived: Oklch| match appearance {
+            DefaultAppearance::Light => given,
+            DefaultAppearance::Dark => derived.into(),
+        };
+        let accent = base(brand.accent);
+        let success = base(brand.success);
+        let warning = base(brand.warning);
+        let error = base(brand.error);
+        let selected = Rgba::from(accent.lighten(-2. * STATE_LIGHTNESS_STEP));
+        let text = neutral(scale.text);
+        let background = neutral(scale.background);
+
+        Self {
+            text,
+            text_muted: neutral(scale.text_muted),
+            selected_text: if text.contrast_ratio(selected) >= background.contrast_ratio(selected) {
+                text
+            } else {
+                background
+            },
+            disabled: neutral(scale.disabled),
+
+            background,
+            surface: neutral(scale.surface),
+            surface_hover: neutral(scale.surface + step),
+            container: neutral(scale.container),
+
+            border: neutral(scale.border),
+            separator: neutral(scale.border),
+
+            selected,
+
+            accent: resting(brand.accent, accent),
+            accent_hover: accent.lighten(step).into(),
+            accent_active: accent.lighten(2. * step).into(),
+
+            success: resting(brand.success, success),
+            success_hover: success.lighten(step).into(),
+
+            warning: resting(brand.warning, warning),
+            warning_hover: warning.lighten(step).into(),
+
+            error: resting(brand.error, error),
+            error_hover: error.lighten(step).into(),
+        }
+    }
+
     /// Get [Colors] from the global state
     pub fn get_global(cx: &App) -> &Arc<Colors> {
         &cx.global::<GlobalColors>().0
     }
 }
 
+/// The colors of a brand, from which [`Colors::from_brand`] derives a full set of colors.
+#[derive(Clone, Copy, Debug)]
+pub struct BrandColors {
+    /// The primary color of the brand, used for accents and selections. Its hue also tints the
+    /// derived backgrounds, surfaces, borders and text.
+    pub accent: Rgba,
+    /// The color for success and positive states.
+    pub success: Rgba,
+    /// The color for warnings.
+    pub warning: Rgba,
+    /// The color for errors and destructive actions.
+    pub error: Rgba,
+}
+
+impl BrandColors {
+    /// Returns brand colors with the given accent, and gpui's default success, warning and error
+    /// colors.
+    pub fn new(accent: Rgba) -> Self {
+        let defaults = Colors::light();
+        Self {
+            accent,
+            success: defaults.success,
+            warning: defaults.warning,
+            error: defaults.error,
+        }
+    }
+}
+
+/// The Oklab lightness of each neutral color derived by [`Colors::from_brand`].
+struct NeutralScale {
+    background: f32,
+    container: f32,
+    surface: f32,
+    border: f32,
+    disabled: f32,
+    text_muted: f32,
+    text: f32,
+}
+
+const LIGHT_NEUTRAL_SCALE: NeutralScale = NeutralScale {
+    background: 1.0,
+    container: 0.97,
+    surface: 0.97,
+    border: 0.87,
+    disabled: 0.77,
+    text_muted: 0.62,
+    text: 0.22,
+};
+
+const DARK_NEUTRAL_SCALE: NeutralScale = NeutralScale {
+    background: 0.24,
+    container: 0.27,
+    surface: 0.30,
+    border: 0.37,
+    disabled: 0.47,
+    text_muted: 0.70,
+    text: 1.0,
+};
+
+/// The chroma of neutral colors, enough to tint them with the accent's hue without reading as
+/// colored.
+const NEUTRAL_CHROMA: f32 = 0.006;
+
+/// How far the lightness of a hovered color is from its resting state, and of a pressed color from
+/// its hovered state.
+const STATE_LIGHTNESS_STEP: f32 = 0.05;
+
+const DARK_BRAND_LIGHTNESS_LIFT: f32 = 0.04;
+const DARK_BRAND_MIN_LIGHTNESS: f32 = 0.65;
+
 /// Get [Colors] from the global state
 #[derive(Clone, Debug)]
 pub struct GlobalColors(pub Arc<Colors>);
@@ -198,3 +347,33 @@ impl From<WindowAppearance> for DefaultAppearance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContrastLevel;
+
+    #[test]
+    fn test_from_brand() {
+        let brand = BrandColors::new(rgb(0x7c3aed));
+
+        for appearance in [DefaultAppearance::Light, DefaultAppearance::Dark] {
+            let colors = Colors::from_brand(&brand, appearance);
+            for background in [colors.background, colors.surface, colors.surface_hover] {
+                assert!(
+                    colors.text.contrast_ratio(background)
+                        >= ContrastLevel::Aaa.minimum_ratio(false),
+                    "{appearance:?} text on {background:?}"
+                );
+            }
+            assert!(
+                colors.selected_text.contrast_ratio(colors.selected)
+                    >= ContrastLevel::Aa.minimum_ratio(true)
+            );
+        }
+        assert_eq!(
+            u32::from(Colors::from_brand(&brand, DefaultAppearance::Light).accent),
+            u32::from(brand.accent)
+        );
+    }
+}
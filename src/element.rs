@@ -355,6 +355,7 @@ impl<E: Element> Drawable<E> {
                     window.element_id_stack.push(element_id);
                     GlobalElementId(Arc::from(&*window.element_id_stack))
                 });
+                window.record_element();
 
                 let inspector_id;
                 #[cfg(any(feature = "inspector", debug_assertions))]
@@ -1,7 +1,7 @@
 use smallvec::SmallVec;
 
 use crate::{
-    AnyElement, App, Axis, Bounds, Corner, Display, Edges, Element, GlobalElementId,
+    AnyElement, AnyView, App, Axis, Bounds, Corner, Display, Edges, Element, GlobalElementId,
     InspectorElementId, IntoElement, LayoutId, ParentElement, Pixels, Point, Position, Size, Style,
     Window, point, px,
 };
@@ -20,6 +20,7 @@ pub struct Anchored {
     anchor_position: Option<Point<Pixels>>,
     position_mode: AnchoredPositionMode,
     offset: Option<Point<Pixels>>,
+    popup: Option<AnyView>,
 }
 
 /// anchored gives you an element that will avoid overflowing the window bounds.
@@ -32,6 +33,7 @@ pub fn anchored() -> Anchored {
         anchor_position: None,
         position_mode: AnchoredPositionMode::Window,
         offset: None,
+        popup: None,
     }
 }
 
@@ -75,6 +77,16 @@ impl Anchored {
         self.fit_mode = AnchoredFitMode::SnapToWindowWithMargin(edges.into());
         self
     }
+
+    /// Shows `view` in place of this element's children, fitted to the display instead of the
+    /// window. Where it would overflow the window, it's shown in a borderless pop-up window that
+    /// follows the window as it moves, so tooltips and menus aren't clipped by small windows. The
+    /// pop-up doesn't take focus. On platforms that can't place windows on screen, such as
+    /// Wayland, the view is fitted to the window and drawn inside it.
+    pub fn popup(mut self, view: impl Into<AnyView>) -> Self {
+        self.popup = Some(view.into());
+        self
+    }
 }
 
 impl ParentElement for Anchored {
@@ -85,7 +97,7 @@ impl ParentElement for Anchored {
 
 impl Element for Anchored {
     type RequestLayoutState = AnchoredState;
-    type PrepaintState = ();
+    type PrepaintState = Option<Bounds<Pixels>>;
 
     fn id(&self) -> Option<crate::ElementId> {
         None
@@ -102,6 +114,9 @@ impl Element for Anchored {
         window: &mut Window,
         cx: &mut App,
     ) -> (crate::LayoutId, Self::RequestLayoutState) {
+        if let Some(view) = &self.popup {
+            self.children = SmallVec::from_iter([view.clone().into_any_element()]);
+        }
         let child_layout_ids = self
             .children
             .iter_mut()
@@ -127,9 +142,9 @@ impl Element for Anchored {
         request_layout: &mut Self::RequestLayoutState,
         window: &mut Window,
         cx: &mut App,
-    ) {
+    ) -> Option<Bounds<Pixels>> {
         if request_layout.child_layout_ids.is_empty() {
-            return;
+            return None;
         }
 
         let mut child_min = point(Pixels::MAX, Pixels::MAX);
@@ -149,10 +164,12 @@ impl Element for Anchored {
             self.offset,
         );
 
-        let limits = Bounds {
+        let viewport = Bounds {
             origin: Point::default(),
             size: window.viewport_size(),
         };
+        let popup_limits = self.popup.as_ref().and_then(|_| window.popup_limits());
+        let limits = popup_limits.unwrap_or(viewport);
 
         if self.fit_mode == AnchoredFitMode::SwitchAnchor {
             let mut anchor_corner = self.anchor_corner;
@@ -181,7 +198,10 @@ impl Element for Anchored {
             }
         }
 
-        let client_inset = window.client_inset.unwrap_or(px(0.));
+        let client_inset = match popup_limits {
+            Some(_) => px(0.),
+            None => window.client_inset.unwrap_or(px(0.)),
+        };
         let edges = match self.fit_mode {
             AnchoredFitMode::SnapToWindowWithMargin(edges) => edges,
             _ => Edges::default(),
@@ -209,11 +229,19 @@ impl Element for Anchored {
         let offset = desired.origin - bounds.origin;
         let offset = point(offset.x.round(), offset.y.round());
 
+        if popup_limits.is_some() {
+            let popup_bounds = Bounds::new(bounds.origin + offset, size);
+            if !popup_bounds.is_contained_within(&viewport) {
+                return Some(popup_bounds);
+            }
+        }
+
         window.with_element_offset(offset, |window| {
             for child in &mut self.children {
                 child.prepaint(window, cx);
             }
-        })
+        });
+        None
     }
 
     fn paint(
@@ -222,10 +250,14 @@ impl Element for Anchored {
         _inspector_id: Option<&InspectorElementId>,
         _bounds: crate::Bounds<crate::Pixels>,
         _request_layout: &mut Self::RequestLayoutState,
-        _prepaint: &mut Self::PrepaintState,
+        popup_bounds: &mut Self::PrepaintState,
         window: &mut Window,
         cx: &mut App,
     ) {
+        if let Some((popup_bounds, view)) = popup_bounds.zip(self.popup.clone()) {
+            window.paint_popup(popup_bounds, view);
+            return;
+        }
         for child in &mut self.children {
             child.paint(window, cx);
         }
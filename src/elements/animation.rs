@@ -48,6 +48,73 @@ impl Animation {
     }
 }
 
+/// The clock that [`AnimationExt::with_animation`] measures animations against, shared by every
+/// window. It keeps real time by default, and [`App::set_animation_speed`] and
+/// [`App::pause_animations`] slow it down, speed it up or stop it, which helps when stepping
+/// through animation choreography, and lets apps honor a preference for reduced motion by slowing
+/// animations rather than skipping them.
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClock {
+    time: Duration,
+    updated_at: Instant,
+    speed: f32,
+    paused: bool,
+}
+
+impl AnimationClock {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            time: Duration::ZERO,
+            updated_at: now,
+            speed: 1.,
+            paused: false,
+        }
+    }
+
+    /// Returns how much time has passed on the clock since the app started.
+    pub fn now(&self) -> Duration {
+        self.time_at(Instant::now())
+    }
+
+    /// Returns how fast the clock runs, relative to real time.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Returns whether the clock is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns whether the clock is advancing, i.e. it's neither paused nor running at speed 0.
+    pub fn is_running(&self) -> bool {
+        !self.paused && self.speed > 0.
+    }
+
+    fn time_at(&self, now: Instant) -> Duration {
+        if self.paused {
+            return self.time;
+        }
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let scaled =
+            Duration::try_from_secs_f64(elapsed * f64::from(self.speed)).unwrap_or(Duration::MAX);
+        self.time.saturating_add(scaled)
+    }
+
+    /// Sets how fast the clock runs from `now` on. Negative and non-finite speeds stop the clock.
+    pub(crate) fn set_speed(&mut self, speed: f32, now: Instant) {
+        self.time = self.time_at(now);
+        self.updated_at = now;
+        self.speed = if speed.is_finite() { speed.max(0.) } else { 0. };
+    }
+
+    pub(crate) fn set_paused(&mut self, paused: bool, now: Instant) {
+        self.time = self.time_at(now);
+        self.updated_at = now;
+        self.paused = paused;
+    }
+}
+
 /// An extension trait for adding the animation wrapper to both Elements and Components
 pub trait AnimationExt {
     /// Render this component or element with an animation
@@ -115,7 +182,7 @@ impl<E: IntoElement + 'static> IntoElement for AnimationElement<E> {
 }
 
 struct AnimationState {
-    start: Instant,
+    start: Duration,
     animation_ix: usize,
 }
 
@@ -138,14 +205,16 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
         window: &mut Window,
         cx: &mut App,
     ) -> (crate::LayoutId, Self::RequestLayoutState) {
+        let clock = *cx.animation_clock();
+        let now = clock.now();
         window.with_element_state(global_id.unwrap(), |state, window| {
-            let mut state = state.unwrap_or_else(|| AnimationState {
-                start: Instant::now(),
+            let mut state = state.unwrap_or(AnimationState {
+                start: now,
                 animation_ix: 0,
             });
             let animation_ix = state.animation_ix;
 
-            let mut delta = state.start.elapsed().as_secs_f32()
+            let mut delta = now.saturating_sub(state.start).as_secs_f32()
                 / self.animations[animation_ix].duration.as_secs_f32();
 
             let mut done = false;
@@ -154,7 +223,7 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
                     if animation_ix >= self.animations.len() - 1 {
                         done = true;
                     } else {
-                        state.start = Instant::now();
+                        state.start = now;
                         state.animation_ix += 1;
                     }
                     delta = 1.0;
@@ -172,7 +241,7 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
             let element = self.element.take().expect("should only be called once");
             let mut element = (self.animator)(element, animation_ix, delta).into_any_element();
 
-            if !done {
+            if !done && clock.is_running() {
                 window.request_animation_frame();
             }
 
@@ -261,3 +330,45 @@ mod easing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animation_clock_speed_and_pause() {
+        let start = Instant::now();
+        let mut clock = AnimationClock::new(start);
+        assert_eq!(
+            clock.time_at(start + Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+
+        clock.set_speed(0.5, start + Duration::from_secs(1));
+        assert_eq!(
+            clock.time_at(start + Duration::from_secs(3)),
+            Duration::from_secs(2)
+        );
+
+        clock.set_paused(true, start + Duration::from_secs(3));
+        assert!(!clock.is_running());
+        assert_eq!(
+            clock.time_at(start + Duration::from_secs(10)),
+            Duration::from_secs(2)
+        );
+
+        clock.set_paused(false, start + Duration::from_secs(10));
+        assert!(clock.is_running());
+        assert_eq!(
+            clock.time_at(start + Duration::from_secs(12)),
+            Duration::from_secs(3)
+        );
+
+        clock.set_speed(f32::NAN, start + Duration::from_secs(12));
+        assert!(!clock.is_running());
+        assert_eq!(
+            clock.time_at(start + Duration::from_secs(20)),
+            Duration::from_secs(3)
+        );
+    }
+}
@@ -17,13 +17,13 @@
 
 use crate::{
     AbsoluteLength, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
-    DispatchPhase, Display, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId,
-    Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext,
-    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseClickEvent, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Overflow,
-    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px,
-    size,
+    DispatchPhase, DispatchedEvent, Display, Element, ElementId, Entity, FocusHandle, Global,
+    GlobalElementId, Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero,
+    KeyContext, KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId,
+    ListenerKind, ModifiersChangedEvent, MouseButton, MouseClickEvent, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, Overflow, ParentElement, Pixels, Point, Render, ScrollWheelEvent,
+    SharedString, Size, Style, StyleRefinement, Styled, Task, TelemetryEvent, TooltipId,
+    Visibility, Window, WindowControlArea, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -84,6 +84,8 @@ impl<T: 'static> DragMoveEvent<T> {
     }
 }
 
+impl<T> DispatchedEvent for DragMoveEvent<T> {}
+
 impl Interactivity {
     /// Create an `Interactivity`, capturing the caller location in debug mode.
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -490,6 +492,20 @@ impl Interactivity {
         }));
     }
 
+    /// Bind the given callback to mouse clicks of this element, during the capture phase. Capture
+    /// listeners run from the outermost element inwards, before any click listeners, so an
+    /// element can handle clicks on its children first and call
+    /// [`App::stop_propagation`] to keep them from seeing the click.
+    /// The imperative API equivalent to [`StatefulInteractiveElement::on_click_capture`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    pub fn on_click_capture(
+        &mut self,
+        listener: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.click_capture_listeners.push(Rc::new(listener));
+    }
+
     /// On drag initiation, this callback will be used to create a new view to render the dragged value for a
     /// drag and drop operation. This API should also be used as the equivalent of 'on drag start' with
     /// the [`Self::on_drag_move`] API.
@@ -1134,6 +1150,21 @@ pub trait StatefulInteractiveElement: InteractiveElement {
         self
     }
 
+    /// Bind the given callback to mouse clicks of this element, during the capture phase.
+    /// The fluent API equivalent to [`Interactivity::on_click_capture`].
+    ///
+    /// See [`Context::listener`](crate::Context::listener) to get access to a view's state from this callback.
+    fn on_click_capture(
+        mut self,
+        listener: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        self.interactivity().on_click_capture(listener);
+        self
+    }
+
     /// On drag initiation, this callback will be used to create a new view to render the dragged value for a
     /// drag and drop operation. This API should also be used as the equivalent of 'on drag start' with
     /// the [`InteractiveElement::on_drag_move`] API.
@@ -1530,6 +1561,7 @@ pub struct Interactivity {
     pub(crate) drop_listeners: Vec<(TypeId, DropListener)>,
     pub(crate) can_drop_predicate: Option<CanDropPredicate>,
     pub(crate) click_listeners: Vec<ClickListener>,
+    pub(crate) click_capture_listeners: Vec<ClickListener>,
     pub(crate) drag_listener: Option<(Arc<dyn Any>, DragListener)>,
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut Window, &mut App)>>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
@@ -1687,7 +1719,12 @@ impl Interactivity {
                         style.overflow_mask(bounds, window.rem_size()),
                         |window| {
                             let hitbox = if self.should_insert_hitbox(&style, window, cx) {
-                                Some(window.insert_hitbox(bounds, self.hitbox_behavior))
+                                let hitbox = window.insert_hitbox(bounds, self.hitbox_behavior);
+                                if let Some(global_id) = global_id {
+                                    window.register_element_hitbox(global_id, &hitbox);
+                                }
+                                window.register_hitbox_listeners(&hitbox, self.listener_kinds());
+                                Some(hitbox)
                             } else {
                                 None
                             };
@@ -1717,6 +1754,7 @@ impl Interactivity {
             || !self.mouse_down_listeners.is_empty()
             || !self.mouse_move_listeners.is_empty()
             || !self.click_listeners.is_empty()
+            || !self.click_capture_listeners.is_empty()
             || !self.scroll_wheel_listeners.is_empty()
             || self.drag_listener.is_some()
             || !self.drop_listeners.is_empty()
@@ -1724,6 +1762,44 @@ impl Interactivity {
             || window.is_inspector_picking(cx)
     }
 
+    fn listener_kinds(&self) -> SmallVec<[ListenerKind; 4]> {
+        let mut kinds = SmallVec::new();
+        if self.tracked_focus_handle.is_some() {
+            kinds.push(ListenerKind::Focus);
+        }
+        if !self.mouse_down_listeners.is_empty() {
+            kinds.push(ListenerKind::MouseDown);
+        }
+        if !self.mouse_up_listeners.is_empty() {
+            kinds.push(ListenerKind::MouseUp);
+        }
+        if !self.mouse_move_listeners.is_empty() {
+            kinds.push(ListenerKind::MouseMove);
+        }
+        if !self.scroll_wheel_listeners.is_empty() {
+            kinds.push(ListenerKind::ScrollWheel);
+        }
+        if !self.click_listeners.is_empty() {
+            kinds.push(ListenerKind::Click);
+        }
+        if !self.click_capture_listeners.is_empty() {
+            kinds.push(ListenerKind::ClickCapture);
+        }
+        if self.drag_listener.is_some() {
+            kinds.push(ListenerKind::Drag);
+        }
+        if !self.drop_listeners.is_empty() {
+            kinds.push(ListenerKind::Drop);
+        }
+        if self.hover_listener.is_some() {
+            kinds.push(ListenerKind::Hover);
+        }
+        if self.tooltip_builder.is_some() {
+            kinds.push(ListenerKind::Tooltip);
+        }
+        kinds
+    }
+
     fn clamp_scroll_position(
         &self,
         bounds: Bounds<Pixels>,
@@ -1912,12 +1988,12 @@ impl Interactivity {
     ) {
         use crate::{BorderStyle, TextAlign};
 
-        if global_id.is_some()
+        if let Some(global_id) = global_id
             && (style.debug || style.debug_below || cx.has_global::<crate::DebugBelow>())
             && hitbox.is_hovered(window)
         {
             const FONT_SIZE: crate::Pixels = crate::Pixels(10.);
-            let element_id = format!("{:?}", global_id.unwrap());
+            let element_id = format!("{global_id:?}");
             let str_len = element_id.len();
 
             let render_debug_text = |window: &mut Window| {
@@ -1940,7 +2016,7 @@ impl Interactivity {
                         origin: hitbox.origin,
                         size: text.size(FONT_SIZE),
                     };
-                    if self.source_location.is_some()
+                    if let Some(location) = self.source_location
                         && text_bounds.contains(&window.mouse_position())
                         && window.modifiers().secondary()
                     {
@@ -1971,7 +2047,6 @@ impl Interactivity {
 
                         window.on_mouse_event({
                             let hitbox = hitbox.clone();
-                            let location = self.source_location.unwrap();
                             move |e: &crate::MouseDownEvent, phase, window, cx| {
                                 if text_bounds.contains(&e.position)
                                     && phase.capture()
@@ -2097,6 +2172,8 @@ impl Interactivity {
         let mut drag_listener = mem::take(&mut self.drag_listener);
         let drop_listeners = mem::take(&mut self.drop_listeners);
         let click_listeners = mem::take(&mut self.click_listeners);
+        let element_id = self.element_id.clone();
+        let click_capture_listeners = mem::take(&mut self.click_capture_listeners);
         let can_drop_predicate = mem::take(&mut self.can_drop_predicate);
 
         if !drop_listeners.is_empty() {
@@ -2133,7 +2210,10 @@ impl Interactivity {
         }
 
         if let Some(element_state) = element_state {
-            if !click_listeners.is_empty() || drag_listener.is_some() {
+            if !click_listeners.is_empty()
+                || !click_capture_listeners.is_empty()
+                || drag_listener.is_some()
+            {
                 let pending_mouse_down = element_state
                     .pending_mouse_down
                     .get_or_insert_with(Default::default)
@@ -2193,6 +2273,7 @@ impl Interactivity {
                     // Press enter, space to trigger click, when the element is focused.
                     window.on_key_event({
                         let click_listeners = click_listeners.clone();
+                        let element_id = element_id.clone();
                         let hitbox = hitbox.clone();
                         move |event: &KeyUpEvent, phase, window, cx| {
                             if phase.bubble() && !window.default_prevented() {
@@ -2216,6 +2297,11 @@ impl Interactivity {
                                     for listener in &click_listeners {
                                         listener(&click_event, window, cx);
                                     }
+                                    cx.report_telemetry(|| TelemetryEvent::Click {
+                                        window: window.window_handle(),
+                                        element_id: element_id.clone(),
+                                        keyboard: true,
+                                    });
                                 }
                             }
                         }
@@ -2230,6 +2316,8 @@ impl Interactivity {
                         // so that it happens even if another event handler stops
                         // propagation.
                         DispatchPhase::Capture => {
+                            // Forget a click whose propagation was stopped before it bubbled.
+                            captured_mouse_down = None;
                             let mut pending_mouse_down = pending_mouse_down.borrow_mut();
                             if pending_mouse_down.is_some() && hitbox.is_hovered(window) {
                                 captured_mouse_down = pending_mouse_down.take();
@@ -2244,6 +2332,19 @@ impl Interactivity {
                                 pending_mouse_down.take();
                                 window.refresh();
                             }
+                            drop(pending_mouse_down);
+
+                            if let Some(mouse_down) = &captured_mouse_down
+                                && !click_capture_listeners.is_empty()
+                            {
+                                let mouse_click = ClickEvent::Mouse(MouseClickEvent {
+                                    down: mouse_down.clone(),
+                                    up: event.clone(),
+                                });
+                                for listener in &click_capture_listeners {
+                                    listener(&mouse_click, window, cx);
+                                }
+                            }
                         }
                         // Fire click handlers during the bubble phase.
                         DispatchPhase::Bubble => {
@@ -2255,6 +2356,13 @@ impl Interactivity {
                                 for listener in &click_listeners {
                                     listener(&mouse_click, window, cx);
                                 }
+                                if !click_listeners.is_empty() {
+                                    cx.report_telemetry(|| TelemetryEvent::Click {
+                                        window: window.window_handle(),
+                                        element_id: element_id.clone(),
+                                        keyboard: false,
+                                    });
+                                }
                             }
                         }
                     }
@@ -0,0 +1,142 @@
+use crate::{
+    App, Bounds, Element, GlobalElementId, Hitbox, Hsla, InspectorElementId, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, Pixels, SharedString, StyleRefinement, Styled, Window,
+};
+use util::ResultExt;
+
+/// An icon drawn from a signed distance field of an SVG asset, which stays crisp at any size
+/// without rasterizing the SVG again for each size.
+pub struct Icon {
+    interactivity: Interactivity,
+    path: SharedString,
+    glow: Option<IconGlow>,
+}
+
+/// A soft glow drawn around an [`Icon`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IconGlow {
+    /// The color of the glow at the icon's edge.
+    pub color: Hsla,
+    /// How far the glow extends from the icon's edge before fading out completely. The glow is
+    /// limited to an eighth of the icon's width.
+    pub radius: Pixels,
+}
+
+/// Creates an icon from the SVG asset at the given path. The icon is colored with the text
+/// color and scaled to fit the element's width.
+#[track_caller]
+pub fn icon(path: impl Into<SharedString>) -> Icon {
+    Icon {
+        interactivity: Interactivity::new(),
+        path: path.into(),
+        glow: None,
+    }
+}
+
+impl Icon {
+    /// Draws a glow of the given color around the icon, fading out over `radius`.
+    pub fn glow(mut self, color: impl Into<Hsla>, radius: Pixels) -> Self {
+        self.glow = Some(IconGlow {
+            color: color.into(),
+            radius,
+        });
+        self
+    }
+}
+
+impl Element for Icon {
+    type RequestLayoutState = ();
+    type PrepaintState = Option<Hitbox>;
+
+    fn id(&self) -> Option<crate::ElementId> {
+        self.interactivity.element_id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.interactivity.source_location()
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let layout_id = self.interactivity.request_layout(
+            global_id,
+            inspector_id,
+            window,
+            cx,
+            |style, window, cx| window.request_layout(style, None, cx),
+        );
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Hitbox> {
+        self.interactivity.prepaint(
+            global_id,
+            inspector_id,
+            bounds,
+            bounds.size,
+            window,
+            cx,
+            |_, _, hitbox, _, _| hitbox,
+        )
+    }
+
+    fn paint(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
+        inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        hitbox: &mut Option<Hitbox>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.interactivity.paint(
+            global_id,
+            inspector_id,
+            bounds,
+            hitbox.as_ref(),
+            window,
+            cx,
+            |style, window, cx| {
+                if let Some(color) = style.text.color {
+                    window
+                        .paint_icon(bounds, self.path.clone(), color, self.glow, cx)
+                        .log_err();
+                }
+            },
+        )
+    }
+}
+
+impl IntoElement for Icon {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Icon {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.interactivity.base_style
+    }
+}
+
+impl InteractiveElement for Icon {
+    fn interactivity(&mut self) -> &mut Interactivity {
+        &mut self.interactivity
+    }
+}
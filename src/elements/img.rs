@@ -1,15 +1,17 @@
 use crate::{
-    AnyElement, AnyImageCache, App, Asset, AssetLogger, Bounds, DefiniteLength, Element, ElementId,
-    Entity, GlobalElementId, Hitbox, Image, ImageCache, InspectorElementId, InteractiveElement,
-    Interactivity, IntoElement, LayoutId, Length, ObjectFit, Pixels, RenderImage, Resource,
-    SharedString, SharedUri, StyleRefinement, Styled, Task, Window, px,
+    AnyElement, AnyImageCache, App, Asset, AssetLogger, Bounds, DefiniteLength, DevicePixels,
+    Element, ElementId, Entity, GlobalElementId, Hitbox, Image, ImageCache, ImageFilter,
+    InspectorElementId, InteractiveElement, Interactivity, IntoElement, LayoutId, Length,
+    ObjectFit, Pixels, RenderImage, Resource, SharedString, SharedUri, Size, StyleRefinement,
+    Styled, Task, Window, px,
 };
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 
 use futures::{AsyncReadExt, Future};
 use image::{
-    AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, Rgba,
+    AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, ImageReader, Rgba,
     codecs::{gif::GifDecoder, webp::WebPDecoder},
+    imageops::FilterType,
 };
 use smallvec::SmallVec;
 use std::{
@@ -126,6 +128,7 @@ where
 /// The style of an image element.
 pub struct ImageStyle {
     grayscale: bool,
+    filter: ImageFilter,
     object_fit: ObjectFit,
     loading: Option<Box<dyn Fn() -> AnyElement>>,
     fallback: Option<Box<dyn Fn() -> AnyElement>>,
@@ -135,6 +138,7 @@ impl Default for ImageStyle {
     fn default() -> Self {
         Self {
             grayscale: false,
+            filter: ImageFilter::default(),
             object_fit: ObjectFit::Contain,
             loading: None,
             fallback: None,
@@ -153,6 +157,13 @@ pub trait StyledImage: Sized {
         self
     }
 
+    /// Set how the image is filtered when it's drawn at a size other than its own. Defaults to
+    /// [`ImageFilter::Mipmapped`].
+    fn image_filter(mut self, filter: ImageFilter) -> Self {
+        self.image_style().filter = filter;
+        self
+    }
+
     /// Set the object fit for the image.
     fn object_fit(mut self, object_fit: ObjectFit) -> Self {
         self.image_style().object_fit = object_fit;
@@ -192,6 +203,7 @@ pub struct Img {
     source: ImageSource,
     style: ImageStyle,
     image_cache: Option<AnyImageCache>,
+    max_decode_size: Option<Size<Pixels>>,
 }
 
 /// Create a new image element.
@@ -202,16 +214,17 @@ pub fn img(source: impl Into<ImageSource>) -> Img {
         source: source.into(),
         style: ImageStyle::default(),
         image_cache: None,
+        max_decode_size: None,
     }
 }
 
 impl Img {
     /// A list of all format extensions currently supported by this img element
     pub fn extensions() -> &'static [&'static str] {
-        // This is the list in [image::ImageFormat::from_extension] + `svg`
+        // This is the list in [image::ImageFormat::from_extension] + `svg` + `jxl`
         &[
             "avif", "jpg", "jpeg", "png", "gif", "webp", "tif", "tiff", "tga", "dds", "bmp", "ico",
-            "hdr", "exr", "pbm", "pam", "ppm", "pgm", "ff", "farbfeld", "qoi", "svg",
+            "hdr", "exr", "pbm", "pam", "ppm", "pgm", "ff", "farbfeld", "qoi", "svg", "jxl",
         ]
     }
 
@@ -231,6 +244,15 @@ impl Img {
             ..self
         }
     }
+
+    /// Downscales still images loaded from a resource to fit within the given size while
+    /// decoding them, such as thumbnails of large photos in a grid. The image keeps its original
+    /// layout size. Downscaled images are cached by the asset system under their resource and
+    /// size, rather than by the element's image cache.
+    pub fn max_decode_size(mut self, size: Size<Pixels>) -> Self {
+        self.max_decode_size = Some(size);
+        self
+    }
 }
 
 impl Deref for Stateful<Img> {
@@ -307,6 +329,7 @@ impl Element for Img {
                         self.image_cache
                             .clone()
                             .or_else(|| window.image_cache_stack.last().cloned()),
+                        self.max_decode_size,
                         window,
                         cx,
                     ) {
@@ -462,6 +485,7 @@ impl Element for Img {
                     self.image_cache
                         .clone()
                         .or_else(|| window.image_cache_stack.last().cloned()),
+                    self.max_decode_size,
                     window,
                     cx,
                 ) {
@@ -474,12 +498,14 @@ impl Element for Img {
                         .to_pixels(window.rem_size())
                         .clamp_radii_for_quad_size(new_bounds.size);
                     window
-                        .paint_image(
+                        .paint_image_with_filter(
                             new_bounds,
                             corner_radii,
                             data,
                             layout_state.frame_index,
                             self.style.grayscale,
+                            self.style.filter,
+                            cx,
                         )
                         .log_err();
                 } else if let Some(replacement) = &mut layout_state.replacement {
@@ -516,12 +542,21 @@ impl ImageSource {
     pub(crate) fn use_data(
         &self,
         cache: Option<AnyImageCache>,
+        max_decode_size: Option<Size<Pixels>>,
         window: &mut Window,
         cx: &mut App,
     ) -> Option<Result<Arc<RenderImage>, ImageCacheError>> {
         match self {
             ImageSource::Resource(resource) => {
-                if let Some(cache) = cache {
+                if let Some(max_size) = max_decode_size {
+                    let scale_factor = window.rasterization_scale();
+                    let source = DownscaledResource {
+                        resource: resource.clone(),
+                        max_size: max_size
+                            .map(|length| DevicePixels((length.0 * scale_factor).ceil() as i32)),
+                    };
+                    window.use_asset::<AssetLogger<DownscaledImageAssetLoader>>(&source, cx)
+                } else if let Some(cache) = cache {
                     cache.load(resource, window, cx)
                 } else {
                     window.use_asset::<ImgResourceLoader>(resource, cx)
@@ -593,49 +628,108 @@ impl Asset for ImageAssetLoader {
         source: Self::Source,
         cx: &mut App,
     ) -> impl Future<Output = Self::Output> + Send + 'static {
-        let client = cx.http_client();
-        // TODO: Can we make SVGs always rescale?
-        // let scale_factor = cx.scale_factor();
-        let svg_renderer = cx.svg_renderer();
-        let asset_source = cx.asset_source().clone();
-        async move {
-            let bytes = match source.clone() {
-                Resource::Path(uri) => fs::read(uri.as_ref())?,
-                Resource::Uri(uri) => {
-                    let mut response = client
-                        .get(uri.as_ref(), ().into(), true)
-                        .await
-                        .with_context(|| format!("loading image asset from {uri:?}"))?;
-                    let mut body = Vec::new();
-                    response.body_mut().read_to_end(&mut body).await?;
-                    if !response.status().is_success() {
-                        let mut body = String::from_utf8_lossy(&body).into_owned();
-                        let first_line = body.lines().next().unwrap_or("").trim_end();
-                        body.truncate(first_line.len());
-                        return Err(ImageCacheError::BadStatus {
-                            uri,
-                            status: response.status(),
-                            body,
-                        });
-                    }
-                    body
+        load_image(source, None, cx)
+    }
+}
+
+/// An image resource that is downscaled while decoding. See [`Img::max_decode_size`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DownscaledResource {
+    /// The image resource.
+    pub resource: Resource,
+    /// The largest size to decode still images at, in device pixels.
+    pub max_size: Size<DevicePixels>,
+}
+
+/// An image loader for the GPUI asset system that downscales still images while decoding them,
+/// so large images displayed small don't use more memory and atlas space than they need.
+#[derive(Clone)]
+pub enum DownscaledImageAssetLoader {}
+
+impl Asset for DownscaledImageAssetLoader {
+    type Source = DownscaledResource;
+    type Output = Result<Arc<RenderImage>, ImageCacheError>;
+
+    fn load(
+        source: Self::Source,
+        cx: &mut App,
+    ) -> impl Future<Output = Self::Output> + Send + 'static {
+        load_image(source.resource, Some(source.max_size), cx)
+    }
+}
+
+/// Loads and decodes an image. This runs on the background executor when loaded through the asset
+/// system, so decoding never blocks the UI thread.
+fn load_image(
+    source: Resource,
+    max_size: Option<Size<DevicePixels>>,
+    cx: &mut App,
+) -> impl Future<Output = Result<Arc<RenderImage>, ImageCacheError>> + Send + 'static {
+    let client = cx.http_client();
+    // TODO: Can we make SVGs always rescale?
+    // let scale_factor = cx.scale_factor();
+    let svg_renderer = cx.svg_renderer();
+    let asset_source = cx.asset_source().clone();
+    async move {
+        let bytes = match source.clone() {
+            Resource::Path(uri) => fs::read(uri.as_ref())?,
+            Resource::Uri(uri) => {
+                let mut response = client
+                    .get(uri.as_ref(), ().into(), true)
+                    .await
+                    .with_context(|| format!("loading image asset from {uri:?}"))?;
+                let mut body = Vec::new();
+                response.body_mut().read_to_end(&mut body).await?;
+                if !response.status().is_success() {
+                    let mut body = String::from_utf8_lossy(&body).into_owned();
+                    let first_line = body.lines().next().unwrap_or("").trim_end();
+                    body.truncate(first_line.len());
+                    return Err(ImageCacheError::BadStatus {
+                        uri,
+                        status: response.status(),
+                        body,
+                    });
                 }
-                Resource::Embedded(path) => {
-                    let data = asset_source.load(&path).ok().flatten();
-                    if let Some(data) = data {
-                        data.to_vec()
-                    } else {
-                        return Err(ImageCacheError::Asset(
-                            format!("Embedded resource not found: {}", path).into(),
-                        ));
+                body
+            }
+            Resource::Embedded(path) => {
+                let data = asset_source.load(&path).ok().flatten();
+                if let Some(data) = data {
+                    data.to_vec()
+                } else {
+                    return Err(ImageCacheError::Asset(
+                        format!("Embedded resource not found: {}", path).into(),
+                    ));
+                }
+            }
+        };
+
+        if is_jpeg_xl(&bytes) {
+            return Ok(Arc::new(decode_jpeg_xl(&bytes, max_size)?));
+        }
+
+        if let Ok(format) = image::guess_format(&bytes) {
+            let data = match format {
+                ImageFormat::Gif => {
+                    let decoder = GifDecoder::new(Cursor::new(&bytes))?;
+                    let mut frames = SmallVec::new();
+
+                    for frame in decoder.into_frames() {
+                        let mut frame = frame?;
+                        // Convert from RGBA to BGRA.
+                        for pixel in frame.buffer_mut().chunks_exact_mut(4) {
+                            pixel.swap(0, 2);
+                        }
+                        frames.push(frame);
                     }
+
+                    frames
                 }
-            };
+                ImageFormat::WebP => {
+                    let mut decoder = WebPDecoder::new(Cursor::new(&bytes))?;
 
-            if let Ok(format) = image::guess_format(&bytes) {
-                let data = match format {
-                    ImageFormat::Gif => {
-                        let decoder = GifDecoder::new(Cursor::new(&bytes))?;
+                    if decoder.has_animation() {
+                        let _ = decoder.set_background_color(Rgba([0, 0, 0, 0]));
                         let mut frames = SmallVec::new();
 
                         for frame in decoder.into_frames() {
@@ -648,56 +742,92 @@ impl Asset for ImageAssetLoader {
                         }
 
                         frames
+                    } else {
+                        return Ok(Arc::new(decode_still_image(&bytes, format, max_size)?));
                     }
-                    ImageFormat::WebP => {
-                        let mut decoder = WebPDecoder::new(Cursor::new(&bytes))?;
-
-                        if decoder.has_animation() {
-                            let _ = decoder.set_background_color(Rgba([0, 0, 0, 0]));
-                            let mut frames = SmallVec::new();
-
-                            for frame in decoder.into_frames() {
-                                let mut frame = frame?;
-                                // Convert from RGBA to BGRA.
-                                for pixel in frame.buffer_mut().chunks_exact_mut(4) {
-                                    pixel.swap(0, 2);
-                                }
-                                frames.push(frame);
-                            }
+                }
+                ImageFormat::Avif if cfg!(not(feature = "avif")) => {
+                    return Err(anyhow!("decoding AVIF images requires the `avif` feature").into());
+                }
+                _ => return Ok(Arc::new(decode_still_image(&bytes, format, max_size)?)),
+            };
 
-                            frames
-                        } else {
-                            let mut data = DynamicImage::from_decoder(decoder)?.into_rgba8();
+            Ok(Arc::new(RenderImage::new(data)))
+        } else {
+            svg_renderer
+                .render_single_frame(&bytes, 1.0, true)
+                .map_err(Into::into)
+        }
+    }
+}
 
-                            // Convert from RGBA to BGRA.
-                            for pixel in data.chunks_exact_mut(4) {
-                                pixel.swap(0, 2);
-                            }
+/// Decodes a still image, applying its EXIF orientation and downscaling it to fit within
+/// `max_size`. Downscaled images keep the layout size of the original image.
+pub(crate) fn decode_still_image(
+    bytes: &[u8],
+    format: ImageFormat,
+    max_size: Option<Size<DevicePixels>>,
+) -> Result<RenderImage, ImageError> {
+    let decoder = ImageReader::with_format(Cursor::new(bytes), format).into_decoder()?;
+    decode_still_image_with(decoder, max_size)
+}
+
+fn decode_still_image_with(
+    mut decoder: impl image::ImageDecoder,
+    max_size: Option<Size<DevicePixels>>,
+) -> Result<RenderImage, ImageError> {
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    let original_width = image.width();
+    if let Some(max_size) = max_size {
+        let max_width = max_size.width.0.max(1) as u32;
+        let max_height = max_size.height.0.max(1) as u32;
+        if image.width() > max_width || image.height() > max_height {
+            image = image.resize(max_width, max_height, FilterType::Triangle);
+        }
+    }
 
-                            SmallVec::from_elem(Frame::new(data), 1)
-                        }
-                    }
-                    _ => {
-                        let mut data =
-                            image::load_from_memory_with_format(&bytes, format)?.into_rgba8();
+    let mut data = image.into_rgba8();
+    // Convert from RGBA to BGRA.
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
 
-                        // Convert from RGBA to BGRA.
-                        for pixel in data.chunks_exact_mut(4) {
-                            pixel.swap(0, 2);
-                        }
+    let mut render_image = RenderImage::new(SmallVec::from_elem(Frame::new(data), 1));
+    if original_width > 0 {
+        render_image.scale_factor = render_image.size(0).width.0 as f32 / original_width as f32;
+    }
+    Ok(render_image)
+}
 
-                        SmallVec::from_elem(Frame::new(data), 1)
-                    }
-                };
+/// Returns whether the bytes start with a JPEG XL codestream or container signature, which
+/// [`image::guess_format`] doesn't recognize.
+fn is_jpeg_xl(bytes: &[u8]) -> bool {
+    const CODESTREAM_SIGNATURE: [u8; 2] = [0xff, 0x0a];
+    const CONTAINER_SIGNATURE: [u8; 12] = [
+        0x00, 0x00, 0x00, 0x0c, b'J', b'X', b'L', b' ', 0x0d, 0x0a, 0x87, 0x0a,
+    ];
+    bytes.starts_with(&CODESTREAM_SIGNATURE) || bytes.starts_with(&CONTAINER_SIGNATURE)
+}
 
-                Ok(Arc::new(RenderImage::new(data)))
-            } else {
-                svg_renderer
-                    .render_single_frame(&bytes, 1.0, true)
-                    .map_err(Into::into)
-            }
-        }
-    }
+#[cfg(feature = "jxl")]
+fn decode_jpeg_xl(
+    bytes: &[u8],
+    max_size: Option<Size<DevicePixels>>,
+) -> Result<RenderImage, ImageCacheError> {
+    let decoder = jxl_oxide::integration::JxlDecoder::new(Cursor::new(bytes))
+        .context("decoding JPEG XL image")?;
+    Ok(decode_still_image_with(decoder, max_size)?)
+}
+
+#[cfg(not(feature = "jxl"))]
+fn decode_jpeg_xl(
+    _bytes: &[u8],
+    _max_size: Option<Size<DevicePixels>>,
+) -> Result<RenderImage, ImageCacheError> {
+    Err(anyhow!("decoding JPEG XL images requires the `jxl` feature").into())
 }
 
 /// An error that can occur when interacting with the image cache.
@@ -753,3 +883,40 @@ impl From<image::ImageError> for ImageCacheError {
         Self::Image(Arc::new(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::size;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_decode_still_image_downscales() {
+        let image = RgbaImage::from_pixel(400, 200, Rgba([255, 0, 0, 255]));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+
+        let max_size = size(DevicePixels(100), DevicePixels(100));
+        let decoded = decode_still_image(&bytes, ImageFormat::Png, Some(max_size)).unwrap();
+        assert_eq!(decoded.size(0), size(DevicePixels(100), DevicePixels(50)));
+        assert_eq!(
+            decoded.render_size(0),
+            size(px(400.), px(200.)),
+            "downscaled images keep their layout size"
+        );
+        assert_eq!(decoded.as_bytes(0).unwrap()[..4], [0, 0, 255, 255]);
+
+        let decoded = decode_still_image(&bytes, ImageFormat::Png, None).unwrap();
+        assert_eq!(decoded.size(0), size(DevicePixels(400), DevicePixels(200)));
+    }
+
+    #[test]
+    fn test_is_jpeg_xl() {
+        assert!(is_jpeg_xl(&[0xff, 0x0a, 0xfa]));
+        assert!(is_jpeg_xl(b"\0\0\0\x0cJXL \r\n\x87\n\0\0\0\x14ftypjxl "));
+        assert!(!is_jpeg_xl(&[0xff, 0xd8, 0xff]), "plain JPEG");
+        assert!(!is_jpeg_xl(&[]));
+    }
+}
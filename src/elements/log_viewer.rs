@@ -0,0 +1,105 @@
+use crate::{
+    Context, Hsla, IntoElement, LogRecord, ParentElement, Render, ScrollStrategy, Styled, Task,
+    UniformListScrollHandle, Window, div, log_record_count, opaque_grey, recent_log_records, red,
+    uniform_list, white, yellow,
+};
+use log::Level;
+use std::time::Duration;
+
+/// How often a [`LogViewer`] checks for new records.
+const LOG_VIEWER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A debug view that lists the records most recently kept by GPUI's logger, newest last, and
+/// follows new records as they're logged. Records only reach it once [`crate::init_logger`] has
+/// installed GPUI's logger.
+pub struct LogViewer {
+    records: Vec<LogRecord>,
+    record_count: usize,
+    min_level: Level,
+    scroll_handle: UniformListScrollHandle,
+    _poll: Task<()>,
+}
+
+impl LogViewer {
+    /// Creates a viewer that lists records at `min_level` or more severe.
+    pub fn new(min_level: Level, cx: &mut Context<Self>) -> Self {
+        let poll = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor()
+                    .timer(LOG_VIEWER_POLL_INTERVAL)
+                    .await;
+                if this.update(cx, |this, cx| this.refresh(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut this = Self {
+            records: Vec::new(),
+            record_count: 0,
+            min_level,
+            scroll_handle: UniformListScrollHandle::new(),
+            _poll: poll,
+        };
+        this.reload();
+        this
+    }
+
+    /// Changes the least severe level of the records listed.
+    pub fn set_min_level(&mut self, min_level: Level, cx: &mut Context<Self>) {
+        self.min_level = min_level;
+        self.reload();
+        cx.notify();
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        if log_record_count() != self.record_count {
+            self.reload();
+            cx.notify();
+        }
+    }
+
+    fn reload(&mut self) {
+        self.record_count = log_record_count();
+        self.records = recent_log_records();
+        self.records.retain(|record| record.level <= self.min_level);
+        if let Some(last_index) = self.records.len().checked_sub(1) {
+            self.scroll_handle
+                .scroll_to_item(last_index, ScrollStrategy::Bottom);
+        }
+    }
+}
+
+fn level_color(level: Level) -> Hsla {
+    match level {
+        Level::Error => red(),
+        Level::Warn => yellow(),
+        Level::Info => white(),
+        Level::Debug | Level::Trace => opaque_grey(0.6, 1.),
+    }
+}
+
+impl Render for LogViewer {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        let records = self.records.clone();
+        div().size_full().bg(opaque_grey(0.1, 1.)).child(
+            uniform_list("log-viewer", records.len(), move |range, _, _| {
+                records[range]
+                    .iter()
+                    .map(|record| {
+                        div()
+                            .flex()
+                            .gap_2()
+                            .px_2()
+                            .text_xs()
+                            .text_color(level_color(record.level))
+                            .child(record.level.as_str())
+                            .child(record.target.clone())
+                            .child(record.message.clone())
+                    })
+                    .collect()
+            })
+            .size_full()
+            .track_scroll(&self.scroll_handle),
+        )
+    }
+}
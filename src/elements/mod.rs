@@ -3,23 +3,42 @@ mod animation;
 mod canvas;
 mod deferred;
 mod div;
+mod icon;
 mod image_cache;
 mod img;
 mod list;
+mod log_viewer;
+mod native_view;
+mod subsurface;
 mod surface;
 mod svg;
 mod text;
+mod ui_node;
 mod uniform_list;
+#[cfg(all(feature = "webview", any(target_os = "macos", target_os = "windows")))]
+mod webview;
+#[cfg(all(
+    feature = "webview",
+    not(any(target_os = "macos", target_os = "windows"))
+))]
+compile_error!("the `webview` feature is only supported on macOS and Windows");
 
 pub use anchored::*;
 pub use animation::*;
 pub use canvas::*;
 pub use deferred::*;
 pub use div::*;
+pub use icon::*;
 pub use image_cache::*;
 pub use img::*;
 pub use list::*;
+pub use log_viewer::*;
+pub use native_view::*;
+pub use subsurface::*;
 pub use surface::*;
 pub use svg::*;
 pub use text::*;
+pub use ui_node::*;
 pub use uniform_list::*;
+#[cfg(all(feature = "webview", any(target_os = "macos", target_os = "windows")))]
+pub use webview::*;
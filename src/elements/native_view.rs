@@ -0,0 +1,98 @@
+use crate::{
+    App, Bounds, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement, LayoutId,
+    Pixels, Style, StyleRefinement, Styled, Window,
+};
+use raw_window_handle::RawWindowHandle;
+use refineable::Refineable;
+
+/// An element that positions a platform view created outside of GPUI, such as a web view, video
+/// player or map control, over the bounds it's laid out at.
+///
+/// The platform view is moved and resized to follow the element as it scrolls, and is clipped
+/// to the element's content mask. It's hidden in frames that don't paint the element.
+///
+/// Platform views are composited by the operating system above the window's contents, so GPUI
+/// content can't be drawn over them: popovers and menus that overlap a native view appear
+/// beneath it. Overlapping native views stack in the order they were first painted, with later
+/// ones on top. Native views are supported on macOS, where `view` must be a
+/// [`RawWindowHandle::AppKit`] `NSView`, and on Windows, where it must be a
+/// [`RawWindowHandle::Win32`] window. Other handles are ignored.
+pub struct NativeView {
+    id: ElementId,
+    view: RawWindowHandle,
+    style: StyleRefinement,
+}
+
+/// Create a new native view element that embeds the given platform view. The `id` identifies the
+/// embedded view across frames, and must be unique among the native views of the same parent.
+pub fn native_view(id: impl Into<ElementId>, view: RawWindowHandle) -> NativeView {
+    NativeView {
+        id: id.into(),
+        view,
+        style: Default::default(),
+    }
+}
+
+impl Element for NativeView {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style, [], cx);
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Self::PrepaintState {
+    }
+
+    fn paint(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        _: &mut Self::PrepaintState,
+        window: &mut Window,
+        _: &mut App,
+    ) {
+        window.paint_native_view(self.id.clone(), bounds, self.view);
+    }
+}
+
+impl IntoElement for NativeView {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for NativeView {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
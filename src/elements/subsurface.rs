@@ -0,0 +1,106 @@
+use crate::{
+    AnyView, App, Bounds, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement,
+    LayoutId, Pixels, Style, StyleRefinement, Styled, Window,
+};
+use refineable::Refineable;
+
+/// Create a new subsurface element, which draws the given view as an independently redrawn
+/// layer of the window.
+pub fn subsurface(view: impl Into<AnyView>) -> Subsurface {
+    Subsurface {
+        view: view.into(),
+        frame_rate: None,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// A subsurface element, for content such as a picture-in-picture video preview that redraws
+/// far more often than the rest of the window.
+///
+/// The element reserves space in the layout, and its view is drawn at the element's bounds above
+/// the rest of the window's base layer, clipped to the element's content mask. The view keeps its
+/// own cached scene: it's redrawn when it notifies or at its [frame rate](Subsurface::frame_rate),
+/// without marking the views around it dirty. In exchange, the view starts from the window's
+/// default text style, and actions dispatched within it bubble straight to the window's root view.
+///
+/// For these redraws to reuse whatever the rest of the window painted instead of rendering the
+/// root view again, cache the root view with [`Window::set_root_view_cached`].
+pub struct Subsurface {
+    view: AnyView,
+    frame_rate: Option<u32>,
+    style: StyleRefinement,
+}
+
+impl Subsurface {
+    /// Redraw the view this many times a second while the subsurface is drawn, for content that
+    /// changes continuously. While the root view is [cached](Window::set_root_view_cached),
+    /// other views in the window aren't redrawn at this rate.
+    pub fn frame_rate(mut self, frames_per_second: u32) -> Self {
+        self.frame_rate = Some(frames_per_second);
+        self
+    }
+}
+
+impl Element for Subsurface {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.refine(&self.style);
+        let layout_id = window.request_layout(style, [], cx);
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        _cx: &mut App,
+    ) -> Self::PrepaintState {
+        window.insert_subsurface(self.view.clone(), bounds, self.frame_rate);
+    }
+
+    fn paint(
+        &mut self,
+        _global_id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) {
+    }
+}
+
+impl IntoElement for Subsurface {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Styled for Subsurface {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
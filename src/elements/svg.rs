@@ -122,7 +122,7 @@ impl Element for Svg {
                         .transformation
                         .as_ref()
                         .map(|transformation| {
-                            transformation.into_matrix(bounds.center(), window.scale_factor())
+                            transformation.into_matrix(bounds.center(), window.rasterization_scale())
                         })
                         .unwrap_or_default();
 
@@ -143,7 +143,7 @@ impl Element for Svg {
                         .transformation
                         .as_ref()
                         .map(|transformation| {
-                            transformation.into_matrix(bounds.center(), window.scale_factor())
+                            transformation.into_matrix(bounds.center(), window.rasterization_scale())
                         })
                         .unwrap_or_default();
 
@@ -248,7 +248,9 @@ impl Transformation {
         self
     }
 
-    fn into_matrix(self, center: Point<Pixels>, scale_factor: f32) -> TransformationMatrix {
+    /// Converts the transformation into a matrix in the scaled pixels of the scene, rotating and
+    /// scaling around `center`, as given to [`crate::Window::with_element_transformation`].
+    pub fn into_matrix(self, center: Point<Pixels>, scale_factor: f32) -> TransformationMatrix {
         //Note: if you read this as a sequence of matrix multiplications, start from the bottom
         TransformationMatrix::unit()
             .translate(center.scale(scale_factor) + self.translate.scale(scale_factor))
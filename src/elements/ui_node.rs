@@ -0,0 +1,208 @@
+use crate::{
+    AnyElement, ElementId, InteractiveElement, IntoElement, ParentElement, SharedString,
+    StatefulInteractiveElement, StyleRefinement, Styled, div, img,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A description of an element tree as data, for UI that is sent by a server or exported from a
+/// design tool. Nodes are converted into elements with [`IntoElement`].
+///
+/// ```json
+/// {
+///   "type": "div",
+///   "style": { "padding": { "top": "8px", "bottom": "8px" } },
+///   "on_click": { "name": "editor::Save" },
+///   "children": [{ "type": "text", "text": "Save" }]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UiNode {
+    /// A [`div`] containing other nodes.
+    Div {
+        /// Identifies the div among its siblings, so that its state, such as a pending click,
+        /// stays with it when siblings are inserted or reordered. Defaults to its index.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<SharedString>,
+        /// The style of the div.
+        #[serde(default)]
+        style: StyleRefinement,
+        /// The action dispatched when the div is clicked.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        on_click: Option<ActionBinding>,
+        /// The nodes inside the div.
+        #[serde(default)]
+        children: Vec<UiNode>,
+    },
+    /// A run of text, styled by its own style and those of the nodes containing it.
+    Text {
+        /// The text to show.
+        text: SharedString,
+        /// The style of the text.
+        #[serde(default)]
+        style: StyleRefinement,
+    },
+    /// An [`img`] loaded from a URI or an embedded asset path.
+    Img {
+        /// The URI or asset path of the image.
+        source: SharedString,
+        /// The style of the image.
+        #[serde(default)]
+        style: StyleRefinement,
+    },
+}
+
+/// An action named by the name it's registered under, as used in keymaps.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionBinding {
+    /// The name of the action, such as `"editor::Save"`.
+    pub name: SharedString,
+    /// The data the action is built from, for actions with fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl UiNode {
+    /// Returns the id of the node at `index` among its siblings: its key, or else its index.
+    fn id(&self, index: usize) -> ElementId {
+        match self {
+            UiNode::Div { key: Some(key), .. } => ElementId::Name(key.clone()),
+            _ => ElementId::NamedInteger("ui-node".into(), index as u64),
+        }
+    }
+
+    fn into_any_element(self, id: ElementId) -> AnyElement {
+        match self {
+            UiNode::Div {
+                key: _,
+                style,
+                on_click,
+                children,
+            } => {
+                // Every div has an id, so the global id of each div is its path from the root,
+                // and divs at the same index in different subtrees don't share element state.
+                let mut element = div().id(id).children(children.into_iter().enumerate().map(
+                    |(index, child)| {
+                        let id = child.id(index);
+                        child.into_any_element(id)
+                    },
+                ));
+                *element.style() = style;
+                if let Some(binding) = on_click {
+                    element = element.on_click(move |_, window, cx| {
+                        match cx.build_action(&binding.name, binding.data.clone()) {
+                            Ok(action) => window.dispatch_action(action, cx),
+                            Err(error) => log::error!("failed to build action: {error}"),
+                        }
+                    });
+                }
+                element.into_any_element()
+            }
+            UiNode::Text { text, style } => {
+                let mut element = div().child(text);
+                *element.style() = style;
+                element.into_any_element()
+            }
+            UiNode::Img { source, style } => {
+                let mut element = img(source);
+                *element.style() = style;
+                element.into_any_element()
+            }
+        }
+    }
+}
+
+impl IntoElement for UiNode {
+    type Element = AnyElement;
+
+    fn into_element(self) -> Self::Element {
+        let id = self.id(0);
+        self.into_any_element(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as gpui, Context, Render, TestAppContext, Window, px};
+
+    #[test]
+    fn test_deserialize_ui_node() {
+        let node: UiNode = serde_json::from_str(
+            r#"{
+                "type": "div",
+                "style": { "size": { "width": "100px" } },
+                "on_click": { "name": "test::Save" },
+                "children": [
+                    { "type": "text", "text": "Save" },
+                    { "type": "img", "source": "icons/save.svg" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let UiNode::Div {
+            style,
+            on_click,
+            children,
+            ..
+        } = &node
+        else {
+            panic!("expected a div, got {node:?}");
+        };
+        assert_eq!(style.size.width, Some(px(100.).into()));
+        assert_eq!(
+            on_click.as_ref().map(|binding| &binding.name),
+            Some(&"test::Save".into())
+        );
+        assert_eq!(children.len(), 2);
+
+        let json = serde_json::to_string(&node).unwrap();
+        assert_eq!(serde_json::from_str::<UiNode>(&json).unwrap(), node);
+    }
+
+    struct Tree(UiNode);
+
+    impl Render for Tree {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.0.clone()
+        }
+    }
+
+    #[gpui::test]
+    fn test_node_ids_are_paths(cx: &mut TestAppContext) {
+        let node = serde_json::from_str(
+            r#"{
+                "type": "div",
+                "children": [
+                    { "type": "div", "children": [{ "type": "div", "on_click": { "name": "a" } }] },
+                    {
+                        "type": "div",
+                        "children": [{ "type": "div", "key": "save", "on_click": { "name": "b" } }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let (_, cx) = cx.add_window_view(|_, _| Tree(node));
+        let ids = cx.update(|window, _| {
+            window
+                .rendered_frame
+                .element_hitboxes
+                .iter()
+                // Skip the id of the view the tree is rendered in.
+                .map(|(global_id, _)| global_id.0[1..].to_vec())
+                .collect::<Vec<_>>()
+        });
+
+        let index_id = |index| ElementId::NamedInteger("ui-node".into(), index);
+        assert_eq!(
+            ids,
+            [
+                vec![index_id(0), index_id(0), index_id(0)],
+                vec![index_id(0), index_id(1), ElementId::Name("save".into())],
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    App, AppContext as _, Bounds, Context, Entity, EventEmitter, IntoElement, Pixels, Render,
+    SharedString, Styled, Task, Window, canvas,
+};
+use anyhow::{Context as _, Result};
+use futures::{StreamExt, channel::mpsc};
+use raw_window_handle::RawWindowHandle;
+use util::ResultExt;
+use wry::{
+    PageLoadEvent, Rect, WebViewBuilder,
+    dpi::{LogicalPosition, LogicalSize},
+};
+
+/// An event emitted by a [`WebView`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebViewEvent {
+    /// The web view started loading a page.
+    NavigationStarted(SharedString),
+    /// The web view finished loading a page.
+    NavigationFinished(SharedString),
+    /// The page sent a message with `window.ipc.postMessage(message)`.
+    Message(String),
+}
+
+/// A web page shown inline in a window, for OAuth flows, documentation and other web content.
+///
+/// The page is rendered by the system's web engine (WKWebView on macOS, WebView2 on Windows)
+/// and is embedded with [`crate::native_view`], so it follows the same layout, clipping and
+/// z-order rules: the web view is drawn above GPUI content that overlaps it. Like native views,
+/// web views are only available on macOS and Windows, and enabling the `webview` feature on
+/// other platforms fails to compile.
+pub struct WebView {
+    webview: wry::WebView,
+    size: Option<crate::Size<Pixels>>,
+    _events: Task<()>,
+    // Declared after `webview` so the web view is dropped before its container.
+    #[cfg(target_os = "windows")]
+    container: windows_container::Container,
+}
+
+/// Create a web view showing `url` in `window`.
+pub fn webview(
+    url: impl Into<SharedString>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Result<Entity<WebView>> {
+    let url = url.into();
+    let (events_tx, mut events_rx) = mpsc::unbounded();
+    let builder = WebViewBuilder::new()
+        .with_url(url.as_ref())
+        .with_bounds(Rect {
+            position: LogicalPosition::new(0., 0.).into(),
+            size: LogicalSize::new(0., 0.).into(),
+        })
+        .with_ipc_handler({
+            let events_tx = events_tx.clone();
+            move |request| {
+                events_tx
+                    .unbounded_send(WebViewEvent::Message(request.into_body()))
+                    .ok();
+            }
+        })
+        .with_on_page_load_handler(move |event, url| {
+            let event = match event {
+                PageLoadEvent::Started => WebViewEvent::NavigationStarted(url.into()),
+                PageLoadEvent::Finished => WebViewEvent::NavigationFinished(url.into()),
+            };
+            events_tx.unbounded_send(event).ok();
+        });
+
+    #[cfg(target_os = "macos")]
+    let webview = builder
+        .build_as_child(&*window)
+        .context("failed to create web view")?;
+    #[cfg(target_os = "windows")]
+    let container = windows_container::Container::new(window)?;
+    #[cfg(target_os = "windows")]
+    let webview = builder
+        .build_as_child(&container)
+        .context("failed to create web view")?;
+
+    Ok(cx.new(|cx| {
+        let events = cx.spawn(async move |this, cx| {
+            while let Some(event) = events_rx.next().await {
+                let emitted = this.update(cx, |_, cx| cx.emit(event));
+                if emitted.is_err() {
+                    break;
+                }
+            }
+        });
+        WebView {
+            webview,
+            size: None,
+            _events: events,
+            #[cfg(target_os = "windows")]
+            container,
+        }
+    }))
+}
+
+impl WebView {
+    /// Navigates to `url`.
+    pub fn load_url(&self, url: &str) -> Result<()> {
+        self.webview
+            .load_url(url)
+            .context("failed to load url in web view")
+    }
+
+    /// Returns the url of the page being shown.
+    pub fn url(&self) -> Result<String> {
+        self.webview.url().context("failed to get web view url")
+    }
+
+    /// Runs `script` in the page.
+    pub fn evaluate_script(&self, script: &str) -> Result<()> {
+        self.webview
+            .evaluate_script(script)
+            .context("failed to evaluate script in web view")
+    }
+
+    /// Sends `message` to the page, which receives it as the `data` of a `message` event on
+    /// `window`.
+    pub fn post_message(&self, message: &str) -> Result<()> {
+        let data = serde_json::to_string(message)?;
+        self.evaluate_script(&format!(
+            "window.dispatchEvent(new MessageEvent('message', {{ data: {data} }}));"
+        ))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn platform_view(&self) -> RawWindowHandle {
+        use wry::WebViewExtMacOS as _;
+        let view = self.webview.webview();
+        let view = std::ptr::NonNull::from(&*view).cast();
+        raw_window_handle::AppKitWindowHandle::new(view).into()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn platform_view(&self) -> RawWindowHandle {
+        self.container.raw_window_handle()
+    }
+
+    fn layout(&mut self, bounds: Bounds<Pixels>) {
+        // On Windows the container is resized by the native view, but the web view inside it
+        // has to be resized to fill it.
+        if cfg!(target_os = "windows") && self.size != Some(bounds.size) {
+            self.size = Some(bounds.size);
+            self.webview
+                .set_bounds(Rect {
+                    position: LogicalPosition::new(0., 0.).into(),
+                    size: LogicalSize::new(bounds.size.width.0, bounds.size.height.0).into(),
+                })
+                .log_err();
+        }
+    }
+}
+
+impl EventEmitter<WebViewEvent> for WebView {}
+
+impl Render for WebView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let this = cx.entity();
+        let view = self.platform_view();
+        canvas(
+            |_, _, _| {},
+            move |bounds, _, window, cx| {
+                this.update(cx, |this, _| this.layout(bounds));
+                window.paint_native_view("web-view", bounds, view);
+            },
+        )
+        .size_full()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_container {
+    use crate::Window;
+    use anyhow::{Context as _, Result, anyhow};
+    use raw_window_handle::{self as rwh, HasWindowHandle};
+    use std::num::NonZeroIsize;
+    use windows::Win32::{
+        Foundation::HWND,
+        UI::WindowsAndMessaging::{
+            CreateWindowExW, DestroyWindow, WINDOW_EX_STYLE, WS_CHILD, WS_CLIPCHILDREN,
+        },
+    };
+    use windows::core::w;
+
+    /// A child window of a GPUI window that hosts a web view, so that it can be positioned and
+    /// clipped as a native view.
+    pub(super) struct Container(NonZeroIsize);
+
+    impl Container {
+        pub(super) fn new(window: &Window) -> Result<Self> {
+            let rwh::RawWindowHandle::Win32(parent) =
+                HasWindowHandle::window_handle(window)?.as_raw()
+            else {
+                return Err(anyhow!("expected a Win32 window"));
+            };
+            let parent = HWND(parent.hwnd.get() as _);
+            let container = unsafe {
+                CreateWindowExW(
+                    WINDOW_EX_STYLE::default(),
+                    w!("STATIC"),
+                    None,
+                    WS_CHILD | WS_CLIPCHILDREN,
+                    0,
+                    0,
+                    0,
+                    0,
+                    Some(parent),
+                    None,
+                    None,
+                    None,
+                )
+            }
+            .context("failed to create web view container")?;
+            let Some(container) = NonZeroIsize::new(container.0 as isize) else {
+                return Err(anyhow!("web view container has a null window handle"));
+            };
+            Ok(Self(container))
+        }
+
+        pub(super) fn raw_window_handle(&self) -> rwh::RawWindowHandle {
+            rwh::Win32WindowHandle::new(self.0).into()
+        }
+    }
+
+    impl rwh::HasWindowHandle for Container {
+        fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+            Ok(unsafe { rwh::WindowHandle::borrow_raw(self.raw_window_handle()) })
+        }
+    }
+
+    impl Drop for Container {
+        fn drop(&mut self) {
+            unsafe { DestroyWindow(HWND(self.0.get() as _)) }.ok();
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Extension functions for geometry types that require access to the application context.
 
-use crate::{App, Bounds, DisplayId, Pixels, Size, point, px, size};
+use crate::{App, Bounds, DisplayId, Pixels, Size, Window, point, px, size};
+
+/// How far each window opened with [`cascade_from`] is offset from the one before it.
+const CASCADE_OFFSET: Pixels = px(24.);
 
 /// Generate centered bounds for the given display or primary display if none is provided.
 pub fn centered_bounds(
@@ -33,3 +36,86 @@ pub fn maximized_bounds(display_id: Option<DisplayId>, cx: &App) -> Bounds<Pixel
             size: size(px(1024.), px(768.)),
         })
 }
+
+/// Generate bounds of the given size centered in the work area of the given display, the area
+/// not covered by docks and taskbars. The size is clamped to fit the work area.
+pub fn centered_on_display(display_id: DisplayId, size: Size<Pixels>, cx: &App) -> Bounds<Pixels> {
+    match cx.find_display(display_id) {
+        Some(display) => {
+            let work_area = display.visible_bounds();
+            Bounds::centered_at(work_area.center(), size.min(&work_area.size))
+        }
+        None => centered_bounds(None, size, cx),
+    }
+}
+
+/// Generate bounds for a window that covers the given fraction of the work area of the given
+/// display, or the primary display if none is provided, centered in it. For example, a
+/// fraction of `size(0.5, 0.75)` covers half the work area's width and three quarters of its
+/// height.
+pub fn fraction_of_display(
+    display_id: Option<DisplayId>,
+    fraction: Size<f32>,
+    cx: &App,
+) -> Bounds<Pixels> {
+    let display = display_id
+        .and_then(|id| cx.find_display(id))
+        .or_else(|| cx.primary_display());
+
+    match display {
+        Some(display) => {
+            let work_area = display.visible_bounds();
+            let size = size(
+                work_area.size.width * fraction.width.clamp(0., 1.),
+                work_area.size.height * fraction.height.clamp(0., 1.),
+            );
+            Bounds::centered_at(work_area.center(), size)
+        }
+        None => maximized_bounds(None, cx),
+    }
+}
+
+/// Generate bounds for a new window cascaded from the given window: the same size, offset
+/// down and to the right, on the same display. When the offset window wouldn't fit in the
+/// display's work area, it starts again from the work area's top left corner.
+pub fn cascade_from(window: &Window, cx: &App) -> Bounds<Pixels> {
+    let bounds = window.bounds();
+    match window.display(cx) {
+        Some(display) => cascade(bounds, display.visible_bounds()),
+        None => Bounds::new(
+            bounds.origin + point(CASCADE_OFFSET, CASCADE_OFFSET),
+            bounds.size,
+        ),
+    }
+}
+
+fn cascade(bounds: Bounds<Pixels>, work_area: Bounds<Pixels>) -> Bounds<Pixels> {
+    let size = bounds.size.min(&work_area.size);
+    let origin = bounds.origin + point(CASCADE_OFFSET, CASCADE_OFFSET);
+    if origin.x + size.width > work_area.right() || origin.y + size.height > work_area.bottom() {
+        Bounds::new(work_area.origin, size)
+    } else {
+        Bounds::new(origin, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cascade_wraps_to_work_area() {
+        let work_area = Bounds::new(point(px(0.), px(30.)), size(px(1000.), px(800.)));
+        let window = Bounds::new(point(px(100.), px(100.)), size(px(600.), px(400.)));
+        assert_eq!(
+            cascade(window, work_area),
+            Bounds::new(point(px(124.), px(124.)), window.size)
+        );
+
+        let window = Bounds::new(point(px(390.), px(100.)), size(px(600.), px(400.)));
+        assert_eq!(
+            cascade(window, work_area),
+            Bounds::new(work_area.origin, window.size)
+        );
+    }
+}
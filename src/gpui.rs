@@ -13,6 +13,7 @@ mod app;
 mod arena;
 mod asset_cache;
 mod assets;
+mod crash_report;
 /// The default colors used by GPUI.
 pub mod default_colors;
 mod element;
@@ -21,11 +22,14 @@ mod executor;
 mod geometry_ext;
 pub use geometry_ext::*;
 mod global;
+mod gpui_extension;
 mod input;
 mod inspector;
 mod interactive;
 mod key_dispatch;
 mod keymap;
+mod locale;
+mod logger;
 mod path_builder;
 mod platform;
 pub mod prelude;
@@ -53,6 +57,7 @@ pub mod _ownership_and_data_flow;
 pub mod private {
     pub use anyhow;
     pub use inventory;
+    pub use rust_embed;
     pub use schemars;
     pub use serde;
     pub use serde_json;
@@ -70,6 +75,7 @@ pub use app::*;
 pub(crate) use arena::*;
 pub use asset_cache::*;
 pub use assets::*;
+pub(crate) use crash_report::*;
 pub use ctor::ctor;
 pub use default_colors::*;
 pub use element::*;
@@ -77,6 +83,7 @@ pub use elements::*;
 pub use executor::*;
 pub use global::*;
 pub use gpui_core::*;
+pub use gpui_extension::*;
 pub use gpui_macros::{AppContext, IntoElement, Render, VisualContext, register_action, test};
 pub use http_client;
 pub use input::*;
@@ -84,6 +91,8 @@ pub use inspector::*;
 pub use interactive::*;
 use key_dispatch::*;
 pub use keymap::*;
+pub use locale::*;
+pub use logger::*;
 pub use path_builder::*;
 pub use platform::*;
 pub use profiler::*;
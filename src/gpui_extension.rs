@@ -0,0 +1,436 @@
+//! A stable C ABI through which extensions loaded at runtime can add panels to GPUI apps, without
+//! the host app being recompiled.
+//!
+//! An extension is a `cdylib` that exports a function named [`GPUI_EXTENSION_ENTRY_SYMBOL`] of
+//! type [`ExtensionEntry`], which returns a static [`ExtensionDescriptor`]. Panels built by the
+//! extension are opaque to GPUI: it asks the extension to create one, to render it into an
+//! [`ExtensionElementBuilder`] whenever it's drawn, and to handle clicks on the containers it
+//! marked as clickable. The builder only offers styled containers and text, so extensions can't
+//! reach into the host app's entities or windows.
+//!
+//! Only the types in this module, all `#[repr(C)]`, cross the boundary. Extensions built against
+//! a different [`GPUI_EXTENSION_ABI_VERSION`] are rejected when they're loaded.
+
+use crate::{
+    AbsoluteLength, App, AppContext as _, Context, DefiniteLength, Div, EdgesRefinement, Entity,
+    InteractiveElement as _, IntoElement, ParentElement as _, Render, Rgba, SharedString,
+    StatefulInteractiveElement as _, Styled as _, WeakEntity, Window, div, px,
+};
+use anyhow::{Context as _, Result, anyhow};
+use std::ffi::{CStr, c_char, c_void};
+
+/// The version of the extension ABI that this build of GPUI implements. It's bumped whenever a
+/// type in this module changes layout.
+pub const GPUI_EXTENSION_ABI_VERSION: u32 = 1;
+
+/// The symbol that extensions export their [`ExtensionEntry`] as.
+pub const GPUI_EXTENSION_ENTRY_SYMBOL: &str = "gpui_extension_entry";
+
+/// The function that extensions export as [`GPUI_EXTENSION_ENTRY_SYMBOL`]. It returns a
+/// descriptor that must stay valid for as long as the extension is loaded.
+pub type ExtensionEntry = unsafe extern "C" fn() -> *const ExtensionDescriptor;
+
+/// What an extension provides, as returned by its [`ExtensionEntry`].
+///
+/// None of these functions may unwind across the boundary.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct ExtensionDescriptor {
+    /// The [`GPUI_EXTENSION_ABI_VERSION`] the extension was built against.
+    pub abi_version: u32,
+    /// The extension's name, as a NUL-terminated UTF-8 string.
+    pub name: *const c_char,
+    /// Creates the state of a new panel, which is passed back to the other functions.
+    pub create_panel: unsafe extern "C" fn() -> *mut c_void,
+    /// Builds a panel's elements by calling into `builder`, which is only valid during the call.
+    pub render_panel:
+        unsafe extern "C" fn(panel: *mut c_void, builder: *const ExtensionElementBuilder),
+    /// Handles a click on a container opened with the given `click_id`, returning whether the
+    /// panel needs to be rendered again.
+    pub click_panel: unsafe extern "C" fn(panel: *mut c_void, click_id: u64) -> bool,
+    /// Frees the state of a panel created with `create_panel`.
+    pub free_panel: unsafe extern "C" fn(panel: *mut c_void),
+}
+
+/// The functions an extension builds a panel's elements with. Every function takes `context` as
+/// its first argument.
+///
+/// Containers are opened and closed like tags: elements added while a container is open become
+/// its children, and containers left open when rendering returns are closed by GPUI.
+#[derive(Debug)]
+#[repr(C)]
+pub struct ExtensionElementBuilder {
+    /// GPUI's state for the panel being built.
+    pub context: *mut c_void,
+    /// Opens a container styled by `style`, or by the default style if `style` is null.
+    pub open_container:
+        unsafe extern "C" fn(context: *mut c_void, style: *const ExtensionContainerStyle),
+    /// Closes the most recently opened container.
+    pub close_container: unsafe extern "C" fn(context: *mut c_void),
+    /// Adds `len` bytes of UTF-8 text at `text`, styled by `style`.
+    pub text: unsafe extern "C" fn(
+        context: *mut c_void,
+        text: *const u8,
+        len: usize,
+        style: *const ExtensionTextStyle,
+    ),
+}
+
+/// The direction a container lays out its children in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub enum ExtensionLayoutDirection {
+    /// Top to bottom.
+    #[default]
+    Column,
+    /// Left to right.
+    Row,
+}
+
+/// The style of a container built by an extension. Lengths are in logical pixels.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct ExtensionContainerStyle {
+    /// The direction the container lays out its children in.
+    pub direction: ExtensionLayoutDirection,
+    /// The space between the container's children.
+    pub gap: f32,
+    /// The space between the container's edges and its children.
+    pub padding: f32,
+    /// The color of the container's background.
+    pub background: Rgba,
+    /// The color of the container's border.
+    pub border_color: Rgba,
+    /// The width of the container's border.
+    pub border_width: f32,
+    /// The radius of the container's corners.
+    pub corner_radius: f32,
+    /// The id passed to [`ExtensionDescriptor::click_panel`] when the container is clicked, or
+    /// zero if the container isn't clickable.
+    pub click_id: u64,
+}
+
+/// The style of text built by an extension.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct ExtensionTextStyle {
+    /// The color of the text, or transparent to inherit it.
+    pub color: Rgba,
+    /// The font size of the text in logical pixels, or zero to inherit it.
+    pub font_size: f32,
+}
+
+/// An extension whose panels can be added to windows.
+#[derive(Clone)]
+pub struct Extension {
+    name: SharedString,
+    descriptor: ExtensionDescriptor,
+    #[cfg(feature = "extensions")]
+    _library: Option<std::sync::Arc<libloading::Library>>,
+}
+
+impl Extension {
+    /// Loads the extension from the dynamic library at `path`, which stays loaded until the
+    /// extension and all of its panels have been dropped.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initializers, and GPUI trusts the descriptor it returns, so
+    /// `path` must be an extension built for this ABI.
+    #[cfg(feature = "extensions")]
+    pub unsafe fn load(path: &std::path::Path) -> Result<Self> {
+        let library = unsafe { libloading::Library::new(path) }
+            .with_context(|| format!("loading extension {}", path.display()))?;
+        let descriptor = unsafe {
+            let entry = library
+                .get::<ExtensionEntry>(GPUI_EXTENSION_ENTRY_SYMBOL.as_bytes())
+                .with_context(|| {
+                    format!(
+                        "extension {} doesn't export {GPUI_EXTENSION_ENTRY_SYMBOL}",
+                        path.display()
+                    )
+                })?;
+            entry().as_ref()
+        }
+        .copied()
+        .ok_or_else(|| anyhow!("extension {} returned no descriptor", path.display()))?;
+        let mut extension = unsafe { Self::from_descriptor(descriptor) }?;
+        extension._library = Some(std::sync::Arc::new(library));
+        Ok(extension)
+    }
+
+    /// Creates an extension from a descriptor that's linked into the app, rather than loaded.
+    ///
+    /// # Safety
+    ///
+    /// The descriptor's name and functions must be valid for as long as the extension and its
+    /// panels are alive.
+    pub unsafe fn from_descriptor(descriptor: ExtensionDescriptor) -> Result<Self> {
+        if descriptor.abi_version != GPUI_EXTENSION_ABI_VERSION {
+            return Err(anyhow!(
+                "extension was built for ABI version {}, but GPUI implements version {}",
+                descriptor.abi_version,
+                GPUI_EXTENSION_ABI_VERSION
+            ));
+        }
+        if descriptor.name.is_null() {
+            return Err(anyhow!("extension has no name"));
+        }
+        let name = unsafe { CStr::from_ptr(descriptor.name) }
+            .to_str()
+            .context("extension name isn't UTF-8")?
+            .to_string();
+        Ok(Self {
+            name: name.into(),
+            descriptor,
+            #[cfg(feature = "extensions")]
+            _library: None,
+        })
+    }
+
+    /// Returns the extension's name.
+    pub fn name(&self) -> &SharedString {
+        &self.name
+    }
+
+    /// Creates a new panel built by the extension, which can be rendered as a child of any
+    /// element.
+    pub fn new_panel(&self, cx: &mut App) -> Entity<ExtensionPanel> {
+        let state = unsafe { (self.descriptor.create_panel)() };
+        cx.new(|_| ExtensionPanel {
+            extension: self.clone(),
+            state,
+        })
+    }
+}
+
+/// A panel built by an [`Extension`], created with [`Extension::new_panel`].
+pub struct ExtensionPanel {
+    extension: Extension,
+    state: *mut c_void,
+}
+
+impl ExtensionPanel {
+    /// Returns the extension that builds this panel.
+    pub fn extension(&self) -> &Extension {
+        &self.extension
+    }
+
+    fn click(&mut self, click_id: u64, cx: &mut Context<Self>) {
+        if unsafe { (self.extension.descriptor.click_panel)(self.state, click_id) } {
+            cx.notify();
+        }
+    }
+}
+
+impl Drop for ExtensionPanel {
+    fn drop(&mut self) {
+        unsafe { (self.extension.descriptor.free_panel)(self.state) };
+    }
+}
+
+impl Render for ExtensionPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut tree = ElementTree {
+            panel: cx.weak_entity(),
+            containers: vec![(div().size_full(), 0)],
+        };
+        let builder = ExtensionElementBuilder {
+            context: (&mut tree as *mut ElementTree).cast(),
+            open_container,
+            close_container,
+            text,
+        };
+        unsafe { (self.extension.descriptor.render_panel)(self.state, &builder) };
+        while tree.containers.len() > 1 {
+            tree.close();
+        }
+        tree.containers
+            .pop()
+            .map(|(root, _)| root)
+            .unwrap_or_else(div)
+    }
+}
+
+struct ElementTree {
+    panel: WeakEntity<ExtensionPanel>,
+    containers: Vec<(Div, u64)>,
+}
+
+impl ElementTree {
+    fn close(&mut self) {
+        if self.containers.len() < 2 {
+            return;
+        }
+        let Some((container, click_id)) = self.containers.pop() else {
+            return;
+        };
+        let element = if click_id == 0 {
+            container.into_any_element()
+        } else {
+            let panel = self.panel.clone();
+            container
+                .id(("extension-container", click_id))
+                .on_click(move |_, _, cx| {
+                    panel.update(cx, |panel, cx| panel.click(click_id, cx)).ok();
+                })
+                .into_any_element()
+        };
+        if let Some((parent, _)) = self.containers.last_mut() {
+            parent.extend([element]);
+        }
+    }
+}
+
+unsafe extern "C" fn open_container(context: *mut c_void, style: *const ExtensionContainerStyle) {
+    let Some(tree) = (unsafe { context.cast::<ElementTree>().as_mut() }) else {
+        return;
+    };
+    // Opening a container even without a style keeps it paired with its `close_container`.
+    let style = unsafe { style.as_ref() }.copied().unwrap_or_default();
+    let mut container = div()
+        .flex()
+        .p(px(style.padding))
+        .rounded(px(style.corner_radius));
+    container = match style.direction {
+        ExtensionLayoutDirection::Column => container.flex_col(),
+        ExtensionLayoutDirection::Row => container.flex_row(),
+    };
+    if style.background.a > 0. {
+        container = container.bg(style.background);
+    }
+    if style.border_width > 0. {
+        container = container.border_color(style.border_color);
+        let border_width = Some(AbsoluteLength::from(px(style.border_width)));
+        container.style().border_widths = EdgesRefinement {
+            top: border_width,
+            right: border_width,
+            bottom: border_width,
+            left: border_width,
+        };
+    }
+    let gap = DefiniteLength::from(px(style.gap));
+    container.style().gap.width = Some(gap);
+    container.style().gap.height = Some(gap);
+    tree.containers.push((container, style.click_id));
+}
+
+unsafe extern "C" fn close_container(context: *mut c_void) {
+    if let Some(tree) = unsafe { context.cast::<ElementTree>().as_mut() } {
+        tree.close();
+    }
+}
+
+unsafe extern "C" fn text(
+    context: *mut c_void,
+    text: *const u8,
+    len: usize,
+    style: *const ExtensionTextStyle,
+) {
+    let Some(tree) = (unsafe { context.cast::<ElementTree>().as_mut() }) else {
+        return;
+    };
+    if text.is_null() {
+        return;
+    }
+    let text = String::from_utf8_lossy(unsafe { std::slice::from_raw_parts(text, len) });
+    let style = unsafe { style.as_ref() }.copied().unwrap_or_default();
+    let mut element = div().child(SharedString::from(text.into_owned()));
+    if style.color.a > 0. {
+        element = element.text_color(style.color);
+    }
+    if style.font_size > 0. {
+        element = element.text_size(px(style.font_size));
+    }
+    if let Some((parent, _)) = tree.containers.last_mut() {
+        parent.extend([element.into_any_element()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Modifiers, TestAppContext, point};
+
+    unsafe extern "C" fn create_panel() -> *mut c_void {
+        Box::into_raw(Box::new(0u64)).cast()
+    }
+
+    unsafe extern "C" fn render_panel(panel: *mut c_void, builder: *const ExtensionElementBuilder) {
+        let (clicks, builder) = unsafe { (*panel.cast::<u64>(), &*builder) };
+        let label = format!("clicked {clicks} times");
+        let style = ExtensionContainerStyle {
+            click_id: 1,
+            ..Default::default()
+        };
+        unsafe {
+            (builder.open_container)(builder.context, &style);
+            (builder.text)(
+                builder.context,
+                label.as_ptr(),
+                label.len(),
+                std::ptr::null(),
+            );
+        }
+    }
+
+    unsafe extern "C" fn click_panel(panel: *mut c_void, click_id: u64) -> bool {
+        unsafe { *panel.cast::<u64>() += click_id };
+        true
+    }
+
+    unsafe extern "C" fn free_panel(panel: *mut c_void) {
+        drop(unsafe { Box::from_raw(panel.cast::<u64>()) });
+    }
+
+    fn descriptor(abi_version: u32) -> ExtensionDescriptor {
+        ExtensionDescriptor {
+            abi_version,
+            name: c"counter".as_ptr(),
+            create_panel,
+            render_panel,
+            click_panel,
+            free_panel,
+        }
+    }
+
+    #[gpui::test]
+    fn test_extension_panel(cx: &mut TestAppContext) {
+        assert!(unsafe { Extension::from_descriptor(descriptor(0)) }.is_err());
+
+        let extension = unsafe { Extension::from_descriptor(descriptor(1)) }.unwrap();
+        assert_eq!(extension.name().as_ref(), "counter");
+
+        let panel = cx.update(|cx| extension.new_panel(cx));
+        let (_, cx) = cx.add_window_view({
+            let panel = panel.clone();
+            |_, _| PanelRoot(panel)
+        });
+        cx.run_until_parked();
+        cx.simulate_click(point(px(5.), px(5.)), Modifiers::none());
+        panel.read_with(cx, |panel, _| {
+            assert_eq!(unsafe { *panel.state.cast::<u64>() }, 1);
+        });
+    }
+
+    #[test]
+    fn test_container_without_style_is_balanced() {
+        let mut tree = ElementTree {
+            panel: WeakEntity::new_invalid(),
+            containers: vec![(div(), 0)],
+        };
+        let context = (&mut tree as *mut ElementTree).cast();
+        unsafe { open_container(context, std::ptr::null()) };
+        assert_eq!(tree.containers.len(), 2);
+        unsafe { close_container(context) };
+        assert_eq!(tree.containers.len(), 1);
+    }
+
+    struct PanelRoot(Entity<ExtensionPanel>);
+
+    impl Render for PanelRoot {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(self.0.clone())
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
-    Bounds, Capslock, Context, Empty, IntoElement, Keystroke, Modifiers, Pixels, Point, Render,
-    Window, point, seal::Sealed,
+    App, Bounds, Capslock, Context, Empty, IntoElement, Keystroke, Modifiers, Pixels, Point,
+    Render, Window, point, seal::Sealed,
 };
 use smallvec::SmallVec;
-use std::{any::Any, fmt::Debug, ops::Deref, path::PathBuf};
+use std::{any::Any, fmt::Debug, ops::Deref, path::PathBuf, time::Instant};
 
 /// An event from a platform input source.
 pub trait InputEvent: Sealed + 'static {
@@ -17,6 +17,34 @@ pub trait KeyEvent: InputEvent {}
 /// A mouse event from the platform.
 pub trait MouseEvent: InputEvent {}
 
+/// Controls over an event that's being dispatched, for the listeners it's dispatched to, like the
+/// DOM's `Event.stopPropagation()` and `Event.preventDefault()`. They only affect the dispatch
+/// in progress.
+pub trait DispatchedEvent {
+    /// Keeps the event from the listeners after this one. Called from a bubble phase listener,
+    /// this skips the elements further from the target. Called from a capture phase listener,
+    /// such as one registered with [`crate::InteractiveElement::capture_key_down`] or
+    /// [`crate::StatefulInteractiveElement::on_click_capture`], this keeps the event from the
+    /// elements inside the listener's element. See [`App::stop_propagation`].
+    fn stop_propagation(&self, cx: &mut App) {
+        cx.stop_propagation();
+    }
+
+    /// Prevents the event's default action without stopping its propagation: for a mouse down,
+    /// focusing the elements around the target, and for a key down, inserting its text. See
+    /// [`Window::prevent_default`].
+    fn prevent_default(&self, window: &mut Window) {
+        window.prevent_default();
+    }
+
+    /// Returns whether a listener that ran earlier prevented the event's default action.
+    fn default_prevented(&self, window: &Window) -> bool {
+        window.default_prevented()
+    }
+}
+
+impl<E: InputEvent> DispatchedEvent for E {}
+
 /// The key down event equivalent for the platform.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct KeyDownEvent {
@@ -29,6 +57,15 @@ pub struct KeyDownEvent {
     /// Whether to prefer character input over keybindings for this keystroke.
     /// In some cases, like AltGr on Windows, modifiers are significant for character input.
     pub prefer_character_input: bool,
+
+    /// The platform's code for the physical key, which doesn't depend on the keyboard layout:
+    /// the virtual key code on macOS, the scan code on Windows and the XKB keycode on Linux.
+    /// `None` for keystrokes that weren't typed on a keyboard.
+    pub scan_code: Option<u32>,
+
+    /// When the platform reported the event, converted from the OS event time.
+    /// `None` for events that didn't come from the platform, or whose time it didn't report.
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for KeyDownEvent {}
@@ -44,6 +81,12 @@ impl KeyEvent for KeyDownEvent {}
 pub struct KeyUpEvent {
     /// The keystroke that was released.
     pub keystroke: Keystroke,
+
+    /// The platform's code for the physical key. See [`KeyDownEvent::scan_code`].
+    pub scan_code: Option<u32>,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for KeyUpEvent {}
@@ -61,6 +104,9 @@ pub struct ModifiersChangedEvent {
     pub modifiers: Modifiers,
     /// The new state of the capslock key
     pub capslock: Capslock,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for ModifiersChangedEvent {}
@@ -109,6 +155,9 @@ pub struct MouseDownEvent {
 
     /// Whether this is the first, focusing click.
     pub first_mouse: bool,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for MouseDownEvent {}
@@ -143,6 +192,9 @@ pub struct MouseUpEvent {
 
     /// The number of times the button has been clicked.
     pub click_count: usize,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for MouseUpEvent {}
@@ -193,6 +245,8 @@ pub enum ClickEvent {
     Keyboard(KeyboardClickEvent),
 }
 
+impl DispatchedEvent for ClickEvent {}
+
 impl Default for ClickEvent {
     fn default() -> Self {
         ClickEvent::Keyboard(KeyboardClickEvent::default())
@@ -356,6 +410,9 @@ pub struct MouseMoveEvent {
 
     /// The modifiers that were held down when the mouse was moved.
     pub modifiers: Modifiers,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for MouseMoveEvent {}
@@ -387,6 +444,9 @@ pub struct ScrollWheelEvent {
 
     /// The phase of the touch event.
     pub touch_phase: TouchPhase,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for ScrollWheelEvent {}
@@ -489,6 +549,9 @@ pub struct MouseExitEvent {
     pub pressed_button: Option<MouseButton>,
     /// The modifiers that were held down when the mouse was moved.
     pub modifiers: Modifiers,
+
+    /// When the platform reported the event. See [`KeyDownEvent::timestamp`].
+    pub timestamp: Option<Instant>,
 }
 
 impl Sealed for MouseExitEvent {}
@@ -558,6 +621,18 @@ impl InputEvent for FileDropEvent {
 }
 impl MouseEvent for FileDropEvent {}
 
+/// Where an input event came from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    /// A physical input device, as reported by the platform.
+    #[default]
+    Platform,
+    /// The application itself, such as keystrokes dispatched by tests or input replay tooling.
+    Synthetic,
+    /// A remote client, identified by an id the application assigns to it.
+    Remote(u64),
+}
+
 /// An enum corresponding to all kinds of platform input events.
 #[derive(Clone, Debug)]
 pub enum PlatformInput {
@@ -582,6 +657,21 @@ pub enum PlatformInput {
 }
 
 impl PlatformInput {
+    /// Returns when the platform reported this event, if it did.
+    pub fn timestamp(&self) -> Option<Instant> {
+        match self {
+            PlatformInput::KeyDown(event) => event.timestamp,
+            PlatformInput::KeyUp(event) => event.timestamp,
+            PlatformInput::ModifiersChanged(event) => event.timestamp,
+            PlatformInput::MouseDown(event) => event.timestamp,
+            PlatformInput::MouseUp(event) => event.timestamp,
+            PlatformInput::MouseMove(event) => event.timestamp,
+            PlatformInput::MouseExited(event) => event.timestamp,
+            PlatformInput::ScrollWheel(event) => event.timestamp,
+            PlatformInput::FileDrop(_) => None,
+        }
+    }
+
     pub(crate) fn mouse_event(&self) -> Option<&dyn Any> {
         match self {
             PlatformInput::KeyDown { .. } => None,
@@ -614,14 +704,21 @@ impl PlatformInput {
 #[cfg(test)]
 mod test {
 
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
     use crate::{
-        self as gpui, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
-        KeyBinding, Keystroke, ParentElement, Render, TestAppContext, Window, div,
+        self as gpui, AppContext as _, Context, DispatchedEvent, FocusHandle, HitboxBehavior,
+        InputModality, InputSource, InteractiveElement, IntoElement, KeyBinding, KeyDownEvent,
+        Keystroke, ListenerKind, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
+        MouseMoveEvent, MouseUpEvent, ParentElement, PlatformInput, RawInputEvent, Render,
+        StatefulInteractiveElement, Styled, TestAppContext, VisualTestContext, Window, canvas, div,
+        point, px,
     };
 
     struct TestView {
         saw_key_down: bool,
         saw_action: bool,
+        key_down_sources: Vec<InputSource>,
         focus_handle: FocusHandle,
     }
 
@@ -632,9 +729,10 @@ mod test {
             div().id("testview").child(
                 div()
                     .key_context("parent")
-                    .on_key_down(cx.listener(|this, _, _, cx| {
+                    .on_key_down(cx.listener(|this, _, window, cx| {
                         cx.stop_propagation();
-                        this.saw_key_down = true
+                        this.saw_key_down = true;
+                        this.key_down_sources.push(window.input_source());
                     }))
                     .on_action(cx.listener(|this: &mut TestView, _: &TestAction, _, _| {
                         this.saw_action = true
@@ -656,6 +754,7 @@ mod test {
                 cx.new(|cx| TestView {
                     saw_key_down: false,
                     saw_action: false,
+                    key_down_sources: Vec::new(),
                     focus_handle: cx.focus_handle(),
                 })
             })
@@ -683,4 +782,431 @@ mod test {
             })
             .unwrap();
     }
+
+    #[gpui::test]
+    fn test_input_source_filter(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| TestView {
+                    saw_key_down: false,
+                    saw_action: false,
+                    key_down_sources: Vec::new(),
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+
+        let key_down = || {
+            PlatformInput::KeyDown(KeyDownEvent {
+                keystroke: Keystroke::parse("a").unwrap(),
+                is_held: false,
+                prefer_character_input: false,
+                scan_code: None,
+                timestamp: None,
+            })
+        };
+        window
+            .update(cx, |test_view, window, _cx| {
+                window.focus(&test_view.focus_handle)
+            })
+            .unwrap();
+        cx.update_window(*window, |_, window, cx| {
+            window.set_input_source_filter(|source| source != InputSource::Remote(2));
+            window.dispatch_event_from(InputSource::Remote(1), key_down(), cx);
+            window.dispatch_event_from(InputSource::Remote(2), key_down(), cx);
+            window.dispatch_event(key_down(), cx);
+        })
+        .unwrap();
+        cx.dispatch_keystroke(*window, Keystroke::parse("a").unwrap());
+
+        window
+            .update(cx, |test_view, window, _| {
+                assert_eq!(
+                    test_view.key_down_sources,
+                    [
+                        InputSource::Remote(1),
+                        InputSource::Platform,
+                        InputSource::Synthetic
+                    ]
+                );
+                assert_eq!(window.input_source(), InputSource::Platform);
+            })
+            .unwrap();
+    }
+
+    struct Viewport {
+        focus_handle: FocusHandle,
+        events: Rc<RefCell<Vec<RawInputEvent>>>,
+    }
+
+    impl Render for Viewport {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let focus_handle = self.focus_handle.clone();
+            let events = self.events.clone();
+            canvas(
+                |bounds, window, _| window.insert_hitbox(bounds, HitboxBehavior::Normal),
+                move |_, hitbox, window, _| {
+                    let events = events.clone();
+                    window.on_raw_input(&hitbox, Some(&focus_handle), move |event, _, _| {
+                        events.borrow_mut().push(event.clone())
+                    });
+                },
+            )
+            .size_full()
+        }
+    }
+
+    #[gpui::test]
+    fn test_raw_input_region(cx: &mut TestAppContext) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| Viewport {
+                    focus_handle: cx.focus_handle(),
+                    events: events.clone(),
+                })
+            })
+            .unwrap()
+        });
+
+        cx.update_window(*window, |_, window, cx| {
+            for position in [point(px(10.), px(10.)), point(px(15.), px(12.))] {
+                window.dispatch_event(
+                    PlatformInput::MouseMove(MouseMoveEvent {
+                        position,
+                        pressed_button: None,
+                        modifiers: Modifiers::default(),
+                        timestamp: None,
+                    }),
+                    cx,
+                );
+            }
+            window.dispatch_event(
+                PlatformInput::MouseDown(MouseDownEvent {
+                    button: MouseButton::Left,
+                    position: point(px(15.), px(12.)),
+                    modifiers: Modifiers::default(),
+                    click_count: 1,
+                    first_mouse: false,
+                    timestamp: None,
+                }),
+                cx,
+            );
+            window.dispatch_event(
+                PlatformInput::KeyDown(KeyDownEvent {
+                    keystroke: Keystroke::parse("w").unwrap(),
+                    is_held: false,
+                    prefer_character_input: false,
+                    scan_code: Some(13),
+                    timestamp: None,
+                }),
+                cx,
+            );
+        })
+        .unwrap();
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[1].mouse_delta, point(px(5.), px(2.)));
+        assert_eq!(events[3].scan_code, Some(13));
+        window
+            .update(cx, |viewport, window, _| {
+                assert!(viewport.focus_handle.is_focused(window));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_last_input_modality(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |_, cx| {
+                cx.new(|cx| TestView {
+                    saw_key_down: false,
+                    saw_action: false,
+                    key_down_sources: Vec::new(),
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+
+        cx.update_window(*window, |_, window, cx| {
+            assert_eq!(window.last_input_modality(), InputModality::Pointer);
+            window.dispatch_event(
+                PlatformInput::KeyDown(KeyDownEvent {
+                    keystroke: Keystroke::parse("tab").unwrap(),
+                    is_held: false,
+                    prefer_character_input: false,
+                    scan_code: None,
+                    timestamp: None,
+                }),
+                cx,
+            );
+            assert_eq!(window.last_input_modality(), InputModality::Keyboard);
+
+            window.dispatch_event(
+                PlatformInput::MouseDown(MouseDownEvent {
+                    button: MouseButton::Right,
+                    position: point(px(10.), px(10.)),
+                    modifiers: Modifiers::default(),
+                    click_count: 1,
+                    first_mouse: false,
+                    timestamp: None,
+                }),
+                cx,
+            );
+            assert_eq!(window.last_input_modality(), InputModality::Pointer);
+
+            window.dispatch_event(
+                PlatformInput::ModifiersChanged(ModifiersChangedEvent {
+                    modifiers: Modifiers::shift(),
+                    capslock: Default::default(),
+                    timestamp: None,
+                }),
+                cx,
+            );
+            assert_eq!(
+                window.last_input_modality(),
+                InputModality::Pointer,
+                "pressing a modifier on its own doesn't switch to the keyboard"
+            );
+        })
+        .unwrap();
+    }
+
+    struct ClickCaptureView {
+        stop_in_capture: bool,
+        clicks: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Render for ClickCaptureView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let stop_in_capture = self.stop_in_capture;
+            let clicks = self.clicks.clone();
+            div()
+                .id("outer")
+                .size_full()
+                .on_click_capture({
+                    let clicks = clicks.clone();
+                    move |_, _, cx| {
+                        clicks.borrow_mut().push("outer capture");
+                        if stop_in_capture {
+                            cx.stop_propagation();
+                        }
+                    }
+                })
+                .on_click({
+                    let clicks = clicks.clone();
+                    move |_, _, _| clicks.borrow_mut().push("outer")
+                })
+                .child(
+                    div()
+                        .id("inner")
+                        .size_full()
+                        .on_click(move |_, _, _| clicks.borrow_mut().push("inner")),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_click_capture(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, _| ClickCaptureView {
+            stop_in_capture: false,
+            clicks: clicks.clone(),
+        });
+
+        cx.simulate_click(point(px(10.), px(10.)), Modifiers::default());
+        assert_eq!(*clicks.borrow(), ["outer capture", "inner", "outer"]);
+
+        clicks.borrow_mut().clear();
+        view.update(cx, |view, cx| {
+            view.stop_in_capture = true;
+            cx.notify();
+        });
+        cx.simulate_click(point(px(10.), px(10.)), Modifiers::default());
+        assert_eq!(
+            *clicks.borrow(),
+            ["outer capture"],
+            "stopping propagation during capture keeps the click from its target"
+        );
+    }
+
+    struct ButtonsView {
+        clicks: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Render for ButtonsView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let button = |id: &'static str, clicks: Rc<RefCell<Vec<&'static str>>>| {
+                div()
+                    .id(id)
+                    .size(px(50.))
+                    .on_click(move |_, _, _| clicks.borrow_mut().push(id))
+            };
+            div()
+                .flex()
+                .child(button("left", self.clicks.clone()))
+                .child(button("right", self.clicks.clone()))
+        }
+    }
+
+    #[gpui::test]
+    fn test_dispatch_event_to(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| ButtonsView {
+            clicks: clicks.clone(),
+        });
+
+        cx.update(|window, cx| {
+            let mouse_down = PlatformInput::MouseDown(MouseDownEvent {
+                button: MouseButton::Left,
+                position: Default::default(),
+                modifiers: Modifiers::default(),
+                click_count: 1,
+                first_mouse: false,
+                timestamp: None,
+            });
+            let mouse_up = PlatformInput::MouseUp(MouseUpEvent {
+                button: MouseButton::Left,
+                position: Default::default(),
+                modifiers: Modifiers::default(),
+                click_count: 1,
+                timestamp: None,
+            });
+            assert!(
+                window
+                    .dispatch_event_to("right", mouse_down.clone(), cx)
+                    .is_some()
+            );
+            assert!(window.dispatch_event_to("right", mouse_up, cx).is_some());
+            assert!(
+                window
+                    .dispatch_event_to("missing", mouse_down, cx)
+                    .is_none()
+            );
+        });
+        assert_eq!(*clicks.borrow(), ["right"]);
+    }
+
+    #[gpui::test]
+    fn test_hit_test(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| ButtonsView {
+            clicks: Rc::new(RefCell::new(Vec::new())),
+        });
+
+        cx.update(|window, _| {
+            let hits = window.hit_test(point(px(60.), px(10.)));
+            assert_eq!(hits.len(), 1);
+            let hit = &hits[0];
+            assert_eq!(
+                hit.element_id.as_ref().and_then(|id| id.last()),
+                Some(&"right".into())
+            );
+            assert_eq!(hit.bounds.origin, point(px(50.), px(0.)));
+            assert_eq!(hit.listeners, [ListenerKind::Click]);
+            assert!(hit.hovered);
+
+            assert!(window.hit_test(point(px(110.), px(10.))).is_empty());
+        });
+    }
+
+    #[gpui::test]
+    fn test_input_recording(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view(|_, _| ButtonsView {
+            clicks: clicks.clone(),
+        });
+
+        cx.update(|window, cx| window.start_input_recording(cx));
+        cx.simulate_click(point(px(10.), px(10.)), Modifiers::default());
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.simulate_click(point(px(60.), px(10.)), Modifiers::default());
+        let recording = cx
+            .update(|window, _| window.stop_input_recording())
+            .unwrap();
+        assert_eq!(recording.events.len(), 4);
+        assert_eq!(recording.duration(), Duration::from_secs(1));
+
+        clicks.borrow_mut().clear();
+        let replay = cx.update(|window, cx| window.replay_input(recording, 2., cx));
+        cx.run_until_parked();
+        assert_eq!(*clicks.borrow(), ["left"]);
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.run_until_parked();
+        assert_eq!(*clicks.borrow(), ["left", "right"]);
+        drop(replay);
+    }
+
+    struct KeyCaptureView {
+        stop_in_capture: bool,
+        keys: Rc<RefCell<Vec<&'static str>>>,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for KeyCaptureView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let stop_in_capture = self.stop_in_capture;
+            let keys = self.keys.clone();
+            div()
+                .capture_key_down({
+                    let keys = keys.clone();
+                    move |event, _, cx| {
+                        keys.borrow_mut().push("outer capture");
+                        if stop_in_capture {
+                            event.stop_propagation(cx);
+                        }
+                    }
+                })
+                .child(div().track_focus(&self.focus_handle).on_key_down(
+                    move |event, window, _| {
+                        keys.borrow_mut().push("inner");
+                        event.prevent_default(window);
+                    },
+                ))
+        }
+    }
+
+    #[gpui::test]
+    fn test_key_capture_and_event_controls(cx: &mut TestAppContext) {
+        let keys = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, cx| KeyCaptureView {
+            stop_in_capture: false,
+            keys: keys.clone(),
+            focus_handle: cx.focus_handle(),
+        });
+        cx.update(|window, cx| window.focus(&view.read(cx).focus_handle));
+        let dispatch_key_down = |cx: &mut VisualTestContext| {
+            cx.update(|window, cx| {
+                window.dispatch_event(
+                    PlatformInput::KeyDown(KeyDownEvent {
+                        keystroke: Keystroke::parse("a").unwrap(),
+                        is_held: false,
+                        prefer_character_input: false,
+                        scan_code: None,
+                        timestamp: None,
+                    }),
+                    cx,
+                )
+            })
+        };
+
+        let result = dispatch_key_down(cx);
+        assert_eq!(*keys.borrow(), ["outer capture", "inner"]);
+        assert!(result.default_prevented);
+        assert!(
+            !result.propagate,
+            "a key down whose default was prevented isn't inserted as text"
+        );
+
+        keys.borrow_mut().clear();
+        view.update(cx, |view, cx| {
+            view.stop_in_capture = true;
+            cx.notify();
+        });
+        let result = dispatch_key_down(cx);
+        assert_eq!(*keys.borrow(), ["outer capture"]);
+        assert!(!result.default_prevented);
+    }
 }
@@ -0,0 +1,378 @@
+use crate::{Global, SharedString};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+
+pub use util::locale::{Date, Locale, PluralCategory};
+
+/// The translated messages for a single locale, keyed by message id.
+///
+/// Messages may reference named arguments with `{name}`. Literal braces are
+/// written as `{{` and `}}`.
+#[derive(Clone, Debug, Default)]
+pub struct MessageCatalog {
+    messages: HashMap<SharedString, SharedString>,
+}
+
+impl MessageCatalog {
+    /// Creates an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a catalog from a flat JSON object mapping message ids to messages.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let messages = serde_json::from_str(json).context("failed to parse message catalog")?;
+        Ok(Self { messages })
+    }
+
+    /// Adds a message to the catalog, returning the catalog.
+    pub fn with_message(
+        mut self,
+        key: impl Into<SharedString>,
+        message: impl Into<SharedString>,
+    ) -> Self {
+        self.insert(key, message);
+        self
+    }
+
+    /// Adds a message to the catalog, replacing any previous message with the same id.
+    pub fn insert(&mut self, key: impl Into<SharedString>, message: impl Into<SharedString>) {
+        self.messages.insert(key.into(), message.into());
+    }
+
+    /// Returns the message with the given id.
+    pub fn get(&self, key: &str) -> Option<&SharedString> {
+        self.messages.get(key)
+    }
+
+    /// Copies every message from `other` into this catalog.
+    pub fn extend(&mut self, other: MessageCatalog) {
+        self.messages.extend(other.messages);
+    }
+}
+
+/// An argument that can be substituted into a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MessageArg {
+    /// A string, inserted verbatim.
+    String(SharedString),
+    /// A number, formatted according to the active locale.
+    Number(f64),
+}
+
+impl From<&'static str> for MessageArg {
+    fn from(value: &'static str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for MessageArg {
+    fn from(value: String) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<SharedString> for MessageArg {
+    fn from(value: SharedString) -> Self {
+        Self::String(value)
+    }
+}
+
+macro_rules! impl_number_message_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for MessageArg {
+                fn from(value: $ty) -> Self {
+                    Self::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_message_arg!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Substitutes `args` into `pattern`, formatting numbers for `locale`.
+///
+/// Besides plain `{name}` placeholders, ICU-style `plural` and `select` arguments are supported:
+///
+/// ```text
+/// {count, plural, =0 {No files} one {# file} other {# files}}
+/// {kind, select, folder {Open folder} other {Open file}}
+/// ```
+///
+/// Within a plural branch, `#` is replaced by the formatted count. Plural branches are chosen
+/// using the CLDR plural rules of `locale`. Arguments that aren't provided are left in place so
+/// missing translations are easy to spot.
+pub fn format_message(pattern: &str, args: &[(&str, MessageArg)], locale: &Locale) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    write_message(&mut result, pattern, args, locale, None);
+    result
+}
+
+fn write_message(
+    output: &mut String,
+    pattern: &str,
+    args: &[(&str, MessageArg)],
+    locale: &Locale,
+    plural_count: Option<f64>,
+) {
+    let mut rest = pattern;
+    while let Some(ix) = rest.find(['{', '}', '#']) {
+        output.push_str(&rest[..ix]);
+        let tail = &rest[ix..];
+        if let Some(stripped) = tail.strip_prefix('#') {
+            match plural_count {
+                Some(count) => output.push_str(&locale.format_number(count)),
+                None => output.push('#'),
+            }
+            rest = stripped;
+        } else if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if tail.starts_with('{')
+            && let Some(end) = matching_brace(tail)
+        {
+            if !write_placeholder(output, &tail[1..end], args, locale) {
+                output.push_str(&tail[..=end]);
+            }
+            rest = &tail[end + 1..];
+        } else {
+            output.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+    }
+    output.push_str(rest);
+}
+
+/// Returns the byte index of the brace closing the one that `text` starts with.
+fn matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (ix, character) in text.char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(ix);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn write_placeholder(
+    output: &mut String,
+    placeholder: &str,
+    args: &[(&str, MessageArg)],
+    locale: &Locale,
+) -> bool {
+    let mut parts = placeholder.splitn(3, ',');
+    let name = parts.next().unwrap_or_default().trim();
+    let Some((_, arg)) = args.iter().find(|(arg_name, _)| *arg_name == name) else {
+        return false;
+    };
+
+    match (parts.next().map(str::trim), parts.next(), arg) {
+        (None, _, MessageArg::String(string)) => output.push_str(string),
+        (None, _, MessageArg::Number(number)) => output.push_str(&locale.format_number(*number)),
+        (Some("plural"), Some(branches), MessageArg::Number(count)) => {
+            let exact = format!("={count}");
+            let category = locale.plural_category(*count).as_str();
+            let Some(branch) = select_branch(branches, &[exact.as_str(), category, "other"]) else {
+                return false;
+            };
+            write_message(output, branch, args, locale, Some(*count));
+        }
+        (Some("select"), Some(branches), MessageArg::String(value)) => {
+            let Some(branch) = select_branch(branches, &[value.as_ref(), "other"]) else {
+                return false;
+            };
+            write_message(output, branch, args, locale, None);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Parses `selector {message} selector {message} ...` and returns the message of the first
+/// selector in `preferences` that is present.
+fn select_branch<'a>(branches: &'a str, preferences: &[&str]) -> Option<&'a str> {
+    let mut parsed = Vec::new();
+    let mut rest = branches.trim_start();
+    while !rest.is_empty() {
+        let selector_end = rest.find(|c: char| c == '{' || c.is_whitespace())?;
+        let selector = &rest[..selector_end];
+        let body = rest[selector_end..].trim_start();
+        let end = matching_brace(body)?;
+        parsed.push((selector, &body[1..end]));
+        rest = body[end + 1..].trim_start();
+    }
+
+    preferences.iter().find_map(|preference| {
+        parsed
+            .iter()
+            .find(|(selector, _)| selector == preference)
+            .map(|(_, message)| *message)
+    })
+}
+
+/// The active locale and the message catalogs registered for each locale.
+pub(crate) struct Localization {
+    pub(crate) locale: Locale,
+    pub(crate) fallback_locale: Locale,
+    catalogs: Vec<(Locale, MessageCatalog)>,
+}
+
+impl Global for Localization {}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self {
+            locale: Locale::system(),
+            fallback_locale: Locale::en_us(),
+            catalogs: Vec::new(),
+        }
+    }
+}
+
+impl Localization {
+    pub(crate) fn add_catalog(&mut self, locale: Locale, catalog: MessageCatalog) {
+        if let Some((_, existing)) = self
+            .catalogs
+            .iter_mut()
+            .find(|(existing_locale, _)| existing_locale.matches(&locale))
+        {
+            existing.extend(catalog);
+        } else {
+            self.catalogs.push((locale, catalog));
+        }
+    }
+
+    /// Looks up a message in the active locale, then its less specific parents,
+    /// then the fallback locale.
+    pub(crate) fn message(&self, key: &str) -> Option<&SharedString> {
+        self.locale
+            .fallback_chain()
+            .into_iter()
+            .chain(self.fallback_locale.fallback_chain())
+            .find_map(|locale| {
+                self.catalogs
+                    .iter()
+                    .find(|(catalog_locale, _)| catalog_locale.matches(&locale))
+                    .and_then(|(_, catalog)| catalog.get(key))
+            })
+    }
+
+    pub(crate) fn translate(&self, key: &str, args: &[(&str, MessageArg)]) -> SharedString {
+        match self.message(key) {
+            // Messages without braces have nothing to substitute or unescape.
+            Some(message) if !message.contains(['{', '}']) => message.clone(),
+            Some(message) => format_message(message, args, &self.locale).into(),
+            None => {
+                log::debug!("missing translation for {key:?} in {}", self.locale);
+                SharedString::from(key.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestAppContext;
+
+    #[test]
+    fn test_format_message() {
+        let locale = Locale::new("de-DE");
+        assert_eq!(
+            format_message(
+                "{name} hat {count} Dateien {{geöffnet}}",
+                &[("name", "Ada".into()), ("count", 1200.into())],
+                &locale
+            ),
+            "Ada hat 1.200 Dateien {geöffnet}"
+        );
+        assert_eq!(
+            format_message("{missing} {ratio}", &[("ratio", 0.5.into())], &locale),
+            "{missing} 0,5"
+        );
+    }
+
+    #[test]
+    fn test_format_plural_and_select() {
+        let pattern = "{count, plural, =0 {No files} one {# file} other {# files}} in {kind, select, folder {this folder} other {this project}}";
+        let english = Locale::en_us();
+        assert_eq!(
+            format_message(
+                pattern,
+                &[("count", 0.into()), ("kind", "folder".into())],
+                &english
+            ),
+            "No files in this folder"
+        );
+        assert_eq!(
+            format_message(
+                pattern,
+                &[("count", 1.into()), ("kind", "workspace".into())],
+                &english
+            ),
+            "1 file in this project"
+        );
+        assert_eq!(
+            format_message(
+                pattern,
+                &[("count", 2500.into()), ("kind", "folder".into())],
+                &english
+            ),
+            "2,500 files in this folder"
+        );
+
+        let russian = "{count, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+        let locale = Locale::new("ru");
+        assert_eq!(
+            format_message(russian, &[("count", 22.into())], &locale),
+            "22 файла"
+        );
+        assert_eq!(
+            format_message(russian, &[("count", 11.into())], &locale),
+            "11 файлов"
+        );
+    }
+
+    #[test]
+    fn test_catalog_fallback() {
+        let mut localization = Localization {
+            locale: Locale::new("pt-BR"),
+            fallback_locale: Locale::en_us(),
+            catalogs: Vec::new(),
+        };
+        localization.add_catalog(
+            Locale::new("pt"),
+            MessageCatalog::new().with_message("open", "Abrir"),
+        );
+        localization.add_catalog(
+            Locale::new("en-US"),
+            MessageCatalog::new()
+                .with_message("open", "Open")
+                .with_message("close", "Close")
+                .with_message("braces", "Use {{braces}}"),
+        );
+
+        assert_eq!(localization.translate("open", &[]), "Abrir");
+        assert_eq!(localization.translate("close", &[]), "Close");
+        assert_eq!(localization.translate("braces", &[]), "Use {braces}");
+        assert_eq!(localization.translate("quit", &[]), "quit");
+    }
+
+    #[crate::test]
+    fn test_locale_is_per_app(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+        cx_a.update(|cx| cx.set_locale("de-DE"));
+        cx_b.update(|cx| cx.set_locale("en-US"));
+
+        assert_eq!(cx_a.update(|cx| cx.format_number(1200.5)), "1.200,5");
+        assert_eq!(cx_b.update(|cx| cx.format_number(1200.5)), "1,200.5");
+    }
+}
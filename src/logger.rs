@@ -0,0 +1,216 @@
+use crate::SharedString;
+use anyhow::{Context as _, Result, anyhow};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::VecDeque,
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::SystemTime,
+};
+
+/// The log target of text shaping, font loading and glyph rasterization.
+pub const TEXT_LOG_TARGET: &str = "gpui::text";
+/// The log target of layout.
+pub const LAYOUT_LOG_TARGET: &str = "gpui::layout";
+/// The log target of the renderers and their GPU resources.
+pub const RENDER_LOG_TARGET: &str = "gpui::render";
+/// The log target of the platform integrations, such as windows, input, displays and the
+/// clipboard. Records logged from GPUI's platform modules are logged to targets nested in this one,
+/// such as `gpui::platform::linux::x11::clipboard`.
+pub const PLATFORM_LOG_TARGET: &str = "gpui::platform";
+
+/// The number of records GPUI's logger keeps for [`recent_log_records`].
+const MAX_LOG_RECORDS: usize = 1000;
+
+/// A record logged through GPUI's logger.
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    /// The level the record was logged at.
+    pub level: Level,
+    /// The target the record was logged to, such as [`RENDER_LOG_TARGET`].
+    pub target: SharedString,
+    /// The formatted message.
+    pub message: SharedString,
+    /// When the record was logged.
+    pub timestamp: SystemTime,
+}
+
+/// Which records GPUI's logger keeps, by target.
+///
+/// Filters are parsed from comma-separated directives in the syntax of `RUST_LOG`: a bare level
+/// sets the default, and `target=level` sets the level of a target and the targets nested in it.
+/// For example, `warn,gpui::render=debug` keeps warnings from everywhere, and debug records from
+/// the renderers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::Info,
+            directives: Vec::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Parses a filter from comma-separated directives, such as `warn,gpui::render=debug`.
+    pub fn parse(filter: &str) -> Result<Self> {
+        let mut parsed = Self::default();
+        for directive in filter.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let level = level
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("invalid log level in {directive:?}"))?;
+                    parsed.directives.push((target.trim().to_string(), level));
+                }
+                None => match directive.parse() {
+                    Ok(level) => parsed.default = level,
+                    Err(_) if is_target(directive) => {
+                        parsed
+                            .directives
+                            .push((directive.to_string(), LevelFilter::Trace));
+                    }
+                    Err(_) => return Err(anyhow!("invalid log directive {directive:?}")),
+                },
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the most verbose level that records logged to `target` are kept at.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// Returns the most verbose level that any target is kept at.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+fn is_target(directive: &str) -> bool {
+    directive
+        .chars()
+        .all(|character| character.is_alphanumeric() || character == '_' || character == ':')
+}
+
+struct Logger {
+    filter: RwLock<LogFilter>,
+    records: Mutex<VecDeque<LogRecord>>,
+    record_count: AtomicUsize,
+    installed: AtomicBool,
+}
+
+static LOGGER: LazyLock<Logger> = LazyLock::new(|| Logger {
+    filter: RwLock::new(LogFilter::default()),
+    records: Mutex::new(VecDeque::with_capacity(MAX_LOG_RECORDS)),
+    record_count: AtomicUsize::new(0),
+    installed: AtomicBool::new(false),
+});
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.read().level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        let mut records = self.records.lock();
+        if records.len() == MAX_LOG_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(LogRecord {
+            level: record.level(),
+            target: SharedString::from(record.target().to_string()),
+            message: SharedString::from(record.args().to_string()),
+            timestamp: SystemTime::now(),
+        });
+        self.record_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs GPUI's logger, which writes records to stderr and keeps the most recent ones for
+/// [`recent_log_records`] and the [`crate::LogViewer`]. Records are filtered by the `RUST_LOG`
+/// environment variable if it's set, and can be refiltered at runtime with
+/// [`crate::App::set_log_filter`].
+///
+/// Fails if another logger has already been installed.
+pub fn init_logger() -> Result<()> {
+    if let Ok(filter) = std::env::var("RUST_LOG") {
+        *LOGGER.filter.write() = LogFilter::parse(&filter)?;
+    }
+    log::set_logger(&*LOGGER).map_err(|error| anyhow!("failed to install logger: {error}"))?;
+    log::set_max_level(LOGGER.filter.read().max_level());
+    LOGGER.installed.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Returns the records most recently kept by GPUI's logger, oldest first.
+pub fn recent_log_records() -> Vec<LogRecord> {
+    LOGGER.records.lock().iter().cloned().collect()
+}
+
+/// Returns the number of records GPUI's logger has kept since it was installed.
+pub(crate) fn log_record_count() -> usize {
+    LOGGER.record_count.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_log_filter(filter: LogFilter) {
+    if LOGGER.installed.load(Ordering::Relaxed) {
+        log::set_max_level(filter.max_level());
+    }
+    *LOGGER.filter.write() = filter;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::parse("warn, gpui::render=debug,gpui::render::atlas=off").unwrap();
+        assert_eq!(filter.level_for("gpui::text"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("gpui::render"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("gpui::render::blade"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("gpui::rendering"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("gpui::render::atlas"), LevelFilter::Off);
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+
+        assert_eq!(
+            LogFilter::parse("gpui::layout")
+                .unwrap()
+                .level_for("gpui::layout"),
+            LevelFilter::Trace
+        );
+        assert!(LogFilter::parse("gpui::text=loud").is_err());
+    }
+}
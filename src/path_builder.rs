@@ -9,7 +9,7 @@ use lyon::tessellation::{
 };
 
 pub use lyon::math::Transform;
-pub use lyon::tessellation::{FillOptions, FillRule, StrokeOptions};
+pub use lyon::tessellation::{FillOptions, FillRule, LineCap, LineJoin, StrokeOptions};
 
 use crate::{Path, Pixels, Point, point, px};
 
@@ -108,6 +108,33 @@ impl PathBuilder {
         self
     }
 
+    /// Sets how the segments of a stroke path are joined at corners. Has no effect on fill paths.
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options = options.with_line_join(line_join);
+        }
+        self
+    }
+
+    /// Sets the shape of both ends of each open sub-path of a stroke path, and of each dash. Has
+    /// no effect on fill paths.
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options = options.with_line_cap(line_cap);
+        }
+        self
+    }
+
+    /// Sets the limit on the ratio of a miter join's length to the stroke width, beyond which
+    /// the join is beveled instead. Has no effect on fill paths.
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        if let PathStyle::Stroke(options) = &mut self.style {
+            *options =
+                options.with_miter_limit(miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
+        }
+        self
+    }
+
     /// Move the current point to the given point.
     #[inline]
     pub fn move_to(&mut self, to: Point<Pixels>) {
@@ -123,7 +150,8 @@ impl PathBuilder {
     /// Draw a curve from the current point to the given point, using the given control point.
     #[inline]
     pub fn curve_to(&mut self, to: Point<Pixels>, ctrl: Point<Pixels>) {
-        self.raw.quadratic_bezier_to(lyon_point(ctrl), lyon_point(to));
+        self.raw
+            .quadratic_bezier_to(lyon_point(ctrl), lyon_point(to));
     }
 
     /// Adds a cubic Bézier to the [`Path`] given its two control points
@@ -303,7 +331,40 @@ impl PathBuilder {
             &mut BuffersBuilder::new(&mut buf, |vertex: StrokeVertex| vertex.position()),
         )?;
 
-        Ok(Self::build_path(buf))
+        Self::tessellate_union(&buf)
+    }
+
+    /// Tessellates the area covered by the given triangles without any overlap.
+    ///
+    /// The stroke tessellator's triangles overlap at joins and wherever the path crosses itself,
+    /// which would blend translucent strokes twice there, so they're filled as one shape instead.
+    fn tessellate_union(
+        triangles: &VertexBuffers<lyon::math::Point, u16>,
+    ) -> Result<Path<Pixels>, Error> {
+        let mut builder = lyon::path::Path::builder();
+        for indices in triangles.indices.chunks_exact(3) {
+            let a = triangles.vertices[indices[0] as usize];
+            let b = triangles.vertices[indices[1] as usize];
+            let c = triangles.vertices[indices[2] as usize];
+            // With every triangle wound the same way, the non-zero rule fills their union.
+            let winding = (b - a).cross(c - a);
+            let points = if winding > 0. {
+                [a, b, c]
+            } else if winding < 0. {
+                [a, c, b]
+            } else {
+                continue;
+            };
+            builder.add_polygon(Polygon {
+                points: &points,
+                closed: true,
+            });
+        }
+
+        Self::tessellate_fill(
+            &builder.build(),
+            &FillOptions::default().with_fill_rule(FillRule::NonZero),
+        )
     }
 
     /// Builds a [`Path`] from a [`lyon::tessellation::VertexBuffers`].
@@ -325,7 +386,11 @@ impl PathBuilder {
             let v2 = buf.vertices[i2];
 
             path.push_triangle(
-                (from_lyon_point(v0), from_lyon_point(v1), from_lyon_point(v2)),
+                (
+                    from_lyon_point(v0),
+                    from_lyon_point(v1),
+                    from_lyon_point(v2),
+                ),
                 (point(0., 1.), point(0., 1.), point(0., 1.)),
             );
         }
@@ -333,3 +398,32 @@ impl PathBuilder {
         path
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(path: &Path<Pixels>) -> f32 {
+        path.vertices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let a = triangle[0].xy_position;
+                let b = triangle[1].xy_position;
+                let c = triangle[2].xy_position;
+                ((b.x - a.x).0 * (c.y - a.y).0 - (b.y - a.y).0 * (c.x - a.x).0).abs() / 2.
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_stroke_triangles_do_not_overlap_at_joins() {
+        let mut builder = PathBuilder::stroke(px(2.)).line_join(LineJoin::Miter);
+        builder.move_to(point(px(0.), px(0.)));
+        builder.line_to(point(px(10.), px(0.)));
+        builder.line_to(point(px(10.), px(10.)));
+        let path = builder.build().unwrap();
+
+        // An 11x2 horizontal bar and a 2x9 vertical bar below it, meeting at a square corner.
+        assert!((area(&path) - 40.).abs() < 0.01, "area was {}", area(&path));
+    }
+}
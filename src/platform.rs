@@ -1,6 +1,11 @@
 mod app_menu;
+mod capture_atlas;
+mod key_value_store;
 mod keyboard;
 mod keystroke;
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+mod native_views;
+mod scene_renderer;
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 mod linux;
@@ -38,22 +43,25 @@ pub(crate) mod scap_screen_capture;
 use crate::{
     Action, AnyWindowHandle, App, AsyncWindowContext, AtlasTextureKind, AtlasTile,
     BackgroundExecutor, Bounds, DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font,
-    FontId, FontMetrics, FontRun, ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap,
-    LineLayout, Pixels, PlatformInput, Point, Priority, RealtimePriority, RenderGlyphParams,
-    RenderImage, RenderImageParams, RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString,
-    Size, SvgRenderer, SystemWindowTab, Task, TaskLabel, TaskTiming, ThreadTaskTimings, Window,
-    WindowControlArea, hash, point, px, size,
+    FontId, FontMetrics, FontRun, ForegroundExecutor, GlobalElementId, GlyphId, GpuSpecs,
+    ImageSource, Keymap, LineLayout, Pixels, PlatformInput, Point, Priority, RealtimePriority,
+    RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Scene, ShadowParams,
+    ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer, SystemWindowTab, Task, TaskLabel,
+    TaskTiming, ThreadTaskTimings, Window, WindowControlArea, decode_still_image, hash, point, px,
+    size,
 };
 use anyhow::Result;
 use async_task::Runnable;
 use futures::channel::oneshot;
+use image::AnimationDecoder as _;
+use image::RgbaImage;
 use image::codecs::gif::GifDecoder;
-use image::{AnimationDecoder as _, Frame};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use schemars::JsonSchema;
 use seahash::SeaHasher;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::any::Any;
 use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 use std::io::Cursor;
@@ -72,6 +80,12 @@ use uuid::Uuid;
 pub use app_menu::*;
 pub use keyboard::*;
 pub use keystroke::*;
+pub use scene_renderer::*;
+
+pub(crate) use capture_atlas::{CaptureAtlas, CapturedSprites};
+pub(crate) use key_value_store::KeyValueStore;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) use native_views::EmbeddedNativeViews;
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 pub(crate) use linux::*;
@@ -85,6 +99,15 @@ pub(crate) use windows::*;
 #[cfg(all(target_os = "linux", feature = "wayland"))]
 pub use linux::layer_shell;
 
+#[cfg(any(
+    all(
+        any(target_os = "linux", target_os = "freebsd"),
+        any(feature = "x11", feature = "wayland")
+    ),
+    all(target_os = "macos", feature = "macos-blade")
+))]
+pub use blade::BladeRenderContext;
+
 #[cfg(any(test, feature = "test-support"))]
 pub use test::{TestDispatcher, TestScreenCaptureSource, TestScreenCaptureStream};
 
@@ -230,6 +253,16 @@ pub(crate) trait Platform: 'static {
     fn can_select_mixed_files_and_dirs(&self) -> bool;
     fn reveal_path(&self, path: &Path);
     fn open_with_system(&self, path: &Path);
+    /// Shows the system print dialog for the PDF document at `path`. Resolves once the platform
+    /// no longer needs the file, or is canceled if the platform can't tell when that is.
+    fn print_file(&self, _path: &Path) -> oneshot::Receiver<Result<()>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Err(anyhow::anyhow!(
+            "printing is not supported on this platform"
+        )))
+        .ok();
+        rx
+    }
 
     fn on_quit(&self, callback: Box<dyn FnMut()>);
     fn on_reopen(&self, callback: Box<dyn FnMut()>);
@@ -260,6 +293,8 @@ pub(crate) trait Platform: 'static {
     fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf>;
 
     fn set_cursor_style(&self, style: CursorStyle);
+    fn register_cursor_image(&self, _id: CustomCursorId, _image: CursorImage) {}
+    fn drop_cursor_image(&self, _id: CustomCursorId) {}
     fn should_auto_hide_scrollbars(&self) -> bool;
 
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -273,9 +308,185 @@ pub(crate) trait Platform: 'static {
     fn read_credentials(&self, url: &str) -> Task<Result<Option<(String, Vec<u8>)>>>;
     fn delete_credentials(&self, url: &str) -> Task<Result<()>>;
 
+    /// Reads the value stored under `key` in storage that persists across launches of the
+    /// application. Native platforms keep it in `store`.
+    fn read_key_value(&self, store: KeyValueStore, key: &str) -> Task<Result<Option<String>>> {
+        let key = key.to_string();
+        self.background_executor()
+            .spawn(async move { store.read(&key) })
+    }
+
+    /// Stores `value` under `key` in persistent storage, or removes the key if `value` is `None`.
+    fn write_key_value(
+        &self,
+        store: KeyValueStore,
+        key: &str,
+        value: Option<String>,
+    ) -> Task<Result<()>> {
+        let key = key.to_string();
+        self.background_executor()
+            .spawn(async move { store.write(&key, value) })
+    }
+
     fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout>;
     fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper>;
     fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>);
+
+    /// Registers a callback invoked after the GPU device was lost and the renderers were
+    /// recreated, e.g. after a driver update or reset.
+    #[cfg(target_os = "windows")]
+    fn on_gpu_reset(&self, _callback: Box<dyn FnMut()>) {}
+
+    /// Registers a callback invoked when the system reports that it's running low on memory.
+    fn on_memory_pressure(&self, _callback: Box<dyn FnMut(MemoryPressureLevel)>) {}
+
+    fn power_state(&self) -> PowerState {
+        PowerState::default()
+    }
+
+    /// Returns the time since the user last interacted with the system, if the platform reports it.
+    fn system_idle_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns how long a blinking text caret stays shown, and then hidden, according to the
+    /// system's settings, or `None` if the user turned caret blinking off.
+    fn caret_blink_interval(&self) -> Option<Duration> {
+        Some(DEFAULT_CARET_BLINK_INTERVAL)
+    }
+
+    /// Returns whether the user asked the system to replace translucent materials with opaque
+    /// ones.
+    fn should_reduce_transparency(&self) -> bool {
+        false
+    }
+}
+
+/// How long a blinking text caret stays shown, and then hidden, on platforms without a setting
+/// for it.
+pub(crate) const DEFAULT_CARET_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The power source and power saving state of the system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Whether the system is running on battery power.
+    pub on_battery: bool,
+    /// Whether the system is conserving power, e.g. because macOS Low Power Mode or
+    /// Windows Battery Saver is enabled.
+    pub low_power_mode: bool,
+}
+
+impl PowerState {
+    /// Returns whether the application should reduce its energy use, e.g. by lowering
+    /// animation frame rates and polling less often.
+    pub fn is_constrained(&self) -> bool {
+        self.on_battery || self.low_power_mode
+    }
+}
+
+/// How urgently the system needs applications to free memory, as reported to
+/// [`App::on_memory_pressure`] handlers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressureLevel {
+    /// Memory is getting low. Caches that are cheap to rebuild should be trimmed.
+    Warning,
+    /// Memory is critically low, and the system may start terminating applications. Everything
+    /// that can be rebuilt should be freed.
+    Critical,
+}
+
+/// A full-screen shader applied to a window's contents after the scene has been composited,
+/// e.g. for a CRT effect or color grading.
+///
+/// The source is WGSL and must define `fn post_process(uv: vec2<f32>) -> vec4<f32>`, which
+/// returns the color of the pixel at `uv` in the range `[0, 1]`. It can read the composited scene
+/// with `textureSample(t_scene, s_scene, uv)`, and `post_process_globals` provides the
+/// `viewport_size` in device pixels and the `time` in seconds since the shader was set.
+/// Time-based effects must request animation frames to keep the window redrawing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostProcessShader {
+    /// The WGSL source of the shader.
+    pub source: SharedString,
+}
+
+impl PostProcessShader {
+    /// Creates a post-process shader from WGSL source.
+    pub fn wgsl(source: impl Into<SharedString>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+}
+
+/// Adjustments to the alpha ramp of grayscale antialiased text, which make glyphs look heavier or
+/// lighter without changing their outlines. See [`crate::App::set_text_contrast`].
+///
+/// Light text on a dark background looks thinner than dark text on a light background at the same
+/// weight, so apps typically raise `light_text_enhanced_contrast` for dark appearances. The Metal
+/// renderer draws text with the system's own antialiasing, so it only applies
+/// `light_text_enhanced_contrast`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextContrast {
+    /// The display gamma that glyph coverage is corrected for. Values are clamped to the range
+    /// `[1.0, 2.2]`.
+    pub gamma: f32,
+    /// How much to darken the stems of dark text, from `0.0` for no adjustment.
+    pub enhanced_contrast: f32,
+    /// How much to thicken the stems of light text, from `0.0` for no adjustment.
+    pub light_text_enhanced_contrast: f32,
+}
+
+impl Default for TextContrast {
+    fn default() -> Self {
+        Self {
+            gamma: 1.8,
+            enhanced_contrast: 1.0,
+            light_text_enhanced_contrast: 0.0,
+        }
+    }
+}
+
+impl TextContrast {
+    #[cfg(any(
+        target_os = "windows",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        ),
+        all(target_os = "macos", feature = "macos-blade")
+    ))]
+    pub(crate) fn gamma_ratios(&self) -> [f32; 4] {
+        get_gamma_correction_ratios(self.gamma)
+    }
+}
+
+/// Draws part of a window with the platform renderer's GPU API, for content such as 3D viewports
+/// that GPUI's own primitives can't express. See [`crate::Window::paint_custom_render`].
+///
+/// Only the method for the renderer in use is called. The Metal and DirectX renderers don't
+/// support custom rendering yet; they skip these regions and log a warning the first time.
+pub trait CustomRenderer: Send + Sync + 'static {
+    /// Encodes the commands that draw this region with the Blade renderer.
+    #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        ),
+        all(target_os = "macos", feature = "macos-blade")
+    ))]
+    fn render_blade(&self, _context: &mut BladeRenderContext<'_>) {}
+}
+
+/// The function a [`crate::CustomRenderPrimitive`]'s `renderer_cast` points to.
+pub(crate) type CustomRendererCast = fn(&(dyn Any + Send + Sync)) -> Option<&dyn CustomRenderer>;
+
+/// Converts a type-erased renderer of type `R` back into a [`CustomRenderer`].
+pub(crate) fn cast_custom_renderer<R: CustomRenderer>(
+    renderer: &(dyn Any + Send + Sync),
+) -> Option<&dyn CustomRenderer> {
+    renderer
+        .downcast_ref::<R>()
+        .map(|renderer| renderer as &dyn CustomRenderer)
 }
 
 /// A handle to a platform's display, e.g. a monitor or laptop screen.
@@ -460,6 +671,88 @@ impl Tiling {
     }
 }
 
+/// Where a platform view embedded with [`crate::native_view`] is shown, in logical pixels relative
+/// to the top left of the window's content.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NativeViewPlacement {
+    /// The element that placed the view, which identifies it across frames.
+    pub(crate) id: GlobalElementId,
+    pub(crate) view: raw_window_handle::RawWindowHandle,
+    pub(crate) bounds: Bounds<Pixels>,
+    /// The part of `bounds` that's visible.
+    pub(crate) clip: Bounds<Pixels>,
+}
+
+/// How the alpha channel of a frame read back from the GPU is stored.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum CapturedAlpha {
+    /// The window is opaque, and the alpha channel holds no meaningful value.
+    Ignored,
+    /// Colors are multiplied by alpha.
+    Premultiplied,
+    /// Colors aren't multiplied by alpha.
+    Straight,
+}
+
+/// Converts a frame read back from the GPU, given as rows of BGRA pixels starting
+/// `bytes_per_row` bytes apart, to an image with straight alpha.
+#[allow(dead_code)]
+pub(crate) fn captured_frame_image(
+    size: Size<DevicePixels>,
+    bytes_per_row: usize,
+    bytes: &[u8],
+    alpha: CapturedAlpha,
+) -> Result<RgbaImage> {
+    let width = size.width.0.max(0) as u32;
+    let height = size.height.0.max(0) as u32;
+    let row_length = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_length * height as usize);
+    for row_index in 0..height as usize {
+        let row_start = row_index * bytes_per_row;
+        let row = bytes
+            .get(row_start..row_start + row_length)
+            .ok_or_else(|| anyhow::anyhow!("captured frame is smaller than {width}x{height}"))?;
+        for bgra in row.chunks_exact(4) {
+            let [blue, green, red, alpha_value] = [bgra[0], bgra[1], bgra[2], bgra[3]];
+            pixels.extend_from_slice(&match alpha {
+                CapturedAlpha::Ignored => [red, green, blue, u8::MAX],
+                CapturedAlpha::Straight => [red, green, blue, alpha_value],
+                CapturedAlpha::Premultiplied if alpha_value == 0 => [0, 0, 0, 0],
+                CapturedAlpha::Premultiplied => {
+                    let unpremultiply = |component: u8| {
+                        ((component as u32 * 255 + alpha_value as u32 / 2) / alpha_value as u32)
+                            .min(255) as u8
+                    };
+                    [
+                        unpremultiply(red),
+                        unpremultiply(green),
+                        unpremultiply(blue),
+                        alpha_value,
+                    ]
+                }
+            });
+        }
+    }
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("captured frame is smaller than {width}x{height}"))
+}
+
+/// Sends the image of a frame read back from the GPU to each request for it.
+#[allow(dead_code)]
+pub(crate) fn send_captured_frame(
+    captures: impl IntoIterator<Item = oneshot::Sender<Result<RgbaImage>>>,
+    image: Result<RgbaImage>,
+) {
+    for capture in captures {
+        let result = match &image {
+            Ok(image) => Ok(image.clone()),
+            Err(error) => Err(anyhow::anyhow!("{error:#}")),
+        };
+        capture.send(result).ok();
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub(crate) struct RequestFrameOptions {
     pub(crate) require_presentation: bool,
@@ -510,6 +803,52 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn draw(&self, scene: &Scene);
     fn completed_frame(&self) {}
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
+    /// Renders subsequent frames at `render_scale` times the native resolution, upscaling them to
+    /// fill the window. Returns whether the platform supports rendering at a reduced resolution.
+    fn set_render_scale(&self, _render_scale: f32) -> bool {
+        false
+    }
+    /// Attaches and positions the embedded platform views painted in the latest frame, and hides
+    /// previously attached views that weren't painted.
+    fn set_native_views(&self, _views: &[NativeViewPlacement]) {}
+    /// Enables or disables system gestures and shortcuts that switch to other applications.
+    /// Returns whether the platform supports disabling them.
+    fn set_system_gestures_enabled(&self, _enabled: bool) -> bool {
+        false
+    }
+    /// Applies `shader` to every subsequent frame after the scene is composited, replacing any
+    /// previous post-process shader, or removes it if `shader` is `None`.
+    fn set_post_process_shader(&self, shader: Option<&PostProcessShader>) -> Result<()> {
+        match shader {
+            Some(_) => Err(anyhow::anyhow!(
+                "post-process shaders are not supported by this platform's renderer"
+            )),
+            None => Ok(()),
+        }
+    }
+    /// Renders grayscale antialiased text in subsequent frames with `contrast`, or with the
+    /// platform's default contrast if it's `None`.
+    fn set_text_contrast(&self, _contrast: Option<TextContrast>) {}
+    /// Reads back the pixels of the next frame the window draws, once the GPU has finished
+    /// rendering it.
+    fn capture_frame(&self) -> oneshot::Receiver<Result<RgbaImage>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Err(anyhow::anyhow!(
+            "frame capture is not supported by this platform's renderer"
+        )))
+        .ok();
+        rx
+    }
+    /// Returns the top left corner of the window's content area in the coordinate space of
+    /// [`PlatformWindow::bounds`], or `None` if the platform doesn't let windows be placed there.
+    /// Pop-up windows are only opened where this is known.
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        None
+    }
+    /// Moves and resizes the window to `bounds`, in the coordinate space of
+    /// [`PlatformWindow::bounds`]. Only called on platforms that report a
+    /// [`PlatformWindow::content_origin`].
+    fn set_bounds(&mut self, _bounds: Bounds<Pixels>) {}
 
     // macOS specific methods
     fn get_title(&self) -> String {
@@ -650,6 +989,8 @@ impl PlatformTextSystem for NoopTextSystem {
             line_gap: 0.0,
             underline_position: -95.0,
             underline_thickness: 60.0,
+            strikethrough_position: 300.0,
+            strikethrough_thickness: 60.0,
             cap_height: 698.0,
             x_height: 516.0,
             bounding_box: Bounds {
@@ -779,17 +1120,12 @@ pub(crate) fn get_gamma_correction_ratios(gamma: f32) -> [f32; 4] {
 pub(crate) enum AtlasKey {
     Glyph(RenderGlyphParams),
     Svg(RenderSvgParams),
+    SvgDistanceField(SharedString),
     Image(RenderImageParams),
+    Shadow(ShadowParams),
 }
 
 impl AtlasKey {
-    #[cfg_attr(
-        all(
-            any(target_os = "linux", target_os = "freebsd"),
-            not(any(feature = "x11", feature = "wayland"))
-        ),
-        allow(dead_code)
-    )]
     pub(crate) fn texture_kind(&self) -> AtlasTextureKind {
         match self {
             AtlasKey::Glyph(params) => {
@@ -799,7 +1135,9 @@ impl AtlasKey {
                     AtlasTextureKind::Monochrome
                 }
             }
-            AtlasKey::Svg(_) => AtlasTextureKind::Monochrome,
+            AtlasKey::Svg(_) | AtlasKey::SvgDistanceField(_) | AtlasKey::Shadow(_) => {
+                AtlasTextureKind::Monochrome
+            }
             AtlasKey::Image(_) => AtlasTextureKind::Polychrome,
         }
     }
@@ -830,6 +1168,37 @@ pub(crate) trait PlatformAtlas: Send + Sync {
         build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
     ) -> Result<Option<AtlasTile>>;
     fn remove(&self, key: &AtlasKey);
+
+    /// Evicts the tiles that haven't been drawn recently, to free memory.
+    fn trim(&self) {}
+
+    fn stats(&self) -> AtlasStats {
+        AtlasStats::default()
+    }
+}
+
+/// How much of a window's sprite atlas is in use, as returned by [`Window::sprite_atlas_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtlasStats {
+    /// The number of textures the atlas has allocated.
+    pub textures: usize,
+    /// The number of tiles, such as glyphs and images, stored in the atlas.
+    pub tiles: usize,
+    /// The total area of the atlas's textures, in device pixels.
+    pub capacity: u64,
+    /// The area of the atlas's textures allocated to tiles, in device pixels.
+    pub used: u64,
+}
+
+impl AtlasStats {
+    /// Returns the fraction of the atlas's capacity that's allocated to tiles, from zero to one.
+    pub fn occupancy(&self) -> f32 {
+        if self.capacity == 0 {
+            0.
+        } else {
+            self.used as f32 / self.capacity as f32
+        }
+    }
 }
 
 struct AtlasTextureList<T> {
@@ -867,6 +1236,42 @@ impl<T> AtlasTextureList<T> {
     }
 }
 
+/// Input events older than this when they're converted are assumed to be timed by a different
+/// clock than the one they were compared with, and are left without a timestamp.
+const MAX_INPUT_EVENT_AGE: Duration = Duration::from_secs(60);
+
+/// Converts the time of an input event into an [`Instant`], given how long ago the platform says
+/// the event happened.
+#[cfg_attr(
+    all(
+        any(target_os = "linux", target_os = "freebsd"),
+        not(any(feature = "x11", feature = "wayland"))
+    ),
+    allow(dead_code)
+)]
+pub(crate) fn input_event_instant(age: Duration) -> Option<Instant> {
+    if age > MAX_INPUT_EVENT_AGE {
+        return None;
+    }
+    Instant::now().checked_sub(age)
+}
+
+/// Converts an input event time in milliseconds on a wrapping 32-bit clock, as X11, Wayland and
+/// Windows report them, into an [`Instant`], given the current time on the same clock.
+#[cfg_attr(
+    any(
+        target_os = "macos",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            not(any(feature = "x11", feature = "wayland"))
+        )
+    ),
+    allow(dead_code)
+)]
+pub(crate) fn input_event_instant_from_millis(event_time: u32, now: u32) -> Option<Instant> {
+    input_event_instant(Duration::from_millis(now.wrapping_sub(event_time) as u64))
+}
+
 pub(crate) struct PlatformInputHandler {
     cx: AsyncWindowContext,
     handler: Box<dyn InputHandler>,
@@ -957,7 +1362,10 @@ impl PlatformInputHandler {
 
     fn bounds_for_range(&mut self, range_utf16: Range<usize>) -> Option<Bounds<Pixels>> {
         self.cx
-            .update(|window, cx| self.handler.bounds_for_range(range_utf16, window, cx))
+            .update(|window, cx| {
+                let bounds = self.handler.bounds_for_range(range_utf16, window, cx)?;
+                Some(window.content_to_window_bounds(bounds))
+            })
             .ok()
             .flatten()
     }
@@ -973,7 +1381,7 @@ impl PlatformInputHandler {
 
     pub fn selected_bounds(&mut self, window: &mut Window, cx: &mut App) -> Option<Bounds<Pixels>> {
         let selection = self.handler.selected_text_range(true, window, cx)?;
-        self.handler.bounds_for_range(
+        let bounds = self.handler.bounds_for_range(
             if selection.reversed {
                 selection.range.start..selection.range.start
             } else {
@@ -981,13 +1389,17 @@ impl PlatformInputHandler {
             },
             window,
             cx,
-        )
+        )?;
+        Some(window.content_to_window_bounds(bounds))
     }
 
     #[allow(unused)]
     pub fn character_index_for_point(&mut self, point: Point<Pixels>) -> Option<usize> {
         self.cx
-            .update(|window, cx| self.handler.character_index_for_point(point, window, cx))
+            .update(|window, cx| {
+                let point = window.window_to_content_point(point);
+                self.handler.character_index_for_point(point, window, cx)
+            })
             .ok()
             .flatten()
     }
@@ -1159,6 +1571,15 @@ pub struct WindowOptions {
 
     /// Tab group name, allows opening the window as a native tab on macOS 10.12+. Windows with the same tabbing identifier will be grouped together.
     pub tabbing_identifier: Option<String>,
+
+    /// Identifies the window across launches when window sessions are enabled with
+    /// [`App::persist_window_sessions`]. The window's bounds, display and fullscreen state
+    /// are saved whenever they change, and a previously saved session takes precedence over
+    /// `window_bounds` and `display_id`.
+    pub session_id: Option<SharedString>,
+
+    /// The color space the window's colors are given in.
+    pub color_space: WindowColorSpace,
 }
 
 /// The variables that can be configured when creating a new window
@@ -1208,10 +1629,15 @@ pub(crate) struct WindowParams {
     pub window_min_size: Option<Size<Pixels>>,
     #[cfg(target_os = "macos")]
     pub tabbing_identifier: Option<String>,
+
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub color_space: WindowColorSpace,
 }
 
 /// Represents the status of how a window should be opened.
-#[derive(Debug, Copy, Clone, PartialEq)]
+///
+/// Window bounds can be serialized, e.g. to restore windows with [`App::write_key_value`].
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WindowBounds {
     /// Indicates that the window should open in a windowed state with the given bounds.
     Windowed(Bounds<Pixels>),
@@ -1266,6 +1692,8 @@ impl Default for WindowOptions {
             window_min_size: None,
             window_decorations: None,
             tabbing_identifier: None,
+            session_id: None,
+            color_space: WindowColorSpace::default(),
         }
     }
 }
@@ -1356,6 +1784,34 @@ pub enum WindowBackgroundAppearance {
     MicaAltBackdrop,
 }
 
+impl WindowBackgroundAppearance {
+    /// Returns the appearance to use when the user asked to reduce transparency. Blurred and
+    /// backdrop materials become opaque, while plain transparency is kept because windows rely
+    /// on it for rounded corners and shadows.
+    pub(crate) fn with_reduced_transparency(self) -> Self {
+        match self {
+            Self::Blurred | Self::MicaBackdrop | Self::MicaAltBackdrop => Self::Opaque,
+            Self::Opaque | Self::Transparent => self,
+        }
+    }
+}
+
+/// The color space in which a window interprets [`Hsla`] and [`Rgba`] colors.
+///
+/// Images are always drawn as sRGB. Only the Metal renderer on macOS draws to a surface that can
+/// show colors outside of sRGB, so it's the only one that offers other color spaces.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WindowColorSpace {
+    /// Colors are sRGB.
+    #[default]
+    Srgb,
+    /// Colors are Display P3, which reaches more saturated colors than sRGB with the same
+    /// components. The window draws to an extended range surface so these colors are shown in
+    /// full on wide gamut displays.
+    #[cfg(all(target_os = "macos", not(feature = "macos-blade")))]
+    DisplayP3,
+}
+
 /// The options that can be configured for a file dialog prompt
 #[derive(Clone, Debug)]
 pub struct PathPromptOptions {
@@ -1523,6 +1979,90 @@ pub enum CursorStyle {
 
     /// Hide the cursor
     None,
+
+    /// An image registered with [`App::register_cursor_image`]. Falls back to the default cursor
+    /// on platforms that can't draw custom cursors, and once the image is dropped.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Custom(CustomCursorId),
+}
+
+/// Identifies an image registered as a cursor with [`App::register_cursor_image`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CustomCursorId(pub(crate) u64);
+
+/// An image to show as the cursor, such as a brush outline in a drawing tool.
+///
+/// The image is drawn at [`CursorImage::size`] logical pixels, from the variant drawn for the
+/// nearest scale factor at or above the display's. Platforms may limit how large a cursor can be.
+#[derive(Clone, Debug)]
+pub struct CursorImage {
+    /// The size of the cursor, in logical pixels.
+    pub size: Size<Pixels>,
+    /// The point the cursor points with, in logical pixels from the image's top left corner.
+    pub hotspot: Point<Pixels>,
+    variants: SmallVec<[(f32, Arc<RenderImage>); 2]>,
+}
+
+impl CursorImage {
+    /// Creates a cursor image of the given logical size, pointing with `hotspot`. Add an image
+    /// to draw it with [`CursorImage::variant`].
+    pub fn new(size: Size<Pixels>, hotspot: Point<Pixels>) -> Self {
+        Self {
+            size,
+            hotspot,
+            variants: SmallVec::new(),
+        }
+    }
+
+    /// Adds the image drawn on displays with the given scale factor, which should be the
+    /// cursor's size multiplied by `scale_factor` in pixels. Only the first frame of the image
+    /// is drawn.
+    pub fn variant(mut self, scale_factor: f32, image: Arc<RenderImage>) -> Self {
+        let index = self
+            .variants
+            .partition_point(|(variant_scale_factor, _)| *variant_scale_factor < scale_factor);
+        self.variants.insert(index, (scale_factor, image));
+        self
+    }
+
+    /// Returns the variants of the image and the scale factors they're drawn at, from the
+    /// smallest scale factor to the largest.
+    pub fn variants(&self) -> impl Iterator<Item = (f32, &Arc<RenderImage>)> {
+        self.variants
+            .iter()
+            .map(|(scale_factor, image)| (*scale_factor, image))
+    }
+
+    /// Returns the variant to draw on a display with the given scale factor, and the scale
+    /// factor it was drawn for: the variant with the smallest scale factor at or above
+    /// `scale_factor`, or else the variant with the largest.
+    pub fn variant_for_scale_factor(&self, scale_factor: f32) -> Option<(f32, &Arc<RenderImage>)> {
+        self.variants()
+            .find(|(variant_scale_factor, _)| *variant_scale_factor >= scale_factor)
+            .or_else(|| self.variants().last())
+    }
+
+    /// Returns the pixels of the variant to draw at `scale_factor` in BGRA order, with alpha
+    /// premultiplied, along with the variant's scale factor and size in pixels.
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd"),
+        any(feature = "wayland", feature = "x11")
+    ))]
+    pub(crate) fn premultiplied_bgra(
+        &self,
+        scale_factor: f32,
+    ) -> Option<(f32, Size<DevicePixels>, Vec<u8>)> {
+        let (variant_scale_factor, image) = self.variant_for_scale_factor(scale_factor)?;
+        let mut bytes = image.as_bytes(0)?.to_vec();
+        for pixel in bytes.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            for component in &mut pixel[..3] {
+                *component = ((*component as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+        Some((variant_scale_factor, image.size(0), bytes))
+    }
 }
 
 /// A clipboard item that should be copied to the clipboard
@@ -1764,7 +2304,7 @@ impl Image {
         cx: &mut App,
     ) -> Option<Arc<RenderImage>> {
         ImageSource::Image(self)
-            .use_data(None, window, cx)
+            .use_data(None, None, window, cx)
             .and_then(|result| result.ok())
     }
 
@@ -1786,18 +2326,8 @@ impl Image {
 
     /// Convert the clipboard image to an `ImageData` object.
     pub fn to_image_data(&self, svg_renderer: SvgRenderer) -> Result<Arc<RenderImage>> {
-        fn frames_for_image(
-            bytes: &[u8],
-            format: image::ImageFormat,
-        ) -> Result<SmallVec<[Frame; 1]>> {
-            let mut data = image::load_from_memory_with_format(bytes, format)?.into_rgba8();
-
-            // Convert from RGBA to BGRA.
-            for pixel in data.chunks_exact_mut(4) {
-                pixel.swap(0, 2);
-            }
-
-            Ok(SmallVec::from_elem(Frame::new(data), 1))
+        fn still_image(bytes: &[u8], format: image::ImageFormat) -> Result<Arc<RenderImage>> {
+            Ok(Arc::new(decode_still_image(bytes, format, None)?))
         }
 
         let frames = match self.format {
@@ -1816,12 +2346,12 @@ impl Image {
 
                 frames
             }
-            ImageFormat::Png => frames_for_image(&self.bytes, image::ImageFormat::Png)?,
-            ImageFormat::Jpeg => frames_for_image(&self.bytes, image::ImageFormat::Jpeg)?,
-            ImageFormat::Webp => frames_for_image(&self.bytes, image::ImageFormat::WebP)?,
-            ImageFormat::Bmp => frames_for_image(&self.bytes, image::ImageFormat::Bmp)?,
-            ImageFormat::Tiff => frames_for_image(&self.bytes, image::ImageFormat::Tiff)?,
-            ImageFormat::Ico => frames_for_image(&self.bytes, image::ImageFormat::Ico)?,
+            ImageFormat::Png => return still_image(&self.bytes, image::ImageFormat::Png),
+            ImageFormat::Jpeg => return still_image(&self.bytes, image::ImageFormat::Jpeg),
+            ImageFormat::Webp => return still_image(&self.bytes, image::ImageFormat::WebP),
+            ImageFormat::Bmp => return still_image(&self.bytes, image::ImageFormat::Bmp),
+            ImageFormat::Tiff => return still_image(&self.bytes, image::ImageFormat::Tiff),
+            ImageFormat::Ico => return still_image(&self.bytes, image::ImageFormat::Ico),
             ImageFormat::Svg => {
                 return svg_renderer
                     .render_single_frame(&self.bytes, 1.0, false)
@@ -1902,3 +2432,88 @@ impl From<String> for ClipboardString {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point, px, size};
+    use image::{Frame, RgbaImage};
+
+    fn render_image(width: u32, height: u32, pixel: [u8; 4]) -> Arc<RenderImage> {
+        let buffer = RgbaImage::from_pixel(width, height, image::Rgba(pixel));
+        Arc::new(RenderImage::new(SmallVec::from_const([Frame::new(buffer)])))
+    }
+
+    #[test]
+    fn test_input_event_instant_from_millis() {
+        let before = Instant::now();
+        let instant = input_event_instant_from_millis(1_000, 1_250).unwrap();
+        assert!(instant <= before - Duration::from_millis(250) + Duration::from_millis(50));
+        assert!(instant >= before - Duration::from_millis(300));
+
+        let instant = input_event_instant_from_millis(u32::MAX - 9, 10).unwrap();
+        assert!(instant >= before - Duration::from_millis(100));
+
+        assert_eq!(input_event_instant_from_millis(1_250, 1_000), None);
+        assert_eq!(input_event_instant_from_millis(0, 3_600_000), None);
+    }
+
+    #[test]
+    fn test_captured_frame_image() {
+        // Two rows of two BGRA pixels, padded to eight pixels per row.
+        let mut bytes = vec![0; 64];
+        bytes[..8].copy_from_slice(&[10, 20, 30, 255, 50, 100, 150, 128]);
+        bytes[32..40].copy_from_slice(&[0, 0, 0, 0, 1, 2, 3, 4]);
+        let frame_size = size(DevicePixels(2), DevicePixels(2));
+
+        let image =
+            captured_frame_image(frame_size, 32, &bytes, CapturedAlpha::Premultiplied).unwrap();
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [30, 20, 10, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 199, 100, 128]);
+        assert_eq!(image.get_pixel(0, 1).0, [0, 0, 0, 0]);
+
+        let image = captured_frame_image(frame_size, 32, &bytes, CapturedAlpha::Ignored).unwrap();
+        assert_eq!(image.get_pixel(1, 1).0, [3, 2, 1, 255]);
+        assert!(
+            captured_frame_image(frame_size, 32, &bytes[..36], CapturedAlpha::Straight).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cursor_variant_for_scale_factor() {
+        let cursor = CursorImage::new(size(px(16.), px(16.)), point(px(8.), px(8.)));
+        assert!(cursor.variant_for_scale_factor(1.).is_none());
+
+        let cursor = cursor
+            .variant(2., render_image(32, 32, [0; 4]))
+            .variant(1., render_image(16, 16, [0; 4]));
+        let chosen_scale_factor = |scale_factor| {
+            cursor
+                .variant_for_scale_factor(scale_factor)
+                .map(|(variant_scale_factor, _)| variant_scale_factor)
+        };
+        assert_eq!(chosen_scale_factor(1.), Some(1.));
+        assert_eq!(chosen_scale_factor(1.25), Some(2.));
+        assert_eq!(chosen_scale_factor(2.), Some(2.));
+        assert_eq!(
+            chosen_scale_factor(3.),
+            Some(2.),
+            "falls back to the largest variant"
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "freebsd"),
+        any(feature = "wayland", feature = "x11")
+    ))]
+    #[test]
+    fn test_cursor_premultiplied_bgra() {
+        let cursor = CursorImage::new(size(px(1.), px(1.)), point(px(0.), px(0.)))
+            .variant(1., render_image(1, 1, [200, 100, 50, 128]));
+        let (scale_factor, pixel_size, bytes) = cursor.premultiplied_bgra(1.).unwrap();
+        assert_eq!(scale_factor, 1.);
+        assert_eq!(pixel_size, size(DevicePixels(1), DevicePixels(1)));
+        assert_eq!(bytes, [100, 50, 25, 128]);
+    }
+}
@@ -2,10 +2,12 @@
 mod apple_compat;
 mod blade_atlas;
 mod blade_context;
+mod blade_frame_graph;
 mod blade_renderer;
 
 #[cfg(target_os = "macos")]
 pub(crate) use apple_compat::*;
 pub(crate) use blade_atlas::*;
 pub(crate) use blade_context::*;
+pub use blade_renderer::BladeRenderContext;
 pub(crate) use blade_renderer::*;
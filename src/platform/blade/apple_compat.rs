@@ -1,4 +1,5 @@
 use super::{BladeContext, BladeRenderer, BladeSurfaceConfig};
+use crate::WindowColorSpace;
 use blade_graphics as gpu;
 use std::{ffi::c_void, ptr::NonNull};
 
@@ -22,6 +23,8 @@ pub unsafe fn new_renderer(
     native_view: *mut c_void,
     bounds: crate::Size<f32>,
     transparent: bool,
+    // Blade only draws to sRGB surfaces, where Display P3 isn't offered.
+    _color_space: WindowColorSpace,
 ) -> Renderer {
     use raw_window_handle as rwh;
     struct RawWindow {
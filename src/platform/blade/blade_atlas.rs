@@ -1,6 +1,6 @@
 use crate::{
-    AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels, PlatformAtlas,
-    Point, Size, platform::AtlasTextureList,
+    AtlasKey, AtlasStats, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels,
+    PlatformAtlas, Point, Scene, Size, TileId, platform::AtlasTextureList,
 };
 use anyhow::Result;
 use blade_graphics as gpu;
@@ -10,8 +10,22 @@ use etagere::BucketedAtlasAllocator;
 use parking_lot::Mutex;
 use std::{borrow::Cow, ops, sync::Arc};
 
+/// The number of frames a tile must go undrawn before it can be evicted. Tiles drawn more
+/// recently may still be read by frames in flight.
+const EVICTION_MIN_AGE: u64 = 3;
+
+/// The total area of the textures of each kind, in pixels, beyond which the atlas evicts tiles
+/// that haven't been drawn recently instead of allocating another texture.
+const TEXTURE_AREA_BUDGET: u64 = 4 * 1024 * 1024;
+
 pub(crate) struct BladeAtlas(Mutex<BladeAtlasState>);
 
+struct TileUsage {
+    key: AtlasKey,
+    area: u64,
+    last_used_frame: u64,
+}
+
 struct PendingUpload {
     id: AtlasTextureId,
     bounds: Bounds<DevicePixels>,
@@ -23,6 +37,8 @@ struct BladeAtlasState {
     upload_belt: BufferBelt,
     storage: BladeAtlasStorage,
     tiles_by_key: FxHashMap<AtlasKey, AtlasTile>,
+    tile_usage: FxHashMap<(AtlasTextureId, TileId), TileUsage>,
+    frame: u64,
     initializations: Vec<AtlasTextureId>,
     uploads: Vec<PendingUpload>,
 }
@@ -52,6 +68,8 @@ impl BladeAtlas {
             }),
             storage: BladeAtlasStorage::default(),
             tiles_by_key: Default::default(),
+            tile_usage: Default::default(),
+            frame: 0,
             initializations: Vec::new(),
             uploads: Vec::new(),
         }))
@@ -61,8 +79,16 @@ impl BladeAtlas {
         self.0.lock().destroy();
     }
 
-    pub fn before_frame(&self, gpu_encoder: &mut gpu::CommandEncoder) {
+    /// Uploads the tiles inserted since the last frame, and records which tiles `scene` draws.
+    pub fn before_frame(&self, scene: &Scene, gpu_encoder: &mut gpu::CommandEncoder) {
         let mut lock = self.0.lock();
+        lock.frame += 1;
+        for sprite in &scene.monochrome_sprites {
+            lock.touch(&sprite.tile);
+        }
+        for sprite in &scene.polychrome_sprites {
+            lock.touch(&sprite.tile);
+        }
         lock.flush(gpu_encoder);
     }
 
@@ -87,8 +113,9 @@ impl PlatformAtlas for BladeAtlas {
         build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
     ) -> Result<Option<AtlasTile>> {
         let mut lock = self.0.lock();
-        if let Some(tile) = lock.tiles_by_key.get(key) {
-            Ok(Some(tile.clone()))
+        if let Some(tile) = lock.tiles_by_key.get(key).cloned() {
+            lock.touch(&tile);
+            Ok(Some(tile))
         } else {
             profiling::scope!("new tile");
             let Some((size, bytes)) = build()? else {
@@ -97,32 +124,47 @@ impl PlatformAtlas for BladeAtlas {
             let tile = lock.allocate(size, key.texture_kind());
             lock.upload_texture(tile.texture_id, tile.bounds, &bytes);
             lock.tiles_by_key.insert(key.clone(), tile.clone());
+            let usage = TileUsage {
+                key: key.clone(),
+                area: tile_area(&tile),
+                last_used_frame: lock.frame,
+            };
+            lock.tile_usage
+                .insert((tile.texture_id, tile.tile_id), usage);
             Ok(Some(tile))
         }
     }
 
     fn remove(&self, key: &AtlasKey) {
         let mut lock = self.0.lock();
+        if let Some(tile) = lock.tiles_by_key.get(key).cloned() {
+            lock.remove_tile(tile.texture_id, tile.tile_id);
+        }
+    }
 
-        let Some(id) = lock.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
-            return;
-        };
+    fn trim(&self) {
+        let mut lock = self.0.lock();
+        for kind in [AtlasTextureKind::Monochrome, AtlasTextureKind::Polychrome] {
+            for (texture_id, tile_id) in lock.stale_tiles(kind) {
+                lock.remove_tile(texture_id, tile_id);
+            }
+        }
+    }
 
-        let Some(texture_slot) = lock.storage[id.kind].textures.get_mut(id.index as usize) else {
-            return;
+    fn stats(&self) -> AtlasStats {
+        let mut lock = self.0.lock();
+        let mut stats = AtlasStats {
+            tiles: lock.tiles_by_key.len(),
+            ..Default::default()
         };
-
-        if let Some(mut texture) = texture_slot.take() {
-            texture.decrement_ref_count();
-            if texture.is_unreferenced() {
-                lock.storage[id.kind]
-                    .free_list
-                    .push(texture.id.index as usize);
-                texture.destroy(&lock.gpu);
-            } else {
-                *texture_slot = Some(texture);
+        for kind in [AtlasTextureKind::Monochrome, AtlasTextureKind::Polychrome] {
+            for texture in lock.storage[kind].iter_mut() {
+                stats.textures += 1;
+                stats.capacity += texture.area();
+                stats.used += texture.allocated_area;
             }
         }
+        stats
     }
 }
 
@@ -140,10 +182,83 @@ impl BladeAtlasState {
             }
         }
 
+        if self.texture_area(texture_kind) >= TEXTURE_AREA_BUDGET
+            && let Some(tile) = self.evict_for(size, texture_kind)
+        {
+            return tile;
+        }
+
         let texture = self.push_texture(size, texture_kind);
         texture.allocate(size).unwrap()
     }
 
+    fn texture_area(&mut self, kind: AtlasTextureKind) -> u64 {
+        self.storage[kind]
+            .iter_mut()
+            .map(|texture| texture.area())
+            .sum()
+    }
+
+    /// Evicts the least recently drawn tiles of `kind` until a tile of `size` fits where they
+    /// were, and allocates it.
+    fn evict_for(&mut self, size: Size<DevicePixels>, kind: AtlasTextureKind) -> Option<AtlasTile> {
+        for (texture_id, tile_id) in self.stale_tiles(kind) {
+            self.remove_tile(texture_id, tile_id);
+            if let Some(Some(texture)) = self.storage[kind]
+                .textures
+                .get_mut(texture_id.index as usize)
+                && let Some(tile) = texture.allocate(size)
+            {
+                return Some(tile);
+            }
+        }
+        None
+    }
+
+    /// Returns the tiles of `kind` that haven't been drawn for [`EVICTION_MIN_AGE`] frames,
+    /// least recently drawn first.
+    fn stale_tiles(&self, kind: AtlasTextureKind) -> Vec<(AtlasTextureId, TileId)> {
+        let mut stale_tiles = self
+            .tile_usage
+            .iter()
+            .filter(|((texture_id, _), usage)| {
+                texture_id.kind == kind
+                    && self.frame.saturating_sub(usage.last_used_frame) >= EVICTION_MIN_AGE
+            })
+            .map(|(tile, usage)| (usage.last_used_frame, *tile))
+            .collect::<Vec<_>>();
+        stale_tiles.sort_unstable_by_key(|(last_used_frame, _)| *last_used_frame);
+        stale_tiles.into_iter().map(|(_, tile)| tile).collect()
+    }
+
+    fn touch(&mut self, tile: &AtlasTile) {
+        if let Some(usage) = self.tile_usage.get_mut(&(tile.texture_id, tile.tile_id)) {
+            usage.last_used_frame = self.frame;
+        }
+    }
+
+    /// Frees a tile's space in its texture, and destroys the texture once it holds no tiles.
+    fn remove_tile(&mut self, texture_id: AtlasTextureId, tile_id: TileId) {
+        let Some(usage) = self.tile_usage.remove(&(texture_id, tile_id)) else {
+            return;
+        };
+        self.tiles_by_key.remove(&usage.key);
+
+        let textures = &mut self.storage[texture_id.kind];
+        let Some(texture_slot) = textures.textures.get_mut(texture_id.index as usize) else {
+            return;
+        };
+        if let Some(mut texture) = texture_slot.take() {
+            texture.deallocate(tile_id, usage.area);
+            if texture.is_unreferenced() {
+                textures.free_list.push(texture.id.index as usize);
+                texture.destroy(&self.gpu);
+            } else {
+                *texture_slot = Some(texture);
+            }
+        }
+    }
+
     fn push_texture(
         &mut self,
         min_size: Size<DevicePixels>,
@@ -206,6 +321,7 @@ impl BladeAtlasState {
             raw,
             raw_view,
             live_atlas_keys: 0,
+            allocated_area: 0,
         };
 
         self.initializations.push(atlas_texture.id);
@@ -314,6 +430,7 @@ struct BladeAtlasTexture {
     raw_view: gpu::TextureView,
     format: gpu::TextureFormat,
     live_atlas_keys: u32,
+    allocated_area: u64,
 }
 
 impl BladeAtlasTexture {
@@ -329,9 +446,21 @@ impl BladeAtlasTexture {
             },
         };
         self.live_atlas_keys += 1;
+        self.allocated_area += tile_area(&tile);
         Some(tile)
     }
 
+    fn deallocate(&mut self, tile_id: TileId, area: u64) {
+        self.allocator.deallocate(tile_id.into());
+        self.live_atlas_keys -= 1;
+        self.allocated_area -= area;
+    }
+
+    fn area(&self) -> u64 {
+        let size = self.allocator.size();
+        size.width as u64 * size.height as u64
+    }
+
     fn destroy(&mut self, gpu: &gpu::Context) {
         gpu.destroy_texture(self.raw);
         gpu.destroy_texture_view(self.raw_view);
@@ -341,15 +470,16 @@ impl BladeAtlasTexture {
         self.format.block_info().size
     }
 
-    fn decrement_ref_count(&mut self) {
-        self.live_atlas_keys -= 1;
-    }
-
     fn is_unreferenced(&mut self) -> bool {
         self.live_atlas_keys == 0
     }
 }
 
+fn tile_area(tile: &AtlasTile) -> u64 {
+    let size = tile.bounds.size;
+    size.width.0 as u64 * size.height.0 as u64
+}
+
 fn to_etagere_size(size: Size<DevicePixels>) -> etagere::Size {
     etagere::Size::new(size.width.into(), size.height.into())
 }
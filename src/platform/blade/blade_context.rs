@@ -1,3 +1,4 @@
+use super::PipelineCache;
 use anyhow::Context as _;
 use blade_graphics as gpu;
 use std::sync::Arc;
@@ -6,6 +7,7 @@ use util::ResultExt;
 #[cfg_attr(target_os = "macos", derive(Clone))]
 pub struct BladeContext {
     pub(super) gpu: Arc<gpu::Context>,
+    pub(super) pipeline_cache: Arc<PipelineCache>,
 }
 
 impl BladeContext {
@@ -32,7 +34,10 @@ impl BladeContext {
             }
             .map_err(|e| anyhow::anyhow!("{e:?}"))?,
         );
-        Ok(Self { gpu })
+        Ok(Self {
+            gpu,
+            pipeline_cache: Arc::default(),
+        })
     }
 }
 
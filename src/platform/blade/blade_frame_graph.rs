@@ -0,0 +1,199 @@
+use super::blade_renderer::create_intermediate_texture;
+use blade_graphics as gpu;
+
+/// The number of frames a pooled texture can go unused before it's destroyed. Textures last used
+/// this long ago are no longer in flight.
+const TRANSIENT_TEXTURE_LIFETIME: u32 = 3;
+
+/// Identifies a texture declared in a [`FrameGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TextureHandle(usize);
+
+/// A texture that only lives between the passes of a [`FrameGraph`] that use it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TransientTextureDesc {
+    pub name: &'static str,
+    pub format: gpu::TextureFormat,
+    pub size: gpu::Extent,
+}
+
+type PassExecutor<'a> = Box<dyn FnOnce(&mut gpu::CommandEncoder, &FrameGraphTextures) + 'a>;
+
+struct Pass<'a> {
+    reads: Vec<TextureHandle>,
+    writes: Vec<TextureHandle>,
+    execute: PassExecutor<'a>,
+}
+
+/// The passes of a multi-pass effect, such as a blur, and the intermediate textures they pass
+/// their results through.
+///
+/// Passes run in the order they're added. Each texture is taken from a [`TransientTexturePool`]
+/// before the first pass that uses it and returned after the last, so textures whose uses don't
+/// overlap share memory, and the pool reuses them in later effects and frames.
+#[derive(Default)]
+pub(crate) struct FrameGraph<'a> {
+    textures: Vec<TransientTextureDesc>,
+    passes: Vec<Pass<'a>>,
+}
+
+impl<'a> FrameGraph<'a> {
+    pub fn create_texture(&mut self, desc: TransientTextureDesc) -> TextureHandle {
+        self.textures.push(desc);
+        TextureHandle(self.textures.len() - 1)
+    }
+
+    /// Adds a pass that reads and writes the given textures. Passes may also draw into textures
+    /// the graph doesn't own, such as the frame.
+    pub fn add_pass(
+        &mut self,
+        reads: &[TextureHandle],
+        writes: &[TextureHandle],
+        execute: impl FnOnce(&mut gpu::CommandEncoder, &FrameGraphTextures) + 'a,
+    ) {
+        self.passes.push(Pass {
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            execute: Box::new(execute),
+        });
+    }
+
+    pub fn execute(
+        self,
+        gpu: &gpu::Context,
+        encoder: &mut gpu::CommandEncoder,
+        pool: &mut TransientTexturePool,
+    ) {
+        let mut last_uses = vec![None; self.textures.len()];
+        for (pass_index, pass) in self.passes.iter().enumerate() {
+            for handle in pass.reads.iter().chain(&pass.writes) {
+                last_uses[handle.0] = Some(pass_index);
+            }
+        }
+
+        let mut textures = FrameGraphTextures {
+            textures: vec![None; self.textures.len()],
+        };
+        let mut pooled = vec![None; self.textures.len()];
+        for (pass_index, pass) in self.passes.into_iter().enumerate() {
+            for handle in &pass.writes {
+                if pooled[handle.0].is_none() {
+                    let index = pool.acquire(gpu, &self.textures[handle.0]);
+                    let (texture, view) = pool.texture(index);
+                    encoder.init_texture(texture);
+                    textures.textures[handle.0] = Some((texture, view));
+                    pooled[handle.0] = Some(index);
+                }
+            }
+            debug_assert!(
+                pass.reads
+                    .iter()
+                    .all(|handle| textures.textures[handle.0].is_some()),
+                "frame graph pass reads a texture before it's written"
+            );
+
+            (pass.execute)(encoder, &textures);
+
+            for (handle, last_use) in last_uses.iter().enumerate() {
+                if *last_use == Some(pass_index)
+                    && let Some(index) = pooled[handle].take()
+                {
+                    pool.release(index);
+                }
+            }
+        }
+    }
+}
+
+/// The textures of a [`FrameGraph`], as they're given to its passes.
+pub(crate) struct FrameGraphTextures {
+    textures: Vec<Option<(gpu::Texture, gpu::TextureView)>>,
+}
+
+impl FrameGraphTextures {
+    /// Returns the texture for `handle`, or `None` if no pass has written it yet.
+    pub fn texture(&self, handle: TextureHandle) -> Option<gpu::Texture> {
+        Some(self.textures.get(handle.0).copied().flatten()?.0)
+    }
+
+    /// Returns the view of the texture for `handle`, or `None` if no pass has written it yet.
+    pub fn view(&self, handle: TextureHandle) -> Option<gpu::TextureView> {
+        Some(self.textures.get(handle.0).copied().flatten()?.1)
+    }
+}
+
+struct PooledTexture {
+    format: gpu::TextureFormat,
+    size: gpu::Extent,
+    texture: gpu::Texture,
+    view: gpu::TextureView,
+    in_use: bool,
+    unused_frames: u32,
+}
+
+/// The textures that [`FrameGraph`]s allocate their transient textures from.
+#[derive(Default)]
+pub(crate) struct TransientTexturePool {
+    textures: Vec<PooledTexture>,
+}
+
+impl TransientTexturePool {
+    fn acquire(&mut self, gpu: &gpu::Context, desc: &TransientTextureDesc) -> usize {
+        if let Some(index) = self.textures.iter().position(|texture| {
+            !texture.in_use && texture.format == desc.format && texture.size == desc.size
+        }) {
+            let texture = &mut self.textures[index];
+            texture.in_use = true;
+            texture.unused_frames = 0;
+            return index;
+        }
+
+        let (texture, view) = create_intermediate_texture(
+            gpu,
+            desc.name,
+            desc.format,
+            desc.size.width,
+            desc.size.height,
+        );
+        self.textures.push(PooledTexture {
+            format: desc.format,
+            size: desc.size,
+            texture,
+            view,
+            in_use: true,
+            unused_frames: 0,
+        });
+        self.textures.len() - 1
+    }
+
+    fn texture(&self, index: usize) -> (gpu::Texture, gpu::TextureView) {
+        let texture = &self.textures[index];
+        (texture.texture, texture.view)
+    }
+
+    fn release(&mut self, index: usize) {
+        self.textures[index].in_use = false;
+    }
+
+    /// Destroys the textures that haven't been used for a few frames, such as those sized for
+    /// the window before it was resized.
+    pub fn end_frame(&mut self, gpu: &gpu::Context) {
+        self.textures.retain_mut(|texture| {
+            texture.unused_frames += 1;
+            if texture.unused_frames <= TRANSIENT_TEXTURE_LIFETIME {
+                return true;
+            }
+            gpu.destroy_texture(texture.texture);
+            gpu.destroy_texture_view(texture.view);
+            false
+        });
+    }
+
+    /// Destroys every texture in the pool. The GPU must have finished using them.
+    pub fn destroy(&mut self, gpu: &gpu::Context) {
+        for texture in self.textures.drain(..) {
+            gpu.destroy_texture(texture.texture);
+            gpu.destroy_texture_view(texture.view);
+        }
+    }
+}
@@ -1,20 +1,40 @@
 // Doing `if let` gives you nice scoping with passes/encoders
 #![allow(irrefutable_let_patterns)]
 
-use super::{BladeAtlas, BladeContext};
+use super::{
+    BladeAtlas, BladeContext,
+    blade_frame_graph::{FrameGraph, TransientTextureDesc, TransientTexturePool},
+};
 use crate::{
-    Background, Bounds, DevicePixels, GpuSpecs, MonochromeSprite, Path, Point, PolychromeSprite,
-    PrimitiveBatch, Quad, ScaledPixels, Scene, Shadow, Size, Underline,
-    get_gamma_correction_ratios,
+    BackdropBlur, Background, Bounds, CapturedAlpha, ContentMask, CustomRenderPrimitive,
+    CustomRenderer, CustomRendererCast, DevicePixels, GpuSpecs, MonochromeSprite, Path, Point,
+    PolychromeSprite, PostProcessShader, PrimitiveBatch, Quad, RENDER_LOG_TARGET, ScaledPixels,
+    Scene, Shadow, Size, TextContrast, TiledQuad, Underline, captured_frame_image,
+    get_gamma_correction_ratios, send_captured_frame,
 };
+use anyhow::{Context as _, anyhow};
 use blade_graphics as gpu;
 use blade_util::{BufferBelt, BufferBeltDescriptor};
 use bytemuck::{Pod, Zeroable};
+use futures::channel::oneshot;
+use image::RgbaImage;
 #[cfg(target_os = "macos")]
 use media::core_video::CVMetalTextureCache;
-use std::sync::Arc;
+use parking_lot::Mutex;
+use std::{
+    borrow::Cow,
+    path::Path as FilePath,
+    slice,
+    sync::{
+        Arc,
+        mpsc::{self, TryRecvError},
+    },
+    time::{Instant, SystemTime},
+};
+use util::ResultExt;
 
 const MAX_FRAME_TIME_MS: u32 = 10000;
+const SHADER_SOURCE: &str = include_str!("shaders.wgsl");
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -49,18 +69,57 @@ struct SurfaceParams {
     content_mask: PodBounds,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct PostProcessParams {
+    viewport_size: [f32; 2],
+    time: f32,
+    pad: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct BackdropBlurParams {
+    direction: [f32; 2],
+    pad: [u32; 2],
+}
+
 #[derive(blade_macros::ShaderData)]
 struct ShaderQuadsData {
     globals: GlobalParams,
+    time: f32,
     b_quads: gpu::BufferPiece,
 }
 
+#[derive(blade_macros::ShaderData)]
+struct ShaderTiledQuadsData {
+    globals: GlobalParams,
+    b_tiled_quads: gpu::BufferPiece,
+}
+
 #[derive(blade_macros::ShaderData)]
 struct ShaderShadowsData {
     globals: GlobalParams,
     b_shadows: gpu::BufferPiece,
 }
 
+#[derive(blade_macros::ShaderData)]
+struct ShaderBackdropBlurPassData {
+    globals: GlobalParams,
+    backdrop_blur_params: BackdropBlurParams,
+    t_sprite: gpu::TextureView,
+    s_sprite: gpu::Sampler,
+    b_backdrop_blurs: gpu::BufferPiece,
+}
+
+#[derive(blade_macros::ShaderData)]
+struct ShaderBackdropBlursData {
+    globals: GlobalParams,
+    t_sprite: gpu::TextureView,
+    s_sprite: gpu::Sampler,
+    b_backdrop_blurs: gpu::BufferPiece,
+}
+
 #[derive(blade_macros::ShaderData)]
 struct ShaderPathRasterizationData {
     globals: GlobalParams,
@@ -86,6 +145,7 @@ struct ShaderMonoSpritesData {
     globals: GlobalParams,
     gamma_ratios: [f32; 4],
     grayscale_enhanced_contrast: f32,
+    light_text_enhanced_contrast: f32,
     t_sprite: gpu::TextureView,
     s_sprite: gpu::Sampler,
     b_mono_sprites: gpu::BufferPiece,
@@ -108,6 +168,13 @@ struct ShaderSurfacesData {
     s_surface: gpu::Sampler,
 }
 
+#[derive(blade_macros::ShaderData)]
+struct ShaderPostProcessData {
+    post_process_globals: PostProcessParams,
+    t_scene: gpu::TextureView,
+    s_scene: gpu::Sampler,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[repr(C)]
 struct PathSprite {
@@ -121,11 +188,16 @@ struct PathRasterizationVertex {
     st_position: Point<f32>,
     color: Background,
     bounds: Bounds<ScaledPixels>,
+    content_mask: ContentMask<ScaledPixels>,
 }
 
 struct BladePipelines {
+    key: PipelineKey,
     quads: gpu::RenderPipeline,
+    tiled_quads: gpu::RenderPipeline,
     shadows: gpu::RenderPipeline,
+    backdrop_blur_passes: gpu::RenderPipeline,
+    backdrop_blurs: gpu::RenderPipeline,
     path_rasterization: gpu::RenderPipeline,
     paths: gpu::RenderPipeline,
     underlines: gpu::RenderPipeline,
@@ -135,20 +207,29 @@ struct BladePipelines {
 }
 
 impl BladePipelines {
-    fn new(gpu: &gpu::Context, surface_info: gpu::SurfaceInfo, path_sample_count: u32) -> Self {
+    fn new(
+        gpu: &gpu::Context,
+        surface_info: gpu::SurfaceInfo,
+        path_sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<Self> {
         use gpu::ShaderData as _;
 
         log::info!(
+            target: RENDER_LOG_TARGET,
             "Initializing Blade pipelines for surface {:?}",
             surface_info
         );
-        let shader = gpu.create_shader(gpu::ShaderDesc {
-            source: include_str!("shaders.wgsl"),
-        });
+        let shader = gpu
+            .try_create_shader(gpu::ShaderDesc { source })
+            .map_err(|error| anyhow!("failed to compile shaders: {error:?}"))?;
         shader.check_struct_size::<GlobalParams>();
         shader.check_struct_size::<SurfaceParams>();
         shader.check_struct_size::<Quad>();
+        shader.check_struct_size::<TiledQuad>();
         shader.check_struct_size::<Shadow>();
+        shader.check_struct_size::<BackdropBlur>();
+        shader.check_struct_size::<BackdropBlurParams>();
         shader.check_struct_size::<PathRasterizationVertex>();
         shader.check_struct_size::<PathSprite>();
         shader.check_struct_size::<Underline>();
@@ -167,7 +248,8 @@ impl BladePipelines {
             write_mask: gpu::ColorWrites::default(),
         }];
 
-        Self {
+        Ok(Self {
+            key: PipelineKey::new(surface_info, path_sample_count),
             quads: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
                 name: "quads",
                 data_layouts: &[&ShaderQuadsData::layout()],
@@ -182,6 +264,20 @@ impl BladePipelines {
                 color_targets,
                 multisample_state: gpu::MultisampleState::default(),
             }),
+            tiled_quads: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+                name: "tiled-quads",
+                data_layouts: &[&ShaderTiledQuadsData::layout()],
+                vertex: shader.at("vs_tiled_quad"),
+                vertex_fetches: &[],
+                primitive: gpu::PrimitiveState {
+                    topology: gpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                fragment: Some(shader.at("fs_tiled_quad")),
+                color_targets,
+                multisample_state: gpu::MultisampleState::default(),
+            }),
             shadows: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
                 name: "shadows",
                 data_layouts: &[&ShaderShadowsData::layout()],
@@ -196,6 +292,38 @@ impl BladePipelines {
                 color_targets,
                 multisample_state: gpu::MultisampleState::default(),
             }),
+            backdrop_blur_passes: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+                name: "backdrop-blur-passes",
+                data_layouts: &[&ShaderBackdropBlurPassData::layout()],
+                vertex: shader.at("vs_backdrop_blur_pass"),
+                vertex_fetches: &[],
+                primitive: gpu::PrimitiveState {
+                    topology: gpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                fragment: Some(shader.at("fs_backdrop_blur_pass")),
+                color_targets: &[gpu::ColorTargetState {
+                    format: surface_info.format,
+                    blend: None,
+                    write_mask: gpu::ColorWrites::default(),
+                }],
+                multisample_state: gpu::MultisampleState::default(),
+            }),
+            backdrop_blurs: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+                name: "backdrop-blurs",
+                data_layouts: &[&ShaderBackdropBlursData::layout()],
+                vertex: shader.at("vs_backdrop_blur"),
+                vertex_fetches: &[],
+                primitive: gpu::PrimitiveState {
+                    topology: gpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                fragment: Some(shader.at("fs_backdrop_blur")),
+                color_targets,
+                multisample_state: gpu::MultisampleState::default(),
+            }),
             path_rasterization: gpu.create_render_pipeline(gpu::RenderPipelineDesc {
                 name: "path_rasterization",
                 data_layouts: &[&ShaderPathRasterizationData::layout()],
@@ -301,12 +429,15 @@ impl BladePipelines {
                 color_targets,
                 multisample_state: gpu::MultisampleState::default(),
             }),
-        }
+        })
     }
 
     fn destroy(&mut self, gpu: &gpu::Context) {
         gpu.destroy_render_pipeline(&mut self.quads);
+        gpu.destroy_render_pipeline(&mut self.tiled_quads);
         gpu.destroy_render_pipeline(&mut self.shadows);
+        gpu.destroy_render_pipeline(&mut self.backdrop_blur_passes);
+        gpu.destroy_render_pipeline(&mut self.backdrop_blurs);
         gpu.destroy_render_pipeline(&mut self.path_rasterization);
         gpu.destroy_render_pipeline(&mut self.paths);
         gpu.destroy_render_pipeline(&mut self.underlines);
@@ -316,22 +447,316 @@ impl BladePipelines {
     }
 }
 
+/// The pipelines built from the built-in shaders, shared by all the windows of a [`BladeContext`]
+/// that draw with the same surface format, alpha mode and path sample count. Only the first of
+/// these windows waits for the pipelines to compile before it can be shown. Pipelines are
+/// evicted and destroyed once no window draws with them.
+#[derive(Default)]
+pub(crate) struct PipelineCache(Mutex<Vec<Arc<BladePipelines>>>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PipelineKey {
+    surface_info: gpu::SurfaceInfo,
+    path_sample_count: u32,
+}
+
+impl PipelineKey {
+    fn new(surface_info: gpu::SurfaceInfo, path_sample_count: u32) -> Self {
+        Self {
+            surface_info,
+            path_sample_count,
+        }
+    }
+}
+
+impl PipelineCache {
+    fn get(&self, key: PipelineKey) -> Option<Arc<BladePipelines>> {
+        self.0
+            .lock()
+            .iter()
+            .find(|pipelines| pipelines.key == key)
+            .cloned()
+    }
+
+    fn get_or_create(
+        &self,
+        gpu: &gpu::Context,
+        surface_info: gpu::SurfaceInfo,
+        path_sample_count: u32,
+    ) -> anyhow::Result<Arc<BladePipelines>> {
+        if let Some(pipelines) = self.get(PipelineKey::new(surface_info, path_sample_count)) {
+            return Ok(pipelines);
+        }
+        let pipelines = BladePipelines::new(gpu, surface_info, path_sample_count, SHADER_SOURCE)?;
+        Ok(self.insert(gpu, pipelines))
+    }
+
+    /// Caches pipelines compiled from the built-in shaders. If pipelines with the same key were
+    /// cached while these compiled, the cached ones are returned and these are destroyed.
+    fn insert(&self, gpu: &gpu::Context, mut pipelines: BladePipelines) -> Arc<BladePipelines> {
+        let mut entries = self.0.lock();
+        if let Some(cached) = entries.iter().find(|cached| cached.key == pipelines.key) {
+            pipelines.destroy(gpu);
+            return cached.clone();
+        }
+        let pipelines = Arc::new(pipelines);
+        entries.push(pipelines.clone());
+        pipelines
+    }
+
+    /// Releases pipelines a renderer no longer draws with, destroying them unless another
+    /// renderer still does. Pipelines that aren't cached, like hot-reloaded ones, are only held
+    /// by their renderer and are destroyed right away.
+    fn release(&self, gpu: &gpu::Context, pipelines: &mut Arc<BladePipelines>) {
+        let mut entries = self.0.lock();
+        if let Some(ix) = entries
+            .iter()
+            .position(|cached| Arc::ptr_eq(cached, pipelines))
+        {
+            // The cache holds one reference and `pipelines` is another.
+            if Arc::strong_count(pipelines) > 2 {
+                return;
+            }
+            entries.remove(ix);
+        }
+        if let Some(pipelines) = Arc::get_mut(pipelines) {
+            pipelines.destroy(gpu);
+        }
+    }
+}
+
+/// Pipelines compiled on a background thread, which renderers keep drawing with their current
+/// pipelines while they wait for.
+struct PendingPipelines {
+    /// The source of hot-reloaded shaders, or `None` for the built-in ones.
+    source: Option<String>,
+    compiled: mpsc::Receiver<anyhow::Result<CompiledPipelines>>,
+}
+
+struct CompiledPipelines(BladePipelines);
+
+// SAFETY: Compiled pipeline states and shader libraries are immutable, and Metal and Vulkan
+// allow them to be used from any thread once created.
+unsafe impl Send for CompiledPipelines {}
+
+impl PendingPipelines {
+    /// Starts compiling pipelines for `key` from `source`, or from the built-in shaders.
+    fn compile(
+        gpu: &Arc<gpu::Context>,
+        key: PipelineKey,
+        source: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let (sender, compiled) = mpsc::channel();
+        let gpu = gpu.clone();
+        let shader_source = source.clone();
+        std::thread::Builder::new()
+            .name("BladePipelines".to_owned())
+            .spawn(move || {
+                let pipelines = BladePipelines::new(
+                    &gpu,
+                    key.surface_info,
+                    key.path_sample_count,
+                    shader_source.as_deref().unwrap_or(SHADER_SOURCE),
+                );
+                // Pipelines that are no longer waited for must still be destroyed.
+                if let Err(mpsc::SendError(Ok(CompiledPipelines(mut pipelines)))) =
+                    sender.send(pipelines.map(CompiledPipelines))
+                {
+                    pipelines.destroy(&gpu);
+                }
+            })
+            .context("failed to spawn a thread to compile pipelines")?;
+        Ok(Self { source, compiled })
+    }
+}
+
+/// A user-provided shader that is applied to the composited scene before it's presented.
+struct PostProcess {
+    pipeline: gpu::RenderPipeline,
+    scene_texture: gpu::Texture,
+    scene_texture_view: gpu::TextureView,
+    start_time: Instant,
+}
+
+impl PostProcess {
+    fn new(
+        gpu: &gpu::Context,
+        surface_info: gpu::SurfaceInfo,
+        size: gpu::Extent,
+        shader: &PostProcessShader,
+    ) -> anyhow::Result<Self> {
+        use gpu::ShaderData as _;
+
+        let source = format!("{}\n{}", include_str!("post_process.wgsl"), shader.source);
+        let shader = gpu
+            .try_create_shader(gpu::ShaderDesc { source: &source })
+            .map_err(|error| anyhow!("failed to compile post-process shader: {error:?}"))?;
+        shader.check_struct_size::<PostProcessParams>();
+
+        let pipeline = gpu.create_render_pipeline(gpu::RenderPipelineDesc {
+            name: "post-process",
+            data_layouts: &[&ShaderPostProcessData::layout()],
+            vertex: shader.at("vs_post_process"),
+            vertex_fetches: &[],
+            primitive: gpu::PrimitiveState {
+                topology: gpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            fragment: Some(shader.at("fs_post_process")),
+            color_targets: &[gpu::ColorTargetState {
+                format: surface_info.format,
+                blend: None,
+                write_mask: gpu::ColorWrites::default(),
+            }],
+            multisample_state: gpu::MultisampleState::default(),
+        });
+        let (scene_texture, scene_texture_view) = create_intermediate_texture(
+            gpu,
+            "post-process scene",
+            surface_info.format,
+            size.width,
+            size.height,
+        );
+        Ok(Self {
+            pipeline,
+            scene_texture,
+            scene_texture_view,
+            start_time: Instant::now(),
+        })
+    }
+
+    fn resize(&mut self, gpu: &gpu::Context, format: gpu::TextureFormat, size: gpu::Extent) {
+        gpu.destroy_texture(self.scene_texture);
+        gpu.destroy_texture_view(self.scene_texture_view);
+        let (scene_texture, scene_texture_view) =
+            create_intermediate_texture(gpu, "post-process scene", format, size.width, size.height);
+        self.scene_texture = scene_texture;
+        self.scene_texture_view = scene_texture_view;
+    }
+
+    fn destroy(&mut self, gpu: &gpu::Context) {
+        gpu.destroy_render_pipeline(&mut self.pipeline);
+        gpu.destroy_texture(self.scene_texture);
+        gpu.destroy_texture_view(self.scene_texture_view);
+    }
+}
+
+/// Reloads `shaders.wgsl` from the source tree whenever it changes, so that shaders can be
+/// iterated on without restarting the application.
+struct ShaderHotReload {
+    path: &'static FilePath,
+    modified: Option<SystemTime>,
+}
+
+impl ShaderHotReload {
+    fn new() -> Self {
+        let path = FilePath::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/platform/blade/shaders.wgsl"
+        ));
+        log::info!(target: RENDER_LOG_TARGET, "Watching {path:?} for shader changes");
+        Self {
+            path,
+            modified: modified_time(path),
+        }
+    }
+
+    /// Returns the new shader source if the file has changed since it was last read.
+    fn poll(&mut self) -> Option<String> {
+        let modified = modified_time(self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        std::fs::read_to_string(self.path)
+            .with_context(|| format!("failed to read shaders from {:?}", self.path))
+            .log_err()
+    }
+}
+
+fn modified_time(path: &FilePath) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The state a [`CustomRenderer`] draws with when the Blade renderer paints its region.
+pub struct BladeRenderContext<'a> {
+    /// The GPU context, for creating the pipelines and resources the renderer needs.
+    pub gpu: &'a gpu::Context,
+    /// The command encoder of the frame being drawn. Render passes must use
+    /// [`gpu::InitOp::Load`] on [`Self::target`] to keep the content drawn beneath this region.
+    pub encoder: &'a mut gpu::CommandEncoder,
+    /// The texture the scene is being drawn into.
+    pub target: gpu::TextureView,
+    /// The format of [`Self::target`].
+    pub target_format: gpu::TextureFormat,
+    /// The size of [`Self::target`].
+    pub viewport_size: Size<DevicePixels>,
+    /// The bounds of the region in device pixels.
+    pub bounds: Bounds<ScaledPixels>,
+    /// The visible part of [`Self::bounds`]. Nothing outside it may be drawn, e.g. it can be used as
+    /// the scissor rectangle.
+    pub clip_bounds: Bounds<ScaledPixels>,
+}
+
+/// Returns the renderer that draws `primitive`.
+fn custom_renderer(primitive: &CustomRenderPrimitive) -> Option<&dyn CustomRenderer> {
+    let cast = primitive
+        .renderer_cast
+        .downcast_ref::<CustomRendererCast>()?;
+    cast(primitive.renderer.as_ref())
+}
+
+/// Returns the scissor rectangle for a batch as `(x, y, width, height)` in device pixels: the
+/// batch's shared content mask rounded out to whole pixels, or the whole target if the batch's
+/// primitives have different content masks or the mask lies outside the target.
+fn scissor_rect(
+    content_mask: Option<&ContentMask<ScaledPixels>>,
+    target_size: gpu::Extent,
+) -> (i32, i32, u32, u32) {
+    let full = (0, 0, target_size.width, target_size.height);
+    let Some(content_mask) = content_mask else {
+        return full;
+    };
+    let bounds = &content_mask.bounds;
+    let left = bounds.left().0.floor().max(0.);
+    let top = bounds.top().0.floor().max(0.);
+    let right = bounds.right().0.ceil().min(target_size.width as f32);
+    let bottom = bounds.bottom().0.ceil().min(target_size.height as f32);
+    if right <= left || bottom <= top {
+        return full;
+    }
+    (
+        left as i32,
+        top as i32,
+        (right - left) as u32,
+        (bottom - top) as u32,
+    )
+}
+
 pub struct BladeSurfaceConfig {
     pub size: gpu::Extent,
     pub transparent: bool,
 }
 
 //Note: we could see some of these fields moved into `BladeContext`
-// so that they are shared between windows. E.g. `pipelines`.
-// But that is complicated by the fact that pipelines depend on
-// the format and alpha mode.
+// so that they are shared between windows, like `pipelines` are
+// through the `PipelineCache`.
 pub struct BladeRenderer {
     gpu: Arc<gpu::Context>,
     surface: gpu::Surface,
     surface_config: gpu::SurfaceConfig,
     command_encoder: gpu::CommandEncoder,
     last_sync_point: Option<gpu::SyncPoint>,
-    pipelines: BladePipelines,
+    pipeline_cache: Arc<PipelineCache>,
+    pipelines: Arc<BladePipelines>,
+    pending_pipelines: Option<PendingPipelines>,
+    shader_source: Cow<'static, str>,
+    shader_hot_reload: Option<ShaderHotReload>,
+    post_process: Option<PostProcess>,
+    transient_textures: TransientTexturePool,
     instance_belt: BufferBelt,
     atlas: Arc<BladeAtlas>,
     atlas_sampler: gpu::Sampler,
@@ -342,6 +767,9 @@ pub struct BladeRenderer {
     path_intermediate_msaa_texture: Option<gpu::Texture>,
     path_intermediate_msaa_texture_view: Option<gpu::TextureView>,
     rendering_parameters: RenderingParameters,
+    text_contrast: Option<TextContrast>,
+    frame_captures: Vec<oneshot::Sender<anyhow::Result<RgbaImage>>>,
+    start_time: Instant,
 }
 
 impl BladeRenderer {
@@ -352,7 +780,8 @@ impl BladeRenderer {
     ) -> anyhow::Result<Self> {
         let surface_config = gpu::SurfaceConfig {
             size: config.size,
-            usage: gpu::TextureUsage::TARGET,
+            // Backdrop blurs copy what has been drawn to the frame so far.
+            usage: gpu::TextureUsage::TARGET | gpu::TextureUsage::COPY,
             display_sync: gpu::DisplaySync::Recent,
             color_space: gpu::ColorSpace::Srgb,
             allow_exclusive_full_screen: false,
@@ -368,11 +797,14 @@ impl BladeRenderer {
             buffer_count: 2,
         });
         let rendering_parameters = RenderingParameters::from_env(context);
-        let pipelines = BladePipelines::new(
+        let pipelines = context.pipeline_cache.get_or_create(
             &context.gpu,
             surface.info(),
             rendering_parameters.path_sample_count,
-        );
+        )?;
+        let shader_hot_reload = rendering_parameters
+            .shader_hot_reload
+            .then(ShaderHotReload::new);
         let instance_belt = BufferBelt::new(BufferBeltDescriptor {
             memory: gpu::Memory::Shared,
             min_chunk_size: 0x1000,
@@ -387,8 +819,9 @@ impl BladeRenderer {
         });
 
         let (path_intermediate_texture, path_intermediate_texture_view) =
-            create_path_intermediate_texture(
+            create_intermediate_texture(
                 &context.gpu,
+                "path intermediate",
                 surface.info().format,
                 config.size.width,
                 config.size.height,
@@ -417,7 +850,13 @@ impl BladeRenderer {
             surface_config,
             command_encoder,
             last_sync_point: None,
+            pipeline_cache: context.pipeline_cache.clone(),
             pipelines,
+            pending_pipelines: None,
+            shader_source: Cow::Borrowed(SHADER_SOURCE),
+            shader_hot_reload,
+            post_process: None,
+            transient_textures: TransientTexturePool::default(),
             instance_belt,
             atlas,
             atlas_sampler,
@@ -428,6 +867,9 @@ impl BladeRenderer {
             path_intermediate_msaa_texture,
             path_intermediate_msaa_texture_view,
             rendering_parameters,
+            text_contrast: None,
+            frame_captures: Vec::new(),
+            start_time: Instant::now(),
         })
     }
 
@@ -435,17 +877,20 @@ impl BladeRenderer {
         if let Some(last_sp) = self.last_sync_point.take()
             && !self.gpu.wait_for(&last_sp, MAX_FRAME_TIME_MS)
         {
-            log::error!("GPU hung");
+            log::error!(target: RENDER_LOG_TARGET, "GPU hung");
             #[cfg(target_os = "linux")]
             if self.gpu.device_information().driver_name == "radv" {
                 log::error!(
+                    target: RENDER_LOG_TARGET,
                     "there's a known bug with amdgpu/radv, try setting ZED_PATH_SAMPLE_COUNT=0 as a workaround"
                 );
                 log::error!(
+                    target: RENDER_LOG_TARGET,
                     "if that helps you're running into a known amdgpu/radv path rendering bug"
                 );
             }
             log::error!(
+                target: RENDER_LOG_TARGET,
                 "your device information is: {:?}",
                 self.gpu.device_information()
             );
@@ -490,8 +935,9 @@ impl BladeRenderer {
                 self.gpu.destroy_texture_view(msaa_view);
             }
             let (path_intermediate_texture, path_intermediate_texture_view) =
-                create_path_intermediate_texture(
+                create_intermediate_texture(
                     &self.gpu,
+                    "path intermediate",
                     self.surface.info().format,
                     gpu_size.width,
                     gpu_size.height,
@@ -509,6 +955,10 @@ impl BladeRenderer {
                 .unzip();
             self.path_intermediate_msaa_texture = path_intermediate_msaa_texture;
             self.path_intermediate_msaa_texture_view = path_intermediate_msaa_texture_view;
+            if let Some(post_process) = self.post_process.as_mut() {
+                post_process.resize(&self.gpu, self.surface.info().format, gpu_size);
+            }
+            self.transient_textures.destroy(&self.gpu);
         }
     }
 
@@ -518,12 +968,184 @@ impl BladeRenderer {
             self.surface_config.transparent = transparent;
             self.gpu
                 .reconfigure_surface(&mut self.surface, self.surface_config);
-            self.pipelines.destroy(&self.gpu);
-            self.pipelines = BladePipelines::new(
-                &self.gpu,
+            let key = PipelineKey::new(
                 self.surface.info(),
                 self.rendering_parameters.path_sample_count,
             );
+            // Shaders that are still being reloaded are compiled for the new surface instead.
+            let source = match self.pending_pipelines.take() {
+                Some(PendingPipelines {
+                    source: Some(source),
+                    ..
+                }) => Some(source),
+                _ => match &self.shader_source {
+                    Cow::Borrowed(_) => None,
+                    Cow::Owned(source) => Some(source.clone()),
+                },
+            };
+            if source.is_none()
+                && let Some(pipelines) = self.pipeline_cache.get(key)
+            {
+                self.replace_pipelines(pipelines);
+            } else if self.pipelines.key.surface_info.format == key.surface_info.format {
+                // The current pipelines can still draw to the surface, only blending for the
+                // previous alpha mode, until the new ones are compiled.
+                self.compile_pipelines(key, source);
+            } else {
+                let pipelines = match source {
+                    None => self.pipeline_cache.get_or_create(
+                        &self.gpu,
+                        key.surface_info,
+                        key.path_sample_count,
+                    ),
+                    Some(source) => BladePipelines::new(
+                        &self.gpu,
+                        key.surface_info,
+                        key.path_sample_count,
+                        &source,
+                    )
+                    .map(|pipelines| {
+                        self.shader_source = Cow::Owned(source);
+                        Arc::new(pipelines)
+                    }),
+                };
+                if let Some(pipelines) = pipelines.log_err() {
+                    self.replace_pipelines(pipelines);
+                }
+            }
+        }
+    }
+
+    /// Applies `shader` to every subsequent frame, or removes the current post-process shader.
+    pub fn set_post_process_shader(
+        &mut self,
+        shader: Option<&PostProcessShader>,
+    ) -> anyhow::Result<()> {
+        let post_process = shader
+            .map(|shader| {
+                PostProcess::new(
+                    &self.gpu,
+                    self.surface.info(),
+                    self.surface_config.size,
+                    shader,
+                )
+            })
+            .transpose()?;
+        self.wait_for_gpu();
+        if let Some(mut previous) = std::mem::replace(&mut self.post_process, post_process) {
+            previous.destroy(&self.gpu);
+        }
+        Ok(())
+    }
+
+    /// Renders grayscale text with `contrast`, or with the contrast from the environment if it's
+    /// `None`.
+    pub fn set_text_contrast(&mut self, contrast: Option<TextContrast>) {
+        self.text_contrast = contrast;
+    }
+
+    /// Switches to the given pipelines, releasing the previous ones. The GPU must be done with
+    /// the previous pipelines.
+    /// Reads back the pixels of the next frame drawn, once the GPU has finished rendering it.
+    pub fn capture_frame(&mut self) -> oneshot::Receiver<anyhow::Result<RgbaImage>> {
+        let (tx, rx) = oneshot::channel();
+        self.frame_captures.push(tx);
+        rx
+    }
+
+    /// Copies `texture`, the frame being drawn, into a buffer the CPU can read once the frame's
+    /// commands have run.
+    fn encode_frame_capture(&mut self, texture: gpu::Texture) -> gpu::Buffer {
+        let size = self.surface_config.size;
+        let bytes_per_row = size.width * 4;
+        let buffer = self.gpu.create_buffer(gpu::BufferDesc {
+            name: "frame capture",
+            size: bytes_per_row as u64 * size.height as u64,
+            memory: gpu::Memory::Shared,
+        });
+        if let mut transfers = self.command_encoder.transfer("frame capture") {
+            transfers.copy_texture_to_buffer(texture.into(), buffer.into(), bytes_per_row, size);
+        }
+        buffer
+    }
+
+    /// Waits for the frame copied into `buffer` to finish rendering, and sends its pixels to
+    /// the pending frame captures.
+    fn finish_frame_capture(&mut self, buffer: gpu::Buffer) {
+        self.wait_for_gpu();
+        let size = self.surface_config.size;
+        let info = self.surface.info();
+        let bytes_per_row = size.width as usize * 4;
+        let mut bytes =
+            unsafe { slice::from_raw_parts(buffer.data(), bytes_per_row * size.height as usize) }
+                .to_vec();
+        self.gpu.destroy_buffer(buffer);
+        let image = match info.format {
+            gpu::TextureFormat::Bgra8Unorm | gpu::TextureFormat::Bgra8UnormSrgb => Ok(()),
+            gpu::TextureFormat::Rgba8Unorm | gpu::TextureFormat::Rgba8UnormSrgb => {
+                for pixel in bytes.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+                Ok(())
+            }
+            format => Err(anyhow!("can't capture frames in the {format:?} format")),
+        }
+        .and_then(|()| {
+            let alpha = match info.alpha {
+                gpu::AlphaMode::Ignored => CapturedAlpha::Ignored,
+                gpu::AlphaMode::PreMultiplied => CapturedAlpha::Premultiplied,
+                gpu::AlphaMode::PostMultiplied => CapturedAlpha::Straight,
+            };
+            let size = Size {
+                width: DevicePixels(size.width as i32),
+                height: DevicePixels(size.height as i32),
+            };
+            captured_frame_image(size, bytes_per_row, &bytes, alpha)
+        });
+        send_captured_frame(self.frame_captures.drain(..), image);
+    }
+
+    fn replace_pipelines(&mut self, pipelines: Arc<BladePipelines>) {
+        let mut previous = std::mem::replace(&mut self.pipelines, pipelines);
+        self.pipeline_cache.release(&self.gpu, &mut previous);
+    }
+
+    /// Compiles pipelines on a background thread, replacing any that are already compiling.
+    /// Frames are drawn with the current pipelines until they're ready.
+    fn compile_pipelines(&mut self, key: PipelineKey, source: Option<String>) {
+        self.pending_pipelines = PendingPipelines::compile(&self.gpu, key, source).log_err();
+    }
+
+    /// Switches to the pipelines compiled in the background once they're ready.
+    fn poll_pending_pipelines(&mut self) {
+        let Some(pending) = &self.pending_pipelines else {
+            return;
+        };
+        let compiled = match pending.compiled.try_recv() {
+            Ok(compiled) => compiled,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(anyhow!("pipeline compilation stopped")),
+        };
+        let Some(pending) = self.pending_pipelines.take() else {
+            return;
+        };
+        match compiled {
+            Ok(CompiledPipelines(pipelines)) => {
+                self.wait_for_gpu();
+                let pipelines = match pending.source {
+                    None => self.pipeline_cache.insert(&self.gpu, pipelines),
+                    Some(source) => {
+                        log::info!(target: RENDER_LOG_TARGET, "Reloaded shaders");
+                        self.shader_source = Cow::Owned(source);
+                        Arc::new(pipelines)
+                    }
+                };
+                self.replace_pipelines(pipelines);
+            }
+            Err(error) => log::error!(
+                target: RENDER_LOG_TARGET,
+                "{error:?}, keeping the previous shaders"
+            ),
         }
     }
 
@@ -608,6 +1230,7 @@ impl BladeRenderer {
                     st_position: v.st_position,
                     color: path.color,
                     bounds: path.clipped_bounds(),
+                    content_mask: path.content_mask.clone(),
                 }));
             }
             let vertex_buf = unsafe { self.instance_belt.alloc_typed(&vertices, &self.gpu) };
@@ -622,13 +1245,171 @@ impl BladeRenderer {
         }
     }
 
+    /// Copies the region of the target that `blur` samples, blurs it horizontally and then
+    /// vertically into half-resolution textures, and draws the result back into the target.
+    #[profiling::function]
+    fn draw_backdrop_blur(
+        &mut self,
+        blur: &BackdropBlur,
+        target: gpu::Texture,
+        target_view: gpu::TextureView,
+        globals: GlobalParams,
+    ) {
+        let size = self.surface_config.size;
+        let sampled_bounds = blur.bounds.dilate(ScaledPixels(blur.blur_radius.0 * 3.));
+        let left = sampled_bounds.left().0.floor().max(0.);
+        let top = sampled_bounds.top().0.floor().max(0.);
+        let right = sampled_bounds.right().0.ceil().min(size.width as f32);
+        let bottom = sampled_bounds.bottom().0.ceil().min(size.height as f32);
+        if right <= left || bottom <= top {
+            return;
+        }
+        let origin = [left as u32, top as u32, 0];
+        let extent = gpu::Extent {
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+            depth: 1,
+        };
+        let format = self.surface.info().format;
+        let half_size = gpu::Extent {
+            width: size.width.div_ceil(2),
+            height: size.height.div_ceil(2),
+            depth: 1,
+        };
+        let instance_buf = unsafe {
+            self.instance_belt
+                .alloc_typed(slice::from_ref(blur), &self.gpu)
+        };
+        let pipelines = &*self.pipelines;
+        let sampler = self.atlas_sampler;
+
+        let mut graph = FrameGraph::default();
+        let backdrop = graph.create_texture(TransientTextureDesc {
+            name: "backdrop",
+            format,
+            size,
+        });
+        let horizontal = graph.create_texture(TransientTextureDesc {
+            name: "backdrop blur horizontal",
+            format,
+            size: half_size,
+        });
+        let blurred = graph.create_texture(TransientTextureDesc {
+            name: "backdrop blur",
+            format,
+            size: half_size,
+        });
+
+        graph.add_pass(&[], &[backdrop], move |encoder, textures| {
+            let Some(backdrop) = textures.texture(backdrop) else {
+                return;
+            };
+            if let mut transfers = encoder.transfer("backdrop") {
+                transfers.copy_texture_to_texture(
+                    gpu::TexturePiece {
+                        texture: target,
+                        mip_level: 0,
+                        array_layer: 0,
+                        origin,
+                    },
+                    gpu::TexturePiece {
+                        texture: backdrop,
+                        mip_level: 0,
+                        array_layer: 0,
+                        origin,
+                    },
+                    extent,
+                );
+            }
+        });
+        for (source, destination, direction) in [
+            (backdrop, horizontal, [1., 0.]),
+            (horizontal, blurred, [0., 1.]),
+        ] {
+            graph.add_pass(&[source], &[destination], move |encoder, textures| {
+                let (Some(source), Some(destination)) =
+                    (textures.view(source), textures.view(destination))
+                else {
+                    return;
+                };
+                if let mut pass = encoder.render(
+                    "backdrop blur pass",
+                    gpu::RenderTargetSet {
+                        colors: &[gpu::RenderTarget {
+                            view: destination,
+                            init_op: gpu::InitOp::Clear(gpu::TextureColor::TransparentBlack),
+                            finish_op: gpu::FinishOp::Store,
+                        }],
+                        depth_stencil: None,
+                    },
+                ) {
+                    let mut encoder = pass.with(&pipelines.backdrop_blur_passes);
+                    encoder.bind(
+                        0,
+                        &ShaderBackdropBlurPassData {
+                            globals,
+                            backdrop_blur_params: BackdropBlurParams {
+                                direction,
+                                pad: [0; 2],
+                            },
+                            t_sprite: source,
+                            s_sprite: sampler,
+                            b_backdrop_blurs: instance_buf,
+                        },
+                    );
+                    encoder.draw(0, 4, 0, 1);
+                }
+            });
+        }
+        graph.add_pass(&[blurred], &[], move |encoder, textures| {
+            let Some(blurred) = textures.view(blurred) else {
+                return;
+            };
+            if let mut pass = encoder.render(
+                "backdrop blur",
+                gpu::RenderTargetSet {
+                    colors: &[gpu::RenderTarget {
+                        view: target_view,
+                        init_op: gpu::InitOp::Load,
+                        finish_op: gpu::FinishOp::Store,
+                    }],
+                    depth_stencil: None,
+                },
+            ) {
+                let mut encoder = pass.with(&pipelines.backdrop_blurs);
+                encoder.bind(
+                    0,
+                    &ShaderBackdropBlursData {
+                        globals,
+                        t_sprite: blurred,
+                        s_sprite: sampler,
+                        b_backdrop_blurs: instance_buf,
+                    },
+                );
+                encoder.draw(0, 4, 0, 1);
+            }
+        });
+        graph.execute(
+            &self.gpu,
+            &mut self.command_encoder,
+            &mut self.transient_textures,
+        );
+    }
+
     pub fn destroy(&mut self) {
         self.wait_for_gpu();
         self.atlas.destroy();
         self.gpu.destroy_sampler(self.atlas_sampler);
         self.instance_belt.destroy(&self.gpu);
         self.gpu.destroy_command_encoder(&mut self.command_encoder);
-        self.pipelines.destroy(&self.gpu);
+        // Pipelines still compiling are destroyed by their thread once it finds they're no
+        // longer waited for.
+        self.pending_pipelines = None;
+        self.pipeline_cache.release(&self.gpu, &mut self.pipelines);
+        if let Some(post_process) = self.post_process.as_mut() {
+            post_process.destroy(&self.gpu);
+        }
+        self.transient_textures.destroy(&self.gpu);
         self.gpu.destroy_surface(&mut self.surface);
         self.gpu.destroy_texture(self.path_intermediate_texture);
         self.gpu
@@ -642,14 +1423,37 @@ impl BladeRenderer {
     }
 
     pub fn draw(&mut self, scene: &Scene) {
+        if let Some(source) = self
+            .shader_hot_reload
+            .as_mut()
+            .and_then(ShaderHotReload::poll)
+        {
+            let key = PipelineKey::new(
+                self.surface.info(),
+                self.rendering_parameters.path_sample_count,
+            );
+            self.compile_pipelines(key, Some(source));
+        }
+        self.poll_pending_pipelines();
+
         self.command_encoder.start();
-        self.atlas.before_frame(&mut self.command_encoder);
+        self.atlas.before_frame(scene, &mut self.command_encoder);
 
         let frame = {
             profiling::scope!("acquire frame");
             self.surface.acquire_frame()
         };
         self.command_encoder.init_texture(frame.texture());
+        // With a post-process shader the scene is composited into an intermediate texture,
+        // which the shader then samples to draw the frame.
+        let (scene_target_texture, scene_target) = match &self.post_process {
+            Some(post_process) => {
+                self.command_encoder
+                    .init_texture(post_process.scene_texture);
+                (post_process.scene_texture, post_process.scene_texture_view)
+            }
+            None => (frame.texture(), frame.texture_view()),
+        };
 
         let globals = GlobalParams {
             viewport_size: [
@@ -662,12 +1466,25 @@ impl BladeRenderer {
             },
             pad: 0,
         };
+        let (gamma_ratios, grayscale_enhanced_contrast, light_text_enhanced_contrast) =
+            match self.text_contrast {
+                Some(contrast) => (
+                    contrast.gamma_ratios(),
+                    contrast.enhanced_contrast.max(0.0),
+                    contrast.light_text_enhanced_contrast.max(0.0),
+                ),
+                None => (
+                    self.rendering_parameters.gamma_ratios,
+                    self.rendering_parameters.grayscale_enhanced_contrast,
+                    0.0,
+                ),
+            };
 
         let mut pass = self.command_encoder.render(
             "main",
             gpu::RenderTargetSet {
                 colors: &[gpu::RenderTarget {
-                    view: frame.texture_view(),
+                    view: scene_target,
                     init_op: gpu::InitOp::Clear(gpu::TextureColor::TransparentBlack),
                     finish_op: gpu::FinishOp::Store,
                 }],
@@ -676,7 +1493,25 @@ impl BladeRenderer {
         );
 
         profiling::scope!("render pass");
+        // The scissor rectangle set on the current pass, as `(x, y, width, height)`.
+        let mut current_scissor = None;
         for batch in scene.batches() {
+            // Paths, backdrop blurs and custom renderers start new passes, and primitives are
+            // also clipped by their shaders, so the scissor rectangle only saves shading hidden
+            // pixels.
+            if !matches!(
+                batch,
+                PrimitiveBatch::Paths(_)
+                    | PrimitiveBatch::BackdropBlurs(_)
+                    | PrimitiveBatch::CustomRenderPrimitives(_)
+            ) {
+                let scissor = scissor_rect(batch.shared_content_mask(), self.surface_config.size);
+                if current_scissor != Some(scissor) {
+                    let (x, y, w, h) = scissor;
+                    pass.set_scissor_rect(&gpu::ScissorRect { x, y, w, h });
+                    current_scissor = Some(scissor);
+                }
+            }
             match batch {
                 PrimitiveBatch::Quads(quads) => {
                     let instance_buf = unsafe { self.instance_belt.alloc_typed(quads, &self.gpu) };
@@ -685,11 +1520,24 @@ impl BladeRenderer {
                         0,
                         &ShaderQuadsData {
                             globals,
+                            time: self.start_time.elapsed().as_secs_f32(),
                             b_quads: instance_buf,
                         },
                     );
                     encoder.draw(0, 4, 0, quads.len() as u32);
                 }
+                PrimitiveBatch::TiledQuads(quads) => {
+                    let instance_buf = unsafe { self.instance_belt.alloc_typed(quads, &self.gpu) };
+                    let mut encoder = pass.with(&self.pipelines.tiled_quads);
+                    encoder.bind(
+                        0,
+                        &ShaderTiledQuadsData {
+                            globals,
+                            b_tiled_quads: instance_buf,
+                        },
+                    );
+                    encoder.draw(0, 4, 0, quads.len() as u32);
+                }
                 PrimitiveBatch::Shadows(shadows) => {
                     let instance_buf =
                         unsafe { self.instance_belt.alloc_typed(shadows, &self.gpu) };
@@ -703,6 +1551,25 @@ impl BladeRenderer {
                     );
                     encoder.draw(0, 4, 0, shadows.len() as u32);
                 }
+                PrimitiveBatch::BackdropBlurs(blurs) => {
+                    drop(pass);
+                    // Each blur is drawn separately, so that a blur sees the blurs beneath it.
+                    for blur in blurs {
+                        self.draw_backdrop_blur(blur, scene_target_texture, scene_target, globals);
+                    }
+                    pass = self.command_encoder.render(
+                        "main",
+                        gpu::RenderTargetSet {
+                            colors: &[gpu::RenderTarget {
+                                view: scene_target,
+                                init_op: gpu::InitOp::Load,
+                                finish_op: gpu::FinishOp::Store,
+                            }],
+                            depth_stencil: None,
+                        },
+                    );
+                    current_scissor = None;
+                }
                 PrimitiveBatch::Paths(paths) => {
                     let Some(first_path) = paths.first() else {
                         continue;
@@ -717,13 +1584,14 @@ impl BladeRenderer {
                         "main",
                         gpu::RenderTargetSet {
                             colors: &[gpu::RenderTarget {
-                                view: frame.texture_view(),
+                                view: scene_target,
                                 init_op: gpu::InitOp::Load,
                                 finish_op: gpu::FinishOp::Store,
                             }],
                             depth_stencil: None,
                         },
                     );
+                    current_scissor = None;
                     let mut encoder = pass.with(&self.pipelines.paths);
                     // When copying paths from the intermediate texture to the drawable,
                     // each pixel must only be copied once, in case of transparent paths.
@@ -784,10 +1652,9 @@ impl BladeRenderer {
                         0,
                         &ShaderMonoSpritesData {
                             globals,
-                            gamma_ratios: self.rendering_parameters.gamma_ratios,
-                            grayscale_enhanced_contrast: self
-                                .rendering_parameters
-                                .grayscale_enhanced_contrast,
+                            gamma_ratios,
+                            grayscale_enhanced_contrast,
+                            light_text_enhanced_contrast,
                             t_sprite: tex_info.raw_view,
                             s_sprite: self.atlas_sampler,
                             b_mono_sprites: instance_buf,
@@ -903,10 +1770,75 @@ impl BladeRenderer {
                         }
                     }
                 }
+                PrimitiveBatch::CustomRenderPrimitives(primitives) => {
+                    drop(pass);
+                    let target_format = self.surface.info().format;
+                    let viewport_size = Size {
+                        width: DevicePixels(self.surface_config.size.width as i32),
+                        height: DevicePixels(self.surface_config.size.height as i32),
+                    };
+                    for primitive in primitives {
+                        let Some(renderer) = custom_renderer(primitive) else {
+                            continue;
+                        };
+                        renderer.render_blade(&mut BladeRenderContext {
+                            gpu: &self.gpu,
+                            encoder: &mut self.command_encoder,
+                            target: scene_target,
+                            target_format,
+                            viewport_size,
+                            bounds: primitive.bounds,
+                            clip_bounds: primitive.bounds.intersect(&primitive.content_mask.bounds),
+                        });
+                    }
+                    pass = self.command_encoder.render(
+                        "main",
+                        gpu::RenderTargetSet {
+                            colors: &[gpu::RenderTarget {
+                                view: scene_target,
+                                init_op: gpu::InitOp::Load,
+                                finish_op: gpu::FinishOp::Store,
+                            }],
+                            depth_stencil: None,
+                        },
+                    );
+                    current_scissor = None;
+                }
             }
         }
         drop(pass);
 
+        if let Some(post_process) = &self.post_process
+            && let mut pass = self.command_encoder.render(
+                "post-process",
+                gpu::RenderTargetSet {
+                    colors: &[gpu::RenderTarget {
+                        view: frame.texture_view(),
+                        init_op: gpu::InitOp::Clear(gpu::TextureColor::TransparentBlack),
+                        finish_op: gpu::FinishOp::Store,
+                    }],
+                    depth_stencil: None,
+                },
+            )
+        {
+            let mut encoder = pass.with(&post_process.pipeline);
+            encoder.bind(
+                0,
+                &ShaderPostProcessData {
+                    post_process_globals: PostProcessParams {
+                        viewport_size: globals.viewport_size,
+                        time: post_process.start_time.elapsed().as_secs_f32(),
+                        pad: 0,
+                    },
+                    t_scene: post_process.scene_texture_view,
+                    s_scene: self.atlas_sampler,
+                },
+            );
+            encoder.draw(0, 4, 0, 1);
+        }
+
+        let frame_capture =
+            (!self.frame_captures.is_empty()).then(|| self.encode_frame_capture(frame.texture()));
         self.command_encoder.present(frame);
         let sync_point = self.gpu.submit(&mut self.command_encoder);
 
@@ -916,17 +1848,22 @@ impl BladeRenderer {
 
         self.wait_for_gpu();
         self.last_sync_point = Some(sync_point);
+        if let Some(buffer) = frame_capture {
+            self.finish_frame_capture(buffer);
+        }
+        self.transient_textures.end_frame(&self.gpu);
     }
 }
 
-fn create_path_intermediate_texture(
+pub(super) fn create_intermediate_texture(
     gpu: &gpu::Context,
+    name: &str,
     format: gpu::TextureFormat,
     width: u32,
     height: u32,
 ) -> (gpu::Texture, gpu::TextureView) {
     let texture = gpu.create_texture(gpu::TextureDesc {
-        name: "path intermediate",
+        name,
         format,
         size: gpu::Extent {
             width,
@@ -943,7 +1880,7 @@ fn create_path_intermediate_texture(
     let texture_view = gpu.create_texture_view(
         texture,
         gpu::TextureViewDesc {
-            name: "path intermediate view",
+            name,
             format,
             dimension: gpu::ViewDimension::D2,
             subresources: &Default::default(),
@@ -1004,6 +1941,10 @@ struct RenderingParameters {
     // Allowed range: [0.0, ..), other values are clipped
     // Default: 1.0
     grayscale_enhanced_contrast: f32,
+
+    // Env var: GPUI_SHADER_HOT_RELOAD
+    // Reloads shaders.wgsl from the source tree when it changes. Debug builds only.
+    shader_hot_reload: bool,
 }
 
 impl RenderingParameters {
@@ -1030,11 +1971,14 @@ impl RenderingParameters {
             .and_then(|v| v.parse().ok())
             .unwrap_or(1.0_f32)
             .max(0.0);
+        let shader_hot_reload =
+            cfg!(debug_assertions) && env::var_os("GPUI_SHADER_HOT_RELOAD").is_some();
 
         Self {
             path_sample_count,
             gamma_ratios,
             grayscale_enhanced_contrast,
+            shader_hot_reload,
         }
     }
 }
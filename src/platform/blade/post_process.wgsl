@@ -0,0 +1,32 @@
+// Prelude for user-provided post-process shaders. The user's source is appended after this file
+// and must define `fn post_process(uv: vec2<f32>) -> vec4<f32>`.
+
+struct PostProcessParams {
+    viewport_size: vec2<f32>,
+    time: f32,
+    pad: u32,
+}
+
+var<uniform> post_process_globals: PostProcessParams;
+var t_scene: texture_2d<f32>;
+var s_scene: sampler;
+
+struct PostProcessVarying {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_post_process(@builtin(vertex_index) vertex_id: u32) -> PostProcessVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+
+    var out = PostProcessVarying();
+    out.position = vec4<f32>(unit_vertex * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = unit_vertex;
+    return out;
+}
+
+@fragment
+fn fs_post_process(input: PostProcessVarying) -> @location(0) vec4<f32> {
+    return post_process(input.uv);
+}
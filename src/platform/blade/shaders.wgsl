@@ -42,6 +42,13 @@ fn light_on_dark_contrast(enhancedContrast: f32, color: vec3<f32>) -> f32 {
     return enhancedContrast * multiplier;
 }
 
+// Thickens the stems of light text, which `light_on_dark_contrast` leaves out.
+fn light_text_contrast(enhancedContrast: f32, color: vec3<f32>) -> f32 {
+    let brightness = color_brightness(color);
+    let multiplier = saturate(4.0 * (brightness - 0.5));
+    return enhancedContrast * multiplier;
+}
+
 fn enhance_contrast(alpha: f32, k: f32) -> f32 {
     return alpha * (k + 1.0) / (alpha * k + 1.0);
 }
@@ -69,6 +76,9 @@ struct GlobalParams {
 var<uniform> globals: GlobalParams;
 var<uniform> gamma_ratios: vec4<f32>;
 var<uniform> grayscale_enhanced_contrast: f32;
+var<uniform> light_text_enhanced_contrast: f32;
+// Seconds since the renderer was created, which moves the dashes of borders.
+var<uniform> time: f32;
 var t_sprite: texture_2d<f32>;
 var s_sprite: sampler;
 
@@ -87,6 +97,11 @@ struct Corners {
     bottom_left: f32,
 }
 
+struct ContentMask {
+    bounds: Bounds,
+    corner_radii: Corners,
+}
+
 struct Edges {
     top: f32,
     right: f32,
@@ -312,6 +327,37 @@ fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: vec2<f32>) -
   return integral.y - integral.x;
 }
 
+// The coverage of a blurred rounded rectangle at the given position.
+fn shadow_alpha(position: vec2<f32>, bounds: Bounds, corner_radii: Corners, blur_radius: f32) -> f32 {
+    if (blur_radius == 0.0) {
+        return saturate(0.5 - quad_sdf(position, bounds, corner_radii));
+    }
+
+    let half_size = bounds.size / 2.0;
+    let center = bounds.origin + half_size;
+    let center_to_point = position - center;
+
+    let corner_radius = pick_corner_radius(center_to_point, corner_radii);
+
+    // The signal is only non-zero in a limited range, so don't waste samples
+    let low = center_to_point.y - half_size.y;
+    let high = center_to_point.y + half_size.y;
+    let start = clamp(-3.0 * blur_radius, low, high);
+    let end = clamp(3.0 * blur_radius, low, high);
+
+    // Accumulate samples (we can get away with surprisingly few samples)
+    let step = (end - start) / 4.0;
+    var y = start + step * 0.5;
+    var alpha = 0.0;
+    for (var i = 0; i < 4; i += 1) {
+        let blur = blur_along_x(center_to_point.x, center_to_point.y - y,
+            blur_radius, corner_radius, half_size);
+        alpha +=  blur * gaussian(y, blur_radius) * step;
+        y += step;
+    }
+    return alpha;
+}
+
 // Selects corner radius based on quadrant.
 fn pick_corner_radius(center_to_point: vec2<f32>, radii: Corners) -> f32 {
     if (center_to_point.x < 0.0) {
@@ -329,6 +375,23 @@ fn pick_corner_radius(center_to_point: vec2<f32>, radii: Corners) -> f32 {
     }
 }
 
+// The horizontal and vertical radii of a corner. A zero vertical radius takes
+// the horizontal radius, for circular corners, and a corner with no horizontal
+// radius is square.
+fn elliptical_corner_radii(horizontal: f32, vertical: f32) -> vec2<f32> {
+    if (horizontal == 0.0) {
+        return vec2<f32>(0.0);
+    }
+    return vec2<f32>(horizontal, select(vertical, horizontal, vertical == 0.0));
+}
+
+// Selects the horizontal and vertical radii of a corner based on quadrant.
+fn pick_corner_radii(center_to_point: vec2<f32>, horizontal: Corners, vertical: Corners) -> vec2<f32> {
+    return elliptical_corner_radii(
+        pick_corner_radius(center_to_point, horizontal),
+        pick_corner_radius(center_to_point, vertical));
+}
+
 // Signed distance of the point to the quad's border - positive outside the
 // border, and negative inside.
 //
@@ -361,6 +424,29 @@ fn quad_sdf_impl(corner_center_to_point: vec2<f32>, corner_radius: f32) -> f32 {
     }
 }
 
+// Like `quad_sdf_impl`, for corners that may be elliptical. The distance to
+// elliptical corners is approximate.
+fn elliptical_quad_sdf_impl(corner_center_to_point: vec2<f32>, corner_radii: vec2<f32>) -> f32 {
+    if (corner_radii.x == corner_radii.y) {
+        return quad_sdf_impl(corner_center_to_point, corner_radii.x);
+    }
+    if (corner_center_to_point.x <= 0.0 || corner_center_to_point.y <= 0.0) {
+        let corner_to_point = corner_center_to_point - corner_radii;
+        return max(corner_to_point.x, corner_to_point.y);
+    }
+    return -quarter_ellipse_sdf(corner_center_to_point, corner_radii);
+}
+
+// The coverage of a point by a content mask with rounded corners. The
+// mask's bounds are clipped against separately.
+fn content_mask_alpha(point: vec2<f32>, content_mask: ContentMask) -> f32 {
+    let radii = content_mask.corner_radii;
+    if (max(max(radii.top_left, radii.top_right), max(radii.bottom_right, radii.bottom_left)) == 0.0) {
+        return 1.0;
+    }
+    return saturate(0.5 - quad_sdf(point, content_mask.bounds, radii));
+}
+
 // Abstract away the final color transformation based on the
 // target alpha compositing mode.
 fn blend_color(color: vec4<f32>, alpha_factor: f32) -> vec4<f32> {
@@ -482,11 +568,23 @@ struct Quad {
     order: u32,
     border_style: u32,
     bounds: Bounds,
-    content_mask: Bounds,
+    content_mask: ContentMask,
     background: Background,
     border_color: Hsla,
     corner_radii: Corners,
+    vertical_corner_radii: Corners,
     border_widths: Edges,
+    shadow: QuadShadow,
+    transformation: TransformationMatrix,
+    dash_speed: f32,
+    pad: u32,
+}
+
+struct QuadShadow {
+    bounds: Bounds,
+    color: Hsla,
+    blur_radius: f32,
+    pad: u32,
 }
 var<storage, read> b_quads: array<Quad>;
 
@@ -499,15 +597,30 @@ struct QuadVarying {
     @location(3) @interpolate(flat) background_solid: vec4<f32>,
     @location(4) @interpolate(flat) background_color0: vec4<f32>,
     @location(5) @interpolate(flat) background_color1: vec4<f32>,
+    @location(6) @interpolate(flat) shadow_color: vec4<f32>,
+    @location(7) local_position: vec2<f32>,
 }
 
 @vertex
 fn vs_quad(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> QuadVarying {
     let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
     let quad = b_quads[instance_id];
+    let shadow_color = hsla_to_rgba(quad.shadow.color);
+
+    // Grow the drawn bounds to cover the quad's shadow, including its blur
+    var bounds = quad.bounds;
+    if (shadow_color.a > 0.0) {
+        let margin = 3.0 * quad.shadow.blur_radius;
+        let shadow_min = quad.shadow.bounds.origin - vec2<f32>(margin);
+        let shadow_max = quad.shadow.bounds.origin + quad.shadow.bounds.size + vec2<f32>(margin);
+        let bounds_min = min(quad.bounds.origin, shadow_min);
+        let bounds_max = max(quad.bounds.origin + quad.bounds.size, shadow_max);
+        bounds = Bounds(bounds_min, bounds_max - bounds_min);
+    }
 
     var out = QuadVarying();
-    out.position = to_device_position(unit_vertex, quad.bounds);
+    out.position = to_device_position_transformed(unit_vertex, bounds, quad.transformation);
+    out.local_position = unit_vertex * bounds.size + bounds.origin;
 
     let gradient = prepare_gradient_color(
         quad.background.tag,
@@ -519,8 +632,9 @@ fn vs_quad(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) insta
     out.background_color0 = gradient.color0;
     out.background_color1 = gradient.color1;
     out.border_color = hsla_to_rgba(quad.border_color);
+    out.shadow_color = shadow_color;
     out.quad_id = instance_id;
-    out.clip_distances = distance_from_clip_rect(unit_vertex, quad.bounds, quad.content_mask);
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, bounds, quad.content_mask.bounds, quad.transformation);
     return out;
 }
 
@@ -532,8 +646,29 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
     }
 
     let quad = b_quads[input.quad_id];
+    let mask_alpha = content_mask_alpha(input.position.xy, quad.content_mask);
+    if (input.shadow_color.a == 0.0) {
+        return blend_color(quad_color(input, quad), mask_alpha);
+    }
 
-    let background_color = gradient_color(quad.background, input.position.xy, quad.bounds,
+    // The quad is drawn over its shadow, which extends past the quad's bounds
+    let point = input.local_position;
+    var color = vec4<f32>(0.0);
+    if (all(point >= quad.bounds.origin) && all(point < quad.bounds.origin + quad.bounds.size)) {
+        color = quad_color(input, quad);
+    }
+    let alpha = shadow_alpha(point, quad.shadow.bounds, quad.corner_radii, quad.shadow.blur_radius);
+    let shadow_color = vec4<f32>(input.shadow_color.rgb, input.shadow_color.a * alpha);
+    if (shadow_color.a <= 0.0) {
+        return blend_color(color, mask_alpha);
+    }
+    return blend_color(over(shadow_color, color), mask_alpha);
+}
+
+// The color of the quad's background and border at the fragment's position,
+// which must be within the quad's bounds.
+fn quad_color(input: QuadVarying, quad: Quad) -> vec4<f32> {
+    let background_color = gradient_color(quad.background, input.local_position, quad.bounds,
         input.background_solid, input.background_color0, input.background_color1);
 
     let unrounded = quad.corner_radii.top_left == 0.0 &&
@@ -547,20 +682,20 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
             quad.border_widths.right == 0.0 &&
             quad.border_widths.bottom == 0.0 &&
             unrounded) {
-        return blend_color(background_color, 1.0);
+        return background_color;
     }
 
     let size = quad.bounds.size;
     let half_size = size / 2.0;
-    let point = input.position.xy - quad.bounds.origin;
+    let point = input.local_position - quad.bounds.origin;
     let center_to_point = point - half_size;
 
     // Signed distance field threshold for inclusion of pixels. 0.5 is the
     // minimum distance between the center of the pixel and the edge.
     let antialias_threshold = 0.5;
 
-    // Radius of the nearest corner
-    let corner_radius = pick_corner_radius(center_to_point, quad.corner_radii);
+    // Horizontal and vertical radii of the nearest corner
+    let corner_radii = pick_corner_radii(center_to_point, quad.corner_radii, quad.vertical_corner_radii);
 
     // Width of the nearest borders
     let border = vec2<f32>(
@@ -583,9 +718,9 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
     // the point into the bottom right quadrant. Both components are <= 0.
     let corner_to_point = abs(center_to_point) - half_size;
 
-    // Vector from the point to the center of the rounded corner's circle, also
+    // Vector from the point to the center of the rounded corner's ellipse, also
     // mirrored into bottom right quadrant.
-    let corner_center_to_point = corner_to_point + corner_radius;
+    let corner_center_to_point = corner_to_point + corner_radii;
 
     // Whether the nearest point on the border is rounded
     let is_near_rounded_corner =
@@ -613,12 +748,12 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
     // However, that might negatively impact performance in the case of
     // reasonable sizes for rounded corners.
     if (is_within_inner_straight_border && !is_near_rounded_corner) {
-        return blend_color(background_color, 1.0);
+        return background_color;
     }
 
     // Signed distance of the point to the outside edge of the quad's border. It
     // is positive outside this edge, and negative inside.
-    let outer_sdf = quad_sdf_impl(corner_center_to_point, corner_radius);
+    let outer_sdf = elliptical_quad_sdf_impl(corner_center_to_point, corner_radii);
 
     // Approximate signed distance of the point to the inside edge of the quad's
     // border. It is negative outside this edge (within the border), and
@@ -636,11 +771,11 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
     } else if (is_beyond_inner_straight_border) {
         // Fast path for points that must be outside the inner edge.
         inner_sdf = -1.0;
-    } else if (reduced_border.x == reduced_border.y) {
+    } else if (reduced_border.x == reduced_border.y && corner_radii.x == corner_radii.y) {
         // Fast path for circular inner edge.
         inner_sdf = -(outer_sdf + reduced_border.x);
     } else {
-        let ellipse_radii = max(vec2<f32>(0.0), corner_radius - reduced_border);
+        let ellipse_radii = max(vec2<f32>(0.0), corner_radii - reduced_border);
         inner_sdf = quarter_ellipse_sdf(corner_center_to_point, ellipse_radii);
     }
 
@@ -651,8 +786,13 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
     if (border_sdf < antialias_threshold) {
         var border_color = input.border_color;
 
-        // Dashed border logic when border_style == 1
-        if (quad.border_style == 1) {
+        // Dashed and dotted border logic when border_style is 1 or 2
+        if (quad.border_style != 0) {
+            let dotted = quad.border_style == 2;
+            // Moving dashes are laid out around the whole perimeter so that
+            // they can travel around the corners.
+            let dashes_per_side = unrounded && quad.dash_speed == 0.0;
+
             // Position along the perimeter in "dash space", where each dash
             // period has length 1
             var t = 0.0;
@@ -665,8 +805,9 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
             // used by browsers, but also avoids dashes from different segments
             // overlapping when dash size is smaller than the border width.
             //
-            // Dash pattern: (2 * border width) dash, (1 * border width) gap
-            let dash_length_per_width = 2.0;
+            // Dash pattern: (2 * border width) dash, (1 * border width) gap.
+            // Dots are round, one border width across, with the same gap.
+            let dash_length_per_width = select(2.0, 1.0, dotted);
             let dash_gap_per_width = 1.0;
             let dash_period_per_width = dash_length_per_width + dash_gap_per_width;
 
@@ -679,7 +820,7 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
             // Dividing this by the border width gives the dash velocity
             let dv_numerator = 1.0 / dash_period_per_width;
 
-            if (unrounded) {
+            if (dashes_per_side) {
                 // When corners aren't rounded, the dashes are separately laid
                 // out on each straight line, rather than around the whole
                 // perimeter. This way each line starts and ends with a dash.
@@ -711,10 +852,14 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                 // When corners are rounded, the dashes are laid out clockwise
                 // around the whole perimeter.
 
-                let r_tr = quad.corner_radii.top_right;
-                let r_br = quad.corner_radii.bottom_right;
-                let r_bl = quad.corner_radii.bottom_left;
-                let r_tl = quad.corner_radii.top_left;
+                let r_tr = elliptical_corner_radii(quad.corner_radii.top_right,
+                                                   quad.vertical_corner_radii.top_right);
+                let r_br = elliptical_corner_radii(quad.corner_radii.bottom_right,
+                                                   quad.vertical_corner_radii.bottom_right);
+                let r_bl = elliptical_corner_radii(quad.corner_radii.bottom_left,
+                                                   quad.vertical_corner_radii.bottom_left);
+                let r_tl = elliptical_corner_radii(quad.corner_radii.top_left,
+                                                   quad.vertical_corner_radii.top_left);
 
                 let w_t = quad.border_widths.top;
                 let w_r = quad.border_widths.right;
@@ -728,21 +873,22 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                 let dv_l = select(dv_numerator / w_l, 0.0, w_l <= 0.0);
 
                 // Straight side lengths in dash space
-                let s_t = (size.x - r_tl - r_tr) * dv_t;
-                let s_r = (size.y - r_tr - r_br) * dv_r;
-                let s_b = (size.x - r_br - r_bl) * dv_b;
-                let s_l = (size.y - r_bl - r_tl) * dv_l;
+                let s_t = (size.x - r_tl.x - r_tr.x) * dv_t;
+                let s_r = (size.y - r_tr.y - r_br.y) * dv_r;
+                let s_b = (size.x - r_br.x - r_bl.x) * dv_b;
+                let s_l = (size.y - r_bl.y - r_tl.y) * dv_l;
 
                 let corner_dash_velocity_tr = corner_dash_velocity(dv_t, dv_r);
                 let corner_dash_velocity_br = corner_dash_velocity(dv_b, dv_r);
                 let corner_dash_velocity_bl = corner_dash_velocity(dv_b, dv_l);
                 let corner_dash_velocity_tl = corner_dash_velocity(dv_t, dv_l);
 
-                // Corner lengths in dash space
-                let c_tr = r_tr * (M_PI_F / 2.0) * corner_dash_velocity_tr;
-                let c_br = r_br * (M_PI_F / 2.0) * corner_dash_velocity_br;
-                let c_bl = r_bl * (M_PI_F / 2.0) * corner_dash_velocity_bl;
-                let c_tl = r_tl * (M_PI_F / 2.0) * corner_dash_velocity_tl;
+                // Corner lengths in dash space. Elliptical corners are
+                // measured as circles of their mean radius.
+                let c_tr = mean_radius(r_tr) * (M_PI_F / 2.0) * corner_dash_velocity_tr;
+                let c_br = mean_radius(r_br) * (M_PI_F / 2.0) * corner_dash_velocity_br;
+                let c_bl = mean_radius(r_bl) * (M_PI_F / 2.0) * corner_dash_velocity_bl;
+                let c_tl = mean_radius(r_tl) * (M_PI_F / 2.0) * corner_dash_velocity_tl;
 
                 // Cumulative dash space upto each segment
                 let upto_tr = s_t;
@@ -755,9 +901,11 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                 max_t = upto_tl + c_tl;
 
                 if (is_near_rounded_corner) {
-                    let radians = atan2(corner_center_to_point.y,
-                                        corner_center_to_point.x);
-                    let corner_t = radians * corner_radius;
+                    // The angle of the point on a unit circle, once the
+                    // ellipse is scaled into one.
+                    let radians = atan2(corner_center_to_point.y * corner_radii.x,
+                                        corner_center_to_point.x * corner_radii.y);
+                    let corner_t = radians * mean_radius(corner_radii);
 
                     if (center_to_point.x >= 0.0) {
                         if (center_to_point.y < 0.0) {
@@ -795,18 +943,18 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                     if (is_horizontal) {
                         if (center_to_point.y < 0.0) {
                             dash_velocity = dv_t;
-                            t = (point.x - r_tl) * dash_velocity;
+                            t = (point.x - r_tl.x) * dash_velocity;
                         } else {
                             dash_velocity = dv_b;
-                            t = upto_bl - (point.x - r_bl) * dash_velocity;
+                            t = upto_bl - (point.x - r_bl.x) * dash_velocity;
                         }
                     } else {
                         if (center_to_point.x < 0.0) {
                             dash_velocity = dv_l;
-                            t = upto_tl - (point.y - r_tl) * dash_velocity;
+                            t = upto_tl - (point.y - r_tl.y) * dash_velocity;
                         } else {
                             dash_velocity = dv_r;
-                            t = upto_r + (point.y - r_tr) * dash_velocity;
+                            t = upto_r + (point.y - r_tr.y) * dash_velocity;
                         }
                     }
                 }
@@ -817,18 +965,23 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
 
             // Straight borders should start and end with a dash, so max_t is
             // reduced to cause this.
-            max_t -= select(0.0, dash_length, unrounded);
+            max_t -= select(0.0, dash_length, dashes_per_side);
             if (max_t >= 1.0) {
                 // Adjust dash gap to evenly divide max_t.
                 let dash_count = floor(max_t);
                 let dash_period = max_t / dash_count;
+                // Move the dashes clockwise by dash_speed periods per second.
+                // The offset is kept positive for the modulo.
+                t += dash_period * (1.0 - fract(quad.dash_speed * time));
                 border_color.a *= dash_alpha(
                     t,
                     dash_period,
                     dash_length,
                     dash_velocity,
+                    border_sdf,
+                    dotted,
                     antialias_threshold);
-            } else if (unrounded) {
+            } else if (dashes_per_side) {
                 // When there isn't enough space for the full gap between the
                 // two start / end dashes of a straight border, reduce gap to
                 // make them fit.
@@ -840,6 +993,8 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                         dash_period,
                         dash_length,
                         dash_velocity,
+                        border_sdf,
+                        dotted,
                         antialias_threshold);
                 }
             }
@@ -852,7 +1007,12 @@ fn fs_quad(input: QuadVarying) -> @location(0) vec4<f32> {
                     saturate(antialias_threshold - inner_sdf));
     }
 
-    return blend_color(color, saturate(antialias_threshold - outer_sdf));
+    return vec4<f32>(color.rgb, color.a * saturate(antialias_threshold - outer_sdf));
+}
+
+// The mean of a corner's horizontal and vertical radii.
+fn mean_radius(corner_radii: vec2<f32>) -> f32 {
+    return (corner_radii.x + corner_radii.y) / 2.0;
 }
 
 // Returns the dash velocity of a corner given the dash velocity of the two
@@ -873,14 +1033,29 @@ fn corner_dash_velocity(dv1: f32, dv2: f32) -> f32 {
     }
 }
 
-// Returns alpha used to render antialiased dashes.
-// `t` is within the dash when `fmod(t, period) < length`.
-fn dash_alpha(t: f32, period: f32, length: f32, dash_velocity: f32, antialias_threshold: f32) -> f32 {
+// Returns alpha used to render antialiased dashes, or round dots when `dotted`.
+// `t` is within the dash when `fmod(t, period) < dash_length`. Dots are as long
+// as the border is wide, and `border_sdf` places the point across the border.
+fn dash_alpha(
+    t: f32,
+    period: f32,
+    dash_length: f32,
+    dash_velocity: f32,
+    border_sdf: f32,
+    dotted: bool,
+    antialias_threshold: f32,
+) -> f32 {
     let half_period = period / 2;
-    let half_length = length / 2;
+    let half_length = dash_length / 2;
     // Value in [-half_period, half_period].
     // The dash is in [-half_length, half_length].
     let centered = fmod(t + half_period - half_length, period) - half_period;
+    if (dotted) {
+        // Signed distance to the dot, in pixels.
+        let radius = half_length / dash_velocity;
+        let offset = vec2<f32>(centered / dash_velocity, border_sdf + radius);
+        return saturate(antialias_threshold - (length(offset) - radius));
+    }
     // Signed distance for the dash, negative values are inside the dash.
     let signed_distance = abs(centered) - half_length;
     // Antialiased alpha based on the signed distance.
@@ -908,6 +1083,66 @@ fn fmod(a: f32, b: f32) -> f32 {
     return a - b * trunc(a / b);
 }
 
+// --- tiled quads --- //
+
+struct TiledQuad {
+    order: u32,
+    corner_radius: f32,
+    bounds: Bounds,
+    content_mask: ContentMask,
+    tile_size: vec2<f32>,
+    quad_bounds: Bounds,
+    color: Hsla,
+    alternate_color: Hsla,
+    transformation: TransformationMatrix,
+}
+var<storage, read> b_tiled_quads: array<TiledQuad>;
+
+struct TiledQuadVarying {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) color: vec4<f32>,
+    @location(1) @interpolate(flat) alternate_color: vec4<f32>,
+    @location(2) @interpolate(flat) quad_id: u32,
+    //TODO: use `clip_distance` once Naga supports it
+    @location(3) clip_distances: vec4<f32>,
+    // The position before the quad's transformation, which the tiles are laid out in.
+    @location(4) local_position: vec2<f32>,
+}
+
+@vertex
+fn vs_tiled_quad(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> TiledQuadVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+    let quad = b_tiled_quads[instance_id];
+
+    var out = TiledQuadVarying();
+    out.position = to_device_position_transformed(unit_vertex, quad.bounds, quad.transformation);
+    out.color = hsla_to_rgba(quad.color);
+    out.alternate_color = hsla_to_rgba(quad.alternate_color);
+    out.quad_id = instance_id;
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, quad.bounds, quad.content_mask.bounds, quad.transformation);
+    out.local_position = unit_vertex * vec2<f32>(quad.bounds.size) + quad.bounds.origin;
+    return out;
+}
+
+@fragment
+fn fs_tiled_quad(input: TiledQuadVarying) -> @location(0) vec4<f32> {
+    // Alpha clip first, since we don't have `clip_distance`.
+    if (any(input.clip_distances < vec4<f32>(0.0))) {
+        return vec4<f32>(0.0);
+    }
+
+    let quad = b_tiled_quads[input.quad_id];
+    let position = input.local_position - quad.bounds.origin;
+    let tile = floor(position / max(quad.tile_size, vec2<f32>(0.001)));
+    let tile_position = position - tile * quad.tile_size;
+    let corner_radii = Corners(quad.corner_radius, quad.corner_radius, quad.corner_radius, quad.corner_radius);
+    let alpha = saturate(0.5 - quad_sdf(tile_position, quad.quad_bounds, corner_radii));
+    // Tiles alternate colors like the squares of a checkerboard.
+    let alternate = (i32(tile.x) + i32(tile.y)) % 2 != 0;
+    let color = select(input.color, input.alternate_color, alternate);
+    return blend_color(color, alpha * content_mask_alpha(input.position.xy, quad.content_mask));
+}
+
 // --- shadows --- //
 
 struct Shadow {
@@ -915,8 +1150,9 @@ struct Shadow {
     blur_radius: f32,
     bounds: Bounds,
     corner_radii: Corners,
-    content_mask: Bounds,
+    content_mask: ContentMask,
     color: Hsla,
+    transformation: TransformationMatrix,
 }
 var<storage, read> b_shadows: array<Shadow>;
 
@@ -926,6 +1162,7 @@ struct ShadowVarying {
     @location(1) @interpolate(flat) shadow_id: u32,
     //TODO: use `clip_distance` once Naga supports it
     @location(3) clip_distances: vec4<f32>,
+    @location(4) local_position: vec2<f32>,
 }
 
 @vertex
@@ -940,10 +1177,11 @@ fn vs_shadow(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) ins
     shadow.bounds.size += 2.0 * vec2<f32>(margin);
 
     var out = ShadowVarying();
-    out.position = to_device_position(unit_vertex, shadow.bounds);
+    out.position = to_device_position_transformed(unit_vertex, shadow.bounds, shadow.transformation);
+    out.local_position = unit_vertex * shadow.bounds.size + shadow.bounds.origin;
     out.color = hsla_to_rgba(shadow.color);
     out.shadow_id = instance_id;
-    out.clip_distances = distance_from_clip_rect(unit_vertex, shadow.bounds, shadow.content_mask);
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, shadow.bounds, shadow.content_mask.bounds, shadow.transformation);
     return out;
 }
 
@@ -955,30 +1193,104 @@ fn fs_shadow(input: ShadowVarying) -> @location(0) vec4<f32> {
     }
 
     let shadow = b_shadows[input.shadow_id];
-    let half_size = shadow.bounds.size / 2.0;
-    let center = shadow.bounds.origin + half_size;
-    let center_to_point = input.position.xy - center;
+    let alpha = shadow_alpha(input.local_position, shadow.bounds, shadow.corner_radii, shadow.blur_radius);
+    return blend_color(input.color, alpha * content_mask_alpha(input.position.xy, shadow.content_mask));
+}
 
-    let corner_radius = pick_corner_radius(center_to_point, shadow.corner_radii);
+// --- backdrop blurs --- //
 
-    // The signal is only non-zero in a limited range, so don't waste samples
-    let low = center_to_point.y - half_size.y;
-    let high = center_to_point.y + half_size.y;
-    let start = clamp(-3.0 * shadow.blur_radius, low, high);
-    let end = clamp(3.0 * shadow.blur_radius, low, high);
+struct BackdropBlur {
+    order: u32,
+    blur_radius: f32,
+    bounds: Bounds,
+    corner_radii: Corners,
+    content_mask: ContentMask,
+}
+var<storage, read> b_backdrop_blurs: array<BackdropBlur>;
 
-    // Accumulate samples (we can get away with surprisingly few samples)
-    let step = (end - start) / 4.0;
-    var y = start + step * 0.5;
-    var alpha = 0.0;
-    for (var i = 0; i < 4; i += 1) {
-        let blur = blur_along_x(center_to_point.x, center_to_point.y - y,
-            shadow.blur_radius, corner_radius, half_size);
-        alpha +=  blur * gaussian(y, shadow.blur_radius) * step;
-        y += step;
+struct BackdropBlurParams {
+    direction: vec2<f32>,
+    pad: vec2<u32>,
+}
+var<uniform> backdrop_blur_params: BackdropBlurParams;
+
+// The number of samples taken on each side of a pixel by each blur pass.
+const BACKDROP_BLUR_SAMPLES: i32 = 16;
+
+struct BackdropBlurVarying {
+    @builtin(position) position: vec4<f32>,
+    @location(0) texture_coords: vec2<f32>,
+    @location(1) @interpolate(flat) blur_id: u32,
+    @location(3) clip_distances: vec4<f32>,
+}
+
+@vertex
+fn vs_backdrop_blur_pass(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> BackdropBlurVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+    var blur = b_backdrop_blurs[instance_id];
+
+    // The vertical pass reads the horizontal pass's results this far outside the blur.
+    let margin = 3.0 * blur.blur_radius;
+    blur.bounds.origin -= vec2<f32>(margin);
+    blur.bounds.size += 2.0 * vec2<f32>(margin);
+
+    var out = BackdropBlurVarying();
+    out.position = to_device_position(unit_vertex, blur.bounds);
+    out.texture_coords = (blur.bounds.origin + unit_vertex * blur.bounds.size) / globals.viewport_size;
+    out.blur_id = instance_id;
+    out.clip_distances = vec4<f32>(0.0);
+    return out;
+}
+
+@fragment
+fn fs_backdrop_blur_pass(input: BackdropBlurVarying) -> @location(0) vec4<f32> {
+    let blur = b_backdrop_blurs[input.blur_id];
+    let sigma = max(blur.blur_radius, 0.5);
+    // Spread the samples over three standard deviations, beyond which the weights are negligible.
+    let step = 3.0 * sigma / f32(BACKDROP_BLUR_SAMPLES);
+    let texture_step = backdrop_blur_params.direction * step / globals.viewport_size;
+
+    var color = vec4<f32>(0.0);
+    var total_weight = 0.0;
+    for (var i = -BACKDROP_BLUR_SAMPLES; i <= BACKDROP_BLUR_SAMPLES; i += 1) {
+        let weight = gaussian(f32(i) * step, sigma);
+        let position = input.texture_coords + f32(i) * texture_step;
+        color += textureSampleLevel(t_sprite, s_sprite, position, 0.0) * weight;
+        total_weight += weight;
     }
+    return color / total_weight;
+}
 
-    return blend_color(input.color, alpha);
+@vertex
+fn vs_backdrop_blur(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index) instance_id: u32) -> BackdropBlurVarying {
+    let unit_vertex = vec2<f32>(f32(vertex_id & 1u), 0.5 * f32(vertex_id & 2u));
+    let blur = b_backdrop_blurs[instance_id];
+
+    var out = BackdropBlurVarying();
+    out.position = to_device_position(unit_vertex, blur.bounds);
+    out.texture_coords = (blur.bounds.origin + unit_vertex * blur.bounds.size) / globals.viewport_size;
+    out.blur_id = instance_id;
+    out.clip_distances = distance_from_clip_rect(unit_vertex, blur.bounds, blur.content_mask.bounds);
+    return out;
+}
+
+@fragment
+fn fs_backdrop_blur(input: BackdropBlurVarying) -> @location(0) vec4<f32> {
+    // Alpha clip first, since we don't have `clip_distance`.
+    if (any(input.clip_distances < vec4<f32>(0.0))) {
+        return vec4<f32>(0.0);
+    }
+
+    let blur = b_backdrop_blurs[input.blur_id];
+    let sample = textureSampleLevel(t_sprite, s_sprite, input.texture_coords, 0.0);
+    let distance = quad_sdf(input.position.xy, blur.bounds, blur.corner_radii);
+    let coverage = saturate(0.5 - distance) * content_mask_alpha(input.position.xy, blur.content_mask);
+    // The blurred backdrop was read from the target, so its color is already in the target's
+    // alpha mode.
+    if (globals.premultiplied_alpha != 0u) {
+        return sample * coverage;
+    }
+    return vec4<f32>(sample.rgb, sample.a * coverage);
 }
 
 // --- path rasterization --- //
@@ -988,6 +1300,7 @@ struct PathRasterizationVertex {
     st_position: vec2<f32>,
     color: Background,
     bounds: Bounds,
+    content_mask: ContentMask,
 }
 
 var<storage, read> b_path_vertices: array<PathRasterizationVertex>;
@@ -1034,6 +1347,7 @@ fn fs_path_rasterization(input: PathRasterizationVarying) -> @location(0) vec4<f
         let distance = f / length(gradient);
         alpha = saturate(0.5 - distance);
     }
+    alpha *= content_mask_alpha(input.position.xy, v.content_mask);
     let gradient_color = prepare_gradient_color(
         background.tag,
         background.color_space,
@@ -1086,10 +1400,13 @@ struct Underline {
     order: u32,
     pad: u32,
     bounds: Bounds,
-    content_mask: Bounds,
+    content_mask: ContentMask,
     color: Hsla,
     thickness: f32,
     wavy: u32,
+    wave_amplitude: f32,
+    wavelength: f32,
+    transformation: TransformationMatrix,
 }
 var<storage, read> b_underlines: array<Underline>;
 
@@ -1099,6 +1416,7 @@ struct UnderlineVarying {
     @location(1) @interpolate(flat) underline_id: u32,
     //TODO: use `clip_distance` once Naga supports it
     @location(3) clip_distances: vec4<f32>,
+    @location(4) local_position: vec2<f32>,
 }
 
 @vertex
@@ -1107,34 +1425,33 @@ fn vs_underline(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index)
     let underline = b_underlines[instance_id];
 
     var out = UnderlineVarying();
-    out.position = to_device_position(unit_vertex, underline.bounds);
+    out.position = to_device_position_transformed(unit_vertex, underline.bounds, underline.transformation);
+    out.local_position = unit_vertex * underline.bounds.size + underline.bounds.origin;
     out.color = hsla_to_rgba(underline.color);
     out.underline_id = instance_id;
-    out.clip_distances = distance_from_clip_rect(unit_vertex, underline.bounds, underline.content_mask);
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, underline.bounds, underline.content_mask.bounds, underline.transformation);
     return out;
 }
 
 @fragment
 fn fs_underline(input: UnderlineVarying) -> @location(0) vec4<f32> {
-    const WAVE_FREQUENCY: f32 = 2.0;
-    const WAVE_HEIGHT_RATIO: f32 = 0.8;
-
     // Alpha clip first, since we don't have `clip_distance`.
     if (any(input.clip_distances < vec4<f32>(0.0))) {
         return vec4<f32>(0.0);
     }
 
     let underline = b_underlines[input.underline_id];
+    let mask_alpha = content_mask_alpha(input.position.xy, underline.content_mask);
     if ((underline.wavy & 0xFFu) == 0u)
     {
-        return blend_color(input.color, input.color.a);
+        return blend_color(input.color, input.color.a * mask_alpha);
     }
 
     let half_thickness = underline.thickness * 0.5;
 
-    let st = (input.position.xy - underline.bounds.origin) / underline.bounds.size.y - vec2<f32>(0.0, 0.5);
-    let frequency = M_PI_F * WAVE_FREQUENCY * underline.thickness / underline.bounds.size.y;
-    let amplitude = (underline.thickness * WAVE_HEIGHT_RATIO) / underline.bounds.size.y;
+    let st = (input.local_position - underline.bounds.origin) / underline.bounds.size.y - vec2<f32>(0.0, 0.5);
+    let frequency = 2.0 * M_PI_F * underline.bounds.size.y / max(underline.wavelength, 0.001);
+    let amplitude = underline.wave_amplitude / underline.bounds.size.y;
 
     let sine = sin(st.x * frequency) * amplitude;
     let dSine = cos(st.x * frequency) * amplitude * frequency;
@@ -1143,16 +1460,16 @@ fn fs_underline(input: UnderlineVarying) -> @location(0) vec4<f32> {
     let distance_from_top_border = distance_in_pixels - half_thickness;
     let distance_from_bottom_border = distance_in_pixels + half_thickness;
     let alpha = saturate(0.5 - max(-distance_from_bottom_border, distance_from_top_border));
-    return blend_color(input.color, alpha * input.color.a);
+    return blend_color(input.color, alpha * input.color.a * mask_alpha);
 }
 
 // --- monochrome sprites --- //
 
 struct MonochromeSprite {
     order: u32,
-    pad: u32,
+    distance_field: u32,
     bounds: Bounds,
-    content_mask: Bounds,
+    content_mask: ContentMask,
     color: Hsla,
     tile: AtlasTile,
     transformation: TransformationMatrix,
@@ -1163,7 +1480,9 @@ struct MonoSpriteVarying {
     @builtin(position) position: vec4<f32>,
     @location(0) tile_position: vec2<f32>,
     @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) @interpolate(flat) distance_field: u32,
     @location(3) clip_distances: vec4<f32>,
+    @location(4) @interpolate(flat) sprite_id: u32,
 }
 
 @vertex
@@ -1176,35 +1495,61 @@ fn vs_mono_sprite(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index
 
     out.tile_position = to_tile_position(unit_vertex, sprite.tile);
     out.color = hsla_to_rgba(sprite.color);
-    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds, sprite.content_mask, sprite.transformation);
+    out.distance_field = sprite.distance_field;
+    out.sprite_id = instance_id;
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds, sprite.content_mask.bounds, sprite.transformation);
     return out;
 }
 
+// The distance field value of sprites whose tile holds coverage that isn't corrected.
+const LINEAR_COVERAGE: u32 = 0xffffffffu;
+
+// Returns the coverage of a signed distance field sample, given how much the sample changes
+// across a pixel and the packed edge value and falloff width of the field.
+fn distance_field_alpha(sample: f32, sample_width: f32, distance_field: u32) -> f32 {
+    let edge = f32(distance_field & 0xffffu) / 65535.0;
+    let falloff = max(f32(distance_field >> 16u) / 65535.0, sample_width * 0.5);
+    return smoothstep(edge - falloff, edge + falloff, sample);
+}
+
 @fragment
 fn fs_mono_sprite(input: MonoSpriteVarying) -> @location(0) vec4<f32> {
     let sample = textureSample(t_sprite, s_sprite, input.tile_position).r;
-    let alpha_corrected = apply_contrast_and_gamma_correction(sample, input.color.rgb, grayscale_enhanced_contrast, gamma_ratios);
+    // Derivatives must be taken in uniform control flow.
+    let sample_width = fwidth(sample);
+    var alpha_corrected: f32;
+    if (input.distance_field == LINEAR_COVERAGE) {
+        alpha_corrected = sample;
+    } else if (input.distance_field != 0u) {
+        alpha_corrected = distance_field_alpha(sample, sample_width, input.distance_field);
+    } else {
+        alpha_corrected = apply_contrast_and_gamma_correction(sample, input.color.rgb, grayscale_enhanced_contrast, gamma_ratios);
+        alpha_corrected = enhance_contrast(alpha_corrected, light_text_contrast(light_text_enhanced_contrast, input.color.rgb));
+    }
 
     // Alpha clip after using the derivatives.
     if (any(input.clip_distances < vec4<f32>(0.0))) {
         return vec4<f32>(0.0);
     }
 
+    let sprite = b_mono_sprites[input.sprite_id];
+    let mask_alpha = content_mask_alpha(input.position.xy, sprite.content_mask);
     // convert to srgb space as the rest of the code (output swapchain) expects that
-    return blend_color(input.color, alpha_corrected);
+    return blend_color(input.color, alpha_corrected * mask_alpha);
 }
 
 // --- polychrome sprites --- //
 
 struct PolychromeSprite {
     order: u32,
-    pad: u32,
+    nearest: u32,
     grayscale: u32,
     opacity: f32,
     bounds: Bounds,
-    content_mask: Bounds,
+    content_mask: ContentMask,
     corner_radii: Corners,
     tile: AtlasTile,
+    transformation: TransformationMatrix,
 }
 var<storage, read> b_poly_sprites: array<PolychromeSprite>;
 
@@ -1213,6 +1558,7 @@ struct PolySpriteVarying {
     @location(0) tile_position: vec2<f32>,
     @location(1) @interpolate(flat) sprite_id: u32,
     @location(3) clip_distances: vec4<f32>,
+    @location(4) local_position: vec2<f32>,
 }
 
 @vertex
@@ -1221,30 +1567,41 @@ fn vs_poly_sprite(@builtin(vertex_index) vertex_id: u32, @builtin(instance_index
     let sprite = b_poly_sprites[instance_id];
 
     var out = PolySpriteVarying();
-    out.position = to_device_position(unit_vertex, sprite.bounds);
+    out.position = to_device_position_transformed(unit_vertex, sprite.bounds, sprite.transformation);
+    out.local_position = unit_vertex * sprite.bounds.size + sprite.bounds.origin;
     out.tile_position = to_tile_position(unit_vertex, sprite.tile);
     out.sprite_id = instance_id;
-    out.clip_distances = distance_from_clip_rect(unit_vertex, sprite.bounds, sprite.content_mask);
+    out.clip_distances = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds, sprite.content_mask.bounds, sprite.transformation);
     return out;
 }
 
 @fragment
 fn fs_poly_sprite(input: PolySpriteVarying) -> @location(0) vec4<f32> {
-    let sample = textureSample(t_sprite, s_sprite, input.tile_position);
+    var sample = textureSample(t_sprite, s_sprite, input.tile_position);
     // Alpha clip after using the derivatives.
     if (any(input.clip_distances < vec4<f32>(0.0))) {
         return vec4<f32>(0.0);
     }
 
     let sprite = b_poly_sprites[input.sprite_id];
-    let distance = quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
+    if (sprite.nearest != 0u) {
+        let atlas_size = vec2<f32>(textureDimensions(t_sprite, 0));
+        let texel = clamp(
+            vec2<i32>(floor(input.tile_position * atlas_size)),
+            sprite.tile.bounds.origin,
+            sprite.tile.bounds.origin + sprite.tile.bounds.size - vec2<i32>(1),
+        );
+        sample = textureLoad(t_sprite, texel, 0);
+    }
+    let distance = quad_sdf(input.local_position, sprite.bounds, sprite.corner_radii);
 
     var color = sample;
     if ((sprite.grayscale & 0xFFu) != 0u) {
         let grayscale = dot(color.rgb, GRAYSCALE_FACTORS);
         color = vec4<f32>(vec3<f32>(grayscale), sample.a);
     }
-    return blend_color(color, sprite.opacity * saturate(0.5 - distance));
+    let mask_alpha = content_mask_alpha(input.position.xy, sprite.content_mask);
+    return blend_color(color, sprite.opacity * saturate(0.5 - distance) * mask_alpha);
 }
 
 // --- surfaces --- //
@@ -0,0 +1,73 @@
+use crate::{
+    AtlasKey, AtlasPixels, AtlasTextureId, AtlasTile, Bounds, DevicePixels, PlatformAtlas, Point,
+    Size, TileId,
+};
+use anyhow::Result;
+use collections::FxHashMap;
+use parking_lot::Mutex;
+use std::{borrow::Cow, mem};
+
+/// A sprite atlas that keeps the pixels of its tiles in memory, each in a texture of its own, so
+/// that a frame drawn with it can be exported along with its glyphs and images.
+#[derive(Default)]
+pub(crate) struct CaptureAtlas(Mutex<CapturedSprites>);
+
+/// The tiles inserted into a [`CaptureAtlas`] and their pixels.
+#[derive(Default)]
+pub(crate) struct CapturedSprites {
+    tiles: FxHashMap<AtlasKey, AtlasTile>,
+    textures: FxHashMap<AtlasTextureId, (Size<DevicePixels>, Vec<u8>)>,
+}
+
+impl CaptureAtlas {
+    /// Takes the tiles inserted so far, along with their pixels.
+    pub(crate) fn take(&self) -> CapturedSprites {
+        mem::take(&mut *self.0.lock())
+    }
+}
+
+impl PlatformAtlas for CaptureAtlas {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        if let Some(tile) = self.0.lock().tiles.get(key) {
+            return Ok(Some(tile.clone()));
+        }
+        let Some((size, bytes)) = build()? else {
+            return Ok(None);
+        };
+
+        let mut sprites = self.0.lock();
+        let texture_id = AtlasTextureId {
+            index: sprites.textures.len() as u32,
+            kind: key.texture_kind(),
+        };
+        let tile = AtlasTile {
+            texture_id,
+            tile_id: TileId(texture_id.index),
+            padding: 0,
+            bounds: Bounds {
+                origin: Point::default(),
+                size,
+            },
+        };
+        sprites
+            .textures
+            .insert(texture_id, (size, bytes.into_owned()));
+        sprites.tiles.insert(key.clone(), tile.clone());
+        Ok(Some(tile))
+    }
+
+    fn remove(&self, key: &AtlasKey) {
+        self.0.lock().tiles.remove(key);
+    }
+}
+
+impl AtlasPixels for CapturedSprites {
+    fn texture_pixels(&self, texture_id: AtlasTextureId) -> Option<(Size<DevicePixels>, &[u8])> {
+        let (size, pixels) = self.textures.get(&texture_id)?;
+        Some((*size, pixels))
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Context as _, Result};
+use collections::BTreeMap;
+use std::{fs::File, io, path::PathBuf};
+use util::ResultExt;
+
+/// A JSON file mapping string keys to string values, which persists across launches.
+///
+/// Every update rewrites the whole file while holding an exclusive lock on a lock file next to
+/// it, so that processes sharing a store don't lose each other's updates. A store file that can't
+/// be parsed is treated as empty, and is replaced on the next update.
+#[derive(Clone, Debug)]
+pub(crate) struct KeyValueStore {
+    path: PathBuf,
+}
+
+impl KeyValueStore {
+    /// Creates a store kept in the file at `path`.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the store of the application identified by `app_id`, which lives in the user's
+    /// local data directory.
+    pub(crate) fn for_app(app_id: &str) -> Result<Self> {
+        Ok(Self::new(
+            data_directory()?
+                .join(sanitize_file_name(app_id))
+                .join("storage.json"),
+        ))
+    }
+
+    /// Returns the identifier of the running application's store when it doesn't set one: the
+    /// name of the executable, followed by a hash of its path so that unrelated executables with
+    /// the same name don't share a store.
+    pub(crate) fn default_app_id() -> Result<String> {
+        let executable = std::env::current_exe().context("failed to locate the executable")?;
+        let name = executable
+            .file_stem()
+            .context("executable path has no file name")?
+            .to_string_lossy();
+        let path_hash = seahash::hash(executable.as_os_str().as_encoded_bytes());
+        Ok(format!("{name}-{:08x}", path_hash as u32))
+    }
+
+    /// Reads the value stored under `key`.
+    pub(crate) fn read(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.entries()?.remove(key))
+    }
+
+    /// Reads every key and value in the store.
+    pub(crate) fn entries(&self) -> Result<BTreeMap<String, String>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::default());
+        }
+        let lock = self.lock_file()?;
+        lock.lock_shared()
+            .with_context(|| format!("failed to lock {:?}", self.lock_path()))?;
+        Ok(self.load())
+    }
+
+    /// Stores `value` under `key`, or removes the key if `value` is `None`.
+    pub(crate) fn write(&self, key: &str, value: Option<String>) -> Result<()> {
+        self.update(|entries| {
+            if entries.get(key) == value.as_ref() {
+                return false;
+            }
+            match value {
+                Some(value) => entries.insert(key.to_string(), value),
+                None => entries.remove(key),
+            };
+            true
+        })
+    }
+
+    /// Applies `update` to the stored entries, and writes them back if it returns `true`.
+    pub(crate) fn update(
+        &self,
+        update: impl FnOnce(&mut BTreeMap<String, String>) -> bool,
+    ) -> Result<()> {
+        let directory = self
+            .path
+            .parent()
+            .context("key-value store path has no parent directory")?;
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("failed to create directory {directory:?}"))?;
+        let lock = self.lock_file()?;
+        lock.lock()
+            .with_context(|| format!("failed to lock {:?}", self.lock_path()))?;
+
+        let mut entries = self.load();
+        if !update(&mut entries) {
+            return Ok(());
+        }
+        let contents =
+            serde_json::to_vec_pretty(&entries).context("failed to serialize key-value store")?;
+        // Writing to a temporary file first means a crash mid-write can't corrupt the store.
+        let temporary_path = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&temporary_path, contents)
+            .with_context(|| format!("failed to write {temporary_path:?}"))?;
+        std::fs::rename(&temporary_path, &self.path)
+            .with_context(|| format!("failed to replace {:?}", self.path))
+    }
+
+    fn load(&self) -> BTreeMap<String, String> {
+        match std::fs::read(&self.path) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .with_context(|| format!("failed to parse key-value store {:?}", self.path))
+                .log_err()
+                .unwrap_or_default(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::default(),
+            Err(error) => {
+                log::error!("failed to read key-value store {:?}: {error}", self.path);
+                BTreeMap::default()
+            }
+        }
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.path.with_extension("json.lock")
+    }
+
+    fn lock_file(&self) -> Result<File> {
+        let path = self.lock_path();
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {path:?}"))
+    }
+}
+
+/// Replaces the characters that aren't safe in a file name on every platform.
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '.') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn data_directory() -> Result<PathBuf> {
+    Ok(home_directory()?.join("Library/Application Support"))
+}
+
+#[cfg(target_os = "windows")]
+fn data_directory() -> Result<PathBuf> {
+    std::env::var_os("LOCALAPPDATA")
+        .map(PathBuf::from)
+        .context("LOCALAPPDATA is not set")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn data_directory() -> Result<PathBuf> {
+    match std::env::var_os("XDG_DATA_HOME").filter(|directory| !directory.is_empty()) {
+        Some(directory) => Ok(PathBuf::from(directory)),
+        None => Ok(home_directory()?.join(".local/share")),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn home_directory() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .context("HOME is not set")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_store(name: &str) -> (PathBuf, KeyValueStore) {
+        let directory = std::env::temp_dir().join(format!(
+            "gpui-key-value-store-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&directory).ok();
+        let store = KeyValueStore::new(directory.join("storage.json"));
+        (directory, store)
+    }
+
+    #[test]
+    fn test_round_trip_and_delete() {
+        let (directory, store) = temporary_store("round-trip");
+        assert_eq!(store.read("theme").unwrap(), None);
+
+        store.write("theme", Some("dark".into())).unwrap();
+        store.write("font", Some("mono".into())).unwrap();
+        assert_eq!(store.read("theme").unwrap().as_deref(), Some("dark"));
+        let reopened = KeyValueStore::new(directory.join("storage.json"));
+        assert_eq!(reopened.read("font").unwrap().as_deref(), Some("mono"));
+
+        store.write("theme", None).unwrap();
+        assert_eq!(store.read("theme").unwrap(), None);
+        assert_eq!(store.read("font").unwrap().as_deref(), Some("mono"));
+        std::fs::remove_dir_all(&directory).ok();
+    }
+
+    #[test]
+    fn test_corrupt_store_is_replaced() {
+        let (directory, store) = temporary_store("corrupt");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("storage.json"), "{not json").unwrap();
+        assert_eq!(store.read("theme").unwrap(), None);
+
+        store.write("theme", Some("light".into())).unwrap();
+        assert_eq!(store.read("theme").unwrap().as_deref(), Some("light"));
+        std::fs::remove_dir_all(&directory).ok();
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("com.example/App:1"), "com.example_App_1");
+    }
+}
@@ -224,6 +224,22 @@ impl Keystroke {
         unparse(&self.modifiers, &self.key)
     }
 
+    /// Returns this keystroke with only its modifiers and the kind of key pressed, replacing any
+    /// key that types a character with "character", e.g. for logs that mustn't reveal what was
+    /// typed.
+    pub(crate) fn redacted(&self) -> Self {
+        let key = if is_printable_key(&self.key) {
+            "character".to_string()
+        } else {
+            self.key.clone()
+        };
+        Self {
+            modifiers: self.modifiers,
+            key,
+            key_char: None,
+        }
+    }
+
     /// Returns true if this keystroke left
     /// the ime system in an incomplete state.
     pub fn is_ime_in_progress(&self) -> bool {
@@ -3,6 +3,7 @@ use std::{
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
+    time::Duration,
 };
 #[cfg(any(feature = "wayland", feature = "x11"))]
 use std::{
@@ -10,7 +11,6 @@ use std::{
     fs::File,
     io::Read as _,
     os::fd::{AsFd, AsRawFd, FromRawFd},
-    time::Duration,
 };
 
 use anyhow::{Context as _, anyhow};
@@ -22,11 +22,12 @@ use util::command::{new_smol_command, new_std_command};
 use xkbcommon::xkb::{self, Keycode, Keysym, State};
 
 use crate::{
-    Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
-    ForegroundExecutor, Keymap, LinuxDispatcher, Menu, MenuItem, OwnedMenu, PathPromptOptions,
-    Pixels, Platform, PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper,
-    PlatformTextSystem, PlatformWindow, Point, PriorityQueueCalloopReceiver, Result,
-    RunnableVariant, Task, WindowAppearance, WindowParams, px,
+    Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorImage, CursorStyle,
+    CustomCursorId, DEFAULT_CARET_BLINK_INTERVAL, DisplayId, ForegroundExecutor, Keymap,
+    LinuxDispatcher, Menu, MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform,
+    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
+    PlatformWindow, Point, PowerState, PriorityQueueCalloopReceiver, Result, RunnableVariant, Task,
+    WindowAppearance, WindowParams, px,
 };
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
@@ -108,6 +109,8 @@ pub trait LinuxClient {
         options: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>>;
     fn set_cursor_style(&self, style: CursorStyle);
+    fn register_cursor_image(&self, _id: CustomCursorId, _image: CursorImage) {}
+    fn drop_cursor_image(&self, _id: CustomCursorId) {}
     fn open_uri(&self, uri: &str);
     fn reveal_path(&self, path: PathBuf);
     fn write_to_primary(&self, item: ClipboardItem);
@@ -143,6 +146,8 @@ pub(crate) struct LinuxCommon {
     pub(crate) text_system: Arc<dyn PlatformTextSystem>,
     pub(crate) appearance: WindowAppearance,
     pub(crate) auto_hide_scrollbars: bool,
+    pub(crate) caret_blinks: bool,
+    pub(crate) caret_blink_interval: Duration,
     pub(crate) callbacks: PlatformHandlers,
     pub(crate) signal: LoopSignal,
     pub(crate) menus: Vec<OwnedMenu>,
@@ -166,6 +171,8 @@ impl LinuxCommon {
             text_system,
             appearance: WindowAppearance::Light,
             auto_hide_scrollbars: false,
+            caret_blinks: true,
+            caret_blink_interval: DEFAULT_CARET_BLINK_INTERVAL,
             callbacks,
             signal,
             menus: Vec::new(),
@@ -536,10 +543,26 @@ impl<P: LinuxClient + 'static> Platform for P {
         self.set_cursor_style(style)
     }
 
+    fn register_cursor_image(&self, id: CustomCursorId, image: CursorImage) {
+        self.register_cursor_image(id, image)
+    }
+
+    fn drop_cursor_image(&self, id: CustomCursorId) {
+        self.drop_cursor_image(id)
+    }
+
     fn should_auto_hide_scrollbars(&self) -> bool {
         self.with_common(|common| common.auto_hide_scrollbars)
     }
 
+    fn caret_blink_interval(&self) -> Option<Duration> {
+        self.with_common(|common| common.caret_blinks.then_some(common.caret_blink_interval))
+    }
+
+    fn power_state(&self) -> PowerState {
+        power_state()
+    }
+
     fn write_credentials(&self, url: &str, username: &str, password: &[u8]) -> Task<Result<()>> {
         let url = url.to_string();
         let username = username.to_string();
@@ -699,12 +722,53 @@ pub(super) fn reveal_path_internal(
         .detach();
 }
 
+fn power_state() -> PowerState {
+    let read_attribute = |path: &Path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    let on_battery = std::fs::read_dir("/sys/class/power_supply")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|supply| {
+            let path = supply.path();
+            read_attribute(&path.join("type")).as_deref() == Some("Battery")
+                && read_attribute(&path.join("status")).as_deref() == Some("Discharging")
+        });
+    let low_power_mode = read_attribute(Path::new("/sys/firmware/acpi/platform_profile"))
+        .as_deref()
+        == Some("low-power");
+
+    PowerState {
+        on_battery,
+        low_power_mode,
+    }
+}
+
 #[allow(unused)]
 pub(super) fn is_within_click_distance(a: Point<Pixels>, b: Point<Pixels>) -> bool {
     let diff = a - b;
     diff.x.abs() <= DOUBLE_CLICK_DISTANCE && diff.y.abs() <= DOUBLE_CLICK_DISTANCE
 }
 
+/// Converts the millisecond time that X11 and Wayland servers attach to input events, which they
+/// take from `CLOCK_MONOTONIC`, into an [`Instant`](std::time::Instant).
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn input_event_timestamp(event_time: u32) -> Option<std::time::Instant> {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } != 0 {
+        return None;
+    }
+    let now_millis = now.tv_sec as u64 * 1000 + now.tv_nsec as u64 / 1_000_000;
+    crate::platform::input_event_instant_from_millis(event_time, now_millis as u32)
+}
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(super) fn get_xkb_compose_state(cx: &xkb::Context) -> Option<xkb::compose::State> {
     let mut locales = Vec::default();
@@ -765,6 +829,7 @@ impl CursorStyle {
             CursorStyle::DragLink => &["alias"],
             CursorStyle::DragCopy => &["copy"],
             CursorStyle::ContextualMenu => &["context-menu"],
+            CursorStyle::Custom(_) => &[DEFAULT_CURSOR_ICON_NAME],
             CursorStyle::None => {
                 #[cfg(debug_assertions)]
                 panic!("CursorStyle::None should be handled separately in the client");
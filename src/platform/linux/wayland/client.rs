@@ -72,12 +72,13 @@ use super::{
 };
 
 use crate::{
-    AnyWindowHandle, Bounds, Capslock, CursorStyle, DOUBLE_CLICK_INTERVAL, DevicePixels, DisplayId,
-    FileDropEvent, ForegroundExecutor, KeyDownEvent, KeyUpEvent, Keystroke, LinuxCommon,
-    LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
-    MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection, Pixels, PlatformDisplay,
-    PlatformInput, PlatformKeyboardLayout, Point, ResultExt as _, SCROLL_LINES, ScrollDelta,
-    ScrollWheelEvent, Size, TouchPhase, WindowParams, point, profiler, px, size,
+    AnyWindowHandle, Bounds, Capslock, CursorImage, CursorStyle, CustomCursorId,
+    DOUBLE_CLICK_INTERVAL, DevicePixels, DisplayId, FileDropEvent, ForegroundExecutor,
+    KeyDownEvent, KeyUpEvent, Keystroke, LinuxCommon, LinuxKeyboardLayout, Modifiers,
+    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent,
+    MouseUpEvent, NavigationDirection, Pixels, PlatformDisplay, PlatformInput,
+    PlatformKeyboardLayout, Point, ResultExt as _, SCROLL_LINES, ScrollDelta, ScrollWheelEvent,
+    Size, TouchPhase, WindowParams, point, profiler, px, size,
 };
 use crate::{
     RunnableVariant, TaskTiming,
@@ -86,8 +87,8 @@ use crate::{
 use crate::{
     SharedString,
     platform::linux::{
-        LinuxClient, get_xkb_compose_state, is_within_click_distance, open_uri_internal, read_fd,
-        reveal_path_internal,
+        LinuxClient, get_xkb_compose_state, input_event_timestamp, is_within_click_distance,
+        open_uri_internal, read_fd, reveal_path_internal,
         wayland::{
             clipboard::{Clipboard, DataOffer, FILE_LIST_MIME_TYPE, TEXT_MIME_TYPES},
             cursor::Cursor,
@@ -224,6 +225,7 @@ pub(crate) struct WaylandClientState {
     pub modifiers: Modifiers,
     pub capslock: Capslock,
     axis_source: AxisSource,
+    scroll_timestamp: Option<Instant>,
     pub mouse_location: Option<Point<Pixels>>,
     continuous_scroll_delta: Option<Point<Pixels>>,
     discrete_scroll_delta: Option<Point<f32>>,
@@ -580,6 +582,16 @@ impl WaylandClient {
                             client.cursor.set_size(size);
                         }
                     }
+                    XDPEvent::CaretBlinks(blinks) => {
+                        if let Some(client) = client.0.upgrade() {
+                            client.borrow_mut().common.caret_blinks = blinks;
+                        }
+                    }
+                    XDPEvent::CaretBlinkInterval(interval) => {
+                        if let Some(client) = client.0.upgrade() {
+                            client.borrow_mut().common.caret_blink_interval = interval;
+                        }
+                    }
                 }
             })
             .unwrap();
@@ -632,6 +644,7 @@ impl WaylandClient {
             capslock: Capslock { on: false },
             scroll_event_received: false,
             axis_source: AxisSource::Wheel,
+            scroll_timestamp: None,
             mouse_location: None,
             continuous_scroll_delta: None,
             discrete_scroll_delta: None,
@@ -763,6 +776,15 @@ impl LinuxClient for WaylandClient {
                     .clone()
                     .expect("window is focused by pointer");
                 wl_pointer.set_cursor(serial, None, 0, 0);
+            } else if let CursorStyle::Custom(id) = style
+                && let Some(wl_pointer) = state.wl_pointer.clone()
+                && let Some(scale) = state
+                    .mouse_focused_window
+                    .as_ref()
+                    .map(|window| window.primary_output_scale())
+                && state.cursor.set_custom(&wl_pointer, serial, id, scale)
+            {
+                // Custom images are drawn on the cursor surface, even with cursor-shape-v1.
             } else if let Some(cursor_shape_device) = &state.cursor_shape_device {
                 cursor_shape_device.set_shape(serial, style.to_shape());
             } else if let Some(focused_window) = &state.mouse_focused_window {
@@ -779,6 +801,19 @@ impl LinuxClient for WaylandClient {
         }
     }
 
+    fn register_cursor_image(&self, id: CustomCursorId, image: CursorImage) {
+        self.0.borrow_mut().cursor.register_image(id, image);
+    }
+
+    fn drop_cursor_image(&self, id: CustomCursorId) {
+        let mut state = self.0.borrow_mut();
+        state.cursor.drop_image(id);
+        if state.cursor_style == Some(CursorStyle::Custom(id)) {
+            drop(state);
+            self.set_cursor_style(CursorStyle::Arrow);
+        }
+    }
+
     fn open_uri(&self, uri: &str) {
         let mut state = self.0.borrow_mut();
         if let (Some(activation), Some(window)) = (
@@ -1338,6 +1373,7 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
                 let input = PlatformInput::ModifiersChanged(ModifiersChangedEvent {
                     modifiers: state.modifiers,
                     capslock: state.capslock,
+                    timestamp: None,
                 });
                 drop(state);
 
@@ -1351,11 +1387,12 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
             }
             wl_keyboard::Event::Key {
                 serial,
+                time,
                 key,
                 state: WEnum::Value(key_state),
-                ..
             } => {
                 state.serial_tracker.update(SerialKind::KeyPress, serial);
+                let timestamp = input_event_timestamp(time);
 
                 let focused_window = state.keyboard_focused_window.clone();
                 let Some(focused_window) = focused_window else {
@@ -1414,6 +1451,8 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
                             keystroke: keystroke.clone(),
                             is_held: false,
                             prefer_character_input: false,
+                            scan_code: Some(keycode.raw()),
+                            timestamp,
                         });
 
                         state.repeat.current_id += 1;
@@ -1429,6 +1468,8 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
                                     keystroke,
                                     is_held: true,
                                     prefer_character_input: false,
+                                    scan_code: Some(keycode.raw()),
+                                    timestamp: None,
                                 });
                                 move |event_timestamp, _metadata, this| {
                                     let mut client = this.get_client();
@@ -1459,6 +1500,8 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for WaylandClientStatePtr {
                     wl_keyboard::KeyState::Released if !keysym.is_modifier_key() => {
                         let input = PlatformInput::KeyUp(KeyUpEvent {
                             keystroke: Keystroke::from_xkb(keymap_state, state.modifiers, keycode),
+                            scan_code: Some(keycode.raw()),
+                            timestamp,
                         });
 
                         if state.repeat.current_keycode == Some(keycode) {
@@ -1515,6 +1558,8 @@ impl Dispatch<zwp_text_input_v3::ZwpTextInputV3, ()> for WaylandClientStatePtr {
                             },
                             is_held: false,
                             prefer_character_input: false,
+                            scan_code: None,
+                            timestamp: None,
                         }));
                     } else {
                         window.handle_ime(ImeInput::InsertText(commit_text));
@@ -1614,6 +1659,16 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                                 .clone()
                                 .expect("window is focused by pointer");
                             wl_pointer.set_cursor(serial, None, 0, 0);
+                        } else if let CursorStyle::Custom(id) = style
+                            && state.cursor.set_custom(
+                                wl_pointer,
+                                serial,
+                                id,
+                                window.primary_output_scale(),
+                            )
+                        {
+                            // Custom images are drawn on the cursor surface, even with
+                            // cursor-shape-v1.
                         } else if let Some(cursor_shape_device) = &state.cursor_shape_device {
                             cursor_shape_device.set_shape(serial, style.to_shape());
                         } else {
@@ -1633,6 +1688,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                         position: state.mouse_location.unwrap(),
                         pressed_button: state.button_pressed,
                         modifiers: state.modifiers,
+                        timestamp: None,
                     });
                     state.mouse_focused_window = None;
                     state.mouse_location = None;
@@ -1644,9 +1700,9 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                 }
             }
             wl_pointer::Event::Motion {
+                time,
                 surface_x,
                 surface_y,
-                ..
             } => {
                 if state.mouse_focused_window.is_none() {
                     return;
@@ -1665,6 +1721,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                         position: state.mouse_location.unwrap(),
                         pressed_button: state.button_pressed,
                         modifiers: state.modifiers,
+                        timestamp: input_event_timestamp(time),
                     });
                     drop(state);
                     window.handle_input(input);
@@ -1672,9 +1729,9 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
             }
             wl_pointer::Event::Button {
                 serial,
+                time,
                 button,
                 state: WEnum::Value(button_state),
-                ..
             } => {
                 state.serial_tracker.update(SerialKind::MousePress, serial);
                 let button = linux_button_to_gpui(button);
@@ -1731,6 +1788,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                                 modifiers: state.modifiers,
                                 click_count: state.click.current_count,
                                 first_mouse: state.enter_token.take().is_some(),
+                                timestamp: input_event_timestamp(time),
                             });
                             drop(state);
                             window.handle_input(input);
@@ -1745,6 +1803,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                                 position: state.mouse_location.unwrap(),
                                 modifiers: state.modifiers,
                                 click_count: state.click.current_count,
+                                timestamp: input_event_timestamp(time),
                             });
                             drop(state);
                             window.handle_input(input);
@@ -1761,10 +1820,11 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                 state.axis_source = axis_source;
             }
             wl_pointer::Event::Axis {
+                time,
                 axis: WEnum::Value(axis),
                 value,
-                ..
             } => {
+                state.scroll_timestamp = input_event_timestamp(time);
                 if state.axis_source == AxisSource::Wheel {
                     return;
                 }
@@ -1853,6 +1913,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                     state.scroll_event_received = false;
                     let continuous = state.continuous_scroll_delta.take();
                     let discrete = state.discrete_scroll_delta.take();
+                    let timestamp = state.scroll_timestamp.take();
                     if let Some(continuous) = continuous {
                         if let Some(window) = state.mouse_focused_window.clone() {
                             let input = PlatformInput::ScrollWheel(ScrollWheelEvent {
@@ -1860,6 +1921,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                                 delta: ScrollDelta::Pixels(continuous),
                                 modifiers: state.modifiers,
                                 touch_phase: TouchPhase::Moved,
+                                timestamp,
                             });
                             drop(state);
                             window.handle_input(input);
@@ -1872,6 +1934,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                             delta: ScrollDelta::Lines(discrete),
                             modifiers: state.modifiers,
                             touch_phase: TouchPhase::Moved,
+                            timestamp,
                         });
                         drop(state);
                         window.handle_input(input);
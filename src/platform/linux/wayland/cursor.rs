@@ -1,11 +1,20 @@
-use crate::Globals;
+use std::fs::File;
+use std::io::Write as _;
+use std::os::fd::{AsFd, FromRawFd};
+
 use crate::platform::linux::{DEFAULT_CURSOR_ICON_NAME, log_cursor_icon_warning};
+use crate::{CursorImage, CustomCursorId, Globals, WaylandClientStatePtr};
 use anyhow::{Context as _, anyhow};
+use collections::HashMap;
 use util::ResultExt;
 
-use wayland_client::Connection;
+use wayland_client::protocol::wl_buffer::WlBuffer;
 use wayland_client::protocol::wl_surface::WlSurface;
-use wayland_client::protocol::{wl_pointer::WlPointer, wl_shm::WlShm};
+use wayland_client::protocol::{
+    wl_pointer::WlPointer,
+    wl_shm::{self, WlShm},
+};
+use wayland_client::{Connection, QueueHandle};
 use wayland_cursor::{CursorImageBuffer, CursorTheme};
 
 pub(crate) struct Cursor {
@@ -15,6 +24,9 @@ pub(crate) struct Cursor {
     surface: WlSurface,
     shm: WlShm,
     connection: Connection,
+    qh: QueueHandle<WaylandClientStatePtr>,
+    custom_images: HashMap<CustomCursorId, CursorImage>,
+    custom_buffer: Option<WlBuffer>,
 }
 
 pub(crate) struct LoadedTheme {
@@ -27,6 +39,9 @@ impl Drop for Cursor {
     fn drop(&mut self) {
         self.loaded_theme.take();
         self.surface.destroy();
+        if let Some(buffer) = self.custom_buffer.take() {
+            buffer.destroy();
+        }
     }
 }
 
@@ -39,6 +54,9 @@ impl Cursor {
             surface: globals.compositor.create_surface(&globals.qh, ()),
             shm: globals.shm.clone(),
             connection: connection.clone(),
+            qh: globals.qh.clone(),
+            custom_images: HashMap::default(),
+            custom_buffer: None,
         };
         this.set_theme_internal(None);
         this
@@ -149,4 +167,94 @@ impl Cursor {
         self.surface.damage(0, 0, width as i32, height as i32);
         self.surface.commit();
     }
+
+    pub fn register_image(&mut self, id: CustomCursorId, image: CursorImage) {
+        self.custom_images.insert(id, image);
+    }
+
+    pub fn drop_image(&mut self, id: CustomCursorId) {
+        self.custom_images.remove(&id);
+    }
+
+    /// Returns false if the image isn't registered or couldn't be drawn, so the caller can fall
+    /// back on the default cursor.
+    pub fn set_custom(
+        &mut self,
+        wl_pointer: &WlPointer,
+        serial_id: u32,
+        id: CustomCursorId,
+        scale: i32,
+    ) -> bool {
+        let Some(image) = self.custom_images.get(&id) else {
+            return false;
+        };
+        let hotspot = image.hotspot;
+        let Some((variant_scale_factor, size, bytes)) = image.premultiplied_bgra(scale as f32)
+        else {
+            return false;
+        };
+        let (width, height) = (size.width.0, size.height.0);
+        let Some(buffer) = self
+            .create_buffer(width, height, &bytes)
+            .context("Wayland: Failed to create custom cursor buffer")
+            .log_err()
+        else {
+            return false;
+        };
+
+        // Buffer scales are integers, and the buffer's size must be a multiple of its scale.
+        let rounded_scale = variant_scale_factor.round() as i32;
+        let buffer_scale = if rounded_scale >= 1
+            && (variant_scale_factor - rounded_scale as f32).abs() < f32::EPSILON
+            && width % rounded_scale == 0
+            && height % rounded_scale == 0
+        {
+            rounded_scale
+        } else {
+            1
+        };
+        let hotspot_scale = variant_scale_factor / buffer_scale as f32;
+
+        self.surface.set_buffer_scale(buffer_scale);
+
+        wl_pointer.set_cursor(
+            serial_id,
+            Some(&self.surface),
+            (f32::from(hotspot.x) * hotspot_scale) as i32,
+            (f32::from(hotspot.y) * hotspot_scale) as i32,
+        );
+
+        self.surface.attach(Some(&buffer), 0, 0);
+        self.surface.damage(0, 0, width, height);
+        self.surface.commit();
+
+        if let Some(previous_buffer) = self.custom_buffer.replace(buffer) {
+            previous_buffer.destroy();
+        }
+        true
+    }
+
+    fn create_buffer(&self, width: i32, height: i32, bytes: &[u8]) -> anyhow::Result<WlBuffer> {
+        let fd = unsafe { libc::memfd_create(c"gpui-cursor".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(bytes)?;
+
+        let pool = self
+            .shm
+            .create_pool(file.as_fd(), bytes.len() as i32, &self.qh, ());
+        let buffer = pool.create_buffer(
+            0,
+            width,
+            height,
+            width * 4,
+            wl_shm::Format::Argb8888,
+            &self.qh,
+            (),
+        );
+        pool.destroy();
+        Ok(buffer)
+    }
 }
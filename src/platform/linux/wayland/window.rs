@@ -27,10 +27,10 @@ use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 use crate::{
     AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptButton, PromptLevel, RequestFrameOptions,
-    ResizeEdge, Size, Tiling, WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowControls, WindowDecorations, WindowParams,
-    layer_shell::LayerShellNotSupportedError, px, size,
+    PlatformDisplay, PlatformInput, Point, PostProcessShader, PromptButton, PromptLevel,
+    RequestFrameOptions, ResizeEdge, Size, TextContrast, Tiling, WaylandClientStatePtr,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowControls,
+    WindowDecorations, WindowParams, layer_shell::LayerShellNotSupportedError, px, size,
 };
 use crate::{
     Capslock,
@@ -1305,6 +1305,18 @@ impl PlatformWindow for WaylandWindow {
         state.client.update_ime_position(bounds);
     }
 
+    fn set_post_process_shader(&self, shader: Option<&PostProcessShader>) -> anyhow::Result<()> {
+        self.borrow_mut().renderer.set_post_process_shader(shader)
+    }
+
+    fn set_text_contrast(&self, contrast: Option<TextContrast>) {
+        self.borrow_mut().renderer.set_text_contrast(contrast);
+    }
+
+    fn capture_frame(&self) -> Receiver<anyhow::Result<image::RgbaImage>> {
+        self.borrow_mut().renderer.capture_frame()
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.borrow().renderer.gpu_specs().into()
     }
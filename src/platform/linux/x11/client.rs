@@ -25,6 +25,7 @@ use x11rb::{
     cursor,
     errors::ConnectionError,
     protocol::randr::ConnectionExt as _,
+    protocol::render::ConnectionExt as _,
     protocol::xinput::ConnectionExt,
     protocol::xkb::ConnectionExt as _,
     protocol::xproto::{
@@ -52,18 +53,19 @@ use crate::platform::{
     LinuxCommon, PlatformWindow,
     blade::BladeContext,
     linux::{
-        DEFAULT_CURSOR_ICON_NAME, LinuxClient, get_xkb_compose_state, is_within_click_distance,
-        log_cursor_icon_warning, open_uri_internal,
+        DEFAULT_CURSOR_ICON_NAME, LinuxClient, get_xkb_compose_state, input_event_timestamp,
+        is_within_click_distance, log_cursor_icon_warning, open_uri_internal,
         platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
         reveal_path_internal,
         xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
     },
 };
 use crate::{
-    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, FileDropEvent, Keystroke,
-    LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent, MouseButton, Pixels, Platform,
-    PlatformDisplay, PlatformInput, PlatformKeyboardLayout, Point, RequestFrameOptions,
-    ScrollDelta, Size, TouchPhase, WindowParams, X11Window, modifiers_from_xinput_info, point, px,
+    AnyWindowHandle, Bounds, ClipboardItem, CursorImage, CursorStyle, CustomCursorId, DisplayId,
+    FileDropEvent, Keystroke, LinuxKeyboardLayout, Modifiers, ModifiersChangedEvent, MouseButton,
+    Pixels, Platform, PlatformDisplay, PlatformInput, PlatformKeyboardLayout, Point,
+    RequestFrameOptions, ScrollDelta, Size, TouchPhase, WindowParams, X11Window,
+    modifiers_from_xinput_info, point, px,
 };
 
 /// Value for DeviceId parameters which selects all devices.
@@ -209,6 +211,7 @@ pub struct X11ClientState {
     pub(crate) cursor_handle: cursor::Handle,
     pub(crate) cursor_styles: HashMap<xproto::Window, CursorStyle>,
     pub(crate) cursor_cache: HashMap<CursorStyle, Option<xproto::Cursor>>,
+    pub(crate) custom_cursor_images: HashMap<CustomCursorId, CursorImage>,
 
     pointer_device_states: BTreeMap<xinput::DeviceId, PointerDeviceState>,
 
@@ -490,6 +493,12 @@ impl X11Client {
                     XDPEvent::CursorTheme(_) | XDPEvent::CursorSize(_) => {
                         // noop, X11 manages this for us.
                     }
+                    XDPEvent::CaretBlinks(blinks) => {
+                        client.with_common(|common| common.caret_blinks = blinks);
+                    }
+                    XDPEvent::CaretBlinkInterval(interval) => {
+                        client.with_common(|common| common.caret_blink_interval = interval);
+                    }
                 }
             })
             .map_err(|err| anyhow!("Failed to initialize XDP event source: {err:?}"))?;
@@ -534,6 +543,7 @@ impl X11Client {
             cursor_handle,
             cursor_styles: HashMap::default(),
             cursor_cache: HashMap::default(),
+            custom_cursor_images: HashMap::default(),
 
             pointer_device_states,
 
@@ -1003,6 +1013,7 @@ impl X11Client {
                         ModifiersChangedEvent {
                             modifiers,
                             capslock,
+                            timestamp: input_event_timestamp(event.time),
                         },
                     ));
                 }
@@ -1074,6 +1085,8 @@ impl X11Client {
                     keystroke,
                     is_held: false,
                     prefer_character_input: false,
+                    scan_code: Some(event.detail as u32),
+                    timestamp: input_event_timestamp(event.time),
                 }));
             }
             Event::KeyRelease(event) => {
@@ -1098,7 +1111,11 @@ impl X11Client {
                     keystroke
                 };
                 drop(state);
-                window.handle_input(PlatformInput::KeyUp(crate::KeyUpEvent { keystroke }));
+                window.handle_input(PlatformInput::KeyUp(crate::KeyUpEvent {
+                    keystroke,
+                    scan_code: Some(event.detail as u32),
+                    timestamp: input_event_timestamp(event.time),
+                }));
             }
             Event::XinputButtonPress(event) => {
                 let window = self.get_window(event.event)?;
@@ -1151,6 +1168,7 @@ impl X11Client {
                             modifiers,
                             click_count: current_count,
                             first_mouse: false,
+                            timestamp: input_event_timestamp(event.time),
                         }));
                     }
                     Some(ButtonOrScroll::Scroll(direction)) => {
@@ -1168,7 +1186,12 @@ impl X11Client {
                                 ScrollDirection::Right => Point::new(-SCROLL_LINES, 0.0),
                             };
                             window.handle_input(PlatformInput::ScrollWheel(
-                                make_scroll_wheel_event(position, scroll_delta, modifiers),
+                                make_scroll_wheel_event(
+                                    position,
+                                    scroll_delta,
+                                    modifiers,
+                                    input_event_timestamp(event.time),
+                                ),
                             ));
                         }
                     }
@@ -1196,6 +1219,7 @@ impl X11Client {
                             position,
                             modifiers,
                             click_count,
+                            timestamp: input_event_timestamp(event.time),
                         }));
                     }
                     Some(ButtonOrScroll::Scroll(_)) => {}
@@ -1219,6 +1243,7 @@ impl X11Client {
                         position,
                         pressed_button,
                         modifiers,
+                        timestamp: input_event_timestamp(event.time),
                     }));
                 }
 
@@ -1231,6 +1256,7 @@ impl X11Client {
                             position,
                             scroll_delta,
                             modifiers,
+                            input_event_timestamp(event.time),
                         )));
                     }
                 }
@@ -1261,6 +1287,7 @@ impl X11Client {
                     pressed_button,
                     position,
                     modifiers,
+                    timestamp: input_event_timestamp(event.time),
                 }));
                 window.set_hovered(false);
             }
@@ -1556,6 +1583,46 @@ impl LinuxClient for X11Client {
         state.xcb_connection.flush().log_err();
     }
 
+    fn register_cursor_image(&self, id: CustomCursorId, image: CursorImage) {
+        self.0.borrow_mut().custom_cursor_images.insert(id, image);
+    }
+
+    fn drop_cursor_image(&self, id: CustomCursorId) {
+        let mut state = self.0.borrow_mut();
+        state.custom_cursor_images.remove(&id);
+        let style = CursorStyle::Custom(id);
+        let Some(Some(cursor)) = state.cursor_cache.remove(&style) else {
+            return;
+        };
+
+        let windows = state
+            .cursor_styles
+            .iter()
+            .filter(|(_, window_style)| **window_style == style)
+            .map(|(window, _)| *window)
+            .collect::<Vec<_>>();
+        if !windows.is_empty()
+            && let Some(arrow) = state.get_cursor_icon(CursorStyle::Arrow)
+        {
+            for window in windows {
+                state.cursor_styles.insert(window, CursorStyle::Arrow);
+                check_reply(
+                    || "Failed to reset cursor style",
+                    state.xcb_connection.change_window_attributes(
+                        window,
+                        &ChangeWindowAttributesAux {
+                            cursor: Some(arrow),
+                            ..Default::default()
+                        },
+                    ),
+                )
+                .log_err();
+            }
+        }
+        state.xcb_connection.free_cursor(cursor).log_err();
+        state.xcb_connection.flush().log_err();
+    }
+
     fn open_uri(&self, uri: &str) {
         #[cfg(any(feature = "wayland", feature = "x11"))]
         open_uri_internal(self.background_executor(), uri, None);
@@ -1851,6 +1918,15 @@ impl X11ClientState {
                 Ok(loaded_cursor) => result = Ok(loaded_cursor),
                 Err(err) => result = Err(err.context("X11: error while creating invisible cursor")),
             },
+            CursorStyle::Custom(id) => match self.custom_cursor_images.get(&id) {
+                Some(image) => {
+                    let root = self.xcb_connection.setup().roots[self.x_root_index].root;
+                    result =
+                        create_image_cursor(&self.xcb_connection, root, image, self.scale_factor)
+                            .context("X11: error while creating custom cursor");
+                }
+                None => result = Err(anyhow!("X11: custom cursor {id:?} isn't registered")),
+            },
             _ => 'outer: {
                 let mut errors = String::new();
                 let cursor_icon_names = style.to_icon_names();
@@ -2231,6 +2307,7 @@ fn make_scroll_wheel_event(
     position: Point<Pixels>,
     scroll_delta: Point<f32>,
     modifiers: Modifiers,
+    timestamp: Option<Instant>,
 ) -> crate::ScrollWheelEvent {
     // When shift is held down, vertical scrolling turns into horizontal scrolling.
     let delta = if modifiers.shift {
@@ -2246,6 +2323,7 @@ fn make_scroll_wheel_event(
         delta: ScrollDelta::Lines(delta),
         modifiers,
         touch_phase: TouchPhase::default(),
+        timestamp,
     }
 }
 
@@ -2265,6 +2343,66 @@ fn create_invisible_cursor(
     Ok(cursor)
 }
 
+/// Creates a cursor from the image's variant for `scale_factor`. X11 cursors have no scale of
+/// their own, so the variant is shown at its size in pixels.
+fn create_image_cursor(
+    connection: &XCBConnection,
+    root: xproto::Window,
+    image: &CursorImage,
+    scale_factor: f32,
+) -> anyhow::Result<xproto::Cursor> {
+    let (variant_scale_factor, size, bytes) = image
+        .premultiplied_bgra(scale_factor)
+        .context("cursor image has no variants")?;
+    let width = u16::try_from(size.width.0)?;
+    let height = u16::try_from(size.height.0)?;
+    let hotspot_x = (f32::from(image.hotspot.x) * variant_scale_factor) as u16;
+    let hotspot_y = (f32::from(image.hotspot.y) * variant_scale_factor) as u16;
+
+    let formats = connection.render_query_pict_formats()?.reply()?;
+    let format = formats
+        .formats
+        .iter()
+        .find(|format| {
+            format.type_ == render::PictType::DIRECT
+                && format.depth == 32
+                && format.direct.alpha_shift == 24
+                && format.direct.red_shift == 16
+                && format.direct.green_shift == 8
+                && format.direct.blue_shift == 0
+        })
+        .context("no ARGB32 picture format")?
+        .id;
+
+    let pixmap = connection.generate_id()?;
+    connection.create_pixmap(32, pixmap, root, width, height)?;
+    let gc = connection.generate_id()?;
+    connection.create_gc(gc, pixmap, &xproto::CreateGCAux::new())?;
+    connection.put_image(
+        xproto::ImageFormat::Z_PIXMAP,
+        pixmap,
+        gc,
+        width,
+        height,
+        0,
+        0,
+        0,
+        32,
+        &bytes,
+    )?;
+    connection.free_gc(gc)?;
+
+    let picture = connection.generate_id()?;
+    connection.render_create_picture(picture, pixmap, format, &render::CreatePictureAux::new())?;
+    let cursor = connection.generate_id()?;
+    connection.render_create_cursor(cursor, picture, hotspot_x, hotspot_y)?;
+    connection.render_free_picture(picture)?;
+    connection.free_pixmap(pixmap)?;
+
+    xcb_flush(connection);
+    Ok(cursor)
+}
+
 enum DpiMode {
     Randr,
     Scale(f32),
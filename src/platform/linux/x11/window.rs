@@ -5,9 +5,10 @@ use crate::platform::blade::{BladeContext, BladeRenderer, BladeSurfaceConfig};
 use crate::{
     AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
     Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
-    WindowDecorations, WindowKind, WindowParams, X11ClientStatePtr, px, size,
+    Point, PostProcessShader, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge,
+    ScaledPixels, Scene, Size, TextContrast, Tiling, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowControlArea, WindowDecorations, WindowKind, WindowParams,
+    X11ClientStatePtr, point, px, size,
 };
 
 use blade_graphics as gpu;
@@ -1224,6 +1225,34 @@ impl PlatformWindow for X11Window {
         xcb_flush(&self.0.xcb);
     }
 
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        // The window's configured position is relative to the window manager's frame once it's
+        // reparented, so ask the server where the window is on the root window instead.
+        let coordinates = self.get_root_position(Point::default()).log_err()?;
+        let scale_factor = self.0.state.borrow().scale_factor;
+        Some(point(
+            px(coordinates.dst_x as f32 / scale_factor),
+            px(coordinates.dst_y as f32 / scale_factor),
+        ))
+    }
+
+    fn set_bounds(&mut self, bounds: Bounds<Pixels>) {
+        let bounds = bounds.to_device_pixels(self.0.state.borrow().scale_factor);
+        check_reply(
+            || format!("X11 ConfigureWindow failed. bounds: {bounds:?}"),
+            self.0.xcb.configure_window(
+                self.0.x_window,
+                &xproto::ConfigureWindowAux::new()
+                    .x(bounds.origin.x.0)
+                    .y(bounds.origin.y.0)
+                    .width(bounds.size.width.0 as u32)
+                    .height(bounds.size.height.0 as u32),
+            ),
+        )
+        .log_err();
+        xcb_flush(&self.0.xcb);
+    }
+
     fn scale_factor(&self) -> f32 {
         self.0.state.borrow().scale_factor
     }
@@ -1664,6 +1693,28 @@ impl PlatformWindow for X11Window {
         client.update_ime_position(bounds);
     }
 
+    fn set_post_process_shader(&self, shader: Option<&PostProcessShader>) -> anyhow::Result<()> {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .set_post_process_shader(shader)
+    }
+
+    fn set_text_contrast(&self, contrast: Option<TextContrast>) {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .set_text_contrast(contrast);
+    }
+
+    fn capture_frame(
+        &self,
+    ) -> futures::channel::oneshot::Receiver<anyhow::Result<image::RgbaImage>> {
+        self.0.state.borrow_mut().renderer.capture_frame()
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
@@ -6,6 +6,7 @@ use ashpd::desktop::settings::{ColorScheme, Settings};
 use calloop::channel::Channel;
 use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
 use smol::stream::StreamExt;
+use std::time::Duration;
 
 use crate::{BackgroundExecutor, WindowAppearance};
 
@@ -15,6 +16,8 @@ pub enum Event {
     CursorTheme(String),
     #[cfg_attr(feature = "x11", allow(dead_code))]
     CursorSize(u32),
+    CaretBlinks(bool),
+    CaretBlinkInterval(Duration),
 }
 
 pub struct XDPEventSource {
@@ -89,6 +92,59 @@ impl XDPEventSource {
                         .detach();
                 }
 
+                if let Ok(initial_blinks) = settings
+                    .read::<bool>("org.gnome.desktop.interface", "cursor-blink")
+                    .await
+                {
+                    sender.send(Event::CaretBlinks(initial_blinks))?;
+                }
+
+                if let Ok(initial_blink_time) = settings
+                    .read::<i32>("org.gnome.desktop.interface", "cursor-blink-time")
+                    .await
+                    && let Some(interval) = caret_blink_interval(initial_blink_time)
+                {
+                    sender.send(Event::CaretBlinkInterval(interval))?;
+                }
+
+                if let Ok(mut blinks_changed) = settings
+                    .receive_setting_changed_with_args::<bool>(
+                        "org.gnome.desktop.interface",
+                        "cursor-blink",
+                    )
+                    .await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(blinks) = blinks_changed.next().await {
+                                sender.send(Event::CaretBlinks(blinks?))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
+                if let Ok(mut blink_time_changed) = settings
+                    .receive_setting_changed_with_args::<i32>(
+                        "org.gnome.desktop.interface",
+                        "cursor-blink-time",
+                    )
+                    .await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(blink_time) = blink_time_changed.next().await {
+                                if let Some(interval) = caret_blink_interval(blink_time?) {
+                                    sender.send(Event::CaretBlinkInterval(interval))?;
+                                }
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
                 let mut appearance_changed = settings.receive_color_scheme_changed().await?;
                 while let Some(scheme) = appearance_changed.next().await {
                     sender.send(Event::WindowAppearance(WindowAppearance::from_native(
@@ -104,6 +160,13 @@ impl XDPEventSource {
     }
 }
 
+/// Converts GNOME's `cursor-blink-time`, the length of a whole blink cycle in milliseconds, into
+/// how long the caret stays shown and then hidden.
+fn caret_blink_interval(blink_time: i32) -> Option<Duration> {
+    let blink_time = u64::try_from(blink_time).ok().filter(|time| *time > 0)?;
+    Some(Duration::from_millis(blink_time) / 2)
+}
+
 impl EventSource for XDPEventSource {
     type Event = Event;
     type Metadata = ();
@@ -5,6 +5,7 @@ mod display;
 mod display_link;
 mod events;
 mod keyboard;
+mod memory_pressure;
 
 #[cfg(feature = "screen-capture")]
 mod screen_capture;
@@ -43,6 +44,7 @@ pub(crate) use dispatcher::*;
 pub(crate) use display::*;
 pub(crate) use display_link::*;
 pub(crate) use keyboard::*;
+pub(crate) use memory_pressure::*;
 pub(crate) use platform::*;
 pub(crate) use window::*;
 
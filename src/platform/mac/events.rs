@@ -2,9 +2,12 @@ use crate::{
     Capslock, KeyDownEvent, KeyUpEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton,
     MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection, Pixels,
     PlatformInput, ScrollDelta, ScrollWheelEvent, TouchPhase,
-    platform::mac::{
-        LMGetKbdType, NSStringExt, TISCopyCurrentKeyboardLayoutInputSource,
-        TISGetInputSourceProperty, UCKeyTranslate, kTISPropertyUnicodeKeyLayoutData,
+    platform::{
+        input_event_instant,
+        mac::{
+            LMGetKbdType, NSStringExt, TISCopyCurrentKeyboardLayoutInputSource,
+            TISGetInputSourceProperty, UCKeyTranslate, kTISPropertyUnicodeKeyLayoutData,
+        },
     },
     point, px,
 };
@@ -14,8 +17,12 @@ use cocoa::{
 };
 use core_foundation::data::{CFDataGetBytePtr, CFDataRef};
 use core_graphics::event::CGKeyCode;
-use objc::{msg_send, sel, sel_impl};
-use std::{borrow::Cow, ffi::c_void};
+use objc::{class, msg_send, sel, sel_impl};
+use std::{
+    borrow::Cow,
+    ffi::c_void,
+    time::{Duration, Instant},
+};
 
 const BACKSPACE_KEY: u16 = 0x7f;
 const SPACE_KEY: u16 = b' ' as u16;
@@ -100,6 +107,16 @@ unsafe fn read_modifiers(native_event: id) -> Modifiers {
     }
 }
 
+/// Converts the time of the event, in seconds since the system started, into an [`Instant`].
+unsafe fn read_timestamp(native_event: id) -> Option<Instant> {
+    unsafe {
+        let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+        let uptime: f64 = msg_send![process_info, systemUptime];
+        let age = Duration::try_from_secs_f64(uptime - native_event.timestamp()).ok()?;
+        input_event_instant(age)
+    }
+}
+
 impl PlatformInput {
     pub(crate) unsafe fn from_native(
         native_event: id,
@@ -117,6 +134,7 @@ impl PlatformInput {
                 _ => {}
             }
 
+            let timestamp = read_timestamp(native_event);
             match event_type {
                 NSEventType::NSFlagsChanged => {
                     Some(Self::ModifiersChanged(ModifiersChangedEvent {
@@ -126,15 +144,20 @@ impl PlatformInput {
                                 .modifierFlags()
                                 .contains(NSEventModifierFlags::NSAlphaShiftKeyMask),
                         },
+                        timestamp,
                     }))
                 }
                 NSEventType::NSKeyDown => Some(Self::KeyDown(KeyDownEvent {
                     keystroke: parse_keystroke(native_event),
                     is_held: native_event.isARepeat() == YES,
                     prefer_character_input: false,
+                    scan_code: Some(native_event.keyCode() as u32),
+                    timestamp,
                 })),
                 NSEventType::NSKeyUp => Some(Self::KeyUp(KeyUpEvent {
                     keystroke: parse_keystroke(native_event),
+                    scan_code: Some(native_event.keyCode() as u32),
+                    timestamp,
                 })),
                 NSEventType::NSLeftMouseDown
                 | NSEventType::NSRightMouseDown
@@ -159,6 +182,7 @@ impl PlatformInput {
                             modifiers: read_modifiers(native_event),
                             click_count: native_event.clickCount() as usize,
                             first_mouse: false,
+                            timestamp,
                         })
                     })
                 }
@@ -184,6 +208,7 @@ impl PlatformInput {
                             ),
                             modifiers: read_modifiers(native_event),
                             click_count: native_event.clickCount() as usize,
+                            timestamp,
                         })
                     })
                 }
@@ -209,6 +234,7 @@ impl PlatformInput {
                                 modifiers: read_modifiers(native_event),
                                 click_count: 1,
                                 first_mouse: false,
+                                timestamp,
                             })
                         }),
                         _ => None,
@@ -242,6 +268,7 @@ impl PlatformInput {
                         delta,
                         touch_phase: phase,
                         modifiers: read_modifiers(native_event),
+                        timestamp,
                     })
                 }),
                 NSEventType::NSLeftMouseDragged
@@ -265,6 +292,7 @@ impl PlatformInput {
                                 window_height - px(native_event.locationInWindow().y as f32),
                             ),
                             modifiers: read_modifiers(native_event),
+                            timestamp,
                         })
                     })
                 }
@@ -276,6 +304,7 @@ impl PlatformInput {
                         ),
                         pressed_button: None,
                         modifiers: read_modifiers(native_event),
+                        timestamp,
                     })
                 }),
                 NSEventType::NSMouseExited => window_height.map(|window_height| {
@@ -287,6 +316,7 @@ impl PlatformInput {
 
                         pressed_button: None,
                         modifiers: read_modifiers(native_event),
+                        timestamp,
                     })
                 }),
                 _ => None,
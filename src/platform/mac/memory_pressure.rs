@@ -0,0 +1,65 @@
+use crate::{
+    MemoryPressureLevel, dispatch_get_main_queue,
+    dispatch_sys::{
+        _dispatch_source_type_memorypressure, DISPATCH_MEMORYPRESSURE_CRITICAL,
+        DISPATCH_MEMORYPRESSURE_WARN, dispatch_resume, dispatch_set_context,
+        dispatch_source_cancel, dispatch_source_create, dispatch_source_get_data,
+        dispatch_source_set_event_handler_f, dispatch_source_t,
+    },
+};
+use std::ffi::c_void;
+
+struct MemoryPressureHandler {
+    source: dispatch_source_t,
+    callback: Box<dyn FnMut(MemoryPressureLevel)>,
+}
+
+/// Listens for the system's memory pressure notifications on the main queue.
+pub(crate) struct MemoryPressureSource {
+    handler: *mut MemoryPressureHandler,
+}
+
+impl MemoryPressureSource {
+    pub(crate) fn new(callback: Box<dyn FnMut(MemoryPressureLevel)>) -> Self {
+        unsafe extern "C" fn handle_memory_pressure(context: *mut c_void) {
+            let handler = unsafe { &mut *(context as *mut MemoryPressureHandler) };
+            let pressure = unsafe { dispatch_source_get_data(handler.source) };
+            let level = if pressure & DISPATCH_MEMORYPRESSURE_CRITICAL as usize != 0 {
+                MemoryPressureLevel::Critical
+            } else if pressure & DISPATCH_MEMORYPRESSURE_WARN as usize != 0 {
+                MemoryPressureLevel::Warning
+            } else {
+                return;
+            };
+            (handler.callback)(level);
+        }
+
+        unsafe {
+            let source = dispatch_source_create(
+                &_dispatch_source_type_memorypressure,
+                0,
+                (DISPATCH_MEMORYPRESSURE_WARN | DISPATCH_MEMORYPRESSURE_CRITICAL) as usize,
+                dispatch_get_main_queue(),
+            );
+            let handler = Box::into_raw(Box::new(MemoryPressureHandler { source, callback }));
+            dispatch_set_context(
+                crate::dispatch_sys::dispatch_object_t { _ds: source },
+                handler as *mut c_void,
+            );
+            dispatch_source_set_event_handler_f(source, Some(handle_memory_pressure));
+            dispatch_resume(crate::dispatch_sys::dispatch_object_t { _ds: source });
+            Self { handler }
+        }
+    }
+}
+
+impl Drop for MemoryPressureSource {
+    fn drop(&mut self) {
+        // The handler runs on the main queue, so it can't be running while this is dropped on
+        // the main thread, and it won't run again once the source is cancelled.
+        unsafe {
+            dispatch_source_cancel((*self.handler).source);
+            drop(Box::from_raw(self.handler));
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use super::metal_atlas::MetalAtlas;
 use crate::{
-    AtlasTextureId, Background, Bounds, ContentMask, DevicePixels, MonochromeSprite, PaintSurface,
-    Path, Point, PolychromeSprite, PrimitiveBatch, Quad, ScaledPixels, Scene, Shadow, Size,
-    Surface, Underline, point, size,
+    AtlasTextureId, Background, Bounds, CapturedAlpha, ContentMask, DevicePixels, MonochromeSprite,
+    PaintSurface, Path, Point, PolychromeSprite, PrimitiveBatch, Quad, RENDER_LOG_TARGET,
+    ScaledPixels, Scene, Shadow, Size, Surface, TextContrast, TiledQuad, Underline,
+    WindowColorSpace, captured_frame_image, point, send_captured_frame, size,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use block::ConcreteBlock;
 use cocoa::{
     base::{NO, YES},
@@ -13,19 +14,28 @@ use cocoa::{
 };
 
 use core_foundation::base::TCFType;
+use core_graphics::color_space::{CGColorSpace, kCGColorSpaceExtendedSRGB};
 use core_video::{
     metal_texture::CVMetalTextureGetTexture, metal_texture_cache::CVMetalTextureCache,
     pixel_buffer::kCVPixelFormatType_420YpCbCr8BiPlanarFullRange,
 };
 use foreign_types::{ForeignType, ForeignTypeRef};
+use futures::channel::oneshot;
+use image::RgbaImage;
 use metal::{
-    CAMetalLayer, CommandQueue, MTLPixelFormat, MTLResourceOptions, NSRange,
-    RenderPassColorAttachmentDescriptorRef,
+    CAMetalLayer, CommandQueue, MTLBlitOption, MTLOrigin, MTLPixelFormat, MTLResourceOptions,
+    MTLSize, NSRange, RenderPassColorAttachmentDescriptorRef,
 };
 use objc::{self, msg_send, sel, sel_impl};
 use parking_lot::Mutex;
 
-use std::{cell::Cell, ffi::c_void, mem, ptr, sync::Arc};
+use std::{
+    cell::Cell,
+    ffi::c_void,
+    mem, ptr, slice,
+    sync::{Arc, Once},
+    time::Instant,
+};
 
 // Exported to metal
 pub(crate) type PointF = crate::Point<f32>;
@@ -47,8 +57,9 @@ pub unsafe fn new_renderer(
     _native_view: *mut c_void,
     _bounds: crate::Size<f32>,
     _transparent: bool,
+    color_space: WindowColorSpace,
 ) -> Renderer {
-    MetalRenderer::new(context)
+    MetalRenderer::new(context, color_space)
 }
 
 pub(crate) struct InstanceBufferPool {
@@ -105,6 +116,7 @@ pub(crate) struct MetalRenderer {
     path_sprites_pipeline_state: metal::RenderPipelineState,
     shadows_pipeline_state: metal::RenderPipelineState,
     quads_pipeline_state: metal::RenderPipelineState,
+    tiled_quads_pipeline_state: metal::RenderPipelineState,
     underlines_pipeline_state: metal::RenderPipelineState,
     monochrome_sprites_pipeline_state: metal::RenderPipelineState,
     polychrome_sprites_pipeline_state: metal::RenderPipelineState,
@@ -117,6 +129,10 @@ pub(crate) struct MetalRenderer {
     path_intermediate_texture: Option<metal::Texture>,
     path_intermediate_msaa_texture: Option<metal::Texture>,
     path_sample_count: u32,
+    pixel_format: MTLPixelFormat,
+    start_time: Instant,
+    text_contrast: Option<TextContrast>,
+    frame_captures: Vec<oneshot::Sender<Result<RgbaImage>>>,
 }
 
 #[repr(C)]
@@ -125,10 +141,14 @@ pub struct PathRasterizationVertex {
     pub st_position: Point<f32>,
     pub color: Background,
     pub bounds: Bounds<ScaledPixels>,
+    pub content_mask: ContentMask<ScaledPixels>,
 }
 
 impl MetalRenderer {
-    pub fn new(instance_buffer_pool: Arc<Mutex<InstanceBufferPool>>) -> Self {
+    pub fn new(
+        instance_buffer_pool: Arc<Mutex<InstanceBufferPool>>,
+        color_space: WindowColorSpace,
+    ) -> Self {
         // Prefer low‐power integrated GPUs on Intel Mac. On Apple
         // Silicon, there is only ever one GPU, so this is equivalent to
         // `metal::Device::system_default()`.
@@ -141,18 +161,45 @@ impl MetalRenderer {
             // For some reason `metal::Device::all()` can return an empty list on some macOS configurations.
             // In that case, we fall back to the system default device.
             log::error!(
+                target: RENDER_LOG_TARGET,
                 "Unable to enumerate Metal devices; attempting to use system default device"
             );
             metal::Device::system_default().unwrap_or_else(|| {
-                log::error!("unable to access a compatible graphics device");
+                log::error!(
+                    target: RENDER_LOG_TARGET,
+                    "unable to access a compatible graphics device"
+                );
                 std::process::exit(1);
             })
         };
 
+        // Display P3 colors are converted to extended sRGB in the shaders, which needs a float
+        // surface to hold components outside of 0 to 1.
+        let display_p3 = color_space == WindowColorSpace::DisplayP3;
+        let pixel_format = if display_p3 {
+            MTLPixelFormat::RGBA16Float
+        } else {
+            MTLPixelFormat::BGRA8Unorm
+        };
+        let function_constants = metal::FunctionConstantValues::new();
+        function_constants.set_constant_value_at_index(
+            &display_p3 as *const bool as *const c_void,
+            metal::MTLDataType::Bool,
+            0,
+        );
+
         let layer = metal::MetalLayer::new();
         layer.set_device(&device);
-        layer.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+        layer.set_pixel_format(pixel_format);
         layer.set_opaque(false);
+        if display_p3
+            && let Some(extended_srgb) =
+                CGColorSpace::create_with_name(unsafe { kCGColorSpaceExtendedSRGB })
+        {
+            unsafe {
+                let _: () = msg_send![&*layer, setColorspace: extended_srgb.as_ptr()];
+            }
+        }
         layer.set_maximum_drawable_count(3);
         unsafe {
             let _: () = msg_send![&*layer, setAllowsNextDrawableTimeout: NO];
@@ -199,7 +246,8 @@ impl MetalRenderer {
             "paths_rasterization",
             "path_rasterization_vertex",
             "path_rasterization_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
             PATH_SAMPLE_COUNT,
         );
         let path_sprites_pipeline_state = build_path_sprite_pipeline_state(
@@ -208,7 +256,8 @@ impl MetalRenderer {
             "path_sprites",
             "path_sprite_vertex",
             "path_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
         let shadows_pipeline_state = build_pipeline_state(
             &device,
@@ -216,7 +265,8 @@ impl MetalRenderer {
             "shadows",
             "shadow_vertex",
             "shadow_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
         let quads_pipeline_state = build_pipeline_state(
             &device,
@@ -224,7 +274,17 @@ impl MetalRenderer {
             "quads",
             "quad_vertex",
             "quad_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
+        );
+        let tiled_quads_pipeline_state = build_pipeline_state(
+            &device,
+            &library,
+            "tiled_quads",
+            "tiled_quad_vertex",
+            "tiled_quad_fragment",
+            pixel_format,
+            &function_constants,
         );
         let underlines_pipeline_state = build_pipeline_state(
             &device,
@@ -232,7 +292,8 @@ impl MetalRenderer {
             "underlines",
             "underline_vertex",
             "underline_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
         let monochrome_sprites_pipeline_state = build_pipeline_state(
             &device,
@@ -240,7 +301,8 @@ impl MetalRenderer {
             "monochrome_sprites",
             "monochrome_sprite_vertex",
             "monochrome_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
         let polychrome_sprites_pipeline_state = build_pipeline_state(
             &device,
@@ -248,7 +310,8 @@ impl MetalRenderer {
             "polychrome_sprites",
             "polychrome_sprite_vertex",
             "polychrome_sprite_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
         let surfaces_pipeline_state = build_pipeline_state(
             &device,
@@ -256,7 +319,8 @@ impl MetalRenderer {
             "surfaces",
             "surface_vertex",
             "surface_fragment",
-            MTLPixelFormat::BGRA8Unorm,
+            pixel_format,
+            &function_constants,
         );
 
         let command_queue = device.new_command_queue();
@@ -273,6 +337,7 @@ impl MetalRenderer {
             path_sprites_pipeline_state,
             shadows_pipeline_state,
             quads_pipeline_state,
+            tiled_quads_pipeline_state,
             underlines_pipeline_state,
             monochrome_sprites_pipeline_state,
             polychrome_sprites_pipeline_state,
@@ -284,6 +349,10 @@ impl MetalRenderer {
             path_intermediate_texture: None,
             path_intermediate_msaa_texture: None,
             path_sample_count: PATH_SAMPLE_COUNT,
+            pixel_format,
+            start_time: Instant::now(),
+            text_contrast: None,
+            frame_captures: Vec::new(),
         }
     }
 
@@ -305,6 +374,89 @@ impl MetalRenderer {
             .set_presents_with_transaction(presents_with_transaction);
     }
 
+    /// Thickens light grayscale text by `contrast`'s light text contrast. Glyphs are rasterized
+    /// by CoreText, which corrects their gamma and dark text contrast itself, so the rest of
+    /// `contrast` is ignored.
+    pub fn set_text_contrast(&mut self, contrast: Option<TextContrast>) {
+        self.text_contrast = contrast;
+    }
+
+    /// Reads back the pixels of the next frame drawn, once the GPU has finished rendering it.
+    pub fn capture_frame(&mut self) -> oneshot::Receiver<Result<RgbaImage>> {
+        let (tx, rx) = oneshot::channel();
+        // Drawables can only be copied from if they aren't framebuffer-only.
+        self.layer.set_framebuffer_only(false);
+        self.frame_captures.push(tx);
+        rx
+    }
+
+    /// Copies `texture`, the frame being drawn, into a buffer once `command_buffer` has rendered
+    /// it, and sends its pixels to the pending frame captures.
+    fn encode_frame_capture(
+        &mut self,
+        command_buffer: &metal::CommandBufferRef,
+        texture: &metal::TextureRef,
+    ) {
+        let frame_captures = mem::take(&mut self.frame_captures);
+        self.layer.set_framebuffer_only(true);
+        if self.pixel_format != MTLPixelFormat::BGRA8Unorm {
+            send_captured_frame(
+                frame_captures,
+                Err(anyhow!(
+                    "can't capture frames drawn in the {:?} format",
+                    self.pixel_format
+                )),
+            );
+            return;
+        }
+
+        let (width, height) = (texture.width(), texture.height());
+        let bytes_per_row = width * 4;
+        let buffer = self.device.new_buffer(
+            bytes_per_row * height,
+            MTLResourceOptions::StorageModeShared,
+        );
+        let blit_encoder = command_buffer.new_blit_command_encoder();
+        blit_encoder.copy_from_texture_to_buffer(
+            texture,
+            0,
+            0,
+            MTLOrigin { x: 0, y: 0, z: 0 },
+            MTLSize {
+                width,
+                height,
+                depth: 1,
+            },
+            &buffer,
+            0,
+            bytes_per_row,
+            bytes_per_row * height,
+            MTLBlitOption::empty(),
+        );
+        blit_encoder.end_encoding();
+
+        let alpha = if self.layer.is_opaque() {
+            CapturedAlpha::Ignored
+        } else {
+            CapturedAlpha::Premultiplied
+        };
+        let frame_captures = Cell::new(Some(frame_captures));
+        let block = ConcreteBlock::new(move |_| {
+            if let Some(frame_captures) = frame_captures.take() {
+                let bytes = unsafe {
+                    slice::from_raw_parts(buffer.contents() as *const u8, buffer.length() as usize)
+                };
+                let frame_size = size(DevicePixels(width as i32), DevicePixels(height as i32));
+                send_captured_frame(
+                    frame_captures,
+                    captured_frame_image(frame_size, bytes_per_row as usize, bytes, alpha),
+                );
+            }
+        });
+        let block = block.copy();
+        command_buffer.add_completed_handler(&block);
+    }
+
     pub fn update_drawable_size(&mut self, size: Size<DevicePixels>) {
         let size = NSSize {
             width: size.width.0 as f64,
@@ -336,7 +488,7 @@ impl MetalRenderer {
         let texture_descriptor = metal::TextureDescriptor::new();
         texture_descriptor.set_width(size.width.0 as u64);
         texture_descriptor.set_height(size.height.0 as u64);
-        texture_descriptor.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
+        texture_descriptor.set_pixel_format(self.pixel_format);
         texture_descriptor
             .set_usage(metal::MTLTextureUsage::RenderTarget | metal::MTLTextureUsage::ShaderRead);
         self.path_intermediate_texture = Some(self.device.new_texture(&texture_descriptor));
@@ -371,6 +523,7 @@ impl MetalRenderer {
             drawable
         } else {
             log::error!(
+                target: RENDER_LOG_TARGET,
                 "failed to retrieve next drawable, drawable size: {:?}",
                 viewport_size
             );
@@ -394,6 +547,9 @@ impl MetalRenderer {
                     });
                     let block = block.copy();
                     command_buffer.add_completed_handler(&block);
+                    if !self.frame_captures.is_empty() {
+                        self.encode_frame_capture(&command_buffer, drawable.texture());
+                    }
 
                     if self.presents_with_transaction {
                         command_buffer.commit();
@@ -407,17 +563,23 @@ impl MetalRenderer {
                 }
                 Err(err) => {
                     log::error!(
+                        target: RENDER_LOG_TARGET,
                         "failed to render: {}. retrying with larger instance buffer size",
                         err
                     );
                     let mut instance_buffer_pool = self.instance_buffer_pool.lock();
                     let buffer_size = instance_buffer_pool.buffer_size;
                     if buffer_size >= 256 * 1024 * 1024 {
-                        log::error!("instance buffer size grew too large: {}", buffer_size);
+                        log::error!(
+                            target: RENDER_LOG_TARGET,
+                            "instance buffer size grew too large: {}",
+                            buffer_size
+                        );
                         break;
                     }
                     instance_buffer_pool.reset(buffer_size * 2);
                     log::info!(
+                        target: RENDER_LOG_TARGET,
                         "increased instance buffer size to {}",
                         instance_buffer_pool.buffer_size
                     );
@@ -464,6 +626,13 @@ impl MetalRenderer {
                     viewport_size,
                     command_encoder,
                 ),
+                PrimitiveBatch::TiledQuads(quads) => self.draw_tiled_quads(
+                    quads,
+                    instance_buffer,
+                    &mut instance_offset,
+                    viewport_size,
+                    command_encoder,
+                ),
                 PrimitiveBatch::Paths(paths) => {
                     command_encoder.end_encoding();
 
@@ -532,6 +701,18 @@ impl MetalRenderer {
                     viewport_size,
                     command_encoder,
                 ),
+                // Backdrop blurs are only drawn by the Blade renderer.
+                PrimitiveBatch::BackdropBlurs(_) => true,
+                PrimitiveBatch::CustomRenderPrimitives(_) => {
+                    static WARNING: Once = Once::new();
+                    WARNING.call_once(|| {
+                        log::warn!(
+                            target: RENDER_LOG_TARGET,
+                            "the Metal renderer doesn't support custom rendering, skipping custom render regions"
+                        )
+                    });
+                    true
+                }
             };
             if !ok {
                 command_encoder.end_encoding();
@@ -600,6 +781,7 @@ impl MetalRenderer {
                 st_position: v.st_position,
                 color: path.color,
                 bounds: path.bounds.intersect(&path.content_mask.bounds),
+                content_mask: path.content_mask.clone(),
             }));
         }
         let vertices_bytes_len = mem::size_of_val(vertices.as_slice());
@@ -741,6 +923,12 @@ impl MetalRenderer {
             mem::size_of_val(&viewport_size) as u64,
             &viewport_size as *const Size<DevicePixels> as *const _,
         );
+        let time = self.start_time.elapsed().as_secs_f32();
+        command_encoder.set_fragment_bytes(
+            QuadInputIndex::Time as u64,
+            mem::size_of_val(&time) as u64,
+            &time as *const f32 as *const _,
+        );
 
         let quad_bytes_len = mem::size_of_val(quads);
         let buffer_contents =
@@ -855,6 +1043,65 @@ impl MetalRenderer {
         true
     }
 
+    fn draw_tiled_quads(
+        &self,
+        quads: &[TiledQuad],
+        instance_buffer: &mut InstanceBuffer,
+        instance_offset: &mut usize,
+        viewport_size: Size<DevicePixels>,
+        command_encoder: &metal::RenderCommandEncoderRef,
+    ) -> bool {
+        if quads.is_empty() {
+            return true;
+        }
+        align_offset(instance_offset);
+
+        command_encoder.set_render_pipeline_state(&self.tiled_quads_pipeline_state);
+        command_encoder.set_vertex_buffer(
+            TiledQuadInputIndex::Vertices as u64,
+            Some(&self.unit_vertices),
+            0,
+        );
+        command_encoder.set_vertex_buffer(
+            TiledQuadInputIndex::TiledQuads as u64,
+            Some(&instance_buffer.metal_buffer),
+            *instance_offset as u64,
+        );
+        command_encoder.set_fragment_buffer(
+            TiledQuadInputIndex::TiledQuads as u64,
+            Some(&instance_buffer.metal_buffer),
+            *instance_offset as u64,
+        );
+
+        command_encoder.set_vertex_bytes(
+            TiledQuadInputIndex::ViewportSize as u64,
+            mem::size_of_val(&viewport_size) as u64,
+            &viewport_size as *const Size<DevicePixels> as *const _,
+        );
+
+        let quad_bytes_len = mem::size_of_val(quads);
+        let buffer_contents =
+            unsafe { (instance_buffer.metal_buffer.contents() as *mut u8).add(*instance_offset) };
+
+        let next_offset = *instance_offset + quad_bytes_len;
+        if next_offset > instance_buffer.size {
+            return false;
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(quads.as_ptr() as *const u8, buffer_contents, quad_bytes_len);
+        }
+
+        command_encoder.draw_primitives_instanced(
+            metal::MTLPrimitiveType::Triangle,
+            0,
+            6,
+            quads.len() as u64,
+        );
+        *instance_offset = next_offset;
+        true
+    }
+
     fn draw_underlines(
         &self,
         underlines: &[Underline],
@@ -973,6 +1220,14 @@ impl MetalRenderer {
             *instance_offset as u64,
         );
         command_encoder.set_fragment_texture(SpriteInputIndex::AtlasTexture as u64, Some(&texture));
+        let light_text_enhanced_contrast = self
+            .text_contrast
+            .map_or(0., |contrast| contrast.light_text_enhanced_contrast);
+        command_encoder.set_fragment_bytes(
+            SpriteInputIndex::LightTextContrast as u64,
+            mem::size_of_val(&light_text_enhanced_contrast) as u64,
+            &light_text_enhanced_contrast as *const f32 as *const _,
+        );
 
         unsafe {
             ptr::copy_nonoverlapping(
@@ -1200,12 +1455,13 @@ fn build_pipeline_state(
     vertex_fn_name: &str,
     fragment_fn_name: &str,
     pixel_format: metal::MTLPixelFormat,
+    function_constants: &metal::FunctionConstantValues,
 ) -> metal::RenderPipelineState {
     let vertex_fn = library
-        .get_function(vertex_fn_name, None)
+        .get_function(vertex_fn_name, Some(function_constants.clone()))
         .expect("error locating vertex function");
     let fragment_fn = library
-        .get_function(fragment_fn_name, None)
+        .get_function(fragment_fn_name, Some(function_constants.clone()))
         .expect("error locating fragment function");
 
     let descriptor = metal::RenderPipelineDescriptor::new();
@@ -1234,12 +1490,13 @@ fn build_path_sprite_pipeline_state(
     vertex_fn_name: &str,
     fragment_fn_name: &str,
     pixel_format: metal::MTLPixelFormat,
+    function_constants: &metal::FunctionConstantValues,
 ) -> metal::RenderPipelineState {
     let vertex_fn = library
-        .get_function(vertex_fn_name, None)
+        .get_function(vertex_fn_name, Some(function_constants.clone()))
         .expect("error locating vertex function");
     let fragment_fn = library
-        .get_function(fragment_fn_name, None)
+        .get_function(fragment_fn_name, Some(function_constants.clone()))
         .expect("error locating fragment function");
 
     let descriptor = metal::RenderPipelineDescriptor::new();
@@ -1268,13 +1525,14 @@ fn build_path_rasterization_pipeline_state(
     vertex_fn_name: &str,
     fragment_fn_name: &str,
     pixel_format: metal::MTLPixelFormat,
+    function_constants: &metal::FunctionConstantValues,
     path_sample_count: u32,
 ) -> metal::RenderPipelineState {
     let vertex_fn = library
-        .get_function(vertex_fn_name, None)
+        .get_function(vertex_fn_name, Some(function_constants.clone()))
         .expect("error locating vertex function");
     let fragment_fn = library
-        .get_function(fragment_fn_name, None)
+        .get_function(fragment_fn_name, Some(function_constants.clone()))
         .expect("error locating fragment function");
 
     let descriptor = metal::RenderPipelineDescriptor::new();
@@ -1317,6 +1575,14 @@ enum QuadInputIndex {
     Vertices = 0,
     Quads = 1,
     ViewportSize = 2,
+    Time = 3,
+}
+
+#[repr(C)]
+enum TiledQuadInputIndex {
+    Vertices = 0,
+    TiledQuads = 1,
+    ViewportSize = 2,
 }
 
 #[repr(C)]
@@ -1333,6 +1599,7 @@ enum SpriteInputIndex {
     ViewportSize = 2,
     AtlasTextureSize = 3,
     AtlasTexture = 4,
+    LightTextContrast = 5,
 }
 
 #[repr(C)]
@@ -1,15 +1,16 @@
 use super::{
-    BoolExt, MacKeyboardLayout, MacKeyboardMapper,
+    BoolExt, MacKeyboardLayout, MacKeyboardMapper, MemoryPressureSource,
     attributed_string::{NSAttributedString, NSMutableAttributedString},
     events::key_to_native,
     renderer,
 };
 use crate::{
     Action, AnyWindowHandle, BackgroundExecutor, ClipboardEntry, ClipboardItem, ClipboardString,
-    CursorStyle, ForegroundExecutor, Image, ImageFormat, KeyContext, Keymap, MacDispatcher,
-    MacDisplay, MacWindow, Menu, MenuItem, OsMenu, OwnedMenu, PathPromptOptions, Platform,
+    CursorImage, CursorStyle, CustomCursorId, DEFAULT_CARET_BLINK_INTERVAL, ForegroundExecutor,
+    Image, ImageFormat, KeyContext, Keymap, MacDispatcher, MacDisplay, MacWindow,
+    MemoryPressureLevel, Menu, MenuItem, OsMenu, OwnedMenu, PathPromptOptions, Platform,
     PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Result, SystemMenuType, Task, WindowAppearance, WindowParams, hash,
+    PlatformWindow, PowerState, Result, SystemMenuType, Task, WindowAppearance, WindowParams, hash,
 };
 use anyhow::{Context as _, anyhow};
 use block::ConcreteBlock;
@@ -22,10 +23,11 @@ use cocoa::{
     },
     base::{BOOL, NO, YES, id, nil, selector},
     foundation::{
-        NSArray, NSAutoreleasePool, NSBundle, NSData, NSInteger, NSProcessInfo, NSRange, NSString,
-        NSUInteger, NSURL,
+        NSArray, NSAutoreleasePool, NSBundle, NSData, NSInteger, NSPoint, NSProcessInfo, NSRange,
+        NSSize, NSString, NSUInteger, NSURL,
     },
 };
+use collections::HashMap;
 use core_foundation::{
     base::{CFRelease, CFType, CFTypeRef, OSStatus, TCFType},
     boolean::CFBoolean,
@@ -57,6 +59,7 @@ use std::{
     rc::Rc,
     slice, str,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 use strum::IntoEnumIterator;
 use util::{
@@ -169,6 +172,7 @@ pub(crate) struct MacPlatformState {
     metadata_pasteboard_type: id,
     reopen: Option<Box<dyn FnMut()>>,
     on_keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    memory_pressure_source: Option<MemoryPressureSource>,
     quit: Option<Box<dyn FnMut()>>,
     menu_command: Option<Box<dyn FnMut(&dyn Action)>>,
     validate_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
@@ -179,6 +183,7 @@ pub(crate) struct MacPlatformState {
     dock_menu: Option<id>,
     menus: Option<Vec<OwnedMenu>>,
     keyboard_mapper: Rc<MacKeyboardMapper>,
+    custom_cursors: HashMap<CustomCursorId, id>,
 }
 
 impl Default for MacPlatform {
@@ -215,8 +220,10 @@ impl MacPlatform {
             finish_launching: None,
             dock_menu: None,
             on_keyboard_layout_change: None,
+            memory_pressure_source: None,
             menus: None,
             keyboard_mapper,
+            custom_cursors: HashMap::default(),
         }))
     }
 
@@ -876,6 +883,13 @@ impl Platform for MacPlatform {
             .detach();
     }
 
+    fn print_file(&self, path: &Path) -> oneshot::Receiver<Result<()>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        // The print operation runs modally, so the file is no longer needed once it returns.
+        done_tx.send(print_pdf(path)).ok();
+        done_rx
+    }
+
     fn on_quit(&self, callback: Box<dyn FnMut()>) {
         self.0.lock().quit = Some(callback);
     }
@@ -888,6 +902,10 @@ impl Platform for MacPlatform {
         self.0.lock().on_keyboard_layout_change = Some(callback);
     }
 
+    fn on_memory_pressure(&self, callback: Box<dyn FnMut(MemoryPressureLevel)>) {
+        self.0.lock().memory_pressure_source = Some(MemoryPressureSource::new(callback));
+    }
+
     fn on_app_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>) {
         self.0.lock().menu_command = Some(callback);
     }
@@ -1009,6 +1027,12 @@ impl Platform for MacPlatform {
                 CursorStyle::DragLink => msg_send![class!(NSCursor), dragLinkCursor],
                 CursorStyle::DragCopy => msg_send![class!(NSCursor), dragCopyCursor],
                 CursorStyle::ContextualMenu => msg_send![class!(NSCursor), contextualMenuCursor],
+                CursorStyle::Custom(cursor_id) => {
+                    match self.0.lock().custom_cursors.get(&cursor_id) {
+                        Some(cursor) => *cursor,
+                        None => msg_send![class!(NSCursor), arrowCursor],
+                    }
+                }
                 CursorStyle::None => unreachable!(),
             };
 
@@ -1019,6 +1043,29 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn register_cursor_image(&self, cursor_id: CustomCursorId, image: CursorImage) {
+        let cursor = unsafe { ns_cursor_from_image(&image) };
+        if let Some(previous) = self.0.lock().custom_cursors.insert(cursor_id, cursor) {
+            unsafe {
+                let _: () = msg_send![previous, release];
+            }
+        }
+    }
+
+    fn drop_cursor_image(&self, cursor_id: CustomCursorId) {
+        let Some(cursor) = self.0.lock().custom_cursors.remove(&cursor_id) else {
+            return;
+        };
+        unsafe {
+            let current_cursor: id = msg_send![class!(NSCursor), currentCursor];
+            if current_cursor == cursor {
+                let arrow_cursor: id = msg_send![class!(NSCursor), arrowCursor];
+                let _: () = msg_send![arrow_cursor, set];
+            }
+            let _: () = msg_send![cursor, release];
+        }
+    }
+
     fn should_auto_hide_scrollbars(&self) -> bool {
         #[allow(non_upper_case_globals)]
         const NSScrollerStyleOverlay: NSInteger = 1;
@@ -1029,6 +1076,66 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn power_state(&self) -> PowerState {
+        #[allow(non_upper_case_globals)]
+        const kIOPSTimeRemainingUnlimited: f64 = -2.0;
+
+        unsafe {
+            let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+            let low_power_mode: BOOL = msg_send![process_info, isLowPowerModeEnabled];
+            PowerState {
+                on_battery: IOPSGetTimeRemainingEstimate() != kIOPSTimeRemainingUnlimited,
+                low_power_mode: low_power_mode == YES,
+            }
+        }
+    }
+
+    fn system_idle_duration(&self) -> Option<Duration> {
+        #[allow(non_upper_case_globals)]
+        const kCGEventSourceStateCombinedSessionState: i32 = 0;
+        #[allow(non_upper_case_globals)]
+        const kCGAnyInputEventType: u32 = !0;
+
+        let seconds = unsafe {
+            CGEventSourceSecondsSinceLastEventType(
+                kCGEventSourceStateCombinedSessionState,
+                kCGAnyInputEventType,
+            )
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+
+    fn caret_blink_interval(&self) -> Option<Duration> {
+        unsafe {
+            let user_defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+            let period_on: id = msg_send![
+                user_defaults,
+                objectForKey: ns_string("NSTextInsertionPointBlinkPeriodOn")
+            ];
+            if period_on == nil {
+                return Some(DEFAULT_CARET_BLINK_INTERVAL);
+            }
+            let milliseconds: f64 = msg_send![period_on, doubleValue];
+            // AppKit treats a very long period as turning blinking off.
+            if milliseconds >= 60_000. {
+                return None;
+            }
+            Duration::try_from_secs_f64(milliseconds / 1000.)
+                .ok()
+                .filter(|interval| !interval.is_zero())
+                .or(Some(DEFAULT_CARET_BLINK_INTERVAL))
+        }
+    }
+
+    fn should_reduce_transparency(&self) -> bool {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let reduce_transparency: BOOL =
+                msg_send![workspace, accessibilityDisplayShouldReduceTransparency];
+            reduce_transparency == YES
+        }
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         use crate::ClipboardEntry;
 
@@ -1542,6 +1649,61 @@ unsafe fn ns_string(string: &str) -> id {
     unsafe { NSString::alloc(nil).init_str(string).autorelease() }
 }
 
+/// Creates a retained `NSCursor` showing `image`, with a bitmap representation for each of the
+/// image's variants so that AppKit picks the one for the display's scale factor.
+unsafe fn ns_cursor_from_image(image: &CursorImage) -> id {
+    #[allow(non_upper_case_globals)]
+    const NSBitmapFormatAlphaNonpremultiplied: NSUInteger = 1 << 1;
+
+    unsafe {
+        let size = NSSize::new(image.size.width.0 as f64, image.size.height.0 as f64);
+        let ns_image: id = msg_send![class!(NSImage), alloc];
+        let ns_image: id = msg_send![ns_image, initWithSize: size];
+        for (_, variant) in image.variants() {
+            let Some(bytes) = variant.as_bytes(0) else {
+                continue;
+            };
+            let pixel_size = variant.size(0);
+            let width = pixel_size.width.0 as NSInteger;
+            let height = pixel_size.height.0 as NSInteger;
+            let representation: id = msg_send![class!(NSBitmapImageRep), alloc];
+            let representation: id = msg_send![
+                representation,
+                initWithBitmapDataPlanes: null_mut::<*mut u8>()
+                pixelsWide: width
+                pixelsHigh: height
+                bitsPerSample: 8 as NSInteger
+                samplesPerPixel: 4 as NSInteger
+                hasAlpha: YES
+                isPlanar: NO
+                colorSpaceName: ns_string("NSDeviceRGBColorSpace")
+                bitmapFormat: NSBitmapFormatAlphaNonpremultiplied
+                bytesPerRow: width * 4
+                bitsPerPixel: 32 as NSInteger
+            ];
+            if representation == nil {
+                continue;
+            }
+            let data: *mut u8 = msg_send![representation, bitmapData];
+            if !data.is_null() {
+                let pixels = slice::from_raw_parts_mut(data, bytes.len());
+                for (pixel, bgra) in pixels.chunks_exact_mut(4).zip(bytes.chunks_exact(4)) {
+                    pixel.copy_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+                }
+                let _: () = msg_send![representation, setSize: size];
+                let _: () = msg_send![ns_image, addRepresentation: representation];
+            }
+            let _: () = msg_send![representation, release];
+        }
+
+        let hotspot = NSPoint::new(image.hotspot.x.0 as f64, image.hotspot.y.0 as f64);
+        let cursor: id = msg_send![class!(NSCursor), alloc];
+        let cursor: id = msg_send![cursor, initWithImage: ns_image hotSpot: hotspot];
+        let _: () = msg_send![ns_image, release];
+        cursor
+    }
+}
+
 unsafe fn ns_url_to_path(url: id) -> Result<PathBuf> {
     let path: *mut c_char = msg_send![url, fileSystemRepresentation];
     anyhow::ensure!(!path.is_null(), "url is not a file path: {}", unsafe {
@@ -1552,6 +1714,36 @@ unsafe fn ns_url_to_path(url: id) -> Result<PathBuf> {
     })))
 }
 
+fn print_pdf(path: &Path) -> Result<()> {
+    let path_str = path
+        .to_str()
+        .with_context(|| format!("{path:?} is not valid UTF-8"))?;
+    unsafe {
+        let document_class = Class::get("PDFDocument").context("PDFKit is not available")?;
+        let url = NSURL::fileURLWithPath_(nil, ns_string(path_str));
+        let document: id = msg_send![document_class, alloc];
+        let document: id = msg_send![document, initWithURL: url];
+        if document == nil {
+            return Err(anyhow!("failed to load {path:?} for printing"));
+        }
+        let print_info: id = msg_send![class!(NSPrintInfo), sharedPrintInfo];
+        let operation: id = msg_send![
+            document,
+            printOperationForPrintInfo: print_info
+            scalingMode: PDF_PRINT_PAGE_SCALE_DOWN_TO_FIT
+            autoRotate: YES
+        ];
+        let result = if operation == nil {
+            Err(anyhow!("failed to create print operation for {path:?}"))
+        } else {
+            let _: BOOL = msg_send![operation, runOperation];
+            Ok(())
+        };
+        let _: () = msg_send![document, release];
+        result
+    }
+}
+
 #[link(name = "Carbon", kind = "framework")]
 unsafe extern "C" {
     pub(super) fn TISCopyCurrentKeyboardLayoutInputSource() -> *mut Object;
@@ -1578,6 +1770,22 @@ unsafe extern "C" {
     pub(super) static kTISPropertyLocalizedName: CFStringRef;
 }
 
+/// `kPDFPrintPageScaleDownToFit`, which shrinks pages larger than the paper to fit it.
+const PDF_PRINT_PAGE_SCALE_DOWN_TO_FIT: NSInteger = 2;
+
+#[link(name = "PDFKit", kind = "framework")]
+unsafe extern "C" {}
+
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOPSGetTimeRemainingEstimate() -> f64;
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(source_state: i32, event_type: u32) -> f64;
+}
+
 mod security {
     #![allow(non_upper_case_globals)]
     use super::*;
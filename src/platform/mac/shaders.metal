@@ -3,7 +3,11 @@
 
 using namespace metal;
 
+// Whether colors are Display P3 and are drawn to an extended sRGB surface.
+constant bool display_p3 [[function_constant(0)]];
+
 float4 hsla_to_rgba(Hsla hsla);
+float3 display_p3_to_srgb(float3 color);
 float3 srgb_to_linear(float3 color);
 float3 linear_to_srgb(float3 color);
 float4 srgb_to_oklab(float4 color);
@@ -21,17 +25,25 @@ float4 distance_from_clip_rect(float2 unit_vertex, Bounds_ScaledPixels bounds,
 float4 distance_from_clip_rect_transformed(float2 unit_vertex, Bounds_ScaledPixels bounds,
                                Bounds_ScaledPixels clip_bounds, TransformationMatrix transformation);
 float corner_dash_velocity(float dv1, float dv2);
-float dash_alpha(float t, float period, float length, float dash_velocity,
-                 float antialias_threshold);
+float dash_alpha(float t, float period, float dash_length, float dash_velocity,
+                 float border_sdf, bool dotted, float antialias_threshold);
 float quarter_ellipse_sdf(float2 point, float2 radii);
 float pick_corner_radius(float2 center_to_point, Corners_ScaledPixels corner_radii);
+float2 elliptical_corner_radii(float horizontal, float vertical);
+float2 pick_corner_radii(float2 center_to_point, Corners_ScaledPixels horizontal,
+                         Corners_ScaledPixels vertical);
+float mean_radius(float2 corner_radii);
 float quad_sdf(float2 point, Bounds_ScaledPixels bounds,
                Corners_ScaledPixels corner_radii);
 float quad_sdf_impl(float2 center_to_point, float corner_radius);
+float elliptical_quad_sdf_impl(float2 corner_center_to_point, float2 corner_radii);
+float content_mask_alpha(float2 position, ContentMask_ScaledPixels content_mask);
 float gaussian(float x, float sigma);
 float2 erf(float2 x);
 float blur_along_x(float x, float y, float sigma, float corner,
                    float2 half_size);
+float shadow_alpha(float2 position, Bounds_ScaledPixels bounds,
+                   Corners_ScaledPixels corner_radii, float blur_radius);
 float4 over(float4 below, float4 above);
 float radians(float degrees);
 float4 fill_color(Background background, float2 position, Bounds_ScaledPixels bounds,
@@ -51,6 +63,8 @@ struct QuadVertexOutput {
   float4 background_solid [[flat]];
   float4 background_color0 [[flat]];
   float4 background_color1 [[flat]];
+  float4 shadow_color [[flat]];
+  float2 local_position;
   float clip_distance [[clip_distance]][4];
 };
 
@@ -61,8 +75,12 @@ struct QuadFragmentInput {
   float4 background_solid [[flat]];
   float4 background_color0 [[flat]];
   float4 background_color1 [[flat]];
+  float4 shadow_color [[flat]];
+  float2 local_position;
 };
 
+float4 quad_color(QuadFragmentInput input, Quad quad, float time);
+
 vertex QuadVertexOutput quad_vertex(uint unit_vertex_id [[vertex_id]],
                                     uint quad_id [[instance_id]],
                                     constant float2 *unit_vertices
@@ -73,10 +91,31 @@ vertex QuadVertexOutput quad_vertex(uint unit_vertex_id [[vertex_id]],
                                     [[buffer(QuadInputIndex_ViewportSize)]]) {
   float2 unit_vertex = unit_vertices[unit_vertex_id];
   Quad quad = quads[quad_id];
-  float4 device_position =
-      to_device_position(unit_vertex, quad.bounds, viewport_size);
-  float4 clip_distance = distance_from_clip_rect(unit_vertex, quad.bounds,
-                                                 quad.content_mask.bounds);
+  float4 shadow_color = hsla_to_rgba(quad.shadow.color);
+
+  // Grow the drawn bounds to cover the quad's shadow, including its blur
+  Bounds_ScaledPixels bounds = quad.bounds;
+  if (shadow_color.a > 0.) {
+    float margin = 3. * quad.shadow.blur_radius;
+    float2 shadow_min = float2(quad.shadow.bounds.origin.x, quad.shadow.bounds.origin.y) - margin;
+    float2 shadow_max = float2(quad.shadow.bounds.origin.x + quad.shadow.bounds.size.width,
+                               quad.shadow.bounds.origin.y + quad.shadow.bounds.size.height) + margin;
+    float2 bounds_min = min(float2(quad.bounds.origin.x, quad.bounds.origin.y), shadow_min);
+    float2 bounds_max = max(float2(quad.bounds.origin.x + quad.bounds.size.width,
+                                   quad.bounds.origin.y + quad.bounds.size.height), shadow_max);
+    bounds.origin.x = bounds_min.x;
+    bounds.origin.y = bounds_min.y;
+    bounds.size.width = bounds_max.x - bounds_min.x;
+    bounds.size.height = bounds_max.y - bounds_min.y;
+  }
+
+  float4 device_position = to_device_position_transformed(
+      unit_vertex, bounds, quad.transformation, viewport_size);
+  float2 local_position =
+      unit_vertex * float2(bounds.size.width, bounds.size.height) +
+      float2(bounds.origin.x, bounds.origin.y);
+  float4 clip_distance = distance_from_clip_rect_transformed(
+      unit_vertex, bounds, quad.content_mask.bounds, quad.transformation);
   float4 border_color = hsla_to_rgba(quad.border_color);
 
   GradientColor gradient = prepare_fill_color(
@@ -94,14 +133,45 @@ vertex QuadVertexOutput quad_vertex(uint unit_vertex_id [[vertex_id]],
       gradient.solid,
       gradient.color0,
       gradient.color1,
+      shadow_color,
+      local_position,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
 fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
                               constant Quad *quads
-                              [[buffer(QuadInputIndex_Quads)]]) {
+                              [[buffer(QuadInputIndex_Quads)]],
+                              constant float *time
+                              [[buffer(QuadInputIndex_Time)]]) {
   Quad quad = quads[input.quad_id];
-  float4 background_color = fill_color(quad.background, input.position.xy, quad.bounds,
+  float4 mask =
+      float4(1., 1., 1., content_mask_alpha(input.position.xy, quad.content_mask));
+  if (input.shadow_color.a == 0.) {
+    return quad_color(input, quad, *time) * mask;
+  }
+
+  // The quad is drawn over its shadow, which extends past the quad's bounds
+  float2 point = input.local_position;
+  float4 color = float4(0.);
+  if (point.x >= quad.bounds.origin.x &&
+      point.y >= quad.bounds.origin.y &&
+      point.x < quad.bounds.origin.x + quad.bounds.size.width &&
+      point.y < quad.bounds.origin.y + quad.bounds.size.height) {
+    color = quad_color(input, quad, *time);
+  }
+  float alpha = shadow_alpha(point, quad.shadow.bounds, quad.corner_radii,
+                             quad.shadow.blur_radius);
+  float4 shadow_color = input.shadow_color * float4(1., 1., 1., alpha);
+  if (shadow_color.a <= 0.) {
+    return color * mask;
+  }
+  return over(shadow_color, color) * mask;
+}
+
+// The color of the quad's background and border at the fragment's position,
+// which must be within the quad's bounds.
+float4 quad_color(QuadFragmentInput input, Quad quad, float time) {
+  float4 background_color = fill_color(quad.background, input.local_position, quad.bounds,
     input.background_solid, input.background_color0, input.background_color1);
 
   bool unrounded = quad.corner_radii.top_left == 0.0 &&
@@ -120,15 +190,16 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
 
   float2 size = float2(quad.bounds.size.width, quad.bounds.size.height);
   float2 half_size = size / 2.0;
-  float2 point = input.position.xy - float2(quad.bounds.origin.x, quad.bounds.origin.y);
+  float2 point = input.local_position - float2(quad.bounds.origin.x, quad.bounds.origin.y);
   float2 center_to_point = point - half_size;
 
   // Signed distance field threshold for inclusion of pixels. 0.5 is the
   // minimum distance between the center of the pixel and the edge.
   const float antialias_threshold = 0.5;
 
-  // Radius of the nearest corner
-  float corner_radius = pick_corner_radius(center_to_point, quad.corner_radii);
+  // Horizontal and vertical radii of the nearest corner
+  float2 corner_radii =
+    pick_corner_radii(center_to_point, quad.corner_radii, quad.vertical_corner_radii);
 
   // Width of the nearest borders
   float2 border = float2(
@@ -146,9 +217,9 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
   // the point into the bottom right quadrant. Both components are <= 0.
   float2 corner_to_point = fabs(center_to_point) - half_size;
 
-  // Vector from the point to the center of the rounded corner's circle, also
+  // Vector from the point to the center of the rounded corner's ellipse, also
   // mirrored into bottom right quadrant.
-  float2 corner_center_to_point = corner_to_point + corner_radius;
+  float2 corner_center_to_point = corner_to_point + corner_radii;
 
   // Whether the nearest point on the border is rounded
   bool is_near_rounded_corner =
@@ -179,7 +250,7 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
   }
 
   // Signed distance of the point to the outside edge of the quad's border
-  float outer_sdf = quad_sdf_impl(corner_center_to_point, corner_radius);
+  float outer_sdf = elliptical_quad_sdf_impl(corner_center_to_point, corner_radii);
 
   // Approximate signed distance of the point to the inside edge of the quad's
   // border. It is negative outside this edge (within the border), and
@@ -197,11 +268,11 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
   } else if (is_beyond_inner_straight_border) {
     // Fast path for points that must be outside the inner edge
     inner_sdf = -1.0;
-  } else if (reduced_border.x == reduced_border.y) {
+  } else if (reduced_border.x == reduced_border.y && corner_radii.x == corner_radii.y) {
     // Fast path for circular inner edge.
     inner_sdf = -(outer_sdf + reduced_border.x);
   } else {
-    float2 ellipse_radii = max(float2(0.0), float2(corner_radius) - reduced_border);
+    float2 ellipse_radii = max(float2(0.0), corner_radii - reduced_border);
     inner_sdf = quarter_ellipse_sdf(corner_center_to_point, ellipse_radii);
   }
 
@@ -212,8 +283,13 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
   if (border_sdf < antialias_threshold) {
     float4 border_color = input.border_color;
 
-    // Dashed border logic when border_style == 1
-    if (quad.border_style == 1) {
+    // Dashed and dotted border logic when border_style is 1 or 2
+    if (quad.border_style != 0) {
+      const bool dotted = quad.border_style == 2;
+      // Moving dashes are laid out around the whole perimeter so that they
+      // can travel around the corners.
+      const bool dashes_per_side = unrounded && quad.dash_speed == 0.0;
+
       // Position along the perimeter in "dash space", where each dash
       // period has length 1
       float t = 0.0;
@@ -226,8 +302,9 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
       // used by browsers, but also avoids dashes from different segments
       // overlapping when dash size is smaller than the border width.
       //
-      // Dash pattern: (2 * border width) dash, (1 * border width) gap
-      const float dash_length_per_width = 2.0;
+      // Dash pattern: (2 * border width) dash, (1 * border width) gap.
+      // Dots are round, one border width across, with the same gap.
+      const float dash_length_per_width = dotted ? 1.0 : 2.0;
       const float dash_gap_per_width = 1.0;
       const float dash_period_per_width = dash_length_per_width + dash_gap_per_width;
 
@@ -240,7 +317,7 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
       // Dividing this by the border width gives the dash velocity
       const float dv_numerator = 1.0 / dash_period_per_width;
 
-      if (unrounded) {
+      if (dashes_per_side) {
         // When corners aren't rounded, the dashes are separately laid
         // out on each straight line, rather than around the whole
         // perimeter. This way each line starts and ends with a dash.
@@ -263,10 +340,14 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
         // When corners are rounded, the dashes are laid out clockwise
         // around the whole perimeter.
 
-        float r_tr = quad.corner_radii.top_right;
-        float r_br = quad.corner_radii.bottom_right;
-        float r_bl = quad.corner_radii.bottom_left;
-        float r_tl = quad.corner_radii.top_left;
+        float2 r_tr = elliptical_corner_radii(quad.corner_radii.top_right,
+                                              quad.vertical_corner_radii.top_right);
+        float2 r_br = elliptical_corner_radii(quad.corner_radii.bottom_right,
+                                              quad.vertical_corner_radii.bottom_right);
+        float2 r_bl = elliptical_corner_radii(quad.corner_radii.bottom_left,
+                                              quad.vertical_corner_radii.bottom_left);
+        float2 r_tl = elliptical_corner_radii(quad.corner_radii.top_left,
+                                              quad.vertical_corner_radii.top_left);
 
         float w_t = quad.border_widths.top;
         float w_r = quad.border_widths.right;
@@ -280,21 +361,22 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
         float dv_l = w_l <= 0.0 ? 0.0 : dv_numerator / w_l;
 
         // Straight side lengths in dash space
-        float s_t = (size.x - r_tl - r_tr) * dv_t;
-        float s_r = (size.y - r_tr - r_br) * dv_r;
-        float s_b = (size.x - r_br - r_bl) * dv_b;
-        float s_l = (size.y - r_bl - r_tl) * dv_l;
+        float s_t = (size.x - r_tl.x - r_tr.x) * dv_t;
+        float s_r = (size.y - r_tr.y - r_br.y) * dv_r;
+        float s_b = (size.x - r_br.x - r_bl.x) * dv_b;
+        float s_l = (size.y - r_bl.y - r_tl.y) * dv_l;
 
         float corner_dash_velocity_tr = corner_dash_velocity(dv_t, dv_r);
         float corner_dash_velocity_br = corner_dash_velocity(dv_b, dv_r);
         float corner_dash_velocity_bl = corner_dash_velocity(dv_b, dv_l);
         float corner_dash_velocity_tl = corner_dash_velocity(dv_t, dv_l);
 
-        // Corner lengths in dash space
-        float c_tr = r_tr * (M_PI_F / 2.0) * corner_dash_velocity_tr;
-        float c_br = r_br * (M_PI_F / 2.0) * corner_dash_velocity_br;
-        float c_bl = r_bl * (M_PI_F / 2.0) * corner_dash_velocity_bl;
-        float c_tl = r_tl * (M_PI_F / 2.0) * corner_dash_velocity_tl;
+        // Corner lengths in dash space. Elliptical corners are measured as
+        // circles of their mean radius.
+        float c_tr = mean_radius(r_tr) * (M_PI_F / 2.0) * corner_dash_velocity_tr;
+        float c_br = mean_radius(r_br) * (M_PI_F / 2.0) * corner_dash_velocity_br;
+        float c_bl = mean_radius(r_bl) * (M_PI_F / 2.0) * corner_dash_velocity_bl;
+        float c_tl = mean_radius(r_tl) * (M_PI_F / 2.0) * corner_dash_velocity_tl;
 
         // Cumulative dash space upto each segment
         float upto_tr = s_t;
@@ -307,8 +389,11 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
         max_t = upto_tl + c_tl;
 
         if (is_near_rounded_corner) {
-          float radians = atan2(corner_center_to_point.y, corner_center_to_point.x);
-          float corner_t = radians * corner_radius;
+          // The angle of the point on a unit circle, once the ellipse is
+          // scaled into one.
+          float radians = atan2(corner_center_to_point.y * corner_radii.x,
+                                corner_center_to_point.x * corner_radii.y);
+          float corner_t = radians * mean_radius(corner_radii);
 
           if (center_to_point.x >= 0.0) {
             if (center_to_point.y < 0.0) {
@@ -344,18 +429,18 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
           if (is_horizontal) {
             if (center_to_point.y < 0.0) {
               dash_velocity = dv_t;
-              t = (point.x - r_tl) * dash_velocity;
+              t = (point.x - r_tl.x) * dash_velocity;
             } else {
               dash_velocity = dv_b;
-              t = upto_bl - (point.x - r_bl) * dash_velocity;
+              t = upto_bl - (point.x - r_bl.x) * dash_velocity;
             }
           } else {
             if (center_to_point.x < 0.0) {
               dash_velocity = dv_l;
-              t = upto_tl - (point.y - r_tl) * dash_velocity;
+              t = upto_tl - (point.y - r_tl.y) * dash_velocity;
             } else {
               dash_velocity = dv_r;
-              t = upto_r + (point.y - r_tr) * dash_velocity;
+              t = upto_r + (point.y - r_tr.y) * dash_velocity;
             }
           }
         }
@@ -366,14 +451,17 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
 
       // Straight borders should start and end with a dash, so max_t is
       // reduced to cause this.
-      max_t -= unrounded ? dash_length : 0.0;
+      max_t -= dashes_per_side ? dash_length : 0.0;
       if (max_t >= 1.0) {
         // Adjust dash gap to evenly divide max_t
         float dash_count = floor(max_t);
         float dash_period = max_t / dash_count;
+        // Move the dashes clockwise by dash_speed periods per second. The
+        // offset is kept positive for fmod.
+        t += dash_period * (1.0 - fract(quad.dash_speed * time));
         border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity,
-                                     antialias_threshold);
-      } else if (unrounded) {
+                                     border_sdf, dotted, antialias_threshold);
+      } else if (dashes_per_side) {
         // When there isn't enough space for the full gap between the
         // two start / end dashes of a straight border, reduce gap to
         // make them fit.
@@ -381,7 +469,7 @@ fragment float4 quad_fragment(QuadFragmentInput input [[stage_in]],
         if (dash_gap > 0.0) {
           float dash_period = dash_length + dash_gap;
           border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity,
-                                       antialias_threshold);
+                                       border_sdf, dotted, antialias_threshold);
         }
       }
     }
@@ -414,16 +502,23 @@ float corner_dash_velocity(float dv1, float dv2) {
   }
 }
 
-// Returns alpha used to render antialiased dashes.
-// `t` is within the dash when `fmod(t, period) < length`.
+// Returns alpha used to render antialiased dashes, or round dots when `dotted`.
+// `t` is within the dash when `fmod(t, period) < dash_length`. Dots are as long
+// as the border is wide, and `border_sdf` places the point across the border.
 float dash_alpha(
-    float t, float period, float length, float dash_velocity,
-    float antialias_threshold) {
+    float t, float period, float dash_length, float dash_velocity,
+    float border_sdf, bool dotted, float antialias_threshold) {
   float half_period = period / 2.0;
-  float half_length = length / 2.0;
+  float half_length = dash_length / 2.0;
   // Value in [-half_period, half_period]
   // The dash is in [-half_length, half_length]
   float centered = fmod(t + half_period - half_length, period) - half_period;
+  if (dotted) {
+    // Signed distance to the dot, in pixels
+    float radius = half_length / dash_velocity;
+    float2 offset = float2(centered / dash_velocity, border_sdf + radius);
+    return saturate(antialias_threshold - (length(offset) - radius));
+  }
   // Signed distance for the dash, negative values are inside the dash
   float signed_distance = abs(centered) - half_length;
   // Antialiased alpha based on the signed distance
@@ -450,6 +545,7 @@ struct ShadowVertexOutput {
   float4 position [[position]];
   float4 color [[flat]];
   uint shadow_id [[flat]];
+  float2 local_position;
   float clip_distance [[clip_distance]][4];
 };
 
@@ -457,6 +553,7 @@ struct ShadowFragmentInput {
   float4 position [[position]];
   float4 color [[flat]];
   uint shadow_id [[flat]];
+  float2 local_position;
 };
 
 vertex ShadowVertexOutput shadow_vertex(
@@ -477,16 +574,20 @@ vertex ShadowVertexOutput shadow_vertex(
   bounds.size.width += 2. * margin;
   bounds.size.height += 2. * margin;
 
-  float4 device_position =
-      to_device_position(unit_vertex, bounds, viewport_size);
-  float4 clip_distance =
-      distance_from_clip_rect(unit_vertex, bounds, shadow.content_mask.bounds);
+  float4 device_position = to_device_position_transformed(
+      unit_vertex, bounds, shadow.transformation, viewport_size);
+  float2 local_position =
+      unit_vertex * float2(bounds.size.width, bounds.size.height) +
+      float2(bounds.origin.x, bounds.origin.y);
+  float4 clip_distance = distance_from_clip_rect_transformed(
+      unit_vertex, bounds, shadow.content_mask.bounds, shadow.transformation);
   float4 color = hsla_to_rgba(shadow.color);
 
   return ShadowVertexOutput{
       device_position,
       color,
       shadow_id,
+      local_position,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
@@ -494,57 +595,51 @@ fragment float4 shadow_fragment(ShadowFragmentInput input [[stage_in]],
                                 constant Shadow *shadows
                                 [[buffer(ShadowInputIndex_Shadows)]]) {
   Shadow shadow = shadows[input.shadow_id];
+  float alpha = shadow_alpha(input.local_position, shadow.bounds,
+                             shadow.corner_radii, shadow.blur_radius);
+  alpha *= content_mask_alpha(input.position.xy, shadow.content_mask);
+  return input.color * float4(1., 1., 1., alpha);
+}
 
-  float2 origin = float2(shadow.bounds.origin.x, shadow.bounds.origin.y);
-  float2 size = float2(shadow.bounds.size.width, shadow.bounds.size.height);
-  float2 half_size = size / 2.;
-  float2 center = origin + half_size;
-  float2 point = input.position.xy - center;
-  float corner_radius;
-  if (point.x < 0.) {
-    if (point.y < 0.) {
-      corner_radius = shadow.corner_radii.top_left;
-    } else {
-      corner_radius = shadow.corner_radii.bottom_left;
-    }
-  } else {
-    if (point.y < 0.) {
-      corner_radius = shadow.corner_radii.top_right;
-    } else {
-      corner_radius = shadow.corner_radii.bottom_right;
-    }
+// The coverage of a blurred rounded rectangle at the given position
+float shadow_alpha(float2 position, Bounds_ScaledPixels bounds,
+                   Corners_ScaledPixels corner_radii, float blur_radius) {
+  if (blur_radius == 0.) {
+    float distance = quad_sdf(position, bounds, corner_radii);
+    return saturate(0.5 - distance);
   }
 
-  float alpha;
-  if (shadow.blur_radius == 0.) {
-    float distance = quad_sdf(input.position.xy, shadow.bounds, shadow.corner_radii);
-    alpha = saturate(0.5 - distance);
-  } else {
-    // The signal is only non-zero in a limited range, so don't waste samples
-    float low = point.y - half_size.y;
-    float high = point.y + half_size.y;
-    float start = clamp(-3. * shadow.blur_radius, low, high);
-    float end = clamp(3. * shadow.blur_radius, low, high);
-
-    // Accumulate samples (we can get away with surprisingly few samples)
-    float step = (end - start) / 4.;
-    float y = start + step * 0.5;
-    alpha = 0.;
-    for (int i = 0; i < 4; i++) {
-      alpha += blur_along_x(point.x, point.y - y, shadow.blur_radius,
-                            corner_radius, half_size) *
-               gaussian(y, shadow.blur_radius) * step;
-      y += step;
-    }
+  float2 origin = float2(bounds.origin.x, bounds.origin.y);
+  float2 size = float2(bounds.size.width, bounds.size.height);
+  float2 half_size = size / 2.;
+  float2 center = origin + half_size;
+  float2 point = position - center;
+  float corner_radius = pick_corner_radius(point, corner_radii);
+
+  // The signal is only non-zero in a limited range, so don't waste samples
+  float low = point.y - half_size.y;
+  float high = point.y + half_size.y;
+  float start = clamp(-3. * blur_radius, low, high);
+  float end = clamp(3. * blur_radius, low, high);
+
+  // Accumulate samples (we can get away with surprisingly few samples)
+  float step = (end - start) / 4.;
+  float y = start + step * 0.5;
+  float alpha = 0.;
+  for (int i = 0; i < 4; i++) {
+    alpha += blur_along_x(point.x, point.y - y, blur_radius, corner_radius,
+                          half_size) *
+             gaussian(y, blur_radius) * step;
+    y += step;
   }
-
-  return input.color * float4(1., 1., 1., alpha);
+  return alpha;
 }
 
 struct UnderlineVertexOutput {
   float4 position [[position]];
   float4 color [[flat]];
   uint underline_id [[flat]];
+  float2 local_position;
   float clip_distance [[clip_distance]][4];
 };
 
@@ -552,6 +647,7 @@ struct UnderlineFragmentInput {
   float4 position [[position]];
   float4 color [[flat]];
   uint underline_id [[flat]];
+  float2 local_position;
 };
 
 vertex UnderlineVertexOutput underline_vertex(
@@ -562,34 +658,38 @@ vertex UnderlineVertexOutput underline_vertex(
     [[buffer(ShadowInputIndex_ViewportSize)]]) {
   float2 unit_vertex = unit_vertices[unit_vertex_id];
   Underline underline = underlines[underline_id];
-  float4 device_position =
-      to_device_position(unit_vertex, underline.bounds, viewport_size);
-  float4 clip_distance = distance_from_clip_rect(unit_vertex, underline.bounds,
-                                                 underline.content_mask.bounds);
+  float4 device_position = to_device_position_transformed(
+      unit_vertex, underline.bounds, underline.transformation, viewport_size);
+  float2 local_position =
+      unit_vertex * float2(underline.bounds.size.width,
+                           underline.bounds.size.height) +
+      float2(underline.bounds.origin.x, underline.bounds.origin.y);
+  float4 clip_distance = distance_from_clip_rect_transformed(
+      unit_vertex, underline.bounds, underline.content_mask.bounds,
+      underline.transformation);
   float4 color = hsla_to_rgba(underline.color);
   return UnderlineVertexOutput{
       device_position,
       color,
       underline_id,
+      local_position,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
 fragment float4 underline_fragment(UnderlineFragmentInput input [[stage_in]],
                                    constant Underline *underlines
                                    [[buffer(UnderlineInputIndex_Underlines)]]) {
-  const float WAVE_FREQUENCY = 2.0;
-  const float WAVE_HEIGHT_RATIO = 0.8;
-
   Underline underline = underlines[input.underline_id];
+  float mask_alpha = content_mask_alpha(input.position.xy, underline.content_mask);
   if (underline.wavy) {
     float half_thickness = underline.thickness * 0.5;
     float2 origin =
         float2(underline.bounds.origin.x, underline.bounds.origin.y);
 
-    float2 st = ((input.position.xy - origin) / underline.bounds.size.height) -
+    float2 st = ((input.local_position - origin) / underline.bounds.size.height) -
                 float2(0., 0.5);
-    float frequency = (M_PI_F * WAVE_FREQUENCY * underline.thickness) / underline.bounds.size.height;
-    float amplitude = (underline.thickness * WAVE_HEIGHT_RATIO) / underline.bounds.size.height;
+    float frequency = (2. * M_PI_F * underline.bounds.size.height) / max(underline.wavelength, 0.001);
+    float amplitude = underline.wave_amplitude / underline.bounds.size.height;
 
     float sine = sin(st.x * frequency) * amplitude;
     float dSine = cos(st.x * frequency) * amplitude * frequency;
@@ -599,16 +699,82 @@ fragment float4 underline_fragment(UnderlineFragmentInput input [[stage_in]],
     float distance_from_bottom_border = distance_in_pixels + half_thickness;
     float alpha = saturate(
         0.5 - max(-distance_from_bottom_border, distance_from_top_border));
-    return input.color * float4(1., 1., 1., alpha);
+    return input.color * float4(1., 1., 1., alpha * mask_alpha);
   } else {
-    return input.color;
+    return input.color * float4(1., 1., 1., mask_alpha);
   }
 }
 
+struct TiledQuadVertexOutput {
+  float4 position [[position]];
+  float4 color [[flat]];
+  float4 alternate_color [[flat]];
+  uint quad_id [[flat]];
+  // The position before the quad's transformation, which the tiles are laid
+  // out in.
+  float2 local_position;
+  float clip_distance [[clip_distance]][4];
+};
+
+struct TiledQuadFragmentInput {
+  float4 position [[position]];
+  float4 color [[flat]];
+  float4 alternate_color [[flat]];
+  uint quad_id [[flat]];
+  float2 local_position;
+};
+
+vertex TiledQuadVertexOutput tiled_quad_vertex(
+    uint unit_vertex_id [[vertex_id]], uint quad_id [[instance_id]],
+    constant float2 *unit_vertices [[buffer(TiledQuadInputIndex_Vertices)]],
+    constant TiledQuad *quads [[buffer(TiledQuadInputIndex_TiledQuads)]],
+    constant Size_DevicePixels *viewport_size
+    [[buffer(TiledQuadInputIndex_ViewportSize)]]) {
+  float2 unit_vertex = unit_vertices[unit_vertex_id];
+  TiledQuad quad = quads[quad_id];
+  float4 device_position = to_device_position_transformed(
+      unit_vertex, quad.bounds, quad.transformation, viewport_size);
+  float4 clip_distance = distance_from_clip_rect_transformed(
+      unit_vertex, quad.bounds, quad.content_mask.bounds, quad.transformation);
+  float2 local_position =
+      unit_vertex * float2(quad.bounds.size.width, quad.bounds.size.height) +
+      float2(quad.bounds.origin.x, quad.bounds.origin.y);
+  return TiledQuadVertexOutput{
+      device_position,
+      hsla_to_rgba(quad.color),
+      hsla_to_rgba(quad.alternate_color),
+      quad_id,
+      local_position,
+      {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
+}
+
+fragment float4 tiled_quad_fragment(TiledQuadFragmentInput input [[stage_in]],
+                                    constant TiledQuad *quads
+                                    [[buffer(TiledQuadInputIndex_TiledQuads)]]) {
+  TiledQuad quad = quads[input.quad_id];
+  float2 tile_size =
+      max(float2(quad.tile_size.width, quad.tile_size.height), float2(0.001));
+  float2 position =
+      input.local_position - float2(quad.bounds.origin.x, quad.bounds.origin.y);
+  float2 tile = floor(position / tile_size);
+  float2 tile_position = position - tile * tile_size;
+  Corners_ScaledPixels corner_radii = {quad.corner_radius, quad.corner_radius,
+                                       quad.corner_radius, quad.corner_radius};
+  float alpha =
+      saturate(0.5 - quad_sdf(tile_position, quad.quad_bounds, corner_radii));
+  alpha *= content_mask_alpha(input.position.xy, quad.content_mask);
+  // Tiles alternate colors like the squares of a checkerboard.
+  bool alternate = (int(tile.x) + int(tile.y)) % 2 != 0;
+  float4 color = alternate ? input.alternate_color : input.color;
+  return color * float4(1., 1., 1., alpha);
+}
+
 struct MonochromeSpriteVertexOutput {
   float4 position [[position]];
   float2 tile_position;
   float4 color [[flat]];
+  uint distance_field [[flat]];
+  uint sprite_id [[flat]];
   float4 clip_distance;
 };
 
@@ -616,6 +782,8 @@ struct MonochromeSpriteFragmentInput {
   float4 position [[position]];
   float2 tile_position;
   float4 color [[flat]];
+  uint distance_field [[flat]];
+  uint sprite_id [[flat]];
   float4 clip_distance;
 };
 
@@ -639,13 +807,41 @@ vertex MonochromeSpriteVertexOutput monochrome_sprite_vertex(
       device_position,
       tile_position,
       color,
+      sprite.distance_field,
+      sprite_id,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
+// The distance field value of sprites whose tile holds coverage that isn't corrected.
+constant uint LINEAR_COVERAGE = 0xffffffffu;
+
+// Returns the coverage of a signed distance field sample, given the packed edge value and
+// falloff width of the field.
+float distance_field_alpha(float sample, uint distance_field) {
+  float edge = float(distance_field & 0xffffu) / 65535.0;
+  float falloff =
+      max(float(distance_field >> 16u) / 65535.0, fwidth(sample) * 0.5);
+  return smoothstep(edge - falloff, edge + falloff, sample);
+}
+
+// Thickens the stems of light text. CoreText already corrects the coverage of dark text.
+float light_text_contrast(float enhanced_contrast, float3 color) {
+  // REC. 601 luminance coefficients for perceived brightness
+  float brightness = dot(color, float3(0.30, 0.59, 0.11));
+  float multiplier = saturate(4.0 * (brightness - 0.5));
+  return enhanced_contrast * multiplier;
+}
+
+float enhance_contrast(float alpha, float k) {
+  return alpha * (k + 1.0) / (alpha * k + 1.0);
+}
+
 fragment float4 monochrome_sprite_fragment(
     MonochromeSpriteFragmentInput input [[stage_in]],
     constant MonochromeSprite *sprites [[buffer(SpriteInputIndex_Sprites)]],
-    texture2d<float> atlas_texture [[texture(SpriteInputIndex_AtlasTexture)]]) {
+    texture2d<float> atlas_texture [[texture(SpriteInputIndex_AtlasTexture)]],
+    constant float *light_text_enhanced_contrast
+    [[buffer(SpriteInputIndex_LightTextContrast)]]) {
   if (any(input.clip_distance < float4(0.0))) {
     return float4(0.0);
   }
@@ -655,7 +851,17 @@ fragment float4 monochrome_sprite_fragment(
   float4 sample =
       atlas_texture.sample(atlas_texture_sampler, input.tile_position);
   float4 color = input.color;
-  color.a *= sample.a;
+  if (input.distance_field != 0 && input.distance_field != LINEAR_COVERAGE) {
+    color.a *= distance_field_alpha(sample.a, input.distance_field);
+  } else if (input.distance_field == 0) {
+    color.a *= enhance_contrast(
+        sample.a,
+        light_text_contrast(*light_text_enhanced_contrast, color.rgb));
+  } else {
+    color.a *= sample.a;
+  }
+  color.a *= content_mask_alpha(input.position.xy,
+                                sprites[input.sprite_id].content_mask);
   return color;
 }
 
@@ -663,6 +869,7 @@ struct PolychromeSpriteVertexOutput {
   float4 position [[position]];
   float2 tile_position;
   uint sprite_id [[flat]];
+  float2 local_position;
   float clip_distance [[clip_distance]][4];
 };
 
@@ -670,6 +877,7 @@ struct PolychromeSpriteFragmentInput {
   float4 position [[position]];
   float2 tile_position;
   uint sprite_id [[flat]];
+  float2 local_position;
 };
 
 vertex PolychromeSpriteVertexOutput polychrome_sprite_vertex(
@@ -683,15 +891,20 @@ vertex PolychromeSpriteVertexOutput polychrome_sprite_vertex(
 
   float2 unit_vertex = unit_vertices[unit_vertex_id];
   PolychromeSprite sprite = sprites[sprite_id];
-  float4 device_position =
-      to_device_position(unit_vertex, sprite.bounds, viewport_size);
-  float4 clip_distance = distance_from_clip_rect(unit_vertex, sprite.bounds,
-                                                 sprite.content_mask.bounds);
+  float4 device_position = to_device_position_transformed(
+      unit_vertex, sprite.bounds, sprite.transformation, viewport_size);
+  float2 local_position =
+      unit_vertex * float2(sprite.bounds.size.width, sprite.bounds.size.height) +
+      float2(sprite.bounds.origin.x, sprite.bounds.origin.y);
+  float4 clip_distance = distance_from_clip_rect_transformed(
+      unit_vertex, sprite.bounds, sprite.content_mask.bounds,
+      sprite.transformation);
   float2 tile_position = to_tile_position(unit_vertex, sprite.tile, atlas_size);
   return PolychromeSpriteVertexOutput{
       device_position,
       tile_position,
       sprite_id,
+      local_position,
       {clip_distance.x, clip_distance.y, clip_distance.z, clip_distance.w}};
 }
 
@@ -704,8 +917,18 @@ fragment float4 polychrome_sprite_fragment(
                                           min_filter::linear);
   float4 sample =
       atlas_texture.sample(atlas_texture_sampler, input.tile_position);
+  if (sprite.nearest != 0) {
+    float2 atlas_size =
+        float2(atlas_texture.get_width(), atlas_texture.get_height());
+    int2 origin = int2(sprite.tile.bounds.origin.x, sprite.tile.bounds.origin.y);
+    int2 last = origin +
+                int2(sprite.tile.bounds.size.width, sprite.tile.bounds.size.height) -
+                1;
+    int2 texel = clamp(int2(floor(input.tile_position * atlas_size)), origin, last);
+    sample = atlas_texture.read(uint2(texel));
+  }
   float distance =
-      quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
+      quad_sdf(input.local_position, sprite.bounds, sprite.corner_radii);
 
   float4 color = sample;
   if (sprite.grayscale) {
@@ -715,6 +938,7 @@ fragment float4 polychrome_sprite_fragment(
     color.b = grayscale;
   }
   color.a *= sprite.opacity * saturate(0.5 - distance);
+  color.a *= content_mask_alpha(input.position.xy, sprite.content_mask);
   return color;
 }
 
@@ -778,6 +1002,7 @@ fragment float4 path_rasterization_fragment(
     float distance = f / length(gradient);
     alpha = saturate(0.5 - distance);
   }
+  alpha *= content_mask_alpha(input.position.xy, v.content_mask);
 
   GradientColor gradient_color = prepare_fill_color(
     background.tag,
@@ -931,15 +1156,34 @@ float4 hsla_to_rgba(Hsla hsla) {
   rgba.y = (g + m);
   rgba.z = (b + m);
   rgba.w = a;
+  if (display_p3) {
+    rgba.xyz = display_p3_to_srgb(rgba.xyz);
+  }
   return rgba;
 }
 
+// Extended sRGB components can be negative, so these mirror the curves around zero.
 float3 srgb_to_linear(float3 color) {
-  return pow(color, float3(2.2));
+  return sign(color) * pow(abs(color), float3(2.2));
 }
 
 float3 linear_to_srgb(float3 color) {
-  return pow(color, float3(1.0 / 2.2));
+  return sign(color) * pow(abs(color), float3(1.0 / 2.2));
+}
+
+// Converts a Display P3 color to extended sRGB, whose components fall outside of 0 to 1 for
+// colors outside of the sRGB gamut.
+float3 display_p3_to_srgb(float3 color) {
+  float3 magnitude = abs(color);
+  float3 linear_p3 =
+      sign(color) * select(pow((magnitude + 0.055) / 1.055, float3(2.4)), magnitude / 12.92,
+                           magnitude <= 0.04045);
+  float3 linear_srgb = float3(dot(linear_p3, float3(1.2249401, -0.2249404, 0.0)),
+                              dot(linear_p3, float3(-0.0420569, 1.0420571, 0.0)),
+                              dot(linear_p3, float3(-0.0196376, -0.0786361, 1.0982735)));
+  magnitude = abs(linear_srgb);
+  return sign(linear_srgb) * select(1.055 * pow(magnitude, float3(1.0 / 2.4)) - 0.055,
+                                    magnitude * 12.92, magnitude <= 0.0031308);
 }
 
 // Converts a sRGB color to the Oklab color space.
@@ -1045,6 +1289,28 @@ float pick_corner_radius(float2 center_to_point, Corners_ScaledPixels corner_rad
   }
 }
 
+// The horizontal and vertical radii of a corner. A zero vertical radius takes
+// the horizontal radius, for circular corners, and a corner with no horizontal
+// radius is square.
+float2 elliptical_corner_radii(float horizontal, float vertical) {
+  if (horizontal == 0.) {
+    return float2(0.);
+  }
+  return float2(horizontal, vertical == 0. ? horizontal : vertical);
+}
+
+// Selects the horizontal and vertical radii of a corner based on quadrant.
+float2 pick_corner_radii(float2 center_to_point, Corners_ScaledPixels horizontal,
+                         Corners_ScaledPixels vertical) {
+  return elliptical_corner_radii(pick_corner_radius(center_to_point, horizontal),
+                                 pick_corner_radius(center_to_point, vertical));
+}
+
+// The mean of a corner's horizontal and vertical radii.
+float mean_radius(float2 corner_radii) {
+  return (corner_radii.x + corner_radii.y) / 2.;
+}
+
 // Signed distance of the point to the quad's border - positive outside the
 // border, and negative inside.
 float quad_sdf(float2 point, Bounds_ScaledPixels bounds,
@@ -1058,6 +1324,17 @@ float quad_sdf(float2 point, Bounds_ScaledPixels bounds,
     return quad_sdf_impl(corner_center_to_point, corner_radius);
 }
 
+// The coverage of a point by a content mask with rounded corners. The mask's
+// bounds are clipped against separately.
+float content_mask_alpha(float2 position, ContentMask_ScaledPixels content_mask) {
+    Corners_ScaledPixels radii = content_mask.corner_radii;
+    if (max(max(radii.top_left, radii.top_right),
+            max(radii.bottom_right, radii.bottom_left)) == 0.) {
+        return 1.;
+    }
+    return saturate(0.5 - quad_sdf(position, content_mask.bounds, radii));
+}
+
 // Implementation of quad signed distance field
 float quad_sdf_impl(float2 corner_center_to_point, float corner_radius) {
     if (corner_radius == 0.0) {
@@ -1076,6 +1353,19 @@ float quad_sdf_impl(float2 corner_center_to_point, float corner_radius) {
     }
 }
 
+// Like `quad_sdf_impl`, for corners that may be elliptical. The distance to
+// elliptical corners is approximate.
+float elliptical_quad_sdf_impl(float2 corner_center_to_point, float2 corner_radii) {
+    if (corner_radii.x == corner_radii.y) {
+        return quad_sdf_impl(corner_center_to_point, corner_radii.x);
+    }
+    if (corner_center_to_point.x <= 0.0 || corner_center_to_point.y <= 0.0) {
+        float2 corner_to_point = corner_center_to_point - corner_radii;
+        return max(corner_to_point.x, corner_to_point.y);
+    }
+    return -quarter_ellipse_sdf(corner_center_to_point, corner_radii);
+}
+
 // A standard gaussian function, used for weighting samples
 float gaussian(float x, float sigma) {
   return exp(-(x * x) / (2. * sigma * sigma)) / (sqrt(2. * M_PI_F) * sigma);
@@ -1,19 +1,21 @@
 use super::{BoolExt, MacDisplay, NSRange, NSStringExt, ns_string, renderer};
 use crate::{
-    AnyWindowHandle, Bounds, Capslock, DisplayLink, ExternalPaths, FileDropEvent,
-    ForegroundExecutor, KeyDownEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton,
-    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, PlatformAtlas, PlatformDisplay,
-    PlatformInput, PlatformWindow, Point, PromptButton, PromptLevel, RequestFrameOptions,
-    SharedString, Size, SystemWindowTab, Timer, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowKind, WindowParams, dispatch_get_main_queue,
-    dispatch_sys::dispatch_async_f, platform::PlatformInputHandler, point, px, size,
+    AnyWindowHandle, Bounds, Capslock, DisplayLink, EmbeddedNativeViews, ExternalPaths,
+    FileDropEvent, ForegroundExecutor, KeyDownEvent, Modifiers, ModifiersChangedEvent, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, NativeViewPlacement, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformWindow, Point, PromptButton, PromptLevel,
+    RequestFrameOptions, SharedString, Size, SystemWindowTab, TextContrast, Timer,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowKind,
+    WindowParams, dispatch_get_main_queue, dispatch_sys::dispatch_async_f,
+    platform::PlatformInputHandler, point, px, size,
 };
 use block::ConcreteBlock;
 use cocoa::{
     appkit::{
-        NSAppKitVersionNumber, NSAppKitVersionNumber12_0, NSApplication, NSBackingStoreBuffered,
-        NSColor, NSEvent, NSEventModifierFlags, NSFilenamesPboardType, NSPasteboard, NSScreen,
-        NSView, NSViewHeightSizable, NSViewWidthSizable, NSVisualEffectMaterial,
+        NSAppKitVersionNumber, NSAppKitVersionNumber12_0, NSApplication,
+        NSApplicationPresentationOptions, NSBackingStoreBuffered, NSColor, NSEvent,
+        NSEventModifierFlags, NSFilenamesPboardType, NSPasteboard, NSScreen, NSView,
+        NSViewHeightSizable, NSViewMinYMargin, NSViewWidthSizable, NSVisualEffectMaterial,
         NSVisualEffectState, NSVisualEffectView, NSWindow, NSWindowButton,
         NSWindowCollectionBehavior, NSWindowOcclusionState, NSWindowOrderingMode,
         NSWindowStyleMask, NSWindowTitleVisibility,
@@ -407,7 +409,7 @@ struct MacWindowState {
     traffic_light_position: Option<Point<Pixels>>,
     transparent_titlebar: bool,
     previous_modifiers_changed_event: Option<PlatformInput>,
-    keystroke_for_do_command: Option<Keystroke>,
+    key_down_for_do_command: Option<KeyDownEvent>,
     do_command_handled: Option<bool>,
     external_files_dragged: bool,
     // Whether the next left-mouse click is also the focusing click.
@@ -419,6 +421,9 @@ struct MacWindowState {
     select_previous_tab_callback: Option<Box<dyn FnMut()>>,
     toggle_tab_bar_callback: Option<Box<dyn FnMut()>>,
     activated_least_once: bool,
+    render_scale: f32,
+    // Embedded platform views, hosted by the views that clip them.
+    native_views: EmbeddedNativeViews<id>,
 }
 
 impl MacWindowState {
@@ -542,6 +547,26 @@ impl MacWindowState {
         size(px(width as f32), px(height as f32))
     }
 
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        unsafe {
+            let screen = NSWindow::screen(self.native_window);
+            if screen == nil {
+                return None;
+            }
+            let content_view = self.native_window.contentView();
+            let content_frame: NSRect =
+                msg_send![self.native_window, convertRectToScreen: NSView::frame(content_view)];
+            let screen_frame = NSScreen::frame(screen);
+
+            // Flip the y coordinate to be top-left origin, as in `bounds`.
+            let top = screen_frame.size.height - content_frame.origin.y - content_frame.size.height;
+            Some(point(
+                px((content_frame.origin.x - screen_frame.origin.x) as f32),
+                px((top + screen_frame.origin.y) as f32),
+            ))
+        }
+    }
+
     fn scale_factor(&self) -> f32 {
         get_scale_factor(self.native_window)
     }
@@ -582,6 +607,7 @@ impl MacWindow {
             display_id,
             window_min_size,
             tabbing_identifier,
+            color_space,
         }: WindowParams,
         executor: ForegroundExecutor,
         renderer_context: renderer::Context,
@@ -695,6 +721,7 @@ impl MacWindow {
                     native_view as *mut _,
                     bounds.size.map(|pixels| pixels.0),
                     false,
+                    color_space,
                 ),
                 request_frame_callback: None,
                 event_callback: None,
@@ -714,7 +741,7 @@ impl MacWindow {
                     .as_ref()
                     .is_none_or(|titlebar| titlebar.appears_transparent),
                 previous_modifiers_changed_event: None,
-                keystroke_for_do_command: None,
+                key_down_for_do_command: None,
                 do_command_handled: None,
                 external_files_dragged: false,
                 first_mouse: false,
@@ -725,6 +752,8 @@ impl MacWindow {
                 select_previous_tab_callback: None,
                 toggle_tab_bar_callback: None,
                 activated_least_once: false,
+                render_scale: 1.0,
+                native_views: EmbeddedNativeViews::default(),
             })));
 
             (*native_window).set_ivar(
@@ -982,6 +1011,36 @@ impl PlatformWindow for MacWindow {
             .detach();
     }
 
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        self.0.as_ref().lock().content_origin()
+    }
+
+    fn set_bounds(&mut self, bounds: Bounds<Pixels>) {
+        let this = self.0.lock();
+        let window = this.native_window;
+        this.executor
+            .spawn(async move {
+                unsafe {
+                    let screen = NSWindow::screen(window);
+                    if screen == nil {
+                        return;
+                    }
+                    let screen_frame = NSScreen::frame(screen);
+                    // Invert the flip in `MacWindowState::bounds`.
+                    let frame = NSRect::new(
+                        NSPoint::new(
+                            screen_frame.origin.x + bounds.origin.x.0 as f64,
+                            screen_frame.size.height + screen_frame.origin.y
+                                - (bounds.origin.y + bounds.size.height).0 as f64,
+                        ),
+                        NSSize::new(bounds.size.width.0 as f64, bounds.size.height.0 as f64),
+                    );
+                    let _: () = msg_send![window, setFrame: frame display: YES];
+                }
+            })
+            .detach();
+    }
+
     fn merge_all_windows(&self) {
         let native_window = self.0.lock().native_window;
         unsafe extern "C" fn merge_windows_async(context: *mut std::ffi::c_void) {
@@ -1474,10 +1533,112 @@ impl PlatformWindow for MacWindow {
         this.renderer.draw(scene);
     }
 
+    fn set_text_contrast(&self, contrast: Option<TextContrast>) {
+        self.0.lock().renderer.set_text_contrast(contrast);
+    }
+
+    fn capture_frame(&self) -> oneshot::Receiver<anyhow::Result<image::RgbaImage>> {
+        self.0.lock().renderer.capture_frame()
+    }
+
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         self.0.lock().renderer.sprite_atlas().clone()
     }
 
+    fn set_render_scale(&self, render_scale: f32) -> bool {
+        let mut lock = self.0.lock();
+        if lock.render_scale != render_scale {
+            lock.render_scale = render_scale;
+            let drawable_size = lock
+                .content_size()
+                .to_device_pixels(lock.scale_factor() * render_scale);
+            // The layer stretches a smaller drawable to fill its bounds.
+            lock.renderer.update_drawable_size(drawable_size);
+        }
+        true
+    }
+
+    fn set_native_views(&self, views: &[NativeViewPlacement]) {
+        let mut lock = self.0.lock();
+        unsafe {
+            let host_view: id = msg_send![lock.native_view.as_ptr() as id, superview];
+            let host_height = NSView::frame(host_view).size.height;
+            let appkit_view = |placement: &NativeViewPlacement| match placement.view {
+                rwh::RawWindowHandle::AppKit(handle) => Some(handle.ns_view.as_ptr() as id),
+                _ => None,
+            };
+            lock.native_views.update(
+                views,
+                |placement| {
+                    let view = appkit_view(placement)?;
+                    let container: id = msg_send![class!(NSView), alloc];
+                    let container = NSView::initWithFrame_(
+                        container,
+                        NSRect::new(NSPoint::new(0., 0.), NSSize::new(0., 0.)),
+                    );
+                    container.setWantsLayer(YES);
+                    // Keep the container's distance from the top of the window as it resizes.
+                    container.setAutoresizingMask_(NSViewMinYMargin);
+                    let layer: id = msg_send![container, layer];
+                    let _: () = msg_send![layer, setMasksToBounds: YES];
+                    container.addSubview_(view);
+                    host_view.addSubview_(container);
+                    Some(container)
+                },
+                |placement, container| {
+                    let Some(view) = appkit_view(placement) else {
+                        return;
+                    };
+                    // AppKit views have a bottom-left origin.
+                    let clip = placement.clip;
+                    let container_frame = NSRect::new(
+                        NSPoint::new(
+                            clip.origin.x.0 as f64,
+                            host_height - (clip.origin.y + clip.size.height).0 as f64,
+                        ),
+                        NSSize::new(clip.size.width.0 as f64, clip.size.height.0 as f64),
+                    );
+                    let bounds = placement.bounds;
+                    let view_frame = NSRect::new(
+                        NSPoint::new(
+                            (bounds.origin.x - clip.origin.x).0 as f64,
+                            (clip.bottom() - bounds.bottom()).0 as f64,
+                        ),
+                        NSSize::new(bounds.size.width.0 as f64, bounds.size.height.0 as f64),
+                    );
+                    let _: () = msg_send![*container, setFrame: container_frame];
+                    let _: () = msg_send![view, setFrame: view_frame];
+                },
+                |placement, container| {
+                    if let Some(view) = appkit_view(placement) {
+                        let _: () = msg_send![view, removeFromSuperview];
+                    }
+                    let _: () = msg_send![container, removeFromSuperview];
+                    let _: () = msg_send![container, release];
+                },
+            );
+        }
+    }
+
+    fn set_system_gestures_enabled(&self, enabled: bool) -> bool {
+        let options = if enabled {
+            NSApplicationPresentationOptions::NSApplicationPresentationDefault
+        } else {
+            NSApplicationPresentationOptions::NSApplicationPresentationHideDock
+                | NSApplicationPresentationOptions::NSApplicationPresentationHideMenuBar
+                | NSApplicationPresentationOptions::NSApplicationPresentationDisableProcessSwitching
+                | NSApplicationPresentationOptions::NSApplicationPresentationDisableForceQuit
+                | NSApplicationPresentationOptions::NSApplicationPresentationDisableSessionTermination
+                | NSApplicationPresentationOptions::NSApplicationPresentationDisableHideApplication
+                | NSApplicationPresentationOptions::NSApplicationPresentationDisableAppleMenu
+        };
+        unsafe {
+            let app = NSApplication::sharedApplication(nil);
+            let _: () = msg_send![app, setPresentationOptions: options.bits()];
+        }
+        true
+    }
+
     fn gpu_specs(&self) -> Option<crate::GpuSpecs> {
         None
     }
@@ -1698,7 +1859,13 @@ extern "C" fn handle_key_event(this: &Object, native_event: id, key_equivalent:
             // the "key down" event if we've already just processed its "key equivalent" version.
             if key_equivalent {
                 lock.last_key_equivalent = Some(key_down_event.clone());
-            } else if lock.last_key_equivalent.take().as_ref() == Some(&key_down_event) {
+            } else if let Some(last_key_equivalent) = lock.last_key_equivalent.take()
+                // Each reading of the event converts its time anew, so the timestamps can differ.
+                && (KeyDownEvent {
+                    timestamp: key_down_event.timestamp,
+                    ..last_key_equivalent
+                }) == key_down_event
+            {
                 return NO;
             }
 
@@ -1724,7 +1891,7 @@ extern "C" fn handle_key_event(this: &Object, native_event: id, key_equivalent:
             {
                 {
                     let mut lock = window_state.as_ref().lock();
-                    lock.keystroke_for_do_command = Some(key_down_event.keystroke.clone());
+                    lock.key_down_for_do_command = Some(key_down_event.clone());
                     lock.do_command_handled.take();
                     drop(lock);
                 }
@@ -1733,7 +1900,7 @@ extern "C" fn handle_key_event(this: &Object, native_event: id, key_equivalent:
                     let input_context: id = msg_send![this, inputContext];
                     msg_send![input_context, handleEvent: native_event]
                 };
-                window_state.as_ref().lock().keystroke_for_do_command.take();
+                window_state.as_ref().lock().key_down_for_do_command.take();
                 if let Some(handled) = window_state.as_ref().lock().do_command_handled.take() {
                     return handled as BOOL;
                 } else if handled == YES {
@@ -1876,7 +2043,10 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
                         .spawn(synthetic_drag(
                             weak_window_state,
                             lock.synthetic_drag_counter,
-                            event.clone(),
+                            MouseMoveEvent {
+                                timestamp: None,
+                                ..event.clone()
+                            },
                         ))
                         .detach();
                 }
@@ -1889,11 +2059,13 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
             PlatformInput::ModifiersChanged(ModifiersChangedEvent {
                 modifiers,
                 capslock,
+                ..
             }) => {
                 // Only raise modifiers changed event when they have actually changed
                 if let Some(PlatformInput::ModifiersChanged(ModifiersChangedEvent {
                     modifiers: prev_modifiers,
                     capslock: prev_capslock,
+                    ..
                 })) = &lock.previous_modifiers_changed_event
                     && prev_modifiers == modifiers
                     && prev_capslock == capslock
@@ -1983,7 +2155,7 @@ fn update_window_scale_factor(window_state: &Arc<Mutex<MacWindowState>>) {
     let mut lock = window_state.as_ref().lock();
     let scale_factor = lock.scale_factor();
     let size = lock.content_size();
-    let drawable_size = size.to_device_pixels(scale_factor);
+    let drawable_size = size.to_device_pixels(scale_factor * lock.render_scale);
     unsafe {
         let _: () = msg_send![
             lock.renderer.layer(),
@@ -2138,7 +2310,7 @@ extern "C" fn set_frame_size(this: &Object, _: Sel, size: NSSize) {
     }
 
     let scale_factor = lock.scale_factor();
-    let drawable_size = new_size.to_device_pixels(scale_factor);
+    let drawable_size = new_size.to_device_pixels(scale_factor * lock.render_scale);
     lock.renderer.update_drawable_size(drawable_size);
 
     if let Some(mut callback) = lock.resize_callback.take() {
@@ -2327,15 +2499,17 @@ extern "C" fn attributed_substring_for_proposed_range(
 extern "C" fn do_command_by_selector(this: &Object, _: Sel, _: Sel) {
     let state = unsafe { get_window_state(this) };
     let mut lock = state.as_ref().lock();
-    let keystroke = lock.keystroke_for_do_command.take();
+    let key_down = lock.key_down_for_do_command.take();
     let mut event_callback = lock.event_callback.take();
     drop(lock);
 
-    if let Some((keystroke, mut callback)) = keystroke.zip(event_callback.as_mut()) {
+    if let Some((key_down, mut callback)) = key_down.zip(event_callback.as_mut()) {
         let handled = (callback)(PlatformInput::KeyDown(KeyDownEvent {
-            keystroke,
+            keystroke: key_down.keystroke,
             is_held: false,
             prefer_character_input: false,
+            scan_code: None,
+            timestamp: key_down.timestamp,
         }));
         state.as_ref().lock().do_command_handled = Some(!handled.propagate);
     }
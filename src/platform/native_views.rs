@@ -0,0 +1,150 @@
+use super::NativeViewPlacement;
+use std::mem;
+
+/// The platform views embedded in a window, keyed by the id of the element that placed them
+/// rather than by their handle, which the platform may reuse once a view is destroyed.
+///
+/// Each entry keeps the platform object hosting its view, such as a clipping container, along
+/// with the placement it was last positioned at.
+pub(crate) struct EmbeddedNativeViews<T> {
+    entries: Vec<(NativeViewPlacement, T)>,
+}
+
+impl<T> Default for EmbeddedNativeViews<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T> EmbeddedNativeViews<T> {
+    /// Updates the embedded views to match `placements`, in which only the first placement of
+    /// each element is used.
+    ///
+    /// `remove` detaches the views that are no longer placed, or whose element now places a
+    /// different view. Then `embed` embeds each view placed for the first time, returning the
+    /// object hosting it, or `None` if it can't be embedded. `place` positions the newly embedded
+    /// views and those whose bounds or clip changed.
+    pub(crate) fn update(
+        &mut self,
+        placements: &[NativeViewPlacement],
+        mut embed: impl FnMut(&NativeViewPlacement) -> Option<T>,
+        mut place: impl FnMut(&NativeViewPlacement, &T),
+        mut remove: impl FnMut(&NativeViewPlacement, T),
+    ) {
+        let mut previous_entries = Vec::new();
+        for (previous, hosted) in mem::take(&mut self.entries) {
+            let placed = placements
+                .iter()
+                .find(|placement| placement.id == previous.id)
+                .is_some_and(|placement| placement.view == previous.view);
+            if placed {
+                previous_entries.push((previous, hosted));
+            } else {
+                remove(&previous, hosted);
+            }
+        }
+
+        for placement in placements {
+            if self
+                .entries
+                .iter()
+                .any(|(embedded, _)| embedded.id == placement.id)
+            {
+                continue;
+            }
+            let previous_entry = previous_entries
+                .iter()
+                .position(|(previous, _)| previous.id == placement.id)
+                .map(|index| previous_entries.remove(index));
+            let hosted = match previous_entry {
+                Some((previous, hosted)) => {
+                    if previous != *placement {
+                        place(placement, &hosted);
+                    }
+                    hosted
+                }
+                None => {
+                    let Some(hosted) = embed(placement) else {
+                        continue;
+                    };
+                    place(placement, &hosted);
+                    hosted
+                }
+            };
+            self.entries.push((placement.clone(), hosted));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bounds, ElementId, GlobalElementId, point, px, size};
+    use raw_window_handle::{RawWindowHandle, Win32WindowHandle};
+    use std::{num::NonZeroIsize, sync::Arc};
+
+    fn placement(id: &'static str, handle: isize, x: f32) -> NativeViewPlacement {
+        let bounds = Bounds::new(point(px(x), px(0.)), size(px(10.), px(10.)));
+        NativeViewPlacement {
+            id: GlobalElementId(Arc::from([ElementId::Name(id.into())])),
+            view: RawWindowHandle::Win32(Win32WindowHandle::new(
+                NonZeroIsize::new(handle).unwrap(),
+            )),
+            bounds,
+            clip: bounds,
+        }
+    }
+
+    #[derive(Default)]
+    struct Calls {
+        embedded: Vec<f32>,
+        placed: Vec<f32>,
+        removed: Vec<f32>,
+    }
+
+    fn update(views: &mut EmbeddedNativeViews<f32>, placements: &[NativeViewPlacement]) -> Calls {
+        let calls = std::cell::RefCell::new(Calls::default());
+        views.update(
+            placements,
+            |placement| {
+                calls
+                    .borrow_mut()
+                    .embedded
+                    .push(placement.bounds.origin.x.0);
+                Some(placement.bounds.origin.x.0)
+            },
+            |placement, _| calls.borrow_mut().placed.push(placement.bounds.origin.x.0),
+            |_, hosted| calls.borrow_mut().removed.push(hosted),
+        );
+        calls.into_inner()
+    }
+
+    #[test]
+    fn test_embedded_views_are_keyed_by_element() {
+        let mut views = EmbeddedNativeViews::default();
+        let calls = update(&mut views, &[placement("a", 1, 0.), placement("b", 2, 20.)]);
+        assert_eq!(calls.embedded, [0., 20.]);
+        assert_eq!(calls.placed, [0., 20.]);
+
+        // Unchanged placements aren't positioned again, and moved ones keep their host.
+        let calls = update(&mut views, &[placement("a", 1, 0.), placement("b", 2, 30.)]);
+        assert!(calls.embedded.is_empty());
+        assert_eq!(calls.placed, [30.]);
+        assert!(calls.removed.is_empty());
+
+        // An element placing a different view replaces its host, and views no longer placed are
+        // removed, even when another element reuses their handle.
+        let calls = update(
+            &mut views,
+            &[placement("a", 3, 40.), placement("c", 2, 50.)],
+        );
+        assert_eq!(calls.removed, [0., 20.]);
+        assert_eq!(calls.embedded, [40., 50.]);
+
+        let calls = update(&mut views, &[]);
+        assert_eq!(calls.removed, [40., 50.]);
+        assert!(views.entries.is_empty());
+    }
+}
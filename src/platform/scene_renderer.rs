@@ -0,0 +1,226 @@
+use std::{borrow::Cow, cell::RefCell, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use collections::FxHashMap;
+use image::RgbaImage;
+use parking_lot::Mutex;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+
+use crate::{
+    AtlasKey, AtlasTextureKind, AtlasTile, DevicePixels, PlatformAtlas, PlatformWindow, Scene, Size,
+};
+
+/// The window surface a [`SceneRenderer`] draws into.
+#[derive(Clone, Copy, Debug)]
+pub struct SceneRenderTarget {
+    /// The platform window or view to draw into.
+    pub window: RawWindowHandle,
+    /// The display connection the window belongs to.
+    pub display: RawDisplayHandle,
+}
+
+/// A renderer that draws a window's scenes in place of the platform renderer, for alternative
+/// backends such as a Vello-based or CPU renderer. Register one with [`crate::App::set_scene_renderer`].
+///
+/// Each window gets its own renderer, which must create whatever surface it needs for its
+/// target. The platform renderer still owns the window's native layer, so renderers that draw
+/// through a different graphics API may need to present into a surface of their own.
+pub trait SceneRenderer: 'static {
+    /// Returns the atlas that glyphs, icons and images in this renderer's scenes are rasterized
+    /// into. The same atlas must be returned every time.
+    fn sprite_atlas(&self) -> Arc<dyn SceneRendererAtlas>;
+
+    /// Draws `scene`, which has been finished, into `target` at `viewport` size.
+    fn draw(&mut self, scene: &Scene, target: &SceneRenderTarget, viewport: Size<DevicePixels>);
+
+    /// Called when the size of the window's drawable area changes.
+    fn resize(&mut self, viewport: Size<DevicePixels>);
+
+    /// Returns the pixels of the last frame drawn, if the renderer supports capturing them.
+    fn capture(&mut self) -> Option<RgbaImage> {
+        None
+    }
+}
+
+/// Stores the sprites that a [`SceneRenderer`]'s scenes draw from. GPUI caches sprites by what
+/// they were rasterized from, so each is inserted once until it's removed.
+pub trait SceneRendererAtlas: Send + Sync {
+    /// Stores a sprite of `size` and returns the tile that primitives will refer to it by.
+    /// [`AtlasTextureKind::Monochrome`] sprites have one byte of coverage per pixel, and
+    /// [`AtlasTextureKind::Polychrome`] sprites have four bytes per pixel in BGRA order.
+    fn insert(
+        &self,
+        kind: AtlasTextureKind,
+        size: Size<DevicePixels>,
+        bytes: &[u8],
+    ) -> Result<AtlasTile>;
+
+    /// Frees a tile returned by [`SceneRendererAtlas::insert`].
+    fn remove(&self, tile: &AtlasTile);
+}
+
+pub(crate) type SceneRendererFactory =
+    dyn Fn(&SceneRenderTarget, Size<DevicePixels>) -> Result<Box<dyn SceneRenderer>>;
+
+/// A window's [`SceneRenderer`], with the surface it draws into.
+pub(crate) struct WindowSceneRenderer {
+    renderer: RefCell<Box<dyn SceneRenderer>>,
+    target: SceneRenderTarget,
+    viewport: Size<DevicePixels>,
+    sprite_atlas: Arc<dyn PlatformAtlas>,
+}
+
+impl WindowSceneRenderer {
+    pub(crate) fn new(
+        build_renderer: &SceneRendererFactory,
+        platform_window: &dyn PlatformWindow,
+    ) -> Result<Self> {
+        let target = SceneRenderTarget {
+            window: platform_window
+                .window_handle()
+                .map_err(|error| anyhow!("failed to get window handle: {error}"))?
+                .as_raw(),
+            display: platform_window
+                .display_handle()
+                .map_err(|error| anyhow!("failed to get display handle: {error}"))?
+                .as_raw(),
+        };
+        let viewport = platform_window
+            .content_size()
+            .to_device_pixels(platform_window.scale_factor());
+        let renderer = build_renderer(&target, viewport)?;
+        let sprite_atlas = Arc::new(SceneRendererAtlasAdapter::new(renderer.sprite_atlas()));
+        Ok(Self {
+            renderer: RefCell::new(renderer),
+            target,
+            viewport,
+            sprite_atlas,
+        })
+    }
+
+    pub(crate) fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        self.sprite_atlas.clone()
+    }
+
+    pub(crate) fn draw(&self, scene: &Scene) {
+        self.renderer
+            .borrow_mut()
+            .draw(scene, &self.target, self.viewport);
+    }
+
+    pub(crate) fn resize(&mut self, viewport: Size<DevicePixels>) {
+        if self.viewport != viewport {
+            self.viewport = viewport;
+            self.renderer.get_mut().resize(viewport);
+        }
+    }
+
+    pub(crate) fn capture(&self) -> Option<RgbaImage> {
+        self.renderer.borrow_mut().capture()
+    }
+}
+
+/// Adapts a [`SceneRendererAtlas`] to the atlas interface that windows rasterize sprites into.
+pub(crate) struct SceneRendererAtlasAdapter {
+    atlas: Arc<dyn SceneRendererAtlas>,
+    tiles: Mutex<FxHashMap<AtlasKey, AtlasTile>>,
+}
+
+impl SceneRendererAtlasAdapter {
+    pub(crate) fn new(atlas: Arc<dyn SceneRendererAtlas>) -> Self {
+        Self {
+            atlas,
+            tiles: Mutex::default(),
+        }
+    }
+}
+
+impl PlatformAtlas for SceneRendererAtlasAdapter {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        if let Some(tile) = self.tiles.lock().get(key) {
+            return Ok(Some(tile.clone()));
+        }
+        let Some((size, bytes)) = build()? else {
+            return Ok(None);
+        };
+        let tile = self.atlas.insert(key.texture_kind(), size, &bytes)?;
+        self.tiles.lock().insert(key.clone(), tile.clone());
+        Ok(Some(tile))
+    }
+
+    fn remove(&self, key: &AtlasKey) {
+        if let Some(tile) = self.tiles.lock().remove(key) {
+            self.atlas.remove(&tile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtlasTextureId, Bounds, ImageId, RenderImageParams, TileId, size};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Default)]
+    struct CountingAtlas {
+        inserted: AtomicU32,
+        removed: AtomicU32,
+    }
+
+    impl SceneRendererAtlas for CountingAtlas {
+        fn insert(
+            &self,
+            kind: AtlasTextureKind,
+            size: Size<DevicePixels>,
+            _bytes: &[u8],
+        ) -> Result<AtlasTile> {
+            let tile_id = self.inserted.fetch_add(1, Ordering::SeqCst);
+            Ok(AtlasTile {
+                texture_id: AtlasTextureId { index: 0, kind },
+                tile_id: TileId(tile_id),
+                padding: 0,
+                bounds: Bounds {
+                    origin: Default::default(),
+                    size,
+                },
+            })
+        }
+
+        fn remove(&self, _tile: &AtlasTile) {
+            self.removed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_scene_renderer_atlas_caches_tiles() {
+        let atlas = Arc::new(CountingAtlas::default());
+        let adapter = SceneRendererAtlasAdapter::new(atlas.clone());
+        let key = AtlasKey::Image(RenderImageParams {
+            image_id: ImageId(1),
+            frame_index: 0,
+            mip_level: 0,
+        });
+        let mut build = || {
+            Ok(Some((
+                size(DevicePixels(1), DevicePixels(1)),
+                Cow::Owned(vec![0; 4]),
+            )))
+        };
+
+        let tile = adapter.get_or_insert_with(&key, &mut build).unwrap();
+        assert_eq!(adapter.get_or_insert_with(&key, &mut build).unwrap(), tile);
+        assert_eq!(atlas.inserted.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            tile.map(|tile| tile.texture_id.kind),
+            Some(AtlasTextureKind::Polychrome)
+        );
+
+        adapter.remove(&key);
+        adapter.remove(&key);
+        assert_eq!(atlas.removed.load(Ordering::SeqCst), 1);
+    }
+}
@@ -23,6 +23,7 @@ pub(crate) struct TestWindowState {
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     hit_test_window_control_callback: Option<Box<dyn FnMut() -> Option<WindowControlArea>>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
+    request_frame_callback: Option<Box<dyn FnMut(RequestFrameOptions)>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     hover_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     resize_callback: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
@@ -68,6 +69,7 @@ impl TestWindow {
             should_close_handler: None,
             hit_test_window_control_callback: None,
             input_callback: None,
+            request_frame_callback: None,
             active_status_change_callback: None,
             hover_status_change_callback: None,
             resize_callback: None,
@@ -89,6 +91,17 @@ impl TestWindow {
         self.0.lock().resize_callback = Some(callback);
     }
 
+    pub fn simulate_move(&mut self, origin: Point<Pixels>) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.moved_callback.take() else {
+            return;
+        };
+        lock.bounds.origin = origin;
+        drop(lock);
+        callback();
+        self.0.lock().moved_callback = Some(callback);
+    }
+
     pub(crate) fn simulate_active_status_change(&self, active: bool) {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.active_status_change_callback.take() else {
@@ -109,6 +122,16 @@ impl TestWindow {
         self.0.lock().input_callback = Some(callback);
         !result.propagate
     }
+
+    pub fn simulate_request_frame(&mut self) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.request_frame_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(RequestFrameOptions::default());
+        self.0.lock().request_frame_callback = Some(callback);
+    }
 }
 
 impl PlatformWindow for TestWindow {
@@ -133,6 +156,14 @@ impl PlatformWindow for TestWindow {
         lock.bounds.size = size;
     }
 
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        Some(self.bounds().origin)
+    }
+
+    fn set_bounds(&mut self, bounds: Bounds<Pixels>) {
+        self.0.lock().bounds = bounds;
+    }
+
     fn scale_factor(&self) -> f32 {
         2.0
     }
@@ -232,7 +263,9 @@ impl PlatformWindow for TestWindow {
         self.0.lock().is_fullscreen
     }
 
-    fn on_request_frame(&self, _callback: Box<dyn FnMut(RequestFrameOptions)>) {}
+    fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
+        self.0.lock().request_frame_callback = Some(callback)
+    }
 
     fn on_input(&self, callback: Box<dyn FnMut(crate::PlatformInput) -> DispatchEventResult>) {
         self.0.lock().input_callback = Some(callback)
@@ -13,6 +13,13 @@ float light_on_dark_contrast(float enhancedContrast, float3 color) {
     return enhancedContrast * multiplier;
 }
 
+// Thickens the stems of light text, which `light_on_dark_contrast` leaves out.
+float light_text_contrast(float enhancedContrast, float3 color) {
+    float brightness = color_brightness(color);
+    float multiplier = saturate(4.0f * (brightness - 0.5f));
+    return enhancedContrast * multiplier;
+}
+
 float enhance_contrast(float alpha, float k) {
     return alpha * (k + 1.0f) / (alpha * k + 1.0f);
 }
@@ -21,6 +21,8 @@ use windows::Win32::{
 };
 use windows::core::Interface;
 
+use crate::RENDER_LOG_TARGET;
+
 pub(crate) fn try_to_recover_from_device_lost<T>(mut f: impl FnMut() -> Result<T>) -> Result<T> {
     (0..5)
         .map(|i| {
@@ -62,13 +64,22 @@ impl DirectXDevices {
             .context("Creating Direct3D device")?;
             match feature_level {
                 D3D_FEATURE_LEVEL_11_1 => {
-                    log::info!("Created device with Direct3D 11.1 feature level.")
+                    log::info!(
+                        target: RENDER_LOG_TARGET,
+                        "Created device with Direct3D 11.1 feature level."
+                    )
                 }
                 D3D_FEATURE_LEVEL_11_0 => {
-                    log::info!("Created device with Direct3D 11.0 feature level.")
+                    log::info!(
+                        target: RENDER_LOG_TARGET,
+                        "Created device with Direct3D 11.0 feature level."
+                    )
                 }
                 D3D_FEATURE_LEVEL_10_1 => {
-                    log::info!("Created device with Direct3D 10.1 feature level.")
+                    log::info!(
+                        target: RENDER_LOG_TARGET,
+                        "Created device with Direct3D 10.1 feature level."
+                    )
                 }
                 _ => unreachable!(),
             }
@@ -107,6 +118,7 @@ fn get_dxgi_factory(debug_layer_available: bool) -> Result<IDXGIFactory6> {
     } else {
         #[cfg(debug_assertions)]
         log::warn!(
+            target: RENDER_LOG_TARGET,
             "Failed to get DXGI debug interface. DirectX debugging features will be disabled."
         );
         DXGI_CREATE_FACTORY_FLAGS::default()
@@ -122,7 +134,7 @@ fn get_adapter(dxgi_factory: &IDXGIFactory6, debug_layer_available: bool) -> Res
             let gpu_name = String::from_utf16_lossy(&desc.Description)
                 .trim_matches(char::from(0))
                 .to_string();
-            log::info!("Using GPU: {}", gpu_name);
+            log::info!(target: RENDER_LOG_TARGET, "Using GPU: {}", gpu_name);
         }
         // Check to see whether the adapter supports Direct3D 11, but don't
         // create the actual device yet.
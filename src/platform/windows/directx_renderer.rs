@@ -1,10 +1,13 @@
 use std::{
     slice,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Once, OnceLock},
+    time::Instant,
 };
 
 use ::util::ResultExt;
 use anyhow::{Context, Result};
+use futures::channel::oneshot;
+use image::RgbaImage;
 use windows::{
     Win32::{
         Foundation::HWND,
@@ -45,6 +48,9 @@ pub(crate) struct DirectXRenderer {
     pipelines: DirectXRenderPipelines,
     direct_composition: Option<DirectComposition>,
     font_info: &'static FontInfo,
+    text_contrast: Option<TextContrast>,
+    frame_captures: Vec<oneshot::Sender<Result<RgbaImage>>>,
+    start_time: Instant,
 
     width: u32,
     height: u32,
@@ -85,6 +91,7 @@ struct DirectXResources {
 struct DirectXRenderPipelines {
     shadow_pipeline: PipelineState<Shadow>,
     quad_pipeline: PipelineState<Quad>,
+    tiled_quad_pipeline: PipelineState<TiledQuad>,
     path_rasterization_pipeline: PipelineState<PathRasterizationSprite>,
     path_sprite_pipeline: PipelineState<PathSprite>,
     underline_pipeline: PipelineState<Underline>,
@@ -137,7 +144,7 @@ impl DirectXRenderer {
         disable_direct_composition: bool,
     ) -> Result<Self> {
         if disable_direct_composition {
-            log::info!("Direct Composition is disabled.");
+            log::info!(target: RENDER_LOG_TARGET, "Direct Composition is disabled.");
         }
 
         let devices = DirectXRendererDevices::new(directx_devices, disable_direct_composition)
@@ -171,16 +178,101 @@ impl DirectXRenderer {
             pipelines,
             direct_composition,
             font_info: Self::get_font_info(),
+            text_contrast: None,
+            frame_captures: Vec::new(),
+            start_time: Instant::now(),
             width: 1,
             height: 1,
             skip_draws: false,
         })
     }
 
+    /// Renders grayscale text with `contrast`, or with DirectWrite's rendering parameters if it's
+    /// `None`.
+    pub(crate) fn set_text_contrast(&mut self, contrast: Option<TextContrast>) {
+        self.text_contrast = contrast;
+    }
+
     pub(crate) fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         self.atlas.clone()
     }
 
+    /// Reads back the pixels of the next frame drawn, once the GPU has finished rendering it.
+    pub(crate) fn capture_frame(&mut self) -> oneshot::Receiver<Result<RgbaImage>> {
+        let (tx, rx) = oneshot::channel();
+        self.frame_captures.push(tx);
+        rx
+    }
+
+    /// Copies the frame drawn into the render target to a staging texture, and reads its pixels
+    /// once the GPU has finished rendering it.
+    fn read_frame(&self) -> Result<RgbaImage> {
+        let devices = self.devices.as_ref().context("devices missing")?;
+        let render_target = self
+            .resources
+            .as_ref()
+            .context("resources missing")?
+            .render_target
+            .as_ref()
+            .context("missing render target")?;
+        let staging_texture = unsafe {
+            let mut output = None;
+            let desc = D3D11_TEXTURE2D_DESC {
+                Width: self.width,
+                Height: self.height,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: RENDER_TARGET_FORMAT,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_STAGING,
+                BindFlags: 0,
+                CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+                MiscFlags: 0,
+            };
+            devices
+                .device
+                .CreateTexture2D(&desc, None, Some(&mut output))?;
+            output.context("failed to create frame capture texture")?
+        };
+        // The swap chain's alpha is only used when it's composited with DirectComposition.
+        let alpha = if self.direct_composition.is_some() {
+            CapturedAlpha::Premultiplied
+        } else {
+            CapturedAlpha::Ignored
+        };
+        unsafe {
+            devices
+                .device_context
+                .CopyResource(&staging_texture, render_target);
+            let mut mapped = std::mem::zeroed::<D3D11_MAPPED_SUBRESOURCE>();
+            devices.device_context.Map(
+                &staging_texture,
+                0,
+                D3D11_MAP_READ,
+                0,
+                Some(&mut mapped),
+            )?;
+            let bytes = slice::from_raw_parts(
+                mapped.pData as *const u8,
+                mapped.RowPitch as usize * self.height as usize,
+            );
+            let image = captured_frame_image(
+                size(
+                    DevicePixels(self.width as i32),
+                    DevicePixels(self.height as i32),
+                ),
+                mapped.RowPitch as usize,
+                bytes,
+                alpha,
+            );
+            devices.device_context.Unmap(&staging_texture, 0);
+            image
+        }
+    }
+
     fn pre_draw(&self) -> Result<()> {
         let resources = self.resources.as_ref().expect("resources missing");
         let device_context = &self
@@ -188,14 +280,30 @@ impl DirectXRenderer {
             .as_ref()
             .expect("devices missing")
             .device_context;
+        let (gamma_ratios, grayscale_enhanced_contrast, light_text_enhanced_contrast) =
+            match self.text_contrast {
+                Some(contrast) => (
+                    contrast.gamma_ratios(),
+                    contrast.enhanced_contrast.max(0.0),
+                    contrast.light_text_enhanced_contrast.max(0.0),
+                ),
+                None => (
+                    self.font_info.gamma_ratios,
+                    self.font_info.grayscale_enhanced_contrast,
+                    0.0,
+                ),
+            };
         update_buffer(
             device_context,
             self.globals.global_params_buffer.as_ref().unwrap(),
             &[GlobalParams {
-                gamma_ratios: self.font_info.gamma_ratios,
+                gamma_ratios,
                 viewport_size: [resources.viewport.Width, resources.viewport.Height],
-                grayscale_enhanced_contrast: self.font_info.grayscale_enhanced_contrast,
-                _pad: 0,
+                grayscale_enhanced_contrast,
+                _pad0: 0,
+                time: self.start_time.elapsed().as_secs_f32(),
+                light_text_enhanced_contrast,
+                _pad1: [0; 2],
             }],
         )?;
         unsafe {
@@ -310,6 +418,7 @@ impl DirectXRenderer {
             match batch {
                 PrimitiveBatch::Shadows(shadows) => self.draw_shadows(shadows),
                 PrimitiveBatch::Quads(quads) => self.draw_quads(quads),
+                PrimitiveBatch::TiledQuads(quads) => self.draw_tiled_quads(quads),
                 PrimitiveBatch::Paths(paths) => {
                     self.draw_paths_to_intermediate(paths)?;
                     self.draw_paths_from_intermediate(paths)
@@ -324,6 +433,18 @@ impl DirectXRenderer {
                     sprites,
                 } => self.draw_polychrome_sprites(texture_id, sprites),
                 PrimitiveBatch::Surfaces(surfaces) => self.draw_surfaces(surfaces),
+                // Backdrop blurs are only drawn by the Blade renderer.
+                PrimitiveBatch::BackdropBlurs(_) => Ok(()),
+                PrimitiveBatch::CustomRenderPrimitives(_) => {
+                    static WARNING: Once = Once::new();
+                    WARNING.call_once(|| {
+                        log::warn!(
+                            target: RENDER_LOG_TARGET,
+                            "the DirectX renderer doesn't support custom rendering, skipping custom render regions"
+                        )
+                    });
+                    Ok(())
+                }
             }
             .context(format!(
                 "scene too large:\
@@ -337,6 +458,10 @@ impl DirectXRenderer {
                 scene.surfaces.len(),
             ))?;
         }
+        if !self.frame_captures.is_empty() {
+            let image = self.read_frame();
+            send_captured_frame(self.frame_captures.drain(..), image);
+        }
         self.present()
     }
 
@@ -465,6 +590,7 @@ impl DirectXRenderer {
                 st_position: v.st_position,
                 color: path.color,
                 bounds: path.clipped_bounds(),
+                content_mask: path.content_mask.clone(),
             }));
         }
 
@@ -547,6 +673,27 @@ impl DirectXRenderer {
         )
     }
 
+    fn draw_tiled_quads(&mut self, quads: &[TiledQuad]) -> Result<()> {
+        if quads.is_empty() {
+            return Ok(());
+        }
+        let devices = self.devices.as_ref().context("devices missing")?;
+        let resources = self.resources.as_ref().context("resources missing")?;
+        self.pipelines.tiled_quad_pipeline.update_buffer(
+            &devices.device,
+            &devices.device_context,
+            quads,
+        )?;
+        self.pipelines.tiled_quad_pipeline.draw(
+            &devices.device_context,
+            slice::from_ref(&resources.viewport),
+            slice::from_ref(&self.globals.global_params_buffer),
+            D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+            4,
+            quads.len() as u32,
+        )
+    }
+
     fn draw_underlines(&mut self, underlines: &[Underline]) -> Result<()> {
         if underlines.is_empty() {
             return Ok(());
@@ -761,6 +908,13 @@ impl DirectXRenderPipelines {
             64,
             create_blend_state(device)?,
         )?;
+        let tiled_quad_pipeline = PipelineState::new(
+            device,
+            "tiled_quad_pipeline",
+            ShaderModule::TiledQuad,
+            4,
+            create_blend_state(device)?,
+        )?;
         let path_rasterization_pipeline = PipelineState::new(
             device,
             "path_rasterization_pipeline",
@@ -800,6 +954,7 @@ impl DirectXRenderPipelines {
         Ok(Self {
             shadow_pipeline,
             quad_pipeline,
+            tiled_quad_pipeline,
             path_rasterization_pipeline,
             path_sprite_pipeline,
             underline_pipeline,
@@ -878,7 +1033,10 @@ struct GlobalParams {
     gamma_ratios: [f32; 4],
     viewport_size: [f32; 2],
     grayscale_enhanced_contrast: f32,
-    _pad: u32,
+    _pad0: u32,
+    time: f32,
+    light_text_enhanced_contrast: f32,
+    _pad1: [u32; 2],
 }
 
 struct PipelineState<T> {
@@ -932,6 +1090,7 @@ impl<T> PipelineState<T> {
         if self.buffer_size < data.len() {
             let new_buffer_size = data.len().next_power_of_two();
             log::info!(
+                target: RENDER_LOG_TARGET,
                 "Updating {} buffer size from {} to {}",
                 self.label,
                 self.buffer_size,
@@ -1001,13 +1160,14 @@ impl<T> PipelineState<T> {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 #[repr(C)]
 struct PathRasterizationSprite {
     xy_position: Point<ScaledPixels>,
     st_position: Point<f32>,
     color: Background,
     bounds: Bounds<ScaledPixels>,
+    content_mask: ContentMask<ScaledPixels>,
 }
 
 #[derive(Clone, Copy)]
@@ -1405,6 +1565,7 @@ pub(crate) mod shader_resources {
     #[derive(Copy, Clone, Debug, Eq, PartialEq)]
     pub(crate) enum ShaderModule {
         Quad,
+        TiledQuad,
         Shadow,
         Underline,
         PathRasterization,
@@ -1457,6 +1618,10 @@ pub(crate) mod shader_resources {
                     ShaderTarget::Vertex => QUAD_VERTEX_BYTES,
                     ShaderTarget::Fragment => QUAD_FRAGMENT_BYTES,
                 },
+                ShaderModule::TiledQuad => match target {
+                    ShaderTarget::Vertex => TILED_QUAD_VERTEX_BYTES,
+                    ShaderTarget::Fragment => TILED_QUAD_FRAGMENT_BYTES,
+                },
                 ShaderModule::Shadow => match target {
                     ShaderTarget::Vertex => SHADOW_VERTEX_BYTES,
                     ShaderTarget::Fragment => SHADOW_FRAGMENT_BYTES,
@@ -1549,7 +1714,11 @@ pub(crate) mod shader_resources {
                 let error_string =
                     std::ffi::CStr::from_ptr(error_blob.GetBufferPointer() as *const i8)
                         .to_string_lossy();
-                log::error!("Shader compile error: {}", error_string);
+                log::error!(
+                    target: crate::RENDER_LOG_TARGET,
+                    "Shader compile error: {}",
+                    error_string
+                );
                 return Err(anyhow::anyhow!("Compile error: {}", error_string));
             }
             Ok(compile_blob.unwrap())
@@ -1564,6 +1733,7 @@ pub(crate) mod shader_resources {
         pub fn as_str(&self) -> &str {
             match self {
                 ShaderModule::Quad => "quad",
+                ShaderModule::TiledQuad => "tiled_quad",
                 ShaderModule::Shadow => "shadow",
                 ShaderModule::Underline => "underline",
                 ShaderModule::PathRasterization => "path_rasterization",
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
 
 use ::util::ResultExt;
 use anyhow::Context as _;
@@ -6,7 +6,7 @@ use windows::{
     Win32::{
         Foundation::*,
         Graphics::Gdi::*,
-        System::SystemServices::*,
+        System::{SystemInformation::GetTickCount, SystemServices::*},
         UI::{
             Controls::*,
             HiDpi::*,
@@ -307,6 +307,7 @@ impl WindowsWindowInner {
             position: logical_point(x, y, scale_factor),
             pressed_button,
             modifiers: current_modifiers(),
+            timestamp: message_timestamp(),
         });
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -329,7 +330,11 @@ impl WindowsWindowInner {
 
     fn handle_syskeyup_msg(&self, wparam: WPARAM, lparam: LPARAM) -> Option<isize> {
         let input = handle_key_event(wparam, lparam, &self.state, |keystroke, _| {
-            PlatformInput::KeyUp(KeyUpEvent { keystroke })
+            PlatformInput::KeyUp(KeyUpEvent {
+                keystroke,
+                scan_code: Some(scan_code(lparam)),
+                timestamp: message_timestamp(),
+            })
         })?;
         let mut func = self.state.callbacks.input.take()?;
 
@@ -352,6 +357,8 @@ impl WindowsWindowInner {
                     keystroke,
                     is_held: lparam.0 & (0x1 << 30) > 0,
                     prefer_character_input,
+                    scan_code: Some(scan_code(lparam)),
+                    timestamp: message_timestamp(),
                 })
             },
         ) else {
@@ -371,7 +378,11 @@ impl WindowsWindowInner {
 
     fn handle_keyup_msg(&self, wparam: WPARAM, lparam: LPARAM) -> Option<isize> {
         let Some(input) = handle_key_event(wparam, lparam, &self.state, |keystroke, _| {
-            PlatformInput::KeyUp(KeyUpEvent { keystroke })
+            PlatformInput::KeyUp(KeyUpEvent {
+                keystroke,
+                scan_code: Some(scan_code(lparam)),
+                timestamp: message_timestamp(),
+            })
         }) else {
             return Some(1);
         };
@@ -418,6 +429,7 @@ impl WindowsWindowInner {
             modifiers: current_modifiers(),
             click_count,
             first_mouse: false,
+            timestamp: message_timestamp(),
         });
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -446,6 +458,7 @@ impl WindowsWindowInner {
             position: logical_point(x, y, scale_factor),
             modifiers: current_modifiers(),
             click_count,
+            timestamp: message_timestamp(),
         });
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -514,6 +527,7 @@ impl WindowsWindowInner {
             }),
             modifiers,
             touch_phase: TouchPhase::Moved,
+            timestamp: message_timestamp(),
         });
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -552,6 +566,7 @@ impl WindowsWindowInner {
             }),
             modifiers: current_modifiers(),
             touch_phase: TouchPhase::Moved,
+            timestamp: message_timestamp(),
         });
         let handled = !func(event).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -900,6 +915,7 @@ impl WindowsWindowInner {
             position: logical_point(cursor_point.x as f32, cursor_point.y as f32, scale_factor),
             pressed_button: None,
             modifiers: current_modifiers(),
+            timestamp: message_timestamp(),
         });
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
@@ -930,6 +946,7 @@ impl WindowsWindowInner {
                 modifiers: current_modifiers(),
                 click_count,
                 first_mouse: false,
+                timestamp: message_timestamp(),
             });
             let result = func(input);
             let handled = !result.propagate || result.default_prevented;
@@ -975,6 +992,7 @@ impl WindowsWindowInner {
                 position: logical_point(cursor_point.x as f32, cursor_point.y as f32, scale_factor),
                 modifiers: current_modifiers(),
                 click_count: 1,
+                timestamp: message_timestamp(),
             });
             let handled = !func(input).propagate;
             self.state.callbacks.input.set(Some(func));
@@ -1261,6 +1279,7 @@ where
             Some(PlatformInput::ModifiersChanged(ModifiersChangedEvent {
                 modifiers,
                 capslock: current_capslock(),
+                timestamp: message_timestamp(),
             }))
         }
         VK_PACKET => None,
@@ -1277,6 +1296,7 @@ where
             Some(PlatformInput::ModifiersChanged(ModifiersChangedEvent {
                 modifiers,
                 capslock,
+                timestamp: message_timestamp(),
             }))
         }
         vkey => {
@@ -1286,6 +1306,16 @@ where
     }
 }
 
+/// Returns the scan code of a key message, with the `0xE0` prefix for extended keys.
+fn scan_code(lparam: LPARAM) -> u32 {
+    let scan_code = (lparam.hiword() & 0xFF) as u32;
+    if lparam.0 & (0x1 << 24) > 0 {
+        scan_code | 0xE000
+    } else {
+        scan_code
+    }
+}
+
 fn parse_immutable(vkey: VIRTUAL_KEY) -> Option<String> {
     Some(
         match vkey {
@@ -1484,6 +1514,13 @@ pub(crate) fn current_capslock() -> Capslock {
     Capslock { on }
 }
 
+/// Returns when the message being handled was posted, which Windows reports in milliseconds
+/// since the system started.
+fn message_timestamp() -> Option<Instant> {
+    let message_time = unsafe { GetMessageTime() } as u32;
+    input_event_instant_from_millis(message_time, unsafe { GetTickCount() })
+}
+
 fn get_client_area_insets(
     handle: HWND,
     is_maximized: bool,
@@ -7,10 +7,12 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::Duration,
 };
 
 use ::util::{ResultExt, paths::SanitizedPath};
 use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
 use futures::channel::oneshot::{self, Receiver};
 use itertools::Itertools;
 use parking_lot::RwLock;
@@ -21,8 +23,20 @@ use windows::{
         Foundation::*,
         Graphics::{Direct3D11::ID3D11Device, Gdi::*},
         Security::Credentials::*,
-        System::{Com::*, LibraryLoader::*, Ole::*, SystemInformation::*},
-        UI::{Input::KeyboardAndMouse::*, Shell::*, WindowsAndMessaging::*},
+        System::{
+            Com::*,
+            LibraryLoader::*,
+            Ole::*,
+            Power::*,
+            SystemInformation::*,
+            Threading::{INFINITE, WaitForSingleObject},
+        },
+        UI::{
+            HiDpi::{GetDpiForSystem, GetDpiForWindow},
+            Input::KeyboardAndMouse::*,
+            Shell::*,
+            WindowsAndMessaging::*,
+        },
     },
     core::*,
 };
@@ -61,6 +75,8 @@ pub(crate) struct WindowsPlatformState {
     jump_list: RefCell<JumpList>,
     // NOTE: standard cursor handles don't need to close.
     pub(crate) current_cursor: Cell<Option<HCURSOR>>,
+    cursor_style: Cell<CursorStyle>,
+    custom_cursors: RefCell<HashMap<CustomCursorId, CustomCursor>>,
     directx_devices: RefCell<Option<DirectXDevices>>,
 }
 
@@ -73,6 +89,7 @@ struct PlatformCallbacks {
     will_open_app_menu: Cell<Option<Box<dyn FnMut()>>>,
     validate_app_menu_command: Cell<Option<Box<dyn FnMut(&dyn Action) -> bool>>>,
     keyboard_layout_change: Cell<Option<Box<dyn FnMut()>>>,
+    gpu_reset: Cell<Option<Box<dyn FnMut()>>>,
 }
 
 impl WindowsPlatformState {
@@ -86,6 +103,8 @@ impl WindowsPlatformState {
             callbacks,
             jump_list: RefCell::new(jump_list),
             current_cursor: Cell::new(current_cursor),
+            cursor_style: Cell::new(CursorStyle::Arrow),
+            custom_cursors: RefCell::new(HashMap::default()),
             directx_devices: RefCell::new(directx_devices),
             menus: RefCell::new(Vec::new()),
         }
@@ -178,15 +197,6 @@ impl WindowsPlatform {
     }
 
     #[inline]
-    fn post_message(&self, message: u32, wparam: WPARAM, lparam: LPARAM) {
-        self.raw_window_handles
-            .read()
-            .iter()
-            .for_each(|handle| unsafe {
-                PostMessageW(Some(handle.as_raw()), message, wparam, lparam).log_err();
-            });
-    }
-
     fn generate_creation_info(&self) -> WindowCreationInfo {
         WindowCreationInfo {
             icon: self.icon,
@@ -331,6 +341,10 @@ impl Platform for WindowsPlatform {
             .set(Some(callback));
     }
 
+    fn on_gpu_reset(&self, callback: Box<dyn FnMut()>) {
+        self.inner.state.callbacks.gpu_reset.set(Some(callback));
+    }
+
     fn run(&self, on_finish_launching: Box<dyn 'static + FnOnce()>) {
         on_finish_launching();
         self.begin_vsync_thread();
@@ -534,6 +548,45 @@ impl Platform for WindowsPlatform {
             .detach();
     }
 
+    fn print_file(&self, path: &Path) -> Receiver<Result<()>> {
+        let (tx, rx) = oneshot::channel();
+        let file = HSTRING::from(path.as_os_str());
+        let mut info = SHELLEXECUTEINFOW {
+            cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+            fMask: SEE_MASK_NOCLOSEPROCESS,
+            lpVerb: windows::core::w!("print"),
+            lpFile: PCWSTR(file.as_ptr()),
+            nShow: SW_SHOWDEFAULT.0,
+            ..Default::default()
+        };
+        if let Err(error) = unsafe { ShellExecuteExW(&mut info) }
+            .with_context(|| format!("Printing {}", path.display()))
+        {
+            let _ = tx.send(Err(error));
+            return rx;
+        }
+        // The application printing the file reads it after this returns, so wait for it to exit.
+        // There's no process to wait for when the request is handed to an application that is
+        // already running, in which case `tx` is dropped.
+        if info.hProcess.is_invalid() {
+            return rx;
+        }
+        // Handles aren't `Send`, but processes can be waited on from any thread.
+        let process = info.hProcess.0 as isize;
+        std::thread::Builder::new()
+            .name("PrintProcess".to_owned())
+            .spawn(move || {
+                let process = HANDLE(process as _);
+                unsafe {
+                    WaitForSingleObject(process, INFINITE);
+                    CloseHandle(process).log_err();
+                }
+                let _ = tx.send(Ok(()));
+            })
+            .log_err();
+        rx
+    }
+
     fn on_quit(&self, callback: Box<dyn FnMut()>) {
         self.inner.state.callbacks.quit.set(Some(callback));
     }
@@ -588,14 +641,54 @@ impl Platform for WindowsPlatform {
     }
 
     fn set_cursor_style(&self, style: CursorStyle) {
-        let hcursor = load_cursor(style);
-        if self.inner.state.current_cursor.get().map(|c| c.0) != hcursor.map(|c| c.0) {
-            self.post_message(
-                WM_GPUI_CURSOR_STYLE_CHANGED,
-                WPARAM(0),
-                LPARAM(hcursor.map_or(0, |c| c.0 as isize)),
-            );
-            self.inner.state.current_cursor.set(hcursor);
+        if self.inner.state.cursor_style.get() == style {
+            return;
+        }
+        self.inner.state.cursor_style.set(style);
+        // Custom cursors are drawn for the DPI of each window, which may be on a different display.
+        for handle in self.raw_window_handles.read().iter() {
+            let hwnd = handle.as_raw();
+            let hcursor = self
+                .inner
+                .cursor_for_dpi(style, unsafe { GetDpiForWindow(hwnd) });
+            unsafe {
+                PostMessageW(
+                    Some(hwnd),
+                    WM_GPUI_CURSOR_STYLE_CHANGED,
+                    WPARAM(0),
+                    LPARAM(hcursor.map_or(0, |c| c.0 as isize)),
+                )
+            }
+            .log_err();
+        }
+        let hcursor = self
+            .inner
+            .cursor_for_dpi(style, unsafe { GetDpiForSystem() });
+        self.inner.state.current_cursor.set(hcursor);
+    }
+
+    fn register_cursor_image(&self, id: CustomCursorId, image: CursorImage) {
+        let cursor = CustomCursor {
+            image,
+            cursors: HashMap::default(),
+        };
+        if let Some(replaced) = self
+            .inner
+            .state
+            .custom_cursors
+            .borrow_mut()
+            .insert(id, cursor)
+        {
+            replaced.destroy();
+        }
+    }
+
+    fn drop_cursor_image(&self, id: CustomCursorId) {
+        if self.inner.state.cursor_style.get() == CursorStyle::Custom(id) {
+            self.set_cursor_style(CursorStyle::Arrow);
+        }
+        if let Some(cursor) = self.inner.state.custom_cursors.borrow_mut().remove(&id) {
+            cursor.destroy();
         }
     }
 
@@ -603,6 +696,28 @@ impl Platform for WindowsPlatform {
         should_auto_hide_scrollbars().log_err().unwrap_or(false)
     }
 
+    fn power_state(&self) -> PowerState {
+        power_state().log_err().unwrap_or_default()
+    }
+
+    fn system_idle_duration(&self) -> Option<Duration> {
+        system_idle_duration().log_err()
+    }
+
+    fn caret_blink_interval(&self) -> Option<Duration> {
+        match unsafe { GetCaretBlinkTime() } {
+            // Zero means the blink time couldn't be read.
+            0 => Some(DEFAULT_CARET_BLINK_INTERVAL),
+            // INFINITE means the caret doesn't blink.
+            u32::MAX => None,
+            milliseconds => Some(Duration::from_millis(milliseconds.into())),
+        }
+    }
+
+    fn should_reduce_transparency(&self) -> bool {
+        should_reduce_transparency().log_err().unwrap_or(false)
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         write_to_clipboard(item);
     }
@@ -743,6 +858,19 @@ impl WindowsPlatformInner {
         }))
     }
 
+    fn cursor_for_dpi(&self, style: CursorStyle, dpi: u32) -> Option<HCURSOR> {
+        match style {
+            CursorStyle::Custom(id) => self
+                .state
+                .custom_cursors
+                .borrow_mut()
+                .get_mut(&id)
+                .and_then(|cursor| cursor.cursor_for_dpi(dpi))
+                .or_else(|| load_cursor(CursorStyle::Arrow)),
+            _ => load_cursor(style),
+        }
+    }
+
     /// Calls `project` to project to the corresponding callback field, removes it from callbacks, calls `f` with the callback and then puts the callback back.
     fn with_callback<T>(
         &self,
@@ -908,11 +1036,37 @@ impl WindowsPlatformInner {
         let directx_devices = unsafe { &*directx_devices };
         self.state.directx_devices.borrow_mut().take();
         *self.state.directx_devices.borrow_mut() = Some(directx_devices.clone());
+        self.with_callback(|callbacks| &callbacks.gpu_reset, |callback| callback());
 
         Some(0)
     }
 }
 
+/// A custom cursor image, along with the cursors created from it for each DPI it's shown at.
+struct CustomCursor {
+    image: CursorImage,
+    // Unlike standard cursor handles, these must be destroyed.
+    cursors: HashMap<u32, HCURSOR>,
+}
+
+impl CustomCursor {
+    fn cursor_for_dpi(&mut self, dpi: u32) -> Option<HCURSOR> {
+        if let Some(cursor) = self.cursors.get(&dpi) {
+            return Some(*cursor);
+        }
+        let scale_factor = dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32;
+        let cursor = create_cursor(&self.image, scale_factor).log_err()?;
+        self.cursors.insert(dpi, cursor);
+        Some(cursor)
+    }
+
+    fn destroy(self) {
+        for cursor in self.cursors.into_values() {
+            unsafe { DestroyCursor(cursor) }.log_err();
+        }
+    }
+}
+
 impl Drop for WindowsPlatform {
     fn drop(&mut self) {
         unsafe {
@@ -951,11 +1105,15 @@ struct PlatformWindowCreateContext {
 }
 
 fn open_target(target: impl AsRef<OsStr>) -> Result<()> {
+    shell_execute(windows::core::w!("open"), target)
+}
+
+fn shell_execute(verb: PCWSTR, target: impl AsRef<OsStr>) -> Result<()> {
     let target = target.as_ref();
     let ret = unsafe {
         ShellExecuteW(
             None,
-            windows::core::w!("open"),
+            verb,
             &HSTRING::from(target),
             None,
             None,
@@ -1133,6 +1291,31 @@ fn should_auto_hide_scrollbars() -> Result<bool> {
     Ok(ui_settings.AutoHideScrollBars()?)
 }
 
+#[inline]
+fn should_reduce_transparency() -> Result<bool> {
+    let ui_settings = UISettings::new()?;
+    Ok(!ui_settings.AdvancedEffectsEnabled()?)
+}
+
+fn power_state() -> Result<PowerState> {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }?;
+    Ok(PowerState {
+        on_battery: status.ACLineStatus == 0,
+        low_power_mode: status.SystemStatusFlag == 1,
+    })
+}
+
+fn system_idle_duration() -> Result<Duration> {
+    let mut last_input = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe { GetLastInputInfo(&mut last_input) }.ok()?;
+    let idle_milliseconds = unsafe { GetTickCount() }.wrapping_sub(last_input.dwTime);
+    Ok(Duration::from_millis(idle_milliseconds.into()))
+}
+
 fn check_device_lost(device: &ID3D11Device) -> bool {
     let device_state = unsafe { device.GetDeviceRemovedReason() };
     match device_state {
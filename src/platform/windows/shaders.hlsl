@@ -4,7 +4,11 @@ cbuffer GlobalParams: register(b0) {
     float4 gamma_ratios;
     float2 global_viewport_size;
     float grayscale_enhanced_contrast;
-    uint _pad;
+    uint _pad0;
+    // Seconds since the renderer was created, which moves the dashes of borders.
+    float global_time;
+    float light_text_enhanced_contrast;
+    uint2 _pad1;
 };
 
 Texture2D<float4> t_sprite: register(t0);
@@ -22,6 +26,11 @@ struct Corners {
     float bottom_left;
 };
 
+struct ContentMask {
+    Bounds bounds;
+    Corners corner_radii;
+};
+
 struct Edges {
     float top;
     float right;
@@ -302,6 +311,46 @@ float quad_sdf(float2 pt, Bounds bounds, Corners corner_radii) {
     return quad_sdf_impl(corner_center_to_point, corner_radius);
 }
 
+// The coverage of a point by a content mask with rounded corners. The mask's
+// bounds are clipped against separately.
+float content_mask_alpha(float2 position, ContentMask content_mask) {
+    Corners radii = content_mask.corner_radii;
+    if (max(max(radii.top_left, radii.top_right), max(radii.bottom_right, radii.bottom_left)) == 0.) {
+        return 1.;
+    }
+    return saturate(0.5 - quad_sdf(position, content_mask.bounds, radii));
+}
+
+// The coverage of a blurred rounded rectangle at the given position.
+float shadow_alpha(float2 position, Bounds bounds, Corners corner_radii, float blur_radius) {
+    if (blur_radius == 0.) {
+        return saturate(0.5 - quad_sdf(position, bounds, corner_radii));
+    }
+
+    float2 half_size = bounds.size / 2.;
+    float2 center = bounds.origin + half_size;
+    float2 point0 = position - center;
+    float corner_radius = pick_corner_radius(point0, corner_radii);
+
+    // The signal is only non-zero in a limited range, so don't waste samples
+    float low = point0.y - half_size.y;
+    float high = point0.y + half_size.y;
+    float start = clamp(-3. * blur_radius, low, high);
+    float end = clamp(3. * blur_radius, low, high);
+
+    // Accumulate samples (we can get away with surprisingly few samples)
+    float step = (end - start) / 4.;
+    float y = start + step * 0.5;
+    float alpha = 0.;
+    for (int i = 0; i < 4; i++) {
+        alpha += blur_along_x(point0.x, point0.y - y, blur_radius,
+                            corner_radius, half_size) *
+                gaussian(y, blur_radius) * step;
+        y += step;
+    }
+    return alpha;
+}
+
 GradientColor prepare_gradient_color(uint tag, uint color_space, Hsla solid, LinearColorStop colors[2]) {
     GradientColor output;
     if (tag == 0 || tag == 2) {
@@ -420,17 +469,24 @@ float corner_dash_velocity(float dv1, float dv2) {
     }
 }
 
-// Returns alpha used to render antialiased dashes.
-// `t` is within the dash when `fmod(t, period) < length`.
+// Returns alpha used to render antialiased dashes, or round dots when `dotted`.
+// `t` is within the dash when `fmod(t, period) < dash_length`. Dots are as long
+// as the border is wide, and `border_sdf` places the point across the border.
 float dash_alpha(
-    float t, float period, float length, float dash_velocity,
-    float antialias_threshold
+    float t, float period, float dash_length, float dash_velocity,
+    float border_sdf, bool dotted, float antialias_threshold
 ) {
     float half_period = period / 2.0;
-    float half_length = length / 2.0;
+    float half_length = dash_length / 2.0;
     // Value in [-half_period, half_period]
     // The dash is in [-half_length, half_length]
     float centered = fmod(t + half_period - half_length, period) - half_period;
+    if (dotted) {
+        // Signed distance to the dot, in pixels
+        float radius = half_length / dash_velocity;
+        float2 offset = float2(centered / dash_velocity, border_sdf + radius);
+        return saturate(antialias_threshold - (length(offset) - radius));
+    }
     // Signed distance for the dash, negative values are inside the dash
     float signed_distance = abs(centered) - half_length;
     // Antialiased alpha based on the signed distance
@@ -453,21 +509,67 @@ float quarter_ellipse_sdf(float2 pt, float2 radii) {
     return unit_circle_sdf * (radii.x + radii.y) * -0.5;
 }
 
+// The horizontal and vertical radii of a corner. A zero vertical radius takes
+// the horizontal radius, for circular corners, and a corner with no horizontal
+// radius is square.
+float2 elliptical_corner_radii(float horizontal, float vertical) {
+    if (horizontal == 0.0) {
+        return float2(0.0, 0.0);
+    }
+    return float2(horizontal, vertical == 0.0 ? horizontal : vertical);
+}
+
+// Selects the horizontal and vertical radii of a corner based on quadrant.
+float2 pick_corner_radii(float2 center_to_point, Corners horizontal, Corners vertical) {
+    return elliptical_corner_radii(pick_corner_radius(center_to_point, horizontal),
+                                   pick_corner_radius(center_to_point, vertical));
+}
+
+// The mean of a corner's horizontal and vertical radii.
+float mean_radius(float2 corner_radii) {
+    return (corner_radii.x + corner_radii.y) / 2.0;
+}
+
+// Like `quad_sdf_impl`, for corners that may be elliptical. The distance to
+// elliptical corners is approximate.
+float elliptical_quad_sdf_impl(float2 corner_center_to_point, float2 corner_radii) {
+    if (corner_radii.x == corner_radii.y) {
+        return quad_sdf_impl(corner_center_to_point, corner_radii.x);
+    }
+    if (corner_center_to_point.x <= 0.0 || corner_center_to_point.y <= 0.0) {
+        float2 corner_to_point = corner_center_to_point - corner_radii;
+        return max(corner_to_point.x, corner_to_point.y);
+    }
+    return -quarter_ellipse_sdf(corner_center_to_point, corner_radii);
+}
+
 /*
 **
 **              Quads
 **
 */
 
+struct QuadShadow {
+    Bounds bounds;
+    Hsla color;
+    float blur_radius;
+    uint pad;
+};
+
 struct Quad {
     uint order;
     uint border_style;
     Bounds bounds;
-    Bounds content_mask;
+    ContentMask content_mask;
     Background background;
     Hsla border_color;
     Corners corner_radii;
+    Corners vertical_corner_radii;
     Edges border_widths;
+    QuadShadow shadow;
+    TransformationMatrix transformation;
+    float dash_speed;
+    uint pad;
 };
 
 struct QuadVertexOutput {
@@ -477,6 +579,8 @@ struct QuadVertexOutput {
     nointerpolation float4 background_solid: COLOR1;
     nointerpolation float4 background_color0: COLOR2;
     nointerpolation float4 background_color1: COLOR3;
+    nointerpolation float4 shadow_color: COLOR4;
+    float2 local_position: TEXCOORD1;
     float4 clip_distance: SV_ClipDistance;
 };
 
@@ -487,6 +591,8 @@ struct QuadFragmentInput {
     nointerpolation float4 background_solid: COLOR1;
     nointerpolation float4 background_color0: COLOR2;
     nointerpolation float4 background_color1: COLOR3;
+    nointerpolation float4 shadow_color: COLOR4;
+    float2 local_position: TEXCOORD1;
 };
 
 StructuredBuffer<Quad> quads: register(t1);
@@ -494,7 +600,22 @@ StructuredBuffer<Quad> quads: register(t1);
 QuadVertexOutput quad_vertex(uint vertex_id: SV_VertexID, uint quad_id: SV_InstanceID) {
     float2 unit_vertex = float2(float(vertex_id & 1u), 0.5 * float(vertex_id & 2u));
     Quad quad = quads[quad_id];
-    float4 device_position = to_device_position(unit_vertex, quad.bounds);
+    float4 shadow_color = hsla_to_rgba(quad.shadow.color);
+
+    // Grow the drawn bounds to cover the quad's shadow, including its blur
+    Bounds bounds = quad.bounds;
+    if (shadow_color.a > 0.) {
+        float margin = 3. * quad.shadow.blur_radius;
+        float2 shadow_min = quad.shadow.bounds.origin - margin;
+        float2 shadow_max = quad.shadow.bounds.origin + quad.shadow.bounds.size + margin;
+        float2 bounds_min = min(quad.bounds.origin, shadow_min);
+        float2 bounds_max = max(quad.bounds.origin + quad.bounds.size, shadow_max);
+        bounds.origin = bounds_min;
+        bounds.size = bounds_max - bounds_min;
+    }
+
+    float4 device_position = to_device_position_transformed(unit_vertex, bounds, quad.transformation);
+    float2 local_position = unit_vertex * bounds.size + bounds.origin;
 
     GradientColor gradient = prepare_gradient_color(
         quad.background.tag,
@@ -502,23 +623,26 @@ QuadVertexOutput quad_vertex(uint vertex_id: SV_VertexID, uint quad_id: SV_Insta
         quad.background.solid,
         quad.background.colors
     );
-    float4 clip_distance = distance_from_clip_rect(unit_vertex, quad.bounds, quad.content_mask);
+    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, bounds, quad.content_mask.bounds, quad.transformation);
     float4 border_color = hsla_to_rgba(quad.border_color);
 
     QuadVertexOutput output;
     output.position = device_position;
     output.border_color = border_color;
+    output.shadow_color = shadow_color;
     output.quad_id = quad_id;
     output.background_solid = gradient.solid;
     output.background_color0 = gradient.color0;
     output.background_color1 = gradient.color1;
+    output.local_position = local_position;
     output.clip_distance = clip_distance;
     return output;
 }
 
-float4 quad_fragment(QuadFragmentInput input): SV_Target {
-    Quad quad = quads[input.quad_id];
-    float4 background_color = gradient_color(quad.background, input.position.xy, quad.bounds,
+// The color of the quad's background and border at the fragment's position,
+// which must be within the quad's bounds.
+float4 quad_color(QuadFragmentInput input, Quad quad) {
+    float4 background_color = gradient_color(quad.background, input.local_position, quad.bounds,
     input.background_solid, input.background_color0, input.background_color1);
 
     bool unrounded = quad.corner_radii.top_left == 0.0 &&
@@ -537,15 +661,15 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
 
     float2 size = quad.bounds.size;
     float2 half_size = size / 2.;
-    float2 the_point = input.position.xy - quad.bounds.origin;
+    float2 the_point = input.local_position - quad.bounds.origin;
     float2 center_to_point = the_point - half_size;
 
     // Signed distance field threshold for inclusion of pixels. 0.5 is the
     // minimum distance between the center of the pixel and the edge.
     const float antialias_threshold = 0.5;
 
-    // Radius of the nearest corner
-    float corner_radius = pick_corner_radius(center_to_point, quad.corner_radii);
+    // Horizontal and vertical radii of the nearest corner
+    float2 corner_radii = pick_corner_radii(center_to_point, quad.corner_radii, quad.vertical_corner_radii);
 
     float2 border = float2(
         center_to_point.x < 0.0 ? quad.border_widths.left : quad.border_widths.right,
@@ -563,9 +687,9 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
     // the point into the bottom right quadrant. Both components are <= 0.
     float2 corner_to_point = abs(center_to_point) - half_size;
 
-    // Vector from the point to the center of the rounded corner's circle, also
+    // Vector from the point to the center of the rounded corner's ellipse, also
     // mirrored into bottom right quadrant.
-    float2 corner_center_to_point = corner_to_point + corner_radius;
+    float2 corner_center_to_point = corner_to_point + corner_radii;
 
     // Whether the nearest point on the border is rounded
     bool is_near_rounded_corner =
@@ -595,7 +719,7 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
     }
 
     // Signed distance of the point to the outside edge of the quad's border
-    float outer_sdf = quad_sdf_impl(corner_center_to_point, corner_radius);
+    float outer_sdf = elliptical_quad_sdf_impl(corner_center_to_point, corner_radii);
 
     // Approximate signed distance of the point to the inside edge of the quad's
     // border. It is negative outside this edge (within the border), and
@@ -613,11 +737,11 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
     } else if (is_beyond_inner_straight_border) {
         // Fast path for points that must be outside the inner edge
         inner_sdf = -1.0;
-    } else if (reduced_border.x == reduced_border.y) {
+    } else if (reduced_border.x == reduced_border.y && corner_radii.x == corner_radii.y) {
         // Fast path for circular inner edge.
         inner_sdf = -(outer_sdf + reduced_border.x);
     } else {
-        float2 ellipse_radii = max(float2(0.0, 0.0), float2(corner_radius, corner_radius) - reduced_border);
+        float2 ellipse_radii = max(float2(0.0, 0.0), corner_radii - reduced_border);
         inner_sdf = quarter_ellipse_sdf(corner_center_to_point, ellipse_radii);
     }
 
@@ -627,8 +751,13 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
     float4 color = background_color;
     if (border_sdf < antialias_threshold) {
         float4 border_color = input.border_color;
-        // Dashed border logic when border_style == 1
-        if (quad.border_style == 1) {
+        // Dashed and dotted border logic when border_style is 1 or 2
+        if (quad.border_style != 0) {
+            bool dotted = quad.border_style == 2;
+            // Moving dashes are laid out around the whole perimeter so that
+            // they can travel around the corners.
+            bool dashes_per_side = unrounded && quad.dash_speed == 0.0;
+
             // Position along the perimeter in "dash space", where each dash
             // period has length 1
             float t = 0.0;
@@ -641,10 +770,11 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
             // used by browsers, but also avoids dashes from different segments
             // overlapping when dash size is smaller than the border width.
             //
-            // Dash pattern: (2 * border width) dash, (1 * border width) gap
-            const float dash_length_per_width = 2.0;
+            // Dash pattern: (2 * border width) dash, (1 * border width) gap.
+            // Dots are round, one border width across, with the same gap.
+            float dash_length_per_width = dotted ? 1.0 : 2.0;
             const float dash_gap_per_width = 1.0;
-            const float dash_period_per_width = dash_length_per_width + dash_gap_per_width;
+            float dash_period_per_width = dash_length_per_width + dash_gap_per_width;
 
             // Since the dash size is determined by border width, the density of
             // dashes varies. Multiplying a pixel distance by this returns a
@@ -653,9 +783,9 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
             float dash_velocity = 0.0;
 
             // Dividing this by the border width gives the dash velocity
-            const float dv_numerator = 1.0 / dash_period_per_width;
+            float dv_numerator = 1.0 / dash_period_per_width;
 
-            if (unrounded) {
+            if (dashes_per_side) {
                 // When corners aren't rounded, the dashes are separately laid
                 // out on each straight line, rather than around the whole
                 // perimeter. This way each line starts and ends with a dash.
@@ -677,10 +807,14 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
                 // When corners are rounded, the dashes are laid out clockwise
                 // around the whole perimeter.
 
-                float r_tr = quad.corner_radii.top_right;
-                float r_br = quad.corner_radii.bottom_right;
-                float r_bl = quad.corner_radii.bottom_left;
-                float r_tl = quad.corner_radii.top_left;
+                float2 r_tr = elliptical_corner_radii(quad.corner_radii.top_right,
+                                                      quad.vertical_corner_radii.top_right);
+                float2 r_br = elliptical_corner_radii(quad.corner_radii.bottom_right,
+                                                      quad.vertical_corner_radii.bottom_right);
+                float2 r_bl = elliptical_corner_radii(quad.corner_radii.bottom_left,
+                                                      quad.vertical_corner_radii.bottom_left);
+                float2 r_tl = elliptical_corner_radii(quad.corner_radii.top_left,
+                                                      quad.vertical_corner_radii.top_left);
 
                 float w_t = quad.border_widths.top;
                 float w_r = quad.border_widths.right;
@@ -694,21 +828,22 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
                 float dv_l = w_l <= 0.0 ? 0.0 : dv_numerator / w_l;
 
                 // Straight side lengths in dash space
-                float s_t = (size.x - r_tl - r_tr) * dv_t;
-                float s_r = (size.y - r_tr - r_br) * dv_r;
-                float s_b = (size.x - r_br - r_bl) * dv_b;
-                float s_l = (size.y - r_bl - r_tl) * dv_l;
+                float s_t = (size.x - r_tl.x - r_tr.x) * dv_t;
+                float s_r = (size.y - r_tr.y - r_br.y) * dv_r;
+                float s_b = (size.x - r_br.x - r_bl.x) * dv_b;
+                float s_l = (size.y - r_bl.y - r_tl.y) * dv_l;
 
                 float corner_dash_velocity_tr = corner_dash_velocity(dv_t, dv_r);
                 float corner_dash_velocity_br = corner_dash_velocity(dv_b, dv_r);
                 float corner_dash_velocity_bl = corner_dash_velocity(dv_b, dv_l);
                 float corner_dash_velocity_tl = corner_dash_velocity(dv_t, dv_l);
 
-                // Corner lengths in dash space
-                float c_tr = r_tr * (M_PI_F / 2.0) * corner_dash_velocity_tr;
-                float c_br = r_br * (M_PI_F / 2.0) * corner_dash_velocity_br;
-                float c_bl = r_bl * (M_PI_F / 2.0) * corner_dash_velocity_bl;
-                float c_tl = r_tl * (M_PI_F / 2.0) * corner_dash_velocity_tl;
+                // Corner lengths in dash space. Elliptical corners are
+                // measured as circles of their mean radius.
+                float c_tr = mean_radius(r_tr) * (M_PI_F / 2.0) * corner_dash_velocity_tr;
+                float c_br = mean_radius(r_br) * (M_PI_F / 2.0) * corner_dash_velocity_br;
+                float c_bl = mean_radius(r_bl) * (M_PI_F / 2.0) * corner_dash_velocity_bl;
+                float c_tl = mean_radius(r_tl) * (M_PI_F / 2.0) * corner_dash_velocity_tl;
 
                 // Cumulative dash space upto each segment
                 float upto_tr = s_t;
@@ -721,8 +856,11 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
                 max_t = upto_tl + c_tl;
 
                 if (is_near_rounded_corner) {
-                    float radians = atan2(corner_center_to_point.y, corner_center_to_point.x);
-                    float corner_t = radians * corner_radius;
+                    // The angle of the point on a unit circle, once the
+                    // ellipse is scaled into one.
+                    float radians = atan2(corner_center_to_point.y * corner_radii.x,
+                                          corner_center_to_point.x * corner_radii.y);
+                    float corner_t = radians * mean_radius(corner_radii);
 
                     if (center_to_point.x >= 0.0) {
                         if (center_to_point.y < 0.0) {
@@ -758,18 +896,18 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
                     if (is_horizontal) {
                         if (center_to_point.y < 0.0) {
                             dash_velocity = dv_t;
-                            t = (the_point.x - r_tl) * dash_velocity;
+                            t = (the_point.x - r_tl.x) * dash_velocity;
                         } else {
                             dash_velocity = dv_b;
-                            t = upto_bl - (the_point.x - r_bl) * dash_velocity;
+                            t = upto_bl - (the_point.x - r_bl.x) * dash_velocity;
                         }
                     } else {
                         if (center_to_point.x < 0.0) {
                             dash_velocity = dv_l;
-                            t = upto_tl - (the_point.y - r_tl) * dash_velocity;
+                            t = upto_tl - (the_point.y - r_tl.y) * dash_velocity;
                         } else {
                             dash_velocity = dv_r;
-                            t = upto_r + (the_point.y - r_tr) * dash_velocity;
+                            t = upto_r + (the_point.y - r_tr.y) * dash_velocity;
                         }
                     }
                 }
@@ -779,20 +917,23 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
 
             // Straight borders should start and end with a dash, so max_t is
             // reduced to cause this.
-            max_t -= unrounded ? dash_length : 0.0;
+            max_t -= dashes_per_side ? dash_length : 0.0;
             if (max_t >= 1.0) {
                 // Adjust dash gap to evenly divide max_t
                 float dash_count = floor(max_t);
                 float dash_period = max_t / dash_count;
-                border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity, antialias_threshold);
-            } else if (unrounded) {
+                // Move the dashes clockwise by dash_speed periods per second.
+                // The offset is kept positive for the modulo.
+                t += dash_period * (1.0 - frac(quad.dash_speed * global_time));
+                border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity, border_sdf, dotted, antialias_threshold);
+            } else if (dashes_per_side) {
                 // When there isn't enough space for the full gap between the
                 // two start / end dashes of a straight border, reduce gap to
                 // make them fit.
                 float dash_gap = max_t - dash_length;
                 if (dash_gap > 0.0) {
                     float dash_period = dash_length + dash_gap;
-                    border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity, antialias_threshold);
+                    border_color.a *= dash_alpha(t, dash_period, dash_length, dash_velocity, border_sdf, dotted, antialias_threshold);
                 }
             }
         }
@@ -807,6 +948,27 @@ float4 quad_fragment(QuadFragmentInput input): SV_Target {
     return color * float4(1.0, 1.0, 1.0, saturate(antialias_threshold - outer_sdf));
 }
 
+float4 quad_fragment(QuadFragmentInput input): SV_Target {
+    Quad quad = quads[input.quad_id];
+    float4 mask = float4(1., 1., 1., content_mask_alpha(input.position.xy, quad.content_mask));
+    if (input.shadow_color.a == 0.) {
+        return quad_color(input, quad) * mask;
+    }
+
+    // The quad is drawn over its shadow, which extends past the quad's bounds
+    float2 position = input.local_position;
+    float4 color = float4(0., 0., 0., 0.);
+    if (all(position >= quad.bounds.origin) && all(position < quad.bounds.origin + quad.bounds.size)) {
+        color = quad_color(input, quad);
+    }
+    float alpha = shadow_alpha(position, quad.shadow.bounds, quad.corner_radii, quad.shadow.blur_radius);
+    float4 shadow_color = input.shadow_color * float4(1., 1., 1., alpha);
+    if (shadow_color.a <= 0.) {
+        return color * mask;
+    }
+    return over(shadow_color, color) * mask;
+}
+
 /*
 **
 **              Shadows
@@ -818,14 +980,16 @@ struct Shadow {
     float blur_radius;
     Bounds bounds;
     Corners corner_radii;
-    Bounds content_mask;
+    ContentMask content_mask;
     Hsla color;
+    TransformationMatrix transformation;
 };
 
 struct ShadowVertexOutput {
     nointerpolation uint shadow_id: TEXCOORD0;
     float4 position: SV_Position;
     nointerpolation float4 color: COLOR;
+    float2 local_position: TEXCOORD1;
     float4 clip_distance: SV_ClipDistance;
 };
 
@@ -833,6 +997,7 @@ struct ShadowFragmentInput {
   nointerpolation uint shadow_id: TEXCOORD0;
   float4 position: SV_Position;
   nointerpolation float4 color: COLOR;
+  float2 local_position: TEXCOORD1;
 };
 
 StructuredBuffer<Shadow> shadows: register(t1);
@@ -846,14 +1011,15 @@ ShadowVertexOutput shadow_vertex(uint vertex_id: SV_VertexID, uint shadow_id: SV
     bounds.origin -= margin;
     bounds.size += 2.0 * margin;
 
-    float4 device_position = to_device_position(unit_vertex, bounds);
-    float4 clip_distance = distance_from_clip_rect(unit_vertex, bounds, shadow.content_mask);
+    float4 device_position = to_device_position_transformed(unit_vertex, bounds, shadow.transformation);
+    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, bounds, shadow.content_mask.bounds, shadow.transformation);
     float4 color = hsla_to_rgba(shadow.color);
 
     ShadowVertexOutput output;
     output.position = device_position;
     output.color = color;
     output.shadow_id = shadow_id;
+    output.local_position = unit_vertex * bounds.size + bounds.origin;
     output.clip_distance = clip_distance;
 
     return output;
@@ -861,29 +1027,8 @@ ShadowVertexOutput shadow_vertex(uint vertex_id: SV_VertexID, uint shadow_id: SV
 
 float4 shadow_fragment(ShadowFragmentInput input): SV_TARGET {
     Shadow shadow = shadows[input.shadow_id];
-
-    float2 half_size = shadow.bounds.size / 2.;
-    float2 center = shadow.bounds.origin + half_size;
-    float2 point0 = input.position.xy - center;
-    float corner_radius = pick_corner_radius(point0, shadow.corner_radii);
-
-    // The signal is only non-zero in a limited range, so don't waste samples
-    float low = point0.y - half_size.y;
-    float high = point0.y + half_size.y;
-    float start = clamp(-3. * shadow.blur_radius, low, high);
-    float end = clamp(3. * shadow.blur_radius, low, high);
-
-    // Accumulate samples (we can get away with surprisingly few samples)
-    float step = (end - start) / 4.;
-    float y = start + step * 0.5;
-    float alpha = 0.;
-    for (int i = 0; i < 4; i++) {
-        alpha += blur_along_x(point0.x, point0.y - y, shadow.blur_radius,
-                            corner_radius, half_size) *
-                gaussian(y, shadow.blur_radius) * step;
-        y += step;
-    }
-
+    float alpha = shadow_alpha(input.local_position, shadow.bounds, shadow.corner_radii, shadow.blur_radius);
+    alpha *= content_mask_alpha(input.position.xy, shadow.content_mask);
     return input.color * float4(1., 1., 1., alpha);
 }
 
@@ -898,6 +1043,7 @@ struct PathRasterizationSprite {
     float2 st_position;
     Background color;
     Bounds bounds;
+    ContentMask content_mask;
 };
 
 StructuredBuffer<PathRasterizationSprite> path_rasterization_sprites: register(t1);
@@ -944,6 +1090,7 @@ float4 path_rasterization_fragment(PathFragmentInput input): SV_Target {
         float distance = f / length(gradient);
         alpha = saturate(0.5 - distance);
     }
+    alpha *= content_mask_alpha(input.position.xy, sprite.content_mask);
 
     GradientColor gradient = prepare_gradient_color(
         background.tag, background.color_space, background.solid, background.colors);
@@ -1000,16 +1147,20 @@ struct Underline {
     uint order;
     uint pad;
     Bounds bounds;
-    Bounds content_mask;
+    ContentMask content_mask;
     Hsla color;
     float thickness;
     uint wavy;
+    float wave_amplitude;
+    float wavelength;
+    TransformationMatrix transformation;
 };
 
 struct UnderlineVertexOutput {
   nointerpolation uint underline_id: TEXCOORD0;
   float4 position: SV_Position;
   nointerpolation float4 color: COLOR;
+  float2 local_position: TEXCOORD1;
   float4 clip_distance: SV_ClipDistance;
 };
 
@@ -1017,6 +1168,7 @@ struct UnderlineFragmentInput {
   nointerpolation uint underline_id: TEXCOORD0;
   float4 position: SV_Position;
   nointerpolation float4 color: COLOR;
+  float2 local_position: TEXCOORD1;
 };
 
 StructuredBuffer<Underline> underlines: register(t1);
@@ -1024,31 +1176,32 @@ StructuredBuffer<Underline> underlines: register(t1);
 UnderlineVertexOutput underline_vertex(uint vertex_id: SV_VertexID, uint underline_id: SV_InstanceID) {
     float2 unit_vertex = float2(float(vertex_id & 1u), 0.5 * float(vertex_id & 2u));
     Underline underline = underlines[underline_id];
-    float4 device_position = to_device_position(unit_vertex, underline.bounds);
-    float4 clip_distance = distance_from_clip_rect(unit_vertex, underline.bounds,
-                                                    underline.content_mask);
+    float4 device_position = to_device_position_transformed(unit_vertex, underline.bounds,
+                                                            underline.transformation);
+    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, underline.bounds,
+                                                               underline.content_mask.bounds,
+                                                               underline.transformation);
     float4 color = hsla_to_rgba(underline.color);
 
     UnderlineVertexOutput output;
     output.position = device_position;
     output.color = color;
     output.underline_id = underline_id;
+    output.local_position = unit_vertex * underline.bounds.size + underline.bounds.origin;
     output.clip_distance = clip_distance;
     return output;
 }
 
 float4 underline_fragment(UnderlineFragmentInput input): SV_Target {
-    const float WAVE_FREQUENCY = 2.0;
-    const float WAVE_HEIGHT_RATIO = 0.8;
-
     Underline underline = underlines[input.underline_id];
+    float mask_alpha = content_mask_alpha(input.position.xy, underline.content_mask);
     if (underline.wavy) {
         float half_thickness = underline.thickness * 0.5;
         float2 origin = underline.bounds.origin;
 
-        float2 st = ((input.position.xy - origin) / underline.bounds.size.y) - float2(0., 0.5);
-        float frequency = (M_PI_F * WAVE_FREQUENCY * underline.thickness) / underline.bounds.size.y;
-        float amplitude = (underline.thickness * WAVE_HEIGHT_RATIO) / underline.bounds.size.y;
+        float2 st = ((input.local_position - origin) / underline.bounds.size.y) - float2(0., 0.5);
+        float frequency = (2. * M_PI_F * underline.bounds.size.y) / max(underline.wavelength, 0.001);
+        float amplitude = underline.wave_amplitude / underline.bounds.size.y;
 
         float sine = sin(st.x * frequency) * amplitude;
         float dSine = cos(st.x * frequency) * amplitude * frequency;
@@ -1058,12 +1211,79 @@ float4 underline_fragment(UnderlineFragmentInput input): SV_Target {
         float distance_from_bottom_border = distance_in_pixels + half_thickness;
         float alpha = saturate(
             0.5 - max(-distance_from_bottom_border, distance_from_top_border));
-        return input.color * float4(1., 1., 1., alpha);
+        return input.color * float4(1., 1., 1., alpha * mask_alpha);
     } else {
-        return input.color;
+        return input.color * float4(1., 1., 1., mask_alpha);
     }
 }
 
+/*
+**
+**              Tiled quads
+**
+*/
+
+struct TiledQuad {
+    uint order;
+    float corner_radius;
+    Bounds bounds;
+    ContentMask content_mask;
+    float2 tile_size;
+    Bounds quad_bounds;
+    Hsla color;
+    Hsla alternate_color;
+    TransformationMatrix transformation;
+};
+
+struct TiledQuadVertexOutput {
+  nointerpolation uint quad_id: TEXCOORD0;
+  float4 position: SV_Position;
+  nointerpolation float4 color: COLOR0;
+  nointerpolation float4 alternate_color: COLOR1;
+  // The position before the quad's transformation, which the tiles are laid out in.
+  float2 local_position: TEXCOORD1;
+  float4 clip_distance: SV_ClipDistance;
+};
+
+struct TiledQuadFragmentInput {
+  nointerpolation uint quad_id: TEXCOORD0;
+  float4 position: SV_Position;
+  nointerpolation float4 color: COLOR0;
+  nointerpolation float4 alternate_color: COLOR1;
+  float2 local_position: TEXCOORD1;
+};
+
+StructuredBuffer<TiledQuad> tiled_quads: register(t1);
+
+TiledQuadVertexOutput tiled_quad_vertex(uint vertex_id: SV_VertexID, uint quad_id: SV_InstanceID) {
+    float2 unit_vertex = float2(float(vertex_id & 1u), 0.5 * float(vertex_id & 2u));
+    TiledQuad quad = tiled_quads[quad_id];
+
+    TiledQuadVertexOutput output;
+    output.position = to_device_position_transformed(unit_vertex, quad.bounds, quad.transformation);
+    output.color = hsla_to_rgba(quad.color);
+    output.alternate_color = hsla_to_rgba(quad.alternate_color);
+    output.quad_id = quad_id;
+    output.local_position = unit_vertex * quad.bounds.size + quad.bounds.origin;
+    output.clip_distance = distance_from_clip_rect_transformed(unit_vertex, quad.bounds, quad.content_mask.bounds, quad.transformation);
+    return output;
+}
+
+float4 tiled_quad_fragment(TiledQuadFragmentInput input): SV_Target {
+    TiledQuad quad = tiled_quads[input.quad_id];
+    float2 tile_size = max(quad.tile_size, float2(0.001, 0.001));
+    float2 position = input.local_position - quad.bounds.origin;
+    float2 tile = floor(position / tile_size);
+    float2 tile_position = position - tile * tile_size;
+    Corners corner_radii = { quad.corner_radius, quad.corner_radius, quad.corner_radius, quad.corner_radius };
+    float alpha = saturate(0.5 - quad_sdf(tile_position, quad.quad_bounds, corner_radii));
+    alpha *= content_mask_alpha(input.position.xy, quad.content_mask);
+    // Tiles alternate colors like the squares of a checkerboard.
+    bool alternate = (int(tile.x) + int(tile.y)) % 2 != 0;
+    float4 color = alternate ? input.alternate_color : input.color;
+    return color * float4(1., 1., 1., alpha);
+}
+
 /*
 **
 **              Monochrome sprites
@@ -1072,9 +1292,9 @@ float4 underline_fragment(UnderlineFragmentInput input): SV_Target {
 
 struct MonochromeSprite {
     uint order;
-    uint pad;
+    uint distance_field;
     Bounds bounds;
-    Bounds content_mask;
+    ContentMask content_mask;
     Hsla color;
     AtlasTile tile;
     TransformationMatrix transformation;
@@ -1084,6 +1304,8 @@ struct MonochromeSpriteVertexOutput {
     float4 position: SV_Position;
     float2 tile_position: POSITION;
     nointerpolation float4 color: COLOR;
+    nointerpolation uint distance_field: DISTANCE_FIELD;
+    nointerpolation uint sprite_id: SPRITE_ID;
     float4 clip_distance: SV_ClipDistance;
 };
 
@@ -1091,6 +1313,8 @@ struct MonochromeSpriteFragmentInput {
     float4 position: SV_Position;
     float2 tile_position: POSITION;
     nointerpolation float4 color: COLOR;
+    nointerpolation uint distance_field: DISTANCE_FIELD;
+    nointerpolation uint sprite_id: SPRITE_ID;
     float4 clip_distance: SV_ClipDistance;
 };
 
@@ -1101,7 +1325,7 @@ MonochromeSpriteVertexOutput monochrome_sprite_vertex(uint vertex_id: SV_VertexI
     MonochromeSprite sprite = mono_sprites[sprite_id];
     float4 device_position =
         to_device_position_transformed(unit_vertex, sprite.bounds, sprite.transformation);
-    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds, sprite.content_mask, sprite.transformation);
+    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds, sprite.content_mask.bounds, sprite.transformation);
     float2 tile_position = to_tile_position(unit_vertex, sprite.tile);
     float4 color = hsla_to_rgba(sprite.color);
 
@@ -1109,14 +1333,38 @@ MonochromeSpriteVertexOutput monochrome_sprite_vertex(uint vertex_id: SV_VertexI
     output.position = device_position;
     output.tile_position = tile_position;
     output.color = color;
+    output.distance_field = sprite.distance_field;
+    output.sprite_id = sprite_id;
     output.clip_distance = clip_distance;
     return output;
 }
 
+// The distance field value of sprites whose tile holds coverage that isn't corrected.
+static const uint LINEAR_COVERAGE = 0xffffffffu;
+
+// Returns the coverage of a signed distance field sample, given how much the sample changes
+// across a pixel and the packed edge value and falloff width of the field.
+float distance_field_alpha(float sample, float sample_width, uint distance_field) {
+    float edge = float(distance_field & 0xffffu) / 65535.0;
+    float falloff = max(float(distance_field >> 16u) / 65535.0, sample_width * 0.5);
+    return smoothstep(edge - falloff, edge + falloff, sample);
+}
+
 float4 monochrome_sprite_fragment(MonochromeSpriteFragmentInput input): SV_Target {
     float sample = t_sprite.Sample(s_sprite, input.tile_position).r;
-    float alpha_corrected = apply_contrast_and_gamma_correction(sample, input.color.rgb, grayscale_enhanced_contrast, gamma_ratios);
-    return float4(input.color.rgb, input.color.a * alpha_corrected);
+    float sample_width = fwidth(sample);
+    float alpha_corrected;
+    if (input.distance_field == LINEAR_COVERAGE) {
+        alpha_corrected = sample;
+    } else if (input.distance_field != 0u) {
+        alpha_corrected = distance_field_alpha(sample, sample_width, input.distance_field);
+    } else {
+        alpha_corrected = apply_contrast_and_gamma_correction(sample, input.color.rgb, grayscale_enhanced_contrast, gamma_ratios);
+        alpha_corrected = enhance_contrast(alpha_corrected, light_text_contrast(light_text_enhanced_contrast, input.color.rgb));
+    }
+    MonochromeSprite sprite = mono_sprites[input.sprite_id];
+    float mask_alpha = content_mask_alpha(input.position.xy, sprite.content_mask);
+    return float4(input.color.rgb, input.color.a * alpha_corrected * mask_alpha);
 }
 
 /*
@@ -1127,19 +1375,21 @@ float4 monochrome_sprite_fragment(MonochromeSpriteFragmentInput input): SV_Targe
 
 struct PolychromeSprite {
     uint order;
-    uint pad;
+    uint nearest;
     uint grayscale;
     float opacity;
     Bounds bounds;
-    Bounds content_mask;
+    ContentMask content_mask;
     Corners corner_radii;
     AtlasTile tile;
+    TransformationMatrix transformation;
 };
 
 struct PolychromeSpriteVertexOutput {
     nointerpolation uint sprite_id: TEXCOORD0;
     float4 position: SV_Position;
     float2 tile_position: POSITION;
+    float2 local_position: TEXCOORD1;
     float4 clip_distance: SV_ClipDistance;
 };
 
@@ -1147,6 +1397,7 @@ struct PolychromeSpriteFragmentInput {
     nointerpolation uint sprite_id: TEXCOORD0;
     float4 position: SV_Position;
     float2 tile_position: POSITION;
+    float2 local_position: TEXCOORD1;
 };
 
 StructuredBuffer<PolychromeSprite> poly_sprites: register(t1);
@@ -1154,15 +1405,18 @@ StructuredBuffer<PolychromeSprite> poly_sprites: register(t1);
 PolychromeSpriteVertexOutput polychrome_sprite_vertex(uint vertex_id: SV_VertexID, uint sprite_id: SV_InstanceID) {
     float2 unit_vertex = float2(float(vertex_id & 1u), 0.5 * float(vertex_id & 2u));
     PolychromeSprite sprite = poly_sprites[sprite_id];
-    float4 device_position = to_device_position(unit_vertex, sprite.bounds);
-    float4 clip_distance = distance_from_clip_rect(unit_vertex, sprite.bounds,
-                                                    sprite.content_mask);
+    float4 device_position = to_device_position_transformed(unit_vertex, sprite.bounds,
+                                                            sprite.transformation);
+    float4 clip_distance = distance_from_clip_rect_transformed(unit_vertex, sprite.bounds,
+                                                               sprite.content_mask.bounds,
+                                                               sprite.transformation);
     float2 tile_position = to_tile_position(unit_vertex, sprite.tile);
 
     PolychromeSpriteVertexOutput output;
     output.position = device_position;
     output.tile_position = tile_position;
     output.sprite_id = sprite_id;
+    output.local_position = unit_vertex * sprite.bounds.size + sprite.bounds.origin;
     output.clip_distance = clip_distance;
     return output;
 }
@@ -1170,7 +1424,15 @@ PolychromeSpriteVertexOutput polychrome_sprite_vertex(uint vertex_id: SV_VertexI
 float4 polychrome_sprite_fragment(PolychromeSpriteFragmentInput input): SV_Target {
     PolychromeSprite sprite = poly_sprites[input.sprite_id];
     float4 sample = t_sprite.Sample(s_sprite, input.tile_position);
-    float distance = quad_sdf(input.position.xy, sprite.bounds, sprite.corner_radii);
+    if (sprite.nearest != 0u) {
+        float2 atlas_size;
+        t_sprite.GetDimensions(atlas_size.x, atlas_size.y);
+        int2 texel = clamp(int2(floor(input.tile_position * atlas_size)),
+                           sprite.tile.bounds.origin,
+                           sprite.tile.bounds.origin + sprite.tile.bounds.size - 1);
+        sample = t_sprite.Load(int3(texel, 0));
+    }
+    float distance = quad_sdf(input.local_position, sprite.bounds, sprite.corner_radii);
 
     float4 color = sample;
     if ((sprite.grayscale & 0xFFu) != 0u) {
@@ -1178,5 +1440,6 @@ float4 polychrome_sprite_fragment(PolychromeSpriteFragmentInput input): SV_Targe
         color = float4(grayscale, sample.a);
     }
     color.a *= sprite.opacity * saturate(0.5 - distance);
+    color.a *= content_mask_alpha(input.position.xy, sprite.content_mask);
     return color;
 }
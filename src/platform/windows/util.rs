@@ -9,7 +9,12 @@ use windows::{
     },
     Wdk::System::SystemServices::RtlGetVersion,
     Win32::{
-        Foundation::*, Graphics::Dwm::*, System::LibraryLoader::LoadLibraryA,
+        Foundation::*,
+        Graphics::{
+            Dwm::*,
+            Gdi::{CreateBitmap, DeleteObject},
+        },
+        System::LibraryLoader::LoadLibraryA,
         UI::WindowsAndMessaging::*,
     },
     core::{BOOL, HSTRING, PCSTR},
@@ -147,6 +152,36 @@ pub(crate) fn load_cursor(style: CursorStyle) -> Option<HCURSOR> {
     )
 }
 
+/// Creates a cursor showing the variant of `image` for `scale_factor`. The cursor must be
+/// destroyed with `DestroyCursor` once it's no longer used.
+pub(crate) fn create_cursor(image: &CursorImage, scale_factor: f32) -> anyhow::Result<HCURSOR> {
+    let (variant_scale_factor, variant) = image
+        .variant_for_scale_factor(scale_factor)
+        .context("cursor image has no variants")?;
+    let bytes = variant
+        .as_bytes(0)
+        .context("cursor image variant has no frames")?;
+    let size = variant.size(0);
+    let (width, height) = (size.width.0, size.height.0);
+    // The mask is ignored where the color bitmap has an alpha channel, but must be present. Its
+    // rows are padded to 16 bits.
+    let mask = vec![0xffu8; (width as usize).div_ceil(16) * 2 * height as usize];
+    unsafe {
+        let color_bitmap = CreateBitmap(width, height, 1, 32, Some(bytes.as_ptr().cast()));
+        let mask_bitmap = CreateBitmap(width, height, 1, 1, Some(mask.as_ptr().cast()));
+        let icon = CreateIconIndirect(&ICONINFO {
+            fIcon: false.into(),
+            xHotspot: (image.hotspot.x.0 * variant_scale_factor).round().max(0.) as u32,
+            yHotspot: (image.hotspot.y.0 * variant_scale_factor).round().max(0.) as u32,
+            hbmMask: mask_bitmap,
+            hbmColor: color_bitmap,
+        });
+        DeleteObject(color_bitmap.into()).ok().log_err();
+        DeleteObject(mask_bitmap.into()).ok().log_err();
+        Ok(HCURSOR(icon.context("failed to create cursor")?.0))
+    }
+}
+
 /// This function is used to configure the dark mode for the window built-in title bar.
 pub(crate) fn configure_dwm_dark_mode(hwnd: HWND, appearance: WindowAppearance) {
     let dark_mode_enabled: BOOL = match appearance {
@@ -67,6 +67,7 @@ pub struct WindowsWindowState {
     pub invalidate_devices: Arc<AtomicBool>,
     fullscreen: Cell<Option<StyleAndBounds>>,
     initial_placement: Cell<Option<WindowOpenStatus>>,
+    native_views: RefCell<EmbeddedNativeViews<HWND>>,
     hwnd: HWND,
 }
 
@@ -150,6 +151,7 @@ impl WindowsWindowState {
             display: Cell::new(display),
             fullscreen: Cell::new(fullscreen),
             initial_placement: Cell::new(initial_placement),
+            native_views: RefCell::new(EmbeddedNativeViews::default()),
             hwnd,
             invalidate_devices,
         })
@@ -577,6 +579,38 @@ impl PlatformWindow for WindowsWindow {
             .detach();
     }
 
+    fn content_origin(&self) -> Option<Point<Pixels>> {
+        // The window's origin is tracked from `WM_MOVE`, which reports its client area.
+        Some(self.bounds().origin)
+    }
+
+    fn set_bounds(&mut self, bounds: Bounds<Pixels>) {
+        let hwnd = self.0.hwnd;
+        let rect = calculate_window_rect(
+            bounds.to_device_pixels(self.scale_factor()),
+            &self.state.border_offset,
+        );
+
+        self.0
+            .executor
+            .spawn(async move {
+                unsafe {
+                    SetWindowPos(
+                        hwnd,
+                        None,
+                        rect.left,
+                        rect.top,
+                        rect.right - rect.left,
+                        rect.bottom - rect.top,
+                        SWP_NOZORDER | SWP_NOACTIVATE,
+                    )
+                    .context("unable to set window bounds")
+                    .log_err();
+                }
+            })
+            .detach();
+    }
+
     fn scale_factor(&self) -> f32 {
         self.state.scale_factor.get()
     }
@@ -892,10 +926,75 @@ impl PlatformWindow for WindowsWindow {
         self.0.hwnd
     }
 
+    fn set_text_contrast(&self, contrast: Option<TextContrast>) {
+        self.state.renderer.borrow_mut().set_text_contrast(contrast);
+    }
+
+    fn capture_frame(&self) -> Receiver<anyhow::Result<image::RgbaImage>> {
+        self.state.renderer.borrow_mut().capture_frame()
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.state.renderer.borrow().gpu_specs().log_err()
     }
 
+    fn set_native_views(&self, views: &[NativeViewPlacement]) {
+        let scale_factor = self.state.scale_factor.get();
+        let hwnd = self.0.hwnd;
+        let win32_view = |placement: &NativeViewPlacement| match placement.view {
+            rwh::RawWindowHandle::Win32(handle) => Some(HWND(handle.hwnd.get() as _)),
+            _ => None,
+        };
+        self.state.native_views.borrow_mut().update(
+            views,
+            |placement| {
+                let view = win32_view(placement)?;
+                unsafe {
+                    let style = get_window_long(view, GWL_STYLE);
+                    set_window_long(
+                        view,
+                        GWL_STYLE,
+                        (style | WS_CHILD.0 as isize) & !(WS_POPUP.0 as isize),
+                    );
+                    SetParent(view, Some(hwnd))
+                        .context("unable to embed native view")
+                        .log_err()?;
+                }
+                Some(view)
+            },
+            |placement, view| {
+                let bounds = placement.bounds.to_device_pixels(scale_factor);
+                let clip = placement.clip.to_device_pixels(scale_factor);
+                unsafe {
+                    SetWindowPos(
+                        *view,
+                        None,
+                        bounds.origin.x.0,
+                        bounds.origin.y.0,
+                        bounds.size.width.0,
+                        bounds.size.height.0,
+                        SWP_NOZORDER | SWP_NOACTIVATE | SWP_SHOWWINDOW,
+                    )
+                    .log_err();
+                    // The window takes ownership of the region.
+                    let region = CreateRectRgn(
+                        (clip.left() - bounds.left()).0,
+                        (clip.top() - bounds.top()).0,
+                        (clip.right() - bounds.left()).0,
+                        (clip.bottom() - bounds.top()).0,
+                    );
+                    SetWindowRgn(*view, Some(region), true);
+                }
+            },
+            |_, view| unsafe {
+                // The view may have been destroyed, and its handle reused by another window.
+                if IsChild(hwnd, view).as_bool() {
+                    let _ = ShowWindow(view, SW_HIDE);
+                }
+            },
+        );
+    }
+
     fn update_ime_position(&self, _bounds: Bounds<Pixels>) {
         // There is no such thing on Windows.
     }
@@ -7,9 +7,9 @@ use std::{
 use crate::{
     AbsoluteLength, App, Background, BackgroundTag, BorderStyle, Bounds, ContentMask, Corners,
     CornersRefinement, CursorStyle, DefiniteLength, DevicePixels, Edges, EdgesRefinement, Font,
-    FontFallbacks, FontFeatures, FontStyle, FontWeight, GridLocation, Hsla, Length, Pixels, Point,
-    PointRefinement, Rgba, SharedString, Size, SizeRefinement, Styled, TextRun, Window, black, phi,
-    point, quad, rems, size,
+    FontFallbacks, FontFeatures, FontStyle, FontWeight, GridLocation, Hsla, Length, PaintQuad,
+    Pixels, Point, PointRefinement, Rgba, SharedString, Size, SizeRefinement, Styled, TextRun,
+    Window, black, phi, point, quad, rems, size,
 };
 use collections::HashSet;
 use refineable::Refineable;
@@ -248,9 +248,27 @@ pub struct Style {
     #[refineable]
     pub corner_radii: Corners<AbsoluteLength>,
 
+    /// The vertical radius of the corners of this element, which makes them elliptical with
+    /// `corner_radii` as their horizontal radius. Corners are circular when these are all zero.
+    /// Box shadows, backdrop blur and content masks keep circular corners with the horizontal
+    /// radius.
+    #[refineable]
+    pub vertical_corner_radii: Corners<AbsoluteLength>,
+
     /// Box shadow of the element
     pub box_shadow: Vec<BoxShadow>,
 
+    /// The radius of the blur applied to whatever is drawn behind this element. Only the Blade
+    /// renderer draws backdrop blurs.
+    #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        ),
+        all(target_os = "macos", feature = "macos-blade")
+    ))]
+    pub backdrop_blur: Option<Pixels>,
+
     /// The text style of this element
     pub text: TextStyleRefinement,
 
@@ -533,6 +551,49 @@ impl Hash for HighlightStyle {
 }
 
 impl Style {
+    /// Replaces the [`DefiniteLength::Ch`] lengths that lay this element out with pixels.
+    /// `ch_width` measures one column in the element's font, and is only called if there are any.
+    pub(crate) fn resolve_ch_lengths(&mut self, ch_width: impl Fn() -> Pixels) {
+        let mut width = None;
+        let mut resolve = |length: &mut DefiniteLength| {
+            if let DefiniteLength::Ch(columns) = *length {
+                *length = (*width.get_or_insert_with(&ch_width) * columns).into();
+            }
+        };
+        let lengths = [
+            &mut self.inset.top,
+            &mut self.inset.right,
+            &mut self.inset.bottom,
+            &mut self.inset.left,
+            &mut self.size.width,
+            &mut self.size.height,
+            &mut self.min_size.width,
+            &mut self.min_size.height,
+            &mut self.max_size.width,
+            &mut self.max_size.height,
+            &mut self.margin.top,
+            &mut self.margin.right,
+            &mut self.margin.bottom,
+            &mut self.margin.left,
+            &mut self.flex_basis,
+        ];
+        for length in lengths {
+            if let Length::Definite(length) = length {
+                resolve(length);
+            }
+        }
+        for length in [
+            &mut self.padding.top,
+            &mut self.padding.right,
+            &mut self.padding.bottom,
+            &mut self.padding.left,
+            &mut self.gap.width,
+            &mut self.gap.height,
+        ] {
+            resolve(length);
+        }
+    }
+
     /// Returns true if the style is visible and the background is opaque.
     pub fn has_opaque_background(&self) -> bool {
         self.background
@@ -551,6 +612,7 @@ impl Style {
 
     /// Get the content mask for this element style, based on the given bounds.
     /// If the element does not hide its overflow, this will return `None`.
+    /// When overflow is hidden on both axes, the mask follows the element's rounded corners.
     pub fn overflow_mask(
         &self,
         bounds: Bounds<Pixels>,
@@ -575,7 +637,7 @@ impl Style {
                     max.y -= self.border_widths.bottom.to_pixels(rem_size);
                 }
 
-                let bounds = match (
+                let mask_bounds = match (
                     self.overflow.x == Overflow::Visible,
                     self.overflow.y == Overflow::Visible,
                 ) {
@@ -592,10 +654,49 @@ impl Style {
                         point(bounds.bottom_right().x, max.y),
                     ),
                     // both hidden
-                    (false, false) => Bounds::from_corners(min, max),
+                    (false, false) => {
+                        let corner_radii = self
+                            .corner_radii
+                            .to_pixels(rem_size)
+                            .clamp_radii_for_quad_size(bounds.size);
+                        // Inside a border, the corners curve less by the border's width.
+                        let top_left_inset = min - bounds.origin;
+                        let bottom_right_inset = bounds.bottom_right() - max;
+                        let inner_radius = |radius: Pixels, x_inset: Pixels, y_inset: Pixels| {
+                            (radius - x_inset.max(y_inset)).max(Pixels::ZERO)
+                        };
+                        return Some(ContentMask {
+                            bounds: Bounds::from_corners(min, max),
+                            corner_radii: Corners {
+                                top_left: inner_radius(
+                                    corner_radii.top_left,
+                                    top_left_inset.x,
+                                    top_left_inset.y,
+                                ),
+                                top_right: inner_radius(
+                                    corner_radii.top_right,
+                                    bottom_right_inset.x,
+                                    top_left_inset.y,
+                                ),
+                                bottom_right: inner_radius(
+                                    corner_radii.bottom_right,
+                                    bottom_right_inset.x,
+                                    bottom_right_inset.y,
+                                ),
+                                bottom_left: inner_radius(
+                                    corner_radii.bottom_left,
+                                    top_left_inset.x,
+                                    bottom_right_inset.y,
+                                ),
+                            },
+                        });
+                    }
                 };
 
-                Some(ContentMask { bounds })
+                Some(ContentMask {
+                    bounds: mask_bounds,
+                    ..Default::default()
+                })
             }
         }
     }
@@ -619,15 +720,38 @@ impl Style {
         }
 
         let rem_size = window.rem_size();
-        let corner_radii = self
-            .corner_radii
-            .to_pixels(rem_size)
-            .clamp_radii_for_quad_size(bounds.size);
-
-        window.paint_shadows(bounds, corner_radii, &self.box_shadow);
+        let corner_radii = self.corner_radii.to_pixels(rem_size);
+        let vertical_corner_radii = self.vertical_corner_radii.to_pixels(rem_size);
+        let (corner_radii, vertical_corner_radii) = if vertical_corner_radii == Corners::default() {
+            (corner_radii.clamp_radii_for_quad_size(bounds.size), None)
+        } else {
+            let (corner_radii, vertical_corner_radii) = corner_radii
+                .clamp_elliptical_radii_for_quad_size(vertical_corner_radii, bounds.size);
+            (corner_radii, Some(vertical_corner_radii))
+        };
 
         let background_color = self.background.as_ref().and_then(Fill::color);
-        if background_color.is_some_and(|color| !color.is_transparent()) {
+        let paints_background = background_color.is_some_and(|color| !color.is_transparent());
+
+        // The topmost shadow is drawn by the background quad, saving a separate shadow pass.
+        let (background_shadow, shadows) = match self.box_shadow.split_last() {
+            Some((shadow, shadows)) if paints_background => (Some(shadow.clone()), shadows),
+            _ => (None, self.box_shadow.as_slice()),
+        };
+        window.paint_shadows(bounds, corner_radii, shadows);
+
+        #[cfg(any(
+            all(
+                any(target_os = "linux", target_os = "freebsd"),
+                any(feature = "x11", feature = "wayland")
+            ),
+            all(target_os = "macos", feature = "macos-blade")
+        ))]
+        if let Some(blur_radius) = self.backdrop_blur {
+            window.paint_backdrop_blur(bounds, corner_radii, blur_radius);
+        }
+
+        if paints_background {
             let mut border_color = match background_color {
                 Some(color) => match color.tag {
                     BackgroundTag::Solid => color.solid,
@@ -641,22 +765,32 @@ impl Style {
                 None => Hsla::default(),
             };
             border_color.a = 0.;
-            window.paint_quad(quad(
-                bounds,
-                corner_radii,
-                background_color.unwrap_or_default(),
-                Edges::default(),
-                border_color,
-                self.border_style,
-            ));
+            window.paint_quad(PaintQuad {
+                vertical_corner_radii,
+                ..quad(
+                    bounds,
+                    corner_radii,
+                    background_color.unwrap_or_default(),
+                    Edges::default(),
+                    border_color,
+                    self.border_style,
+                )
+                .shadow(background_shadow)
+            });
         }
 
-        continuation(window, cx);
+        // Text can only be checked against solid backgrounds.
+        let contrast_background = background_color
+            .filter(|background| background.tag == BackgroundTag::Solid)
+            .map(|background| background.solid);
+        window.with_contrast_background(contrast_background, |window| continuation(window, cx));
 
         if self.is_border_visible() {
             let border_widths = self.border_widths.to_pixels(rem_size);
             let max_border_width = border_widths.max();
-            let max_corner_radius = corner_radii.max();
+            let max_corner_radius = vertical_corner_radii.map_or(corner_radii.max(), |vertical| {
+                corner_radii.max().max(vertical.max())
+            });
 
             let top_bounds = Bounds::from_corners(
                 bounds.origin,
@@ -677,21 +811,31 @@ impl Style {
 
             let mut background = self.border_color.unwrap_or_default();
             background.a = 0.;
-            let quad = quad(
-                bounds,
-                corner_radii,
-                background,
-                border_widths,
-                self.border_color.unwrap_or_default(),
-                self.border_style,
-            );
+            let quad = PaintQuad {
+                vertical_corner_radii,
+                ..quad(
+                    bounds,
+                    corner_radii,
+                    background,
+                    border_widths,
+                    self.border_color.unwrap_or_default(),
+                    self.border_style,
+                )
+            };
 
-            window.with_content_mask(Some(ContentMask { bounds: top_bounds }), |window| {
-                window.paint_quad(quad.clone());
-            });
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: top_bounds,
+                    ..Default::default()
+                }),
+                |window| {
+                    window.paint_quad(quad.clone());
+                },
+            );
             window.with_content_mask(
                 Some(ContentMask {
                     bounds: right_bounds,
+                    ..Default::default()
                 }),
                 |window| {
                     window.paint_quad(quad.clone());
@@ -700,6 +844,7 @@ impl Style {
             window.with_content_mask(
                 Some(ContentMask {
                     bounds: bottom_bounds,
+                    ..Default::default()
                 }),
                 |window| {
                     window.paint_quad(quad.clone());
@@ -708,6 +853,7 @@ impl Style {
             window.with_content_mask(
                 Some(ContentMask {
                     bounds: left_bounds,
+                    ..Default::default()
                 }),
                 |window| {
                     window.paint_quad(quad);
@@ -765,7 +911,16 @@ impl Default for Style {
             border_color: None,
             border_style: BorderStyle::default(),
             corner_radii: Corners::default(),
+            vertical_corner_radii: Corners::default(),
             box_shadow: Default::default(),
+            #[cfg(any(
+                all(
+                    any(target_os = "linux", target_os = "freebsd"),
+                    any(feature = "x11", feature = "wayland")
+                ),
+                all(target_os = "macos", feature = "macos-blade")
+            ))]
+            backdrop_blur: None,
             text: TextStyleRefinement::default(),
             mouse_cursor: None,
             opacity: None,
@@ -794,6 +949,13 @@ pub struct UnderlineStyle {
 
     /// Whether the underline should be wavy, like in a spell checker.
     pub wavy: bool,
+
+    /// How far a wavy underline strays above and below its center line. Defaults to 0.8 times
+    /// the thickness.
+    pub wave_amplitude: Option<Pixels>,
+
+    /// The length of one period of a wavy underline. Defaults to 9 times the thickness.
+    pub wavelength: Option<Pixels>,
 }
 
 /// The properties that can be applied to a strikethrough.
@@ -801,7 +963,7 @@ pub struct UnderlineStyle {
     Refineable, Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
 )]
 pub struct StrikethroughStyle {
-    /// The thickness of the strikethrough.
+    /// The thickness of the strikethrough, or zero to use the thickness suggested by the font.
     pub thickness: Pixels,
 
     /// The color of the strikethrough.
@@ -1321,6 +1483,7 @@ mod tests {
                 thickness: px(2.),
                 color: Some(red()),
                 wavy: true,
+                ..Default::default()
             }),
         };
         let expected_style = style_b;
@@ -1353,6 +1516,7 @@ mod tests {
                 thickness: px(4.),
                 color: None,
                 wavy: false,
+                ..Default::default()
             }),
         };
 
@@ -1371,6 +1535,7 @@ mod tests {
                 thickness: px(4.),
                 color: None,
                 wavy: false,
+                ..Default::default()
             }),
         };
 
@@ -1,9 +1,9 @@
 use crate::{
-    self as gpui, AbsoluteLength, AlignContent, AlignItems, BorderStyle, CursorStyle,
-    DefiniteLength, Display, Fill, FlexDirection, FlexWrap, Font, FontFeatures, FontStyle,
-    FontWeight, GridPlacement, Hsla, JustifyContent, Length, SharedString, StrikethroughStyle,
-    StyleRefinement, TextAlign, TextOverflow, TextStyleRefinement, UnderlineStyle, WhiteSpace, px,
-    relative, rems,
+    self as gpui, AbsoluteLength, AlignContent, AlignItems, BorderStyle, CornersRefinement,
+    CursorStyle, DefiniteLength, Display, Fill, FlexDirection, FlexWrap, Font, FontFeatures,
+    FontStyle, FontWeight, GridPlacement, Hsla, JustifyContent, Length, Pixels, SharedString,
+    StrikethroughStyle, StyleRefinement, TextAlign, TextOverflow, TextStyleRefinement,
+    UnderlineStyle, WhiteSpace, px, relative, rems,
 };
 pub use gpui_macros::{
     border_style_methods, box_shadow_style_methods, cursor_style_methods, margin_style_methods,
@@ -395,6 +395,12 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets the border of the element to be drawn as round dots.
+    fn border_dotted(mut self) -> Self {
+        self.style().border_style = Some(BorderStyle::Dotted);
+        self
+    }
+
     /// Returns a mutable reference to the text style that has been configured on this element.
     fn text_style(&mut self) -> &mut Option<TextStyleRefinement> {
         let style: &mut StyleRefinement = self.style();
@@ -535,10 +541,7 @@ pub trait Styled: Sized {
     /// [Docs](https://tailwindcss.com/docs/text-decoration-line#adding-a-line-through-text)
     fn line_through(mut self) -> Self {
         let style = self.text_style().get_or_insert_with(Default::default);
-        style.strikethrough = Some(StrikethroughStyle {
-            thickness: px(1.),
-            ..Default::default()
-        });
+        style.strikethrough = Some(StrikethroughStyle::default());
         self
     }
 
@@ -578,6 +581,20 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets how far a wavy underline strays above and below its center line, and the length of
+    /// one period of its wave.
+    fn text_decoration_wave(
+        mut self,
+        amplitude: impl Into<Pixels>,
+        wavelength: impl Into<Pixels>,
+    ) -> Self {
+        let style = self.text_style().get_or_insert_with(Default::default);
+        let underline = style.underline.get_or_insert_with(Default::default);
+        underline.wave_amplitude = Some(amplitude.into());
+        underline.wavelength = Some(wavelength.into());
+        self
+    }
+
     /// Sets the text decoration to be 0px thick.
     /// [Docs](https://tailwindcss.com/docs/text-decoration-thickness)
     fn text_decoration_0(mut self) -> Self {
@@ -673,6 +690,47 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Blurs whatever is drawn behind this element by the given radius, for frosted-glass
+    /// panels. Give the element a translucent background to tint the blur. Only available where
+    /// GPUI renders with Blade, since the Metal and DirectX renderers don't draw backdrop blurs.
+    /// [Docs](https://tailwindcss.com/docs/backdrop-filter-blur)
+    #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        ),
+        all(target_os = "macos", feature = "macos-blade")
+    ))]
+    fn backdrop_blur(mut self, radius: impl Into<Pixels>) -> Self {
+        self.style().backdrop_blur = Some(radius.into());
+        self
+    }
+
+    /// Rounds all four corners of the element into quarter ellipses with the given horizontal
+    /// and vertical radii. Box shadows and clipped children keep circular corners with the
+    /// horizontal radius.
+    fn rounded_elliptical(
+        mut self,
+        horizontal: impl Into<AbsoluteLength>,
+        vertical: impl Into<AbsoluteLength>,
+    ) -> Self {
+        let horizontal = horizontal.into();
+        let vertical = vertical.into();
+        self.style().corner_radii = CornersRefinement {
+            top_left: Some(horizontal),
+            top_right: Some(horizontal),
+            bottom_right: Some(horizontal),
+            bottom_left: Some(horizontal),
+        };
+        self.style().vertical_corner_radii = CornersRefinement {
+            top_left: Some(vertical),
+            top_right: Some(vertical),
+            bottom_right: Some(vertical),
+            bottom_left: Some(vertical),
+        };
+        self
+    }
+
     /// Sets the grid columns of this element.
     fn grid_cols(mut self, cols: u16) -> Self {
         self.style().grid_cols = Some(cols);
@@ -111,6 +111,11 @@ where
             })
     }
 
+    /// Returns whether no subscribers have been inserted, or all of them have been dropped.
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().subscribers.is_empty()
+    }
+
     /// Call the given callback for each subscriber to the given emitter.
     /// If the callback returns false, the subscriber is removed.
     pub fn retain<F>(&self, emitter: &EmitterKey, mut f: F)
@@ -6,6 +6,7 @@ use image::Frame;
 use resvg::tiny_skia::Pixmap;
 use smallvec::SmallVec;
 use std::{
+    borrow::Cow,
     hash::Hash,
     sync::{Arc, LazyLock},
 };
@@ -13,6 +14,19 @@ use std::{
 /// When rendering SVGs, we render them at twice the size to get a higher-quality result.
 pub const SMOOTH_SVG_SCALE_FACTOR: f32 = 2.;
 
+/// The width in pixels of the shape in an icon's signed distance field. Icons are rendered into
+/// a distance field once and drawn at any size from it.
+pub(crate) const DISTANCE_FIELD_SIZE: f32 = 64.;
+/// How many pixels of the distance field lie between the shape's edge and the point where the
+/// field saturates. The field is padded by this much on each side, which bounds how far a glow
+/// can extend.
+pub(crate) const DISTANCE_FIELD_RANGE: f32 = 8.;
+/// Distances are measured on a grid this many times finer than the field, so the field keeps
+/// the antialiased shape of the SVG.
+const DISTANCE_FIELD_OVERSAMPLING: usize = 4;
+/// Stands in for an infinite squared distance without producing NaNs in the transform.
+const FAR_AWAY: f64 = 1e20;
+
 #[derive(Clone, PartialEq, Hash, Eq)]
 pub(crate) struct RenderSvgParams {
     pub(crate) path: SharedString,
@@ -125,6 +139,50 @@ impl SvgRenderer {
         }
     }
 
+    /// Renders the SVG into a signed distance field whose shape is [`DISTANCE_FIELD_SIZE`] pixels
+    /// wide, padded by [`DISTANCE_FIELD_RANGE`] on each side. Values above 128 are inside the
+    /// shape.
+    pub(crate) fn render_distance_field(
+        &self,
+        path: &SharedString,
+        bytes: Option<&[u8]>,
+    ) -> Result<Option<(Size<DevicePixels>, Vec<u8>)>> {
+        let bytes = match bytes {
+            Some(bytes) => Cow::Borrowed(bytes),
+            None => match self.asset_source.load(path)? {
+                Some(bytes) => bytes,
+                None => return Ok(None),
+            },
+        };
+        let tree = usvg::Tree::from_data(&bytes, &self.usvg_options)?;
+        let svg_size = tree.size();
+        let oversampling = DISTANCE_FIELD_OVERSAMPLING as f32;
+        let scale = DISTANCE_FIELD_SIZE * oversampling / svg_size.width();
+        let padding = DISTANCE_FIELD_RANGE * oversampling;
+        let mut pixmap = Pixmap::new(
+            (svg_size.width() * scale + padding * 2.).ceil() as u32,
+            (svg_size.height() * scale + padding * 2.).ceil() as u32,
+        )
+        .ok_or(usvg::Error::InvalidSize)?;
+        let transform =
+            resvg::tiny_skia::Transform::from_scale(scale, scale).post_translate(padding, padding);
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+        let coverage = pixmap
+            .pixels()
+            .iter()
+            .map(|pixel| pixel.alpha())
+            .collect::<Vec<_>>();
+        let (size, field) = signed_distance_field(
+            &coverage,
+            pixmap.width() as usize,
+            pixmap.height() as usize,
+            DISTANCE_FIELD_OVERSAMPLING,
+            DISTANCE_FIELD_RANGE,
+        );
+        Ok(Some((size, field)))
+    }
+
     fn render_pixmap(&self, bytes: &[u8], size: SvgSize) -> Result<Pixmap, usvg::Error> {
         let tree = usvg::Tree::from_data(bytes, &self.usvg_options)?;
         let svg_size = tree.size();
@@ -147,3 +205,179 @@ impl SvgRenderer {
         Ok(pixmap)
     }
 }
+
+/// Converts a coverage mask into a signed distance field `downsample` times smaller. Each value
+/// encodes the distance from the pixel's center to the shape's edge, mapping `-range..range`
+/// pixels of the output to `0..255`.
+fn signed_distance_field(
+    coverage: &[u8],
+    width: usize,
+    height: usize,
+    downsample: usize,
+    range: f32,
+) -> (Size<DevicePixels>, Vec<u8>) {
+    let is_inside = |ix: usize| coverage[ix] >= 128;
+    let to_inside = squared_distance_transform(width, height, |ix| is_inside(ix));
+    let to_outside = squared_distance_transform(width, height, |ix| !is_inside(ix));
+
+    // Distances are measured between pixel centers, while the edge lies between pixels.
+    let signed_distance = |ix: usize| {
+        if is_inside(ix) {
+            to_outside[ix].sqrt() - 0.5
+        } else {
+            0.5 - to_inside[ix].sqrt()
+        }
+    };
+
+    let field_width = width / downsample;
+    let field_height = height / downsample;
+    let mut field = Vec::with_capacity(field_width * field_height);
+    for y in 0..field_height {
+        for x in 0..field_width {
+            // The distance at the center of each block of pixels is their average distance.
+            let mut total = 0.;
+            for block_y in y * downsample..(y + 1) * downsample {
+                for block_x in x * downsample..(x + 1) * downsample {
+                    total += signed_distance(block_y * width + block_x);
+                }
+            }
+            let distance = total / (downsample * downsample * downsample) as f64;
+            let value = 0.5 + distance as f32 / (range * 2.);
+            field.push((value.clamp(0., 1.) * 255.).round() as u8);
+        }
+    }
+    let size = Size::new(
+        DevicePixels(field_width as i32),
+        DevicePixels(field_height as i32),
+    );
+    (size, field)
+}
+
+/// Returns the squared distance from each pixel to the nearest pixel for which `is_feature`
+/// returns true, using the separable algorithm of Felzenszwalb and Huttenlocher.
+fn squared_distance_transform(
+    width: usize,
+    height: usize,
+    is_feature: impl Fn(usize) -> bool,
+) -> Vec<f64> {
+    let mut distances = (0..width * height)
+        .map(|ix| if is_feature(ix) { 0. } else { FAR_AWAY })
+        .collect::<Vec<_>>();
+    let longest_side = width.max(height);
+    let mut line = vec![0.; longest_side];
+    let mut transformed = vec![0.; longest_side];
+    let mut parabolas = vec![0; longest_side];
+    let mut boundaries = vec![0.; longest_side + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            line[y] = distances[y * width + x];
+        }
+        distance_transform_1d(
+            &line[..height],
+            &mut transformed[..height],
+            &mut parabolas,
+            &mut boundaries,
+        );
+        for y in 0..height {
+            distances[y * width + x] = transformed[y];
+        }
+    }
+    for y in 0..height {
+        let row = y * width..(y + 1) * width;
+        line[..width].copy_from_slice(&distances[row.clone()]);
+        distance_transform_1d(
+            &line[..width],
+            &mut distances[row],
+            &mut parabolas,
+            &mut boundaries,
+        );
+    }
+    distances
+}
+
+/// Computes the lower envelope of the parabolas rooted at each input sample.
+fn distance_transform_1d(
+    input: &[f64],
+    output: &mut [f64],
+    parabolas: &mut [usize],
+    boundaries: &mut [f64],
+) {
+    if input.is_empty() {
+        return;
+    }
+    let intersection = |first: usize, second: usize| {
+        ((input[second] + (second * second) as f64) - (input[first] + (first * first) as f64))
+            / (2. * second as f64 - 2. * first as f64)
+    };
+
+    let mut count = 0;
+    parabolas[0] = 0;
+    boundaries[0] = f64::NEG_INFINITY;
+    boundaries[1] = f64::INFINITY;
+    for position in 1..input.len() {
+        let mut boundary = intersection(parabolas[count], position);
+        while boundary <= boundaries[count] {
+            count -= 1;
+            boundary = intersection(parabolas[count], position);
+        }
+        count += 1;
+        parabolas[count] = position;
+        boundaries[count] = boundary;
+        boundaries[count + 1] = f64::INFINITY;
+    }
+
+    let mut ix = 0;
+    for (position, output) in output.iter_mut().enumerate() {
+        while boundaries[ix + 1] < position as f64 {
+            ix += 1;
+        }
+        let offset = position as f64 - parabolas[ix] as f64;
+        *output = offset * offset + input[parabolas[ix]];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_squared_distance_transform() {
+        let features = [false, false, true, false, false, false];
+        let distances = squared_distance_transform(6, 1, |ix| features[ix]);
+        assert_eq!(distances, vec![4., 1., 0., 1., 4., 9.]);
+
+        let distances = squared_distance_transform(3, 3, |ix| ix == 0);
+        assert_eq!(distances, vec![0., 1., 4., 1., 2., 5., 4., 5., 8.]);
+    }
+
+    #[test]
+    fn test_signed_distance_field() {
+        // A 16x16 square in the middle of a 32x32 mask.
+        let coverage = (0..32 * 32)
+            .map(|ix| {
+                let (x, y) = (ix % 32, ix / 32);
+                if (8..24).contains(&x) && (8..24).contains(&y) {
+                    255
+                } else {
+                    0
+                }
+            })
+            .collect::<Vec<u8>>();
+        let (size, field) = signed_distance_field(&coverage, 32, 32, 2, 2.);
+        assert_eq!(size, Size::new(DevicePixels(16), DevicePixels(16)));
+        assert_eq!(field[0], 0, "pixels far outside the shape saturate");
+        assert_eq!(
+            field[8 * 16 + 8],
+            255,
+            "pixels deep inside the shape saturate"
+        );
+
+        let (outside, inside) = (field[8 * 16 + 3], field[8 * 16 + 4]);
+        assert!(outside < 128 && inside > 128);
+        assert!(
+            (outside as i32 + inside as i32 - 255).abs() <= 16,
+            "pixels on either side of the edge are about equally far from it"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    AbsoluteLength, App, Bounds, DefiniteLength, Edges, Length, Pixels, Size, Style, Window,
-    point, size,
+    AbsoluteLength, App, Bounds, DefiniteLength, Edges, LAYOUT_LOG_TARGET, Length, Pixels, Size,
+    Style, Window, point, size,
 };
 use collections::{FxHashMap, FxHashSet};
 use stacksafe::{StackSafe, stacksafe};
@@ -183,7 +183,7 @@ impl TaffyLayoutEngine {
             }
         }
 
-        let scale_factor = window.scale_factor();
+        let scale_factor = window.rasterization_scale();
 
         let transform = |v: AvailableSpace| match v {
             AvailableSpace::Definite(pixels) => {
@@ -230,6 +230,11 @@ impl TaffyLayoutEngine {
                 },
             )
             .expect(EXPECT_MESSAGE);
+        log::trace!(
+            target: LAYOUT_LOG_TARGET,
+            "computed layout of {id:?} in a tree of {} nodes",
+            self.taffy.total_node_count()
+        );
     }
 
     pub fn layout_bounds(&mut self, id: LayoutId, scale_factor: f32) -> Bounds<Pixels> {
@@ -407,6 +412,10 @@ impl ToTaffy<taffy::style::LengthPercentage> for DefiniteLength {
             DefiniteLength::Fraction(fraction) => {
                 taffy::style::LengthPercentage::percent(*fraction)
             }
+            DefiniteLength::Ch(columns) => {
+                let pixels: f32 = unresolved_ch(*columns, rem_size).into();
+                taffy::style::LengthPercentage::length(pixels * scale_factor)
+            }
         }
     }
 }
@@ -427,6 +436,10 @@ impl ToTaffy<taffy::style::LengthPercentageAuto> for DefiniteLength {
             DefiniteLength::Fraction(fraction) => {
                 taffy::style::LengthPercentageAuto::percent(*fraction)
             }
+            DefiniteLength::Ch(columns) => {
+                let pixels: f32 = unresolved_ch(*columns, rem_size).into();
+                taffy::style::LengthPercentageAuto::length(pixels * scale_factor)
+            }
         }
     }
 }
@@ -444,10 +457,20 @@ impl ToTaffy<taffy::style::Dimension> for DefiniteLength {
                 }
             },
             DefiniteLength::Fraction(fraction) => taffy::style::Dimension::percent(*fraction),
+            DefiniteLength::Ch(columns) => {
+                let pixels: f32 = unresolved_ch(*columns, rem_size).into();
+                taffy::style::Dimension::length(pixels * scale_factor)
+            }
         }
     }
 }
 
+/// Measures `ch` lengths that weren't resolved against the element's font before layout, with
+/// the same fallback as [`DefiniteLength::to_pixels`].
+fn unresolved_ch(columns: f32, rem_size: Pixels) -> Pixels {
+    DefiniteLength::Ch(columns).to_pixels(AbsoluteLength::Pixels(Pixels::ZERO), rem_size)
+}
+
 impl ToTaffy<taffy::style::LengthPercentage> for AbsoluteLength {
     fn to_taffy(&self, rem_size: Pixels, scale_factor: f32) -> taffy::style::LengthPercentage {
         match self {
@@ -15,8 +15,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result, SharedString, Size,
-    StrikethroughStyle, UnderlineStyle, px,
+    BackgroundExecutor, Bounds, DevicePixels, Hsla, Pixels, PlatformTextSystem, Point, Result,
+    SharedString, Size, StrikethroughStyle, TEXT_LOG_TARGET, UnderlineStyle, px,
 };
 use anyhow::{Context as _, anyhow};
 use collections::FxHashMap;
@@ -225,6 +225,22 @@ impl TextSystem {
         Ok(self.advance(font_id, font_size, '0')?.width)
     }
 
+    /// Returns whether every character in the font advances by the same width, so that text in it
+    /// lines up in columns.
+    ///
+    /// In a monospace font, a run of `n` characters is exactly `n` times [`Self::ch_advance`]
+    /// wide, so terminal and code views can size gutters and grids from it without shaping
+    /// sample strings.
+    pub fn is_monospace(&self, font_id: FontId) -> bool {
+        let mut advances = ['0', 'i', 'm', 'W', ' ', '.']
+            .into_iter()
+            .map(|ch| self.advance(font_id, px(1.), ch).map(|advance| advance.width));
+        let Some(Ok(first_advance)) = advances.next() else {
+            return false;
+        };
+        advances.all(|advance| advance.is_ok_and(|advance| advance == first_advance))
+    }
+
     /// Get the number of font size units per 'em square',
     /// Per MDN: "an abstract square whose height is the intended distance between
     /// lines of type in the same type size"
@@ -253,6 +269,19 @@ impl TextSystem {
         self.read_metrics(font_id, |metrics| metrics.descent(font_size))
     }
 
+    /// Get the suggested distance from the baseline to the top of a strikethrough in the given
+    /// font and size.
+    pub fn strikethrough_position(&self, font_id: FontId, font_size: Pixels) -> Pixels {
+        self.read_metrics(font_id, |metrics| metrics.strikethrough_position(font_size))
+    }
+
+    /// Get the suggested thickness of a strikethrough in the given font and size.
+    pub fn strikethrough_thickness(&self, font_id: FontId, font_size: Pixels) -> Pixels {
+        self.read_metrics(font_id, |metrics| {
+            metrics.strikethrough_thickness(font_size)
+        })
+    }
+
     /// Get the recommended baseline offset for the given font and line height.
     pub fn baseline_offset(
         &self,
@@ -297,6 +326,13 @@ impl TextSystem {
         }
     }
 
+    /// Frees memory held by caches that are cheap to rebuild, such as pooled line wrappers.
+    pub(crate) fn trim_caches(&self) {
+        self.raster_bounds.write().clear();
+        self.wrapper_pool.lock().clear();
+        self.font_runs_pool.lock().clear();
+    }
+
     /// Get the rasterized size and location of a specific, rendered glyph.
     pub(crate) fn raster_bounds(&self, params: &RenderGlyphParams) -> Result<Bounds<DevicePixels>> {
         let raster_bounds = self.raster_bounds.upgradable_read();
@@ -329,13 +365,33 @@ pub struct WindowTextSystem {
 }
 
 impl WindowTextSystem {
-    pub(crate) fn new(text_system: Arc<TextSystem>) -> Self {
+    /// Creates a text system with its own line layout cache, e.g. to shape text outside of a
+    /// window.
+    pub fn new(text_system: Arc<TextSystem>) -> Self {
         Self {
             line_layout_cache: LineLayoutCache::new(text_system.platform_text_system.clone()),
             text_system,
         }
     }
 
+    /// Creates a text system that shapes long lines on the given executor, laying them out as
+    /// placeholders in the meantime. The returned receiver is notified when a line has been
+    /// shaped and should be redrawn.
+    pub(crate) fn with_background_shaping(
+        text_system: Arc<TextSystem>,
+        executor: BackgroundExecutor,
+    ) -> (Self, futures::channel::mpsc::UnboundedReceiver<()>) {
+        let (line_layout_cache, lines_shaped) = LineLayoutCache::with_background_shaping(
+            text_system.platform_text_system.clone(),
+            executor,
+        );
+        let text_system = Self {
+            line_layout_cache,
+            text_system,
+        };
+        (text_system, lines_shaped)
+    }
+
     pub(crate) fn layout_index(&self) -> LineLayoutIndex {
         self.line_layout_cache.layout_index()
     }
@@ -420,7 +476,10 @@ impl WindowTextSystem {
             let mut run_start = line_start;
             while run_start < line_end {
                 let Some(run) = runs.peek_mut() else {
-                    log::warn!("`TextRun`s do not cover the entire to be shaped text");
+                    log::warn!(
+                        target: TEXT_LOG_TARGET,
+                        "`TextRun`s do not cover the entire to be shaped text"
+                    );
                     break;
                 };
 
@@ -530,6 +589,12 @@ impl WindowTextSystem {
         self.line_layout_cache.finish_frame()
     }
 
+    /// Releases memory that this window's line layout cache holds beyond the layouts of the
+    /// last frame.
+    pub(crate) fn trim_layout_cache(&self) {
+        self.line_layout_cache.trim();
+    }
+
     /// Layout the given line of text, at the given font_size.
     /// Subsets of the line can be styled independently with the `runs` parameter.
     /// Generally, you should prefer to use [`Self::shape_line`] instead, which
@@ -857,6 +922,12 @@ pub struct FontMetrics {
     /// The suggested thickness of the underline.
     pub(crate) underline_thickness: f32,
 
+    /// The suggested distance from the baseline to the top of the strikethrough.
+    pub(crate) strikethrough_position: f32,
+
+    /// The suggested thickness of the strikethrough.
+    pub(crate) strikethrough_thickness: f32,
+
     /// The height of a capital letter measured from the baseline of the font.
     pub(crate) cap_height: f32,
 
@@ -894,6 +965,16 @@ impl FontMetrics {
         Pixels((self.underline_thickness / self.units_per_em as f32) * font_size.0)
     }
 
+    /// Returns the suggested distance from the baseline to the top of the strikethrough in pixels.
+    pub fn strikethrough_position(&self, font_size: Pixels) -> Pixels {
+        Pixels((self.strikethrough_position / self.units_per_em as f32) * font_size.0)
+    }
+
+    /// Returns the suggested thickness of the strikethrough in pixels.
+    pub fn strikethrough_thickness(&self, font_size: Pixels) -> Pixels {
+        Pixels((self.strikethrough_thickness / self.units_per_em as f32) * font_size.0)
+    }
+
     /// Returns the height of a capital letter measured from the baseline of the font in pixels.
     pub fn cap_height(&self, font_size: Pixels) -> Pixels {
         Pixels((self.cap_height / self.units_per_em as f32) * font_size.0)
@@ -916,3 +997,53 @@ pub(crate) fn font_name_with_fallbacks<'a>(name: &'a str, system: &'a str) -> &'
         _ => name,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_system_with_fonts() -> TextSystem {
+        let text_system = TextSystem::new(Arc::new(ParleyTextSystem::new()));
+        text_system
+            .add_fonts(vec![
+                Cow::Borrowed(include_bytes!("../assets/fonts/lilex/Lilex-Regular.ttf").as_slice()),
+                Cow::Borrowed(
+                    include_bytes!("../assets/fonts/ibm-plex-sans/IBMPlexSans-Regular.ttf")
+                        .as_slice(),
+                ),
+            ])
+            .unwrap();
+        text_system
+    }
+
+    #[test]
+    fn test_is_monospace() {
+        let text_system = text_system_with_fonts();
+        let monospace = text_system.font_id(&font("Lilex")).unwrap();
+        let proportional = text_system.font_id(&font("IBM Plex Sans")).unwrap();
+
+        assert!(text_system.is_monospace(monospace));
+        assert!(!text_system.is_monospace(proportional));
+    }
+
+    #[test]
+    fn test_ch_advance() {
+        let text_system = text_system_with_fonts();
+        let monospace = text_system.font_id(&font("Lilex")).unwrap();
+        let proportional = text_system.font_id(&font("IBM Plex Sans")).unwrap();
+
+        let ch_advance = text_system.ch_advance(monospace, px(20.)).unwrap();
+        assert!(ch_advance > px(0.));
+        for character in ['i', 'W', ' '] {
+            let advance = text_system.advance(monospace, px(20.), character).unwrap();
+            assert_eq!(
+                advance.width, ch_advance,
+                "{character:?} in a monospace font"
+            );
+        }
+
+        let ch_advance = text_system.ch_advance(proportional, px(20.)).unwrap();
+        let advance = text_system.advance(proportional, px(20.), 'i').unwrap();
+        assert!(advance.width < ch_advance, "'i' in a proportional font");
+    }
+}
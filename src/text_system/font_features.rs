@@ -3,6 +3,8 @@ use std::sync::Arc;
 
 use schemars::{JsonSchema, json_schema};
 
+use crate::TEXT_LOG_TARGET;
+
 /// The OpenType features that can be configured for a given font.
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
 pub struct FontFeatures(pub Arc<Vec<(String, u32)>>);
@@ -75,7 +77,7 @@ impl<'de> serde::Deserialize<'de> for FontFeatures {
                     access.next_entry::<String, Option<FeatureValue>>()?
                 {
                     if !is_valid_feature_tag(&key) {
-                        log::error!("Incorrect font feature tag: {}", key);
+                        log::error!(target: TEXT_LOG_TARGET, "Incorrect font feature tag: {}", key);
                         continue;
                     }
                     if let Some(value) = value {
@@ -92,6 +94,7 @@ impl<'de> serde::Deserialize<'de> for FontFeatures {
                                     feature_list.push((key, value.as_u64().unwrap() as u32));
                                 } else {
                                     log::error!(
+                                        target: TEXT_LOG_TARGET,
                                         "Incorrect font feature value {} for feature tag {}",
                                         value,
                                         key
@@ -1,7 +1,7 @@
 use crate::{
     App, Bounds, Half, Hsla, LineLayout, Pixels, Point, Result, SharedString, StrikethroughStyle,
-    TextAlign, UnderlineStyle, Window, WrapBoundary, WrappedLineLayout, black, fill, point, px,
-    size,
+    TextAlign, UnderlineStyle, Window, WrapBoundary, WrappedLineLayout, black, fill,
+    paint_baseline_guide, point, px, size,
 };
 use derive_more::{Deref, DerefMut};
 use smallvec::SmallVec;
@@ -305,8 +305,7 @@ fn paint_line(
                                 ),
                                 UnderlineStyle {
                                     color: Some(run_underline.color.unwrap_or(style_run.color)),
-                                    thickness: run_underline.thickness,
-                                    wavy: run_underline.wavy,
+                                    ..*run_underline
                                 },
                             ));
                         }
@@ -316,17 +315,31 @@ fn paint_line(
                             finished_strikethrough = current_strikethrough.take();
                         }
                         if let Some(run_strikethrough) = style_run.strikethrough.as_ref() {
-                            current_strikethrough.get_or_insert((
-                                point(
-                                    glyph_origin.x,
-                                    glyph_origin.y
-                                        + (((layout.ascent * 0.5) + baseline_offset.y) * 0.5),
-                                ),
-                                StrikethroughStyle {
-                                    color: Some(run_strikethrough.color.unwrap_or(style_run.color)),
-                                    thickness: run_strikethrough.thickness,
-                                },
-                            ));
+                            current_strikethrough.get_or_insert_with(|| {
+                                let thickness = if run_strikethrough.thickness > px(0.) {
+                                    run_strikethrough.thickness
+                                } else {
+                                    // Small sizes and fonts without a suggested thickness would
+                                    // otherwise get a hairline that doesn't rasterize.
+                                    text_system
+                                        .strikethrough_thickness(run.font_id, layout.font_size)
+                                        .max(px(1.))
+                                };
+                                let position = text_system
+                                    .strikethrough_position(run.font_id, layout.font_size);
+                                (
+                                    point(
+                                        glyph_origin.x,
+                                        glyph_origin.y + baseline_offset.y - position,
+                                    ),
+                                    StrikethroughStyle {
+                                        color: Some(
+                                            run_strikethrough.color.unwrap_or(style_run.color),
+                                        ),
+                                        thickness,
+                                    },
+                                )
+                            });
                         }
 
                         run_end += style_run.len as usize;
@@ -419,6 +432,15 @@ fn paint_line(
             );
         }
 
+        if window.debug_overlay().baselines {
+            let width = align_width.unwrap_or(layout.width);
+            for line_ix in 0..=wrap_boundaries.len() {
+                let baseline =
+                    origin + baseline_offset + point(px(0.), line_height * line_ix as f32);
+                paint_baseline_guide(baseline, width, window);
+            }
+        }
+
         Ok(())
     })
 }
@@ -1,5 +1,9 @@
-use crate::{FontId, GlyphId, Pixels, PlatformTextSystem, Point, SharedString, Size, point, px};
+use crate::{
+    BackgroundExecutor, FontId, GlyphId, Pixels, PlatformTextSystem, Point, SharedString, Size,
+    point, px,
+};
 use collections::FxHashMap;
+use futures::channel::mpsc;
 use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
 use smallvec::SmallVec;
 use std::{
@@ -389,10 +393,113 @@ impl WrappedLineLayout {
     }
 }
 
+/// Lines at least this many bytes long are shaped on the background executor, when the cache
+/// supports it, so that typing in minified files or long log lines doesn't stall frames.
+const BACKGROUND_SHAPING_MIN_LEN: usize = 10_000;
+
+/// Frames a line shaped in the background is kept for without being requested, e.g. because it
+/// was edited while it was being shaped.
+const MAX_UNCLAIMED_FRAMES: u32 = 2;
+
 pub(crate) struct LineLayoutCache {
     previous_frame: Mutex<FrameCache>,
     current_frame: RwLock<FrameCache>,
     platform_text_system: Arc<dyn PlatformTextSystem>,
+    background_shaping: Option<BackgroundShaping>,
+}
+
+/// Long lines being shaped on the background executor, or shaped and waiting to be requested
+/// again by the next frame.
+struct BackgroundShaping {
+    executor: BackgroundExecutor,
+    lines: Arc<Mutex<FxHashMap<Arc<CacheKey>, Option<BackgroundLayout>>>>,
+    lines_shaped: mpsc::UnboundedSender<()>,
+}
+
+struct BackgroundLayout {
+    layout: Arc<LineLayout>,
+    unclaimed_frames: u32,
+}
+
+impl BackgroundShaping {
+    /// Returns the line's layout if it has been shaped, and otherwise starts shaping it unless
+    /// it's already being shaped.
+    fn take_or_start(
+        &self,
+        key: &Arc<CacheKey>,
+        platform_text_system: &Arc<dyn PlatformTextSystem>,
+    ) -> Option<Arc<LineLayout>> {
+        let mut lines = self.lines.lock();
+        match lines.get(key) {
+            Some(Some(_)) => {
+                return lines
+                    .remove(key)
+                    .flatten()
+                    .map(|background_layout| background_layout.layout);
+            }
+            Some(None) => return None,
+            None => {}
+        }
+        lines.insert(key.clone(), None);
+        drop(lines);
+
+        let key = key.clone();
+        let lines = self.lines.clone();
+        let platform_text_system = platform_text_system.clone();
+        let lines_shaped = self.lines_shaped.clone();
+        self.executor
+            .spawn(async move {
+                let layout = shape_line(
+                    platform_text_system.as_ref(),
+                    &key.text,
+                    key.font_size,
+                    &key.runs,
+                    key.force_width,
+                );
+                lines.lock().insert(
+                    key,
+                    Some(BackgroundLayout {
+                        layout: Arc::new(layout),
+                        unclaimed_frames: 0,
+                    }),
+                );
+                lines_shaped.unbounded_send(()).ok();
+            })
+            .detach();
+        None
+    }
+
+    fn finish_frame(&self) {
+        self.lines.lock().retain(|_, line| match line {
+            Some(background_layout) => {
+                background_layout.unclaimed_frames += 1;
+                background_layout.unclaimed_frames <= MAX_UNCLAIMED_FRAMES
+            }
+            None => true,
+        });
+    }
+}
+
+fn shape_line(
+    platform_text_system: &dyn PlatformTextSystem,
+    text: &str,
+    font_size: Pixels,
+    runs: &[FontRun],
+    force_width: Option<Pixels>,
+) -> LineLayout {
+    let mut layout = platform_text_system.layout_line(text, font_size, runs);
+    if let Some(force_width) = force_width {
+        let mut glyph_pos = 0;
+        for run in layout.runs.iter_mut() {
+            for glyph in run.glyphs.iter_mut() {
+                if (glyph.position.x - glyph_pos * force_width).abs() > px(1.) {
+                    glyph.position.x = glyph_pos * force_width;
+                }
+                glyph_pos += 1;
+            }
+        }
+    }
+    layout
 }
 
 #[derive(Default)]
@@ -415,9 +522,27 @@ impl LineLayoutCache {
             previous_frame: Mutex::default(),
             current_frame: RwLock::default(),
             platform_text_system,
+            background_shaping: None,
         }
     }
 
+    /// Creates a cache that shapes long lines on the given executor. Until a line is shaped, it's
+    /// laid out as a placeholder without glyphs whose width is estimated from its font. The
+    /// returned receiver is notified whenever a line finishes shaping, so it can be redrawn.
+    pub fn with_background_shaping(
+        platform_text_system: Arc<dyn PlatformTextSystem>,
+        executor: BackgroundExecutor,
+    ) -> (Self, mpsc::UnboundedReceiver<()>) {
+        let (lines_shaped, lines_shaped_receiver) = mpsc::unbounded();
+        let mut cache = Self::new(platform_text_system);
+        cache.background_shaping = Some(BackgroundShaping {
+            executor,
+            lines: Arc::default(),
+            lines_shaped,
+        });
+        (cache, lines_shaped_receiver)
+    }
+
     pub fn layout_index(&self) -> LineLayoutIndex {
         let frame = self.current_frame.read();
         LineLayoutIndex {
@@ -463,6 +588,20 @@ impl LineLayoutCache {
         curr_frame.wrapped_lines.clear();
         curr_frame.used_lines.clear();
         curr_frame.used_wrapped_lines.clear();
+        if let Some(background_shaping) = &self.background_shaping {
+            background_shaping.finish_frame();
+        }
+    }
+
+    /// Drops the layouts kept from the previous frame and releases the capacity that the current
+    /// frame's cache grew to. Layouts dropped this way are shaped again when next requested.
+    pub fn trim(&self) {
+        *self.previous_frame.lock() = FrameCache::default();
+        let mut curr_frame = self.current_frame.write();
+        curr_frame.lines.shrink_to_fit();
+        curr_frame.wrapped_lines.shrink_to_fit();
+        curr_frame.used_lines.shrink_to_fit();
+        curr_frame.used_wrapped_lines.shrink_to_fit();
     }
 
     pub fn layout_wrapped_line<Text>(
@@ -501,7 +640,8 @@ impl LineLayoutCache {
         } else {
             drop(current_frame);
             let text = SharedString::from(text);
-            let unwrapped_layout = self.layout_line::<&SharedString>(&text, font_size, runs, None);
+            let (unwrapped_layout, shaped) =
+                self.layout_line_if_shaped::<&SharedString>(&text, font_size, runs, None);
             let wrap_boundaries = if let Some(wrap_width) = wrap_width {
                 unwrapped_layout.compute_wrap_boundaries(text.as_ref(), wrap_width, max_lines)
             } else {
@@ -512,6 +652,10 @@ impl LineLayoutCache {
                 wrap_boundaries,
                 wrap_width,
             });
+            // Wrap placeholders again once the line is shaped.
+            if !shaped {
+                return layout;
+            }
             let key = Arc::new(CacheKey {
                 text,
                 font_size,
@@ -537,6 +681,23 @@ impl LineLayoutCache {
         runs: &[FontRun],
         force_width: Option<Pixels>,
     ) -> Arc<LineLayout>
+    where
+        Text: AsRef<str>,
+        SharedString: From<Text>,
+    {
+        self.layout_line_if_shaped(text, font_size, runs, force_width)
+            .0
+    }
+
+    /// Lays out a line, returning whether it was shaped or is a placeholder for a long line that
+    /// is being shaped in the background. Placeholders aren't cached.
+    fn layout_line_if_shaped<Text>(
+        &self,
+        text: Text,
+        font_size: Pixels,
+        runs: &[FontRun],
+        force_width: Option<Pixels>,
+    ) -> (Arc<LineLayout>, bool)
     where
         Text: AsRef<str>,
         SharedString: From<Text>,
@@ -551,45 +712,69 @@ impl LineLayoutCache {
 
         let current_frame = self.current_frame.upgradable_read();
         if let Some(layout) = current_frame.lines.get(key) {
-            return layout.clone();
+            return (layout.clone(), true);
         }
 
         let mut current_frame = RwLockUpgradableReadGuard::upgrade(current_frame);
         if let Some((key, layout)) = self.previous_frame.lock().lines.remove_entry(key) {
             current_frame.lines.insert(key.clone(), layout.clone());
             current_frame.used_lines.push(key);
-            layout
+            (layout, true)
         } else {
-            let text = SharedString::from(text);
-            let mut layout = self
-                .platform_text_system
-                .layout_line(&text, font_size, runs);
-
-            if let Some(force_width) = force_width {
-                let mut glyph_pos = 0;
-                for run in layout.runs.iter_mut() {
-                    for glyph in run.glyphs.iter_mut() {
-                        if (glyph.position.x - glyph_pos * force_width).abs() > px(1.) {
-                            glyph.position.x = glyph_pos * force_width;
-                        }
-                        glyph_pos += 1;
-                    }
-                }
-            }
-
             let key = Arc::new(CacheKey {
-                text,
+                text: SharedString::from(text),
                 font_size,
                 runs: SmallVec::from(runs),
                 wrap_width: None,
                 force_width,
             });
-            let layout = Arc::new(layout);
+            let layout = match &self.background_shaping {
+                Some(background_shaping) if key.text.len() >= BACKGROUND_SHAPING_MIN_LEN => {
+                    match background_shaping.take_or_start(&key, &self.platform_text_system) {
+                        Some(layout) => layout,
+                        None => {
+                            let placeholder = self.placeholder_layout(&key.text, font_size, runs);
+                            return (Arc::new(placeholder), false);
+                        }
+                    }
+                }
+                _ => Arc::new(shape_line(
+                    self.platform_text_system.as_ref(),
+                    &key.text,
+                    font_size,
+                    runs,
+                    force_width,
+                )),
+            };
             current_frame.lines.insert(key.clone(), layout.clone());
             current_frame.used_lines.push(key);
-            layout
+            (layout, true)
         }
     }
+
+    /// Estimates a line's size from the advance of its first font, without shaping it.
+    fn placeholder_layout(&self, text: &str, font_size: Pixels, runs: &[FontRun]) -> LineLayout {
+        let mut layout = LineLayout {
+            font_size,
+            len: text.len(),
+            ..Default::default()
+        };
+        let Some(font_id) = runs.first().map(|run| run.font_id) else {
+            return layout;
+        };
+        let metrics = self.platform_text_system.font_metrics(font_id);
+        let advance = self
+            .platform_text_system
+            .glyph_for_char(font_id, 'n')
+            .and_then(|glyph_id| self.platform_text_system.advance(font_id, glyph_id).ok())
+            .map_or(font_size * 0.5, |advance| {
+                font_size * (advance.width / metrics.units_per_em as f32)
+            });
+        layout.width = advance * text.chars().count() as f32;
+        layout.ascent = metrics.ascent(font_size);
+        layout.descent = -metrics.descent(font_size);
+        layout
+    }
 }
 
 /// A run of text with a single font.
@@ -670,3 +855,69 @@ impl AsCacheKeyRef for CacheKeyRef<'_> {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoopTextSystem, TestDispatcher};
+    use rand::prelude::*;
+
+    fn background_shaping_cache() -> (
+        LineLayoutCache,
+        mpsc::UnboundedReceiver<()>,
+        BackgroundExecutor,
+    ) {
+        let dispatcher = TestDispatcher::new(StdRng::seed_from_u64(0));
+        let executor = BackgroundExecutor::new(Arc::new(dispatcher));
+        let (cache, lines_shaped) = LineLayoutCache::with_background_shaping(
+            Arc::new(NoopTextSystem::new()),
+            executor.clone(),
+        );
+        (cache, lines_shaped, executor)
+    }
+
+    #[test]
+    fn test_long_line_is_shaped_after_the_requesting_frame() {
+        let (cache, mut lines_shaped, executor) = background_shaping_cache();
+        let text = SharedString::from("a".repeat(BACKGROUND_SHAPING_MIN_LEN));
+        let runs = [FontRun {
+            len: text.len(),
+            font_id: FontId(0),
+        }];
+
+        let placeholder = cache.layout_line(&text, px(16.), &runs, None);
+        assert!(placeholder.runs.is_empty());
+        assert!(placeholder.width > px(0.));
+        cache.finish_frame();
+        assert!(lines_shaped.try_next().is_err(), "shaping hasn't run yet");
+
+        executor.run_until_parked();
+        assert_eq!(lines_shaped.try_next().unwrap(), Some(()));
+        let shaped = cache.layout_line(&text, px(16.), &runs, None);
+        assert_eq!(shaped.runs[0].glyphs.len(), text.len());
+    }
+
+    #[test]
+    fn test_shaped_long_line_is_cached() {
+        let (cache, mut lines_shaped, executor) = background_shaping_cache();
+        let text = SharedString::from("a".repeat(BACKGROUND_SHAPING_MIN_LEN));
+        let runs = [FontRun {
+            len: text.len(),
+            font_id: FontId(0),
+        }];
+        cache.layout_line(&text, px(16.), &runs, None);
+        cache.finish_frame();
+        executor.run_until_parked();
+        lines_shaped.try_next().unwrap();
+
+        let shaped = cache.layout_line(&text, px(16.), &runs, None);
+        cache.finish_frame();
+        let cached = cache.layout_line(&text, px(16.), &runs, None);
+        assert!(Arc::ptr_eq(&shaped, &cached));
+        executor.run_until_parked();
+        assert!(
+            lines_shaped.try_next().is_err(),
+            "a cached line isn't shaped again"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{FontId, FontRun, Pixels, PlatformTextSystem, SharedString, TextRun, px};
 use collections::HashMap;
 use std::{borrow::Cow, iter, sync::Arc};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// The GPUI line wrapper, used to wrap lines of text to a given width.
 pub struct LineWrapper {
@@ -52,13 +53,22 @@ impl LineWrapper {
                 let ix = index;
                 index += candidate.len_utf8();
                 let mut new_prev_c = prev_c;
+                let mut continues_cluster = false;
                 let item_width = match candidate {
-                    WrapBoundaryCandidate::Char { character: c } => {
+                    WrapBoundaryCandidate::Char {
+                        character: c,
+                        continues_cluster: continuation,
+                    } => {
                         if c == '\n' {
                             continue;
                         }
 
-                        if Self::is_word_char(c) {
+                        // Combining marks and the rest of a conjunct stay on the line with the
+                        // character they attach to.
+                        continues_cluster = continuation;
+                        if continues_cluster {
+                            // Not a place to wrap.
+                        } else if Self::is_word_char(c) {
                             if prev_c == ' ' && c != ' ' && first_non_whitespace_ix.is_some() {
                                 last_candidate_ix = ix;
                                 last_candidate_width = width;
@@ -97,7 +107,7 @@ impl LineWrapper {
                 };
 
                 width += item_width;
-                if width > wrap_width && ix > last_wrap_ix {
+                if width > wrap_width && ix > last_wrap_ix && !continues_cluster {
                     if let (None, Some(first_non_whitespace_ix)) = (indent, first_non_whitespace_ix)
                     {
                         indent = Some(
@@ -141,15 +151,19 @@ impl LineWrapper {
             .chars()
             .map(|c| self.width_for_char(c))
             .fold(px(0.0), |a, x| a + x);
-        let mut char_indices = line.char_indices();
         let mut truncate_ix = 0;
-        for (ix, c) in char_indices {
+        // Truncate between grapheme clusters, so that combining marks and conjuncts in scripts
+        // like Devanagari and Thai aren't cut off from the characters they attach to.
+        for (ix, cluster) in line.grapheme_indices(true) {
             if width + suffix_width < truncate_width {
                 truncate_ix = ix;
             }
 
-            let char_width = self.width_for_char(c);
-            width += char_width;
+            let cluster_width = cluster
+                .chars()
+                .map(|c| self.width_for_char(c))
+                .fold(px(0.), |a, x| a + x);
+            width += cluster_width;
 
             if width.floor() > truncate_width {
                 let result =
@@ -270,28 +284,39 @@ impl<'a> LineFragment<'a> {
             LineFragment::Text { text } => text,
             LineFragment::Element { .. } => "\0",
         };
-        text.chars().map(move |character| {
-            if let LineFragment::Element { width, len_utf8 } = self {
-                WrapBoundaryCandidate::Element {
-                    width: *width,
-                    len_utf8: *len_utf8,
+        text.graphemes(true)
+            .flat_map(|cluster| cluster.chars().enumerate())
+            .map(move |(cluster_ix, character)| {
+                if let LineFragment::Element { width, len_utf8 } = self {
+                    WrapBoundaryCandidate::Element {
+                        width: *width,
+                        len_utf8: *len_utf8,
+                    }
+                } else {
+                    WrapBoundaryCandidate::Char {
+                        character,
+                        continues_cluster: cluster_ix > 0,
+                    }
                 }
-            } else {
-                WrapBoundaryCandidate::Char { character }
-            }
-        })
+            })
     }
 }
 
 enum WrapBoundaryCandidate {
-    Char { character: char },
-    Element { width: Pixels, len_utf8: usize },
+    Char {
+        character: char,
+        continues_cluster: bool,
+    },
+    Element {
+        width: Pixels,
+        len_utf8: usize,
+    },
 }
 
 impl WrapBoundaryCandidate {
     pub fn len_utf8(&self) -> usize {
         match self {
-            WrapBoundaryCandidate::Char { character } => character.len_utf8(),
+            WrapBoundaryCandidate::Char { character, .. } => character.len_utf8(),
             WrapBoundaryCandidate::Element { len_utf8: len, .. } => *len,
         }
     }
@@ -323,7 +348,9 @@ mod tests {
     fn build_wrapper() -> LineWrapper {
         let dispatcher = TestDispatcher::new(StdRng::seed_from_u64(0));
         let cx = TestAppContext::build(dispatcher, None);
-        let id = cx.text_system().resolve_font(&font(crate::DEFAULT_FONT_FAMILY));
+        let id = cx
+            .text_system()
+            .resolve_font(&font(crate::DEFAULT_FONT_FAMILY));
         LineWrapper::new(id, px(16.), cx.text_system().platform_text_system.clone())
     }
 
@@ -520,6 +547,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_truncate_and_wrap_complex_scripts() {
+        let mut wrapper = build_wrapper();
+
+        // Devanagari with conjuncts and vowel signs, and Thai with tone marks and vowels above and
+        // below the consonants they attach to.
+        for text in ["नमस्ते दुनिया क्षत्रिय संस्कृत", "สวัสดีชาวโลก กำลังทำงาน น้ำแข็ง"]
+        {
+            let cluster_boundaries = text
+                .grapheme_indices(true)
+                .map(|(ix, _)| ix)
+                .chain([text.len()])
+                .collect::<Vec<_>>();
+            for width in [px(20.), px(45.), px(70.), px(95.)] {
+                let runs = generate_test_runs(&[text.len()]);
+                let (result, runs) = wrapper.truncate_line(text.into(), width, "…", &runs);
+                let prefix = result.strip_suffix('…').unwrap();
+                assert!(text.starts_with(prefix));
+                assert!(
+                    cluster_boundaries.contains(&prefix.len()),
+                    "{text:?} truncated inside a cluster: {result:?}"
+                );
+                assert_eq!(runs.first().unwrap().len, result.len());
+
+                for boundary in wrapper.wrap_line(&[LineFragment::text(text)], width) {
+                    assert!(
+                        cluster_boundaries.contains(&boundary.ix),
+                        "{text:?} wrapped inside a cluster at {}",
+                        boundary.ix
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_truncate_multiple_runs() {
         let mut wrapper = build_wrapper();
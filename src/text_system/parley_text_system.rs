@@ -107,6 +107,13 @@ impl PlatformTextSystem for ParleyTextSystem {
             .expect("failed to create font reference");
 
         let metrics = font_ref.metrics(SkriSize::unscaled(), LocationRef::default());
+        let x_height = metrics.x_height.unwrap_or(metrics.ascent * 0.5);
+        let underline_thickness = metrics.underline.map(|d| d.thickness).unwrap_or(0.0);
+        let strikethrough_thickness = metrics
+            .strikeout
+            .map(|d| d.thickness)
+            .filter(|thickness| *thickness > 0.0)
+            .unwrap_or(underline_thickness);
 
         FontMetrics {
             units_per_em: metrics.units_per_em as u32,
@@ -114,9 +121,14 @@ impl PlatformTextSystem for ParleyTextSystem {
             descent: metrics.descent,
             line_gap: metrics.leading,
             underline_position: metrics.underline.map(|d| d.offset).unwrap_or(0.0),
-            underline_thickness: metrics.underline.map(|d| d.thickness).unwrap_or(0.0),
+            underline_thickness,
+            strikethrough_position: metrics
+                .strikeout
+                .map(|d| d.offset)
+                .unwrap_or((x_height + strikethrough_thickness) * 0.5),
+            strikethrough_thickness,
             cap_height: metrics.cap_height.unwrap_or(metrics.ascent),
-            x_height: metrics.x_height.unwrap_or(metrics.ascent * 0.5),
+            x_height,
             bounding_box: metrics
                 .bounds
                 .map(|b| Bounds {
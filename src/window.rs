@@ -2,30 +2,35 @@
 use crate::Inspector;
 use crate::{
     Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
-    AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds, BoxShadow, Capslock,
-    ContentMask, Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
+    AsyncWindowContext, AtlasKey, AtlasStats, AvailableSpace, Background, BorderStyle, Bounds,
+    BoxShadow, Capslock, CaptureAtlas, CapturedSprites, ContentMask, Context, ContrastLevel,
+    Corners, CursorStyle, CustomRenderPrimitive, CustomRenderer, CustomRendererCast,
+    DISTANCE_FIELD_RANGE, DISTANCE_FIELD_SIZE, Decorations, DevicePixels, DispatchActionListener,
     DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
-    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
-    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, Priority, PromptButton,
-    PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams,
-    Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y,
-    ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style, SubscriberSet,
-    Subscription, SystemWindowTab, SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task,
-    TextStyle, TextStyleRefinement, TransformationMatrix, Underline, UnderlineStyle,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
-    WindowOptions, WindowParams, WindowTextSystem, point, prelude::*, px, rems, size,
-    transparent_black,
+    FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Half, Hsla, IconGlow,
+    ImageFilter, InputHandler, InputSource, IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent,
+    Keystroke, KeystrokeEvent, LayoutId, LineLayoutIndex, Modifiers, ModifiersChangedEvent,
+    MonochromeSprite, MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent, NativeViewPlacement,
+    Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
+    PlatformWindow, Point, PolychromeSprite, PostProcessShader, Priority, PromptButton,
+    PromptLevel, Quad, QuadShadow, Render, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X,
+    SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, SceneLayer, SceneStats, ScrollDelta, Shadow,
+    SharedString, Size, StrikethroughStyle, Style, StyleRefinement, SubscriberSet, Subscription,
+    SystemWindowTab, SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task,
+    TelemetryEvent, TextContrast, TextStyle, TextStyleRefinement, TiledQuad, TransformationMatrix,
+    Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControls, WindowDecorations, WindowKind, WindowOptions, WindowParams,
+    WindowSceneRenderer, WindowTextSystem, black, cast_custom_renderer, point, prelude::*, px,
+    record_crash_event, record_crash_scene, rems, size, transparent_black, white,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
 #[cfg(target_os = "macos")]
 use core_video::pixel_buffer::CVPixelBuffer;
 use derive_more::{Deref, DerefMut};
-use futures::FutureExt;
 use futures::channel::oneshot;
+use futures::{FutureExt, StreamExt};
 use itertools::FoldWhile::{Continue, Done};
 use itertools::Itertools;
 use parking_lot::RwLock;
@@ -54,10 +59,38 @@ use util::post_inc;
 use util::{ResultExt, measure};
 use uuid::Uuid;
 
+mod caret_blink;
+mod content_scale;
+mod debug_overlay;
+mod element_budget;
+mod frame_pacing;
+mod kiosk;
 mod prompts;
+mod render_quality;
+mod session;
+mod shadow_cache;
 
 use crate::util::atomic_incr_if_not_zero;
+use caret_blink::CaretBlink;
+use content_scale::{ContentScale, ContentTransform};
+use debug_overlay::low_contrast_marker;
+pub(crate) use debug_overlay::paint_baseline_guide;
+pub use debug_overlay::{DebugOverlay, ElementDebugInfo, ListenerKind};
+pub use element_budget::ElementBudget;
+use element_budget::ElementBudgetTracker;
+pub(crate) use frame_pacing::DEFAULT_JANK_BUDGET;
+use frame_pacing::{FramePacing, FrameTimings};
+pub use frame_pacing::{InputLatency, Jank};
+pub use kiosk::KioskOptions;
+use kiosk::{IdleCursor, Kiosk};
 pub use prompts::*;
+use render_quality::AdaptiveRenderQuality;
+pub use render_quality::{RenderQuality, ShadowQuality};
+pub(crate) use session::WindowSessions;
+pub use session::{WindowSession, WindowSessionState};
+pub(crate) use shadow_cache::ShadowParams;
+
+type ShouldCloseHandler = Rc<dyn Fn(&mut Window, &mut App) -> bool>;
 
 pub(crate) const DEFAULT_WINDOW_SIZE: Size<Pixels> = size(px(1536.), px(864.));
 
@@ -190,6 +223,8 @@ type AnyObserver = Box<dyn FnMut(&mut Window, &mut App) -> bool + 'static>;
 pub(crate) type AnyWindowFocusListener =
     Box<dyn FnMut(&WindowFocusEvent, &mut Window, &mut App) -> bool + 'static>;
 
+type AnyFrameStatsObserver = Box<dyn FnMut(&SceneStats, &mut Window, &mut App) -> bool + 'static>;
+
 pub(crate) struct WindowFocusEvent {
     pub(crate) previous_focus_path: SmallVec<[FocusId; 8]>,
     pub(crate) current_focus_path: SmallVec<[FocusId; 8]>,
@@ -470,6 +505,44 @@ type FrameCallback = Box<dyn FnOnce(&mut Window, &mut App)>;
 pub(crate) type AnyMouseListener =
     Box<dyn FnMut(&dyn Any, DispatchPhase, &mut Window, &mut App) + 'static>;
 
+/// An input event that no element handled, forwarded to a region registered with
+/// [`Window::on_raw_input`].
+#[derive(Clone, Debug)]
+pub struct RawInputEvent {
+    /// The event as it was dispatched to the window.
+    pub input: PlatformInput,
+    /// How far the pointer moved since the previous mouse event, in logical pixels. Zero for
+    /// keyboard events.
+    pub mouse_delta: Point<Pixels>,
+    /// The platform's code for the physical key of key down and key up events. See
+    /// [`crate::KeyDownEvent::scan_code`].
+    pub scan_code: Option<u32>,
+}
+
+pub(crate) type RawInputListener = Rc<dyn Fn(&RawInputEvent, &mut Window, &mut App)>;
+
+#[derive(Clone)]
+pub(crate) struct RawInputRegion {
+    pub(crate) hitbox_id: HitboxId,
+    pub(crate) focus_handle: Option<FocusHandle>,
+    pub(crate) listener: RawInputListener,
+}
+
+impl RawInputEvent {
+    pub(crate) fn new(input: &PlatformInput, mouse_delta: Point<Pixels>) -> Self {
+        let scan_code = match input {
+            PlatformInput::KeyDown(event) => event.scan_code,
+            PlatformInput::KeyUp(event) => event.scan_code,
+            _ => None,
+        };
+        Self {
+            input: input.clone(),
+            mouse_delta,
+            scan_code,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct CursorStyleRequest {
     pub(crate) hitbox_id: Option<HitboxId>,
@@ -660,6 +733,31 @@ pub(crate) struct TooltipRequest {
     tooltip: AnyTooltip,
 }
 
+/// A view painted in a pop-up window outside of its window, in that window's content coordinates.
+#[derive(Clone)]
+pub(crate) struct PopupRequest {
+    view: AnyView,
+    bounds: Bounds<Pixels>,
+}
+
+/// An open pop-up window, showing the view of a [`PopupRequest`].
+pub(crate) struct Popup {
+    pub(crate) handle: WindowHandle<PopupRoot>,
+    view_id: EntityId,
+    bounds: Bounds<Pixels>,
+}
+
+/// The root view of a pop-up window, which renders the view it was opened for.
+pub(crate) struct PopupRoot {
+    view: AnyView,
+}
+
+impl Render for PopupRoot {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.view.clone()
+    }
+}
+
 pub(crate) struct DeferredDraw {
     current_view: EntityId,
     priority: usize,
@@ -672,6 +770,14 @@ pub(crate) struct DeferredDraw {
     paint_range: Range<PaintIndex>,
 }
 
+#[derive(Clone)]
+pub(crate) struct SubsurfacePlacement {
+    view: AnyView,
+    bounds: Bounds<Pixels>,
+    content_mask: ContentMask<Pixels>,
+    frame_rate: Option<u32>,
+}
+
 pub(crate) struct Frame {
     pub(crate) focus: Option<FocusId>,
     pub(crate) window_active: bool,
@@ -681,11 +787,17 @@ pub(crate) struct Frame {
     pub(crate) dispatch_tree: DispatchTree,
     pub(crate) scene: Scene,
     pub(crate) hitboxes: Vec<Hitbox>,
+    pub(crate) element_hitboxes: Vec<(GlobalElementId, Hitbox)>,
+    pub(crate) hitbox_listeners: Vec<(HitboxId, SmallVec<[ListenerKind; 4]>)>,
     pub(crate) window_control_hitboxes: Vec<(WindowControlArea, Hitbox)>,
     pub(crate) deferred_draws: Vec<DeferredDraw>,
+    pub(crate) subsurfaces: Vec<SubsurfacePlacement>,
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
+    pub(crate) native_views: Vec<NativeViewPlacement>,
+    pub(crate) popups: Vec<PopupRequest>,
+    pub(crate) raw_input_regions: Vec<RawInputRegion>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -698,8 +810,11 @@ pub(crate) struct Frame {
 #[derive(Clone, Default)]
 pub(crate) struct PrepaintStateIndex {
     hitboxes_index: usize,
+    element_hitboxes_index: usize,
+    hitbox_listeners_index: usize,
     tooltips_index: usize,
     deferred_draws_index: usize,
+    subsurfaces_index: usize,
     dispatch_tree_index: usize,
     accessed_element_states_index: usize,
     line_layout_index: LineLayoutIndex,
@@ -711,6 +826,9 @@ pub(crate) struct PaintIndex {
     mouse_listeners_index: usize,
     input_handlers_index: usize,
     cursor_styles_index: usize,
+    native_views_index: usize,
+    popups_index: usize,
+    raw_input_regions_index: usize,
     accessed_element_states_index: usize,
     tab_handle_index: usize,
     line_layout_index: LineLayoutIndex,
@@ -727,11 +845,17 @@ impl Frame {
             dispatch_tree,
             scene: Scene::default(),
             hitboxes: Vec::new(),
+            element_hitboxes: Vec::new(),
+            hitbox_listeners: Vec::new(),
             window_control_hitboxes: Vec::new(),
             deferred_draws: Vec::new(),
+            subsurfaces: Vec::new(),
             input_handlers: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
+            native_views: Vec::new(),
+            popups: Vec::new(),
+            raw_input_regions: Vec::new(),
 
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
@@ -754,9 +878,15 @@ impl Frame {
         self.input_handlers.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
+        self.native_views.clear();
+        self.popups.clear();
+        self.raw_input_regions.clear();
         self.hitboxes.clear();
+        self.element_hitboxes.clear();
+        self.hitbox_listeners.clear();
         self.window_control_hitboxes.clear();
         self.deferred_draws.clear();
+        self.subsurfaces.clear();
         self.tab_stops.clear();
         self.focus = None;
 
@@ -823,9 +953,66 @@ impl Frame {
     }
 }
 
+/// An input event captured in an [`InputRecording`].
+#[derive(Clone, Debug)]
+pub struct RecordedInput {
+    /// How long after the recording started the event was dispatched.
+    pub offset: Duration,
+    /// The event, as the platform delivered it to the window.
+    pub input: PlatformInput,
+}
+
+/// Keystrokes and mouse events captured from a window with
+/// [`Window::start_input_recording`](crate::Window::start_input_recording), which can be played
+/// back with [`Window::replay_input`](crate::Window::replay_input) to reproduce an interaction or
+/// run a demo.
+#[derive(Clone, Debug, Default)]
+pub struct InputRecording {
+    /// The recorded events, in the order they were dispatched.
+    pub events: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    /// How long the recording lasts, up to its last event.
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map_or(Duration::ZERO, |event| event.offset)
+    }
+}
+
+pub(crate) struct InputRecorder {
+    started_at: Instant,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    pub(crate) fn new(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            recording: InputRecording::default(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, now: Instant, input: &PlatformInput) {
+        self.recording.events.push(RecordedInput {
+            offset: now.saturating_duration_since(self.started_at),
+            input: input.clone(),
+        });
+    }
+
+    pub(crate) fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// How the user last interacted with a window, which decides whether focus indicators are
+/// shown. See [`Window::last_input_modality`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
-enum InputModality {
-    Mouse,
+pub enum InputModality {
+    /// The user last pressed a mouse, trackpad or pen button.
+    Pointer,
+    /// The user last pressed a key.
     Keyboard,
 }
 
@@ -837,6 +1024,7 @@ pub struct Window {
     pub(crate) platform_window: Box<dyn PlatformWindow>,
     display_id: Option<DisplayId>,
     sprite_atlas: Arc<dyn PlatformAtlas>,
+    scene_renderer: Option<WindowSceneRenderer>,
     text_system: Arc<WindowTextSystem>,
     rem_size: Pixels,
     /// The stack of override values for the window's rem size.
@@ -852,6 +1040,7 @@ pub struct Window {
     pub(crate) rendered_entity_stack: Vec<EntityId>,
     pub(crate) element_offset_stack: Vec<Point<Pixels>>,
     pub(crate) element_opacity: f32,
+    element_transformation: TransformationMatrix,
     pub(crate) content_mask_stack: Vec<ContentMask<Pixels>>,
     pub(crate) requested_autoscroll: Option<Bounds<Pixels>>,
     pub(crate) image_cache_stack: Vec<AnyImageCache>,
@@ -870,14 +1059,43 @@ pub struct Window {
     modifiers: Modifiers,
     capslock: Capslock,
     scale_factor: f32,
+    rasterization_scale: f32,
+    render_quality: RenderQuality,
+    adaptive_render_quality: Option<AdaptiveRenderQuality>,
+    text_contrast: Option<TextContrast>,
+    frame_timings: FrameTimings,
+    element_budget: Option<ElementBudgetTracker>,
+    content_scale: ContentScale,
+    content_transform: ContentTransform,
+    debug_overlay: DebugOverlay,
+    contrast_backgrounds: Vec<Hsla>,
+    kiosk: Option<Kiosk>,
+    kiosk_close_request: Option<ShouldCloseHandler>,
+    should_close_handler: RefCell<Option<ShouldCloseHandler>>,
     pub(crate) bounds_observers: SubscriberSet<(), AnyObserver>,
     appearance: WindowAppearance,
+    appearance_override: Option<WindowAppearance>,
     pub(crate) appearance_observers: SubscriberSet<(), AnyObserver>,
+    frame_stats_observers: SubscriberSet<(), AnyFrameStatsObserver>,
     active: Rc<Cell<bool>>,
     hovered: Rc<Cell<bool>>,
     pub(crate) needs_present: Rc<Cell<bool>>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
+    input_timestamp: Option<Instant>,
+    undrawn_input_timestamp: Option<Instant>,
+    unpresented_input_timestamp: Option<Instant>,
+    frame_pacing: Rc<FramePacing>,
+    caret_blink: CaretBlink,
+    subsurface_frame_tasks: FxHashMap<EntityId, (u32, Task<()>)>,
+    root_view_cached: bool,
+    background_appearance: Cell<WindowBackgroundAppearance>,
+    reduce_transparency: bool,
     last_input_modality: InputModality,
+    input_source: InputSource,
+    input_source_filter: Option<Box<dyn Fn(InputSource) -> bool>>,
+    input_recorder: Option<InputRecorder>,
+    pub(crate) popups: Vec<Popup>,
+    raw_input_capture: Option<RawInputListener>,
     pub(crate) refreshing: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
@@ -1006,8 +1224,17 @@ impl Window {
             window_decorations,
             #[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
             tabbing_identifier,
+            session_id,
+            color_space,
         } = options;
 
+        let (window_bounds, display_id) = match session_id
+            .as_ref()
+            .and_then(|session_id| WindowSessions::restore(session_id, cx))
+        {
+            Some((bounds, restored_display_id)) => (Some(bounds), restored_display_id),
+            None => (window_bounds, display_id),
+        };
         let window_bounds = window_bounds.unwrap_or_else(|| default_bounds(display_id, cx));
         let mut platform_window = cx.platform.open_window(
             handle,
@@ -1024,6 +1251,7 @@ impl Window {
                 window_min_size,
                 #[cfg(target_os = "macos")]
                 tabbing_identifier,
+                color_space,
             },
         )?;
 
@@ -1034,24 +1262,53 @@ impl Window {
         }
 
         let display_id = platform_window.display().map(|display| display.id());
-        let sprite_atlas = platform_window.sprite_atlas();
+        let scene_renderer = cx
+            .scene_renderer_factory
+            .clone()
+            .and_then(|build_renderer| {
+                WindowSceneRenderer::new(&*build_renderer, &*platform_window)
+                    .context("failed to build scene renderer")
+                    .log_err()
+            });
+        let sprite_atlas = match &scene_renderer {
+            Some(scene_renderer) => scene_renderer.sprite_atlas(),
+            None => platform_window.sprite_atlas(),
+        };
         let mouse_position = platform_window.mouse_position();
         let modifiers = platform_window.modifiers();
         let capslock = platform_window.capslock();
         let content_size = platform_window.content_size();
         let scale_factor = platform_window.scale_factor();
         let appearance = platform_window.appearance();
-        let text_system = Arc::new(WindowTextSystem::new(cx.text_system().clone()));
+        let (text_system, mut lines_shaped) = WindowTextSystem::with_background_shaping(
+            cx.text_system().clone(),
+            cx.background_executor().clone(),
+        );
+        let text_system = Arc::new(text_system);
+        cx.spawn(async move |cx| {
+            while lines_shaped.next().await.is_some() {
+                if handle.update(cx, |_, window, _| window.refresh()).is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
         let invalidator = WindowInvalidator::new();
         let active = Rc::new(Cell::new(platform_window.is_active()));
         let hovered = Rc::new(Cell::new(platform_window.is_hovered()));
         let needs_present = Rc::new(Cell::new(false));
         let next_frame_callbacks: Rc<RefCell<Vec<FrameCallback>>> = Default::default();
         let last_input_timestamp = Rc::new(Cell::new(Instant::now()));
+        let frame_pacing = Rc::new(FramePacing::default());
 
         platform_window
             .request_decorations(window_decorations.unwrap_or(WindowDecorations::Server));
-        platform_window.set_background_appearance(window_background);
+        let reduce_transparency = cx.should_reduce_transparency();
+        platform_window.set_background_appearance(if reduce_transparency {
+            window_background.with_reduced_transparency()
+        } else {
+            window_background
+        });
 
         match window_bounds {
             WindowBounds::Fullscreen(_) => platform_window.toggle_fullscreen(),
@@ -1076,7 +1333,19 @@ impl Window {
             let needs_present = needs_present.clone();
             let next_frame_callbacks = next_frame_callbacks.clone();
             let last_input_timestamp = last_input_timestamp.clone();
+            let frame_pacing = frame_pacing.clone();
             move |request_frame_options| {
+                let now = Instant::now();
+                if !request_frame_options.force_render
+                    && !request_frame_options.require_presentation
+                    && frame_pacing.should_skip_frame(active.get(), now)
+                {
+                    handle
+                        .update(&mut cx, |_, window, _| window.complete_frame())
+                        .log_err();
+                    return;
+                }
+
                 let next_frame_callbacks = next_frame_callbacks.take();
                 if !next_frame_callbacks.is_empty() {
                     handle
@@ -1096,19 +1365,33 @@ impl Window {
                         && last_input_timestamp.get().elapsed() < Duration::from_secs(1));
 
                 if invalidator.is_dirty() || request_frame_options.force_render {
+                    frame_pacing.record_frame(now);
                     measure("frame duration", || {
                         handle
                             .update(&mut cx, |_, window, cx| {
+                                let frame_start = Instant::now();
                                 let arena_clear_needed = window.draw(cx);
+                                let present_start = Instant::now();
                                 window.present();
+                                let present_time = present_start.elapsed();
                                 // drop the arena elements after present to reduce latency
                                 arena_clear_needed.clear();
+                                let frame_time = frame_start.elapsed();
+                                window.record_frame_time(frame_time, cx);
+                                window.report_jank(frame_time, present_time, cx);
+                                window.report_input_latency(cx);
                             })
                             .log_err();
                     })
                 } else if needs_present {
+                    frame_pacing.record_frame(now);
                     handle
-                        .update(&mut cx, |_, window, _| window.present())
+                        .update(&mut cx, |_, window, cx| {
+                            window.present();
+                            window.report_input_latency(cx);
+                            // Input that didn't change what's drawn has no latency to report.
+                            window.undrawn_input_timestamp = None;
+                        })
                         .log_err();
                 }
 
@@ -1135,6 +1418,14 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_should_close(Box::new({
+            let mut cx = cx.to_async();
+            move || {
+                handle
+                    .update(&mut cx, |_, window, cx| window.should_close(cx))
+                    .unwrap_or(true)
+            }
+        }));
         platform_window.on_appearance_changed(Box::new({
             let mut cx = cx.to_async();
             move || {
@@ -1156,6 +1447,7 @@ impl Window {
                             .clone()
                             .retain(&(), |callback| callback(window, cx));
 
+                        window.update_reduce_transparency(cx);
                         window.bounds_changed(cx);
                         window.refresh();
 
@@ -1258,6 +1550,19 @@ impl Window {
 
         platform_window.map_window().unwrap();
 
+        let bounds_observers = SubscriberSet::new();
+        if let Some(session_id) = session_id {
+            let (subscription, activate) = bounds_observers.insert(
+                (),
+                Box::new(move |window: &mut Window, cx: &mut App| {
+                    WindowSessions::save_window(&session_id, window, cx);
+                    true
+                }) as AnyObserver,
+            );
+            activate();
+            subscription.detach();
+        }
+
         Ok(Window {
             handle,
             invalidator,
@@ -1265,6 +1570,7 @@ impl Window {
             platform_window,
             display_id,
             sprite_atlas,
+            scene_renderer,
             text_system,
             rem_size: px(16.),
             rem_size_override_stack: SmallVec::new(),
@@ -1277,6 +1583,7 @@ impl Window {
             element_offset_stack: Vec::new(),
             content_mask_stack: Vec::new(),
             element_opacity: 1.0,
+            element_transformation: TransformationMatrix::unit(),
             requested_autoscroll: None,
             rendered_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
             next_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
@@ -1293,14 +1600,43 @@ impl Window {
             modifiers,
             capslock,
             scale_factor,
-            bounds_observers: SubscriberSet::new(),
+            rasterization_scale: scale_factor,
+            render_quality: RenderQuality::FULL,
+            adaptive_render_quality: None,
+            text_contrast: None,
+            frame_timings: FrameTimings::default(),
+            element_budget: None,
+            content_scale: ContentScale::default(),
+            content_transform: ContentTransform::IDENTITY,
+            debug_overlay: DebugOverlay::default(),
+            contrast_backgrounds: Vec::new(),
+            kiosk: None,
+            kiosk_close_request: None,
+            should_close_handler: RefCell::new(None),
+            bounds_observers,
             appearance,
+            appearance_override: None,
             appearance_observers: SubscriberSet::new(),
+            frame_stats_observers: SubscriberSet::new(),
             active,
             hovered,
             needs_present,
             last_input_timestamp,
-            last_input_modality: InputModality::Mouse,
+            input_timestamp: None,
+            undrawn_input_timestamp: None,
+            unpresented_input_timestamp: None,
+            frame_pacing,
+            caret_blink: CaretBlink::new(Instant::now()),
+            subsurface_frame_tasks: FxHashMap::default(),
+            root_view_cached: false,
+            background_appearance: Cell::new(window_background),
+            reduce_transparency,
+            last_input_modality: InputModality::Pointer,
+            input_source: InputSource::Platform,
+            input_source_filter: None,
+            input_recorder: None,
+            popups: Vec::new(),
+            raw_input_capture: None,
             refreshing: false,
             activation_observers: SubscriberSet::new(),
             focus: None,
@@ -1324,22 +1660,41 @@ impl Window {
     }
 }
 
+/// The outcome of dispatching an event to a window.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub(crate) struct DispatchEventResult {
+pub struct DispatchEventResult {
+    /// Whether the event should continue on to the platform, because no handler stopped it.
     pub propagate: bool,
+    /// Whether a handler asked for the platform's default behavior to be skipped.
     pub default_prevented: bool,
 }
 
 impl Window {
     fn mark_view_dirty(&mut self, view_id: EntityId) {
         // Mark ancestor views as dirty. If already in the `dirty_views` set, then all its ancestors
-        // should already be dirty.
-        for view_id in self
+        // should already be dirty. Subsurface views are redrawn on their own, so their ancestors
+        // aren't marked.
+        let mut view_path = self
             .rendered_frame
             .dispatch_tree
             .view_path_reversed(view_id)
-        {
-            if !self.dirty_views.insert(view_id) {
+            .peekable();
+        if view_path.peek().is_none() {
+            // Entities that aren't rendered views may have been read by any view, so the root is
+            // redrawn.
+            if let Some(root) = self.root.as_ref() {
+                self.dirty_views.insert(root.entity_id());
+            }
+            return;
+        }
+        for view_id in view_path {
+            if !self.dirty_views.insert(view_id)
+                || self
+                    .rendered_frame
+                    .subsurfaces
+                    .iter()
+                    .any(|placement| placement.view.entity_id() == view_id)
+            {
                 break;
             }
         }
@@ -1361,6 +1716,23 @@ impl Window {
         subscription
     }
 
+    /// Registers a callback to be invoked with the primitive and batch counts of each frame the
+    /// window draws. Useful for finding out why a frame is slow to render.
+    pub fn on_frame_stats(
+        &self,
+        mut callback: impl FnMut(&SceneStats, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.frame_stats_observers.insert(
+            (),
+            Box::new(move |stats, window, cx| {
+                callback(stats, window, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Replaces the root entity of the window with a new one.
     pub fn replace_root<E>(
         &mut self,
@@ -1723,22 +2095,177 @@ impl Window {
     }
 
     fn bounds_changed(&mut self, cx: &mut App) {
-        self.scale_factor = self.platform_window.scale_factor();
-        self.viewport_size = self.platform_window.content_size();
+        self.update_scale_factor();
+        self.update_viewport_size();
         self.display_id = self.platform_window.display().map(|display| display.id());
+        if let Some(scene_renderer) = &mut self.scene_renderer {
+            scene_renderer.resize(
+                self.platform_window
+                    .content_size()
+                    .to_device_pixels(self.platform_window.scale_factor()),
+            );
+        }
 
         self.refresh();
+        self.reposition_popups(cx);
 
         self.bounds_observers
             .clone()
             .retain(&(), |callback| callback(self, cx));
     }
 
+    /// Returns how much of the atlas that this window's glyphs, icons and images are rasterized
+    /// into is in use. Platforms that don't report atlas usage return empty stats.
+    pub fn sprite_atlas_stats(&self) -> AtlasStats {
+        self.sprite_atlas.stats()
+    }
+
+    /// Frees memory held by this window's caches that are cheap to rebuild: sprites that
+    /// haven't been drawn recently, and spare line layout capacity.
+    pub(crate) fn trim_caches(&self) {
+        self.sprite_atlas.trim();
+        self.text_system.trim_layout_cache();
+    }
+
+    /// Captures the pixels of the next frame the window draws, for visual regression tests or
+    /// exporting the window as an image. The window is redrawn, and the capture resolves once the
+    /// GPU has finished rendering the frame and its pixels have been read back.
+    ///
+    /// A window drawn by a [`SceneRenderer`](crate::SceneRenderer) instead captures the last
+    /// frame the renderer drew, if it supports capturing frames. See [`App::set_scene_renderer`].
+    pub fn capture_frame(&mut self) -> oneshot::Receiver<Result<image::RgbaImage>> {
+        let Some(scene_renderer) = self.scene_renderer.as_ref() else {
+            self.refresh();
+            return self.platform_window.capture_frame();
+        };
+        let (sender, receiver) = oneshot::channel();
+        sender
+            .send(
+                scene_renderer
+                    .capture()
+                    .context("the window's scene renderer doesn't support capturing frames"),
+            )
+            .ok();
+        receiver
+    }
+
     /// Returns the bounds of the current window in the global coordinate space, which could span across multiple displays.
     pub fn bounds(&self) -> Bounds<Pixels> {
         self.platform_window.bounds()
     }
 
+    /// Maps bounds in this window's content coordinates, such as an element's bounds, to the
+    /// global coordinate space of [`Self::bounds`]. Returns `None` on platforms that don't let
+    /// windows be placed in that space, such as Wayland.
+    pub fn content_to_screen_bounds(&self, bounds: Bounds<Pixels>) -> Option<Bounds<Pixels>> {
+        let content_origin = self.platform_window.content_origin()?;
+        let bounds = self.content_to_window_bounds(bounds);
+        Some(Bounds::new(content_origin + bounds.origin, bounds.size))
+    }
+
+    /// Returns the part of this window's display that pop-ups may cover, in content coordinates,
+    /// or `None` if the platform can't place pop-ups on it.
+    pub(crate) fn popup_limits(&self) -> Option<Bounds<Pixels>> {
+        let content_origin = self.platform_window.content_origin()?;
+        let display_bounds = self.platform_window.display()?.visible_bounds();
+        let scale = self.content_transform.scale;
+        Some(Bounds::new(
+            (display_bounds.origin - content_origin).map(|coordinate| coordinate / scale),
+            display_bounds.size.map(|length| length / scale),
+        ))
+    }
+
+    /// Opens, moves and closes pop-up windows to match the pop-ups painted in the latest frame.
+    fn update_popups(&mut self, cx: &mut App) {
+        let requests = self.rendered_frame.popups.clone();
+        let mut popups = mem::take(&mut self.popups);
+        popups.retain(|popup| {
+            let requested = requests
+                .iter()
+                .any(|request| request.view.entity_id() == popup.view_id);
+            if !requested {
+                popup
+                    .handle
+                    .update(cx, |_, popup, _| popup.remove_window())
+                    .ok();
+            }
+            requested && cx.windows.get(popup.handle.window_id()).is_some()
+        });
+
+        let content_scale = self.content_scale();
+        for request in requests {
+            let Some(screen_bounds) = self.content_to_screen_bounds(request.bounds) else {
+                continue;
+            };
+            let view_id = request.view.entity_id();
+            if let Some(popup) = popups.iter_mut().find(|popup| popup.view_id == view_id) {
+                let moved = popup.bounds != request.bounds;
+                popup.bounds = request.bounds;
+                popup
+                    .handle
+                    .update(cx, |_, popup, _| {
+                        if moved {
+                            popup.platform_window.set_bounds(screen_bounds);
+                        }
+                        if popup.content_scale() != content_scale {
+                            popup.set_content_scale(content_scale);
+                        }
+                    })
+                    .ok();
+            } else if let Some(handle) = self.open_popup(request.view, screen_bounds, cx).log_err()
+            {
+                popups.push(Popup {
+                    handle,
+                    view_id,
+                    bounds: request.bounds,
+                });
+            }
+        }
+        self.popups = popups;
+    }
+
+    /// Opens a borderless pop-up window showing `view` at `bounds` in the global coordinate space.
+    /// The pop-up doesn't take focus, and is closed along with this window.
+    fn open_popup(
+        &self,
+        view: AnyView,
+        bounds: Bounds<Pixels>,
+        cx: &mut App,
+    ) -> Result<WindowHandle<PopupRoot>> {
+        let options = WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(bounds)),
+            titlebar: None,
+            focus: false,
+            kind: WindowKind::PopUp,
+            is_movable: false,
+            is_resizable: false,
+            is_minimizable: false,
+            display_id: self.display_id,
+            window_background: WindowBackgroundAppearance::Transparent,
+            window_decorations: Some(WindowDecorations::Client),
+            ..Default::default()
+        };
+        let content_scale = self.content_scale();
+        cx.open_window(options, |window, cx| {
+            window.set_content_scale(content_scale);
+            cx.new(|_| PopupRoot { view })
+        })
+    }
+
+    /// Moves the pop-ups along with this window, keeping them over the same content.
+    fn reposition_popups(&mut self, cx: &mut App) {
+        for popup in &self.popups {
+            if let Some(screen_bounds) = self.content_to_screen_bounds(popup.bounds) {
+                popup
+                    .handle
+                    .update(cx, |_, popup, _| {
+                        popup.platform_window.set_bounds(screen_bounds)
+                    })
+                    .ok();
+            }
+        }
+    }
+
     /// Set the content size of the window.
     pub fn resize(&mut self, size: Size<Pixels>) {
         self.platform_window.resize(size);
@@ -1749,129 +2276,729 @@ impl Window {
         self.platform_window.is_fullscreen()
     }
 
-    pub(crate) fn appearance_changed(&mut self, cx: &mut App) {
-        self.appearance = self.platform_window.appearance();
-
-        self.appearance_observers
-            .clone()
-            .retain(&(), |callback| callback(self, cx));
+    /// Returns whether the window is in kiosk mode.
+    pub fn is_kiosk_mode(&self) -> bool {
+        self.kiosk.is_some()
     }
 
-    /// Returns the appearance of the current window.
-    pub fn appearance(&self) -> WindowAppearance {
-        self.appearance
+    /// Puts the window in kiosk mode, for installations and dashboards. The cursor is hidden
+    /// while the pointer is still, system gestures that switch to other applications are
+    /// disabled where the platform allows it, and the window can only be closed when the
+    /// callback given to [`Window::on_kiosk_close_request`] allows it.
+    ///
+    /// This doesn't change the window's size or fullscreen state; open the window with
+    /// [`App::open_kiosk_window`] to fill a display.
+    pub fn enter_kiosk_mode(&mut self, options: KioskOptions, cx: &mut App) {
+        self.exit_kiosk_mode(cx);
+        if options.disable_system_gestures
+            && !self.platform_window.set_system_gestures_enabled(false)
+        {
+            log::info!("system gestures can't be disabled on this platform");
+        }
+        let idle_cursor = options
+            .hide_cursor_after
+            .map(|hide_after| IdleCursor::new(hide_after, Instant::now()));
+        let hide_cursor_task = idle_cursor.is_some().then(|| {
+            self.spawn(cx, async move |cx| {
+                while let Ok(Some(delay)) = cx.update(|window, cx| window.hide_idle_cursor(cx)) {
+                    cx.background_executor().timer(delay).await;
+                }
+            })
+        });
+        self.kiosk = Some(Kiosk {
+            options,
+            idle_cursor,
+            _hide_cursor_task: hide_cursor_task,
+        });
     }
 
-    /// Returns the size of the drawable area within the window.
-    pub fn viewport_size(&self) -> Size<Pixels> {
-        self.viewport_size
+    /// Leaves kiosk mode, showing the cursor and restoring system gestures. The window stays
+    /// fullscreen until [`Window::toggle_fullscreen`] is called.
+    pub fn exit_kiosk_mode(&mut self, cx: &mut App) {
+        let Some(kiosk) = self.kiosk.take() else {
+            return;
+        };
+        if kiosk.options.disable_system_gestures {
+            self.platform_window.set_system_gestures_enabled(true);
+        }
+        if kiosk
+            .idle_cursor
+            .is_some_and(|idle_cursor| idle_cursor.is_hidden())
+        {
+            self.reset_cursor_style(cx);
+        }
     }
 
-    /// Returns whether this window is focused by the operating system (receiving key events).
-    pub fn is_window_active(&self) -> bool {
-        self.active.get()
+    /// Registers a callback that decides whether the window may be closed while it's in kiosk
+    /// mode, replacing any previous one. Without a callback, closing the window is always
+    /// blocked in kiosk mode. The callback can return false and show a confirmation of its own,
+    /// then call [`Window::exit_kiosk_mode`] and [`Window::remove_window`] once it's confirmed.
+    pub fn on_kiosk_close_request(&mut self, f: impl Fn(&mut Window, &mut App) -> bool + 'static) {
+        self.kiosk_close_request = Some(Rc::new(f));
     }
 
-    /// Returns whether this window is considered to be the window
-    /// that currently owns the mouse cursor.
-    /// On mac, this is equivalent to `is_window_active`.
-    pub fn is_window_hovered(&self) -> bool {
-        if cfg!(any(
-            target_os = "windows",
-            target_os = "linux",
-            target_os = "freebsd"
-        )) {
-            self.hovered.get()
-        } else {
-            self.is_window_active()
+    fn hide_idle_cursor(&mut self, cx: &mut App) -> Option<Duration> {
+        let idle_cursor = self.kiosk.as_mut()?.idle_cursor.as_mut()?;
+        let (hidden, delay) = idle_cursor.poll(Instant::now());
+        if hidden {
+            self.reset_cursor_style(cx);
         }
+        Some(delay)
     }
 
-    /// Toggle zoom on the window.
-    pub fn zoom_window(&self) {
-        self.platform_window.zoom();
+    fn is_cursor_hidden(&self) -> bool {
+        self.kiosk
+            .as_ref()
+            .and_then(|kiosk| kiosk.idle_cursor.as_ref())
+            .is_some_and(IdleCursor::is_hidden)
     }
 
-    /// Opens the native title bar context menu, useful when implementing client side decorations (Wayland and X11)
-    pub fn show_window_menu(&self, position: Point<Pixels>) {
-        self.platform_window.show_window_menu(position)
+    fn should_close(&mut self, cx: &mut App) -> bool {
+        let should_close_handler = self.should_close_handler.borrow().clone();
+        if should_close_handler.is_some_and(|should_close| !should_close(self, cx)) {
+            return false;
+        }
+        let Some(disable_system_gestures) = self
+            .kiosk
+            .as_ref()
+            .map(|kiosk| kiosk.options.disable_system_gestures)
+        else {
+            return true;
+        };
+        let close_request = self.kiosk_close_request.clone();
+        if !close_request.is_some_and(|close_request| close_request(self, cx)) {
+            return false;
+        }
+        if disable_system_gestures {
+            self.platform_window.set_system_gestures_enabled(true);
+        }
+        true
     }
 
-    /// Handle window movement for Linux and macOS.
-    /// Tells the compositor to take control of window movement (Wayland and X11)
-    ///
-    /// Events may not be received during a move operation.
-    pub fn start_window_move(&self) {
-        self.platform_window.start_window_move()
+    pub(crate) fn appearance_changed(&mut self, cx: &mut App) {
+        self.appearance = self.platform_window.appearance();
+
+        // The system appearance doesn't show through an override.
+        if self.appearance_override.is_none() {
+            self.appearance_observers
+                .clone()
+                .retain(&(), |callback| callback(self, cx));
+        }
     }
 
-    /// When using client side decorations, set this to the width of the invisible decorations (Wayland and X11)
-    pub fn set_client_inset(&mut self, inset: Pixels) {
-        self.client_inset = Some(inset);
-        self.platform_window.set_client_inset(inset);
+    /// Makes this window use the given appearance regardless of the system's, such as to keep a
+    /// video preview dark while other windows follow the system. Pass `None` to follow the system
+    /// again. Observers registered with [`Self::observe_window_appearance`] are notified if this
+    /// changes the window's appearance.
+    pub fn set_appearance_override(&mut self, appearance: Option<WindowAppearance>, cx: &mut App) {
+        let previous_appearance = self.appearance();
+        self.appearance_override = appearance;
+        if self.appearance() != previous_appearance {
+            self.appearance_observers
+                .clone()
+                .retain(&(), |callback| callback(self, cx));
+            self.refresh();
+        }
     }
 
-    /// Returns the client_inset value by [`Self::set_client_inset`].
-    pub fn client_inset(&self) -> Option<Pixels> {
-        self.client_inset
+    /// Returns the appearance set with [`Self::set_appearance_override`], if any.
+    pub fn appearance_override(&self) -> Option<WindowAppearance> {
+        self.appearance_override
     }
 
-    /// Returns whether the title bar window controls need to be rendered by the application (Wayland and X11)
-    pub fn window_decorations(&self) -> Decorations {
-        self.platform_window.window_decorations()
+    /// Returns the quality the window is currently rendered at.
+    pub fn render_quality(&self) -> RenderQuality {
+        self.render_quality
     }
 
-    /// Returns which window controls are currently visible (Wayland)
-    pub fn window_controls(&self) -> WindowControls {
-        self.platform_window.window_controls()
+    /// Enables or disables adaptive render quality.
+    ///
+    /// While enabled, the window renders shadows with less blur and, where the platform supports
+    /// it, at a lower internal resolution whenever frames take longer than `frame_budget` or the
+    /// system is on battery or conserving power. Full quality is restored once frames are well
+    /// within budget again.
+    pub fn set_adaptive_render_quality(&mut self, frame_budget: Option<Duration>) {
+        self.adaptive_render_quality = frame_budget.map(|frame_budget| {
+            let supports_render_scale = self.platform_window.set_render_scale(1.0);
+            AdaptiveRenderQuality::new(frame_budget, supports_render_scale)
+        });
+        self.set_render_quality(RenderQuality::FULL);
     }
 
-    /// Updates the window's title at the platform level.
-    pub fn set_window_title(&mut self, title: &str) {
-        self.platform_window.set_title(title);
+    /// Returns the thresholds for how much the window draws each frame, if any.
+    pub fn element_budget(&self) -> Option<ElementBudget> {
+        self.element_budget
+            .as_ref()
+            .map(ElementBudgetTracker::budget)
     }
 
-    /// Sets the application identifier.
-    pub fn set_app_id(&mut self, app_id: &str) {
-        self.platform_window.set_app_id(app_id);
+    /// Sets thresholds for how many elements and primitives the window draws each frame.
+    ///
+    /// When frames start exceeding them, the window logs a warning naming the view and element
+    /// path that was being rendered when the element threshold was crossed, which usually points
+    /// at a list that renders every item rather than only the visible ones. Drawing isn't
+    /// affected.
+    pub fn set_element_budget(&mut self, budget: Option<ElementBudget>) {
+        self.element_budget = budget.map(ElementBudgetTracker::new);
     }
 
-    /// Sets the window background appearance.
-    pub fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
-        self.platform_window
-            .set_background_appearance(background_appearance);
+    /// Sets whether the window's root view is drawn as a [cached](AnyView::cached) view, which is
+    /// off by default.
+    ///
+    /// While cached, frames in which only [subsurface](crate::subsurface) views notified reuse
+    /// what the root view painted rather than rendering it again. The root view is then only
+    /// re-rendered when it or one of its descendants notifies, or when the window is refreshed,
+    /// so it mustn't read state that changes without notifying.
+    pub fn set_root_view_cached(&mut self, cached: bool) {
+        self.root_view_cached = cached;
+        self.refresh();
     }
 
-    /// Mark the window as dirty at the platform level.
-    pub fn set_window_edited(&mut self, edited: bool) {
-        self.platform_window.set_edited(edited);
+    /// Returns the most frames per second the window draws, if capped.
+    pub fn max_fps(&self) -> Option<u32> {
+        self.frame_pacing.max_fps()
     }
 
-    /// Determine the display on which the window is visible.
-    pub fn display(&self, cx: &App) -> Option<Rc<dyn PlatformDisplay>> {
-        cx.platform
-            .displays()
-            .into_iter()
-            .find(|display| Some(display.id()) == self.display_id)
+    /// Caps how many frames per second the window draws and presents, or removes the cap if
+    /// `max_fps` is `None`.
+    ///
+    /// Frames the display delivers sooner than the cap allows are skipped, so animations advance
+    /// at the capped rate rather than the display's refresh rate. Frames the platform requires,
+    /// such as after a resize, are always drawn.
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.frame_pacing.set_max_fps(max_fps);
     }
 
-    /// Show the platform character palette.
-    pub fn show_character_palette(&self) {
-        self.platform_window.show_character_palette();
+    /// Returns the most frames per second the window draws while it isn't active, if capped.
+    pub fn inactive_max_fps(&self) -> Option<u32> {
+        self.frame_pacing.inactive_max_fps()
     }
 
-    /// The scale factor of the display associated with the window. For example, it could
-    /// return 2.0 for a "retina" display, indicating that each logical pixel should actually
-    /// be rendered as two pixels on screen.
-    pub fn scale_factor(&self) -> f32 {
-        self.scale_factor
+    /// Caps how many frames per second the window draws and presents while it isn't the active
+    /// window, so that animations in background windows cost less, or removes the cap if
+    /// `max_fps` is `None`. The lower of this and [`Self::max_fps`] applies while inactive.
+    pub fn set_inactive_max_fps(&mut self, max_fps: Option<u32>) {
+        self.frame_pacing.set_inactive_max_fps(max_fps);
     }
 
-    /// The size of an em for the base font of the application. Adjusting this value allows the
-    /// UI to scale, just like zooming a web page.
-    pub fn rem_size(&self) -> Pixels {
-        self.rem_size_override_stack
-            .last()
+    /// Returns whether a text caret should be drawn in this frame, following
+    /// [`App::caret_blink_interval`]. Call this while rendering or painting the caret: the view
+    /// being drawn is redrawn whenever the caret toggles, and the caret stays shown while the
+    /// user is typing.
+    pub fn caret_visible(&mut self, cx: &App) -> bool {
+        let Some(interval) = cx
+            .caret_blink_interval()
+            .filter(|interval| !interval.is_zero())
+        else {
+            return true;
+        };
+        let shown = self.caret_blink.is_shown(Instant::now(), interval);
+        let view = self.rendered_entity_stack.last().copied();
+        if self.caret_blink.subscribe(view, shown) {
+            self.spawn(cx, async move |cx| {
+                while let Ok(Some(delay)) = cx.update(|window, cx| window.blink_caret(cx)) {
+                    cx.background_executor().timer(delay).await;
+                }
+            })
+            .detach();
+        }
+        shown
+    }
+
+    fn blink_caret(&mut self, cx: &mut App) -> Option<Duration> {
+        let now = Instant::now();
+        let interval = cx
+            .caret_blink_interval()
+            .filter(|interval| !interval.is_zero());
+        self.notify_caret_views(now, interval, cx);
+        if !self.caret_blink.keep_blinking(interval.is_some()) {
+            return None;
+        }
+        interval.map(|interval| self.caret_blink.until_toggle(now, interval))
+    }
+
+    fn notify_caret_views(&mut self, now: Instant, interval: Option<Duration>, cx: &mut App) {
+        let Some((views, refresh_window)) = self.caret_blink.take_stale(now, interval) else {
+            return;
+        };
+        if refresh_window {
+            self.refresh();
+        }
+        for view in views {
+            cx.notify(view);
+        }
+    }
+
+    pub(crate) fn record_element(&mut self) {
+        if let Some(element_budget) = self.element_budget.as_mut() {
+            element_budget.record_element(
+                self.rendered_entity_stack.last().copied(),
+                &self.element_id_stack,
+            );
+        }
+    }
+
+    /// Returns the debugging aids drawn over the window's contents.
+    pub fn debug_overlay(&self) -> DebugOverlay {
+        self.debug_overlay
+    }
+
+    /// Sets the debugging aids drawn over the window's contents, such as a pixel grid and
+    /// baseline guides, and redraws the window.
+    pub fn set_debug_overlay(&mut self, overlay: DebugOverlay) {
+        if overlay != self.debug_overlay {
+            self.debug_overlay = overlay;
+            self.refresh();
+        }
+    }
+
+    /// Runs `f` with `background` as the color that text painted by `f` is checked against by
+    /// [`DebugOverlay::low_contrast_text`].
+    pub(crate) fn with_contrast_background<R>(
+        &mut self,
+        background: Option<Hsla>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let background = background
+            .filter(|_| self.debug_overlay.low_contrast_text)
+            .map(|background| background.opacity(self.element_opacity()))
+            .and_then(|background| match self.contrast_backgrounds.last() {
+                Some(below) => Some(below.blend(background)),
+                None => background.is_opaque().then_some(background),
+            });
+        let Some(background) = background else {
+            return f(self);
+        };
+        self.contrast_backgrounds.push(background);
+        let result = f(self);
+        self.contrast_backgrounds.pop();
+        result
+    }
+
+    /// Converts the window's contents into an SVG document, including its text and images; see
+    /// [`Scene::to_svg_with_sprites`].
+    ///
+    /// The window is drawn twice: once with a sprite atlas that keeps the pixels of its glyphs
+    /// and images, and again to restore the frame it presents. When called while the window is
+    /// drawing, the most recently rendered frame is converted without its text and images.
+    pub fn rendered_frame_to_svg(&mut self, cx: &mut App) -> String {
+        match self.draw_with_captured_sprites(cx) {
+            Some((scene, sprites)) => scene.to_svg_with_sprites(&sprites),
+            None => self.rendered_frame.scene.to_svg(),
+        }
+    }
+
+    /// Draws the window with a [`CaptureAtlas`], returning the scene along with the pixels of its
+    /// sprites, and then draws it again with its own atlas. Returns `None` while drawing.
+    fn draw_with_captured_sprites(&mut self, cx: &mut App) -> Option<(Scene, CapturedSprites)> {
+        if !self.invalidator.not_drawing() {
+            return None;
+        }
+        let capture_atlas = Arc::new(CaptureAtlas::default());
+        let sprite_atlas = mem::replace(&mut self.sprite_atlas, capture_atlas.clone());
+        self.refresh();
+        self.draw(cx).clear();
+        self.sprite_atlas = sprite_atlas;
+        let scene = mem::take(&mut self.rendered_frame.scene);
+
+        self.refresh();
+        self.draw(cx).clear();
+        Some((scene, capture_atlas.take()))
+    }
+
+    /// Converts the window's contents into a PDF document with pages of `page_size` points, e.g.
+    /// `size(612., 792.)` for US Letter, including its text and images; see
+    /// [`Scene::to_pdf_with_sprites`]. The window's width is fitted to the page and its height
+    /// is split across as many pages as needed.
+    ///
+    /// The window is drawn twice, as in [`Window::rendered_frame_to_svg`]. When called while the
+    /// window is drawing, the most recently rendered frame is converted without its text and
+    /// images.
+    pub fn rendered_frame_to_pdf(&mut self, page_size: Size<f32>, cx: &mut App) -> Vec<u8> {
+        let width = self.viewport_size.width.0 * self.rasterization_scale();
+        let scale = if width > 0. {
+            page_size.width / width
+        } else {
+            1.
+        };
+        match self.draw_with_captured_sprites(cx) {
+            Some((scene, sprites)) => scene.to_pdf_with_sprites(page_size, scale, &sprites),
+            None => self.rendered_frame.scene.to_pdf(page_size, scale),
+        }
+    }
+
+    /// Shows the system print dialog for the window's contents, exported as with
+    /// [`Window::rendered_frame_to_pdf`]. The document is written to a temporary file, which is
+    /// removed once the dialog is done with it. The returned task fails on platforms without a
+    /// print dialog.
+    pub fn print(&mut self, page_size: Size<f32>, cx: &mut App) -> Task<Result<()>> {
+        let document = self.rendered_frame_to_pdf(page_size, cx);
+        let path = std::env::temp_dir().join(format!(
+            "gpui-print-{}-{}.pdf",
+            std::process::id(),
+            self.handle.window_id().as_u64()
+        ));
+        if let Err(error) = std::fs::write(&path, document)
+            .with_context(|| format!("failed to write print document to {path:?}"))
+        {
+            return Task::ready(Err(error));
+        }
+        let printed = cx.platform.print_file(&path);
+        cx.background_executor().spawn(async move {
+            match printed.await {
+                Ok(result) => {
+                    std::fs::remove_file(&path)
+                        .with_context(|| format!("failed to remove print document {path:?}"))
+                        .log_err();
+                    result
+                }
+                // The platform can't tell when it's done with the document, so it's left for
+                // the system to clean up with its other temporary files.
+                Err(_) => Ok(()),
+            }
+        })
+    }
+
+    /// Applies `shader` to the window's contents after the scene is composited, replacing any
+    /// previous post-process shader, or removes it if `shader` is `None`.
+    ///
+    /// Returns an error if the shader fails to compile or the platform's renderer doesn't support
+    /// post-processing, in which case the previous shader remains in effect.
+    pub fn set_post_process_shader(&mut self, shader: Option<PostProcessShader>) -> Result<()> {
+        self.platform_window
+            .set_post_process_shader(shader.as_ref())?;
+        self.refresh();
+        Ok(())
+    }
+
+    fn record_frame_time(&mut self, frame_time: Duration, cx: &App) {
+        let Some(adaptive_render_quality) = self.adaptive_render_quality.as_mut() else {
+            return;
+        };
+        let mut new_quality = None;
+        if adaptive_render_quality.needs_power_check(Instant::now()) {
+            new_quality =
+                adaptive_render_quality.set_power_constrained(cx.power_state().is_constrained());
+        }
+        if let Some(quality) = adaptive_render_quality
+            .record_frame(frame_time)
+            .or(new_quality)
+        {
+            self.set_render_quality(quality);
+        }
+    }
+
+    fn report_jank(&self, frame_time: Duration, present_time: Duration, cx: &mut App) {
+        if frame_time <= cx.jank_budget {
+            return;
+        }
+        let jank = Jank {
+            window: self.handle,
+            budget: cx.jank_budget,
+            frame_time,
+            layout: self.frame_timings.layout,
+            paint: self.frame_timings.paint,
+            gpu: present_time,
+        };
+        cx.defer(move |cx| {
+            cx.jank_observers
+                .clone()
+                .retain(&(), |callback| callback(&jank, cx));
+        });
+    }
+
+    fn report_input_latency(&mut self, cx: &mut App) {
+        let Some(input_timestamp) = self.unpresented_input_timestamp.take() else {
+            return;
+        };
+        if cx.input_latency_observers.is_empty() {
+            return;
+        }
+        let input_latency = InputLatency {
+            window: self.handle,
+            input_timestamp,
+            latency: input_timestamp.elapsed(),
+        };
+        cx.defer(move |cx| {
+            cx.input_latency_observers
+                .clone()
+                .retain(&(), |callback| callback(&input_latency, cx));
+        });
+    }
+
+    fn set_render_quality(&mut self, quality: RenderQuality) {
+        if quality == self.render_quality {
+            return;
+        }
+        self.render_quality = quality;
+        self.platform_window.set_render_scale(quality.render_scale);
+        self.update_scale_factor();
+        self.refresh();
+    }
+
+    fn update_scale_factor(&mut self) {
+        self.scale_factor = self.platform_window.scale_factor();
+        self.rasterization_scale =
+            self.scale_factor * self.render_quality.render_scale * self.content_transform.scale;
+    }
+
+    fn update_viewport_size(&mut self) {
+        let layout_scale = self.content_scale.layout_scale();
+        self.viewport_size = self
+            .platform_window
+            .content_size()
+            .map(|length| length / layout_scale);
+    }
+
+    /// Returns the scale the window's contents are laid out at, or are animating towards.
+    pub fn content_scale(&self) -> f32 {
+        self.content_scale.target_scale()
+    }
+
+    /// Scales everything drawn in the window, like zooming a web page, e.g. for accessibility
+    /// zoom or presentation mode. Contents are laid out in a viewport that's smaller by the
+    /// scale, then drawn at the scale, so text and borders stay crisp.
+    pub fn set_content_scale(&mut self, scale: f32) {
+        self.content_scale.set(scale);
+        self.refresh();
+    }
+
+    /// Smoothly zooms the window's contents to `scale` over `duration`, keeping the content
+    /// under `anchor` in place. The contents are scaled without being laid out again until the
+    /// animation finishes, when they're laid out at the new scale.
+    pub fn animate_content_scale(&mut self, scale: f32, anchor: Point<Pixels>, duration: Duration) {
+        self.content_scale
+            .animate(scale, anchor, duration, Instant::now());
+        self.refresh();
+    }
+
+    /// Draws the window's contents at `scale` around `anchor` without laying them out again, to
+    /// follow a zoom gesture such as a pinch. Call [`Window::set_content_scale`] with the final
+    /// scale once the gesture ends.
+    pub fn preview_content_scale(&mut self, scale: f32, anchor: Point<Pixels>) {
+        self.content_scale.preview(scale, anchor);
+        self.refresh();
+    }
+
+    fn apply_content_transform(&mut self) {
+        self.content_transform = self.content_scale.frame(Instant::now());
+        self.update_scale_factor();
+        self.update_viewport_size();
+        if self.content_transform.animating {
+            self.on_next_frame(|window, _| window.refresh());
+        }
+    }
+
+    /// Maps positions in input events from window coordinates to content coordinates.
+    fn content_input(&self, mut event: PlatformInput) -> PlatformInput {
+        let scale = self.content_transform.scale;
+        if scale == 1. {
+            return event;
+        }
+        let to_content = |position: &mut Point<Pixels>| {
+            *position = position.map(|coordinate| coordinate / scale);
+        };
+        match &mut event {
+            PlatformInput::MouseDown(mouse_down) => to_content(&mut mouse_down.position),
+            PlatformInput::MouseUp(mouse_up) => to_content(&mut mouse_up.position),
+            PlatformInput::MouseMove(mouse_move) => to_content(&mut mouse_move.position),
+            PlatformInput::MouseExited(mouse_exited) => to_content(&mut mouse_exited.position),
+            PlatformInput::ScrollWheel(scroll_wheel) => {
+                to_content(&mut scroll_wheel.position);
+                if let ScrollDelta::Pixels(delta) = &mut scroll_wheel.delta {
+                    to_content(delta);
+                }
+            }
+            PlatformInput::FileDrop(
+                FileDropEvent::Entered { position, .. }
+                | FileDropEvent::Pending { position }
+                | FileDropEvent::Submit { position },
+            ) => to_content(position),
+            PlatformInput::FileDrop(FileDropEvent::Exited)
+            | PlatformInput::KeyDown(_)
+            | PlatformInput::KeyUp(_)
+            | PlatformInput::ModifiersChanged(_) => {}
+        }
+        event
+    }
+
+    /// Maps bounds in content coordinates to window coordinates, e.g. to position an IME
+    /// candidate window.
+    pub(crate) fn content_to_window_bounds(&self, bounds: Bounds<Pixels>) -> Bounds<Pixels> {
+        let scale = self.content_transform.scale;
+        bounds.map(|length| length * scale)
+    }
+
+    /// Maps a point in window coordinates to content coordinates.
+    pub(crate) fn window_to_content_point(&self, point: Point<Pixels>) -> Point<Pixels> {
+        let scale = self.content_transform.scale;
+        point.map(|coordinate| coordinate / scale)
+    }
+
+    /// Returns the appearance of the current window: the one set with
+    /// [`Self::set_appearance_override`], or else the system's.
+    pub fn appearance(&self) -> WindowAppearance {
+        self.appearance_override.unwrap_or(self.appearance)
+    }
+
+    /// Returns the size of the drawable area within the window.
+    pub fn viewport_size(&self) -> Size<Pixels> {
+        self.viewport_size
+    }
+
+    /// Returns whether this window is focused by the operating system (receiving key events).
+    pub fn is_window_active(&self) -> bool {
+        self.active.get()
+    }
+
+    /// Returns whether this window is considered to be the window
+    /// that currently owns the mouse cursor.
+    /// On mac, this is equivalent to `is_window_active`.
+    pub fn is_window_hovered(&self) -> bool {
+        if cfg!(any(
+            target_os = "windows",
+            target_os = "linux",
+            target_os = "freebsd"
+        )) {
+            self.hovered.get()
+        } else {
+            self.is_window_active()
+        }
+    }
+
+    /// Toggle zoom on the window.
+    pub fn zoom_window(&self) {
+        self.platform_window.zoom();
+    }
+
+    /// Opens the native title bar context menu, useful when implementing client side decorations (Wayland and X11)
+    pub fn show_window_menu(&self, position: Point<Pixels>) {
+        self.platform_window.show_window_menu(position)
+    }
+
+    /// Handle window movement for Linux and macOS.
+    /// Tells the compositor to take control of window movement (Wayland and X11)
+    ///
+    /// Events may not be received during a move operation.
+    pub fn start_window_move(&self) {
+        self.platform_window.start_window_move()
+    }
+
+    /// When using client side decorations, set this to the width of the invisible decorations (Wayland and X11)
+    pub fn set_client_inset(&mut self, inset: Pixels) {
+        self.client_inset = Some(inset);
+        self.platform_window.set_client_inset(inset);
+    }
+
+    /// Returns the client_inset value by [`Self::set_client_inset`].
+    pub fn client_inset(&self) -> Option<Pixels> {
+        self.client_inset
+    }
+
+    /// Returns whether the title bar window controls need to be rendered by the application (Wayland and X11)
+    pub fn window_decorations(&self) -> Decorations {
+        self.platform_window.window_decorations()
+    }
+
+    /// Returns which window controls are currently visible (Wayland)
+    pub fn window_controls(&self) -> WindowControls {
+        self.platform_window.window_controls()
+    }
+
+    /// Updates the window's title at the platform level.
+    pub fn set_window_title(&mut self, title: &str) {
+        self.platform_window.set_title(title);
+    }
+
+    /// Sets the application identifier.
+    pub fn set_app_id(&mut self, app_id: &str) {
+        self.platform_window.set_app_id(app_id);
+    }
+
+    /// Sets the window background appearance. Blurred and backdrop materials are drawn opaque
+    /// while the user asks the system to reduce transparency.
+    pub fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+        self.background_appearance.set(background_appearance);
+        self.apply_background_appearance();
+    }
+
+    fn apply_background_appearance(&self) {
+        let background_appearance = self.background_appearance.get();
+        self.platform_window
+            .set_background_appearance(if self.reduce_transparency {
+                background_appearance.with_reduced_transparency()
+            } else {
+                background_appearance
+            });
+    }
+
+    fn update_reduce_transparency(&mut self, cx: &App) {
+        let reduce_transparency = cx.should_reduce_transparency();
+        if reduce_transparency != self.reduce_transparency {
+            self.reduce_transparency = reduce_transparency;
+            self.apply_background_appearance();
+        }
+    }
+
+    /// Returns whether translucent materials are drawn opaque in this window, because the user
+    /// asked the system to reduce transparency. This is checked again whenever the window is
+    /// activated.
+    pub fn reduces_transparency(&self) -> bool {
+        self.reduce_transparency
+    }
+
+    /// Returns the color to fill a translucent background or overlay with. This is `color`
+    /// itself, unless the window [reduces transparency](Self::reduces_transparency), in which
+    /// case it's `color` blended onto an opaque backdrop matching the window's appearance.
+    pub fn material_color(&self, color: impl Into<Hsla>) -> Hsla {
+        let color = color.into();
+        if !self.reduce_transparency {
+            return color;
+        }
+        let backdrop = match self.appearance() {
+            WindowAppearance::Light | WindowAppearance::VibrantLight => white(),
+            WindowAppearance::Dark | WindowAppearance::VibrantDark => black(),
+        };
+        backdrop.blend(color)
+    }
+
+    /// Mark the window as dirty at the platform level.
+    pub fn set_window_edited(&mut self, edited: bool) {
+        self.platform_window.set_edited(edited);
+    }
+
+    /// Determine the display on which the window is visible.
+    pub fn display(&self, cx: &App) -> Option<Rc<dyn PlatformDisplay>> {
+        cx.platform
+            .displays()
+            .into_iter()
+            .find(|display| Some(display.id()) == self.display_id)
+    }
+
+    /// Show the platform character palette.
+    pub fn show_character_palette(&self) {
+        self.platform_window.show_character_palette();
+    }
+
+    /// The scale factor of the display associated with the window. For example, it could
+    /// return 2.0 for a "retina" display, indicating that each logical pixel should actually
+    /// be rendered as two pixels on screen.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// The number of scaled pixels per logical pixel that the window's contents are painted at.
+    /// This is the display's scale factor, reduced by the window's
+    /// [`RenderQuality::render_scale`] and multiplied by its content scale.
+    pub(crate) fn rasterization_scale(&self) -> f32 {
+        self.rasterization_scale
+    }
+
+    /// The size of an em for the base font of the application. Adjusting this value allows the
+    /// UI to scale, just like zooming a web page.
+    pub fn rem_size(&self) -> Pixels {
+        self.rem_size_override_stack
+            .last()
             .copied()
             .unwrap_or(self.rem_size)
     }
@@ -1924,8 +3051,20 @@ impl Window {
         self.text_style().line_height_in_pixels(self.rem_size())
     }
 
-    /// Call to prevent the default action of an event. Currently only used to prevent
-    /// parent elements from becoming focused on mouse down.
+    /// The width of one [`ch`](crate::ch) column in the current text style: the advance of the
+    /// `0` character.
+    fn ch_width(&self) -> Pixels {
+        let text_style = self.text_style();
+        let font_size = text_style.font_size.to_pixels(self.rem_size());
+        let font_id = self.text_system.resolve_font(&text_style.font());
+        self.text_system
+            .ch_advance(font_id, font_size)
+            .log_err()
+            .unwrap_or(font_size * 0.5)
+    }
+
+    /// Call to prevent the default action of an event: focusing parent elements on mouse down,
+    /// and inserting text on key down. See [`crate::DispatchedEvent::prevent_default`].
     pub fn prevent_default(&mut self) {
         self.default_prevented = true;
     }
@@ -1954,6 +3093,17 @@ impl Window {
         self.modifiers
     }
 
+    /// Returns how the user last interacted with the window: by pressing a key or a pointer
+    /// button. Modifier keys on their own, pointer movement and scrolling don't change it, so
+    /// that a shift-click is still a pointer interaction.
+    ///
+    /// Styles set with [`crate::InteractiveElement::focus_visible`] only apply while this is
+    /// [`InputModality::Keyboard`], and the window is redrawn when it changes while an element
+    /// is focused, as with CSS's `:focus-visible`.
+    pub fn last_input_modality(&self) -> InputModality {
+        self.last_input_modality
+    }
+
     /// Returns true if the last input event was keyboard-based (key press, tab navigation, etc.)
     /// This is used for focus-visible styling to show focus indicators only for keyboard navigation.
     pub fn last_input_was_keyboard(&self) -> bool {
@@ -1978,6 +3128,20 @@ impl Window {
         debug_assert!(self.rendered_entity_stack.is_empty());
         self.invalidator.set_dirty(false);
         self.requested_autoscroll = None;
+        self.frame_timings = FrameTimings::default();
+        if let Some(input_timestamp) = self.undrawn_input_timestamp.take() {
+            self.unpresented_input_timestamp
+                .get_or_insert(input_timestamp);
+        }
+        self.apply_content_transform();
+        if let Some(element_budget) = self.element_budget.as_mut() {
+            element_budget.begin_frame();
+        }
+        let text_contrast = cx.text_contrast(self.appearance);
+        if text_contrast != self.text_contrast {
+            self.text_contrast = text_contrast;
+            self.platform_window.set_text_contrast(text_contrast);
+        }
 
         // Restore the previously-used input handler.
         if let Some(input_handler) = self.platform_window.take_input_handler() {
@@ -1985,6 +3149,9 @@ impl Window {
         }
         if !cx.mode.skip_drawing() {
             self.draw_roots(cx);
+            if let Some(element_budget) = self.element_budget.as_mut() {
+                element_budget.finish_frame(self.next_frame.scene.len());
+            }
         }
         self.dirty_views.clear();
         self.next_frame.window_active = self.active.get();
@@ -2003,13 +3170,47 @@ impl Window {
         let previous_focus_path = self.rendered_frame.focus_path();
         let previous_window_active = self.rendered_frame.window_active;
         mem::swap(&mut self.rendered_frame, &mut self.next_frame);
+        if self.rendered_frame.native_views != self.next_frame.native_views {
+            self.platform_window
+                .set_native_views(&self.rendered_frame.native_views);
+        }
+        if !self.popups.is_empty() || !self.rendered_frame.popups.is_empty() {
+            let handle = self.handle;
+            cx.defer(move |cx| {
+                handle
+                    .update(cx, |_, window, cx| window.update_popups(cx))
+                    .ok();
+            });
+        }
         self.next_frame.clear();
         let current_focus_path = self.rendered_frame.focus_path();
         let current_window_active = self.rendered_frame.window_active;
+        record_crash_scene(
+            self.handle.window_id(),
+            self.viewport_size,
+            self.rasterization_scale(),
+            &self.rendered_frame.scene,
+        );
+        if !self.frame_stats_observers.is_empty() {
+            let stats = self.rendered_frame.scene.stats();
+            self.frame_stats_observers
+                .clone()
+                .retain(&(), |observer| observer(&stats, self, cx));
+        }
 
         if previous_focus_path != current_focus_path
             || previous_window_active != current_window_active
         {
+            let previous_focus = previous_focus_path.last().copied();
+            let focus = current_focus_path.last().copied();
+            if previous_focus != focus {
+                cx.report_telemetry(|| TelemetryEvent::FocusChanged {
+                    window: self.handle,
+                    previous: previous_focus,
+                    focused: focus,
+                });
+            }
+
             if !previous_focus_path.is_empty() && current_focus_path.is_empty() {
                 self.focus_lost_listeners
                     .clone()
@@ -2068,12 +3269,17 @@ impl Window {
 
     #[profiling::function]
     fn present(&self) {
-        self.platform_window.draw(&self.rendered_frame.scene);
-        self.needs_present.set(false);
+        match &self.scene_renderer {
+            Some(scene_renderer) => scene_renderer.draw(&self.rendered_frame.scene),
+            None => self.platform_window.draw(&self.rendered_frame.scene),
+        }
+        self.needs_present
+            .set(self.rendered_frame.scene.has_moving_dashes());
         profiling::finish_frame!();
     }
 
     fn draw_roots(&mut self, cx: &mut App) {
+        let layout_start = Instant::now();
         self.invalidator.set_phase(DrawPhase::Prepaint);
         self.tooltip_bounds.take();
 
@@ -2096,8 +3302,14 @@ impl Window {
         };
 
         // Layout all root elements.
-        let mut root_element = self.root.as_ref().unwrap().clone().into_any();
-        root_element.prepaint_as_root(Point::default(), AvailableSpace::from_size(root_size), self, cx);
+        let mut root_view = self.root.as_ref().unwrap().clone();
+        if self.root_view_cached {
+            root_view = root_view.cached(StyleRefinement::default().size_full());
+        }
+        let mut root_element = root_view.into_any();
+        let root_origin = self.content_transform.origin;
+        root_element.prepaint_as_root(root_origin, AvailableSpace::from_size(root_size), self, cx);
+        let mut subsurface_elements = self.prepaint_subsurfaces(cx);
 
         #[cfg(any(feature = "inspector", debug_assertions))]
         let inspector_element = self.prepaint_inspector(_inspector_width, cx);
@@ -2112,7 +3324,7 @@ impl Window {
         let mut tooltip_element = None;
         if let Some(prompt) = self.prompt.take() {
             let mut element = prompt.view.any_view().into_any();
-            element.prepaint_as_root(Point::default(), AvailableSpace::from_size(root_size), self, cx);
+            element.prepaint_as_root(root_origin, AvailableSpace::from_size(root_size), self, cx);
             prompt_element = Some(element);
             self.prompt = Some(prompt);
         } else if let Some(active_drag) = cx.active_drag.take() {
@@ -2127,13 +3339,23 @@ impl Window {
 
         self.mouse_hit_test = self.next_frame.hit_test(self.mouse_position);
 
+        let paint_start = Instant::now();
+        self.frame_timings.layout = paint_start - layout_start;
+
         // Now actually paint the elements.
         self.invalidator.set_phase(DrawPhase::Paint);
         root_element.paint(self, cx);
+        for (content_mask, element) in &mut subsurface_elements {
+            self.with_content_mask(Some(content_mask.clone()), |window| {
+                element.paint(window, cx)
+            });
+        }
+        self.update_subsurface_frame_tasks(cx);
 
         #[cfg(any(feature = "inspector", debug_assertions))]
         self.paint_inspector(inspector_element, cx);
 
+        self.next_frame.scene.set_scene_layer(SceneLayer::Overlay);
         self.paint_deferred_draws(&sorted_deferred_draws, cx);
 
         if let Some(mut prompt_element) = prompt_element {
@@ -2144,8 +3366,75 @@ impl Window {
             tooltip_element.paint(self, cx);
         }
 
+        self.next_frame.scene.set_scene_layer(SceneLayer::Debug);
         #[cfg(any(feature = "inspector", debug_assertions))]
         self.paint_inspector_hitbox(cx);
+
+        if self.debug_overlay.is_enabled() {
+            let scale_factor = self.rasterization_scale();
+            debug_overlay::paint_debug_overlay(
+                self.debug_overlay,
+                self.viewport_size.scale(scale_factor),
+                scale_factor,
+                &mut self.next_frame.scene,
+            );
+        }
+        self.frame_timings.paint = paint_start.elapsed();
+    }
+
+    /// Lays out the views of the subsurfaces inserted so far, including those inserted by other
+    /// subsurfaces, as children of the root view's dispatch node. Views between a subsurface and
+    /// the root aren't its ancestors, and notifying a subsurface view doesn't dirty the root, so
+    /// while the root view is cached, redrawing it reuses everything else the window painted.
+    fn prepaint_subsurfaces(&mut self, cx: &mut App) -> Vec<(ContentMask<Pixels>, AnyElement)> {
+        let mut elements = Vec::new();
+        let mut subsurface_index = 0;
+        while let Some(placement) = self.next_frame.subsurfaces.get(subsurface_index).cloned() {
+            subsurface_index += 1;
+            let root_node = self.next_frame.dispatch_tree.root_node_id();
+            self.next_frame.dispatch_tree.set_active_node(root_node);
+            let mut element = placement
+                .view
+                .cached(StyleRefinement::default().size_full())
+                .into_any_element();
+            self.with_content_mask(Some(placement.content_mask.clone()), |window| {
+                element.prepaint_as_root(
+                    placement.bounds.origin,
+                    AvailableSpace::from_size(placement.bounds.size),
+                    window,
+                    cx,
+                )
+            });
+            self.next_frame.dispatch_tree.pop_node();
+            elements.push((placement.content_mask, element));
+        }
+        elements
+    }
+
+    /// Keeps a task redrawing each subsurface painted with a frame rate, and drops the tasks of
+    /// the subsurfaces that weren't.
+    fn update_subsurface_frame_tasks(&mut self, cx: &App) {
+        let mut previous_tasks = mem::take(&mut self.subsurface_frame_tasks);
+        for placement in &self.next_frame.subsurfaces {
+            let Some(frame_rate) = placement.frame_rate.filter(|frame_rate| *frame_rate > 0) else {
+                continue;
+            };
+            let view_id = placement.view.entity_id();
+            let task = match previous_tasks.remove(&view_id) {
+                Some((previous_frame_rate, task)) if previous_frame_rate == frame_rate => task,
+                _ => self.spawn(cx, async move |cx| {
+                    let frame_interval = Duration::from_secs(1) / frame_rate;
+                    loop {
+                        cx.background_executor().timer(frame_interval).await;
+                        if cx.update(|_, cx| cx.notify(view_id)).is_err() {
+                            break;
+                        }
+                    }
+                }),
+            };
+            self.subsurface_frame_tasks
+                .insert(view_id, (frame_rate, task));
+        }
     }
 
     fn prepaint_tooltip(&mut self, cx: &mut App) -> Option<AnyElement> {
@@ -2284,8 +3573,11 @@ impl Window {
     pub(crate) fn prepaint_index(&self) -> PrepaintStateIndex {
         PrepaintStateIndex {
             hitboxes_index: self.next_frame.hitboxes.len(),
+            element_hitboxes_index: self.next_frame.element_hitboxes.len(),
+            hitbox_listeners_index: self.next_frame.hitbox_listeners.len(),
             tooltips_index: self.next_frame.tooltip_requests.len(),
             deferred_draws_index: self.next_frame.deferred_draws.len(),
+            subsurfaces_index: self.next_frame.subsurfaces.len(),
             dispatch_tree_index: self.next_frame.dispatch_tree.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             line_layout_index: self.text_system.layout_index(),
@@ -2298,6 +3590,18 @@ impl Window {
                 .iter()
                 .cloned(),
         );
+        self.next_frame.element_hitboxes.extend(
+            self.rendered_frame.element_hitboxes
+                [range.start.element_hitboxes_index..range.end.element_hitboxes_index]
+                .iter()
+                .cloned(),
+        );
+        self.next_frame.hitbox_listeners.extend(
+            self.rendered_frame.hitbox_listeners
+                [range.start.hitbox_listeners_index..range.end.hitbox_listeners_index]
+                .iter()
+                .cloned(),
+        );
         self.next_frame.tooltip_requests.extend(
             self.rendered_frame.tooltip_requests
                 [range.start.tooltips_index..range.end.tooltips_index]
@@ -2339,6 +3643,12 @@ impl Window {
                     paint_range: deferred_draw.paint_range.clone(),
                 }),
         );
+        self.next_frame.subsurfaces.extend(
+            self.rendered_frame.subsurfaces
+                [range.start.subsurfaces_index..range.end.subsurfaces_index]
+                .iter()
+                .cloned(),
+        );
     }
 
     pub(crate) fn paint_index(&self) -> PaintIndex {
@@ -2347,6 +3657,9 @@ impl Window {
             mouse_listeners_index: self.next_frame.mouse_listeners.len(),
             input_handlers_index: self.next_frame.input_handlers.len(),
             cursor_styles_index: self.next_frame.cursor_styles.len(),
+            native_views_index: self.next_frame.native_views.len(),
+            popups_index: self.next_frame.popups.len(),
+            raw_input_regions_index: self.next_frame.raw_input_regions.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             tab_handle_index: self.next_frame.tab_stops.paint_index(),
             line_layout_index: self.text_system.layout_index(),
@@ -2360,6 +3673,23 @@ impl Window {
                 .iter()
                 .cloned(),
         );
+        self.next_frame.native_views.extend(
+            self.rendered_frame.native_views
+                [range.start.native_views_index..range.end.native_views_index]
+                .iter()
+                .cloned(),
+        );
+        self.next_frame.popups.extend(
+            self.rendered_frame.popups[range.start.popups_index..range.end.popups_index]
+                .iter()
+                .cloned(),
+        );
+        self.next_frame.raw_input_regions.extend(
+            self.rendered_frame.raw_input_regions
+                [range.start.raw_input_regions_index..range.end.raw_input_regions_index]
+                .iter()
+                .cloned(),
+        );
         self.next_frame.input_handlers.extend(
             self.rendered_frame.input_handlers
                 [range.start.input_handlers_index..range.end.input_handlers_index]
@@ -2517,6 +3847,29 @@ impl Window {
         result
     }
 
+    /// Paints the primitives painted by `f`, such as those of a subtree of elements, mapped
+    /// through `transformation` after any transformation that's already applied. This lets
+    /// elements rotate, scale or skew their children.
+    ///
+    /// The transformation is in the scaled pixels of the scene, like the one given to
+    /// [`Self::paint_svg`]; see [`crate::Transformation::into_matrix`] to build one around a
+    /// point of an element. Content masks are applied after transforming, and hitboxes are
+    /// not transformed. This method should only be called as part of the paint phase of
+    /// element drawing.
+    pub fn with_element_transformation<R>(
+        &mut self,
+        transformation: TransformationMatrix,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.invalidator.debug_assert_paint();
+
+        let previous_transformation = self.element_transformation;
+        self.element_transformation = previous_transformation.compose(transformation);
+        let result = f(self);
+        self.element_transformation = previous_transformation;
+        result
+    }
+
     /// Perform prepaint on child elements in a "retryable" manner, so that any side effects
     /// of prepaints can be discarded before prepainting again. This is used to support autoscroll
     /// where we need to prepaint children to detect the autoscroll bounds, then adjust the
@@ -2528,6 +3881,12 @@ impl Window {
         let result = f(self);
         if result.is_err() {
             self.next_frame.hitboxes.truncate(index.hitboxes_index);
+            self.next_frame
+                .element_hitboxes
+                .truncate(index.element_hitboxes_index);
+            self.next_frame
+                .hitbox_listeners
+                .truncate(index.hitbox_listeners_index);
             self.next_frame
                 .tooltip_requests
                 .truncate(index.tooltips_index);
@@ -2616,6 +3975,13 @@ impl Window {
         self.element_opacity
     }
 
+    /// Obtain the transformation that primitives are currently painted with. This method should
+    /// only be called as part of the paint phase of element drawing.
+    pub fn element_transformation(&self) -> TransformationMatrix {
+        self.invalidator.debug_assert_paint();
+        self.element_transformation
+    }
+
     /// Obtain the current content mask. This method should only be called during element drawing.
     pub fn content_mask(&self) -> ContentMask<Pixels> {
         self.invalidator.debug_assert_paint_or_prepaint();
@@ -2625,11 +3991,25 @@ impl Window {
             .unwrap_or_else(|| ContentMask {
                 bounds: Bounds {
                     origin: Point::default(),
-                    size: self.viewport_size,
+                    size: self.visible_content_size(),
                 },
+                ..Default::default()
             })
     }
 
+    /// The size of the window in content pixels, which differs from the viewport the contents
+    /// were laid out in while the content scale is zoomed without laying them out again.
+    fn visible_content_size(&self) -> Size<Pixels> {
+        let layout_scale = self.content_scale.layout_scale();
+        let scale = self.content_transform.scale;
+        if scale == layout_scale {
+            self.viewport_size
+        } else {
+            self.viewport_size
+                .map(|length| length * (layout_scale / scale))
+        }
+    }
+
     /// Provide elements in the called function with a new namespace in which their identifiers must be unique.
     /// This can be used within a custom element to distinguish multiple sets of child elements.
     pub fn with_element_namespace<R>(
@@ -2841,15 +4221,42 @@ impl Window {
         });
     }
 
+    /// Inserts a subsurface drawing `view` at `bounds`, clipped to the current content mask, above
+    /// the rest of the window's base layer. A `frame_rate` redraws the view that many times a
+    /// second for as long as the subsurface is inserted. See [`crate::Subsurface`].
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub(crate) fn insert_subsurface(
+        &mut self,
+        view: AnyView,
+        bounds: Bounds<Pixels>,
+        frame_rate: Option<u32>,
+    ) {
+        self.invalidator.debug_assert_prepaint();
+        let content_mask = ContentMask {
+            bounds,
+            ..Default::default()
+        }
+        .intersect(&self.content_mask());
+        self.next_frame.subsurfaces.push(SubsurfacePlacement {
+            view,
+            bounds,
+            content_mask,
+            frame_rate,
+        });
+    }
+
     /// Creates a new painting layer for the specified bounds. A "layer" is a batch
-    /// of geometry that are non-overlapping and have the same draw order. This is typically used
+    /// of geometry that shares a draw order, so that it's drawn in as few batches as possible.
+    /// Geometry in a layer is still drawn in the order it's painted where it overlaps, and
+    /// geometry painted after the layer is drawn above all of it. This is typically used
     /// for performance reasons.
     ///
     /// This method should only be called as part of the paint phase of element drawing.
     pub fn paint_layer<R>(&mut self, bounds: Bounds<Pixels>, f: impl FnOnce(&mut Self) -> R) -> R {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let content_mask = self.content_mask();
         let clipped_bounds = bounds.intersect(&content_mask.bounds);
         if !clipped_bounds.is_empty() {
@@ -2868,6 +4275,8 @@ impl Window {
     }
 
     /// Paint one or more drop shadows into the scene for the next frame at the current z-index.
+    /// Large blurred shadows are rasterized once into the sprite atlas and drawn nine-sliced to
+    /// fit, rather than blurred for every pixel of every frame.
     ///
     /// This method should only be called as part of the paint phase of element drawing.
     pub fn paint_shadows(
@@ -2878,20 +4287,69 @@ impl Window {
     ) {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
-        let content_mask = self.content_mask();
-        let opacity = self.element_opacity();
         for shadow in shadows {
-            let shadow_bounds = (bounds + shadow.offset).dilate(shadow.spread_radius);
-            self.next_frame.scene.insert_primitive(Shadow {
-                order: 0,
-                blur_radius: shadow.blur_radius.scale(scale_factor),
-                bounds: shadow_bounds.scale(scale_factor),
-                content_mask: content_mask.scale(scale_factor),
-                corner_radii: corner_radii.scale(scale_factor),
-                color: shadow.color.opacity(opacity),
-            });
+            let shadow = self.scaled_shadow(bounds, corner_radii, shadow);
+            if !self.paint_cached_shadow(&shadow) {
+                self.next_frame.scene.insert_primitive(shadow);
+            }
+        }
+    }
+
+    /// Converts a shadow cast by the given rounded rectangle to device pixels, applying the
+    /// window's shadow quality and the element's opacity.
+    fn scaled_shadow(
+        &self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        shadow: &BoxShadow,
+    ) -> Shadow {
+        let scale_factor = self.rasterization_scale();
+        let blur_scale = match self.render_quality.shadow_quality {
+            ShadowQuality::Full => 1.,
+            ShadowQuality::Reduced => 0.5,
+        };
+        let shadow_bounds = (bounds + shadow.offset).dilate(shadow.spread_radius);
+        Shadow {
+            order: 0,
+            blur_radius: (shadow.blur_radius * blur_scale).scale(scale_factor),
+            bounds: shadow_bounds.scale(scale_factor),
+            content_mask: self.content_mask().scale(scale_factor),
+            corner_radii: corner_radii.scale(scale_factor),
+            color: shadow.color.opacity(self.element_opacity()),
+            transformation: self.element_transformation,
+        }
+    }
+
+    /// Paints the shadow from a rasterization cached in the sprite atlas, returning false if the
+    /// shadow can't be cached and must be blurred by the shaders instead.
+    fn paint_cached_shadow(&mut self, shadow: &Shadow) -> bool {
+        let Some(params) =
+            ShadowParams::new(shadow.bounds, shadow.blur_radius, &shadow.corner_radii)
+        else {
+            return false;
+        };
+        let Some(tile) = self
+            .sprite_atlas
+            .get_or_insert_with(&AtlasKey::Shadow(params.clone()), &mut || {
+                let (size, coverage) = params.rasterize();
+                Ok(Some((size, Cow::Owned(coverage))))
+            })
+            .log_err()
+            .flatten()
+        else {
+            return false;
+        };
+        for mut sprite in shadow_cache::nine_slice_shadow(
+            &params,
+            &tile,
+            shadow.bounds,
+            &shadow.content_mask,
+            shadow.color,
+        ) {
+            sprite.transformation = shadow.transformation;
+            self.next_frame.scene.insert_primitive(sprite);
         }
+        true
     }
 
     /// Paint one or more quads into the scene for the next frame at the current stacking context.
@@ -2902,11 +4360,29 @@ impl Window {
     ///
     /// Note that the `quad.corner_radii` are allowed to exceed the bounds, creating sharp corners
     /// where the circular arcs meet. This will not display well when combined with dashed borders.
-    /// Use `Corners::clamp_radii_for_quad_size` if the radii should fit within the bounds.
+    /// Use `Corners::clamp_radii_for_quad_size` if the radii should fit within the bounds, or
+    /// `Corners::clamp_elliptical_radii_for_quad_size` for elliptical corners. The shadow of a
+    /// quad with elliptical corners is cast with circular corners of its horizontal radii.
+    ///
+    /// A shadow set with [`PaintQuad::shadow`] is drawn beneath the quad by the same draw call,
+    /// unless it's blurred enough to be drawn from the cache described in [`Self::paint_shadows`].
     pub fn paint_quad(&mut self, quad: PaintQuad) {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let mut quad_shadow = QuadShadow::default();
+        if let Some(shadow) = &quad.shadow {
+            let shadow = self.scaled_shadow(quad.bounds, quad.corner_radii, shadow);
+            if !self.paint_cached_shadow(&shadow) {
+                quad_shadow = QuadShadow {
+                    bounds: shadow.bounds,
+                    color: shadow.color,
+                    blur_radius: shadow.blur_radius,
+                    pad: 0,
+                };
+            }
+        }
+
+        let scale_factor = self.rasterization_scale();
         let content_mask = self.content_mask();
         let opacity = self.element_opacity();
         self.next_frame.scene.insert_primitive(Quad {
@@ -2916,8 +4392,86 @@ impl Window {
             background: quad.background.opacity(opacity),
             border_color: quad.border_color.opacity(opacity),
             corner_radii: quad.corner_radii.scale(scale_factor),
+            vertical_corner_radii: quad
+                .vertical_corner_radii
+                .map_or_else(Corners::default, |radii| radii.scale(scale_factor)),
             border_widths: quad.border_widths.scale(scale_factor),
             border_style: quad.border_style,
+            shadow: quad_shadow,
+            transformation: self.element_transformation,
+            dash_speed: dash_periods_per_second(&quad),
+            pad: 0,
+        });
+    }
+
+    /// Paint a blur of whatever has been painted behind the given rounded rectangle into the
+    /// scene for the next frame. Paint a translucent quad over it to tint it. Only available where
+    /// GPUI renders with Blade, since the Metal and DirectX renderers don't draw backdrop blurs.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    #[cfg(any(
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        ),
+        all(target_os = "macos", feature = "macos-blade")
+    ))]
+    pub fn paint_backdrop_blur(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        blur_radius: Pixels,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        if blur_radius <= Pixels::ZERO {
+            return;
+        }
+        let scale_factor = self.rasterization_scale();
+        let content_mask = self.content_mask();
+        self.next_frame.scene.insert_primitive(crate::BackdropBlur {
+            order: 0,
+            blur_radius: blur_radius.scale(scale_factor),
+            bounds: bounds.scale(scale_factor),
+            corner_radii: corner_radii.scale(scale_factor),
+            content_mask: content_mask.scale(scale_factor),
+        });
+    }
+
+    /// Paint a rounded rectangle repeated in every tile of a grid over `bounds` into the scene
+    /// for the next frame, as a single primitive however many tiles there are. `quad_bounds` is
+    /// relative to the origin of each tile, and adjacent tiles alternate between `color` and
+    /// `alternate_color` like the squares of a checkerboard. Pass the same color twice for
+    /// stripes, dot grids or grid lines.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_tiled_quad(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        tile_size: Size<Pixels>,
+        quad_bounds: Bounds<Pixels>,
+        corner_radius: Pixels,
+        color: Hsla,
+        alternate_color: Hsla,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        if tile_size.width <= Pixels::ZERO || tile_size.height <= Pixels::ZERO {
+            return;
+        }
+        let scale_factor = self.rasterization_scale();
+        let content_mask = self.content_mask();
+        let opacity = self.element_opacity();
+        self.next_frame.scene.insert_primitive(TiledQuad {
+            order: 0,
+            corner_radius: corner_radius.scale(scale_factor),
+            bounds: bounds.scale(scale_factor),
+            content_mask: content_mask.scale(scale_factor),
+            tile_size: tile_size.scale(scale_factor),
+            quad_bounds: quad_bounds.scale(scale_factor),
+            color: color.opacity(opacity),
+            alternate_color: alternate_color.opacity(opacity),
+            transformation: self.element_transformation,
         });
     }
 
@@ -2927,15 +4481,15 @@ impl Window {
     pub fn paint_path(&mut self, mut path: Path<Pixels>, color: impl Into<Background>) {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let content_mask = self.content_mask();
         let opacity = self.element_opacity();
         path.content_mask = content_mask;
         let color: Background = color.into();
         path.color = color.opacity(opacity);
-        self.next_frame
-            .scene
-            .insert_primitive(path.scale(scale_factor));
+        let mut path = path.scale(scale_factor);
+        path.transform(&self.element_transformation);
+        self.next_frame.scene.insert_primitive(path);
     }
 
     /// Paint an underline into the scene for the next frame at the current z-index.
@@ -2949,16 +4503,19 @@ impl Window {
     ) {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
-        let height = if style.wavy {
-            style.thickness * 3.
-        } else {
-            style.thickness
-        };
-        let bounds = Bounds {
+        let scale_factor = self.rasterization_scale();
+        let wave_amplitude = style.wave_amplitude.unwrap_or(style.thickness * 0.8);
+        let wavelength = style.wavelength.unwrap_or(style.thickness * 9.);
+        let mut bounds = Bounds {
             origin,
-            size: size(width, height),
+            size: size(width, style.thickness),
         };
+        if style.wavy {
+            let default_height = style.thickness * 3.;
+            let height = default_height.max(wave_amplitude * 2. + style.thickness);
+            bounds.origin.y -= (height - default_height) / 2.;
+            bounds.size.height = height;
+        }
         let content_mask = self.content_mask();
         let element_opacity = self.element_opacity();
 
@@ -2970,6 +4527,9 @@ impl Window {
             color: style.color.unwrap_or_default().opacity(element_opacity),
             thickness: style.thickness.scale(scale_factor),
             wavy: if style.wavy { 1 } else { 0 },
+            wave_amplitude: wave_amplitude.scale(scale_factor),
+            wavelength: wavelength.scale(scale_factor),
+            transformation: self.element_transformation,
         });
     }
 
@@ -2984,7 +4544,7 @@ impl Window {
     ) {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let height = style.thickness;
         let bounds = Bounds {
             origin,
@@ -3001,6 +4561,9 @@ impl Window {
             thickness: style.thickness.scale(scale_factor),
             color: style.color.unwrap_or_default().opacity(opacity),
             wavy: 0,
+            wave_amplitude: ScaledPixels(0.),
+            wavelength: ScaledPixels(0.),
+            transformation: self.element_transformation,
         });
     }
 
@@ -3023,7 +4586,7 @@ impl Window {
         self.invalidator.debug_assert_paint();
 
         let element_opacity = self.element_opacity();
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let glyph_origin = origin.scale(scale_factor);
 
         let subpixel_variant = Point {
@@ -3053,15 +4616,33 @@ impl Window {
                 size: tile.bounds.size.map(Into::into),
             };
             let content_mask = self.content_mask().scale(scale_factor);
+            let color = color.opacity(element_opacity);
+            let low_contrast_marker = self
+                .contrast_backgrounds
+                .last()
+                .filter(|background| {
+                    !background.blend(color).meets_contrast(
+                        **background,
+                        ContrastLevel::Aa,
+                        font_size >= px(24.),
+                    )
+                })
+                .map(|_| Quad {
+                    transformation: self.element_transformation,
+                    ..low_contrast_marker(bounds, &content_mask)
+                });
             self.next_frame.scene.insert_primitive(MonochromeSprite {
                 order: 0,
-                pad: 0,
+                distance_field: 0,
                 bounds,
                 content_mask,
-                color: color.opacity(element_opacity),
+                color,
                 tile,
-                transformation: TransformationMatrix::unit(),
+                transformation: self.element_transformation,
             });
+            if let Some(marker) = low_contrast_marker {
+                self.next_frame.scene.insert_primitive(marker);
+            }
         }
         Ok(())
     }
@@ -3083,7 +4664,7 @@ impl Window {
     ) -> Result<()> {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let glyph_origin = origin.scale(scale_factor);
         let params = RenderGlyphParams {
             font_id,
@@ -3114,13 +4695,14 @@ impl Window {
 
             self.next_frame.scene.insert_primitive(PolychromeSprite {
                 order: 0,
-                pad: 0,
+                nearest: 0,
                 grayscale: false,
                 bounds,
                 corner_radii: Default::default(),
                 content_mask,
                 tile,
                 opacity,
+                transformation: self.element_transformation,
             });
         }
         Ok(())
@@ -3141,7 +4723,7 @@ impl Window {
         self.invalidator.debug_assert_paint();
 
         let element_opacity = self.element_opacity();
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
 
         let bounds = bounds.scale(scale_factor);
         let params = RenderSvgParams {
@@ -3178,14 +4760,91 @@ impl Window {
 
         self.next_frame.scene.insert_primitive(MonochromeSprite {
             order: 0,
-            pad: 0,
+            distance_field: 0,
             bounds: svg_bounds
                 .map_origin(|origin| origin.round())
                 .map_size(|size| size.ceil()),
             content_mask,
             color: color.opacity(element_opacity),
             tile,
-            transformation,
+            transformation: self.element_transformation.compose(transformation),
+        });
+
+        Ok(())
+    }
+
+    /// Paints a monochrome SVG icon into the scene for the next frame at the current z-index,
+    /// scaled to fit the width of `bounds`. The icon is rendered into a signed distance field
+    /// once and drawn from it at any size, optionally surrounded by a glow.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_icon(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        path: SharedString,
+        color: Hsla,
+        glow: Option<IconGlow>,
+        cx: &App,
+    ) -> Result<()> {
+        self.invalidator.debug_assert_paint();
+
+        let element_opacity = self.element_opacity();
+        let scale_factor = self.rasterization_scale();
+        let bounds = bounds.scale(scale_factor);
+        if bounds.size.width.0 <= 0. {
+            return Ok(());
+        }
+
+        let Some(tile) = self.sprite_atlas.get_or_insert_with(
+            &AtlasKey::SvgDistanceField(path.clone()),
+            &mut || {
+                let Some((size, bytes)) = cx.svg_renderer.render_distance_field(&path, None)?
+                else {
+                    return Ok(None);
+                };
+                Ok(Some((size, Cow::Owned(bytes))))
+            },
+        )?
+        else {
+            return Ok(());
+        };
+
+        // The field is scaled so that the icon's shape, excluding the padding around it, fills
+        // the width of the bounds.
+        let field_scale = bounds.size.width.0 / DISTANCE_FIELD_SIZE;
+        let sprite_size = tile
+            .bounds
+            .size
+            .map(|value| ScaledPixels(value.0 as f32 * field_scale));
+        let sprite_bounds = Bounds {
+            origin: bounds.center() - point(sprite_size.width.half(), sprite_size.height.half()),
+            size: sprite_size,
+        };
+        let content_mask = self.content_mask().scale(scale_factor);
+
+        if let Some(glow) = glow {
+            // The glow fades out over its radius, which can extend at most as far as the field.
+            let radius = glow.radius.0 * scale_factor / field_scale;
+            let spread = (radius / DISTANCE_FIELD_RANGE).clamp(0., 1.) * 0.5;
+            self.next_frame.scene.insert_primitive(MonochromeSprite {
+                order: 0,
+                distance_field: pack_distance_field(0.5 - spread / 2., spread / 2.),
+                bounds: sprite_bounds,
+                content_mask: content_mask.clone(),
+                color: glow.color.opacity(element_opacity),
+                tile: tile.clone(),
+                transformation: self.element_transformation,
+            });
+        }
+
+        self.next_frame.scene.insert_primitive(MonochromeSprite {
+            order: 0,
+            distance_field: pack_distance_field(0.5, 0.),
+            bounds: sprite_bounds,
+            content_mask,
+            color: color.opacity(element_opacity),
+            tile,
+            transformation: self.element_transformation,
         });
 
         Ok(())
@@ -3202,35 +4861,100 @@ impl Window {
         data: Arc<RenderImage>,
         frame_index: usize,
         grayscale: bool,
+    ) -> Result<()> {
+        self.paint_image_mip_level(bounds, corner_radii, data, frame_index, grayscale, 0, false)
+    }
+
+    /// Paint an image into the scene for the next frame at the current z-index, filtered with
+    /// `filter` where it's drawn at a size other than its own. Mip levels the filter needs are
+    /// generated in the background, redrawing the window when they're ready.
+    /// This method will panic if the frame_index is not valid
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_image_with_filter(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        data: Arc<RenderImage>,
+        frame_index: usize,
+        grayscale: bool,
+        filter: ImageFilter,
+        cx: &App,
+    ) -> Result<()> {
+        let mip_level = match filter {
+            ImageFilter::Mipmapped => {
+                let image_size = data.size(frame_index);
+                let drawn_size = bounds.size.scale(self.rasterization_scale());
+                let downscale = (image_size.width.0 as f32 / drawn_size.width.0)
+                    .min(image_size.height.0 as f32 / drawn_size.height.0);
+                let mip_level = (downscale.log2().floor().max(0.) as u32)
+                    .min(data.mip_level_count(frame_index).saturating_sub(1));
+                let generated_count = data.generated_mip_level_count(frame_index);
+                if mip_level >= generated_count && data.begin_generating_mip_levels(frame_index) {
+                    let image = data.clone();
+                    let generate = cx.background_spawn(async move {
+                        image.generate_mip_levels(frame_index, mip_level);
+                    });
+                    self.spawn(cx, async move |cx| {
+                        generate.await;
+                        cx.update(|window, _| window.refresh()).ok();
+                    })
+                    .detach();
+                }
+                mip_level.min(generated_count - 1)
+            }
+            ImageFilter::Nearest | ImageFilter::Linear => 0,
+        };
+        self.paint_image_mip_level(
+            bounds,
+            corner_radii,
+            data,
+            frame_index,
+            grayscale,
+            mip_level,
+            filter == ImageFilter::Nearest,
+        )
+    }
+
+    fn paint_image_mip_level(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        data: Arc<RenderImage>,
+        frame_index: usize,
+        grayscale: bool,
+        mip_level: u32,
+        nearest: bool,
     ) -> Result<()> {
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let bounds = bounds.scale(scale_factor);
         let params = RenderImageParams {
             image_id: data.id,
             frame_index,
+            mip_level,
         };
+        let generated_level = data.mip_level(frame_index, mip_level);
 
         let tile = self
             .sprite_atlas
             .get_or_insert_with(&params.into(), &mut || {
-                Ok(Some((
-                    data.size(frame_index),
-                    Cow::Borrowed(
-                        data.as_bytes(frame_index)
-                            .expect("It's the caller's job to pass a valid frame index"),
-                    ),
-                )))
+                Ok(match &generated_level {
+                    Some(level) => Some((level.size, Cow::Borrowed(&level.bytes[..]))),
+                    None => data
+                        .as_bytes(frame_index)
+                        .map(|bytes| (data.size(frame_index), Cow::Borrowed(bytes))),
+                })
             })?
-            .expect("Callback above only returns Some");
+            .expect("It's the caller's job to pass a valid frame index");
         let content_mask = self.content_mask().scale(scale_factor);
         let corner_radii = corner_radii.scale(scale_factor);
         let opacity = self.element_opacity();
 
         self.next_frame.scene.insert_primitive(PolychromeSprite {
             order: 0,
-            pad: 0,
+            nearest: nearest as u32,
             grayscale,
             bounds: bounds
                 .map_origin(|origin| origin.floor())
@@ -3239,6 +4963,7 @@ impl Window {
             corner_radii,
             tile,
             opacity,
+            transformation: self.element_transformation,
         });
         Ok(())
     }
@@ -3252,7 +4977,7 @@ impl Window {
 
         self.invalidator.debug_assert_paint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let bounds = bounds.scale(scale_factor);
         let content_mask = self.content_mask().scale(scale_factor);
         self.next_frame.scene.insert_primitive(PaintSurface {
@@ -3263,15 +4988,78 @@ impl Window {
         });
     }
 
+    /// Positions the platform `view` over `bounds` for the next frame, clipped to the current
+    /// content mask. The view is identified across frames by `id`, within the current element's
+    /// id, so that a view placed by another element is embedded anew. See [`crate::native_view`].
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_native_view(
+        &mut self,
+        id: impl Into<ElementId>,
+        bounds: Bounds<Pixels>,
+        view: raw_window_handle::RawWindowHandle,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        let clip = bounds.intersect(&self.content_mask().bounds);
+        let placement = self.with_global_id(id.into(), |id, window| NativeViewPlacement {
+            id: id.clone(),
+            view,
+            bounds: window.content_to_window_bounds(bounds),
+            clip: window.content_to_window_bounds(clip),
+        });
+        self.next_frame.native_views.push(placement);
+    }
+
+    /// Shows `view` in a borderless pop-up window over `bounds` for the next frame, where it isn't
+    /// clipped by this window's edges. The pop-up follows this window when it moves, and is closed
+    /// once a frame doesn't paint it. See [`crate::Anchored::popup`].
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_popup(&mut self, bounds: Bounds<Pixels>, view: AnyView) {
+        self.invalidator.debug_assert_paint();
+
+        self.next_frame.popups.push(PopupRequest { view, bounds });
+    }
+
+    /// Paint a region that `renderer` draws with the platform renderer's GPU API into the scene
+    /// for the next frame. The region is ordered with the rest of the scene and clipped to the
+    /// current content mask.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn paint_custom_render<R: CustomRenderer>(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        renderer: Arc<R>,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        let scale_factor = self.rasterization_scale();
+        let bounds = bounds.scale(scale_factor);
+        let content_mask = self.content_mask().scale(scale_factor);
+        self.next_frame
+            .scene
+            .insert_primitive(CustomRenderPrimitive {
+                order: 0,
+                bounds,
+                content_mask,
+                renderer,
+                renderer_cast: const { &(cast_custom_renderer::<R> as CustomRendererCast) },
+            });
+    }
+
     /// Removes an image from the sprite atlas.
     pub fn drop_image(&mut self, data: Arc<RenderImage>) -> Result<()> {
         for frame_index in 0..data.frame_count() {
-            let params = RenderImageParams {
-                image_id: data.id,
-                frame_index,
-            };
+            for mip_level in 0..data.mip_level_count(frame_index) {
+                let params = RenderImageParams {
+                    image_id: data.id,
+                    frame_index,
+                    mip_level,
+                };
 
-            self.sprite_atlas.remove(&params.clone().into());
+                self.sprite_atlas.remove(&params.into());
+            }
         }
 
         Ok(())
@@ -3294,7 +5082,9 @@ impl Window {
         cx.layout_id_buffer.clear();
         cx.layout_id_buffer.extend(children);
         let rem_size = self.rem_size();
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
+        let mut style = style;
+        style.resolve_ch_lengths(|| self.ch_width());
 
         self.layout_engine.as_mut().unwrap().request_layout(
             style,
@@ -3320,7 +5110,9 @@ impl Window {
         self.invalidator.debug_assert_prepaint();
 
         let rem_size = self.rem_size();
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
+        let mut style = style;
+        style.resolve_ch_lengths(|| self.ch_width());
         self.layout_engine
             .as_mut()
             .unwrap()
@@ -3352,7 +5144,7 @@ impl Window {
     pub fn layout_bounds(&mut self, layout_id: LayoutId) -> Bounds<Pixels> {
         self.invalidator.debug_assert_prepaint();
 
-        let scale_factor = self.scale_factor();
+        let scale_factor = self.rasterization_scale();
         let mut bounds = self
             .layout_engine
             .as_mut()
@@ -3371,17 +5163,85 @@ impl Window {
     pub fn insert_hitbox(&mut self, bounds: Bounds<Pixels>, behavior: HitboxBehavior) -> Hitbox {
         self.invalidator.debug_assert_prepaint();
 
-        let content_mask = self.content_mask();
-        let mut id = self.next_hitbox_id;
-        self.next_hitbox_id = self.next_hitbox_id.next();
-        let hitbox = Hitbox {
-            id,
-            bounds,
-            content_mask,
-            behavior,
-        };
-        self.next_frame.hitboxes.push(hitbox.clone());
-        hitbox
+        let content_mask = self.content_mask();
+        let mut id = self.next_hitbox_id;
+        self.next_hitbox_id = self.next_hitbox_id.next();
+        let hitbox = Hitbox {
+            id,
+            bounds,
+            content_mask,
+            behavior,
+        };
+        self.next_frame.hitboxes.push(hitbox.clone());
+        hitbox
+    }
+
+    /// Associates a hitbox with the element that inserted it, so that events can be dispatched to
+    /// the element with [`Window::dispatch_event_to`].
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub fn register_element_hitbox(&mut self, global_id: &GlobalElementId, hitbox: &Hitbox) {
+        self.invalidator.debug_assert_prepaint();
+        self.next_frame
+            .element_hitboxes
+            .push((global_id.clone(), hitbox.clone()));
+    }
+
+    /// Describes the kinds of listener an element registered for a hitbox, which
+    /// [`Window::hit_test`] reports when debugging why events do or don't reach the element.
+    ///
+    /// This method should only be called as part of the prepaint phase of element drawing.
+    pub fn register_hitbox_listeners(
+        &mut self,
+        hitbox: &Hitbox,
+        listeners: impl IntoIterator<Item = ListenerKind>,
+    ) {
+        self.invalidator.debug_assert_prepaint();
+        let listeners = listeners.into_iter().collect::<SmallVec<_>>();
+        if !listeners.is_empty() {
+            self.next_frame
+                .hitbox_listeners
+                .push((hitbox.id, listeners));
+        }
+    }
+
+    /// Returns the hitboxes at `position` in the last rendered frame, frontmost first, with the
+    /// elements that inserted them and the kinds of listener those elements registered.
+    ///
+    /// Hitboxes behind one that blocks the mouse are included but aren't hovered, which is
+    /// usually why an element's mouse listeners don't fire. `position` is in the same
+    /// coordinates as [`Window::mouse_position`].
+    pub fn hit_test(&self, position: Point<Pixels>) -> Vec<ElementDebugInfo> {
+        let frame = &self.rendered_frame;
+        let hit_test = frame.hit_test(position);
+        let hovered_ids = &hit_test.ids[..hit_test.hover_hitbox_count];
+        frame
+            .hitboxes
+            .iter()
+            .rev()
+            .filter(|hitbox| {
+                hitbox
+                    .bounds
+                    .intersect(&hitbox.content_mask.bounds)
+                    .contains(&position)
+            })
+            .map(|hitbox| ElementDebugInfo {
+                element_id: frame
+                    .element_hitboxes
+                    .iter()
+                    .find(|(_, element_hitbox)| element_hitbox.id == hitbox.id)
+                    .map(|(global_id, _)| global_id.clone()),
+                bounds: hitbox.bounds,
+                listeners: frame
+                    .hitbox_listeners
+                    .iter()
+                    .filter(|(hitbox_id, _)| *hitbox_id == hitbox.id)
+                    .flat_map(|(_, listeners)| listeners.iter().copied())
+                    .collect(),
+                hovered: hovered_ids.contains(&hitbox.id),
+                handles_scroll: hit_test.ids.contains(&hitbox.id),
+            })
+            .collect()
     }
 
     /// Set a hitbox which will act as a control area of the platform window.
@@ -3499,6 +5359,29 @@ impl Window {
         )));
     }
 
+    /// Register a region that receives input no element handled, for embedding a game or 3D
+    /// engine viewport beneath GPUI overlays. Mouse events over `hitbox` and keyboard events
+    /// while `focus_handle` is focused are forwarded to `listener` with the pointer's movement
+    /// and the key's scan code. Clicking in the region focuses `focus_handle`, and once a mouse
+    /// down has been forwarded, mouse events are forwarded to the region until the button is
+    /// released, even outside of it.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn on_raw_input(
+        &mut self,
+        hitbox: &Hitbox,
+        focus_handle: Option<&FocusHandle>,
+        listener: impl Fn(&RawInputEvent, &mut Window, &mut App) + 'static,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        self.next_frame.raw_input_regions.push(RawInputRegion {
+            hitbox_id: hitbox.id,
+            focus_handle: focus_handle.cloned(),
+            listener: Rc::new(listener),
+        });
+    }
+
     /// Register a key event listener on this node for the next frame. The type of event
     /// is determined by the first parameter of the given listener. When the next frame is rendered
     /// the listener will be cleared.
@@ -3593,23 +5476,40 @@ impl Window {
     fn reset_cursor_style(&self, cx: &mut App) {
         // Set the cursor only if we're the active window.
         if self.is_window_hovered() {
-            let style = self
-                .rendered_frame
-                .cursor_style(self)
-                .unwrap_or(CursorStyle::Arrow);
+            let style = if self.is_cursor_hidden() {
+                CursorStyle::None
+            } else {
+                self.rendered_frame
+                    .cursor_style(self)
+                    .unwrap_or(CursorStyle::Arrow)
+            };
             cx.platform.set_cursor_style(style);
         }
     }
 
     /// Dispatch a given keystroke as though the user had typed it.
     /// You can create a keystroke with Keystroke::parse("").
+    /// The keystroke's events come from [`InputSource::Synthetic`].
     pub fn dispatch_keystroke(&mut self, keystroke: Keystroke, cx: &mut App) -> bool {
+        if !self.accepts_input_from(InputSource::Synthetic) {
+            return false;
+        }
+        let previous_source = mem::replace(&mut self.input_source, InputSource::Synthetic);
+        let handled = self.dispatch_synthetic_keystroke(keystroke, cx);
+        self.input_source = previous_source;
+        handled
+    }
+
+    fn dispatch_synthetic_keystroke(&mut self, keystroke: Keystroke, cx: &mut App) -> bool {
         let keystroke = keystroke.with_simulated_ime();
-        let result = self.dispatch_event(
+        let result = self.dispatch_event_from(
+            InputSource::Synthetic,
             PlatformInput::KeyDown(KeyDownEvent {
                 keystroke: keystroke.clone(),
                 is_held: false,
                 prefer_character_input: false,
+                scan_code: None,
+                timestamp: None,
             }),
             cx,
         );
@@ -3643,25 +5543,226 @@ impl Window {
             .unwrap_or_else(|| action.name().to_string())
     }
 
+    /// Returns where the input event being dispatched came from. Outside of event dispatch, this
+    /// is [`InputSource::Platform`].
+    pub fn input_source(&self) -> InputSource {
+        self.input_source
+    }
+
+    /// Returns when the platform reported the input event being dispatched, for measuring how long
+    /// handling it takes. For events without a [timestamp](PlatformInput::timestamp) or that
+    /// didn't come from the platform, this is when the window received them. Outside of event
+    /// dispatch, this is `None`.
+    pub fn input_timestamp(&self) -> Option<Instant> {
+        self.input_timestamp
+    }
+
+    /// Sets which input sources the window accepts events from, such as only a remote client
+    /// during a remote-control session or only synthetic events while replaying input. Events
+    /// from other sources are dropped before they're dispatched.
+    pub fn set_input_source_filter(&mut self, filter: impl Fn(InputSource) -> bool + 'static) {
+        self.input_source_filter = Some(Box::new(filter));
+    }
+
+    /// Accepts events from every input source again.
+    pub fn clear_input_source_filter(&mut self) {
+        self.input_source_filter = None;
+    }
+
+    /// Starts capturing the input events dispatched to the window, with when they arrived,
+    /// discarding any recording in progress. Synthetic events, including those being replayed,
+    /// aren't recorded.
+    pub fn start_input_recording(&mut self, cx: &App) {
+        self.input_recorder = Some(InputRecorder::new(cx.background_executor().now()));
+    }
+
+    /// Stops capturing input and returns what was recorded, or `None` if the window wasn't
+    /// recording.
+    pub fn stop_input_recording(&mut self) -> Option<InputRecording> {
+        self.input_recorder.take().map(InputRecorder::finish)
+    }
+
+    /// Returns whether the window is recording its input.
+    pub fn is_recording_input(&self) -> bool {
+        self.input_recorder.is_some()
+    }
+
+    /// Dispatches the events of `recording` to the window as [`InputSource::Synthetic`] input,
+    /// spaced as they were recorded. A `speed` of 2 replays them twice as fast; speeds that
+    /// aren't positive replay them at the original speed. Dropping the returned task stops the
+    /// replay.
+    pub fn replay_input(&self, recording: InputRecording, speed: f32, cx: &App) -> Task<()> {
+        let speed = if speed > 0. && speed.is_finite() {
+            speed
+        } else {
+            1.
+        };
+        self.spawn(cx, async move |cx| {
+            let started_at = cx.background_executor().now();
+            for event in recording.events {
+                let due = started_at + event.offset.div_f32(speed);
+                let now = cx.background_executor().now();
+                if due > now {
+                    cx.background_executor().timer(due - now).await;
+                }
+                let dispatched = cx.update(|window, cx| {
+                    window.dispatch_event_from(InputSource::Synthetic, event.input, cx);
+                });
+                if dispatched.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn accepts_input_from(&self, source: InputSource) -> bool {
+        self.input_source_filter
+            .as_ref()
+            .is_none_or(|filter| filter(source))
+    }
+
     /// Dispatch a mouse or keyboard event on the window.
-    #[profiling::function]
     pub fn dispatch_event(&mut self, event: PlatformInput, cx: &mut App) -> DispatchEventResult {
+        self.dispatch_event_from(InputSource::Platform, event, cx)
+    }
+
+    /// Dispatch a mouse or keyboard event on the window as though it came from `source`. Events
+    /// from sources rejected by the window's input source filter are dropped.
+    pub fn dispatch_event_from(
+        &mut self,
+        source: InputSource,
+        event: PlatformInput,
+        cx: &mut App,
+    ) -> DispatchEventResult {
+        if !self.accepts_input_from(source) {
+            return DispatchEventResult {
+                propagate: true,
+                default_prevented: false,
+            };
+        }
+        if source != InputSource::Synthetic
+            && let Some(recorder) = self.input_recorder.as_mut()
+        {
+            recorder.record(cx.background_executor().now(), &event);
+        }
+        record_crash_event(self.handle.window_id(), &event);
+        if let PlatformInput::MouseDown(crate::MouseDownEvent {
+            button: MouseButton::Navigate(direction),
+            ..
+        }) = &event
+        {
+            cx.report_telemetry(|| TelemetryEvent::Navigated {
+                window: self.handle,
+                direction: *direction,
+            });
+        }
+        // Replayed events keep the timestamps they were recorded with, which are long past.
+        let timestamp = match source {
+            InputSource::Platform => event.timestamp().unwrap_or_else(Instant::now),
+            _ => Instant::now(),
+        };
+        if source == InputSource::Platform {
+            self.undrawn_input_timestamp.get_or_insert(timestamp);
+        }
+        let previous_source = mem::replace(&mut self.input_source, source);
+        let previous_timestamp = self.input_timestamp.replace(timestamp);
+        let result = self.dispatch_input(event, cx);
+        self.input_source = previous_source;
+        self.input_timestamp = previous_timestamp;
+        result
+    }
+
+    /// Dispatch an event to the element with the given id, as painted in the last frame, through
+    /// the same path as platform input. Mouse events are moved to the center of the element's
+    /// visible bounds, so they reach whatever is drawn there, and keyboard events go to the
+    /// focused element as usual. A click is a [`MouseDownEvent`] followed by a [`MouseUpEvent`].
+    ///
+    /// The event is dispatched as [`InputSource::Synthetic`]. Returns `None` if no element with
+    /// the id registered a hitbox in the last frame. When several did, the one painted last is
+    /// used.
+    pub fn dispatch_event_to(
+        &mut self,
+        element_id: impl Into<ElementId>,
+        mut event: PlatformInput,
+        cx: &mut App,
+    ) -> Option<DispatchEventResult> {
+        let element_id = element_id.into();
+        let target = self
+            .rendered_frame
+            .element_hitboxes
+            .iter()
+            .rev()
+            .find(|(global_id, _)| global_id.0.last() == Some(&element_id))
+            .map(|(_, hitbox)| {
+                hitbox
+                    .bounds
+                    .intersect(&hitbox.content_mask.bounds)
+                    .center()
+            })?;
+        // Dispatch maps positions from window coordinates into the scaled content.
+        let target = target.map(|coordinate| coordinate * self.content_transform.scale);
+
+        match &mut event {
+            PlatformInput::MouseDown(event) => event.position = target,
+            PlatformInput::MouseUp(event) => event.position = target,
+            PlatformInput::MouseMove(event) => event.position = target,
+            PlatformInput::ScrollWheel(event) => event.position = target,
+            PlatformInput::FileDrop(
+                FileDropEvent::Entered { position, .. }
+                | FileDropEvent::Pending { position }
+                | FileDropEvent::Submit { position },
+            ) => *position = target,
+            PlatformInput::KeyDown(_)
+            | PlatformInput::KeyUp(_)
+            | PlatformInput::ModifiersChanged(_)
+            | PlatformInput::MouseExited(_)
+            | PlatformInput::FileDrop(FileDropEvent::Exited) => {}
+        }
+        Some(self.dispatch_event_from(InputSource::Synthetic, event, cx))
+    }
+
+    #[profiling::function]
+    fn dispatch_input(&mut self, event: PlatformInput, cx: &mut App) -> DispatchEventResult {
         self.last_input_timestamp.set(Instant::now());
+        let event = self.content_input(event);
+
+        if let PlatformInput::MouseMove(_)
+        | PlatformInput::MouseDown(_)
+        | PlatformInput::ScrollWheel(_) = &event
+            && let Some(idle_cursor) = self
+                .kiosk
+                .as_mut()
+                .and_then(|kiosk| kiosk.idle_cursor.as_mut())
+            && idle_cursor.pointer_moved(Instant::now())
+        {
+            self.reset_cursor_style(cx);
+        }
+
+        if let PlatformInput::KeyDown(_) = &event {
+            let now = Instant::now();
+            self.caret_blink.reset(now);
+            self.notify_caret_views(now, cx.caret_blink_interval(), cx);
+        }
 
         // Track whether this input was keyboard-based for focus-visible styling
-        self.last_input_modality = match &event {
-            PlatformInput::KeyDown(_) | PlatformInput::ModifiersChanged(_) => {
-                InputModality::Keyboard
-            }
-            PlatformInput::MouseDown(e) if e.is_focusing() => InputModality::Mouse,
+        let input_modality = match &event {
+            PlatformInput::KeyDown(_) => InputModality::Keyboard,
+            PlatformInput::MouseDown(_) => InputModality::Pointer,
             _ => self.last_input_modality,
         };
+        if input_modality != self.last_input_modality {
+            self.last_input_modality = input_modality;
+            if self.focus.is_some() {
+                self.refresh();
+            }
+        }
 
         // Handlers may set this to false by calling `stop_propagation`.
         cx.propagate_event = true;
         // Handlers may set this to true by calling `prevent_default`.
         self.default_prevented = false;
 
+        let previous_mouse_position = self.mouse_position;
         let event = match event {
             // Track the mouse position with our own state, since accessing the platform
             // API for the mouse position can only occur on the main thread.
@@ -3711,6 +5812,7 @@ impl Window {
                         position,
                         pressed_button: Some(MouseButton::Left),
                         modifiers: Modifiers::default(),
+                        timestamp: None,
                     })
                 }
                 FileDropEvent::Pending { position } => {
@@ -3719,6 +5821,7 @@ impl Window {
                         position,
                         pressed_button: Some(MouseButton::Left),
                         modifiers: Modifiers::default(),
+                        timestamp: None,
                     })
                 }
                 FileDropEvent::Submit { position } => {
@@ -3729,6 +5832,7 @@ impl Window {
                         position,
                         modifiers: Modifiers::default(),
                         click_count: 1,
+                        timestamp: None,
                     })
                 }
                 FileDropEvent::Exited => {
@@ -3745,12 +5849,103 @@ impl Window {
             self.dispatch_key_event(any_key_event, cx);
         }
 
+        if !self.rendered_frame.raw_input_regions.is_empty() || self.raw_input_capture.is_some() {
+            self.dispatch_raw_input(&event, previous_mouse_position, cx);
+        }
+
+        // As in the DOM, preventing the default action of a key down keeps its text from being
+        // inserted, which platforms only do for key downs that aren't handled.
+        let key_down_prevented =
+            self.default_prevented && matches!(event, PlatformInput::KeyDown(_));
         DispatchEventResult {
-            propagate: cx.propagate_event,
+            propagate: cx.propagate_event && !key_down_prevented,
             default_prevented: self.default_prevented,
         }
     }
 
+    fn dispatch_raw_input(
+        &mut self,
+        event: &PlatformInput,
+        previous_mouse_position: Point<Pixels>,
+        cx: &mut App,
+    ) {
+        let unhandled = cx.propagate_event && !self.default_prevented;
+        let listener = match event {
+            PlatformInput::KeyDown(_)
+            | PlatformInput::KeyUp(_)
+            | PlatformInput::ModifiersChanged(_) => {
+                if !unhandled {
+                    return;
+                }
+                self.rendered_frame
+                    .raw_input_regions
+                    .iter()
+                    .rev()
+                    .find(|region| {
+                        region
+                            .focus_handle
+                            .as_ref()
+                            .is_some_and(|focus_handle| focus_handle.is_focused(self))
+                    })
+                    .map(|region| region.listener.clone())
+            }
+            PlatformInput::MouseDown(_) => {
+                self.raw_input_capture = None;
+                if !unhandled {
+                    return;
+                }
+                let Some(region) = self.hovered_raw_input_region(false) else {
+                    return;
+                };
+                if let Some(focus_handle) = &region.focus_handle {
+                    self.focus(focus_handle);
+                }
+                self.raw_input_capture = Some(region.listener.clone());
+                Some(region.listener)
+            }
+            PlatformInput::MouseUp(_) => self.raw_input_capture.take().or_else(|| {
+                unhandled
+                    .then(|| self.hovered_raw_input_region(false))
+                    .flatten()
+                    .map(|region| region.listener)
+            }),
+            PlatformInput::MouseMove(_) if self.raw_input_capture.is_some() => {
+                self.raw_input_capture.clone()
+            }
+            PlatformInput::ScrollWheel(_) if unhandled => self
+                .hovered_raw_input_region(true)
+                .map(|region| region.listener),
+            _ if unhandled => self
+                .hovered_raw_input_region(false)
+                .map(|region| region.listener),
+            _ => None,
+        };
+
+        if let Some(listener) = listener {
+            let mouse_delta = if event.mouse_event().is_some() {
+                self.mouse_position - previous_mouse_position
+            } else {
+                Point::default()
+            };
+            listener(&RawInputEvent::new(event, mouse_delta), self, cx);
+        }
+    }
+
+    fn hovered_raw_input_region(&self, scroll: bool) -> Option<RawInputRegion> {
+        self.rendered_frame
+            .raw_input_regions
+            .iter()
+            .rev()
+            .find(|region| {
+                if scroll {
+                    region.hitbox_id.should_handle_scroll(self)
+                } else {
+                    region.hitbox_id.is_hovered(self)
+                }
+            })
+            .cloned()
+    }
+
     fn dispatch_mouse_event(&mut self, event: &dyn Any, cx: &mut App) {
         let hit_test = self.rendered_frame.hit_test(self.mouse_position());
         if hit_test != self.mouse_hit_test {
@@ -4066,6 +6261,8 @@ impl Window {
                 keystroke: replay.keystroke.clone(),
                 is_held: false,
                 prefer_character_input: true,
+                scan_code: None,
+                timestamp: None,
             };
 
             cx.propagate_event = true;
@@ -4112,6 +6309,10 @@ impl Window {
         cx: &mut App,
     ) {
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
+        cx.report_telemetry(|| TelemetryEvent::ActionDispatched {
+            window: Some(self.handle),
+            action: action.name(),
+        });
 
         // Capture phase for global actions.
         cx.propagate_event = true;
@@ -4444,13 +6645,10 @@ impl Window {
     /// If the callback returns false, the window won't be closed.
     pub fn on_window_should_close(
         &self,
-        cx: &App,
+        _cx: &App,
         f: impl Fn(&mut Window, &mut App) -> bool + 'static,
     ) {
-        let mut cx = self.to_async(cx);
-        self.platform_window.on_should_close(Box::new(move || {
-            cx.update(|window, cx| f(window, cx)).unwrap_or(true)
-        }))
+        self.should_close_handler.replace(Some(Rc::new(f)));
     }
 
     /// Register an action listener on this node for the next frame. The type of action
@@ -4911,7 +7109,7 @@ impl<V: 'static> From<WindowHandle<V>> for AnyWindowHandle {
 }
 
 /// A handle to a window with any root view type, which can be downcast to a window with a specific root view type.
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AnyWindowHandle {
     pub(crate) id: WindowId,
     state_type: TypeId,
@@ -5147,6 +7345,10 @@ pub struct PaintQuad {
     pub bounds: Bounds<Pixels>,
     /// The radii of the quad's corners.
     pub corner_radii: Corners<Pixels>,
+    /// The vertical radii of the quad's corners, which makes them elliptical with `corner_radii`
+    /// as their horizontal radii, as in CSS's `border-radius: 20px / 10px`. Corners are circular
+    /// when this is `None`. The quad's shadow keeps circular corners with the horizontal radii.
+    pub vertical_corner_radii: Option<Corners<Pixels>>,
     /// The background color of the quad.
     pub background: Background,
     /// The widths of the quad's borders.
@@ -5155,6 +7357,11 @@ pub struct PaintQuad {
     pub border_color: Hsla,
     /// The style of the quad's borders.
     pub border_style: BorderStyle,
+    /// A shadow drawn beneath the quad, cast by the quad's rounded rectangle.
+    pub shadow: Option<BoxShadow>,
+    /// How fast the dashes of a dashed or dotted border move clockwise around the quad, in
+    /// pixels per second. The renderer moves them without the quad being painted again.
+    pub dash_speed: Pixels,
 }
 
 impl PaintQuad {
@@ -5166,6 +7373,15 @@ impl PaintQuad {
         }
     }
 
+    /// Sets the vertical radii of the quad's corners, making them elliptical. The radii set with
+    /// [`PaintQuad::corner_radii`] become their horizontal radii.
+    pub fn vertical_corner_radii(self, vertical_corner_radii: impl Into<Corners<Pixels>>) -> Self {
+        PaintQuad {
+            vertical_corner_radii: Some(vertical_corner_radii.into()),
+            ..self
+        }
+    }
+
     /// Sets the border widths of the quad.
     pub fn border_widths(self, border_widths: impl Into<Edges<Pixels>>) -> Self {
         PaintQuad {
@@ -5189,6 +7405,23 @@ impl PaintQuad {
             ..self
         }
     }
+
+    /// Sets the shadow drawn beneath the quad.
+    pub fn shadow(self, shadow: impl Into<Option<BoxShadow>>) -> Self {
+        PaintQuad {
+            shadow: shadow.into(),
+            ..self
+        }
+    }
+
+    /// Sets how fast the dashes of a dashed or dotted border move clockwise around the quad, in
+    /// pixels per second.
+    pub fn dash_speed(self, dash_speed: impl Into<Pixels>) -> Self {
+        PaintQuad {
+            dash_speed: dash_speed.into(),
+            ..self
+        }
+    }
 }
 
 /// Creates a quad with the given parameters.
@@ -5203,22 +7436,51 @@ pub fn quad(
     PaintQuad {
         bounds,
         corner_radii: corner_radii.into(),
+        vertical_corner_radii: None,
         background: background.into(),
         border_widths: border_widths.into(),
         border_color: border_color.into(),
         border_style,
+        shadow: None,
+        dash_speed: Pixels::ZERO,
     }
 }
 
+/// Converts a quad's dash speed from pixels per second into the dash periods per second that the
+/// renderers move dashes by. Dashes are twice as long as the border is wide and dots are as long,
+/// each followed by a gap as long as the border is wide.
+fn dash_periods_per_second(quad: &PaintQuad) -> f32 {
+    let border_width = quad.border_widths.max();
+    if quad.border_style == BorderStyle::Solid || border_width <= Pixels::ZERO {
+        return 0.;
+    }
+    let widths_per_period = match quad.border_style {
+        BorderStyle::Dotted => 2.,
+        _ => 3.,
+    };
+    quad.dash_speed / (border_width * widths_per_period)
+}
+
+/// Packs the edge value and falloff width of a signed distance field sprite into
+/// [`MonochromeSprite::distance_field`].
+fn pack_distance_field(edge: f32, falloff: f32) -> u32 {
+    let to_fraction = |value: f32| (value.clamp(0., 1.) * u16::MAX as f32).round() as u32;
+    // Zero means the sprite isn't a distance field, so the edge is kept above it.
+    (to_fraction(falloff) << 16) | to_fraction(edge).max(1)
+}
+
 /// Creates a filled quad with the given bounds and background color.
 pub fn fill(bounds: impl Into<Bounds<Pixels>>, background: impl Into<Background>) -> PaintQuad {
     PaintQuad {
         bounds: bounds.into(),
         corner_radii: (0.).into(),
+        vertical_corner_radii: None,
         background: background.into(),
         border_widths: (0.).into(),
         border_color: transparent_black(),
         border_style: BorderStyle::default(),
+        shadow: None,
+        dash_speed: Pixels::ZERO,
     }
 }
 
@@ -5231,9 +7493,375 @@ pub fn outline(
     PaintQuad {
         bounds: bounds.into(),
         corner_radii: (0.).into(),
+        vertical_corner_radii: None,
         background: transparent_black().into(),
         border_widths: (1.).into(),
         border_color: border_color.into(),
         border_style,
+        shadow: None,
+        dash_speed: Pixels::ZERO,
+    }
+}
+
+/// The speed of the dashes of a [`marquee`], in pixels per second.
+const MARQUEE_DASH_SPEED: Pixels = px(8.);
+
+/// Creates a selection marquee: a rectangle outline of "marching ants", whose 1px dashes move
+/// clockwise around it without it being painted again. Paint a solid [`outline`] beneath it to
+/// keep the gaps between dashes visible on any background.
+pub fn marquee(bounds: impl Into<Bounds<Pixels>>, border_color: impl Into<Hsla>) -> PaintQuad {
+    outline(bounds, border_color, BorderStyle::Dashed).dash_speed(MARQUEE_DASH_SPEED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        MouseDownEvent, TestAppContext, VisualTestContext, anchored, canvas, ch, div, img, red,
+        subsurface,
+    };
+    use image::{Frame, Rgba, RgbaImage};
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_dash_periods_per_second() {
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(100.), px(100.)));
+        let dashed = outline(bounds, red(), BorderStyle::Dashed)
+            .border_widths(px(2.))
+            .dash_speed(px(30.));
+        assert_eq!(dash_periods_per_second(&dashed), 5.);
+
+        let dotted = PaintQuad {
+            border_style: BorderStyle::Dotted,
+            ..dashed.clone()
+        };
+        assert_eq!(dash_periods_per_second(&dotted), 7.5);
+
+        let solid = PaintQuad {
+            border_style: BorderStyle::Solid,
+            ..dashed.clone()
+        };
+        assert_eq!(dash_periods_per_second(&solid), 0.);
+        assert_eq!(dash_periods_per_second(&dashed.border_widths(px(0.))), 0.);
+    }
+
+    struct Marquee {
+        moving: bool,
+    }
+
+    impl Render for Marquee {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let moving = self.moving;
+            canvas(
+                |_, _, _| {},
+                move |bounds, _, window, _| {
+                    let speed = if moving { MARQUEE_DASH_SPEED } else { px(0.) };
+                    window.paint_quad(marquee(bounds, red()).dash_speed(speed));
+                },
+            )
+            .size_full()
+        }
+    }
+
+    #[gpui::test]
+    fn test_moving_dashes_keep_presenting(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| Marquee { moving: true });
+        let draw_and_present = |cx: &mut VisualTestContext| {
+            cx.update(|window, cx| {
+                window.refresh();
+                window.draw(cx).clear();
+                window.present();
+                window.needs_present.get()
+            })
+        };
+        assert!(draw_and_present(cx));
+
+        view.update(cx, |view, _| view.moving = false);
+        assert!(!draw_and_present(cx));
+    }
+
+    struct Columns {
+        widths: Rc<RefCell<Vec<Pixels>>>,
+    }
+
+    impl Render for Columns {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let column = |text_size: Pixels| {
+                let widths = self.widths.clone();
+                div().text_size(text_size).child(
+                    canvas(
+                        move |bounds, _, _| widths.borrow_mut().push(bounds.size.width),
+                        |_, _, _, _| {},
+                    )
+                    .w(ch(4.))
+                    .h(px(1.)),
+                )
+            };
+            div().child(column(px(10.))).child(column(px(20.)))
+        }
+    }
+
+    #[gpui::test]
+    fn test_ch_lengths_use_the_element_font(cx: &mut TestAppContext) {
+        let widths = Rc::new(RefCell::new(Vec::new()));
+        let (_, cx) = cx.add_window_view({
+            let widths = widths.clone();
+            |_, _| Columns { widths }
+        });
+        let ch_width = cx.update(|window, cx| {
+            widths.borrow_mut().clear();
+            window.refresh();
+            window.draw(cx).clear();
+            let font_id = window
+                .text_system()
+                .resolve_font(&window.text_style().font());
+            window.text_system().ch_advance(font_id, px(10.)).unwrap()
+        });
+
+        assert!(ch_width > px(0.));
+        assert_eq!(*widths.borrow(), [ch_width * 4., ch_width * 8.]);
+    }
+
+    struct Clicks {
+        count: usize,
+    }
+
+    impl Render for Clicks {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _, _, cx| {
+                    this.count += 1;
+                    cx.notify();
+                }),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_input_latency_is_reported_once(cx: &mut TestAppContext) {
+        let latencies = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let latencies = latencies.clone();
+            cx.on_input_latency(move |latency, _| latencies.borrow_mut().push(*latency))
+        });
+        let (view, cx) = cx.add_window_view(|_, _| Clicks { count: 0 });
+        cx.simulate_request_frame();
+
+        let timestamp = Instant::now() - Duration::from_millis(5);
+        cx.simulate_event(MouseDownEvent {
+            button: MouseButton::Left,
+            position: point(px(10.), px(10.)),
+            click_count: 1,
+            timestamp: Some(timestamp),
+            ..Default::default()
+        });
+        assert_eq!(view.read_with(cx, |view, _| view.count), 1);
+        assert!(latencies.borrow().is_empty());
+
+        cx.simulate_request_frame();
+        cx.simulate_request_frame();
+        let latencies = latencies.borrow();
+        assert_eq!(latencies.len(), 1);
+        assert_eq!(latencies[0].input_timestamp, timestamp);
+        assert!(latencies[0].latency >= Duration::from_millis(5));
+    }
+
+    #[gpui::test]
+    fn test_content_scale_keeps_the_device_scale_factor(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| Clicks { count: 0 });
+        let device_scale = cx.update(|window, _| window.scale_factor());
+        cx.update(|window, _| window.set_content_scale(2.));
+        cx.simulate_request_frame();
+        cx.update(|window, _| {
+            assert_eq!(window.scale_factor(), device_scale);
+            assert_eq!(window.rasterization_scale(), device_scale * 2.);
+        });
+    }
+
+    struct PopupContent;
+
+    impl Render for PopupContent {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().w(px(50.)).h(px(20.))
+        }
+    }
+
+    struct Overlay {
+        popup: Option<AnyView>,
+    }
+
+    impl Render for Overlay {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().children(
+                self.popup
+                    .clone()
+                    .map(|popup| anchored().position(point(px(80.), px(10.))).popup(popup)),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_anchored_popups_escape_and_follow_the_window(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| Overlay { popup: None });
+        let window = cx.update(|window, _| window.handle);
+        cx.simulate_window_resize(window, size(px(100.), px(100.)));
+        cx.simulate_window_move(window, point(px(200.), px(300.)));
+        let content = cx.new(|_| PopupContent);
+        let show_popup = |show: bool, cx: &mut VisualTestContext| {
+            view.update(cx, |view, cx| {
+                view.popup = show.then(|| content.clone().into());
+                cx.notify();
+            });
+            cx.run_until_parked();
+        };
+        let popup_bounds = |cx: &mut VisualTestContext| {
+            let popup = cx.update(|window, _| window.popups[0].handle);
+            cx.test_window(popup.into()).bounds()
+        };
+
+        show_popup(true, cx);
+        assert_eq!(cx.windows().len(), 2);
+        assert_eq!(
+            popup_bounds(cx),
+            Bounds::new(point(px(280.), px(310.)), size(px(50.), px(20.)))
+        );
+
+        cx.simulate_window_move(window, point(px(400.), px(500.)));
+        assert_eq!(popup_bounds(cx).origin, point(px(480.), px(510.)));
+
+        show_popup(false, cx);
+        assert_eq!(cx.windows().len(), 1);
+
+        show_popup(true, cx);
+        assert_eq!(cx.windows().len(), 2);
+        cx.update(|window, _| window.remove_window());
+        assert!(cx.windows().is_empty());
+    }
+
+    struct CountedView {
+        renders: usize,
+    }
+
+    impl Render for CountedView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.renders += 1;
+            div().size_full()
+        }
+    }
+
+    struct SubsurfaceRoot {
+        renders: usize,
+        sibling: Entity<CountedView>,
+        preview: Entity<CountedView>,
+    }
+
+    impl Render for SubsurfaceRoot {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.renders += 1;
+            div().size_full().child(self.sibling.clone()).child(
+                subsurface(self.preview.clone())
+                    .size(px(40.))
+                    .frame_rate(10),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_subsurface_redraws_only_its_view(cx: &mut TestAppContext) {
+        let (root, cx) = cx.add_window_view(|window, cx| {
+            window.set_root_view_cached(true);
+            SubsurfaceRoot {
+                renders: 0,
+                sibling: cx.new(|_| CountedView { renders: 0 }),
+                preview: cx.new(|_| CountedView { renders: 0 }),
+            }
+        });
+        let (sibling, preview) =
+            root.read_with(cx, |root, _| (root.sibling.clone(), root.preview.clone()));
+        let renders = |cx: &mut VisualTestContext| {
+            (
+                root.read_with(cx, |root, _| root.renders),
+                sibling.read_with(cx, |sibling, _| sibling.renders),
+                preview.read_with(cx, |preview, _| preview.renders),
+            )
+        };
+        assert_eq!(renders(cx), (1, 1, 1));
+
+        preview.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(renders(cx), (1, 1, 2));
+
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        assert_eq!(renders(cx), (1, 1, 3));
+
+        sibling.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(renders(cx), (2, 2, 3));
+    }
+
+    #[gpui::test]
+    fn test_subsurface_redraws_uncached_root(cx: &mut TestAppContext) {
+        let (root, cx) = cx.add_window_view(|_, cx| SubsurfaceRoot {
+            renders: 0,
+            sibling: cx.new(|_| CountedView { renders: 0 }),
+            preview: cx.new(|_| CountedView { renders: 0 }),
+        });
+        let (sibling, preview) =
+            root.read_with(cx, |root, _| (root.sibling.clone(), root.preview.clone()));
+
+        preview.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(root.read_with(cx, |root, _| root.renders), 2);
+        assert_eq!(sibling.read_with(cx, |sibling, _| sibling.renders), 2);
+        assert_eq!(preview.read_with(cx, |preview, _| preview.renders), 2);
+    }
+
+    struct Picture {
+        image: Arc<RenderImage>,
+    }
+
+    impl Render for Picture {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(img(self.image.clone()).size(px(10.)))
+        }
+    }
+
+    #[gpui::test]
+    fn test_svg_export_includes_images(cx: &mut TestAppContext) {
+        let buffer = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]));
+        let image = Arc::new(RenderImage::new(SmallVec::from_const([Frame::new(buffer)])));
+        let (_, cx) = cx.add_window_view(|_, _| Picture { image });
+
+        let svg = cx.update(|window, cx| window.rendered_frame_to_svg(cx));
+        assert_eq!(svg.matches("<symbol id=\"image").count(), 1);
+        assert!(svg.contains("<use href=\"#image"));
+        let sprite_count =
+            cx.update(|window, _| window.rendered_frame.scene.polychrome_sprites.len());
+        assert_eq!(sprite_count, 1, "the window is drawn again afterwards");
+    }
+
+    #[gpui::test]
+    fn test_pdf_export_includes_images(cx: &mut TestAppContext) {
+        let buffer = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]));
+        let image = Arc::new(RenderImage::new(SmallVec::from_const([Frame::new(buffer)])));
+        let (_, cx) = cx.add_window_view(|_, _| Picture { image });
+
+        let pdf = cx.update(|window, cx| window.rendered_frame_to_pdf(size(612., 792.), cx));
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert_eq!(pdf.matches("/Subtype /Image").count(), 1);
+        assert!(pdf.contains("/Im0 Do"));
+
+        let print = cx.update(|window, cx| window.print(size(612., 792.), cx));
+        cx.run_until_parked();
+        let error = cx.executor().block(print).unwrap_err();
+        assert!(error.to_string().contains("not supported"));
+        let window_id = cx.update(|window, _| window.handle.window_id().as_u64());
+        let path =
+            std::env::temp_dir().join(format!("gpui-print-{}-{window_id}.pdf", std::process::id()));
+        assert!(!path.exists(), "the print document is removed");
     }
 }
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use collections::FxHashSet;
+
+use crate::EntityId;
+
+/// Tracks the phase of a window's caret blink and which views painted a caret, so that only
+/// those views are redrawn when the caret toggles.
+pub(crate) struct CaretBlink {
+    epoch: Instant,
+    shown: bool,
+    views: FxHashSet<EntityId>,
+    refresh_window: bool,
+    blinking: bool,
+}
+
+impl CaretBlink {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            epoch: now,
+            shown: true,
+            views: FxHashSet::default(),
+            refresh_window: false,
+            blinking: false,
+        }
+    }
+
+    /// Restarts the blink cycle with the caret shown, as happens while the user types.
+    pub(crate) fn reset(&mut self, now: Instant) {
+        self.epoch = now;
+    }
+
+    /// Whether the caret is shown at `now`, blinking every `interval`.
+    pub(crate) fn is_shown(&self, now: Instant, interval: Duration) -> bool {
+        let interval = interval.as_nanos();
+        if interval == 0 {
+            return true;
+        }
+        let elapsed = now.saturating_duration_since(self.epoch).as_nanos();
+        (elapsed / interval).is_multiple_of(2)
+    }
+
+    /// How long after `now` the caret next toggles, blinking every `interval`.
+    pub(crate) fn until_toggle(&self, now: Instant, interval: Duration) -> Duration {
+        let interval = interval.as_nanos().max(1);
+        let elapsed = now.saturating_duration_since(self.epoch).as_nanos();
+        let remaining = interval - elapsed % interval;
+        Duration::from_nanos(u64::try_from(remaining).unwrap_or(u64::MAX))
+    }
+
+    /// Records that the caret was painted as `shown` by `view`, or by an element outside of any
+    /// view. Returns whether a blink task needs to be started.
+    pub(crate) fn subscribe(&mut self, view: Option<EntityId>, shown: bool) -> bool {
+        self.shown = shown;
+        match view {
+            Some(view) => {
+                self.views.insert(view);
+            }
+            None => self.refresh_window = true,
+        }
+        !std::mem::replace(&mut self.blinking, true)
+    }
+
+    /// Returns the views to redraw if the caret's visibility at `now` differs from the last one
+    /// painted, and whether the whole window needs redrawing. Views subscribe again when they
+    /// paint their caret.
+    pub(crate) fn take_stale(
+        &mut self,
+        now: Instant,
+        interval: Option<Duration>,
+    ) -> Option<(FxHashSet<EntityId>, bool)> {
+        let shown = interval.is_none_or(|interval| self.is_shown(now, interval));
+        if shown == self.shown {
+            return None;
+        }
+        self.shown = shown;
+        Some((
+            std::mem::take(&mut self.views),
+            std::mem::take(&mut self.refresh_window),
+        ))
+    }
+
+    /// Whether the caret `can_blink` and any view is waiting for it to toggle. Otherwise the
+    /// blink task stops until a caret is painted again.
+    pub(crate) fn keep_blinking(&mut self, can_blink: bool) -> bool {
+        self.blinking = can_blink && (!self.views.is_empty() || self.refresh_window);
+        self.blinking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caret_blink_phase() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(500);
+        let mut blink = CaretBlink::new(start);
+        assert!(blink.is_shown(start, interval));
+        assert!(!blink.is_shown(start + Duration::from_millis(600), interval));
+        assert!(blink.is_shown(start + Duration::from_millis(1100), interval));
+        assert_eq!(
+            blink.until_toggle(start + Duration::from_millis(600), interval),
+            Duration::from_millis(400)
+        );
+
+        blink.reset(start + Duration::from_millis(600));
+        assert!(blink.is_shown(start + Duration::from_millis(700), interval));
+        assert!(blink.is_shown(start, Duration::ZERO));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{Pixels, Point, ease_in_out};
+use std::time::{Duration, Instant};
+
+/// The smallest scale contents can be drawn at, which keeps the viewport they're laid out in
+/// finite.
+const MIN_SCALE: f32 = 0.1;
+
+/// The scale a window's contents are laid out at, and any zoom that is drawing them at a different
+/// scale without laying them out again. See [`Window::set_content_scale`](crate::Window::set_content_scale).
+pub(crate) struct ContentScale {
+    layout_scale: f32,
+    zoom: Option<Zoom>,
+}
+
+enum Zoom {
+    /// Follows a gesture, until the final scale is set.
+    Preview { scale: f32, anchor: Point<Pixels> },
+    /// Animates towards a new scale, which the contents are laid out at once it's reached.
+    Animation {
+        from: f32,
+        to: f32,
+        anchor: Point<Pixels>,
+        start: Instant,
+        duration: Duration,
+    },
+}
+
+/// How a frame's contents are mapped into the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ContentTransform {
+    /// Window pixels per content pixel.
+    pub(crate) scale: f32,
+    /// The origin of the root element, in content pixels, which keeps the zoom's anchor in place.
+    pub(crate) origin: Point<Pixels>,
+    /// Whether the scale is still animating.
+    pub(crate) animating: bool,
+}
+
+impl ContentTransform {
+    pub(crate) const IDENTITY: Self = Self {
+        scale: 1.,
+        origin: Point {
+            x: Pixels::ZERO,
+            y: Pixels::ZERO,
+        },
+        animating: false,
+    };
+}
+
+impl Default for ContentScale {
+    fn default() -> Self {
+        Self {
+            layout_scale: 1.,
+            zoom: None,
+        }
+    }
+}
+
+impl ContentScale {
+    /// The scale the contents are laid out at.
+    pub(crate) fn layout_scale(&self) -> f32 {
+        self.layout_scale
+    }
+
+    /// The scale the contents will be laid out at once any animation finishes.
+    pub(crate) fn target_scale(&self) -> f32 {
+        match &self.zoom {
+            Some(Zoom::Animation { to, .. }) => *to,
+            Some(Zoom::Preview { .. }) | None => self.layout_scale,
+        }
+    }
+
+    pub(crate) fn set(&mut self, scale: f32) {
+        self.layout_scale = scale.max(MIN_SCALE);
+        self.zoom = None;
+    }
+
+    pub(crate) fn preview(&mut self, scale: f32, anchor: Point<Pixels>) {
+        self.zoom = Some(Zoom::Preview {
+            scale: scale.max(MIN_SCALE),
+            anchor,
+        });
+    }
+
+    pub(crate) fn animate(
+        &mut self,
+        scale: f32,
+        anchor: Point<Pixels>,
+        duration: Duration,
+        now: Instant,
+    ) {
+        let from = self.visual_scale(now);
+        self.zoom = Some(Zoom::Animation {
+            from,
+            to: scale.max(MIN_SCALE),
+            anchor,
+            start: now,
+            duration,
+        });
+    }
+
+    /// Returns how to draw a frame at `now`, laying the contents out at the new scale if an
+    /// animation has finished.
+    pub(crate) fn frame(&mut self, now: Instant) -> ContentTransform {
+        if let Some(Zoom::Animation {
+            to,
+            start,
+            duration,
+            ..
+        }) = self.zoom
+            && now.saturating_duration_since(start) >= duration
+        {
+            self.set(to);
+        }
+
+        let (scale, anchor, animating) = match &self.zoom {
+            Some(Zoom::Preview { scale, anchor }) => (*scale, *anchor, false),
+            Some(Zoom::Animation { anchor, .. }) => (self.visual_scale(now), *anchor, true),
+            None => (self.layout_scale, Point::default(), false),
+        };
+        // The content under the anchor when laid out is drawn at the anchor.
+        let origin = anchor.map(|coordinate| coordinate / scale - coordinate / self.layout_scale);
+        ContentTransform {
+            scale,
+            origin,
+            animating,
+        }
+    }
+
+    fn visual_scale(&self, now: Instant) -> f32 {
+        match &self.zoom {
+            Some(Zoom::Preview { scale, .. }) => *scale,
+            Some(Zoom::Animation {
+                from,
+                to,
+                start,
+                duration,
+                ..
+            }) => {
+                let progress = if duration.is_zero() {
+                    1.
+                } else {
+                    (now.saturating_duration_since(*start).as_secs_f32() / duration.as_secs_f32())
+                        .min(1.)
+                };
+                from + (to - from) * ease_in_out(progress)
+            }
+            None => self.layout_scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point, px};
+
+    #[test]
+    fn test_animation_keeps_anchor_in_place() {
+        let start = Instant::now();
+        let anchor = point(px(200.), px(100.));
+        let mut content_scale = ContentScale::default();
+        content_scale.animate(2., anchor, Duration::from_millis(100), start);
+
+        let halfway = content_scale.frame(start + Duration::from_millis(50));
+        assert!(halfway.animating);
+        assert_eq!(halfway.scale, 1.5);
+        assert_eq!(content_scale.layout_scale(), 1.);
+        let anchored_content = anchor.map(|coordinate| coordinate / content_scale.layout_scale());
+        let drawn_at =
+            (anchored_content + halfway.origin).map(|coordinate| coordinate * halfway.scale);
+        assert!(
+            (drawn_at.x - anchor.x).abs() < px(0.01) && (drawn_at.y - anchor.y).abs() < px(0.01),
+            "the content laid out under the anchor stays under it"
+        );
+
+        let finished = content_scale.frame(start + Duration::from_millis(100));
+        assert_eq!(
+            finished,
+            ContentTransform {
+                scale: 2.,
+                origin: Point::default(),
+                animating: false,
+            }
+        );
+        assert_eq!(content_scale.layout_scale(), 2.);
+    }
+
+    #[test]
+    fn test_preview_is_replaced_by_final_scale() {
+        let now = Instant::now();
+        let mut content_scale = ContentScale::default();
+        content_scale.preview(1.25, point(px(0.), px(0.)));
+        assert_eq!(content_scale.frame(now).scale, 1.25);
+        assert_eq!(content_scale.layout_scale(), 1.);
+        content_scale.set(1.25);
+        assert_eq!(content_scale.frame(now).scale, 1.25);
+        assert_eq!(content_scale.frame(now).origin, Point::default());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    Bounds, ContentMask, Edges, GlobalElementId, Hsla, Pixels, Point, Quad, ScaledPixels, Scene,
+    Size, Window, fill, point, px, rgba, size,
+};
+
+/// Debugging aids drawn over a window's contents. See [`Window::set_debug_overlay`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DebugOverlay {
+    /// Draws grid lines one device pixel wide at this interval, snapped to device pixels. With an
+    /// interval of one pixel on a high density display, a magnified screenshot shows where each
+    /// logical pixel lands on the device pixel grid.
+    pub pixel_grid: Option<Pixels>,
+    /// Draws a line along the baseline of each line of text.
+    pub baselines: bool,
+    /// Marks quads in red when a thin border has an edge between device pixels, which renders it
+    /// blurry or thicker than intended.
+    pub misaligned_borders: bool,
+    /// Marks glyphs in red when their contrast with the background behind them is below the
+    /// WCAG AA threshold. Only solid backgrounds painted by element styles are considered, and
+    /// text over no background isn't checked.
+    pub low_contrast_text: bool,
+}
+
+impl DebugOverlay {
+    /// Returns whether any part of the overlay is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.pixel_grid.is_some()
+            || self.baselines
+            || self.misaligned_borders
+            || self.low_contrast_text
+    }
+}
+
+/// A kind of listener an element registered for its hitbox. See
+/// [`Window::hit_test`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ListenerKind {
+    /// Listens for mouse buttons being pressed.
+    MouseDown,
+    /// Listens for mouse buttons being released.
+    MouseUp,
+    /// Listens for the mouse moving.
+    MouseMove,
+    /// Listens for scroll wheel and trackpad scrolling.
+    ScrollWheel,
+    /// Listens for clicks in the bubble phase.
+    Click,
+    /// Listens for clicks in the capture phase.
+    ClickCapture,
+    /// Starts a drag when the mouse is dragged from the element.
+    Drag,
+    /// Accepts drags dropped on the element.
+    Drop,
+    /// Listens for the mouse entering and leaving the element.
+    Hover,
+    /// Shows a tooltip while the mouse rests on the element.
+    Tooltip,
+    /// Focuses the element when it's clicked.
+    Focus,
+}
+
+/// A hitbox under a point, with the element that inserted it. See
+/// [`Window::hit_test`].
+#[derive(Clone, Debug)]
+pub struct ElementDebugInfo {
+    /// The path of element ids to the element that inserted the hitbox, if the element has an id.
+    pub element_id: Option<GlobalElementId>,
+    /// The bounds of the hitbox, before clipping by its content mask.
+    pub bounds: Bounds<Pixels>,
+    /// The kinds of listener the element registered for the hitbox.
+    pub listeners: Vec<ListenerKind>,
+    /// Whether the hitbox counts as hovered at the point, which mouse listeners other than
+    /// scroll wheel listeners typically require before handling an event. A hitbox in front
+    /// that blocks the mouse makes this `false`.
+    pub hovered: bool,
+    /// Whether the hitbox should handle scroll events at the point.
+    pub handles_scroll: bool,
+}
+
+/// Borders at most this many device pixels wide are checked for misaligned edges. The blurred
+/// edges of wider borders are much less noticeable.
+const THIN_BORDER_WIDTH: f32 = 2.;
+/// How far from a device pixel boundary an edge may be before it is considered misaligned.
+const ALIGNMENT_TOLERANCE: f32 = 0.01;
+/// Grid lines are at least this many device pixels apart so they don't cover the content.
+const MIN_GRID_SPACING: f32 = 2.;
+
+/// Paints a baseline guide for a line of text whose baseline starts at `origin`.
+pub(crate) fn paint_baseline_guide(origin: Point<Pixels>, width: Pixels, window: &mut Window) {
+    let thickness = px(1.) / window.rasterization_scale();
+    window.paint_quad(fill(
+        Bounds::new(origin, size(width, thickness)),
+        rgba(0x00b4ffcc),
+    ));
+}
+
+/// Returns a marker for a glyph whose contrast with its background is too low.
+pub(crate) fn low_contrast_marker(
+    bounds: Bounds<ScaledPixels>,
+    content_mask: &ContentMask<ScaledPixels>,
+) -> Quad {
+    let red: Hsla = rgba(0xff0000ff).into();
+    let mut marker = overlay_quad(bounds, content_mask, red.opacity(0.2));
+    marker.border_color = red;
+    marker.border_widths = Edges::all(ScaledPixels(1.));
+    marker
+}
+
+/// Paints the pixel grid and misaligned border markers over everything else in the scene.
+pub(crate) fn paint_debug_overlay(
+    overlay: DebugOverlay,
+    viewport_size: Size<ScaledPixels>,
+    scale_factor: f32,
+    scene: &mut Scene,
+) {
+    let viewport = Bounds {
+        origin: Point::default(),
+        size: viewport_size,
+    };
+    let content_mask = ContentMask {
+        bounds: viewport,
+        ..Default::default()
+    };
+
+    if let Some(spacing) = overlay.pixel_grid
+        && spacing > px(0.)
+    {
+        let spacing = (spacing.0 * scale_factor).max(MIN_GRID_SPACING);
+        let color = rgba(0xff00ff40).into();
+        let mut x = 0.;
+        while x < viewport.size.width.0 {
+            let line = Bounds {
+                origin: point(ScaledPixels(x.floor()), ScaledPixels(0.)),
+                size: size(ScaledPixels(1.), viewport.size.height),
+            };
+            scene.insert_primitive(overlay_quad(line, &content_mask, color));
+            x += spacing;
+        }
+        let mut y = 0.;
+        while y < viewport.size.height.0 {
+            let line = Bounds {
+                origin: point(ScaledPixels(0.), ScaledPixels(y.floor())),
+                size: size(viewport.size.width, ScaledPixels(1.)),
+            };
+            scene.insert_primitive(overlay_quad(line, &content_mask, color));
+            y += spacing;
+        }
+    }
+
+    if overlay.misaligned_borders {
+        let red: Hsla = rgba(0xff0000ff).into();
+        for bounds in misaligned_borders(scene) {
+            let mut marker = overlay_quad(bounds, &content_mask, red.opacity(0.2));
+            marker.border_color = red;
+            marker.border_widths = Edges::all(ScaledPixels(2.));
+            scene.insert_primitive(marker);
+        }
+    }
+}
+
+fn overlay_quad(
+    bounds: Bounds<ScaledPixels>,
+    content_mask: &ContentMask<ScaledPixels>,
+    color: Hsla,
+) -> Quad {
+    Quad {
+        bounds,
+        content_mask: content_mask.clone(),
+        background: color.into(),
+        ..Default::default()
+    }
+}
+
+/// Returns the visible bounds of each quad that has a thin border with an edge between device
+/// pixels.
+fn misaligned_borders(scene: &Scene) -> Vec<Bounds<ScaledPixels>> {
+    scene
+        .quads
+        .iter()
+        .filter(|quad| has_misaligned_border(quad))
+        .map(|quad| quad.bounds.intersect(&quad.content_mask.bounds))
+        .filter(|bounds| !bounds.is_empty())
+        .collect()
+}
+
+fn has_misaligned_border(quad: &Quad) -> bool {
+    if quad.border_color.a <= 0. {
+        return false;
+    }
+    let bounds = &quad.bounds;
+    let widths = &quad.border_widths;
+    let edges = [
+        (widths.top, bounds.top(), bounds.top() + widths.top),
+        (
+            widths.bottom,
+            bounds.bottom() - widths.bottom,
+            bounds.bottom(),
+        ),
+        (widths.left, bounds.left(), bounds.left() + widths.left),
+        (widths.right, bounds.right() - widths.right, bounds.right()),
+    ];
+    edges.into_iter().any(|(width, start, end)| {
+        width.0 > 0. && width.0 <= THIN_BORDER_WIDTH && !(is_aligned(start) && is_aligned(end))
+    })
+}
+
+fn is_aligned(position: ScaledPixels) -> bool {
+    (position.0 - position.0.round()).abs() <= ALIGNMENT_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bordered_quad(x: f32, y: f32, border_width: f32) -> Quad {
+        Quad {
+            bounds: Bounds {
+                origin: point(ScaledPixels(x), ScaledPixels(y)),
+                size: size(ScaledPixels(20.), ScaledPixels(10.)),
+            },
+            content_mask: ContentMask {
+                bounds: Bounds {
+                    origin: Point::default(),
+                    size: size(ScaledPixels(100.), ScaledPixels(100.)),
+                },
+                ..Default::default()
+            },
+            border_color: rgba(0x000000ff).into(),
+            border_widths: Edges::all(ScaledPixels(border_width)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_misaligned_borders() {
+        assert!(!has_misaligned_border(&bordered_quad(10., 10., 1.)));
+        assert!(has_misaligned_border(&bordered_quad(10.5, 10., 1.)));
+        assert!(
+            has_misaligned_border(&bordered_quad(10., 10., 1.5)),
+            "the inner edge of the border falls between pixels"
+        );
+        assert!(
+            !has_misaligned_border(&bordered_quad(10.5, 10., 4.)),
+            "thick borders are not flagged"
+        );
+
+        let mut transparent = bordered_quad(10.5, 10., 1.);
+        transparent.border_color = Hsla::transparent_black();
+        assert!(!has_misaligned_border(&transparent));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{ElementId, EntityId};
+use itertools::Itertools;
+
+/// Thresholds for how much a window draws each frame, which help find lists that render every
+/// item when they should be virtualized. See [`Window::set_element_budget`](crate::Window::set_element_budget).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElementBudget {
+    /// The number of elements a frame may lay out.
+    pub max_elements: usize,
+    /// The number of primitives a frame may paint into the scene.
+    pub max_primitives: usize,
+}
+
+impl Default for ElementBudget {
+    fn default() -> Self {
+        Self {
+            max_elements: 10_000,
+            max_primitives: 50_000,
+        }
+    }
+}
+
+/// Counts what a window draws against its [`ElementBudget`], logging once when frames start
+/// exceeding it rather than every frame they do.
+pub(crate) struct ElementBudgetTracker {
+    budget: ElementBudget,
+    element_count: usize,
+    overflow_path: Option<String>,
+    elements_exceeded: bool,
+    primitives_exceeded: bool,
+}
+
+impl ElementBudgetTracker {
+    pub(crate) fn new(budget: ElementBudget) -> Self {
+        Self {
+            budget,
+            element_count: 0,
+            overflow_path: None,
+            elements_exceeded: false,
+            primitives_exceeded: false,
+        }
+    }
+
+    pub(crate) fn budget(&self) -> ElementBudget {
+        self.budget
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.element_count = 0;
+        self.overflow_path = None;
+    }
+
+    /// Counts an element, remembering where it was rendered if it's the first over budget.
+    pub(crate) fn record_element(&mut self, view: Option<EntityId>, element_path: &[ElementId]) {
+        self.element_count += 1;
+        if self.element_count == self.budget.max_elements + 1 {
+            let element_path = element_path.iter().join(" > ");
+            self.overflow_path = Some(match view {
+                Some(view) => format!("view {view}: {element_path}"),
+                None => element_path,
+            });
+        }
+    }
+
+    /// Logs a warning if the frame exceeded the budget and the previous one didn't.
+    pub(crate) fn finish_frame(&mut self, primitive_count: usize) {
+        let elements_exceeded = self.element_count > self.budget.max_elements;
+        if elements_exceeded && !self.elements_exceeded {
+            log::warn!(
+                "frame laid out {} elements, more than the budget of {}; the budget was exceeded \
+                while rendering {}. Consider virtualizing long lists with `uniform_list` or `list`.",
+                self.element_count,
+                self.budget.max_elements,
+                self.overflow_path.as_deref().unwrap_or("the root view"),
+            );
+        }
+        self.elements_exceeded = elements_exceeded;
+
+        let primitives_exceeded = primitive_count > self.budget.max_primitives;
+        if primitives_exceeded && !self.primitives_exceeded {
+            log::warn!(
+                "frame painted {primitive_count} primitives, more than the budget of {}",
+                self.budget.max_primitives,
+            );
+        }
+        self.primitives_exceeded = primitives_exceeded;
+    }
+
+    #[cfg(test)]
+    fn overflow_path(&self) -> Option<&str> {
+        self.overflow_path.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_first_element_over_budget() {
+        let mut tracker = ElementBudgetTracker::new(ElementBudget {
+            max_elements: 2,
+            max_primitives: 10,
+        });
+        tracker.begin_frame();
+        tracker.record_element(None, &["root".into()]);
+        tracker.record_element(None, &["root".into(), "list".into()]);
+        assert_eq!(tracker.overflow_path(), None);
+        tracker.record_element(None, &["root".into(), "list".into(), 3usize.into()]);
+        tracker.record_element(None, &["root".into(), "list".into(), 4usize.into()]);
+        assert_eq!(tracker.overflow_path(), Some("root > list > 3"));
+        tracker.finish_frame(5);
+
+        tracker.begin_frame();
+        assert_eq!(tracker.overflow_path(), None);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::AnyWindowHandle;
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+/// How much earlier than its frame interval a frame may arrive and still be drawn, so that
+/// jitter in the display's refresh doesn't make a capped window skip every other frame.
+const FRAME_INTERVAL_TOLERANCE: Duration = Duration::from_millis(2);
+
+/// Caps how often a window draws and presents frames. Shared with the window's frame callback,
+/// which skips the frames the platform delivers faster than the cap.
+#[derive(Default)]
+pub(crate) struct FramePacing {
+    max_fps: Cell<Option<u32>>,
+    inactive_max_fps: Cell<Option<u32>>,
+    last_frame: Cell<Option<Instant>>,
+}
+
+impl FramePacing {
+    pub(crate) fn max_fps(&self) -> Option<u32> {
+        self.max_fps.get()
+    }
+
+    pub(crate) fn set_max_fps(&self, max_fps: Option<u32>) {
+        self.max_fps.set(max_fps);
+    }
+
+    pub(crate) fn inactive_max_fps(&self) -> Option<u32> {
+        self.inactive_max_fps.get()
+    }
+
+    pub(crate) fn set_inactive_max_fps(&self, max_fps: Option<u32>) {
+        self.inactive_max_fps.set(max_fps);
+    }
+
+    /// Whether a frame delivered at `now` comes too soon after the last one drawn to respect the
+    /// cap that applies while the window is `active` or not.
+    pub(crate) fn should_skip_frame(&self, active: bool, now: Instant) -> bool {
+        let max_fps = if active {
+            self.max_fps.get()
+        } else {
+            [self.max_fps.get(), self.inactive_max_fps.get()]
+                .into_iter()
+                .flatten()
+                .min()
+        };
+        let (Some(max_fps), Some(last_frame)) = (max_fps, self.last_frame.get()) else {
+            return false;
+        };
+        let frame_interval = Duration::from_secs(1) / max_fps.max(1);
+        now.saturating_duration_since(last_frame) + FRAME_INTERVAL_TOLERANCE < frame_interval
+    }
+
+    /// Records that a frame was drawn or presented at `now`.
+    pub(crate) fn record_frame(&self, now: Instant) {
+        self.last_frame.set(Some(now));
+    }
+}
+
+/// The frame budget used until [`App::set_jank_budget`](crate::App::set_jank_budget) is called,
+/// which is one frame at 60 frames per second.
+pub(crate) const DEFAULT_JANK_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// A frame that took longer than the application's jank budget to draw. See
+/// [`App::on_jank`](crate::App::on_jank).
+#[derive(Clone, Copy)]
+pub struct Jank {
+    /// The window that drew the frame.
+    pub window: AnyWindowHandle,
+    /// The budget the frame exceeded.
+    pub budget: Duration,
+    /// How long the frame took to draw and present in total.
+    pub frame_time: Duration,
+    /// Time spent requesting layout and prepainting elements.
+    pub layout: Duration,
+    /// Time spent painting elements into the scene.
+    pub paint: Duration,
+    /// Time spent encoding and submitting the scene to the GPU, which includes waiting for the
+    /// previous frame when the GPU falls behind.
+    pub gpu: Duration,
+}
+
+/// How long it took a window to present a frame after receiving input. See
+/// [`App::on_input_latency`](crate::App::on_input_latency).
+#[derive(Clone, Copy, Debug)]
+pub struct InputLatency {
+    /// The window that received the input.
+    pub window: AnyWindowHandle,
+    /// When the platform reported the earliest input that the frame responds to. See
+    /// [`Window::input_timestamp`](crate::Window::input_timestamp).
+    pub input_timestamp: Instant,
+    /// The time from the platform reporting the input to the frame being presented.
+    pub latency: Duration,
+}
+
+/// How long the phases of the last frame drawn by a window took.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FrameTimings {
+    pub(crate) layout: Duration,
+    pub(crate) paint: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skips_frames_faster_than_the_cap() {
+        let pacing = FramePacing::default();
+        let start = Instant::now();
+        let refresh_interval = Duration::from_micros(8_333);
+        pacing.record_frame(start);
+        assert!(!pacing.should_skip_frame(true, start + refresh_interval));
+
+        pacing.set_max_fps(Some(60));
+        assert!(pacing.should_skip_frame(true, start + refresh_interval));
+        assert!(!pacing.should_skip_frame(true, start + refresh_interval * 2));
+
+        pacing.set_inactive_max_fps(Some(10));
+        assert!(!pacing.should_skip_frame(true, start + refresh_interval * 2));
+        assert!(pacing.should_skip_frame(false, start + refresh_interval * 2));
+        assert!(!pacing.should_skip_frame(false, start + Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use crate::{App, DisplayId, Task, WindowBounds, WindowOptions};
+
+/// How a window behaves in kiosk mode, for installations and dashboards that fill a display
+/// and shouldn't be closed or left by passers-by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KioskOptions {
+    /// The display the window fills, or the primary display if `None`.
+    pub display_id: Option<DisplayId>,
+    /// How long the pointer has to stay still before the cursor is hidden, or `None` to
+    /// always show it.
+    pub hide_cursor_after: Option<Duration>,
+    /// Whether to disable system gestures and shortcuts that switch to other applications, such
+    /// as showing the dock or force quitting, on platforms that allow it.
+    pub disable_system_gestures: bool,
+}
+
+impl Default for KioskOptions {
+    fn default() -> Self {
+        Self {
+            display_id: None,
+            hide_cursor_after: Some(Duration::from_secs(3)),
+            disable_system_gestures: true,
+        }
+    }
+}
+
+impl KioskOptions {
+    /// Returns options for opening a fullscreen window without a titlebar on the chosen display.
+    /// [`App::open_kiosk_window`] opens a window with these options and puts it in kiosk mode.
+    pub fn window_options(&self, cx: &App) -> WindowOptions {
+        let display = self
+            .display_id
+            .and_then(|display_id| cx.find_display(display_id))
+            .or_else(|| cx.primary_display());
+        WindowOptions {
+            window_bounds: display
+                .as_ref()
+                .map(|display| WindowBounds::Fullscreen(display.bounds())),
+            titlebar: None,
+            is_movable: false,
+            is_resizable: false,
+            is_minimizable: false,
+            display_id: display.map(|display| display.id()),
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) struct Kiosk {
+    pub(crate) options: KioskOptions,
+    pub(crate) idle_cursor: Option<IdleCursor>,
+    pub(crate) _hide_cursor_task: Option<Task<()>>,
+}
+
+/// Hides the cursor once the pointer has been still for a while.
+pub(crate) struct IdleCursor {
+    hide_after: Duration,
+    last_moved: Instant,
+    hidden: bool,
+}
+
+impl IdleCursor {
+    pub(crate) fn new(hide_after: Duration, now: Instant) -> Self {
+        Self {
+            hide_after,
+            last_moved: now,
+            hidden: false,
+        }
+    }
+
+    pub(crate) fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Records pointer movement. Returns whether the cursor was hidden and should be shown again.
+    pub(crate) fn pointer_moved(&mut self, now: Instant) -> bool {
+        self.last_moved = now;
+        std::mem::take(&mut self.hidden)
+    }
+
+    /// Hides the cursor if the pointer has been still long enough. Returns whether the cursor
+    /// was just hidden and how long to wait before checking again.
+    pub(crate) fn poll(&mut self, now: Instant) -> (bool, Duration) {
+        let idle = now.saturating_duration_since(self.last_moved);
+        if idle >= self.hide_after {
+            let newly_hidden = !self.hidden;
+            self.hidden = true;
+            (newly_hidden, self.hide_after)
+        } else {
+            (false, self.hide_after - idle)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_cursor_hides_after_timeout() {
+        let start = Instant::now();
+        let mut cursor = IdleCursor::new(Duration::from_secs(3), start);
+
+        assert_eq!(
+            cursor.poll(start + Duration::from_secs(1)),
+            (false, Duration::from_secs(2))
+        );
+        assert!(!cursor.is_hidden());
+
+        assert_eq!(
+            cursor.poll(start + Duration::from_secs(3)),
+            (true, Duration::from_secs(3))
+        );
+        assert!(cursor.is_hidden());
+        assert_eq!(
+            cursor.poll(start + Duration::from_secs(4)),
+            (false, Duration::from_secs(3))
+        );
+
+        let moved = start + Duration::from_secs(5);
+        assert!(cursor.pointer_moved(moved));
+        assert!(!cursor.is_hidden());
+        assert!(!cursor.pointer_moved(moved));
+        assert_eq!(
+            cursor.poll(moved + Duration::from_secs(2)),
+            (false, Duration::from_secs(1))
+        );
+    }
+}
@@ -100,7 +100,7 @@ pub struct FallbackPromptRenderer {
 }
 
 impl Render for FallbackPromptRenderer {
-    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let prompt = div()
             .cursor_default()
             .track_focus(&self.focus)
@@ -151,7 +151,7 @@ impl Render for FallbackPromptRenderer {
             .child(
                 div()
                     .size_full()
-                    .bg(opaque_grey(0.5, 0.6))
+                    .bg(window.material_color(opaque_grey(0.5, 0.6)))
                     .absolute()
                     .top_0()
                     .left_0(),
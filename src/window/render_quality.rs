@@ -0,0 +1,237 @@
+use std::time::{Duration, Instant};
+
+/// How precisely shadow blur is rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadowQuality {
+    /// Shadows are rendered with the blur radius they were painted with.
+    #[default]
+    Full,
+    /// Shadows are rendered with half their blur radius, which shades fewer pixels.
+    Reduced,
+}
+
+/// The quality at which a window is rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderQuality {
+    /// The fraction of the display's native resolution the window is rendered at. The
+    /// rendered frame is upscaled to fill the window.
+    pub render_scale: f32,
+    /// How precisely shadow blur is rendered.
+    pub shadow_quality: ShadowQuality,
+}
+
+impl RenderQuality {
+    /// Rendering at native resolution with full quality shadows.
+    pub const FULL: Self = Self {
+        render_scale: 1.0,
+        shadow_quality: ShadowQuality::Full,
+    };
+}
+
+impl Default for RenderQuality {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Quality levels from best to cheapest. Adaptive quality moves one level at a time.
+const QUALITY_LEVELS: [RenderQuality; 3] = [
+    RenderQuality::FULL,
+    RenderQuality {
+        render_scale: 1.0,
+        shadow_quality: ShadowQuality::Reduced,
+    },
+    RenderQuality {
+        render_scale: 0.75,
+        shadow_quality: ShadowQuality::Reduced,
+    },
+];
+
+/// How much of the previous average is retained when a new frame time is recorded.
+const FRAME_TIME_SMOOTHING: f32 = 0.9;
+/// Quality is reduced once frames take this much longer than the budget on average.
+const DEGRADE_THRESHOLD: f32 = 1.2;
+/// Quality is restored once frames take at most this fraction of the budget on average.
+const RESTORE_THRESHOLD: f32 = 0.6;
+/// Frames to wait after a change before reducing quality further.
+const FRAMES_BEFORE_DEGRADE: u32 = 30;
+/// Frames to wait after a change before restoring quality, which is kept longer than
+/// [`FRAMES_BEFORE_DEGRADE`] to avoid oscillating between levels.
+const FRAMES_BEFORE_RESTORE: u32 = 120;
+const POWER_STATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lowers a window's render quality while frames exceed their time budget or the system is
+/// conserving power, and restores it once there is headroom again.
+pub(crate) struct AdaptiveRenderQuality {
+    frame_budget: Duration,
+    supports_render_scale: bool,
+    average_frame_time: Option<f32>,
+    level: usize,
+    frames_at_level: u32,
+    power_constrained: bool,
+    last_power_check: Option<Instant>,
+}
+
+impl AdaptiveRenderQuality {
+    pub(crate) fn new(frame_budget: Duration, supports_render_scale: bool) -> Self {
+        Self {
+            frame_budget,
+            supports_render_scale,
+            average_frame_time: None,
+            level: 0,
+            frames_at_level: 0,
+            power_constrained: false,
+            last_power_check: None,
+        }
+    }
+
+    pub(crate) fn quality(&self) -> RenderQuality {
+        QUALITY_LEVELS[self.level]
+    }
+
+    /// Returns whether the power state should be queried again.
+    pub(crate) fn needs_power_check(&mut self, now: Instant) -> bool {
+        let needs_check = self
+            .last_power_check
+            .is_none_or(|last_check| now.duration_since(last_check) >= POWER_STATE_POLL_INTERVAL);
+        if needs_check {
+            self.last_power_check = Some(now);
+        }
+        needs_check
+    }
+
+    /// Records whether the system is conserving power, returning the new quality if it changed.
+    pub(crate) fn set_power_constrained(
+        &mut self,
+        power_constrained: bool,
+    ) -> Option<RenderQuality> {
+        self.power_constrained = power_constrained;
+        let level = self.level.clamp(self.min_level(), self.max_level());
+        self.set_level(level)
+    }
+
+    /// Records how long a frame took to draw, returning the new quality if it changed.
+    pub(crate) fn record_frame(&mut self, frame_time: Duration) -> Option<RenderQuality> {
+        let frame_time = frame_time.as_secs_f32();
+        let average_frame_time = match self.average_frame_time {
+            Some(average) => {
+                average * FRAME_TIME_SMOOTHING + frame_time * (1. - FRAME_TIME_SMOOTHING)
+            }
+            None => frame_time,
+        };
+        self.average_frame_time = Some(average_frame_time);
+        self.frames_at_level = self.frames_at_level.saturating_add(1);
+
+        let budget = self.frame_budget.as_secs_f32();
+        if average_frame_time > budget * DEGRADE_THRESHOLD
+            && self.frames_at_level >= FRAMES_BEFORE_DEGRADE
+            && self.level < self.max_level()
+        {
+            self.set_level(self.level + 1)
+        } else if average_frame_time <= budget * RESTORE_THRESHOLD
+            && self.frames_at_level >= FRAMES_BEFORE_RESTORE
+            && self.level > self.min_level()
+        {
+            self.set_level(self.level - 1)
+        } else {
+            None
+        }
+    }
+
+    fn min_level(&self) -> usize {
+        if self.power_constrained { 1 } else { 0 }
+    }
+
+    fn max_level(&self) -> usize {
+        if self.supports_render_scale {
+            QUALITY_LEVELS.len() - 1
+        } else {
+            1
+        }
+    }
+
+    fn set_level(&mut self, level: usize) -> Option<RenderQuality> {
+        if level == self.level {
+            return None;
+        }
+        self.level = level;
+        self.frames_at_level = 0;
+        Some(self.quality())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(16);
+
+    fn record_frames(
+        quality: &mut AdaptiveRenderQuality,
+        frame_time: Duration,
+        count: usize,
+    ) -> Option<RenderQuality> {
+        let mut last_change = None;
+        for _ in 0..count {
+            if let Some(change) = quality.record_frame(frame_time) {
+                last_change = Some(change);
+            }
+        }
+        last_change
+    }
+
+    #[test]
+    fn test_degrades_and_restores() {
+        let mut quality = AdaptiveRenderQuality::new(BUDGET, true);
+        assert_eq!(
+            record_frames(&mut quality, Duration::from_millis(8), 200),
+            None
+        );
+
+        let reduced = record_frames(&mut quality, Duration::from_millis(40), 10);
+        assert_eq!(
+            reduced.map(|quality| quality.shadow_quality),
+            Some(ShadowQuality::Reduced)
+        );
+        assert_eq!(quality.quality().render_scale, 1.0);
+        record_frames(&mut quality, Duration::from_millis(40), 100);
+        assert_eq!(quality.quality().render_scale, 0.75);
+
+        record_frames(&mut quality, Duration::from_millis(12), 1000);
+        assert_eq!(
+            quality.quality().render_scale,
+            0.75,
+            "quality is only restored when there is plenty of headroom"
+        );
+        record_frames(&mut quality, Duration::from_millis(4), 1000);
+        assert_eq!(quality.quality(), RenderQuality::FULL);
+    }
+
+    #[test]
+    fn test_render_scale_unsupported() {
+        let mut quality = AdaptiveRenderQuality::new(BUDGET, false);
+        record_frames(&mut quality, Duration::from_millis(40), 1000);
+        assert_eq!(quality.quality().render_scale, 1.0);
+        assert_eq!(quality.quality().shadow_quality, ShadowQuality::Reduced);
+    }
+
+    #[test]
+    fn test_power_constrained() {
+        let mut quality = AdaptiveRenderQuality::new(BUDGET, true);
+        assert_eq!(
+            quality
+                .set_power_constrained(true)
+                .map(|quality| quality.shadow_quality),
+            Some(ShadowQuality::Reduced)
+        );
+        record_frames(&mut quality, Duration::from_millis(1), 1000);
+        assert_eq!(quality.quality().shadow_quality, ShadowQuality::Reduced);
+        assert_eq!(
+            quality.set_power_constrained(false),
+            None,
+            "quality is restored gradually rather than immediately"
+        );
+        record_frames(&mut quality, Duration::from_millis(1), 1000);
+        assert_eq!(quality.quality(), RenderQuality::FULL);
+    }
+}
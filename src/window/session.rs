@@ -0,0 +1,280 @@
+use crate::{
+    App, BorrowAppContext, Bounds, DisplayId, Global, KeyValueStore, Pixels, SharedString, Task,
+    Window, WindowBounds, point, size,
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+use util::ResultExt;
+use uuid::Uuid;
+
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The state a window was in when its session was saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowSessionState {
+    /// The window was neither maximized nor fullscreen.
+    Windowed,
+    /// The window was maximized.
+    Maximized,
+    /// The window was fullscreen.
+    Fullscreen,
+}
+
+/// The placement of a window, as persisted between launches.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowSession {
+    /// The restore bounds of the window in screen coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// Whether the window was windowed, maximized or fullscreen.
+    pub state: WindowSessionState,
+    /// The UUID of the display the window was on, if the platform provides one.
+    pub display_uuid: Option<Uuid>,
+}
+
+impl WindowSession {
+    /// Captures the placement of a window.
+    pub fn new(window_bounds: WindowBounds, display_uuid: Option<Uuid>) -> Self {
+        let state = match window_bounds {
+            WindowBounds::Windowed(_) => WindowSessionState::Windowed,
+            WindowBounds::Maximized(_) => WindowSessionState::Maximized,
+            WindowBounds::Fullscreen(_) => WindowSessionState::Fullscreen,
+        };
+        Self {
+            bounds: window_bounds.get_bounds(),
+            state,
+            display_uuid,
+        }
+    }
+
+    /// Returns the window bounds to open the window with, using the given restore bounds.
+    pub fn window_bounds(&self, bounds: Bounds<Pixels>) -> WindowBounds {
+        match self.state {
+            WindowSessionState::Windowed => WindowBounds::Windowed(bounds),
+            WindowSessionState::Maximized => WindowBounds::Maximized(bounds),
+            WindowSessionState::Fullscreen => WindowBounds::Fullscreen(bounds),
+        }
+    }
+}
+
+/// The persisted sessions of every window opened with a [`crate::WindowOptions::session_id`].
+///
+/// Each session is stored under its session id, so that processes sharing the store only
+/// overwrite the sessions of the windows they changed.
+pub(crate) struct WindowSessions {
+    store: KeyValueStore,
+    sessions: HashMap<SharedString, WindowSession>,
+    pending_changes: HashMap<SharedString, Option<WindowSession>>,
+    pending_save: Option<Task<()>>,
+}
+
+impl Global for WindowSessions {}
+
+impl WindowSessions {
+    /// Reads the sessions stored at `path`. A missing or corrupt file yields no sessions, and a
+    /// corrupt session is skipped.
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let store = KeyValueStore::new(path);
+        let sessions = store
+            .entries()
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(session_id, json)| {
+                let session = serde_json::from_str(&json)
+                    .with_context(|| format!("failed to parse window session {session_id:?}"))
+                    .log_err()?;
+                Some((SharedString::from(session_id), session))
+            })
+            .collect();
+        Self {
+            store,
+            sessions,
+            pending_changes: HashMap::default(),
+            pending_save: None,
+        }
+    }
+
+    pub(crate) fn get(&self, session_id: &str) -> Option<&WindowSession> {
+        self.sessions.get(session_id)
+    }
+
+    pub(crate) fn remove(&mut self, session_id: &str, cx: &App) {
+        if let Some((session_id, _)) = self.sessions.remove_entry(session_id) {
+            self.pending_changes.insert(session_id, None);
+            self.schedule_save(cx);
+        }
+    }
+
+    /// Returns the bounds and display to open the window with the given session id, if a
+    /// session was saved for it.
+    ///
+    /// If the saved display is no longer connected the window is moved to the primary display.
+    /// In either case the bounds are shrunk and moved as needed to fit the display's visible area.
+    pub(crate) fn restore(session_id: &str, cx: &App) -> Option<(WindowBounds, Option<DisplayId>)> {
+        let session = *cx.try_global::<Self>()?.get(session_id)?;
+        let saved_display = session.display_uuid.and_then(|uuid| {
+            cx.displays()
+                .into_iter()
+                .find(|display| display.uuid().ok() == Some(uuid))
+        });
+
+        let bounds = match (saved_display.as_ref(), cx.primary_display()) {
+            (Some(display), _) => fit_bounds(session.bounds, display.visible_bounds()),
+            (None, Some(primary)) => {
+                let visible_bounds = primary.visible_bounds();
+                let centered = Bounds::centered_at(visible_bounds.center(), session.bounds.size);
+                fit_bounds(centered, visible_bounds)
+            }
+            (None, None) => session.bounds,
+        };
+        let display_id = saved_display.map(|display| display.id());
+        Some((session.window_bounds(bounds), display_id))
+    }
+
+    /// Records the current placement of `window` under `session_id`, scheduling a write to disk.
+    pub(crate) fn save_window(session_id: &SharedString, window: &Window, cx: &mut App) {
+        if !cx.has_global::<Self>() {
+            return;
+        }
+
+        let display_uuid = window.display(cx).and_then(|display| display.uuid().ok());
+        let session = WindowSession::new(window.window_bounds(), display_uuid);
+        if cx.global::<Self>().get(session_id) == Some(&session) {
+            return;
+        }
+
+        cx.update_global(|sessions: &mut Self, cx| {
+            sessions.sessions.insert(session_id.clone(), session);
+            sessions
+                .pending_changes
+                .insert(session_id.clone(), Some(session));
+            sessions.schedule_save(cx);
+        });
+    }
+
+    fn schedule_save(&mut self, cx: &App) {
+        let store = self.store.clone();
+        let changes = self.pending_changes.clone();
+        let executor = cx.background_executor().clone();
+        self.pending_save = Some(cx.background_executor().spawn(async move {
+            executor.timer(SAVE_DEBOUNCE).await;
+            write_sessions(&store, changes).log_err();
+        }));
+    }
+
+    /// Writes any pending changes to disk immediately.
+    pub(crate) fn flush(&mut self) {
+        if self.pending_save.take().is_some() {
+            write_sessions(&self.store, std::mem::take(&mut self.pending_changes)).log_err();
+        }
+    }
+}
+
+/// Writes the changed sessions to `store`, removing the sessions that are `None`.
+fn write_sessions(
+    store: &KeyValueStore,
+    changes: HashMap<SharedString, Option<WindowSession>>,
+) -> Result<()> {
+    let changes = changes
+        .into_iter()
+        .map(|(session_id, session)| {
+            let json = session
+                .map(|session| serde_json::to_string(&session))
+                .transpose()
+                .context("failed to serialize window session")?;
+            Ok((session_id.to_string(), json))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    store.update(|entries| {
+        for (session_id, json) in changes {
+            match json {
+                Some(json) => entries.insert(session_id, json),
+                None => entries.remove(&session_id),
+            };
+        }
+        true
+    })
+}
+
+/// Shrinks and moves `bounds` as needed so that it lies entirely within `visible_bounds`.
+fn fit_bounds(bounds: Bounds<Pixels>, visible_bounds: Bounds<Pixels>) -> Bounds<Pixels> {
+    let fitted_size = size(
+        bounds.size.width.min(visible_bounds.size.width),
+        bounds.size.height.min(visible_bounds.size.height),
+    );
+    let max_origin = point(
+        visible_bounds.right() - fitted_size.width,
+        visible_bounds.bottom() - fitted_size.height,
+    );
+    Bounds {
+        origin: bounds.origin.clamp(&visible_bounds.origin, &max_origin),
+        size: fitted_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::px;
+
+    #[test]
+    fn test_fit_bounds() {
+        let visible_bounds = Bounds::new(point(px(0.), px(25.)), size(px(1440.), px(875.)));
+
+        let inside = Bounds::new(point(px(100.), px(100.)), size(px(800.), px(600.)));
+        assert_eq!(fit_bounds(inside, visible_bounds), inside);
+
+        let off_screen = Bounds::new(point(px(2000.), px(-50.)), size(px(800.), px(600.)));
+        assert_eq!(
+            fit_bounds(off_screen, visible_bounds),
+            Bounds::new(point(px(640.), px(25.)), size(px(800.), px(600.)))
+        );
+
+        let too_large = Bounds::new(point(px(-10.), px(0.)), size(px(2560.), px(1440.)));
+        assert_eq!(fit_bounds(too_large, visible_bounds), visible_bounds);
+    }
+
+    #[test]
+    fn test_corrupt_session_is_skipped() {
+        let directory =
+            std::env::temp_dir().join(format!("gpui-window-sessions-{}", std::process::id()));
+        std::fs::remove_dir_all(&directory).ok();
+        let path = directory.join("sessions.json");
+        let bounds = Bounds::new(point(px(10.), px(20.)), size(px(300.), px(200.)));
+        let session = WindowSession::new(WindowBounds::Windowed(bounds), None);
+        let changes = HashMap::from_iter([
+            (SharedString::from("main"), Some(session)),
+            (SharedString::from("closed"), None),
+        ]);
+        write_sessions(&KeyValueStore::new(path.clone()), changes).unwrap();
+        KeyValueStore::new(path.clone())
+            .write("settings", Some("{not json".into()))
+            .unwrap();
+
+        let sessions = WindowSessions::load(path);
+        assert_eq!(sessions.get("main"), Some(&session));
+        assert_eq!(sessions.get("settings"), None);
+        assert_eq!(sessions.sessions.len(), 1);
+        std::fs::remove_dir_all(&directory).ok();
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let bounds = Bounds::new(point(px(10.), px(20.)), size(px(300.), px(200.)));
+        let session = WindowSession::new(WindowBounds::Fullscreen(bounds), Some(Uuid::nil()));
+        assert_eq!(session.state, WindowSessionState::Fullscreen);
+        assert_eq!(
+            session.window_bounds(bounds),
+            WindowBounds::Fullscreen(bounds)
+        );
+
+        let json = serde_json::to_string(&session).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WindowSession>(&json).unwrap(),
+            session
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    AtlasTile, Bounds, ContentMask, Corners, DevicePixels, Hsla, MonochromeSprite, ScaledPixels,
+    Size, TransformationMatrix, point, size,
+};
+
+/// Shadows blurred at least this much, in device pixels, are drawn from a rasterization cached in
+/// the sprite atlas instead of evaluating the blur for every pixel of every frame.
+const MIN_CACHED_BLUR_RADIUS: f32 = 8.;
+/// The largest texture a cached shadow may use, in device pixels along each side.
+const MAX_CACHED_SHADOW_SIZE: u32 = 512;
+/// The size of the middle slice of a cached shadow, in texels. Only the texel in the middle of it
+/// is stretched, so that texture filtering doesn't blend in the corners.
+const MIDDLE_SLICE_SIZE: u32 = 3;
+/// The shadow shaders treat the blur radius as the standard deviation of the blur, and ignore
+/// the blur beyond this many standard deviations.
+const BLUR_EXTENT: u32 = 3;
+
+/// The shape of a cached shadow, rounded to whole device pixels. The size of the shadow isn't
+/// part of it, because cached shadows are nine-sliced to fit the shadow's bounds.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ShadowParams {
+    blur_radius: u32,
+    corner_radii: [u32; 4],
+}
+
+impl ShadowParams {
+    /// Returns the parameters to draw a shadow from the cache, or `None` if the shadow is too
+    /// sharp to be worth caching, or too small or too round to be nine-sliced.
+    pub(crate) fn new(
+        bounds: Bounds<ScaledPixels>,
+        blur_radius: ScaledPixels,
+        corner_radii: &Corners<ScaledPixels>,
+    ) -> Option<Self> {
+        if blur_radius.0 < MIN_CACHED_BLUR_RADIUS {
+            return None;
+        }
+
+        let params = Self {
+            blur_radius: blur_radius.0.round() as u32,
+            corner_radii: [
+                corner_radii.top_left,
+                corner_radii.top_right,
+                corner_radii.bottom_right,
+                corner_radii.bottom_left,
+            ]
+            .map(|radius| radius.0.max(0.).round() as u32),
+        };
+        let shape_size = params.shape_size() as f32;
+        if params.texture_size() > MAX_CACHED_SHADOW_SIZE
+            || bounds.size.width.0 < shape_size
+            || bounds.size.height.0 < shape_size
+        {
+            return None;
+        }
+        Some(params)
+    }
+
+    fn max_corner_radius(&self) -> u32 {
+        self.corner_radii.into_iter().max().unwrap_or(0)
+    }
+
+    /// The distance from the outer edge of the shadow past which it no longer varies along its
+    /// edges.
+    fn margin(&self) -> u32 {
+        self.max_corner_radius() + 2 * BLUR_EXTENT * self.blur_radius
+    }
+
+    /// The size of the rounded rectangle that casts the rasterized shadow.
+    fn shape_size(&self) -> u32 {
+        2 * (self.max_corner_radius() + BLUR_EXTENT * self.blur_radius) + MIDDLE_SLICE_SIZE
+    }
+
+    fn texture_size(&self) -> u32 {
+        2 * self.margin() + MIDDLE_SLICE_SIZE
+    }
+
+    /// Rasterizes the coverage of the shadow, blurred the same way as by the shadow shaders.
+    pub(crate) fn rasterize(&self) -> (Size<DevicePixels>, Vec<u8>) {
+        let texture_size = self.texture_size();
+        let half_size = self.shape_size() as f32 / 2.;
+        let center = texture_size as f32 / 2.;
+        let sigma = self.blur_radius as f32;
+        let [top_left, top_right, bottom_right, bottom_left] =
+            self.corner_radii.map(|radius| radius as f32);
+
+        let mut coverage = Vec::with_capacity((texture_size * texture_size) as usize);
+        for row in 0..texture_size {
+            let y = row as f32 + 0.5 - center;
+            for column in 0..texture_size {
+                let x = column as f32 + 0.5 - center;
+                let corner_radius = match (x < 0., y < 0.) {
+                    (true, true) => top_left,
+                    (false, true) => top_right,
+                    (false, false) => bottom_right,
+                    (true, false) => bottom_left,
+                };
+                let alpha = shadow_alpha(x, y, sigma, corner_radius, half_size);
+                coverage.push((alpha.clamp(0., 1.) * 255.).round() as u8);
+            }
+        }
+
+        let size = DevicePixels(texture_size as i32);
+        (Size::new(size, size), coverage)
+    }
+}
+
+/// Returns sprites that draw a cached shadow's tile nine-sliced to fit a shadow cast by `bounds`.
+pub(crate) fn nine_slice_shadow(
+    params: &ShadowParams,
+    tile: &AtlasTile,
+    bounds: Bounds<ScaledPixels>,
+    content_mask: &ContentMask<ScaledPixels>,
+    color: Hsla,
+) -> Vec<MonochromeSprite> {
+    let extent = (BLUR_EXTENT * params.blur_radius) as f32;
+    let margin = params.margin();
+    let middle = margin + MIDDLE_SLICE_SIZE / 2;
+
+    // The offsets and sizes of each slice in the tile, and in the shadow's outer bounds.
+    let tile_slices = [
+        (0, margin),
+        (middle, 1),
+        (margin + MIDDLE_SLICE_SIZE, margin),
+    ];
+    let slices = |origin: f32, length: f32| {
+        let margin = margin as f32;
+        [
+            (origin, margin),
+            (origin + margin, length - 2. * margin),
+            (origin + length - margin, margin),
+        ]
+    };
+    let columns = slices(
+        bounds.origin.x.0 - extent,
+        bounds.size.width.0 + 2. * extent,
+    );
+    let rows = slices(
+        bounds.origin.y.0 - extent,
+        bounds.size.height.0 + 2. * extent,
+    );
+
+    let mut sprites = Vec::with_capacity(9);
+    for ((tile_y, tile_height), (y, height)) in tile_slices.into_iter().zip(rows) {
+        for ((tile_x, tile_width), (x, width)) in tile_slices.into_iter().zip(columns) {
+            if width <= 0. || height <= 0. {
+                continue;
+            }
+            let mut slice_tile = tile.clone();
+            slice_tile.bounds = Bounds {
+                origin: tile.bounds.origin
+                    + point(DevicePixels(tile_x as i32), DevicePixels(tile_y as i32)),
+                size: size(
+                    DevicePixels(tile_width as i32),
+                    DevicePixels(tile_height as i32),
+                ),
+            };
+            sprites.push(MonochromeSprite {
+                order: 0,
+                distance_field: MonochromeSprite::LINEAR_COVERAGE,
+                bounds: Bounds {
+                    origin: point(ScaledPixels(x), ScaledPixels(y)),
+                    size: size(ScaledPixels(width), ScaledPixels(height)),
+                },
+                content_mask: content_mask.clone(),
+                color,
+                tile: slice_tile,
+                transformation: TransformationMatrix::unit(),
+            });
+        }
+    }
+    sprites
+}
+
+/// The coverage of a blurred rounded rectangle centered on the origin, at the given point.
+/// This is the algorithm used by the shadow shaders, from
+/// <https://madebyevan.com/shaders/fast-rounded-rectangle-shadows/>.
+fn shadow_alpha(x: f32, y: f32, sigma: f32, corner_radius: f32, half_size: f32) -> f32 {
+    const SAMPLES: usize = 4;
+
+    // The signal is only non-zero in a limited range, so don't waste samples.
+    let low = y - half_size;
+    let high = y + half_size;
+    let start = (-3. * sigma).clamp(low, high);
+    let end = (3. * sigma).clamp(low, high);
+
+    let step = (end - start) / SAMPLES as f32;
+    let mut sample_y = start + step * 0.5;
+    let mut alpha = 0.;
+    for _ in 0..SAMPLES {
+        let blur = blur_along_x(x, y - sample_y, sigma, corner_radius, half_size);
+        alpha += blur * gaussian(sample_y, sigma) * step;
+        sample_y += step;
+    }
+    alpha
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner_radius: f32, half_size: f32) -> f32 {
+    let delta = (half_size - corner_radius - y.abs()).min(0.);
+    let curved = half_size - corner_radius
+        + (corner_radius * corner_radius - delta * delta)
+            .max(0.)
+            .sqrt();
+    let scale = 0.5_f32.sqrt() / sigma;
+    let low = 0.5 + 0.5 * erf((x - curved) * scale);
+    let high = 0.5 + 0.5 * erf((x + curved) * scale);
+    high - low
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * std::f32::consts::PI).sqrt() * sigma)
+}
+
+/// Approximates the error function, needed for the gaussian integral.
+fn erf(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let r1 = 1.
+        + (0.278393 + (0.230389 + (0.000972 + 0.078108 * magnitude) * magnitude) * magnitude)
+            * magnitude;
+    let r2 = r1 * r1;
+    value.signum() * (1. - 1. / (r2 * r2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtlasTextureId, AtlasTextureKind, Point, TileId};
+
+    fn params(blur_radius: f32, corner_radius: f32) -> Option<ShadowParams> {
+        let bounds = Bounds {
+            origin: Point::default(),
+            size: size(ScaledPixels(300.), ScaledPixels(200.)),
+        };
+        ShadowParams::new(
+            bounds,
+            ScaledPixels(blur_radius),
+            &Corners::all(ScaledPixels(corner_radius)),
+        )
+    }
+
+    #[test]
+    fn test_shadow_params() {
+        assert_eq!(params(2., 4.), None, "sharp shadows aren't cached");
+        assert_eq!(params(16., 120.), None, "the corners don't fit the bounds");
+        let params = params(10.2, 4.).unwrap();
+        assert_eq!(params.blur_radius, 10);
+        assert_eq!(params.margin(), 64);
+        assert_eq!(params.texture_size(), 131);
+    }
+
+    #[test]
+    fn test_rasterize_shadow() {
+        let params = params(8., 6.).unwrap();
+        let (texture_size, coverage) = params.rasterize();
+        let side = texture_size.width.0 as usize;
+        assert_eq!(coverage.len(), side * side);
+
+        let middle = side / 2;
+        assert_eq!(coverage[middle * side + middle], 255);
+        assert_eq!(coverage[0], 0, "the shadow fades out at its corners");
+        for row in 0..side {
+            for column in 0..side {
+                assert_eq!(
+                    coverage[row * side + column],
+                    coverage[row * side + side - 1 - column],
+                    "the shadow is symmetric"
+                );
+            }
+        }
+        let margin = params.margin() as usize;
+        let row = 20 * side;
+        assert!(coverage[row + margin] > 0 && coverage[row + margin] < 255);
+        for column in margin..margin + MIDDLE_SLICE_SIZE as usize {
+            assert_eq!(
+                coverage[row + column],
+                coverage[row + margin],
+                "the middle slice doesn't vary along the edge"
+            );
+        }
+    }
+
+    #[test]
+    fn test_nine_slice_shadow() {
+        let params = params(8., 6.).unwrap();
+        let texture_size = DevicePixels(params.texture_size() as i32);
+        let tile = AtlasTile {
+            texture_id: AtlasTextureId {
+                index: 0,
+                kind: AtlasTextureKind::Monochrome,
+            },
+            tile_id: TileId(0),
+            padding: 0,
+            bounds: Bounds {
+                origin: point(DevicePixels(100), DevicePixels(0)),
+                size: size(texture_size, texture_size),
+            },
+        };
+        let bounds = Bounds {
+            origin: point(ScaledPixels(50.), ScaledPixels(50.)),
+            size: size(ScaledPixels(300.), ScaledPixels(200.)),
+        };
+        let content_mask = ContentMask {
+            bounds,
+            ..Default::default()
+        };
+        let sprites = nine_slice_shadow(&params, &tile, bounds, &content_mask, Hsla::black());
+        assert_eq!(sprites.len(), 9);
+
+        let top_left = &sprites[0];
+        assert_eq!(
+            top_left.bounds.origin,
+            point(ScaledPixels(26.), ScaledPixels(26.))
+        );
+        assert_eq!(
+            top_left.tile.bounds.origin,
+            point(DevicePixels(100), DevicePixels(0))
+        );
+        let center = &sprites[4];
+        assert_eq!(
+            center.tile.bounds.size,
+            size(DevicePixels(1), DevicePixels(1))
+        );
+        let bottom_right = &sprites[8];
+        assert_eq!(
+            bottom_right.bounds.bottom_right(),
+            point(ScaledPixels(374.), ScaledPixels(274.))
+        );
+    }
+}